@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::fs::{self, DirBuilder};
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::DirBuilderExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+/// SOCKET_ENV is the environment variable holding the path of the agent's socket, which store
+/// operations ask for unlocked keys before prompting for a passphrase.
+pub const SOCKET_ENV: &str = "MOTUS_AGENT_SOCK";
+
+// POLL_INTERVAL is how often the agent checks for new connections and for keys expiring.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// CLIENT_TIMEOUT is how long a client has to send its request, and the agent to answer it.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Request is a line of JSON sent to the agent, which answers it with a line of JSON too.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Request {
+    /// Asks for the key of the store whose salt is given, answered with it if the agent holds it.
+    Get { salt: String },
+    /// Hands the key of the store whose salt is given over to the agent.
+    Put { salt: String, key: String },
+    /// Asks the agent to forget every key it holds and to exit.
+    Stop,
+}
+
+/// Response is the agent's answer to a request.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+}
+
+/// Agent holds the keys of unlocked stores in memory, each for `timeout` after it was handed
+/// over, and hands them out on a Unix socket, so that store operations do not prompt for a
+/// passphrase every time.
+///
+/// The agent only ever holds derived keys, identified by the salt of the store they open, and
+/// never the passphrases they were derived from. The socket is created in a directory readable
+/// by the current user only.
+pub struct Agent {
+    listener: UnixListener,
+    path: PathBuf,
+    timeout: Duration,
+    keys: HashMap<String, ([u8; 32], Instant)>,
+}
+
+impl Agent {
+    /// Binds the agent's socket at `path`.
+    pub fn bind(path: &Path, timeout: Duration) -> Result<Self, AgentError> {
+        let listener = UnixListener::bind(path)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            timeout,
+            keys: HashMap::new(),
+        })
+    }
+
+    /// Answers requests until asked to stop, forgetting keys as they expire.
+    pub fn serve(mut self) -> Result<(), AgentError> {
+        loop {
+            let now = Instant::now();
+            self.keys.retain(|_, (key, expiry)| {
+                let alive = *expiry > now;
+                if !alive {
+                    key.fill(0);
+                }
+                alive
+            });
+
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            if let Ok(Request::Stop) = self.respond(stream) {
+                return Ok(());
+            }
+        }
+    }
+
+    // respond answers the request on `stream`, and returns it.
+    fn respond(&mut self, stream: UnixStream) -> io::Result<Request> {
+        stream.set_nonblocking(false)?;
+        stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+        stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        let request: Request = serde_json::from_str(&line)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+
+        let response = match &request {
+            Request::Get { salt } => Response {
+                key: self.keys.get(salt).map(|(key, _)| STANDARD.encode(key)),
+            },
+            Request::Put { salt, key } => {
+                if let Ok(Ok(key)) = STANDARD.decode(key).map(<[u8; 32]>::try_from) {
+                    let expiry = Instant::now() + self.timeout;
+                    self.keys.insert(salt.clone(), (key, expiry));
+                }
+                Response::default()
+            }
+            Request::Stop => {
+                for (key, _) in self.keys.values_mut() {
+                    key.fill(0);
+                }
+                Response::default()
+            }
+        };
+
+        let mut stream = stream;
+        writeln!(stream, "{}", serde_json::to_string(&response)?)?;
+        Ok(request)
+    }
+}
+
+impl Drop for Agent {
+    // drop removes the socket, along with the directory socket_path created for it.
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+        if let Some(dir) = self.path.parent().filter(|dir| {
+            dir.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("motus-agent-"))
+        }) {
+            let _ = fs::remove_dir(dir);
+        }
+    }
+}
+
+/// socket_path creates a new directory readable by the current user only, in the runtime
+/// directory or the temporary one otherwise, and returns the path of a socket in it.
+pub fn socket_path() -> Result<PathBuf, AgentError> {
+    let dir = dirs::runtime_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join(format!("motus-agent-{}", std::process::id()));
+    DirBuilder::new().mode(0o700).create(&dir)?;
+    Ok(dir.join("agent.sock"))
+}
+
+/// get returns the key of the store whose salt is given, if an agent is running and holds it.
+///
+/// The agent only spares prompts: failing to reach it is never an error.
+pub fn get(salt: &[u8]) -> Option<[u8; 32]> {
+    let response = request(&Request::Get {
+        salt: STANDARD.encode(salt),
+    })
+    .ok()?;
+    let key = STANDARD.decode(response.key?).ok()?;
+    key.try_into().ok()
+}
+
+/// put hands the key of the store whose salt is given over to the agent, if one is running.
+pub fn put(salt: &[u8], key: &[u8; 32]) {
+    let _ = request(&Request::Put {
+        salt: STANDARD.encode(salt),
+        key: STANDARD.encode(key),
+    });
+}
+
+/// stop asks the agent to forget every key it holds and to exit.
+pub fn stop() -> Result<(), AgentError> {
+    request(&Request::Stop).map(|_| ())
+}
+
+// request sends `request` to the agent whose socket SOCKET_ENV points to, and returns its
+// response.
+fn request(request: &Request) -> Result<Response, AgentError> {
+    let path = std::env::var_os(SOCKET_ENV).ok_or(AgentError::NotRunning)?;
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    serde_json::from_str(&line).map_err(|_| AgentError::Protocol)
+}
+
+/// AgentError represents the errors that can occur while running or reaching the agent.
+#[derive(Debug)]
+pub enum AgentError {
+    Io(io::Error),
+    NotRunning,
+    Protocol,
+}
+
+impl Display for AgentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AgentError::Io(err) => write!(f, "unable to reach the agent: {}", err),
+            AgentError::NotRunning => write!(f, "no agent is running; {} is not set", SOCKET_ENV),
+            AgentError::Protocol => write!(f, "the agent sent an invalid response"),
+        }
+    }
+}
+
+impl From<io::Error> for AgentError {
+    fn from(err: io::Error) -> Self {
+        AgentError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for AgentError {
+    fn from(_: serde_json::Error) -> Self {
+        AgentError::Protocol
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // exchange sends `request` to the agent listening at `path`.
+    fn exchange(path: &Path, request: &Request) -> Response {
+        let mut stream = UnixStream::connect(path).unwrap();
+        writeln!(stream, "{}", serde_json::to_string(request).unwrap()).unwrap();
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn test_agent_holds_keys_until_they_expire() {
+        let path = std::env::temp_dir().join(format!("motus-agent-{}.sock", std::process::id()));
+        let agent = Agent::bind(&path, Duration::from_millis(300)).unwrap();
+        let server = thread::spawn(move || agent.serve());
+
+        let salt = STANDARD.encode([7u8; 16]);
+        let key = STANDARD.encode([42u8; 32]);
+        assert_eq!(
+            exchange(&path, &Request::Get { salt: salt.clone() }).key,
+            None
+        );
+
+        exchange(
+            &path,
+            &Request::Put {
+                salt: salt.clone(),
+                key: key.clone(),
+            },
+        );
+        assert_eq!(
+            exchange(&path, &Request::Get { salt: salt.clone() }).key,
+            Some(key)
+        );
+
+        thread::sleep(Duration::from_millis(400));
+        assert_eq!(exchange(&path, &Request::Get { salt }).key, None);
+
+        exchange(&path, &Request::Stop);
+        server.join().unwrap().unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,123 @@
+use std::io::{self, BufWriter, Write};
+
+use motus::secret::Secret;
+use serde::Serialize;
+
+use crate::{csv_field, OutputFormat};
+
+/// Alias is a generated email alias, optionally paired with a password for the account it signs
+/// up for.
+pub struct Alias {
+    pub address: String,
+    pub password: Option<Secret>,
+}
+
+impl Alias {
+    /// new builds the alias of the given random `tag` at `domain`: `mailbox+tag@domain` when
+    /// plus-addressing a `mailbox`, or `tag@domain` for domains catching all addresses.
+    pub fn new(tag: &str, domain: &str, mailbox: Option<&str>) -> Self {
+        let address = match mailbox {
+            Some(mailbox) => format!("{mailbox}+{tag}@{domain}"),
+            None => format!("{tag}@{domain}"),
+        };
+
+        Self {
+            address,
+            password: None,
+        }
+    }
+
+    /// with_password pairs the alias with the given password.
+    pub fn with_password(mut self, password: String) -> Self {
+        self.password = Some(Secret::new(password));
+        self
+    }
+}
+
+/// display writes the generated aliases, and their passwords if any, to stdout in the requested
+/// format.
+///
+/// Aliases are never copied to the clipboard nor recorded in the history, and neither are the
+/// passwords paired with them: they are meant to be piped into a signup script or a password
+/// manager import.
+pub fn display(aliases: &[Alias], format: &OutputFormat) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    match format {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            for alias in aliases {
+                match &alias.password {
+                    Some(password) => writeln!(out, "{}\t{}", alias.address, password.expose())?,
+                    None => writeln!(out, "{}", alias.address)?,
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let aliases: Vec<AliasOutput> = aliases
+                .iter()
+                .map(|alias| AliasOutput {
+                    address: &alias.address,
+                    password: alias.password.as_ref().map(Secret::expose),
+                })
+                .collect();
+            serde_json::to_writer(&mut out, &AliasesOutput { aliases })?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            let paired = aliases.iter().any(|alias| alias.password.is_some());
+            writeln!(
+                out,
+                "{}",
+                if paired {
+                    "address,password"
+                } else {
+                    "address"
+                }
+            )?;
+            for alias in aliases {
+                match &alias.password {
+                    Some(password) => writeln!(
+                        out,
+                        "{},{}",
+                        csv_field(&alias.address),
+                        csv_field(password.expose())
+                    )?,
+                    None => writeln!(out, "{}", csv_field(&alias.address))?,
+                }
+            }
+        }
+    }
+
+    out.flush()
+}
+
+#[derive(Serialize)]
+struct AliasesOutput<'a> {
+    aliases: Vec<AliasOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct AliasOutput<'a> {
+    address: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    password: Option<&'a str>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alias_address() {
+        assert_eq!(
+            Alias::new("mosaic.malt.86", "example.com", Some("jane")).address,
+            "jane+mosaic.malt.86@example.com"
+        );
+        assert_eq!(
+            Alias::new("mosaic.malt.86", "example.com", None).address,
+            "mosaic.malt.86@example.com"
+        );
+    }
+}
@@ -0,0 +1,425 @@
+use std::ops::Deref;
+
+use colored::{ColoredString, Colorize};
+use motus::analysis::crack::{self, AttackModel};
+use motus::analysis::estimator::{Estimator, EstimatorKind};
+pub use motus::analysis::report::{strength_distribution, PasswordStrength, SecurityAnalysis};
+use motus::denylist::DenyList;
+use motus::policy::Policy;
+use serde::Serialize;
+
+use crate::i18n::Locale;
+use crate::policy::CheckReport;
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+
+/// Analyzer analyzes passwords with the settings chosen by the user for the reports: custom
+/// attacker models, the locale the reports are displayed in, whether to flag the characters
+/// that print ambiguously, the denied terms attackers would try first, the estimators to
+/// compare, whether to compare crack times to relatable durations, and the policy passwords
+/// are checked against.
+#[derive(Default)]
+pub struct Analyzer {
+    attacks: Vec<AttackModel>,
+    locale: Locale,
+    print_safe: bool,
+    deny_list: DenyList,
+    estimators: Vec<EstimatorKind>,
+    humanize: bool,
+    policy: Option<Policy>,
+}
+
+impl Analyzer {
+    pub fn new(attacks: Vec<AttackModel>, locale: Locale, print_safe: bool) -> Self {
+        Self {
+            attacks,
+            locale,
+            print_safe,
+            deny_list: DenyList::default(),
+            estimators: Vec::new(),
+            humanize: false,
+            policy: None,
+        }
+    }
+
+    /// with_deny_list has the analysis treat the terms of the deny list as dictionary words.
+    pub fn with_deny_list(mut self, deny_list: DenyList) -> Self {
+        self.deny_list = deny_list;
+        self
+    }
+
+    /// with_estimators has the analysis compare the estimates of the given estimators.
+    pub fn with_estimators(mut self, estimators: Vec<EstimatorKind>) -> Self {
+        self.estimators = estimators;
+        self
+    }
+
+    /// with_humanize has the reports follow crack times with relatable comparisons, such as
+    /// "about one World Cup cycle".
+    pub fn with_humanize(mut self, humanize: bool) -> Self {
+        self.humanize = humanize;
+        self
+    }
+
+    /// with_policy has the reports tell whether the password complies with the policy.
+    pub fn with_policy(mut self, policy: Option<Policy>) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// analyze performs the security analysis of the given password.
+    pub fn analyze<'a>(&self, password: &'a str) -> Report<'a> {
+        let dictionary: Vec<&str> = self.deny_list.terms().iter().map(String::as_str).collect();
+        let estimators: Vec<Box<dyn Estimator>> = self
+            .estimators
+            .iter()
+            .map(|kind| kind.estimator(self.deny_list.terms()))
+            .collect();
+        let mut analysis = SecurityAnalysis::new_with_dictionary(password, &dictionary)
+            .with_attacks(self.attacks.clone())
+            .with_estimators(&estimators);
+        if self.print_safe {
+            analysis = analysis.with_homoglyphs();
+        }
+
+        Report {
+            analysis,
+            compliance: self
+                .policy
+                .as_ref()
+                .map(|policy| CheckReport::new(policy, password)),
+            locale: self.locale,
+            humanize: self.humanize,
+        }
+    }
+}
+
+/// Report is the security analysis of a password, displayed in the locale chosen by the user,
+/// along with its compliance with the policy, if any. It serializes as the analysis itself, with
+/// the compliance under `policy`.
+#[derive(Serialize)]
+pub struct Report<'a> {
+    #[serde(flatten)]
+    analysis: SecurityAnalysis<'a>,
+
+    #[serde(rename = "policy", skip_serializing_if = "Option::is_none")]
+    compliance: Option<CheckReport>,
+
+    #[serde(skip)]
+    locale: Locale,
+
+    #[serde(skip)]
+    humanize: bool,
+}
+
+impl<'a> Deref for Report<'a> {
+    type Target = SecurityAnalysis<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.analysis
+    }
+}
+
+impl Report<'_> {
+    /// display_report prints the password along with its full analysis.
+    pub fn display_report(&self, table_style: TableStyle, max_width: usize) {
+        self.display_password_table(table_style, max_width);
+        self.display_analysis(table_style, max_width);
+    }
+
+    /// display_analysis prints the analysis of the password, without the password itself.
+    pub fn display_analysis(&self, table_style: TableStyle, max_width: usize) {
+        self.display_analysis_table(table_style, max_width);
+        self.display_estimates_table(table_style, max_width);
+        self.display_crack_times_table(table_style, max_width);
+        self.display_typo_table(table_style, max_width);
+        self.display_warnings_table(table_style, max_width);
+    }
+
+    fn display_password_table(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder(
+            self.locale.text("Generated Password").bold(),
+        )
+        .alignment(Alignment::Left)
+        .build()]));
+
+        table.add_row(Row::new(vec![TableCell::new(self.analysis.password())]));
+
+        println!("{}", table.render());
+    }
+
+    fn display_analysis_table(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder(
+            self.locale.text("Security Analysis"),
+        )
+        .col_span(2)
+        .alignment(Alignment::Left)
+        .build()]));
+
+        table.add_row(Row::new(vec![
+            TableCell::new(self.locale.text("Strength").bold()),
+            TableCell::builder(
+                self.analysis
+                    .strength()
+                    .to_localized_colored_string(self.locale),
+            )
+            .alignment(Alignment::Left)
+            .build(),
+        ]));
+
+        table.add_row(Row::new(vec![
+            TableCell::new(self.locale.text("Guesses").bold()),
+            TableCell::builder(self.analysis.guesses())
+                .alignment(Alignment::Left)
+                .build(),
+        ]));
+
+        println!("{}", table.render());
+    }
+
+    fn display_estimates_table(&self, table_style: TableStyle, max_width: usize) {
+        if self.analysis.estimates().is_empty() {
+            return;
+        }
+
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder(
+            self.locale.text("Strength estimators"),
+        )
+        .col_span(3)
+        .alignment(Alignment::Left)
+        .build()]));
+
+        for estimate in self.analysis.estimates() {
+            table.add_row(Row::new(vec![
+                TableCell::new(estimate.estimator.bold()),
+                TableCell::builder(
+                    PasswordStrength::from(estimate.score())
+                        .to_localized_colored_string(self.locale),
+                )
+                .alignment(Alignment::Left)
+                .build(),
+                TableCell::builder(format!("10^{:.0}", estimate.guesses_log10))
+                    .alignment(Alignment::Left)
+                    .build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+    }
+
+    fn display_crack_times_table(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder(
+            self.locale.text("Crack time estimations"),
+        )
+        .col_span(2)
+        .alignment(Alignment::Left)
+        .build()]));
+
+        for (label, time) in self.crack_time_rows() {
+            table.add_row(Row::new(vec![
+                TableCell::new(label.bold()),
+                TableCell::builder(time).alignment(Alignment::Left).build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+    }
+
+    /// crack_time_rows returns the label and the localized crack time of each reference scenario
+    /// and custom attacker model.
+    pub fn crack_time_rows(&self) -> Vec<(String, String)> {
+        let mut rows: Vec<(String, String)> = self
+            .analysis
+            .crack_times()
+            .into_iter()
+            .map(|crack_time| {
+                (
+                    self.locale.text(crack_time.label).to_string(),
+                    self.humanized(self.locale.duration(&crack_time.time), crack_time.seconds),
+                )
+            })
+            .collect();
+
+        for report in self.analysis.attack_reports() {
+            let time = self.locale.duration(&report.time);
+            let time = match report.cost {
+                Some(cost) => format!(
+                    "{} ({} {})",
+                    time,
+                    self.locale.text("costs"),
+                    self.locale.number(cost, 2)
+                ),
+                None => time,
+            };
+
+            rows.push((
+                format!(
+                    "{:e} {}",
+                    report.guesses_per_second,
+                    self.locale.text("attempts/second")
+                ),
+                self.humanized(time, report.seconds),
+            ));
+        }
+
+        rows
+    }
+
+    /// typo_rows returns the label and the localized value of each typo tolerance metric.
+    pub fn typo_rows(&self) -> Vec<(String, String)> {
+        let typo = self.analysis.typo();
+        vec![
+            (
+                self.locale.text("Typo likelihood").to_string(),
+                self.locale.percent(typo.typo_probability),
+            ),
+            (
+                self.locale.text("Neighbor passwords").to_string(),
+                format!(
+                    "{} ({} {}, {} {}, {} {})",
+                    self.count(typo.neighbors()),
+                    self.count(typo.adjacent_key_neighbors),
+                    self.locale.text("adjacent keys"),
+                    self.count(typo.case_slip_neighbors),
+                    self.locale.text("case slips"),
+                    self.count(typo.transposition_neighbors),
+                    self.locale.text("transpositions")
+                ),
+            ),
+        ]
+    }
+
+    /// warning_rows returns the localized category and message of each warning.
+    pub fn warning_rows(&self) -> Vec<(String, String)> {
+        self.analysis
+            .warnings()
+            .iter()
+            .map(|warning| {
+                let mut message =
+                    format!("'{}': {}", warning.token, self.locale.text(warning.message));
+                if !warning.lookalikes.is_empty() {
+                    message = format!("{} ('{}')", message, warning.lookalikes.join("', '"));
+                }
+                (
+                    self.locale.text(warning.category.label()).to_string(),
+                    message,
+                )
+            })
+            .collect()
+    }
+
+    /// compliance returns the compliance of the password with the policy, if any.
+    pub fn compliance(&self) -> Option<&CheckReport> {
+        self.compliance.as_ref()
+    }
+
+    /// locale returns the locale the report is displayed in.
+    pub fn locale(&self) -> Locale {
+        self.locale
+    }
+
+    // humanized follows the crack time with a relatable comparison for the duration in seconds,
+    // if the user asked for them.
+    fn humanized(&self, time: String, seconds: f64) -> String {
+        if !self.humanize {
+            return time;
+        }
+
+        format!("{}, {}", time, self.locale.text(crack::comparison(seconds)))
+    }
+
+    // count formats a number of passwords according to the locale.
+    #[allow(clippy::cast_precision_loss)] // counts are way below f64's precision limits
+    fn count(&self, count: usize) -> String {
+        self.locale.number(count as f64, 0)
+    }
+
+    fn display_typo_table(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder(
+            self.locale.text("Typo tolerance"),
+        )
+        .col_span(2)
+        .alignment(Alignment::Left)
+        .build()]));
+
+        for (label, value) in self.typo_rows() {
+            table.add_row(Row::new(vec![
+                TableCell::new(label.bold()),
+                TableCell::builder(value).alignment(Alignment::Left).build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+    }
+
+    fn display_warnings_table(&self, table_style: TableStyle, max_width: usize) {
+        if self.analysis.warnings().is_empty() {
+            return;
+        }
+
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder(
+            self.locale.text("Warnings"),
+        )
+        .col_span(2)
+        .alignment(Alignment::Left)
+        .build()]));
+
+        for (category, message) in self.warning_rows() {
+            table.add_row(Row::new(vec![
+                TableCell::new(category.bold()),
+                TableCell::builder(message)
+                    .alignment(Alignment::Left)
+                    .build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+    }
+}
+
+/// ColoredStrength colors password strengths for the terminal.
+pub trait ColoredStrength {
+    fn to_colored_string(&self) -> ColoredString {
+        self.to_localized_colored_string(Locale::En)
+    }
+
+    /// to_localized_colored_string returns the strength label translated in the given locale,
+    /// colored according to the strength.
+    fn to_localized_colored_string(&self, locale: Locale) -> ColoredString;
+}
+
+impl ColoredStrength for PasswordStrength {
+    fn to_localized_colored_string(&self, locale: Locale) -> ColoredString {
+        let label = locale.text(self.label());
+        match self {
+            PasswordStrength::VeryWeak => label.red(),
+            PasswordStrength::Weak => label.bright_red(),
+            PasswordStrength::Reasonable => label.yellow(),
+            PasswordStrength::Strong => label.bright_green(),
+            PasswordStrength::VeryStrong => label.green(),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use std::io::{self, BufWriter, Write};
+
+use motus::secret::Secret;
+use rand::Rng;
+use serde::Serialize;
+
+use crate::{csv_field, OutputFormat};
+
+/// Answer is a random answer to a security question, labeled with the question it belongs to.
+pub struct Answer {
+    pub question: String,
+    pub answer: Secret,
+}
+
+/// answer generates a random answer of `words` words, sharing no word with the `previous`
+/// answers, so that no answer hints at another.
+pub fn answer<R: Rng>(rng: &mut R, words: usize, previous: &[Answer]) -> String {
+    loop {
+        let answer =
+            motus::memorable_password(rng, words, motus::Separator::Space, 1, false, false);
+        let unrelated = answer.split(' ').all(|word| {
+            previous
+                .iter()
+                .all(|other| !other.answer.split(' ').any(|taken| taken == word))
+        });
+        if unrelated {
+            return answer;
+        }
+    }
+}
+
+/// display writes the answers to stdout in the requested format, along with their entropy in
+/// JSON.
+///
+/// Answers are never copied to the clipboard nor recorded in the history: they are meant to be
+/// stored in a password manager, next to the account's password.
+pub fn display(answers: &[Answer], entropy_bits: f64, format: &OutputFormat) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    match format {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            for answer in answers {
+                writeln!(out, "{}: {}", answer.question, answer.answer.expose())?;
+            }
+        }
+        OutputFormat::Json => {
+            let answers: Vec<AnswerOutput> = answers
+                .iter()
+                .map(|answer| AnswerOutput {
+                    question: &answer.question,
+                    answer: answer.answer.expose(),
+                    entropy_bits,
+                })
+                .collect();
+            serde_json::to_writer(&mut out, &AnswersOutput { answers })?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "question,answer")?;
+            for answer in answers {
+                writeln!(
+                    out,
+                    "{},{}",
+                    csv_field(&answer.question),
+                    csv_field(answer.answer.expose())
+                )?;
+            }
+        }
+    }
+
+    out.flush()
+}
+
+#[derive(Serialize)]
+struct AnswersOutput<'a> {
+    answers: Vec<AnswerOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct AnswerOutput<'a> {
+    question: &'a str,
+    answer: &'a str,
+    entropy_bits: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_answers_share_no_word() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut answers: Vec<Answer> = Vec::new();
+        for index in 0..50 {
+            let answer = answer(&mut rng, 3, &answers);
+            assert_eq!(answer.split(' ').count(), 3);
+            answers.push(Answer {
+                question: format!("question {index}"),
+                answer: Secret::new(answer),
+            });
+        }
+
+        let mut words: Vec<&str> = answers
+            .iter()
+            .flat_map(|answer| answer.answer.split(' '))
+            .collect();
+        words.sort_unstable();
+        words.dedup();
+        assert_eq!(words.len(), 150);
+    }
+}
@@ -0,0 +1,92 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use crate::history::{self, HistoryError};
+
+/// Request is what the program invoking motus as its askpass helper expects from it.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Request {
+    /// A secret, such as a password or a passphrase, typed without being echoed.
+    Secret,
+    /// A user name, typed with echo.
+    Username,
+    /// A yes or no answer, given through the exit status, as ssh-agent confirmations expect.
+    Confirm,
+    /// Nothing: the prompt is only displayed, such as a request to touch a security key.
+    Notify,
+}
+
+impl Request {
+    /// Returns what is expected for `prompt`, given the value of the `SSH_ASKPASS_PROMPT`
+    /// environment variable through which OpenSSH tells confirmations and notifications apart.
+    pub fn new(prompt: &str, ssh_askpass_prompt: Option<&str>) -> Self {
+        match ssh_askpass_prompt {
+            Some("confirm") => Request::Confirm,
+            Some("none") => Request::Notify,
+            _ if prompt.trim_start().to_lowercase().starts_with("username") => Request::Username,
+            _ => Request::Secret,
+        }
+    }
+}
+
+/// lookup returns the password most recently stored in the history at `path` for `label`, or
+/// None if there is none.
+pub fn lookup(path: &Path, label: &str) -> Result<Option<String>, HistoryError> {
+    Ok(history::recall(path, label)?.map(|entry| entry.password))
+}
+
+/// read_tty_line displays `prompt` on the terminal, and reads a line typed in it with echo.
+///
+/// The terminal is opened directly, as askpass helpers are run with their standard streams
+/// redirected.
+pub fn read_tty_line(prompt: &str) -> io::Result<String> {
+    #[cfg(unix)]
+    let (input, output) = ("/dev/tty", "/dev/tty");
+    #[cfg(not(unix))]
+    let (input, output) = ("CONIN$", "CONOUT$");
+
+    let mut terminal = OpenOptions::new().write(true).open(output)?;
+    write!(terminal, "{}", prompt)?;
+    terminal.flush()?;
+
+    let mut line = String::new();
+    BufReader::new(File::open(input)?).read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\n', '\r']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request() {
+        assert_eq!(
+            Request::new(
+                "Enter passphrase for key '/home/me/.ssh/id_ed25519': ",
+                None
+            ),
+            Request::Secret
+        );
+        assert_eq!(
+            Request::new("Password for 'https://me@github.com': ", None),
+            Request::Secret
+        );
+        assert_eq!(
+            Request::new("Username for 'https://github.com': ", None),
+            Request::Username
+        );
+        assert_eq!(
+            Request::new(
+                "Allow use of key /home/me/.ssh/id_ed25519?",
+                Some("confirm")
+            ),
+            Request::Confirm
+        );
+        assert_eq!(
+            Request::new("Confirm user presence for key ED25519-SK", Some("none")),
+            Request::Notify
+        );
+        assert_eq!(Request::new("Passphrase: ", Some("")), Request::Secret);
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use colored::Colorize;
+use motus::similarity::{similarity, DEFAULT_THRESHOLD};
+use serde::Serialize;
+
+use crate::analysis::{strength_distribution, ColoredStrength, PasswordStrength, SecurityAnalysis};
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+use crate::{csv_field, OutputFormat};
+
+// WEAKEST_ENTRIES is the number of weakest entries listed in the summary report.
+const WEAKEST_ENTRIES: usize = 5;
+
+/// Entry is a password to audit, along with the line it was found on.
+pub struct Entry {
+    pub line: usize,
+    pub password: String,
+}
+
+/// read_entries reads the passwords to audit from the file at `path`.
+///
+/// Without a `column`, the file is expected to hold one password per line, and empty lines are
+/// ignored. Otherwise, the file is parsed as CSV with a header row, and passwords are read from
+/// the given column, selected either by name or by its 1-based index.
+pub fn read_entries(path: &Path, column: Option<&str>) -> Result<Vec<Entry>, AuditError> {
+    let Some(column) = column else {
+        let content = fs::read_to_string(path)?;
+        return Ok(content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(index, line)| Entry {
+                line: index + 1,
+                password: line.to_string(),
+            })
+            .collect());
+    };
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .from_path(path)?;
+
+    let headers = reader.headers()?.clone();
+    let index = match column.parse::<usize>() {
+        Ok(index) if index >= 1 => index - 1,
+        _ => headers
+            .iter()
+            .position(|header| header == column)
+            .ok_or_else(|| AuditError::UnknownColumn(column.to_string()))?,
+    };
+
+    let mut entries = Vec::new();
+    for record in reader.records() {
+        let record = record?;
+        let line = record.position().map_or(0, |p| p.line() as usize);
+        match record.get(index) {
+            Some(password) if !password.is_empty() => entries.push(Entry {
+                line,
+                password: password.to_string(),
+            }),
+            _ => continue,
+        }
+    }
+
+    Ok(entries)
+}
+
+/// AuditReport is the summary of the analysis of a list of passwords.
+#[derive(Serialize)]
+pub struct AuditReport {
+    total: usize,
+    strengths: BTreeMap<u8, usize>,
+    weakest: Vec<WeakEntry>,
+    duplicates: Vec<Vec<usize>>,
+    clusters: Vec<Vec<usize>>,
+
+    #[serde(skip)]
+    entries: Vec<EntryReport>,
+}
+
+#[derive(Serialize, Clone)]
+struct WeakEntry {
+    line: usize,
+    strength: String,
+    guesses: String,
+}
+
+// EntryReport holds the result of the analysis of a single entry.
+struct EntryReport {
+    line: usize,
+    score: u8,
+    guesses_log10: f64,
+    guesses: String,
+    warnings: usize,
+}
+
+impl AuditReport {
+    /// Analyzes the given entries and summarizes the results.
+    ///
+    /// Duplicates are groups of lines holding the exact same password, while reuse clusters are
+    /// groups of lines holding distinct but similar passwords, such as `Summer2023!` and
+    /// `Summer2024!`.
+    pub fn new(entries: &[Entry]) -> Self {
+        let reports: Vec<EntryReport> = entries
+            .iter()
+            .map(|entry| {
+                let analysis = SecurityAnalysis::new(&entry.password);
+                EntryReport {
+                    line: entry.line,
+                    score: analysis.score(),
+                    guesses_log10: analysis.guesses_log10(),
+                    guesses: analysis.guesses(),
+                    warnings: analysis.warnings().len(),
+                }
+            })
+            .collect();
+
+        let strengths = strength_distribution(reports.iter().map(|report| report.score));
+
+        let mut weakest: Vec<&EntryReport> = reports.iter().collect();
+        weakest.sort_by(|a, b| a.guesses_log10.total_cmp(&b.guesses_log10));
+        let weakest = weakest
+            .into_iter()
+            .take(WEAKEST_ENTRIES)
+            .map(|report| WeakEntry {
+                line: report.line,
+                strength: PasswordStrength::from(report.score).to_string(),
+                guesses: report.guesses.clone(),
+            })
+            .collect();
+
+        // Group the lines by password to find exact duplicates
+        let mut lines_by_password: HashMap<&str, Vec<usize>> = HashMap::new();
+        for entry in entries {
+            lines_by_password
+                .entry(&entry.password)
+                .or_default()
+                .push(entry.line);
+        }
+
+        let mut unique: Vec<(&str, Vec<usize>)> = lines_by_password.into_iter().collect();
+        unique.sort_by_key(|(_, lines)| lines[0]);
+
+        let duplicates = unique
+            .iter()
+            .filter(|(_, lines)| lines.len() > 1)
+            .map(|(_, lines)| lines.clone())
+            .collect();
+
+        Self {
+            total: entries.len(),
+            strengths,
+            weakest,
+            duplicates,
+            clusters: reuse_clusters(&unique),
+            entries: reports,
+        }
+    }
+
+    /// Writes the report to stdout in the requested format.
+    pub fn display(&self, output: &OutputFormat) {
+        match output {
+            OutputFormat::Text
+            | OutputFormat::SystemdCred
+            | OutputFormat::Sops
+            | OutputFormat::Html => self.display_tables(TableStyle::extended(), 80),
+            OutputFormat::Json => println!("{}", serde_json::to_string(self).unwrap()),
+            OutputFormat::Csv => {
+                println!("line,strength,guesses,warnings");
+                for entry in &self.entries {
+                    println!(
+                        "{},{},{},{}",
+                        entry.line,
+                        csv_field(&PasswordStrength::from(entry.score).to_string()),
+                        entry.guesses,
+                        entry.warnings
+                    );
+                }
+            }
+        }
+    }
+
+    fn display_tables(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder(format!(
+            "Strength distribution ({} passwords)",
+            self.total
+        ))
+        .col_span(2)
+        .alignment(Alignment::Left)
+        .build()]));
+
+        for (&score, &count) in &self.strengths {
+            table.add_row(Row::new(vec![
+                TableCell::new(PasswordStrength::from(score).to_colored_string()),
+                TableCell::builder(count).alignment(Alignment::Left).build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder("Weakest passwords")
+            .col_span(2)
+            .alignment(Alignment::Left)
+            .build()]));
+
+        for entry in &self.weakest {
+            table.add_row(Row::new(vec![
+                TableCell::new(format!("line {}", entry.line).bold()),
+                TableCell::builder(format!("{} ({} guesses)", entry.strength, entry.guesses))
+                    .alignment(Alignment::Left)
+                    .build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+
+        for (title, groups) in [
+            ("Duplicated passwords", &self.duplicates),
+            ("Reuse clusters (similar passwords)", &self.clusters),
+        ] {
+            let mut table = Table::new();
+            table.max_column_width = max_width;
+            table.style = table_style;
+
+            table.add_row(Row::new(vec![TableCell::builder(title)
+                .alignment(Alignment::Left)
+                .build()]));
+
+            if groups.is_empty() {
+                table.add_row(Row::new(vec![TableCell::new("none")]));
+            }
+
+            for lines in groups {
+                let lines: Vec<String> = lines.iter().map(ToString::to_string).collect();
+                table.add_row(Row::new(vec![TableCell::new(format!(
+                    "lines {}",
+                    lines.join(", ")
+                ))]));
+            }
+
+            println!("{}", table.render());
+        }
+    }
+}
+
+// reuse_clusters groups the distinct passwords which are similar to each other, and returns the
+// lines they were found on. Similarity is transitive: if A is similar to B, and B to C, then A, B
+// and C belong to the same cluster.
+fn reuse_clusters(unique: &[(&str, Vec<usize>)]) -> Vec<Vec<usize>> {
+    let mut parents: Vec<usize> = (0..unique.len()).collect();
+
+    fn root(parents: &mut [usize], mut i: usize) -> usize {
+        while parents[i] != i {
+            parents[i] = parents[parents[i]];
+            i = parents[i];
+        }
+        i
+    }
+
+    for i in 0..unique.len() {
+        for j in (i + 1)..unique.len() {
+            if similarity(unique[i].0, unique[j].0) >= DEFAULT_THRESHOLD {
+                let (a, b) = (root(&mut parents, i), root(&mut parents, j));
+                parents[b] = a;
+            }
+        }
+    }
+
+    // Clusters made of a single distinct password are duplicates, not reuse
+    let mut clusters: BTreeMap<usize, (usize, Vec<usize>)> = BTreeMap::new();
+    for (i, (_, lines)) in unique.iter().enumerate() {
+        let (distinct, cluster_lines) = clusters.entry(root(&mut parents, i)).or_default();
+        *distinct += 1;
+        cluster_lines.extend(lines.iter().copied());
+    }
+
+    let mut clusters: Vec<Vec<usize>> = clusters
+        .into_values()
+        .filter(|(distinct, _)| *distinct > 1)
+        .map(|(_, lines)| lines)
+        .collect();
+
+    for lines in &mut clusters {
+        lines.sort_unstable();
+    }
+    clusters.sort();
+
+    clusters
+}
+
+/// AuditError represents the errors that can occur while reading the passwords to audit.
+#[derive(Debug)]
+pub enum AuditError {
+    Io(io::Error),
+    Csv(csv::Error),
+    UnknownColumn(String),
+}
+
+impl Display for AuditError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditError::Io(err) => write!(f, "unable to read the passwords file: {}", err),
+            AuditError::Csv(err) => write!(f, "unable to parse the passwords file: {}", err),
+            AuditError::UnknownColumn(column) => {
+                write!(f, "no column named '{}' in the passwords file", column)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for AuditError {
+    fn from(err: io::Error) -> Self {
+        AuditError::Io(err)
+    }
+}
+
+impl From<csv::Error> for AuditError {
+    fn from(err: csv::Error) -> Self {
+        AuditError::Csv(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reuse_clusters() {
+        let unique = vec![
+            ("Summer2023!", vec![1]),
+            ("mHYvjgQAKBHBIRYdpPAI", vec![2]),
+            ("Summer2024!", vec![3, 5]),
+            ("Summer2025!", vec![4]),
+        ];
+
+        assert_eq!(reuse_clusters(&unique), vec![vec![1, 3, 4, 5]]);
+    }
+
+    #[test]
+    fn test_reuse_clusters_ignores_duplicates() {
+        let unique = vec![("Summer2023!", vec![1, 2])];
+        assert!(reuse_clusters(&unique).is_empty());
+    }
+}
@@ -0,0 +1,565 @@
+use std::fmt::{Display, Formatter};
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use blake2::{Blake2s256, Digest};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::PasswordKind;
+
+/// Event records that passwords were generated, and how, as a line of the audit log.
+///
+/// It holds what is needed to demonstrate the passwords were generated with compliant parameters,
+/// and never the passwords themselves, nor any static text they were composed with. Events are
+/// chained: each one holds the digest of the line before it, for lines removed, reordered or
+/// duplicated to break the chain.
+#[derive(Serialize, Debug)]
+pub struct Event<'a> {
+    timestamp: u64,
+    version: &'static str,
+    kind: PasswordKind,
+    count: usize,
+    parameters: Value,
+    entropy_bits: f64,
+    label: Option<&'a str>,
+    previous: Option<String>,
+}
+
+impl<'a> Event<'a> {
+    /// Returns the event of `count` passwords of the given kind being generated now.
+    pub fn new(
+        kind: PasswordKind,
+        count: usize,
+        parameters: Value,
+        entropy_bits: f64,
+        label: Option<&'a str>,
+    ) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            timestamp,
+            version: env!("CARGO_PKG_VERSION"),
+            kind,
+            count,
+            parameters,
+            entropy_bits,
+            label,
+            previous: None,
+        }
+    }
+}
+
+/// Signer is the key audit log events are signed with, so that tampering with them is detected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Signer {
+    /// An OpenPGP key, on disk or on a smartcard, used through gpg.
+    Gpg(String),
+    /// The key in a PIV slot of a smartcard such as a YubiKey, used through yubico-piv-tool,
+    /// along with its algorithm.
+    Piv { slot: String, algorithm: String },
+}
+
+impl Signer {
+    /// Parses the given string as a signer: `gpg:KEY`, naming a key as gpg's --local-user does,
+    /// or `piv:SLOT`, optionally followed by `:ALGORITHM` when the key is not an ECCP256 one.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        const SLOTS: &[&str] = &["9a", "9c", "9d", "9e"];
+
+        match s.split(':').collect::<Vec<_>>().as_slice() {
+            ["gpg", key] if !key.is_empty() => Ok(Signer::Gpg(key.to_string())),
+            ["piv", slot, rest @ ..] if rest.len() <= 1 => {
+                let slot = slot.to_lowercase();
+                let retired =
+                    u8::from_str_radix(&slot, 16).is_ok_and(|n| (0x82..=0x95).contains(&n));
+                if !SLOTS.contains(&slot.as_str()) && !retired {
+                    return Err(format!("'{}' is not a PIV slot, such as 9c", slot));
+                }
+                let algorithm = rest.first().map_or("ECCP256", |algorithm| algorithm);
+                Ok(Signer::Piv {
+                    slot,
+                    algorithm: algorithm.to_uppercase(),
+                })
+            }
+            _ => Err("The signer must be gpg:KEY or piv:SLOT, such as piv:9c".to_string()),
+        }
+    }
+
+    /// Returns the signer with its gpg key named by the fingerprint of its primary key, for
+    /// signatures to be checked against the very key that made them rather than any key of the
+    /// keyring going by the same name.
+    fn resolve(&self) -> Result<Self, AuditLogError> {
+        let Signer::Gpg(key) = self else {
+            return Ok(self.clone());
+        };
+        let output = run(
+            "gpg",
+            &["--batch", "--with-colons", "--list-secret-keys", key],
+            &[],
+        )?;
+        String::from_utf8_lossy(&output)
+            .lines()
+            .find_map(|line| line.strip_prefix("fpr:")?.split(':').nth(8))
+            .filter(|fingerprint| !fingerprint.is_empty())
+            .map(|fingerprint| Signer::Gpg(fingerprint.to_string()))
+            .ok_or_else(|| AuditLogError::Failed("gpg", format!("no secret key found for {}", key)))
+    }
+
+    /// Returns the signature of `payload`.
+    fn sign(&self, payload: &[u8]) -> Result<Vec<u8>, AuditLogError> {
+        match self {
+            Signer::Gpg(key) => run(
+                "gpg",
+                &["--batch", "--detach-sign", "--local-user", key],
+                payload,
+            ),
+            Signer::Piv { slot, algorithm } => run(
+                "yubico-piv-tool",
+                &[
+                    "--action=verify-pin",
+                    "--sign",
+                    &format!("--slot={}", slot),
+                    &format!("--algorithm={}", algorithm),
+                    "--hash=SHA256",
+                    "--input=-",
+                    "--output=-",
+                ],
+                payload,
+            ),
+        }
+    }
+}
+
+impl Display for Signer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Signer::Gpg(key) => write!(f, "gpg:{}", key),
+            Signer::Piv { slot, algorithm } => write!(f, "piv:{}:{}", slot, algorithm),
+        }
+    }
+}
+
+/// Signature is the signature of an event, appended to its line of the audit log.
+#[derive(Serialize, Deserialize, Debug)]
+struct Signature {
+    signer: String,
+    value: String,
+}
+
+/// record appends the event to the audit log at `path`, as a line of JSON, creating the log and
+/// its parent directories as needed.
+///
+/// The log is only ever appended to, and is created readable by the current user only, as labels
+/// may name the accounts the passwords are for. The event is chained to the last line of the log.
+/// With a `signer`, the line holds the signature of the event, as serialized without it, in a
+/// `signature` field, gpg keys being recorded by fingerprint.
+pub fn record(path: &Path, mut event: Event, signer: Option<&Signer>) -> Result<(), AuditLogError> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    event.previous = match fs::read_to_string(path) {
+        Ok(content) => content.lines().last().map(digest),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut options = OpenOptions::new();
+    options.append(true).create(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    // The line is written at once, for concurrent runs not to interleave their events
+    let mut line = serde_json::to_string(&event).expect("events should be serializable");
+    if let Some(signer) = signer {
+        let signer = signer.resolve()?;
+        let signature = Signature {
+            signer: signer.to_string(),
+            value: STANDARD.encode(signer.sign(line.as_bytes())?),
+        };
+        line.pop();
+        line.push_str(",\"signature\":");
+        line.push_str(
+            &serde_json::to_string(&signature).expect("signatures should be serializable"),
+        );
+        line.push('}');
+    }
+    line.push('\n');
+    options.open(path)?.write_all(line.as_bytes())?;
+
+    Ok(())
+}
+
+/// Verification is the outcome of checking the signatures of an audit log, listing the lines of
+/// the events whose signature is invalid, of those that are not signed, and of those that do not
+/// follow the line before them in the chain.
+#[derive(Serialize, Debug, Default, PartialEq, Eq)]
+pub struct Verification {
+    pub valid: usize,
+    pub invalid: Vec<usize>,
+    pub unsigned: Vec<usize>,
+    pub broken: Vec<usize>,
+}
+
+/// verify checks the signature of every event of the audit log at `path`, with gpg for gpg
+/// signatures, and with openssl and the PEM public key at `piv_public_key` for PIV ones, and
+/// that every event holds the digest of the line before it.
+///
+/// A gpg signature is only valid if made by the key the line records, whatever other keys the
+/// keyring holds. Lines cut off the end of the log leave no trace in the chain: compare the
+/// number of events with what is expected.
+pub fn verify(path: &Path, piv_public_key: Option<&Path>) -> Result<Verification, AuditLogError> {
+    let content = fs::read_to_string(path)?;
+    let mut verification = Verification::default();
+    let signatures = SignatureDir::create()?;
+
+    let mut previous = None;
+    for (index, line) in content.lines().enumerate() {
+        let number = index + 1;
+        let chained = serde_json::from_str::<Value>(line)
+            .ok()
+            .and_then(|event| event.get("previous")?.as_str().map(str::to_string));
+        if chained != previous {
+            verification.broken.push(number);
+        }
+        previous = Some(digest(line));
+
+        let Some((payload, signature)) = split(line) else {
+            verification.unsigned.push(number);
+            continue;
+        };
+        let value = STANDARD
+            .decode(&signature.value)
+            .map_err(|_| AuditLogError::Malformed(number))?;
+
+        let signature_path = signatures.write(number, &value)?;
+        let path = signature_path.to_string_lossy();
+        let result = match Signer::parse(&signature.signer) {
+            Ok(Signer::Gpg(key)) => run(
+                "gpg",
+                &["--batch", "--status-fd", "1", "--verify", &path, "-"],
+                payload.as_bytes(),
+            )
+            .and_then(|status| {
+                if signed_by(&status, &key) {
+                    Ok(status)
+                } else {
+                    Err(AuditLogError::Failed(
+                        "gpg",
+                        format!("the signature was not made by {}", key),
+                    ))
+                }
+            }),
+            Ok(Signer::Piv { .. }) => {
+                let key = piv_public_key.ok_or(AuditLogError::MissingPublicKey)?;
+                run(
+                    "openssl",
+                    &[
+                        "dgst",
+                        "-sha256",
+                        "-verify",
+                        &key.to_string_lossy(),
+                        "-signature",
+                        &path,
+                    ],
+                    payload.as_bytes(),
+                )
+            }
+            Err(_) => Err(AuditLogError::Malformed(number)),
+        };
+
+        match result {
+            Ok(_) => verification.valid += 1,
+            Err(AuditLogError::Failed(..)) => verification.invalid.push(number),
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(verification)
+}
+
+// split returns the event of a signed line, as it was serialized when signed, along with its
+// signature, or None if the line is not signed.
+//
+// The line is split as text rather than parsed and serialized again, which could round floats
+// differently. The signature is always the last field, and no string of the event can hold the
+// unescaped quotes of its key.
+fn split(line: &str) -> Option<(String, Signature)> {
+    const KEY: &str = ",\"signature\":";
+
+    let index = line.rfind(KEY)?;
+    let signature = line[index + KEY.len()..].strip_suffix('}')?;
+    let signature = serde_json::from_str(signature).ok()?;
+    Some((format!("{}}}", &line[..index]), signature))
+}
+
+// digest returns the digest a line of the audit log is chained to the next one with, in
+// hexadecimal.
+fn digest(line: &str) -> String {
+    format!("{:x}", Blake2s256::digest(line.as_bytes()))
+}
+
+// signed_by returns whether gpg's `status` output reports a good signature by `key`, the
+// fingerprint of the signing key or of its primary key.
+fn signed_by(status: &[u8], key: &str) -> bool {
+    String::from_utf8_lossy(status)
+        .lines()
+        .filter_map(|line| line.strip_prefix("[GNUPG:] VALIDSIG "))
+        .any(|fields| {
+            let fields: Vec<&str> = fields.split(' ').collect();
+            // The fingerprints of the signing key, and of its primary key
+            [fields.first().copied(), fields.get(9).copied()]
+                .into_iter()
+                .flatten()
+                .any(|fingerprint| fingerprint.eq_ignore_ascii_case(key))
+        })
+}
+
+// SignatureDir is a new directory readable by the current user only, for the verifying tools to
+// read the signatures of the events from, removed along with them once dropped.
+struct SignatureDir(PathBuf);
+
+impl SignatureDir {
+    fn create() -> io::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "motus-audit-log-{}-{:016x}",
+            std::process::id(),
+            rand::random::<u64>()
+        ));
+        // Creating the directory fails rather than reuse whatever is at its path
+        let mut builder = DirBuilder::new();
+        #[cfg(unix)]
+        std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+        builder.create(&path)?;
+        Ok(Self(path))
+    }
+
+    // write writes the signature of the event at line `number` to a new file, and returns its
+    // path.
+    fn write(&self, number: usize, signature: &[u8]) -> io::Result<PathBuf> {
+        let path = self.0.join(format!("{}.sig", number));
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options.open(&path)?.write_all(signature)?;
+        Ok(path)
+    }
+}
+
+impl Drop for SignatureDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// run runs `program` with `args`, handing `input` over on its standard input, and returns its
+// standard output.
+fn run(program: &'static str, args: &[&str], input: &[u8]) -> Result<Vec<u8>, AuditLogError> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => AuditLogError::NotFound(program),
+            _ => AuditLogError::Io(err),
+        })?;
+
+    child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(input)?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(AuditLogError::Failed(program, stderr.trim().to_string()));
+    }
+
+    Ok(output.stdout)
+}
+
+/// AuditLogError represents the errors that can occur while writing to the audit log, or
+/// verifying its signatures.
+#[derive(Debug)]
+pub enum AuditLogError {
+    Io(io::Error),
+    NotFound(&'static str),
+    Failed(&'static str, String),
+    Malformed(usize),
+    MissingPublicKey,
+}
+
+impl Display for AuditLogError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuditLogError::Io(err) => write!(f, "unable to access the audit log: {}", err),
+            AuditLogError::NotFound(program) => write!(
+                f,
+                "{} was not found; install it to sign or verify the audit log",
+                program
+            ),
+            AuditLogError::Failed(program, err) => write!(f, "{} failed: {}", program, err),
+            AuditLogError::Malformed(line) => {
+                write!(f, "the signature on line {} of the audit log is malformed", line)
+            }
+            AuditLogError::MissingPublicKey => write!(
+                f,
+                "the audit log holds PIV signatures; give the public key of the slot with --piv-public-key"
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for AuditLogError {
+    fn from(err: io::Error) -> Self {
+        AuditLogError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_record_appends() {
+        let path = std::env::temp_dir().join(format!(
+            "motus-audit-log-{}/audit.jsonl",
+            std::process::id()
+        ));
+
+        let parameters = json!({"characters": 20, "numbers": true, "symbols": false});
+        record(
+            &path,
+            Event::new(PasswordKind::Random, 1, parameters, 119.1, Some("github")),
+            None,
+        )
+        .unwrap();
+        record(
+            &path,
+            Event::new(PasswordKind::Pin, 3, json!({"numbers": 6}), 19.9, None),
+            None,
+        )
+        .unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let events: Vec<Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["kind"], "random");
+        assert_eq!(events[0]["parameters"]["characters"], 20);
+        assert_eq!(events[0]["label"], "github");
+        assert_eq!(events[1]["count"], 3);
+        assert_eq!(events[1]["label"], Value::Null);
+
+        // Each event is chained to the line before it
+        assert_eq!(events[0]["previous"], Value::Null);
+        assert_eq!(
+            events[1]["previous"],
+            digest(content.lines().next().unwrap())
+        );
+        let verification = verify(&path, None).unwrap();
+        assert_eq!(verification.unsigned, [1, 2]);
+        assert!(verification.broken.is_empty());
+
+        // Removing, duplicating or reordering lines breaks the chain
+        let lines: Vec<&str> = content.lines().collect();
+        for (tampered, broken) in [
+            (vec![lines[1]], vec![1]),
+            (vec![lines[0], lines[0], lines[1]], vec![2]),
+            (vec![lines[1], lines[0]], vec![1, 2]),
+        ] {
+            fs::write(&path, tampered.join("\n") + "\n").unwrap();
+            assert_eq!(verify(&path, None).unwrap().broken, broken);
+        }
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_signer_parse() {
+        assert_eq!(
+            Signer::parse("gpg:audit@example.com"),
+            Ok(Signer::Gpg("audit@example.com".to_string()))
+        );
+        assert_eq!(
+            Signer::parse("piv:9C"),
+            Ok(Signer::Piv {
+                slot: "9c".to_string(),
+                algorithm: "ECCP256".to_string()
+            })
+        );
+        assert_eq!(
+            Signer::parse("piv:82:rsa2048").map(|signer| signer.to_string()),
+            Ok("piv:82:RSA2048".to_string())
+        );
+        assert!(Signer::parse("gpg:").is_err());
+        assert!(Signer::parse("piv:9f").is_err());
+        assert!(Signer::parse("ssh:id_ed25519").is_err());
+    }
+
+    #[test]
+    fn test_signed_by() {
+        let status = b"[GNUPG:] NEWSIG\n[GNUPG:] GOODSIG 4F1E2D3C4B5A6978 audit@example.com\n[GNUPG:] VALIDSIG 0A1B2C3D4E5F60718293A4B5C6D7E8F901234567 2026-10-17 1792167257 0 4 0 22 10 00 F1E2D3C4B5A69788796A5B4C3D2E1F0011223344\n";
+
+        assert!(signed_by(
+            status,
+            "0a1b2c3d4e5f60718293a4b5c6d7e8f901234567"
+        ));
+        assert!(signed_by(
+            status,
+            "F1E2D3C4B5A69788796A5B4C3D2E1F0011223344"
+        ));
+        assert!(!signed_by(status, "audit@example.com"));
+        assert!(!signed_by(status, "4F1E2D3C4B5A6978"));
+        assert!(!signed_by(
+            b"[GNUPG:] BADSIG 4F1E2D3C4B5A6978 audit\n",
+            "4F1E2D3C4B5A6978"
+        ));
+    }
+
+    #[test]
+    fn test_split_restores_signed_payload() {
+        let event = Event::new(
+            PasswordKind::Random,
+            1,
+            json!({"characters": 20}),
+            119.1,
+            None,
+        );
+        let payload = serde_json::to_string(&event).unwrap();
+        let line = format!(
+            "{},\"signature\":{{\"signer\":\"gpg:audit\",\"value\":\"c2ln\"}}}}",
+            &payload[..payload.len() - 1]
+        );
+
+        let (signed, signature) = split(&line).unwrap();
+        assert_eq!(signed, payload);
+        assert_eq!(signature.signer, "gpg:audit");
+        assert!(split(&payload).is_none());
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+
+use clap::ValueEnum;
+use motus::ct;
+use serde::{Deserialize, Serialize};
+
+use crate::envelope::{self, EnvelopeError};
+use crate::history::HistoryEntry;
+
+// FORMAT identifies motus store exports.
+const FORMAT: &str = "motus-store";
+
+// VERSION is the version of the export format.
+const VERSION: u32 = 1;
+
+/// PASSPHRASE_ENV is the environment variable the export passphrase is read from, if set, instead
+/// of prompting for it.
+pub const PASSPHRASE_ENV: &str = "MOTUS_EXPORT_PASSPHRASE";
+
+/// ExportFormat is the format a store is exported in.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum ExportFormat {
+    /// A JSON document holding the entries of the store, sealed with the export passphrase
+    EncryptedJson,
+}
+
+/// Export is the document a store is exported as.
+///
+/// The entries are sealed in an envelope, as `motus wrap` produces, with a key derived from the
+/// export passphrase rather than from the store's own: exporting re-wraps them, so that the
+/// export can be imported on another machine under a new passphrase without ever revealing the
+/// old one.
+#[derive(Serialize, Deserialize)]
+struct Export {
+    format: String,
+    version: u32,
+    entries: usize,
+    envelope: String,
+}
+
+/// export returns the given entries as an export document, sealed with `passphrase`.
+pub fn export(entries: &[HistoryEntry], passphrase: &str) -> Result<String, BackupError> {
+    let plaintext = serde_json::to_vec(entries).expect("entries should be serializable");
+    let sealed = envelope::seal(&plaintext, passphrase)?;
+
+    let document = Export {
+        format: FORMAT.to_string(),
+        version: VERSION,
+        entries: entries.len(),
+        envelope: envelope::armor(&sealed),
+    };
+    Ok(serde_json::to_string_pretty(&document).expect("exports should be serializable"))
+}
+
+/// import returns the entries of the export document `data`, opening it with `passphrase`.
+pub fn import(data: &[u8], passphrase: &str) -> Result<Vec<HistoryEntry>, BackupError> {
+    let document: Export = serde_json::from_slice(data).map_err(|_| BackupError::Malformed)?;
+    if document.format != FORMAT {
+        return Err(BackupError::Malformed);
+    }
+    if document.version != VERSION {
+        return Err(BackupError::UnsupportedVersion(document.version));
+    }
+
+    let sealed = envelope::dearmor(document.envelope.as_bytes())?;
+    let plaintext = envelope::open(&sealed, passphrase)?;
+    serde_json::from_slice(&plaintext).map_err(|_| BackupError::Malformed)
+}
+
+/// read_passphrase returns the export passphrase from the environment if set, or prompts for it
+/// otherwise, twice when `confirm` is set, as a typo would make the export unreadable.
+pub fn read_passphrase(confirm: bool) -> Result<String, BackupError> {
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            let passphrase = rpassword::prompt_password("Export passphrase: ")?;
+            if confirm
+                && !ct::str_eq(
+                    &rpassword::prompt_password("Confirm the passphrase: ")?,
+                    &passphrase,
+                )
+            {
+                return Err(EnvelopeError::PassphraseMismatch.into());
+            }
+            passphrase
+        }
+    };
+
+    if passphrase.is_empty() {
+        return Err(EnvelopeError::EmptyPassphrase.into());
+    }
+    Ok(passphrase)
+}
+
+/// BackupError represents the errors that can occur while exporting or importing a store.
+#[derive(Debug)]
+pub enum BackupError {
+    Envelope(EnvelopeError),
+    Malformed,
+    UnsupportedVersion(u32),
+}
+
+impl Display for BackupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BackupError::Envelope(EnvelopeError::Decryption) => write!(
+                f,
+                "unable to open the export: wrong passphrase, or the export was tampered with"
+            ),
+            BackupError::Envelope(err) => write!(f, "{}", err),
+            BackupError::Malformed => write!(f, "the input is not a motus store export"),
+            BackupError::UnsupportedVersion(version) => write!(
+                f,
+                "the export is in version {} of the format, which this version of motus does not support",
+                version
+            ),
+        }
+    }
+}
+
+impl From<EnvelopeError> for BackupError {
+    fn from(err: EnvelopeError) -> Self {
+        BackupError::Envelope(err)
+    }
+}
+
+impl From<io::Error> for BackupError {
+    fn from(err: io::Error) -> Self {
+        BackupError::Envelope(EnvelopeError::Io(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::PasswordKind;
+
+    fn entry(label: &str, password: &str) -> HistoryEntry {
+        HistoryEntry {
+            label: label.to_string(),
+            kind: PasswordKind::Random,
+            password: password.to_string(),
+            created_at: 1_700_000_000,
+            tags: BTreeMap::new(),
+            parameters: None,
+        }
+    }
+
+    #[test]
+    fn test_export_round_trip() {
+        let entries = vec![
+            entry("db", "mHYvjgQAKBHBIRYdpPAI"),
+            entry("api", "kVHltdbFmiaYXNZmZlTy"),
+        ];
+
+        let document = export(&entries, "new passphrase").unwrap();
+        assert!(!document.contains("mHYvjgQAKBHBIRYdpPAI"));
+        assert_eq!(
+            import(document.as_bytes(), "new passphrase").unwrap(),
+            entries
+        );
+        assert!(matches!(
+            import(document.as_bytes(), "old passphrase"),
+            Err(BackupError::Envelope(EnvelopeError::Decryption))
+        ));
+    }
+
+    #[test]
+    fn test_import_rejects_other_documents() {
+        assert!(matches!(
+            import(b"mHYvjgQAKBHBIRYdpPAI", "k"),
+            Err(BackupError::Malformed)
+        ));
+        assert!(matches!(
+            import(
+                br#"{"format": "motus-store", "version": 2, "entries": 0, "envelope": ""}"#,
+                "k"
+            ),
+            Err(BackupError::UnsupportedVersion(2))
+        ));
+    }
+}
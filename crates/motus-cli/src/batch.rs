@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use colored::Colorize;
+use motus::secret::Secret;
+use serde::Serialize;
+
+use crate::analysis::{strength_distribution, ColoredStrength, PasswordStrength, SecurityAnalysis};
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+
+/// BatchSummary holds aggregate statistics about a batch of generated passwords.
+///
+/// Entropy is estimated in bits from the number of guesses needed to find each password, which
+/// keeps it comparable across memorable, random and PIN passwords.
+#[derive(Serialize)]
+pub struct BatchSummary {
+    count: usize,
+    min_entropy: f64,
+    median_entropy: f64,
+    max_entropy: f64,
+    strengths: BTreeMap<u8, usize>,
+}
+
+impl BatchSummary {
+    /// Analyzes every password of the batch and aggregates the results.
+    pub fn new(passwords: &[Secret]) -> Self {
+        let analyses: Vec<SecurityAnalysis> = passwords
+            .iter()
+            .map(|password| SecurityAnalysis::new(password))
+            .collect();
+
+        let mut entropies: Vec<f64> = analyses
+            .iter()
+            .map(|analysis| round(analysis.guesses_log10() * std::f64::consts::LOG2_10))
+            .collect();
+        entropies.sort_by(f64::total_cmp);
+
+        Self {
+            count: passwords.len(),
+            min_entropy: entropies.first().copied().unwrap_or_default(),
+            median_entropy: median(&entropies),
+            max_entropy: entropies.last().copied().unwrap_or_default(),
+            strengths: strength_distribution(analyses.iter().map(SecurityAnalysis::score)),
+        }
+    }
+
+    /// display prints the aggregate statistics as tables.
+    pub fn display(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder(format!(
+            "Batch analysis ({} passwords)",
+            self.count
+        ))
+        .col_span(2)
+        .alignment(Alignment::Left)
+        .build()]));
+
+        for (label, entropy) in [
+            ("Minimum entropy", self.min_entropy),
+            ("Median entropy", self.median_entropy),
+            ("Maximum entropy", self.max_entropy),
+        ] {
+            table.add_row(Row::new(vec![
+                TableCell::new(label.bold()),
+                TableCell::builder(format!("{:.1} bits", entropy))
+                    .alignment(Alignment::Left)
+                    .build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder("Strength distribution")
+            .col_span(2)
+            .alignment(Alignment::Left)
+            .build()]));
+
+        for (&score, &count) in &self.strengths {
+            table.add_row(Row::new(vec![
+                TableCell::new(PasswordStrength::from(score).to_colored_string()),
+                TableCell::builder(count).alignment(Alignment::Left).build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+    }
+}
+
+// median returns the median of the given sorted values, or 0 if there are none.
+fn median(sorted: &[f64]) -> f64 {
+    match sorted.len() {
+        0 => 0.0,
+        n if n % 2 == 0 => round((sorted[n / 2 - 1] + sorted[n / 2]) / 2.0),
+        n => sorted[n / 2],
+    }
+}
+
+// round rounds the given entropy to one decimal, which is as precise as the estimate gets.
+fn round(entropy: f64) -> f64 {
+    (entropy * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&[]), 0.0);
+        assert_eq!(median(&[1.0, 2.0, 9.0]), 2.0);
+        assert_eq!(median(&[1.0, 2.0, 3.0, 9.0]), 2.5);
+    }
+}
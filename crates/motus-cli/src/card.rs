@@ -0,0 +1,124 @@
+use std::fmt::Write;
+
+use clap::ValueEnum;
+use motus::card::Card;
+
+/// CardFormat is the format a password card is rendered in.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum CardFormat {
+    /// A plain text grid, for the terminal
+    Text,
+    /// A markdown table, for notes and documents
+    Markdown,
+    /// An SVG image, ready to be printed or converted to PDF
+    #[cfg(feature = "qr")]
+    Svg,
+}
+
+/// render renders the card in the given format.
+pub fn render(card: &Card, format: CardFormat) -> String {
+    match format {
+        CardFormat::Text => text(card),
+        CardFormat::Markdown => markdown(card),
+        #[cfg(feature = "qr")]
+        CardFormat::Svg => motus::render::card_svg(card),
+    }
+}
+
+// text renders the card as a plain text grid, with a header line of column labels and each row
+// prefixed with its number.
+fn text(card: &Card) -> String {
+    let width = card.rows().len().to_string().len();
+    let mut output = String::new();
+
+    let _ = writeln!(
+        output,
+        "{:width$} | {}",
+        "",
+        join(card.column_labels().into_iter())
+    );
+    let _ = writeln!(
+        output,
+        "{}-+-{}",
+        "-".repeat(width),
+        "-".repeat((card.column_labels().len() * 2).saturating_sub(1))
+    );
+    for (i, row) in card.rows().iter().enumerate() {
+        let _ = writeln!(output, "{:>width$} | {}", i + 1, join(row.chars()));
+    }
+
+    output
+}
+
+// markdown renders the card as a markdown table, escaping the characters markdown would
+// otherwise interpret.
+fn markdown(card: &Card) -> String {
+    let labels = card.column_labels();
+    let mut output = String::new();
+
+    let _ = writeln!(
+        output,
+        "|   | {} |",
+        labels
+            .iter()
+            .map(char::to_string)
+            .collect::<Vec<_>>()
+            .join(" | ")
+    );
+    let _ = writeln!(output, "|---|{}", ":-:|".repeat(labels.len()));
+    for (i, row) in card.rows().iter().enumerate() {
+        let cells: Vec<String> = row.chars().map(markdown_escape).collect();
+        let _ = writeln!(output, "| {} | {} |", i + 1, cells.join(" | "));
+    }
+
+    output
+}
+
+// markdown_escape returns the character as a markdown table cell, escaping the ones markdown
+// would otherwise interpret.
+fn markdown_escape(c: char) -> String {
+    if c.is_ascii_punctuation() {
+        format!("\\{}", c)
+    } else {
+        c.to_string()
+    }
+}
+
+// join joins characters with spaces.
+fn join(chars: impl Iterator<Item = char>) -> String {
+    chars.map(String::from).collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use motus::policy::Policy;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_render() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let card = Card::generate(&mut rng, 10, 4, true, true, &Policy::new());
+
+        let text = render(&card, CardFormat::Text);
+        assert!(text.starts_with("   | A B C D\n---+--------\n"));
+        assert_eq!(text.lines().count(), 12);
+
+        let markdown = render(&card, CardFormat::Markdown);
+        assert!(markdown.starts_with("|   | A | B | C | D |\n|---|:-:|:-:|:-:|:-:|\n| 1 | "));
+
+        #[cfg(feature = "qr")]
+        {
+            let svg = render(&card, CardFormat::Svg);
+            assert!(svg.starts_with("<svg "));
+            assert_eq!(svg.matches("<text").count(), 10 * 4 + 10 + 4);
+        }
+    }
+
+    #[test]
+    fn test_markdown_escape() {
+        assert_eq!(markdown_escape('|'), "\\|");
+        assert_eq!(markdown_escape('a'), "a");
+    }
+}
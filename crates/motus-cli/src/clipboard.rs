@@ -0,0 +1,644 @@
+use std::fmt::{Display, Formatter};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+#[cfg(feature = "headless")]
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+#[cfg(feature = "clipboard")]
+use std::thread;
+#[cfg(feature = "clipboard")]
+use std::time::Duration;
+
+#[cfg(feature = "clipboard")]
+use arboard::{Clipboard, Set};
+use clap::ValueEnum;
+
+// OCCUPIED_RETRIES is how many times copying is retried while another application holds the
+// clipboard, which happens frequently, if briefly, on Windows.
+#[cfg(feature = "clipboard")]
+const OCCUPIED_RETRIES: u32 = 5;
+
+// OCCUPIED_BACKOFF is the delay before the first retry, doubled before each subsequent one.
+#[cfg(feature = "clipboard")]
+const OCCUPIED_BACKOFF: Duration = Duration::from_millis(20);
+
+/// ClipboardMode controls whether the generated password is copied to the clipboard.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClipboardMode {
+    /// Copy the password, unless no clipboard is plausibly available
+    #[default]
+    Auto,
+    /// Copy the password, even if no clipboard seems to be available
+    Force,
+}
+
+/// ClipboardContent selects what is copied to the clipboard when a single password is generated.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ClipboardContent {
+    /// Copy the password itself
+    #[default]
+    Password,
+    /// Copy the JSON output, password included, for GUI tools reading structured data
+    Json,
+    /// Copy nothing, as with --no-clipboard
+    None,
+}
+
+/// CopyOptions are the settings of a copy to the clipboard.
+#[derive(Clone, Debug, Default)]
+pub struct CopyOptions {
+    /// Mark the contents with the hints asking clipboard managers not to persist them
+    pub hints: bool,
+    /// Keep the contents from syncing to other devices through Apple's Universal Clipboard
+    pub local_only: bool,
+    /// Write the contents to this file instead of any clipboard
+    #[cfg(feature = "headless")]
+    pub file: Option<PathBuf>,
+}
+
+/// copy sets the system clipboard's contents to `text`, trying each available backend in turn
+/// until one succeeds.
+///
+/// On Wayland, the `wl-copy` command from wl-clipboard is tried first, as arboard's Wayland
+/// support misbehaves with some compositors. It serves the password for a single paste, after
+/// which the clipboard is cleared; it cannot set the hints below, which the single paste makes
+/// moot anyway.
+///
+/// With `local_only`, the contents are marked as belonging to the current host only, so they do
+/// not sync to other devices through Universal Clipboard. As only macOS supports it, and as
+/// syncing the password would be worse than not copying it, no other backend is tried then.
+///
+/// Otherwise, and if `hints` is set, the contents are marked with the platform-specific
+/// hints asking clipboard managers not to persist them: the `x-kde-passwordManagerHint` MIME
+/// type on Linux, `ExcludeClipboardContentFromMonitorProcessing` on Windows, and the concealed
+/// pasteboard type on macOS. Copying is retried with a backoff while another application holds
+/// the clipboard.
+///
+/// With the `headless` feature, the contents are written to `file` instead if it is set, and
+/// sent to the terminal with an OSC 52 escape sequence when no other backend succeeds: terminal
+/// emulators supporting it set their host's clipboard, even across SSH. Without the `clipboard`
+/// feature, motus does not link arboard and the platform clipboard libraries, which leaves
+/// `wl-copy` and OSC 52 as the only backends.
+pub fn copy(text: &str, options: &CopyOptions) -> Result<(), ClipboardError> {
+    #[cfg(feature = "headless")]
+    if let Some(path) = &options.file {
+        return file_copy(text, path);
+    }
+
+    let mut result = Err(ClipboardError::NoBackend);
+    for backend in backends(is_set("WAYLAND_DISPLAY"), options.local_only) {
+        result = match backend {
+            Backend::WlCopy => wl_copy(text),
+            #[cfg(feature = "clipboard")]
+            Backend::Arboard => retry_occupied(|| arboard_copy(text, options.hints)),
+            Backend::LocalPasteboard => local_pasteboard_copy(text, options.hints),
+            #[cfg(feature = "headless")]
+            Backend::Osc52 => osc52_copy(text),
+        };
+        if result.is_ok() {
+            break;
+        }
+    }
+
+    result
+}
+
+/// copy_in_turn copies the `fields`, such as a username and a password, to the clipboard one
+/// after the other, as password managers fill login forms: each field is copied once the previous
+/// one is pasted. Fields are pairs of a name, shown in the instructions, and contents.
+///
+/// On Wayland, `wl-copy` serves each field for a single paste and reports it, so that the next
+/// field is copied as soon as the previous one is pasted. Other backends report no paste, and
+/// Enter has to be pressed on the terminal instead, which is opened directly, so that the output
+/// can still be redirected.
+pub fn copy_in_turn(fields: &[(&str, &str)], options: &CopyOptions) -> Result<(), ClipboardError> {
+    #[cfg(feature = "headless")]
+    let to_file = options.file.is_some();
+    #[cfg(not(feature = "headless"))]
+    let to_file = false;
+    let wayland = is_set("WAYLAND_DISPLAY") && !options.local_only && !to_file;
+
+    for (index, (name, text)) in fields.iter().enumerate() {
+        let Some((next, _)) = fields.get(index + 1) else {
+            copy(text, options)?;
+            eprintln!("note: {} copied to the clipboard", name);
+            break;
+        };
+
+        if wayland && wl_copy_until_pasted(text, name).is_ok() {
+            continue;
+        }
+        copy(text, options)?;
+        crate::askpass::read_tty_line(&format!(
+            "{} copied to the clipboard; press Enter once pasted to copy the {} ",
+            name, next
+        ))?;
+    }
+
+    Ok(())
+}
+
+// wl_copy_until_pasted sets the clipboard's contents with the `wl-copy` command, for a single
+// paste, and waits for it.
+fn wl_copy_until_pasted(text: &str, name: &str) -> Result<(), ClipboardError> {
+    let mut child = Command::new("wl-copy")
+        .args(["--foreground", "--paste-once"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    eprintln!(
+        "note: {} copied to the clipboard; paste it to continue",
+        name
+    );
+
+    match child.wait()? {
+        status if status.success() => Ok(()),
+        status => Err(ClipboardError::WlCopy(status.code())),
+    }
+}
+
+// Backend is a way of setting the clipboard's contents.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Backend {
+    WlCopy,
+    #[cfg(feature = "clipboard")]
+    Arboard,
+    LocalPasteboard,
+    #[cfg(feature = "headless")]
+    Osc52,
+}
+
+// backends returns the backends to try, in order, depending on whether a Wayland session is
+// running and whether the contents must stay on the current host.
+fn backends(wayland: bool, local_only: bool) -> Vec<Backend> {
+    if local_only {
+        return vec![Backend::LocalPasteboard];
+    }
+
+    let mut backends = Vec::new();
+    if wayland {
+        backends.push(Backend::WlCopy);
+    }
+    #[cfg(feature = "clipboard")]
+    backends.push(Backend::Arboard);
+    #[cfg(feature = "headless")]
+    backends.push(Backend::Osc52);
+    backends
+}
+
+// wl_copy sets the clipboard's contents with the `wl-copy` command, for a single paste.
+fn wl_copy(text: &str) -> Result<(), ClipboardError> {
+    let mut child = Command::new("wl-copy")
+        .arg("--paste-once")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+
+    match child.wait()? {
+        status if status.success() => Ok(()),
+        status => Err(ClipboardError::WlCopy(status.code())),
+    }
+}
+
+// osc52_copy sends the contents to the terminal on stderr with an OSC 52 escape sequence, for
+// it to set its host's clipboard. Terminals do not acknowledge the sequence, so this succeeds
+// even with a terminal ignoring it.
+#[cfg(feature = "headless")]
+fn osc52_copy(text: &str) -> Result<(), ClipboardError> {
+    let mut stderr = io::stderr();
+    if !stderr.is_terminal() {
+        return Err(ClipboardError::Unsupported(
+            "no terminal to send the OSC 52 sequence to",
+        ));
+    }
+
+    stderr
+        .write_all(osc52_sequence(text, is_set("TMUX")).as_bytes())
+        .and_then(|()| stderr.flush())
+        .map_err(ClipboardError::Terminal)
+}
+
+// osc52_sequence returns the OSC 52 escape sequence setting the clipboard to `text`, wrapped in
+// a passthrough sequence when running inside tmux, which would swallow it otherwise.
+#[cfg(feature = "headless")]
+fn osc52_sequence(text: &str, tmux: bool) -> String {
+    use base64::Engine;
+
+    let sequence = format!(
+        "\x1b]52;c;{}\x07",
+        base64::engine::general_purpose::STANDARD.encode(text)
+    );
+    if tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+// file_copy writes the contents to the file at `path`, readable by the current user only.
+#[cfg(feature = "headless")]
+fn file_copy(text: &str, path: &Path) -> Result<(), ClipboardError> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(ClipboardError::File)
+}
+
+// retry_occupied runs `copy`, retrying it with an exponential backoff for as long as another
+// application holds the clipboard, up to OCCUPIED_RETRIES times.
+#[cfg(feature = "clipboard")]
+fn retry_occupied(
+    mut copy: impl FnMut() -> Result<(), ClipboardError>,
+) -> Result<(), ClipboardError> {
+    let mut delay = OCCUPIED_BACKOFF;
+    for _ in 0..OCCUPIED_RETRIES {
+        match copy() {
+            Err(ClipboardError::Arboard(arboard::Error::ClipboardOccupied)) => {
+                thread::sleep(delay);
+                delay *= 2;
+            }
+            result => return result,
+        }
+    }
+
+    copy().map_err(|err| match err {
+        ClipboardError::Arboard(arboard::Error::ClipboardOccupied) => {
+            ClipboardError::Occupied(owner())
+        }
+        err => err,
+    })
+}
+
+// owner returns the name of the process holding the clipboard open, if it can be found.
+#[cfg(all(windows, feature = "clipboard"))]
+fn owner() -> Option<String> {
+    use windows_sys::Win32::Foundation::CloseHandle;
+    use windows_sys::Win32::System::DataExchange::GetOpenClipboardWindow;
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetWindowThreadProcessId;
+
+    // SAFETY: the window handle is only passed back to the Windows API, which reports stale
+    // handles as errors, the buffer outlives the call writing to it and its length is given,
+    // and the process handle is closed once done with.
+    unsafe {
+        let window = GetOpenClipboardWindow();
+        if window.is_null() {
+            return None;
+        }
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(window, &mut pid);
+        if pid == 0 {
+            return None;
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if process.is_null() {
+            return Some(format!("process {}", pid));
+        }
+
+        let mut buffer = [0u16; 1024];
+        let mut len = buffer.len() as u32;
+        let found =
+            QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, buffer.as_mut_ptr(), &mut len);
+        CloseHandle(process);
+        if found == 0 {
+            return Some(format!("process {}", pid));
+        }
+
+        let path = String::from_utf16_lossy(&buffer[..len as usize]);
+        let name = path.rsplit('\\').next().unwrap_or(&path);
+        Some(format!("{} (process {})", name, pid))
+    }
+}
+
+#[cfg(all(not(windows), feature = "clipboard"))]
+fn owner() -> Option<String> {
+    None
+}
+
+// local_pasteboard_copy sets the general pasteboard's contents, restricting them to the current
+// host so that they do not sync through Universal Clipboard.
+#[cfg(all(target_os = "macos", feature = "clipboard"))]
+fn local_pasteboard_copy(text: &str, hints: bool) -> Result<(), ClipboardError> {
+    use objc2_app_kit::{NSPasteboard, NSPasteboardContentsOptions, NSPasteboardTypeString};
+    use objc2_foundation::NSString;
+
+    let pasteboard = NSPasteboard::generalPasteboard();
+    pasteboard.prepareForNewContentsWithOptions(NSPasteboardContentsOptions::CurrentHostOnly);
+
+    // SAFETY: NSPasteboardTypeString is a constant defined by AppKit, which is linked
+    let string_type = unsafe { NSPasteboardTypeString };
+    if !pasteboard.setString_forType(&NSString::from_str(text), string_type) {
+        return Err(ClipboardError::Pasteboard);
+    }
+
+    // See http://nspasteboard.org/ for the community standard arboard follows as well
+    if hints {
+        pasteboard.setString_forType(
+            &NSString::from_str(""),
+            &NSString::from_str("org.nspasteboard.ConcealedType"),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(not(all(target_os = "macos", feature = "clipboard")))]
+fn local_pasteboard_copy(_text: &str, _hints: bool) -> Result<(), ClipboardError> {
+    Err(ClipboardError::Unsupported(if cfg!(target_os = "macos") {
+        "keeping the clipboard local requires motus to be built with the clipboard feature"
+    } else {
+        "keeping the clipboard local is only supported on macOS"
+    }))
+}
+
+// arboard_copy sets the clipboard's contents with arboard, marking them with the hints asking
+// clipboard managers not to persist them unless `hints` is false.
+#[cfg(feature = "clipboard")]
+fn arboard_copy(text: &str, hints: bool) -> Result<(), ClipboardError> {
+    let mut clipboard = Clipboard::new()?;
+
+    let set = clipboard.set();
+    let set = if hints {
+        exclude_from_history(set)
+    } else {
+        set
+    };
+
+    Ok(set.text(text)?)
+}
+
+/// headless returns why no clipboard is plausibly available to the user, if so, such as when
+/// running over SSH or inside a container without a display server, or when motus was built
+/// without the `clipboard` feature and `wl-copy` cannot stand in for it. With the `headless`
+/// feature, the clipboard of the terminal motus runs in is always available through OSC 52.
+pub fn headless() -> Option<&'static str> {
+    if cfg!(feature = "headless") && io::stderr().is_terminal() {
+        return None;
+    }
+    if !cfg!(feature = "clipboard") && !is_set("WAYLAND_DISPLAY") {
+        return Some(if cfg!(feature = "headless") {
+            "not running in a terminal"
+        } else {
+            "motus was built without clipboard support"
+        });
+    }
+
+    detect_headless(
+        is_set,
+        Path::new("/.dockerenv").exists() || Path::new("/run/.containerenv").exists(),
+        cfg!(all(
+            unix,
+            not(any(
+                target_os = "macos",
+                target_os = "android",
+                target_os = "emscripten"
+            ))
+        )),
+    )
+}
+
+// detect_headless implements headless, given whether an environment variable is set, whether
+// motus runs inside a container, and whether the clipboard is provided by a display server,
+// as with X11 and Wayland.
+fn detect_headless(
+    is_set: impl Fn(&str) -> bool,
+    in_container: bool,
+    display_server: bool,
+) -> Option<&'static str> {
+    let over_ssh = is_set("SSH_TTY") || is_set("SSH_CONNECTION");
+
+    if !display_server {
+        // The clipboard is always there, but over SSH it is the remote machine's
+        return over_ssh.then_some("running over SSH");
+    }
+
+    if is_set("DISPLAY") || is_set("WAYLAND_DISPLAY") {
+        return None;
+    }
+
+    Some(if over_ssh {
+        "running over SSH without display forwarding"
+    } else if in_container {
+        "running inside a container without a display"
+    } else {
+        "no display server found"
+    })
+}
+
+// is_set returns whether the given environment variable is set to a non-empty value.
+fn is_set(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|value| !value.is_empty())
+}
+
+/// ClipboardError represents the errors that can occur while setting the clipboard's contents.
+#[derive(Debug)]
+pub enum ClipboardError {
+    NoBackend,
+    Unsupported(&'static str),
+    #[cfg(all(target_os = "macos", feature = "clipboard"))]
+    Pasteboard,
+    #[cfg(feature = "clipboard")]
+    Occupied(Option<String>),
+    Io(io::Error),
+    WlCopy(Option<i32>),
+    #[cfg(feature = "clipboard")]
+    Arboard(arboard::Error),
+    #[cfg(feature = "headless")]
+    Terminal(io::Error),
+    #[cfg(feature = "headless")]
+    File(io::Error),
+}
+
+impl Display for ClipboardError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClipboardError::NoBackend => write!(f, "no clipboard backend is available"),
+            ClipboardError::Unsupported(message) => write!(f, "{}", message),
+            #[cfg(all(target_os = "macos", feature = "clipboard"))]
+            ClipboardError::Pasteboard => write!(f, "unable to write to the pasteboard"),
+            #[cfg(feature = "clipboard")]
+            ClipboardError::Occupied(Some(owner)) => {
+                write!(
+                    f,
+                    "the clipboard is held open by {}; close it and try again",
+                    owner
+                )
+            }
+            #[cfg(feature = "clipboard")]
+            ClipboardError::Occupied(None) => {
+                write!(
+                    f,
+                    "the clipboard is held open by another application; try again"
+                )
+            }
+            ClipboardError::Io(err) => write!(f, "unable to run wl-copy: {}", err),
+            ClipboardError::WlCopy(Some(code)) => write!(f, "wl-copy exited with status {}", code),
+            ClipboardError::WlCopy(None) => write!(f, "wl-copy was terminated by a signal"),
+            #[cfg(feature = "clipboard")]
+            ClipboardError::Arboard(err) => write!(f, "{}", err),
+            #[cfg(feature = "headless")]
+            ClipboardError::Terminal(err) => write!(f, "unable to write to the terminal: {}", err),
+            #[cfg(feature = "headless")]
+            ClipboardError::File(err) => write!(f, "unable to write the clipboard file: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for ClipboardError {
+    fn from(err: io::Error) -> Self {
+        ClipboardError::Io(err)
+    }
+}
+
+#[cfg(feature = "clipboard")]
+impl From<arboard::Error> for ClipboardError {
+    fn from(err: arboard::Error) -> Self {
+        ClipboardError::Arboard(err)
+    }
+}
+
+#[cfg(all(
+    feature = "clipboard",
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+fn exclude_from_history(set: Set<'_>) -> Set<'_> {
+    use arboard::SetExtLinux;
+    set.exclude_from_history()
+}
+
+#[cfg(all(windows, feature = "clipboard"))]
+fn exclude_from_history(set: Set<'_>) -> Set<'_> {
+    use arboard::SetExtWindows;
+    set.exclude_from_monitoring()
+}
+
+#[cfg(all(target_os = "macos", feature = "clipboard"))]
+fn exclude_from_history(set: Set<'_>) -> Set<'_> {
+    use arboard::SetExtApple;
+    set.exclude_from_history()
+}
+
+#[cfg(all(
+    feature = "clipboard",
+    any(target_os = "android", target_os = "emscripten")
+))]
+fn exclude_from_history(set: Set<'_>) -> Set<'_> {
+    set
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "clipboard")]
+    fn test_backends() {
+        let fallbacks = vec![
+            Backend::Arboard,
+            #[cfg(feature = "headless")]
+            Backend::Osc52,
+        ];
+
+        assert_eq!(
+            backends(true, false),
+            [vec![Backend::WlCopy], fallbacks.clone()].concat()
+        );
+        assert_eq!(backends(false, false), fallbacks);
+        assert_eq!(backends(true, true), vec![Backend::LocalPasteboard]);
+    }
+
+    #[test]
+    #[cfg(feature = "clipboard")]
+    fn test_retry_occupied() {
+        let mut attempts = 0;
+        let result = retry_occupied(|| {
+            attempts += 1;
+            if attempts < 3 {
+                Err(arboard::Error::ClipboardOccupied.into())
+            } else {
+                Ok(())
+            }
+        });
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+
+        let mut attempts = 0;
+        let result = retry_occupied(|| {
+            attempts += 1;
+            Err(arboard::Error::ClipboardOccupied.into())
+        });
+        assert!(matches!(result, Err(ClipboardError::Occupied(_))));
+        assert_eq!(attempts, OCCUPIED_RETRIES + 1);
+
+        let mut attempts = 0;
+        let result = retry_occupied(|| {
+            attempts += 1;
+            Err(ClipboardError::NoBackend)
+        });
+        assert!(matches!(result, Err(ClipboardError::NoBackend)));
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "headless")]
+    fn test_osc52_sequence() {
+        assert_eq!(
+            osc52_sequence("hunter2", false),
+            "\x1b]52;c;aHVudGVyMg==\x07"
+        );
+        assert_eq!(
+            osc52_sequence("hunter2", true),
+            "\x1bPtmux;\x1b\x1b]52;c;aHVudGVyMg==\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_detect_headless() {
+        let env = |vars: &'static [&'static str]| move |name: &str| vars.contains(&name);
+
+        assert_eq!(detect_headless(env(&["DISPLAY"]), false, true), None);
+        assert_eq!(
+            detect_headless(env(&["WAYLAND_DISPLAY", "SSH_TTY"]), false, true),
+            None
+        );
+        assert_eq!(
+            detect_headless(env(&["SSH_CONNECTION"]), false, true),
+            Some("running over SSH without display forwarding")
+        );
+        assert_eq!(
+            detect_headless(env(&[]), true, true),
+            Some("running inside a container without a display")
+        );
+        assert_eq!(
+            detect_headless(env(&[]), false, true),
+            Some("no display server found")
+        );
+
+        assert_eq!(detect_headless(env(&[]), false, false), None);
+        assert_eq!(
+            detect_headless(env(&["SSH_TTY"]), false, false),
+            Some("running over SSH")
+        );
+    }
+}
@@ -0,0 +1,253 @@
+use std::cmp::Ordering;
+use std::io::{self, BufWriter, Write};
+
+use motus::entropy;
+use motus::wordlist::WordFilter;
+use motus::Separator;
+use serde::Serialize;
+
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+use crate::{csv_field, OutputFormat};
+
+// MAX_WORDS and MAX_PIN are the largest number of words and digits the memorable and pin
+// commands generate, and MIN_WORDS the smallest number of words.
+const MIN_WORDS: usize = 3;
+const MAX_WORDS: usize = 15;
+const MAX_PIN: u32 = 12;
+
+// APPLE_LENGTH is the length of Apple-style passwords: three chunks of six characters, and the
+// two hyphens between them.
+const APPLE_LENGTH: u32 = 20;
+
+/// Strategy is a way of generating passwords, sized to make the most of a length budget.
+#[derive(Serialize, Debug)]
+pub struct Strategy {
+    /// The name of the strategy
+    pub strategy: &'static str,
+
+    /// The number of characters to type, on average for passwords made of words
+    pub length: f64,
+
+    /// Whether the length varies from one password to the next
+    pub approximate: bool,
+
+    /// The entropy of the passwords, in bits
+    pub entropy_bits: f64,
+
+    /// Whether the passwords fit in the budget. The length and entropy of strategies that do not
+    /// are those of their shortest passwords
+    pub fits: bool,
+
+    /// The motus command generating the passwords
+    pub command: String,
+}
+
+/// strategies returns the strategies motus can generate passwords of at most `budget`
+/// characters with, from the strongest to the weakest, followed by the ones whose shortest
+/// passwords are still too long.
+#[allow(clippy::cast_precision_loss)] // lengths are way below f64's precision limits
+pub fn strategies(budget: u32) -> Vec<Strategy> {
+    let mut strategies = Vec::new();
+
+    for (strategy, numbers, symbols, flags) in [
+        (
+            "random letters, digits and symbols",
+            true,
+            true,
+            " --numbers --symbols",
+        ),
+        ("random letters and digits", true, false, " --numbers"),
+        ("random letters", false, false, ""),
+    ] {
+        strategies.push(Strategy {
+            strategy,
+            length: budget as f64,
+            approximate: false,
+            entropy_bits: entropy::random(budget, numbers, symbols, &[]).bits(),
+            fits: true,
+            command: format!("motus random --characters {budget}{flags}"),
+        });
+    }
+
+    for (strategy, filter, separator, flags) in [
+        (
+            "words separated by spaces",
+            WordFilter::new(),
+            Separator::Space,
+            "",
+        ),
+        (
+            "words separated by digits",
+            WordFilter::new(),
+            Separator::Numbers,
+            " --separator numbers",
+        ),
+        (
+            "short words, joined",
+            WordFilter::new().with_max_syllables(2),
+            Separator::None,
+            " --separator none --max-syllables-per-word 2",
+        ),
+    ] {
+        strategies.push(memorable(strategy, &filter, separator, flags, budget));
+    }
+
+    let digits = budget.min(MAX_PIN);
+    strategies.push(Strategy {
+        strategy: "digits",
+        length: digits as f64,
+        approximate: false,
+        entropy_bits: entropy::pin(digits).bits(),
+        fits: true,
+        command: format!("motus pin --numbers {digits}"),
+    });
+
+    strategies.push(Strategy {
+        strategy: "Apple-style pattern",
+        length: APPLE_LENGTH as f64,
+        approximate: false,
+        entropy_bits: entropy::apple().bits(),
+        fits: budget >= APPLE_LENGTH,
+        command: "motus apple".to_string(),
+    });
+
+    strategies.sort_by(|a, b| {
+        b.fits.cmp(&a.fits).then(
+            b.entropy_bits
+                .partial_cmp(&a.entropy_bits)
+                .unwrap_or(Ordering::Equal),
+        )
+    });
+    strategies
+}
+
+// memorable returns the strategy generating memorable passwords with as many words drawn from
+// `filter` and joined by `separator` as fit in `budget` on average, or with the fewest words the
+// memorable command accepts if even those do not fit.
+#[allow(clippy::cast_precision_loss)] // word counts are way below f64's precision limits
+fn memorable(
+    strategy: &'static str,
+    filter: &WordFilter,
+    separator: Separator,
+    flags: &str,
+    budget: u32,
+) -> Strategy {
+    let words = filter.words();
+    let average =
+        words.iter().map(|w| w.chars().count()).sum::<usize>() as f64 / words.len() as f64;
+    let separator_len = if separator == Separator::None {
+        0.0
+    } else {
+        1.0
+    };
+    let length = |count: usize| count as f64 * (average + separator_len) - separator_len;
+
+    let count = (MIN_WORDS..=MAX_WORDS)
+        .take_while(|&count| length(count) <= budget as f64)
+        .last();
+
+    let words = count.unwrap_or(MIN_WORDS);
+    Strategy {
+        strategy,
+        length: length(words),
+        approximate: true,
+        entropy_bits: filter.memorable_entropy(words, separator, 1, false).bits(),
+        fits: count.is_some(),
+        command: format!("motus memorable --words {words}{flags}"),
+    }
+}
+
+/// display writes the strategies fitting in `budget` characters to stdout in the requested
+/// format, and the ones needing more characters after them.
+pub fn display(budget: u32, format: &OutputFormat) -> io::Result<()> {
+    let strategies = strategies(budget);
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    match format {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            let mut table = Table::new();
+            table.max_column_width = 60;
+            table.style = TableStyle::extended();
+            table.add_row(Row::new(vec![TableCell::builder(format!(
+                "Passwords of at most {budget} characters"
+            ))
+            .col_span(4)
+            .alignment(Alignment::Left)
+            .build()]));
+            table.add_row(Row::new(vec![
+                TableCell::new("Strategy"),
+                TableCell::new("Length"),
+                TableCell::new("Entropy"),
+                TableCell::new("Command"),
+            ]));
+            for strategy in strategies.iter().filter(|s| s.fits) {
+                table.add_row(Row::new(vec![
+                    TableCell::new(strategy.strategy),
+                    TableCell::builder(length(strategy))
+                        .alignment(Alignment::Right)
+                        .build(),
+                    TableCell::builder(format!("{:.1} bits", strategy.entropy_bits))
+                        .alignment(Alignment::Right)
+                        .build(),
+                    TableCell::new(&strategy.command),
+                ]));
+            }
+            writeln!(out, "{}", table.render())?;
+
+            for strategy in strategies.iter().filter(|s| !s.fits) {
+                writeln!(
+                    out,
+                    "{}: needs {} characters",
+                    strategy.strategy,
+                    length(strategy)
+                )?;
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer(
+                &mut out,
+                &CompareOutput {
+                    length_budget: budget,
+                    strategies: &strategies,
+                },
+            )?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "strategy,length,approximate,entropy_bits,fits,command")?;
+            for strategy in &strategies {
+                writeln!(
+                    out,
+                    "{},{:.1},{},{:.2},{},{}",
+                    csv_field(strategy.strategy),
+                    strategy.length,
+                    strategy.approximate,
+                    strategy.entropy_bits,
+                    strategy.fits,
+                    csv_field(&strategy.command)
+                )?;
+            }
+        }
+    }
+
+    out.flush()
+}
+
+// length formats the length of the strategy's passwords, prefixed with a tilde when it is an
+// average.
+fn length(strategy: &Strategy) -> String {
+    if strategy.approximate {
+        format!("~{:.0}", strategy.length)
+    } else {
+        format!("{:.0}", strategy.length)
+    }
+}
+
+#[derive(Serialize)]
+struct CompareOutput<'a> {
+    length_budget: u32,
+    strategies: &'a [Strategy],
+}
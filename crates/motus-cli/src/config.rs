@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+
+use crate::OutputFormat;
+
+/// ConfigFile is the format of the configuration file, holding the defaults of the command line
+/// options that are not given explicitly, either as flags or as environment variables.
+///
+/// ```toml
+/// [default]
+/// output = "json"
+///
+/// [profiles.ci]
+/// no_clipboard = true
+/// separator = "hyphen"
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    #[serde(default)]
+    default: Defaults,
+    #[serde(default)]
+    profiles: BTreeMap<String, Defaults>,
+}
+
+/// Defaults are the option values read from the configuration file.
+#[derive(Deserialize, Debug, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Defaults {
+    pub no_clipboard: Option<bool>,
+    pub audit_log: Option<PathBuf>,
+    pub audit_log_sign: Option<String>,
+    pub store: Option<String>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pub output: Option<OutputFormat>,
+    #[serde(default, deserialize_with = "value_enum")]
+    pub separator: Option<motus::Separator>,
+}
+
+impl Defaults {
+    // or returns these defaults, falling back to `other` for the options they leave unset.
+    fn or(self, other: Defaults) -> Defaults {
+        Defaults {
+            no_clipboard: self.no_clipboard.or(other.no_clipboard),
+            audit_log: self.audit_log.or(other.audit_log),
+            audit_log_sign: self.audit_log_sign.or(other.audit_log_sign),
+            store: self.store.or(other.store),
+            output: self.output.or(other.output),
+            separator: self.separator.or(other.separator),
+        }
+    }
+}
+
+/// load reads the defaults from the configuration file at `path`, or at the default location
+/// if no path is given, applying the given profile over the `[default]` table.
+///
+/// A missing file at the default location is not an error, as the configuration file is
+/// optional; a missing profile is.
+pub fn load(path: Option<&Path>, profile: Option<&str>) -> Result<Defaults, ConfigError> {
+    let config = match path {
+        Some(path) => parse(&fs::read_to_string(path)?)?,
+        None => match fs::read_to_string(default_path()) {
+            Ok(content) => parse(&content)?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => ConfigFile::default(),
+            Err(err) => return Err(err.into()),
+        },
+    };
+
+    match profile {
+        None => Ok(config.default),
+        Some(name) => match config.profiles.get(name) {
+            Some(profile) => Ok(profile.clone().or(config.default)),
+            None => Err(ConfigError::UnknownProfile(name.to_string())),
+        },
+    }
+}
+
+// parse parses the given configuration file content.
+fn parse(content: &str) -> Result<ConfigFile, ConfigError> {
+    Ok(toml::from_str(content)?)
+}
+
+/// default_path returns the default location of the configuration file, in the user's
+/// configuration directory.
+pub fn default_path() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("motus")
+        .join("config.toml")
+}
+
+// value_enum deserializes an option value by the name it has on the command line.
+fn value_enum<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: ValueEnum,
+{
+    let name = String::deserialize(deserializer)?;
+    T::from_str(&name, false)
+        .map(Some)
+        .map_err(D::Error::custom)
+}
+
+/// ConfigError represents the errors that can occur while loading the configuration file.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    UnknownProfile(String),
+}
+
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(err) => write!(f, "unable to read the configuration file: {}", err),
+            ConfigError::Parse(err) => write!(f, "invalid configuration file: {}", err),
+            ConfigError::UnknownProfile(name) => {
+                write!(f, "no profile named '{}' in the configuration file", name)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(err: io::Error) -> Self {
+        ConfigError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for ConfigError {
+    fn from(err: toml::de::Error) -> Self {
+        ConfigError::Parse(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [default]
+        no_clipboard = true
+        output = "json"
+
+        [profiles.ci]
+        output = "csv"
+        separator = "hyphen"
+    "#;
+
+    #[test]
+    fn test_load_profile() {
+        let path = std::env::temp_dir().join(format!("motus-config-{}.toml", std::process::id()));
+        fs::write(&path, CONFIG).unwrap();
+
+        let defaults = load(Some(&path), None).unwrap();
+        assert_eq!(defaults.no_clipboard, Some(true));
+        assert!(matches!(defaults.output, Some(OutputFormat::Json)));
+        assert!(defaults.separator.is_none());
+
+        let ci = load(Some(&path), Some("ci")).unwrap();
+        assert_eq!(ci.no_clipboard, Some(true));
+        assert!(matches!(ci.output, Some(OutputFormat::Csv)));
+        assert!(matches!(ci.separator, Some(motus::Separator::Hyphen)));
+
+        assert!(matches!(
+            load(Some(&path), Some("staging")),
+            Err(ConfigError::UnknownProfile(_))
+        ));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_parse_invalid_value() {
+        assert!(parse("[default]\noutput = \"yaml\"").is_err());
+        assert!(parse("[default]\nclipboard = false").is_err());
+    }
+}
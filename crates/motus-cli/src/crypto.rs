@@ -0,0 +1,50 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+/// SALT_LEN is the length, in bytes, of the salt used to derive an encryption key.
+pub const SALT_LEN: usize = 16;
+
+/// NONCE_LEN is the length, in bytes, of the XChaCha20-Poly1305 nonce.
+pub const NONCE_LEN: usize = 24;
+
+/// derive_key derives a 32 bytes encryption key from `secret` and `salt` using Argon2id, with
+/// the memory, iterations and parallelism costs of `params`.
+pub fn derive_key(secret: &[u8], salt: &[u8], params: Params) -> Result<[u8; 32], argon2::Error> {
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(secret, salt, &mut key)?;
+    Ok(key)
+}
+
+/// seal encrypts `plaintext` with XChaCha20-Poly1305, authenticating `aad` along with it.
+pub fn seal(
+    key: &[u8; 32],
+    nonce: &[u8],
+    plaintext: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, chacha20poly1305::Error> {
+    XChaCha20Poly1305::new(key.into()).encrypt(
+        XNonce::from_slice(nonce),
+        Payload {
+            msg: plaintext,
+            aad,
+        },
+    )
+}
+
+/// open decrypts `ciphertext` sealed by `seal` with the same key, nonce and `aad`.
+pub fn open(
+    key: &[u8; 32],
+    nonce: &[u8],
+    ciphertext: &[u8],
+    aad: &[u8],
+) -> Result<Vec<u8>, chacha20poly1305::Error> {
+    XChaCha20Poly1305::new(key.into()).decrypt(
+        XNonce::from_slice(nonce),
+        Payload {
+            msg: ciphertext,
+            aad,
+        },
+    )
+}
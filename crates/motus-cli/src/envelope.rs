@@ -0,0 +1,282 @@
+use std::fmt::{Display, Formatter};
+use std::io;
+
+use argon2::Params;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use motus::ct;
+use rand::prelude::*;
+
+use crate::crypto::{self, NONCE_LEN, SALT_LEN};
+
+// MAGIC identifies motus envelopes, and the version of their format.
+const MAGIC: &[u8; 8] = b"MOTUSW02";
+
+// LEGACY_MAGIC identifies the envelopes of the first format, which did not record the Argon2
+// parameters of their key, and were all sealed with the defaults of the argon2 crate.
+const LEGACY_MAGIC: &[u8; 8] = b"MOTUSW01";
+
+// PARAMS_LEN is the length, in bytes, of the Argon2 memory, iterations and parallelism costs
+// recorded in the header, each as a little-endian u32.
+const PARAMS_LEN: usize = 12;
+
+// HEADER_LEN is the length, in bytes, of the header preceding the ciphertext.
+const HEADER_LEN: usize = MAGIC.len() + PARAMS_LEN + SALT_LEN + NONCE_LEN;
+
+// LEGACY_HEADER_LEN is the length, in bytes, of the header of the first format.
+const LEGACY_HEADER_LEN: usize = LEGACY_MAGIC.len() + SALT_LEN + NONCE_LEN;
+
+// MAX_M_COST, MAX_T_COST and MAX_P_COST bound the Argon2 costs an envelope may ask for, so
+// that opening a crafted envelope cannot exhaust the memory or time of the recipient.
+const MAX_M_COST: u32 = 1024 * 1024;
+const MAX_T_COST: u32 = 16;
+const MAX_P_COST: u32 = 16;
+
+/// PASSPHRASE_ENV is the environment variable the envelope passphrase is read from, if set,
+/// instead of prompting for it.
+pub const PASSPHRASE_ENV: &str = "MOTUS_WRAP_PASSPHRASE";
+
+/// seal encrypts `secret` into an envelope, with a key derived from `passphrase`.
+///
+/// The envelope holds the format's magic bytes, the Argon2 memory, iterations and parallelism
+/// costs and the salt of the key, the nonce, and the secret encrypted with XChaCha20-Poly1305
+/// using a key derived with Argon2id. The header is authenticated along with the secret, so
+/// that tampering with any byte of the envelope is detected when opening it.
+pub fn seal(secret: &[u8], passphrase: &str) -> Result<Vec<u8>, EnvelopeError> {
+    let params = Params::default();
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    thread_rng().fill_bytes(&mut salt);
+    thread_rng().fill_bytes(&mut nonce);
+    header.extend_from_slice(MAGIC);
+    for cost in [params.m_cost(), params.t_cost(), params.p_cost()] {
+        header.extend_from_slice(&cost.to_le_bytes());
+    }
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let key = derive_key(passphrase, &salt, params)?;
+    let ciphertext =
+        crypto::seal(&key, &nonce, secret, &header).map_err(|_| EnvelopeError::Encryption)?;
+
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// open decrypts the secret sealed in `envelope` with `passphrase`.
+pub fn open(envelope: &[u8], passphrase: &str) -> Result<Vec<u8>, EnvelopeError> {
+    let (header, ciphertext, params) = if envelope.starts_with(MAGIC) {
+        if envelope.len() < HEADER_LEN {
+            return Err(EnvelopeError::Malformed);
+        }
+        let (header, ciphertext) = envelope.split_at(HEADER_LEN);
+        (header, ciphertext, read_params(&header[MAGIC.len()..])?)
+    } else if envelope.starts_with(LEGACY_MAGIC) {
+        if envelope.len() < LEGACY_HEADER_LEN {
+            return Err(EnvelopeError::Malformed);
+        }
+        let (header, ciphertext) = envelope.split_at(LEGACY_HEADER_LEN);
+        (header, ciphertext, Params::default())
+    } else {
+        return Err(EnvelopeError::Malformed);
+    };
+
+    let (salt, nonce) = header[header.len() - SALT_LEN - NONCE_LEN..].split_at(SALT_LEN);
+    let key = derive_key(passphrase, salt, params)?;
+
+    crypto::open(&key, nonce, ciphertext, header).map_err(|_| EnvelopeError::Decryption)
+}
+
+// read_params reads the Argon2 costs recorded at the start of `data`, refusing the ones beyond
+// the bounds a recipient is willing to spend on opening an envelope.
+fn read_params(data: &[u8]) -> Result<Params, EnvelopeError> {
+    let cost = |index: usize| {
+        let bytes = &data[index * 4..index * 4 + 4];
+        u32::from_le_bytes(bytes.try_into().expect("cost should be 4 bytes long"))
+    };
+    let (m_cost, t_cost, p_cost) = (cost(0), cost(1), cost(2));
+    if m_cost > MAX_M_COST || t_cost > MAX_T_COST || p_cost > MAX_P_COST {
+        return Err(EnvelopeError::Parameters(m_cost, t_cost, p_cost));
+    }
+
+    Params::new(m_cost, t_cost, p_cost, None).map_err(|_| EnvelopeError::Malformed)
+}
+
+/// armor encodes an envelope as text, to send it through channels that only carry text.
+pub fn armor(envelope: &[u8]) -> String {
+    STANDARD.encode(envelope)
+}
+
+/// dearmor returns the envelope held in `data`, either as is or encoded as text by `armor`.
+pub fn dearmor(data: &[u8]) -> Result<Vec<u8>, EnvelopeError> {
+    if data.starts_with(MAGIC) || data.starts_with(LEGACY_MAGIC) {
+        return Ok(data.to_vec());
+    }
+
+    let text: Vec<u8> = data
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect();
+    STANDARD.decode(text).map_err(|_| EnvelopeError::Malformed)
+}
+
+/// read_passphrase returns the envelope passphrase from the environment if set, or prompts for
+/// it otherwise, twice when `confirm` is set, as a typo would make the envelope unopenable.
+pub fn read_passphrase(confirm: bool) -> Result<String, EnvelopeError> {
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => {
+            let passphrase = rpassword::prompt_password("Envelope passphrase: ")?;
+            if confirm
+                && !ct::str_eq(
+                    &rpassword::prompt_password("Confirm the passphrase: ")?,
+                    &passphrase,
+                )
+            {
+                return Err(EnvelopeError::PassphraseMismatch);
+            }
+            passphrase
+        }
+    };
+
+    if passphrase.is_empty() {
+        return Err(EnvelopeError::EmptyPassphrase);
+    }
+    Ok(passphrase)
+}
+
+// derive_key derives the envelope encryption key from the passphrase using Argon2id.
+fn derive_key(passphrase: &str, salt: &[u8], params: Params) -> Result<[u8; 32], EnvelopeError> {
+    crypto::derive_key(passphrase.as_bytes(), salt, params)
+        .map_err(|_| EnvelopeError::KeyDerivation)
+}
+
+/// EnvelopeError represents the errors that can occur while sealing or opening an envelope.
+#[derive(Debug)]
+pub enum EnvelopeError {
+    Io(io::Error),
+    PassphraseMismatch,
+    EmptyPassphrase,
+    KeyDerivation,
+    Encryption,
+    Decryption,
+    Malformed,
+    Parameters(u32, u32, u32),
+}
+
+impl Display for EnvelopeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EnvelopeError::Io(err) => write!(f, "unable to read the envelope: {}", err),
+            EnvelopeError::PassphraseMismatch => write!(f, "the passphrases do not match"),
+            EnvelopeError::EmptyPassphrase => write!(f, "the passphrase cannot be empty"),
+            EnvelopeError::KeyDerivation => write!(f, "unable to derive the envelope key"),
+            EnvelopeError::Encryption => write!(f, "unable to encrypt the secret"),
+            EnvelopeError::Decryption => write!(
+                f,
+                "unable to open the envelope: wrong passphrase, or the envelope was tampered with"
+            ),
+            EnvelopeError::Malformed => write!(f, "the input is not a motus envelope"),
+            EnvelopeError::Parameters(m_cost, t_cost, p_cost) => write!(
+                f,
+                "the envelope asks for Argon2 costs of {} KiB, {} iterations and {} lanes, \
+                 beyond the {} KiB, {} iterations and {} lanes motus accepts",
+                m_cost, t_cost, p_cost, MAX_M_COST, MAX_T_COST, MAX_P_COST
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for EnvelopeError {
+    fn from(err: io::Error) -> Self {
+        EnvelopeError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let envelope = seal(b"mHYvjgQAKBHBIRYdpPAI", "correct horse").unwrap();
+        assert!(envelope.starts_with(MAGIC));
+        assert_eq!(
+            open(&envelope, "correct horse").unwrap(),
+            b"mHYvjgQAKBHBIRYdpPAI"
+        );
+
+        let armored = armor(&envelope);
+        assert_eq!(dearmor(armored.as_bytes()).unwrap(), envelope);
+        assert_eq!(
+            dearmor(format!("{}\n", armored).as_bytes()).unwrap(),
+            envelope
+        );
+        assert_eq!(dearmor(&envelope).unwrap(), envelope);
+    }
+
+    #[test]
+    fn test_open_rejects_wrong_passphrase_and_tampering() {
+        let envelope = seal(b"mHYvjgQAKBHBIRYdpPAI", "correct horse").unwrap();
+        assert!(matches!(
+            open(&envelope, "battery staple"),
+            Err(EnvelopeError::Decryption)
+        ));
+
+        for index in [
+            MAGIC.len(),
+            MAGIC.len() + PARAMS_LEN,
+            MAGIC.len() + PARAMS_LEN + SALT_LEN,
+            envelope.len() - 1,
+        ] {
+            let mut tampered = envelope.clone();
+            tampered[index] ^= 1;
+            assert!(open(&tampered, "correct horse").is_err());
+        }
+
+        assert!(matches!(
+            open(b"MOTUSH01", "correct horse"),
+            Err(EnvelopeError::Malformed)
+        ));
+        assert!(matches!(
+            dearmor(b"not an envelope!"),
+            Err(EnvelopeError::Malformed)
+        ));
+    }
+
+    #[test]
+    fn test_header_records_argon2_params() {
+        let envelope = seal(b"mHYvjgQAKBHBIRYdpPAI", "correct horse").unwrap();
+        let params = read_params(&envelope[MAGIC.len()..]).unwrap();
+        assert_eq!(params.m_cost(), Params::DEFAULT_M_COST);
+        assert_eq!(params.t_cost(), Params::DEFAULT_T_COST);
+        assert_eq!(params.p_cost(), Params::DEFAULT_P_COST);
+
+        let mut costly = envelope.clone();
+        costly[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            open(&costly, "correct horse"),
+            Err(EnvelopeError::Parameters(u32::MAX, _, _))
+        ));
+    }
+
+    #[test]
+    fn test_open_legacy_envelope() {
+        let salt = [7u8; SALT_LEN];
+        let nonce = [9u8; NONCE_LEN];
+        let mut envelope = LEGACY_MAGIC.to_vec();
+        envelope.extend_from_slice(&salt);
+        envelope.extend_from_slice(&nonce);
+        let key = derive_key("correct horse", &salt, Params::default()).unwrap();
+        let ciphertext = crypto::seal(&key, &nonce, b"mHYvjgQAKBHBIRYdpPAI", &envelope).unwrap();
+        envelope.extend_from_slice(&ciphertext);
+
+        assert_eq!(dearmor(armor(&envelope).as_bytes()).unwrap(), envelope);
+        assert_eq!(
+            open(&envelope, "correct horse").unwrap(),
+            b"mHYvjgQAKBHBIRYdpPAI"
+        );
+    }
+}
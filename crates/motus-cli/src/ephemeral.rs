@@ -0,0 +1,107 @@
+use std::io::{self, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+use motus::secret::Secret;
+
+/// display prints `text` to the terminal, waits for the user to press Enter or for `timeout` to
+/// elapse, whichever comes first, and then erases it from the screen.
+///
+/// The text is erased by moving the cursor back up to its first line and clearing everything
+/// below, so that it does not linger on the screen during screen shares. It may remain in the
+/// scrollback of terminals that keep lines scrolled off the screen.
+pub fn display(text: &str, timeout: Duration) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    writeln!(stdout, "{}", text)?;
+    writeln!(
+        stdout,
+        "(press Enter to erase, or wait {} seconds)",
+        timeout.as_secs()
+    )?;
+    stdout.flush()?;
+
+    // Pressing Enter echoes a line break, moving the cursor down one more line
+    let echoed = usize::from(enter_pressed().recv_timeout(timeout).is_ok());
+    write!(stdout, "{}", erase(text.lines().count() + 1 + echoed))?;
+    stdout.flush()
+}
+
+/// watch prints a new password from `next` every `interval`, until the user presses Enter or
+/// interrupts motus.
+///
+/// With `redraw`, for terminals, each password replaces the previous one on the screen, and the
+/// last one is erased once Enter is pressed. Otherwise, passwords are printed one per line.
+pub fn watch(interval: Duration, redraw: bool, mut next: impl FnMut() -> Secret) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    let pressed = enter_pressed();
+    let mut stdin_open = true;
+
+    loop {
+        let password = next();
+        writeln!(stdout, "{}", password.expose())?;
+        if redraw {
+            writeln!(
+                stdout,
+                "(press Enter to stop, next password in {} seconds)",
+                interval.as_secs()
+            )?;
+        }
+        stdout.flush()?;
+
+        // Without a standard input to read Enter from, only an interruption stops watching
+        let stopped = if stdin_open {
+            match pressed.recv_timeout(interval) {
+                Ok(()) => true,
+                Err(RecvTimeoutError::Timeout) => false,
+                Err(RecvTimeoutError::Disconnected) => {
+                    stdin_open = false;
+                    thread::sleep(interval);
+                    false
+                }
+            }
+        } else {
+            thread::sleep(interval);
+            false
+        };
+
+        if redraw {
+            let echoed = usize::from(stopped);
+            write!(stdout, "{}", erase(password.lines().count() + 1 + echoed))?;
+            stdout.flush()?;
+        }
+        if stopped {
+            return Ok(());
+        }
+    }
+}
+
+// enter_pressed returns a channel receiving a message once the user presses Enter. Reading stdin
+// blocks for as long as nothing is typed, so it is done on a separate thread, which is abandoned
+// when the caller stops waiting. The channel is disconnected if stdin is closed.
+fn enter_pressed() -> Receiver<()> {
+    let (pressed, wait) = mpsc::channel();
+    thread::spawn(move || {
+        let mut line = String::new();
+        if io::stdin().read_line(&mut line).is_ok_and(|read| read > 0) {
+            let _ = pressed.send(());
+        }
+    });
+    wait
+}
+
+// erase returns the ANSI escape sequence moving the cursor to the beginning of the line `lines`
+// lines above, and clearing the screen from there.
+fn erase(lines: usize) -> String {
+    format!("\x1b[{}F\x1b[J", lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erase() {
+        assert_eq!(erase(2), "\x1b[2F\x1b[J");
+    }
+}
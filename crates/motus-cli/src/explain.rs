@@ -0,0 +1,78 @@
+use colored::Colorize;
+use motus::entropy::Entropy;
+use serde::Serialize;
+
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+
+/// EntropyReport explains the theoretical entropy of a generated password, as derived from the
+/// generator's settings, and how much each part of the password contributes to it.
+#[derive(Serialize, Clone)]
+pub struct EntropyReport {
+    bits: f64,
+    components: Vec<ComponentReport>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    ambiguous: bool,
+}
+
+#[derive(Serialize, Clone)]
+struct ComponentReport {
+    source: String,
+    bits: f64,
+}
+
+impl From<&Entropy> for EntropyReport {
+    fn from(entropy: &Entropy) -> Self {
+        EntropyReport {
+            bits: round(entropy.bits()),
+            components: entropy
+                .components()
+                .iter()
+                .map(|component| ComponentReport {
+                    source: component.source.to_string(),
+                    bits: round(component.bits),
+                })
+                .collect(),
+            ambiguous: entropy.is_ambiguous(),
+        }
+    }
+}
+
+impl EntropyReport {
+    /// display prints the entropy breakdown as a table.
+    pub fn display(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder("Entropy")
+            .col_span(2)
+            .alignment(Alignment::Left)
+            .build()]));
+
+        for component in &self.components {
+            table.add_row(Row::new(vec![
+                TableCell::new(component.source.as_str().bold()),
+                TableCell::builder(format!("{:.1} bits", component.bits))
+                    .alignment(Alignment::Left)
+                    .build(),
+            ]));
+        }
+
+        table.add_row(Row::new(vec![
+            TableCell::new("Total".bold()),
+            TableCell::builder(format!("{:.1} bits", self.bits))
+                .alignment(Alignment::Left)
+                .build(),
+        ]));
+
+        println!("{}", table.render());
+        if self.ambiguous {
+            println!("The password may be read back as other words, and holds less entropy than this total");
+        }
+    }
+}
+
+// round rounds the given number of bits to one decimal.
+fn round(bits: f64) -> f64 {
+    (bits * 10.0).round() / 10.0
+}
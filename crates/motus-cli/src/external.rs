@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::io::Read;
+
+use argon2::Argon2;
+use clap::ValueEnum;
+use motus::policy::Preset;
+use motus::wordlist::WordFilter;
+use motus::{Radix, Separator};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+/// KEY_ENV is the environment variable holding the secret key passwords are derived from.
+pub const KEY_ENV: &str = "MOTUS_EXTERNAL_KEY";
+
+// KEYS lists the query keys each kind of password accepts, on top of `label` and `kind`.
+const KEYS: &[(&str, &[&str])] = &[
+    (
+        "random",
+        &["characters", "numbers", "symbols", "policy_preset"],
+    ),
+    ("memorable", &["words", "separator", "capitalize"]),
+    ("pin", &["numbers"]),
+    ("apple", &[]),
+];
+
+/// Query is the query of a Terraform external data source: a JSON object of strings, whose keys
+/// mirror the options of the generation commands, with underscores.
+pub type Query = BTreeMap<String, String>;
+
+/// read_query reads the query Terraform writes on the standard input of the program.
+pub fn read_query(reader: impl Read) -> Result<Query, ExternalError> {
+    serde_json::from_reader(reader).map_err(|_| ExternalError::Malformed)
+}
+
+/// generate returns the password the query asks for, as the JSON object of strings Terraform
+/// expects in return.
+///
+/// Terraform reads data sources on every plan, so the password is derived from `key` and the
+/// query's `label` rather than drawn at random: the same query always yields the same password,
+/// and no plan ever shows a spurious change. The key must be kept secret, as anyone holding it
+/// can derive every password; the generated passwords remain in Terraform's state, like any
+/// other data source result.
+pub fn generate(query: &Query, key: &str) -> Result<BTreeMap<&'static str, String>, ExternalError> {
+    let label = query.get("label").ok_or(ExternalError::MissingLabel)?;
+    let kind = query.get("kind").map_or("random", String::as_str);
+    let accepted = KEYS
+        .iter()
+        .find(|(name, _)| *name == kind)
+        .map(|(_, keys)| *keys)
+        .ok_or_else(|| ExternalError::UnknownKind(kind.to_string()))?;
+    if let Some(unknown) = query
+        .keys()
+        .find(|key| !["label", "kind"].contains(&key.as_str()) && !accepted.contains(&key.as_str()))
+    {
+        return Err(ExternalError::UnknownKey(unknown.clone()));
+    }
+
+    let mut rng = StdRng::from_seed(derive_seed(key, label)?);
+    let (password, entropy) = match kind {
+        "random" => {
+            let characters = parse(query, "characters", "20", crate::validate_character_count)?;
+            let numbers = parse(query, "numbers", "false", parse_bool)?;
+            let symbols = parse(query, "symbols", "false", parse_bool)?;
+            let policy = match query.get("policy_preset") {
+                Some(preset) => Preset::from_str(preset, true)
+                    .map_err(|err| ExternalError::Invalid("policy_preset", err))?
+                    .policy(),
+                None => Default::default(),
+            };
+            let (characters, numbers, symbols) = policy.fit(characters, numbers, symbols);
+
+            (
+                policy.random_password(&mut rng, characters, numbers, symbols, &[]),
+                policy.random_entropy(characters, numbers, symbols, &[]),
+            )
+        }
+        "memorable" => {
+            let words = parse(query, "words", "5", crate::validate_word_count)? as usize;
+            let separator = parse(query, "separator", "space", |s| {
+                Separator::from_str(s, true)
+            })?;
+            let capitalize = parse(query, "capitalize", "false", parse_bool)?;
+            let filter = WordFilter::new().with_family_friendly(true);
+
+            (
+                filter.memorable_password(&mut rng, words, separator, 1, capitalize, false),
+                filter.memorable_entropy(words, separator, 1, false),
+            )
+        }
+        "pin" => {
+            let numbers = parse(query, "numbers", "7", crate::validate_pin_length)?;
+            let alphabet = Radix::Decimal.alphabet();
+
+            (
+                motus::code_password(&mut rng, numbers, alphabet),
+                motus::entropy::code(numbers, alphabet),
+            )
+        }
+        _ => (motus::apple_password(&mut rng), motus::entropy::apple()),
+    };
+
+    Ok(BTreeMap::from([
+        ("password", password),
+        ("kind", kind.to_string()),
+        ("entropy_bits", format!("{:.1}", entropy.bits())),
+    ]))
+}
+
+// parse parses the value of `key` in the query, or `default` if the query leaves it unset.
+fn parse<T>(
+    query: &Query,
+    key: &'static str,
+    default: &str,
+    parser: impl Fn(&str) -> Result<T, String>,
+) -> Result<T, ExternalError> {
+    let value = query.get(key).map_or(default, String::as_str);
+    parser(value).map_err(|err| ExternalError::Invalid(key, err))
+}
+
+// parse_bool parses a boolean, which Terraform passes as the "true" or "false" strings.
+fn parse_bool(s: &str) -> Result<bool, String> {
+    s.parse()
+        .map_err(|_| "The value must be true or false".to_string())
+}
+
+// derive_seed derives the seed of the password's random number generator from the key and the
+// label, using Argon2id, so that the key cannot be recovered from the passwords.
+fn derive_seed(key: &str, label: &str) -> Result<[u8; 32], ExternalError> {
+    if key.is_empty() {
+        return Err(ExternalError::MissingKey);
+    }
+
+    let mut seed = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(
+            key.as_bytes(),
+            format!("motus external {}", label).as_bytes(),
+            &mut seed,
+        )
+        .map_err(|_| ExternalError::KeyDerivation)?;
+    Ok(seed)
+}
+
+/// ExternalError represents the errors that can occur while answering a query.
+#[derive(Debug)]
+pub enum ExternalError {
+    Malformed,
+    MissingKey,
+    MissingLabel,
+    UnknownKind(String),
+    UnknownKey(String),
+    Invalid(&'static str, String),
+    KeyDerivation,
+}
+
+impl Display for ExternalError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExternalError::Malformed => write!(f, "the query must be a JSON object of strings"),
+            ExternalError::MissingKey => write!(
+                f,
+                "the {} environment variable must hold the secret key passwords are derived from",
+                KEY_ENV
+            ),
+            ExternalError::MissingLabel => write!(
+                f,
+                "the query must hold a label, identifying the password to derive"
+            ),
+            ExternalError::UnknownKind(kind) => write!(
+                f,
+                "unknown kind '{}': expected random, memorable, pin or apple",
+                kind
+            ),
+            ExternalError::UnknownKey(key) => write!(f, "unknown query key '{}'", key),
+            ExternalError::Invalid(key, err) => write!(f, "invalid {}: {}", key, err),
+            ExternalError::KeyDerivation => write!(f, "unable to derive the password"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(pairs: &[(&str, &str)]) -> Query {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let db = query(&[("label", "db"), ("characters", "24"), ("symbols", "true")]);
+        let first = generate(&db, "s3cr3t").unwrap();
+        assert_eq!(first["kind"], "random");
+        assert_eq!(first["password"].len(), 24);
+        assert_eq!(generate(&db, "s3cr3t").unwrap(), first);
+
+        let api = query(&[("label", "api"), ("characters", "24"), ("symbols", "true")]);
+        assert_ne!(
+            generate(&api, "s3cr3t").unwrap()["password"],
+            first["password"]
+        );
+        assert_ne!(
+            generate(&db, "an0th3r").unwrap()["password"],
+            first["password"]
+        );
+    }
+
+    #[test]
+    fn test_generate_kinds() {
+        let pin = generate(&query(&[("label", "door"), ("kind", "pin")]), "k").unwrap();
+        assert_eq!(pin["password"].len(), 7);
+        assert!(pin["password"].chars().all(|c| c.is_ascii_digit()));
+
+        let memorable = query(&[("label", "wifi"), ("kind", "memorable"), ("words", "4")]);
+        let memorable = generate(&memorable, "k").unwrap();
+        assert_eq!(memorable["password"].split(' ').count(), 4);
+
+        let preset = query(&[("label", "ad"), ("policy_preset", "pci-dss")]);
+        assert!(generate(&preset, "k").is_ok());
+    }
+
+    #[test]
+    fn test_generate_rejects_invalid_queries() {
+        assert!(matches!(
+            generate(&query(&[]), "k"),
+            Err(ExternalError::MissingLabel)
+        ));
+        assert!(matches!(
+            generate(&query(&[("label", "db")]), ""),
+            Err(ExternalError::MissingKey)
+        ));
+        assert!(matches!(
+            generate(&query(&[("label", "db"), ("kind", "uuid")]), "k"),
+            Err(ExternalError::UnknownKind(_))
+        ));
+        assert!(matches!(
+            generate(&query(&[("label", "db"), ("lenght", "24")]), "k"),
+            Err(ExternalError::UnknownKey(_))
+        ));
+        assert!(matches!(
+            generate(&query(&[("label", "db"), ("characters", "2")]), "k"),
+            Err(ExternalError::Invalid("characters", _))
+        ));
+        assert!(matches!(
+            read_query(&b"{\"characters\": 24}"[..]),
+            Err(ExternalError::Malformed)
+        ));
+    }
+}
@@ -0,0 +1,205 @@
+use std::io::{self, BufWriter, Write};
+
+use rand::prelude::*;
+use serde::Serialize;
+
+use crate::{csv_field, OutputFormat, PasswordKind};
+
+// COMMON_WORDS is a list of base words commonly found in leaked human-chosen passwords.
+const COMMON_WORDS: &[&str] = &[
+    "password", "letmein", "welcome", "dragon", "monkey", "sunshine", "football", "baseball",
+    "princess", "shadow", "master", "admin", "iloveyou", "superman", "batman", "trustno1",
+    "freedom", "whatever", "charlie", "michael", "jessica", "ashley", "hunter", "soccer", "secret",
+    "summer", "winter", "spring", "autumn", "london", "paris", "love", "hello",
+];
+
+// KEYBOARD_WALKS is a list of keyboard sequences commonly used as passwords.
+const KEYBOARD_WALKS: &[&str] = &[
+    "qwerty",
+    "qwertyuiop",
+    "asdfgh",
+    "asdfghjkl",
+    "zxcvbn",
+    "1qaz2wsx",
+    "qazwsx",
+    "azerty",
+    "1q2w3e4r",
+    "qweasd",
+];
+
+// COMMON_SUFFIXES is a list of symbols humans tend to append to satisfy complexity rules.
+const COMMON_SUFFIXES: &[&str] = &["!", "!!", "?", "@", "#", "$", "*", "."];
+
+/// Generates `count` passwords of the given `kind` and writes them to stdout in the requested
+/// format.
+///
+/// Generated passwords are never copied to the clipboard nor analyzed, and output is buffered so
+/// that generating large data sets remains fast.
+///
+/// When `weak` is set, the generated passwords are intentionally insecure and mimic the patterns
+/// humans commonly use. They are meant to seed test databases or exercise strength meters, and
+/// are labeled as insecure in every output format.
+pub fn run<R: Rng>(
+    rng: &mut R,
+    count: u32,
+    kind: PasswordKind,
+    weak: bool,
+    format: &OutputFormat,
+) -> io::Result<()> {
+    if weak {
+        eprintln!("warning: --weak passwords are INSECURE and must only be used as test data");
+    }
+
+    let passwords = (0..count).map(|_| match (kind, weak) {
+        (PasswordKind::Pin, true) => weak_pin(rng),
+        (_, true) => weak_password(rng),
+        (PasswordKind::Memorable, false) => {
+            motus::memorable_password(rng, 5, motus::Separator::Space, 1, false, false)
+        }
+        (PasswordKind::Random, false) => motus::random_password(rng, 20, false, false, &[]),
+        (PasswordKind::Pin, false) => motus::pin_password(rng, 7),
+        (PasswordKind::Apple, false) => motus::apple_password(rng),
+    });
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    match format {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            for password in passwords {
+                writeln!(out, "{}", password)?;
+            }
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "kind,password,insecure")?;
+            for password in passwords {
+                writeln!(out, "{},{},{}", kind, csv_field(&password), weak)?;
+            }
+        }
+        OutputFormat::Json => {
+            let entries: Vec<FakeOutput> = passwords
+                .map(|password| FakeOutput {
+                    kind,
+                    password,
+                    insecure: weak,
+                })
+                .collect();
+            serde_json::to_writer(&mut out, &entries)?;
+            writeln!(out)?;
+        }
+    }
+
+    out.flush()
+}
+
+#[derive(Serialize)]
+struct FakeOutput {
+    kind: PasswordKind,
+    password: String,
+    insecure: bool,
+}
+
+// weak_password returns a password following one of the patterns humans commonly use when
+// choosing their own passwords.
+fn weak_password<R: Rng>(rng: &mut R) -> String {
+    let word = COMMON_WORDS
+        .choose(rng)
+        .expect("common words should not be empty");
+
+    match rng.gen_range(0..6) {
+        // password123
+        0 => format!("{}{}", word, rng.gen_range(1..1000)),
+        // Summer2019!
+        1 => format!(
+            "{}{}{}",
+            capitalize(word),
+            rng.gen_range(1970..2030),
+            COMMON_SUFFIXES
+                .choose(rng)
+                .expect("suffixes should not be empty")
+        ),
+        // p@ssw0rd
+        2 => leetspeak(word),
+        // qwerty123
+        3 => format!(
+            "{}{}",
+            KEYBOARD_WALKS
+                .choose(rng)
+                .expect("keyboard walks should not be empty"),
+            ["", "1", "12", "123", "1234"]
+                .choose(rng)
+                .expect("digits should not be empty")
+        ),
+        // Password1
+        4 => format!("{}{}", capitalize(word), rng.gen_range(0..10)),
+        // letmein
+        _ => (*word).to_string(),
+    }
+}
+
+// weak_pin returns a PIN following one of the patterns humans commonly use when choosing
+// their own PIN codes.
+fn weak_pin<R: Rng>(rng: &mut R) -> String {
+    match rng.gen_range(0..4) {
+        // 0000, 1111, ...
+        0 => rng.gen_range(0..10).to_string().repeat(4),
+        // 1234, 123456, ...
+        1 => ["1234", "12345", "123456", "4321", "1212", "6969", "2580"]
+            .choose(rng)
+            .expect("pin sequences should not be empty")
+            .to_string(),
+        // birth years
+        2 => rng.gen_range(1950..2010).to_string(),
+        // dates formatted as DDMM
+        _ => format!("{:02}{:02}", rng.gen_range(1..29), rng.gen_range(1..13)),
+    }
+}
+
+// capitalize returns the given word with its first letter in uppercase.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+// leetspeak replaces the letters of the given word with their common "leet" substitutions.
+fn leetspeak(word: &str) -> String {
+    word.chars()
+        .map(|c| match c {
+            'a' => '@',
+            'e' => '3',
+            'i' => '1',
+            'o' => '0',
+            's' => '$',
+            _ => c,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weak_pin_is_numeric() {
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let pin = weak_pin(&mut rng);
+            assert!(pin.chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn test_leetspeak() {
+        assert_eq!(leetspeak("password"), "p@$$w0rd");
+    }
+
+    #[test]
+    fn test_capitalize() {
+        assert_eq!(capitalize("summer"), "Summer");
+        assert_eq!(capitalize(""), "");
+    }
+}
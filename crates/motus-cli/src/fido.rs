@@ -0,0 +1,222 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+// RP_ID is the relying party the credentials protecting histories are created for.
+#[cfg(feature = "fido")]
+const RP_ID: &str = "motus";
+
+/// PIN_ENV is the environment variable holding the PIN of the security key, for keys that
+/// require one to be used.
+#[cfg(feature = "fido")]
+pub const PIN_ENV: &str = "MOTUS_FIDO_PIN";
+
+/// Token is the FIDO2 credential protecting a history, recorded in a file next to it.
+///
+/// Unlocking a protected history takes the hmac-secret the security key computes for the
+/// credential and the token's salt, on top of the passphrase: neither the passphrase nor the key
+/// alone is enough. The token itself is no secret.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct Token {
+    credential_id: String,
+    salt: String,
+}
+
+impl Token {
+    /// Loads the token protecting the history at `history`, or returns None if it is not
+    /// protected.
+    pub fn load(history: &Path) -> Result<Option<Self>, FidoError> {
+        match fs::read(path(history)) {
+            Ok(data) => serde_json::from_slice(&data)
+                .map(Some)
+                .map_err(|_| FidoError::Corrupted),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Writes the token next to the history at `history`.
+    pub fn save(&self, history: &Path) -> Result<(), FidoError> {
+        let content = serde_json::to_string(self).expect("tokens should be serializable");
+        fs::write(path(history), content)?;
+        Ok(())
+    }
+
+    /// Creates a new credential with the hmac-secret extension on the security key plugged in,
+    /// and returns the token recording it.
+    #[cfg(feature = "fido")]
+    pub fn enroll() -> Result<Self, FidoError> {
+        use ctap_hid_fido2::fidokey::make_credential::make_credential_params::Extension;
+        use ctap_hid_fido2::fidokey::MakeCredentialArgsBuilder;
+        use ctap_hid_fido2::verifier;
+        use rand::prelude::*;
+
+        let device = device()?;
+        let challenge = verifier::create_challenge();
+        let builder = MakeCredentialArgsBuilder::new(RP_ID, &challenge)
+            .extensions(&[Extension::HmacSecret(Some(true))]);
+        let pin = std::env::var(PIN_ENV).ok();
+        let args = match &pin {
+            Some(pin) => builder.pin(pin).build(),
+            None => builder.without_pin_and_uv().build(),
+        };
+
+        eprintln!("Touch your security key to create the credential protecting the history");
+        let attestation = device
+            .make_credential_with_args(&args)
+            .map_err(|err| FidoError::Device(err.to_string()))?;
+        let verified = verifier::verify_attestation(RP_ID, &challenge, &attestation);
+        if !verified.is_success {
+            return Err(FidoError::Device(
+                "the security key returned an invalid attestation".to_string(),
+            ));
+        }
+
+        let mut salt = [0u8; 32];
+        thread_rng().fill_bytes(&mut salt);
+        Ok(Self {
+            credential_id: STANDARD.encode(verified.credential_id),
+            salt: STANDARD.encode(salt),
+        })
+    }
+
+    /// Returns the hmac-secret the security key plugged in computes for the token.
+    #[cfg(feature = "fido")]
+    pub fn secret(&self) -> Result<[u8; 32], FidoError> {
+        use ctap_hid_fido2::fidokey::get_assertion::get_assertion_params::Extension;
+        use ctap_hid_fido2::fidokey::GetAssertionArgsBuilder;
+        use ctap_hid_fido2::verifier;
+
+        let (credential_id, salt) = self.decode()?;
+        let device = device()?;
+        let challenge = verifier::create_challenge();
+        let builder = GetAssertionArgsBuilder::new(RP_ID, &challenge)
+            .credential_id(&credential_id)
+            .extensions(&[Extension::HmacSecret(Some(salt))]);
+        let pin = std::env::var(PIN_ENV).ok();
+        let args = match &pin {
+            Some(pin) => builder.pin(pin).build(),
+            None => builder.without_pin_and_uv().build(),
+        };
+
+        eprintln!("Touch your security key to unlock the history");
+        let assertions = device
+            .get_assertion_with_args(&args)
+            .map_err(|err| FidoError::Device(err.to_string()))?;
+        assertions
+            .iter()
+            .flat_map(|assertion| &assertion.extensions)
+            .find_map(|extension| match extension {
+                Extension::HmacSecret(Some(secret)) => Some(*secret),
+                _ => None,
+            })
+            .ok_or_else(|| {
+                FidoError::Device("the security key did not return the hmac-secret".to_string())
+            })
+    }
+
+    /// Creates a new credential on the security key plugged in, which requires motus to be
+    /// built with the fido feature.
+    #[cfg(not(feature = "fido"))]
+    pub fn enroll() -> Result<Self, FidoError> {
+        Err(FidoError::Unavailable)
+    }
+
+    /// Returns the hmac-secret of the token, which requires motus to be built with the fido
+    /// feature.
+    #[cfg(not(feature = "fido"))]
+    pub fn secret(&self) -> Result<[u8; 32], FidoError> {
+        self.decode()?;
+        Err(FidoError::Unavailable)
+    }
+
+    // decode returns the credential id and the salt of the token.
+    fn decode(&self) -> Result<(Vec<u8>, [u8; 32]), FidoError> {
+        let credential_id = STANDARD
+            .decode(&self.credential_id)
+            .map_err(|_| FidoError::Corrupted)?;
+        let salt = STANDARD
+            .decode(&self.salt)
+            .ok()
+            .and_then(|salt| salt.try_into().ok())
+            .ok_or(FidoError::Corrupted)?;
+        Ok((credential_id, salt))
+    }
+}
+
+/// path returns the location of the token protecting the history at `history`, next to it.
+pub fn path(history: &Path) -> PathBuf {
+    history.with_extension("fido")
+}
+
+// device opens the first security key plugged in.
+#[cfg(feature = "fido")]
+fn device() -> Result<ctap_hid_fido2::FidoKeyHid, FidoError> {
+    ctap_hid_fido2::FidoKeyHidFactory::create(&ctap_hid_fido2::Cfg::init())
+        .map_err(|_| FidoError::NoDevice)
+}
+
+/// FidoError represents the errors that can occur while using a security key.
+#[derive(Debug)]
+pub enum FidoError {
+    Io(io::Error),
+    Corrupted,
+    #[cfg_attr(feature = "fido", allow(dead_code))]
+    Unavailable,
+    #[cfg_attr(not(feature = "fido"), allow(dead_code))]
+    NoDevice,
+    #[cfg_attr(not(feature = "fido"), allow(dead_code))]
+    Device(String),
+}
+
+impl Display for FidoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FidoError::Io(err) => write!(f, "unable to access the security key token: {}", err),
+            FidoError::Corrupted => write!(f, "the security key token is corrupted"),
+            FidoError::Unavailable => write!(
+                f,
+                "the history is protected by a security key, but motus was built without the fido feature; rebuild it with `--features fido`"
+            ),
+            FidoError::NoDevice => write!(f, "no FIDO2 security key was found"),
+            FidoError::Device(err) => write!(f, "the security key failed: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for FidoError {
+    fn from(err: io::Error) -> Self {
+        FidoError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_round_trip() {
+        let dir = std::env::temp_dir().join(format!("motus-fido-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let history = dir.join("history.enc");
+        assert_eq!(Token::load(&history).unwrap(), None);
+
+        let token = Token {
+            credential_id: STANDARD.encode([1u8; 64]),
+            salt: STANDARD.encode([2u8; 32]),
+        };
+        token.save(&history).unwrap();
+        assert!(dir.join("history.fido").exists());
+        assert_eq!(Token::load(&history).unwrap(), Some(token));
+
+        fs::write(path(&history), "mHYvjgQAKBHBIRYdpPAI").unwrap();
+        assert!(matches!(Token::load(&history), Err(FidoError::Corrupted)));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
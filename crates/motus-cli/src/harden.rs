@@ -0,0 +1,54 @@
+use std::io;
+
+/// harden keeps the secrets the process holds from leaking through it crashing or being
+/// inspected: core dumps are disabled and, on Linux, the process is marked as not dumpable, which
+/// also keeps other processes of the same user from attaching to it with ptrace or reading its
+/// memory through /proc.
+///
+/// Hardening only applies to the running process: programs motus runs, such as gpg or wl-copy,
+/// are dumpable again once executed, although they inherit the disabled core dumps.
+#[cfg(unix)]
+pub fn harden() -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: setrlimit reads the limit it is given, which outlives the call
+    if unsafe { libc::setrlimit(libc::RLIMIT_CORE, &limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    // SAFETY: PR_SET_DUMPABLE takes its value as the only argument, and reads no memory
+    if unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn harden() -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_harden() {
+        harden().unwrap();
+
+        // SAFETY: getrlimit writes the limit to the structure it is given, which outlives the
+        // call, and PR_GET_DUMPABLE reads no memory
+        let (limit, dumpable) = unsafe {
+            let mut limit = std::mem::zeroed::<libc::rlimit>();
+            libc::getrlimit(libc::RLIMIT_CORE, &mut limit);
+            (limit, libc::prctl(libc::PR_GET_DUMPABLE))
+        };
+        assert_eq!((limit.rlim_cur, limit.rlim_max), (0, 0));
+        assert_eq!(dumpable, 0);
+    }
+}
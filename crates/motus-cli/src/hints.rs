@@ -0,0 +1,58 @@
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+
+/// HintsReport lists the words of a memorable password defined in the embedded glossary, along
+/// with their definition, to help users weave them into a story they remember.
+#[derive(Serialize, Clone)]
+#[serde(transparent)]
+pub struct HintsReport {
+    words: Vec<WordHint>,
+}
+
+#[derive(Serialize, Clone)]
+struct WordHint {
+    word: &'static str,
+    hint: &'static str,
+}
+
+impl HintsReport {
+    /// Returns the hints about the words of the given memorable password.
+    pub fn new(password: &str) -> Self {
+        HintsReport {
+            words: motus::glossary::words(password)
+                .into_iter()
+                .filter_map(|word| motus::glossary::hint(word).map(|hint| WordHint { word, hint }))
+                .collect(),
+        }
+    }
+
+    /// display prints the hints as a table, or a notice if the glossary defines none of the words.
+    pub fn display(&self, table_style: TableStyle, max_width: usize) {
+        if self.words.is_empty() {
+            println!("No hints: the glossary defines none of the words");
+            return;
+        }
+
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder("Word hints")
+            .col_span(2)
+            .alignment(Alignment::Left)
+            .build()]));
+
+        for word in &self.words {
+            table.add_row(Row::new(vec![
+                TableCell::new(word.word.bold()),
+                TableCell::builder(word.hint)
+                    .alignment(Alignment::Left)
+                    .build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::Params;
+use motus::ct;
+use motus::similarity::{similarity, DEFAULT_THRESHOLD};
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::{self, NONCE_LEN, SALT_LEN};
+use crate::fido::{self, FidoError};
+use crate::PasswordKind;
+
+// MAGIC identifies motus history files, and the version of their format.
+const MAGIC: &[u8; 8] = b"MOTUSH01";
+
+/// PASSPHRASE_ENV is the environment variable the history passphrase is read from, if set,
+/// instead of prompting for it.
+pub const PASSPHRASE_ENV: &str = "MOTUS_HISTORY_PASSPHRASE";
+
+/// History is the encrypted store of previously generated passwords.
+///
+/// It is persisted as a single file, holding a JSON document encrypted with XChaCha20-Poly1305
+/// using a key derived from the user's passphrase with Argon2id.
+pub struct History {
+    path: PathBuf,
+    key: [u8; 32],
+    salt: [u8; SALT_LEN],
+    entries: Vec<HistoryEntry>,
+}
+
+/// HistoryEntry is a single password stored in the history.
+#[derive(Serialize, Deserialize, Clone, Debug, Eq)]
+pub struct HistoryEntry {
+    pub label: String,
+    pub kind: PasswordKind,
+    pub password: String,
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    /// The parameters the password was generated with, for --idempotent to only reuse it when
+    /// asked for the same password again; entries stored by earlier versions have none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parameters: Option<serde_json::Value>,
+}
+
+// Entries are compared when merging histories, and their passwords are compared in constant time
+// like any other secret.
+impl PartialEq for HistoryEntry {
+    fn eq(&self, other: &Self) -> bool {
+        ct::str_eq(&self.password, &other.password)
+            & (self.label == other.label)
+            & (self.kind == other.kind)
+            & (self.created_at == other.created_at)
+            & (self.tags == other.tags)
+            & (self.parameters == other.parameters)
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct HistoryDocument {
+    entries: Vec<HistoryEntry>,
+}
+
+impl History {
+    /// Opens the history stored at `path`, decrypting it with `passphrase`, along with the
+    /// hmac-secret of the security key protecting it, if any.
+    ///
+    /// If no history exists yet at `path`, an empty one is returned, and will be created upon
+    /// the first call to `save`.
+    pub fn open(path: &Path, passphrase: &str) -> Result<Self, HistoryError> {
+        let hardware = match fido::Token::load(path)? {
+            Some(token) => Some(token.secret()?),
+            None => None,
+        };
+        Self::open_with(path, |salt| derive_key(passphrase, hardware.as_ref(), salt))
+    }
+
+    /// Opens the history stored at `path` with the key an agent holds for it, if one is running
+    /// and does, or with the passphrase otherwise, handing the key over to the agent then.
+    pub fn unlock(path: &Path) -> Result<Self, HistoryError> {
+        #[cfg(unix)]
+        if let Ok(history) = Self::open_with(path, |salt| {
+            crate::agent::get(salt).ok_or(HistoryError::Decryption)
+        }) {
+            return Ok(history);
+        }
+
+        let history = Self::open(path, &read_passphrase()?)?;
+        #[cfg(unix)]
+        crate::agent::put(&history.salt, &history.key);
+        Ok(history)
+    }
+
+    // open_with opens the history stored at `path`, decrypting it with the key `key` returns for
+    // its salt.
+    fn open_with(
+        path: &Path,
+        key: impl FnOnce(&[u8; SALT_LEN]) -> Result<[u8; 32], HistoryError>,
+    ) -> Result<Self, HistoryError> {
+        if !path.exists() {
+            let mut salt = [0u8; SALT_LEN];
+            thread_rng().fill_bytes(&mut salt);
+
+            return Ok(Self {
+                path: path.to_path_buf(),
+                key: key(&salt)?,
+                salt,
+                entries: Vec::new(),
+            });
+        }
+
+        let data = fs::read(path)?;
+        if data.len() < MAGIC.len() + SALT_LEN + NONCE_LEN || !data.starts_with(MAGIC) {
+            return Err(HistoryError::Corrupted);
+        }
+
+        let (salt, rest) = data[MAGIC.len()..].split_at(SALT_LEN);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let salt: [u8; SALT_LEN] = salt.try_into().expect("salt should have the right length");
+        let key = key(&salt)?;
+
+        let plaintext =
+            crypto::open(&key, nonce, ciphertext, &[]).map_err(|_| HistoryError::Decryption)?;
+        let document: HistoryDocument =
+            serde_json::from_slice(&plaintext).map_err(|_| HistoryError::Corrupted)?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            key,
+            salt,
+            entries: document.entries,
+        })
+    }
+
+    /// Derives a new key for the history from `passphrase` and, if given, the hmac-secret of
+    /// the security key protecting it, with a new salt. The history is encrypted with the new
+    /// key from the next call to `save`.
+    pub fn rekey(
+        &mut self,
+        passphrase: &str,
+        hardware: Option<&[u8; 32]>,
+    ) -> Result<(), HistoryError> {
+        thread_rng().fill_bytes(&mut self.salt);
+        self.key = derive_key(passphrase, hardware, &self.salt)?;
+        Ok(())
+    }
+
+    /// Returns every entry of the history.
+    pub fn entries(&self) -> &[HistoryEntry] {
+        &self.entries
+    }
+
+    /// Adds the given entries to the history, skipping the ones it already holds, and returns
+    /// how many were added.
+    pub fn merge(&mut self, entries: Vec<HistoryEntry>) -> usize {
+        let before = self.entries.len();
+        for entry in entries {
+            if !self.entries.contains(&entry) {
+                self.entries.push(entry);
+            }
+        }
+        self.entries.len() - before
+    }
+
+    /// Returns the entries previously stored under `label`.
+    pub fn entries_for<'a>(&'a self, label: &'a str) -> impl Iterator<Item = &'a HistoryEntry> {
+        self.entries.iter().filter(move |e| e.label == label)
+    }
+
+    /// Records a newly generated password under `label`, along with the tags annotating it and
+    /// the parameters it was generated with.
+    pub fn push(
+        &mut self,
+        label: &str,
+        kind: PasswordKind,
+        password: &str,
+        tags: &BTreeMap<String, String>,
+        parameters: Option<&serde_json::Value>,
+    ) {
+        let created_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        self.entries.push(HistoryEntry {
+            label: label.to_string(),
+            kind,
+            password: password.to_string(),
+            created_at,
+            tags: tags.clone(),
+            parameters: parameters.cloned(),
+        });
+    }
+
+    /// Encrypts and writes the history back to its file, creating parent directories as needed.
+    pub fn save(&self) -> Result<(), HistoryError> {
+        let document = HistoryDocument {
+            entries: self.entries.clone(),
+        };
+        let plaintext = serde_json::to_vec(&document).expect("history should be serializable");
+
+        let mut nonce = [0u8; NONCE_LEN];
+        thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = crypto::seal(&self.key, &nonce, &plaintext, &[])
+            .map_err(|_| HistoryError::Encryption)?;
+
+        let mut data = Vec::with_capacity(MAGIC.len() + SALT_LEN + NONCE_LEN + ciphertext.len());
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&self.salt);
+        data.extend_from_slice(&nonce);
+        data.extend_from_slice(&ciphertext);
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, data)?;
+
+        Ok(())
+    }
+}
+
+/// record stores a newly generated `password` in the history at `path` under `label`, along with
+/// the tags annotating it and the `parameters` it was generated with.
+///
+/// Before storing it, the password is compared to the ones previously generated for the same
+/// label, and a warning is printed if it is too similar to any of them, as rotating a credential
+/// should produce a genuinely new one.
+pub fn record(
+    path: &Path,
+    label: &str,
+    kind: PasswordKind,
+    password: &str,
+    tags: &BTreeMap<String, String>,
+    parameters: &serde_json::Value,
+) -> Result<(), HistoryError> {
+    let mut history = History::unlock(path)?;
+
+    let closest = history
+        .entries_for(label)
+        .map(|entry| similarity(&entry.password, password))
+        .fold(0.0, f64::max);
+    if closest >= DEFAULT_THRESHOLD {
+        eprintln!(
+            "warning: the generated password is {:.0}% similar to one previously generated for '{}'; consider generating a new one",
+            closest * 100.0,
+            label
+        );
+    }
+
+    history.push(label, kind, password, tags, Some(parameters));
+    history.save()
+}
+
+/// recall returns the entry most recently stored in the history at `path` under `label`, or None
+/// if there is none.
+pub fn recall(path: &Path, label: &str) -> Result<Option<HistoryEntry>, HistoryError> {
+    let history = History::unlock(path)?;
+
+    Ok(history
+        .entries_for(label)
+        .max_by_key(|entry| entry.created_at)
+        .cloned())
+}
+
+/// default_path returns the default location of the history file, in the user's data directory.
+pub fn default_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("motus")
+        .join("history.enc")
+}
+
+/// store_path returns the location of the named store, a history of its own kept next to the
+/// default one, and encrypted with its own passphrase.
+pub fn store_path(name: &str) -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("motus")
+        .join("stores")
+        .join(format!("{}.enc", name))
+}
+
+/// validate_store_name parses the given string as the name of a store, which must be made of at
+/// most 64 ASCII letters, digits, dashes and underscores, as it names the store's file.
+pub fn validate_store_name(s: &str) -> Result<String, String> {
+    if s.is_empty()
+        || s.len() > 64
+        || !s
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(
+            "The store name must be made of at most 64 letters, digits, dashes and underscores"
+                .to_string(),
+        );
+    }
+    Ok(s.to_string())
+}
+
+/// read_passphrase returns the history passphrase from the `MOTUS_HISTORY_PASSPHRASE`
+/// environment variable if it is set, or prompts the user for it otherwise. The user is prompted
+/// at most once per run, however many times the history is opened.
+pub fn read_passphrase() -> Result<String, HistoryError> {
+    static PASSPHRASE: OnceLock<String> = OnceLock::new();
+
+    if let Some(passphrase) = PASSPHRASE.get() {
+        return Ok(passphrase.clone());
+    }
+    let passphrase = match std::env::var(PASSPHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => rpassword::prompt_password("History passphrase: ")?,
+    };
+    Ok(PASSPHRASE.get_or_init(|| passphrase).clone())
+}
+
+// derive_key derives the history encryption key from the user's passphrase using Argon2id,
+// along with the hmac-secret of the security key protecting the history, if any.
+fn derive_key(
+    passphrase: &str,
+    hardware: Option<&[u8; 32]>,
+    salt: &[u8],
+) -> Result<[u8; 32], HistoryError> {
+    let mut secret = passphrase.as_bytes().to_vec();
+    if let Some(hardware) = hardware {
+        secret.push(0);
+        secret.extend_from_slice(hardware);
+    }
+
+    crypto::derive_key(&secret, salt, Params::default()).map_err(|_| HistoryError::KeyDerivation)
+}
+
+/// HistoryError represents the errors that can occur while interacting with the history.
+#[derive(Debug)]
+pub enum HistoryError {
+    Io(io::Error),
+    KeyDerivation,
+    Encryption,
+    Decryption,
+    Corrupted,
+    Fido(FidoError),
+}
+
+impl Display for HistoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HistoryError::Io(err) => write!(f, "unable to access the history file: {}", err),
+            HistoryError::KeyDerivation => write!(f, "unable to derive the history key"),
+            HistoryError::Encryption => write!(f, "unable to encrypt the history"),
+            HistoryError::Decryption => {
+                write!(
+                    f,
+                    "unable to decrypt the history; is the passphrase correct?"
+                )
+            }
+            HistoryError::Corrupted => write!(f, "the history file is corrupted"),
+            HistoryError::Fido(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl From<io::Error> for HistoryError {
+    fn from(err: io::Error) -> Self {
+        HistoryError::Io(err)
+    }
+}
+
+impl From<FidoError> for HistoryError {
+    fn from(err: FidoError) -> Self {
+        HistoryError::Fido(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("motus-{}-{}.enc", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_history_roundtrip() {
+        let path = temp_path("roundtrip");
+
+        let mut history = History::open(&path, "correct horse").unwrap();
+        let tags = BTreeMap::from([("env".to_string(), "prod".to_string())]);
+        history.push(
+            "db",
+            PasswordKind::Random,
+            "mHYvjgQAKBHBIRYdpPAI",
+            &tags,
+            None,
+        );
+        history.save().unwrap();
+
+        let history = History::open(&path, "correct horse").unwrap();
+        let entries: Vec<_> = history.entries_for("db").collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].password, "mHYvjgQAKBHBIRYdpPAI");
+        assert_eq!(entries[0].tags, tags);
+        assert_eq!(history.entries_for("other").count(), 0);
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_store_path() {
+        assert_ne!(store_path("work"), store_path("personal"));
+        assert_ne!(store_path("work"), default_path());
+        assert!(store_path("work").ends_with("stores/work.enc"));
+    }
+
+    #[test]
+    fn test_validate_store_name() {
+        assert_eq!(validate_store_name("client-a"), Ok("client-a".to_string()));
+        assert_eq!(validate_store_name("work_2"), Ok("work_2".to_string()));
+        assert!(validate_store_name("").is_err());
+        assert!(validate_store_name("../work").is_err());
+        assert!(validate_store_name("my work").is_err());
+        assert!(validate_store_name(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_history_wrong_passphrase() {
+        let path = temp_path("wrong-passphrase");
+
+        let mut history = History::open(&path, "correct horse").unwrap();
+        history.push("db", PasswordKind::Pin, "5564047", &BTreeMap::new(), None);
+        history.save().unwrap();
+
+        assert!(matches!(
+            History::open(&path, "battery staple"),
+            Err(HistoryError::Decryption)
+        ));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_history_rekey() {
+        let path = temp_path("rekey");
+        let hardware = [7u8; 32];
+
+        let mut history = History::open(&path, "correct horse").unwrap();
+        history.push("db", PasswordKind::Pin, "5564047", &BTreeMap::new(), None);
+        history.rekey("correct horse", Some(&hardware)).unwrap();
+        history.save().unwrap();
+
+        assert!(matches!(
+            History::open(&path, "correct horse"),
+            Err(HistoryError::Decryption)
+        ));
+        let history = History::open_with(&path, |salt| {
+            derive_key("correct horse", Some(&hardware), salt)
+        })
+        .unwrap();
+        assert_eq!(history.entries().len(), 1);
+
+        fs::remove_file(path).unwrap();
+    }
+}
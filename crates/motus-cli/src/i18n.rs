@@ -0,0 +1,215 @@
+use clap::ValueEnum;
+
+/// Locale is the language and formatting conventions of the analysis reports.
+///
+/// Messages are written in English throughout the code base, and translated on display by looking
+/// them up in the locale's catalogue; messages missing from a catalogue are displayed in English.
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+    Fr,
+}
+
+impl Locale {
+    /// code returns the ISO 639-1 code of the locale's language, as HTML documents declare it.
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Fr => "fr",
+        }
+    }
+
+    /// text translates the given English message.
+    pub fn text(self, message: &'static str) -> &'static str {
+        match self {
+            Locale::En => message,
+            Locale::Fr => french(message).unwrap_or(message),
+        }
+    }
+
+    /// number formats the given value with the given number of decimals, grouping the digits of
+    /// its integer part by thousands.
+    pub fn number(self, value: f64, decimals: usize) -> String {
+        let (thousands, decimal) = match self {
+            Locale::En => (",", "."),
+            Locale::Fr => ("\u{202f}", ","),
+        };
+
+        let formatted = format!("{:.*}", decimals, value.abs());
+        let (integer, fraction) = match formatted.split_once('.') {
+            Some((integer, fraction)) => (integer, Some(fraction)),
+            None => (formatted.as_str(), None),
+        };
+
+        let mut grouped = String::new();
+        if value < 0.0 {
+            grouped.push('-');
+        }
+        for (i, digit) in integer.chars().enumerate() {
+            if i > 0 && (integer.len() - i) % 3 == 0 {
+                grouped.push_str(thousands);
+            }
+            grouped.push(digit);
+        }
+        if let Some(fraction) = fraction {
+            grouped.push_str(decimal);
+            grouped.push_str(fraction);
+        }
+
+        grouped
+    }
+
+    /// percent formats the given ratio as a percentage with one decimal.
+    pub fn percent(self, ratio: f64) -> String {
+        let number = self.number(ratio * 100.0, 1);
+        match self {
+            Locale::En => format!("{}%", number),
+            Locale::Fr => format!("{}\u{202f}%", number),
+        }
+    }
+
+    /// duration translates a duration formatted in English the way zxcvbn does, such as
+    /// "3 hours", "less than a second" or "centuries".
+    pub fn duration(self, duration: &str) -> String {
+        if self == Locale::En {
+            return duration.to_string();
+        }
+
+        let translated = match duration.split_once(' ') {
+            Some((count, unit)) if count.parse::<u64>().is_ok() => {
+                let unit = match unit.strip_suffix('s') {
+                    Some(singular) => french_plural(singular),
+                    None => french(unit),
+                };
+                unit.map(|unit| format!("{} {}", count, unit))
+            }
+            _ => french(duration).map(str::to_string),
+        };
+
+        translated.unwrap_or_else(|| duration.to_string())
+    }
+}
+
+// french_plural returns the French plural of the given English time unit.
+fn french_plural(unit: &str) -> Option<&'static str> {
+    let plural = match unit {
+        "second" => "secondes",
+        "minute" => "minutes",
+        "hour" => "heures",
+        "day" => "jours",
+        "month" => "mois",
+        "year" => "ans",
+        _ => return None,
+    };
+
+    Some(plural)
+}
+
+// french is the French catalogue.
+fn french(message: &str) -> Option<&'static str> {
+    let translation = match message {
+        "Generated Password" => "Mot de passe généré",
+        "Security Analysis" => "Analyse de sécurité",
+        "Strength" => "Robustesse",
+        "Guesses" => "Tentatives",
+        "very weak" => "très faible",
+        "weak" => "faible",
+        "reasonable" => "moyen",
+        "strong" => "fort",
+        "very strong" => "très fort",
+        "Strength estimators" => "Estimateurs de robustesse",
+        "Crack time estimations" => "Temps de cassage estimés",
+        "100 attempts/hour" => "100 essais/heure",
+        "10 attempts/second" => "10 essais/seconde",
+        "10^4 attempts/second" => "10^4 essais/seconde",
+        "10^10 attempts/second" => "10^10 essais/seconde",
+        "attempts/second" => "essais/seconde",
+        "costs" => "coûte",
+        "less than a second" => "moins d'une seconde",
+        "centuries" => "des siècles",
+        "second" => "seconde",
+        "minute" => "minute",
+        "hour" => "heure",
+        "day" => "jour",
+        "month" => "mois",
+        "year" => "an",
+        "less than the blink of an eye" => "moins qu'un clignement d'œil",
+        "less than brewing a pot of coffee" => "moins que le temps de faire un café",
+        "less than watching a movie" => "moins que la durée d'un film",
+        "less than a day" => "moins d'une journée",
+        "less than a summer vacation" => "moins que les grandes vacances",
+        "less than a trip around the sun" => "moins qu'un tour autour du soleil",
+        "about one World Cup cycle" => "environ l'intervalle entre deux Coupes du monde",
+        "within a human lifetime" => "le temps d'une vie humaine",
+        "longer than a human lifetime" => "plus qu'une vie humaine",
+        "longer than recorded history" => "plus que toute l'histoire écrite",
+        "longer than humanity has existed" => "plus que l'existence de l'humanité",
+        "longer than since the dinosaurs died out" => {
+            "plus que depuis la disparition des dinosaures"
+        }
+        "longer than the age of the universe" => "plus que l'âge de l'univers",
+        "Generated by motus" => "Généré par motus",
+        "Policy compliance" => "Conformité à la politique",
+        "compliant" => "conforme",
+        "not compliant" => "non conforme",
+        "pass" => "respectée",
+        "fail" => "non respectée",
+        "Typo tolerance" => "Tolérance aux fautes de frappe",
+        "Typo likelihood" => "Probabilité de faute de frappe",
+        "Neighbor passwords" => "Mots de passe voisins",
+        "adjacent keys" => "touches adjacentes",
+        "case slips" => "erreurs de casse",
+        "transpositions" => "transpositions",
+        "Warnings" => "Avertissements",
+        "keyboard walk" => "suite de touches",
+        "keys follow each other along a keyboard row" => {
+            "les touches se suivent sur une rangée du clavier"
+        }
+        "keys follow each other across keyboard rows" => {
+            "les touches se suivent d'une rangée du clavier à l'autre"
+        }
+        "homoglyph" => "homoglyphe",
+        "prints ambiguously in common fonts" => {
+            "s'imprime de façon ambiguë dans les polices courantes"
+        }
+        _ => return None,
+    };
+
+    Some(translation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        assert_eq!(Locale::En.number(1234567.891, 2), "1,234,567.89");
+        assert_eq!(
+            Locale::Fr.number(1234567.891, 2),
+            "1\u{202f}234\u{202f}567,89"
+        );
+        assert_eq!(Locale::Fr.number(-999.0, 0), "-999");
+        assert_eq!(Locale::Fr.percent(0.0819), "8,2\u{202f}%");
+    }
+
+    #[test]
+    fn test_duration() {
+        assert_eq!(Locale::En.duration("3 hours"), "3 hours");
+        assert_eq!(Locale::Fr.duration("3 hours"), "3 heures");
+        assert_eq!(Locale::Fr.duration("1 month"), "1 mois");
+        assert_eq!(Locale::Fr.duration("12 months"), "12 mois");
+        assert_eq!(Locale::Fr.duration("centuries"), "des siècles");
+        assert_eq!(
+            Locale::Fr.duration("less than a second"),
+            "moins d'une seconde"
+        );
+    }
+
+    #[test]
+    fn test_text_falls_back_to_english() {
+        assert_eq!(Locale::Fr.text("Strength"), "Robustesse");
+        assert_eq!(Locale::Fr.text("Untranslated"), "Untranslated");
+    }
+}
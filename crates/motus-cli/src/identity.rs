@@ -0,0 +1,99 @@
+use std::io::{self, BufWriter, Write};
+
+use motus::secret::Secret;
+use serde::Serialize;
+
+use crate::{csv_field, OutputFormat};
+
+/// PIN_LENGTH is the number of digits of the PIN of generated identities.
+pub const PIN_LENGTH: u32 = 6;
+
+/// RECOVERY_CODE_LENGTH is the number of base32 characters of each recovery code, 50 bits' worth,
+/// displayed in groups of five.
+pub const RECOVERY_CODE_LENGTH: u32 = 10;
+
+/// Identity is the bundle of credentials of a complete account: a username, an email alias derived
+/// from it, a password, a PIN, and recovery codes.
+pub struct Identity {
+    pub username: String,
+    pub email: String,
+    pub password: Secret,
+    pub pin: Secret,
+    pub recovery_codes: Vec<Secret>,
+}
+
+/// display writes the generated identities to stdout in the requested format.
+///
+/// Identities are never recorded in the history: they are meant to seed test accounts, or to be
+/// imported into a password manager at once.
+pub fn display(identities: &[Identity], format: &OutputFormat) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    match format {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            for (index, identity) in identities.iter().enumerate() {
+                if index > 0 {
+                    writeln!(out)?;
+                }
+                writeln!(out, "username: {}", identity.username)?;
+                writeln!(out, "email: {}", identity.email)?;
+                writeln!(out, "password: {}", identity.password.expose())?;
+                writeln!(out, "pin: {}", identity.pin.expose())?;
+                if !identity.recovery_codes.is_empty() {
+                    writeln!(out, "recovery codes:")?;
+                    for code in &identity.recovery_codes {
+                        writeln!(out, "  {}", code.expose())?;
+                    }
+                }
+            }
+        }
+        OutputFormat::Json => {
+            let identities: Vec<IdentityOutput> = identities
+                .iter()
+                .map(|identity| IdentityOutput {
+                    username: &identity.username,
+                    email: &identity.email,
+                    password: identity.password.expose(),
+                    pin: identity.pin.expose(),
+                    recovery_codes: identity.recovery_codes.iter().map(Secret::expose).collect(),
+                })
+                .collect();
+            serde_json::to_writer(&mut out, &IdentitiesOutput { identities })?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            // Recovery codes hold no spaces, so that they are joined with them in a single field
+            writeln!(out, "username,email,password,pin,recovery_codes")?;
+            for identity in identities {
+                let codes: Vec<&str> = identity.recovery_codes.iter().map(|c| c.expose()).collect();
+                writeln!(
+                    out,
+                    "{},{},{},{},{}",
+                    csv_field(&identity.username),
+                    csv_field(&identity.email),
+                    csv_field(identity.password.expose()),
+                    identity.pin.expose(),
+                    codes.join(" ")
+                )?;
+            }
+        }
+    }
+
+    out.flush()
+}
+
+#[derive(Serialize)]
+struct IdentitiesOutput<'a> {
+    identities: Vec<IdentityOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct IdentityOutput<'a> {
+    username: &'a str,
+    email: &'a str,
+    password: &'a str,
+    pin: &'a str,
+    recovery_codes: Vec<&'a str>,
+}
@@ -0,0 +1,119 @@
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+use crate::OutputFormat;
+
+// FEATURES lists the optional cargo features motus is built with.
+const FEATURES: &[&str] = &[
+    #[cfg(feature = "analysis")]
+    "analysis",
+    #[cfg(feature = "clipboard")]
+    "clipboard",
+    #[cfg(feature = "fido")]
+    "fido",
+    #[cfg(feature = "headless")]
+    "headless",
+    #[cfg(feature = "memlock")]
+    "memlock",
+    #[cfg(feature = "pdf")]
+    "pdf",
+    #[cfg(feature = "qr")]
+    "qr",
+    #[cfg(feature = "tables")]
+    "tables",
+    #[cfg(feature = "tls")]
+    "tls",
+];
+
+/// BuildInfo describes how the running motus binary was built, for bug reports and for auditing
+/// which versions run across a fleet.
+#[derive(Serialize)]
+pub struct BuildInfo {
+    version: &'static str,
+    git_commit: &'static str,
+    profile: &'static str,
+    target: String,
+    features: &'static [&'static str],
+    rng: &'static str,
+    wordlist: WordlistInfo,
+}
+
+#[derive(Serialize)]
+struct WordlistInfo {
+    words: usize,
+    fingerprint: String,
+}
+
+impl BuildInfo {
+    /// Returns the information about the running binary.
+    pub fn current() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_commit: env!("MOTUS_GIT_COMMIT"),
+            profile: if cfg!(debug_assertions) {
+                "debug"
+            } else {
+                "release"
+            },
+            target: format!("{}-{}", std::env::consts::ARCH, std::env::consts::OS),
+            features: FEATURES,
+            rng: "rand 0.8 ThreadRng (ChaCha12), StdRng when seeded",
+            wordlist: WordlistInfo {
+                words: motus::wordlist_len(),
+                fingerprint: format!("fnv1a64:{:016x}", motus::wordlist_fingerprint()),
+            },
+        }
+    }
+
+    /// Writes the build information to stdout in the requested format.
+    pub fn display(&self, output: &OutputFormat) {
+        match output {
+            OutputFormat::Text
+            | OutputFormat::SystemdCred
+            | OutputFormat::Sops
+            | OutputFormat::Html => self.display_table(TableStyle::extended(), 80),
+            OutputFormat::Json => println!("{}", serde_json::to_string(self).unwrap()),
+            OutputFormat::Csv => {
+                println!("key,value");
+                for (key, value) in self.fields() {
+                    println!("{},{}", key, crate::csv_field(&value));
+                }
+            }
+        }
+    }
+
+    // fields returns the build information as flat key-value pairs.
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("version", self.version.to_string()),
+            ("git_commit", self.git_commit.to_string()),
+            ("profile", self.profile.to_string()),
+            ("target", self.target.clone()),
+            ("features", self.features.join(" ")),
+            ("rng", self.rng.to_string()),
+            ("wordlist_words", self.wordlist.words.to_string()),
+            ("wordlist_fingerprint", self.wordlist.fingerprint.clone()),
+        ]
+    }
+
+    fn display_table(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder("Build information")
+            .col_span(2)
+            .alignment(Alignment::Left)
+            .build()]));
+
+        for (key, value) in self.fields() {
+            table.add_row(Row::new(vec![
+                TableCell::new(key.bold()),
+                TableCell::builder(value).alignment(Alignment::Left).build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+    }
+}
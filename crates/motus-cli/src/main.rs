@@ -1,17 +1,105 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::net::SocketAddr;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use arboard::Clipboard;
-use clap::{Parser, Subcommand, ValueEnum};
-use colored::{ColoredString, Colorize};
+use blake2::{Blake2s256, Digest};
+use clap::builder::BoolishValueParser;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 use human_panic::setup_panic;
+#[cfg(feature = "analysis")]
+use motus::analysis::crack::AttackModel;
+use motus::analysis::estimator::EstimatorKind;
+use motus::card::Card;
+use motus::charset::{Charset, SymbolSet};
+use motus::denylist::DenyList;
+use motus::entropy::{Entropy, EntropySource};
+use motus::manual::Outcomes;
+use motus::policy::{Case, Policy, Preset, Rule, StartWith};
+#[cfg(feature = "qr")]
+use motus::render::{self, Format, RenderError};
+use motus::secret::Secret;
+use motus::sites::{Database, SiteRules};
+use motus::username::Usernames;
+use motus::wordlist::{lint, WordFilter};
+use motus::JoinStyle;
 use rand::prelude::*;
-use serde::ser::{SerializeStruct, Serializer};
-use serde::Serialize;
-use term_table::row::Row;
-use term_table::table_cell::{Alignment, TableCell};
-use term_table::{Table, TableStyle};
-use zxcvbn::zxcvbn;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::alias::Alias;
+#[cfg(feature = "analysis")]
+use crate::analysis::{Analyzer, Report};
+use crate::answers::Answer;
+#[cfg(feature = "analysis")]
+use crate::batch::BatchSummary;
+use crate::clipboard::{ClipboardContent, ClipboardMode};
+use crate::explain::EntropyReport;
+use crate::hints::HintsReport;
+use crate::identity::Identity;
+use crate::manual::ManualReport;
+use crate::native::NativeHost;
+#[cfg(feature = "analysis")]
+use crate::report::ReportFormat;
+use crate::schema::{OutputSchema, Payload};
+use crate::table::TableStyle;
+
+#[cfg(unix)]
+mod agent;
+mod alias;
+#[cfg(feature = "analysis")]
+mod analysis;
+mod answers;
+mod askpass;
+#[cfg(feature = "analysis")]
+mod audit;
+mod auditlog;
+mod backup;
+mod banner;
+#[cfg(feature = "analysis")]
+mod batch;
+mod card;
+mod clipboard;
+mod compare;
+mod config;
+mod crypto;
+mod envelope;
+mod ephemeral;
+mod explain;
+mod external;
+mod fake;
+mod fido;
+mod harden;
+mod hints;
+mod history;
+// Only the analysis reports are localized
+#[cfg_attr(not(feature = "analysis"), allow(dead_code))]
+mod i18n;
+mod identity;
+mod info;
+mod manual;
+mod native;
+#[cfg(feature = "pdf")]
+mod pdf;
+mod policy;
+mod practice;
+#[cfg(feature = "analysis")]
+mod report;
+mod rules;
+mod schema;
+mod selfcheck;
+mod selftest;
+mod share;
+mod sops;
+mod split;
+mod stats;
+mod systemd;
+mod table;
+mod username;
 
 /// Args is a struct representing the command line arguments
 #[derive(Parser, Debug)]
@@ -26,20 +114,262 @@ struct Cli {
     command: Commands,
 
     /// Disable automatic copying of generated password to clipboard
-    #[arg(long)]
+    #[arg(long, env = "MOTUS_NO_CLIPBOARD", value_parser = BoolishValueParser::new())]
     no_clipboard: bool,
 
+    /// Keep core dumps and debuggers from exposing the secrets motus handles; pass this to allow them, to debug motus
+    #[arg(long, env = "MOTUS_NO_HARDEN", value_parser = BoolishValueParser::new())]
+    no_harden: bool,
+
+    /// Whether to copy the password to the clipboard when no clipboard seems to be available, such as over SSH or in a container
+    #[arg(long, value_name = "MODE", default_value = "auto", value_enum)]
+    clipboard: clipboard::ClipboardMode,
+
+    /// Choose what is copied to the clipboard: the password, the whole JSON output with --output json, or nothing
+    #[arg(long, value_name = "CONTENT", default_value = "password", value_enum)]
+    clipboard_content: ClipboardContent,
+
+    /// Disable the hints asking clipboard managers not to record the copied password
+    #[arg(long)]
+    no_clipboard_hints: bool,
+
+    /// Keep the copied password from syncing to your other devices through Universal Clipboard (macOS only)
+    #[arg(long)]
+    clipboard_local_only: bool,
+
+    /// Write the copied password to this file instead of the clipboard, readable by you only (headless builds)
+    #[arg(long, value_name = "PATH", env = "MOTUS_CLIPBOARD_FILE")]
+    clipboard_file: Option<PathBuf>,
+
     /// Output the generated password in a specified format
-    #[arg(short, long, default_value = "text", value_enum)]
+    #[arg(
+        short,
+        long,
+        default_value = "text",
+        value_enum,
+        global = true,
+        env = "MOTUS_OUTPUT"
+    )]
     output: OutputFormat,
 
+    /// Name of the credential generated with --output systemd-cred, as LoadCredentialEncrypted= refers to it, or of the key holding the password with --output sops
+    #[arg(long, value_name = "NAME", value_parser = systemd::validate_name)]
+    name: Option<String>,
+
+    /// With --output sops, encrypt the document to this age recipient
+    #[arg(long, value_name = "RECIPIENT", conflicts_with = "kms")]
+    age: Option<String>,
+
+    /// With --output sops, encrypt the document with this AWS KMS key ARN
+    #[arg(long, value_name = "ARN")]
+    kms: Option<String>,
+
+    /// Choose the version of the schema of the JSON output, for scripts written against earlier versions of motus
+    #[arg(
+        long,
+        value_name = "VERSION",
+        default_value = "v2",
+        value_enum,
+        global = true,
+        env = "MOTUS_OUTPUT_SCHEMA"
+    )]
+    output_schema: OutputSchema,
+
     /// Display a safety analysis along the generated password
     #[arg(long)]
     analyze: bool,
 
+    /// Add a crack time estimation for an attacker making this many guesses, per second unless suffixed with /min, /h or /d (e.g. 1e12/s); may be repeated
+    #[arg(long, value_name = "RATE", value_parser = validate_attack_rate, global = true)]
+    attack_rate: Vec<f64>,
+
+    /// Hourly cost of the hardware of the attackers given with --attack-rate, to estimate what cracking the password costs
+    #[arg(long, value_name = "COST", requires = "attack_rate", global = true)]
+    attack_cost: Option<f64>,
+
+    /// Also estimate the strength of analyzed passwords with these estimators, reported side by side, as zxcvbn misjudges some generated formats; may be repeated or comma-separated
+    #[arg(
+        long,
+        value_name = "ESTIMATOR",
+        value_enum,
+        value_delimiter = ',',
+        global = true
+    )]
+    estimator: Vec<EstimatorKind>,
+
+    /// Language and number formatting of the analysis reports
+    #[arg(long, default_value = "en", value_enum, global = true)]
+    locale: i18n::Locale,
+
+    /// Follow crack time estimations with relatable comparisons, such as 'about one World Cup cycle', in analysis reports
+    #[arg(long, global = true)]
+    humanize: bool,
+
+    /// Avoid characters that print ambiguously in common fonts (0/O, 1/l/I, rn/m) in random passwords, and flag them in analysis reports, for passwords written on paper or printed on labels
+    #[arg(long, global = true)]
+    print_safe: bool,
+
+    /// Convert the letters of the generated password to this case, for systems comparing passwords regardless of case; the entropy of random passwords is reduced accordingly
+    #[arg(long, default_value = "mixed", value_enum, global = true)]
+    case: Case,
+
+    /// File of terms, one per line, generated passwords must not contain, such as company names, product names or usernames; analysis reports also treat them as dictionary words
+    #[arg(long, value_name = "FILE", env = "MOTUS_DENY_LIST", value_parser = parse_deny_list, global = true)]
+    deny_list: Option<DenyList>,
+
+    /// File of site password rules, in the format of the embedded database, adding sites to it or replacing the rules it knows for them
+    #[arg(long, value_name = "FILE", env = "MOTUS_SITES_DB", value_parser = parse_sites_db, global = true)]
+    sites_db: Option<Database>,
+
+    /// Write the analysis report to the given file instead of stdout, as a PDF with a .pdf extension (with the pdf feature), and as HTML otherwise
+    #[arg(long, value_name = "PATH", global = true)]
+    report_file: Option<PathBuf>,
+
+    /// Also write the generated password as a QR code to an SVG or PNG image, depending on the file extension, for a phone to scan it
+    #[arg(long, value_name = "PATH")]
+    qr_file: Option<PathBuf>,
+
+    /// Static text prepended verbatim to the generated password
+    #[arg(long, value_name = "TEXT")]
+    prefix: Option<String>,
+
+    /// Static text appended verbatim to the generated password
+    #[arg(long, value_name = "TEXT")]
+    suffix: Option<String>,
+
+    /// Display the password in chunks of this many characters, for easier manual entry
+    #[arg(long, value_name = "SIZE", value_parser = validate_group_size)]
+    group: Option<u32>,
+
+    /// Separator displayed between the chunks of a grouped password
+    #[arg(long, value_name = "TEXT", default_value = "-", requires = "group")]
+    group_separator: String,
+
+    /// Copy the password to the clipboard without the grouping separators
+    #[arg(long, requires = "group")]
+    clipboard_raw: bool,
+
+    /// Display the password as this many parts, each for a different custodian, with recombination instructions, for no single person to know it whole
+    #[arg(
+        long,
+        value_name = "PARTS",
+        value_parser = clap::value_parser!(u32).range(2..=4),
+        conflicts_with_all = ["analyze", "count", "group", "qr_file"]
+    )]
+    split: Option<u32>,
+
+    /// Display the password in large letters, to be read from across a room, such as a Wi-Fi password shown on a TV or a projector
+    #[arg(long, conflicts_with_all = ["analyze", "count", "split"])]
+    big: bool,
+
+    /// Spell the password out one character per line, along with its case or class, such as "capital B" or "symbol dollar", for screen readers to read it unambiguously
+    #[arg(long, conflicts_with_all = ["analyze", "big", "count", "split"])]
+    accessible: bool,
+
+    /// Erase the password from the terminal once Enter is pressed or after this many seconds, so it does not linger during screen shares
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_missing_value = "30",
+        conflicts_with_all = ["analyze", "count", "split"]
+    )]
+    ephemeral: Option<u64>,
+
+    /// After displaying the password, practice retyping it this many times, to commit a new master passphrase to memory
+    #[arg(
+        long,
+        value_name = "ROUNDS",
+        num_args = 0..=1,
+        default_missing_value = "3",
+        value_parser = clap::value_parser!(u32).range(1..=20),
+        conflicts_with_all = ["count", "split"]
+    )]
+    practice: Option<u32>,
+
+    /// Explain how much entropy each part of the generated password contributes
+    #[arg(long)]
+    explain: bool,
+
+    /// Also display the telephone keypad digits the password is typed with, for voicemail and phone systems only accepting digits
+    #[arg(long, conflicts_with_all = ["count", "split"])]
+    t9: bool,
+
+    /// Number of passwords to generate; batches are never copied to the clipboard
+    #[arg(long, default_value = "1", value_parser = validate_count)]
+    count: u32,
+
+    /// With --count and --analyze, display the analysis of each password instead of aggregate statistics
+    #[arg(long, requires = "analyze")]
+    per_item: bool,
+
+    /// Refuse to generate passwords whose theoretical entropy is below this many bits
+    #[arg(long, value_name = "BITS")]
+    min_entropy_bits: Option<u32>,
+
+    /// With --min-entropy-bits, lengthen passwords that would fall short of the floor instead of failing
+    #[arg(long, requires = "min_entropy_bits")]
+    auto_strengthen: bool,
+
     /// Seed value for deterministic password generation (for testing purposes)
     #[arg(long)]
     seed: Option<u64>, // Set the randomness source with an unsigned 64-bit integer for reproducible passwords
+
+    /// Seed deterministic password generation with the hash of this file's content, such as dice rolls or the output of a hardware random number generator, for air-gapped machines
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["seed", "seed_stdin"])]
+    seed_file: Option<PathBuf>,
+
+    /// Seed deterministic password generation with the hash of everything read from the standard input, which is then no longer available to read from
+    #[arg(long, conflicts_with_all = ["seed", "ephemeral", "practice"])]
+    seed_stdin: bool,
+
+    /// Run known-answer self-tests of the generators and the random number generator before producing any password, and fail if any of them does
+    #[arg(long, env = "MOTUS_SELF_CHECK", value_parser = BoolishValueParser::new())]
+    self_check: bool,
+
+    /// Store the generated password in the encrypted history, warning if it is too similar to one previously generated for the same label
+    #[arg(long, requires = "label")]
+    history: bool,
+
+    /// With --history, return the password stored in the encrypted history under --label, if any, instead of generating a new one, and store the generated one otherwise, so that provisioning scripts can safely be re-run; the stored password must be of the same kind, generated with the same parameters
+    #[arg(long, requires_all = ["history", "label"])]
+    idempotent: bool,
+
+    /// Path to the encrypted history file
+    #[arg(long, value_name = "PATH")]
+    history_file: Option<PathBuf>,
+
+    /// Use the named store instead of the default history: each store has its own file and its own passphrase, to keep the credentials of different clients or contexts strictly apart
+    #[arg(long, value_name = "NAME", env = "MOTUS_STORE", conflicts_with = "history_file", value_parser = history::validate_store_name)]
+    store: Option<String>,
+
+    /// Label identifying what the generated password is for (e.g. an account name), echoed in JSON and CSV output
+    #[arg(long)]
+    label: Option<String>,
+
+    /// Annotate the generated passwords with a key=value tag, echoed in JSON and CSV output and stored in the history; can be repeated
+    #[arg(long = "tag", value_name = "KEY=VALUE", value_parser = validate_tag)]
+    tags: Vec<(String, String)>,
+
+    /// Append a record of each generation, with its parameters, entropy and label but never the password, to this JSON Lines audit log
+    #[arg(long, value_name = "PATH", env = "MOTUS_AUDIT_LOG")]
+    audit_log: Option<PathBuf>,
+
+    /// Sign each audit log record, with an OpenPGP key through gpg (gpg:KEY) or the key in a PIV smartcard slot through yubico-piv-tool (piv:SLOT[:ALGORITHM]), so that tampering with the log is detected
+    #[arg(long, value_name = "SIGNER", env = "MOTUS_AUDIT_LOG_SIGN", value_parser = auditlog::Signer::parse)]
+    audit_log_sign: Option<auditlog::Signer>,
+
+    /// Path to the local usage stats file, updated only once enabled with `motus stats enable`
+    #[arg(long, value_name = "PATH", global = true)]
+    stats_file: Option<PathBuf>,
+
+    /// Path to the configuration file providing defaults for the options above
+    #[arg(long, value_name = "PATH", global = true)]
+    config: Option<PathBuf>,
+
+    /// Profile of the configuration file to use the defaults of, on top of its [default] table
+    #[arg(long, value_name = "NAME", global = true, env = "MOTUS_PROFILE")]
+    profile: Option<String>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -55,16 +385,69 @@ enum Commands {
         words: u32,
 
         /// Choose the separator for words in the generated password
-        #[arg(short, long, default_value = "space", value_enum)]
+        #[arg(
+            short,
+            long,
+            default_value = "space",
+            value_enum,
+            env = "MOTUS_SEPARATOR"
+        )]
         separator: motus::Separator,
 
+        /// Specify the number of random characters between words with the numbers and numbers-and-symbols separators
+        #[arg(long, default_value = "1", value_parser = validate_separator_count)]
+        separator_count: u32,
+
         /// Enable capitalization of each word in the generated password
         #[arg(short, long)]
         capitalize: bool,
 
+        /// Case and join the words following a naming convention, instead of --separator and --capitalize
+        #[arg(long, value_enum, conflicts_with = "capitalize")]
+        style: Option<JoinStyle>,
+
+        /// Capitalize each word but the first when words joined without a separator could otherwise be read back in several ways, losing entropy
+        #[arg(long)]
+        disambiguate: bool,
+
         /// Enable the use of unrecognizable words in the generated password
         #[arg(long)]
         no_full_words: bool,
+
+        /// Display the definition of the words found in the embedded glossary, to help remember the password
+        #[arg(long, conflicts_with = "no_full_words")]
+        hints: bool,
+
+        /// Display a silly story made of the words, as a memory aid
+        #[arg(long, conflicts_with = "no_full_words")]
+        story: bool,
+
+        /// Only use words of at most this many syllables, for passphrases easier to say out loud
+        #[arg(long, value_name = "N", value_parser = validate_max_syllables)]
+        max_syllables_per_word: Option<u32>,
+
+        /// Leave offensive or awkward words and combinations of words out of the generated password
+        #[arg(
+            long,
+            value_name = "BOOL",
+            default_value = "true",
+            default_missing_value = "true",
+            num_args = 0..=1,
+            action = clap::ArgAction::Set,
+        )]
+        family_friendly: bool,
+
+        /// Shuffle the chosen words once more, so that their order is uniformly random
+        #[arg(long)]
+        shuffle_order: bool,
+
+        /// Draw the words again until no two of them rhyme or sound alike, for them not to be mixed up when recalling the password
+        #[arg(long)]
+        phonetically_distinct: bool,
+
+        /// Append a verification word derived from the other words, for `motus verify-phrase` to detect a mistyped word when the passphrase is retyped
+        #[arg(long)]
+        checksum_word: bool,
     },
 
     #[command(name = "random")]
@@ -84,6 +467,43 @@ enum Commands {
         /// Enable the inclusion of symbols in the generated password
         #[arg(short, long)]
         symbols: bool,
+
+        /// Choose the symbols the generated password draws from; intl-safe ones are typed alike on international keyboards, such as hotel or kiosk ones
+        #[arg(long, value_name = "SET", default_value = "default", value_enum)]
+        symbols_set: SymbolSet,
+
+        /// Characters that must never appear in the generated password (e.g. quotes or backslashes)
+        #[arg(
+            long,
+            value_name = "CHARS",
+            default_value = "",
+            hide_default_value = true
+        )]
+        exclude_chars: String,
+
+        /// Choose what kind of character the generated password must start with
+        #[arg(long, default_value = "any", value_enum)]
+        start_with: StartWith,
+
+        /// Limit how many times a character may appear consecutively in the generated password
+        #[arg(long, value_name = "N", value_parser = validate_max_repeat)]
+        max_repeat: Option<u32>,
+
+        /// Follow a well-known password policy, raising the length and enabling numbers or symbols as it requires
+        #[arg(long, value_name = "PRESET", value_enum)]
+        policy_preset: Option<Preset>,
+
+        /// Follow the password rules of a known site, such as its maximum length and the symbols it accepts
+        #[arg(long, value_name = "DOMAIN", value_parser = validate_domain, conflicts_with = "policy_preset")]
+        site: Option<String>,
+
+        /// Follow password rules written in the syntax of the passwordrules HTML attribute, such as 'minlength: 8; required: upper; allowed: lower, [-_]'
+        #[arg(long, value_name = "RULES", value_parser = parse_password_rules, conflicts_with_all = ["policy_preset", "site"])]
+        password_rules: Option<SiteRules>,
+
+        /// Regenerate the password every interval, such as 2s, replacing the previous one on the screen, until Enter is pressed; watched passwords are neither copied nor recorded
+        #[arg(long, value_name = "INTERVAL", value_parser = validate_ttl)]
+        watch: Option<Duration>,
     },
 
     #[command(name = "pin")]
@@ -95,361 +515,3481 @@ enum Commands {
         /// Specify the number of digits in the generated PIN code
         #[arg(short, long, default_value = "7", value_parser = validate_pin_length)]
         numbers: u32,
+
+        /// Choose the alphabet of the code, to generate pairing codes and the like beyond decimal PINs
+        #[arg(long, default_value = "decimal", value_enum)]
+        radix: motus::Radix,
     },
-}
 
-fn main() {
-    // Enable human-readable panic messages
-    setup_panic!();
+    #[command(name = "apple")]
+    #[command(about = "Generate an Apple-style password (xxxxxx-xxxxxx-xxxxxx)")]
+    #[command(
+        long_about = "Generate a password shaped like the ones Apple's iCloud Keychain suggests: three chunks of six lowercase letters separated by hyphens, with one uppercase letter and one digit. This familiar shape is accepted by most sites' password rules."
+    )]
+    Apple,
 
-    // Parse command line arguments
-    let opts: Cli = Cli::parse();
+    #[command(name = "username")]
+    #[command(about = "Generate handle-style usernames, such as BrazenWalrus42")]
+    #[command(
+        long_about = "Generate handle-style usernames for pseudonymous accounts, joining an adjective, a noun and a random number, such as BrazenWalrus42. Words are drawn from the wordlist, leaving out the blocklisted ones. Usernames are not secrets: they are never copied to the clipboard nor recorded in the history."
+    )]
+    Username {
+        /// Specify the number of random digits ending the username, 0 for none
+        #[arg(short, long, default_value = "2", value_parser = validate_username_digits)]
+        digits: u32,
 
-    // Initialize the randomness source
-    // If a seed is provided, use it to seed the randomness source
-    // Otherwise, use the main thread's randomness source
-    let mut rng: Box<dyn RngCore> = match opts.seed {
-        Some(seed) => Box::new(StdRng::seed_from_u64(seed)),
-        None => Box::new(thread_rng()),
-    };
+        /// Case and join the words following a naming convention
+        #[arg(long, default_value = "pascal", value_enum)]
+        style: JoinStyle,
 
-    let password = match opts.command {
-        Commands::Memorable {
-            words,
-            separator,
-            capitalize,
-            no_full_words,
-        } => motus::memorable_password(
-            &mut rng,
-            words as usize,
-            separator,
-            capitalize,
-            no_full_words,
-        ),
-        Commands::Random {
-            characters,
-            numbers,
-            symbols,
-        } => motus::random_password(&mut rng, characters, numbers, symbols),
-        Commands::Pin { numbers } => motus::pin_password(&mut rng, numbers),
-    };
+        /// Specify the maximum length of the username, as sites often cap it
+        #[arg(long, value_name = "LENGTH")]
+        max_length: Option<usize>,
+    },
 
-    // Copy the password to the clipboard
-    if !opts.no_clipboard {
-        let mut clipboard =
-            Clipboard::new().expect("unable to interact with your system's clipboard");
-        clipboard
-            .set_text(&password)
-            .expect("unable to set clipboard contents");
-    }
+    #[command(name = "alias")]
+    #[command(about = "Generate email aliases for signing up without giving away your address")]
+    #[command(
+        long_about = "Generate random email aliases at a domain, to sign up to each site with its own address: plus-addressed aliases of a mailbox, such as jane+mosaic.malt.86@example.com, or aliases of domains catching all addresses, such as mosaic.malt.86@example.com. Each alias can be paired with a random password for the account. Aliases are never copied to the clipboard nor recorded in the history, and neither are their passwords."
+    )]
+    Alias {
+        /// Specify the domain of the aliases
+        #[arg(long, value_parser = validate_domain)]
+        domain: String,
 
-    match opts.output {
-        OutputFormat::Text => {
-            if opts.analyze {
-                let analysis = SecurityAnalysis::new(&password);
-                analysis.display_report(TableStyle::extended(), 80)
-            } else {
-                println!("{}", password);
-            }
-        }
-        OutputFormat::Json => {
-            let output = PasswordOutput {
-                kind: match opts.command {
-                    Commands::Memorable { .. } => PasswordKind::Memorable,
-                    Commands::Random { .. } => PasswordKind::Random,
-                    Commands::Pin { .. } => PasswordKind::Pin,
-                },
-                password: &password,
-                analysis: if opts.analyze {
-                    Some(SecurityAnalysis::new(&password))
-                } else {
-                    None
-                },
-            };
-            println!("{}", serde_json::to_string(&output).unwrap());
-        }
-    }
-}
+        /// Plus-address the given mailbox of the domain, instead of generating catch-all aliases
+        #[arg(long, value_name = "MAILBOX", value_parser = validate_mailbox)]
+        plus: Option<String>,
 
-#[derive(ValueEnum, Clone, Debug)]
-enum OutputFormat {
-    Text,
-    Json,
-}
+        /// Pair each alias with a random password of 20 characters, numbers and symbols included
+        #[arg(long)]
+        with_password: bool,
+    },
 
-#[derive(Serialize)]
-struct PasswordOutput<'a> {
-    kind: PasswordKind,
-    password: &'a str,
+    #[command(name = "identity")]
+    #[command(about = "Generate the credentials of complete accounts in one go")]
+    #[command(
+        long_about = "Generate the credentials of complete accounts in one go: a handle-style username, an email alias derived from it, a password of 20 characters, numbers and symbols included, a 6-digit PIN, and recovery codes. Identities are meant to seed test accounts, or to be imported into a password manager, and are never recorded in the history, nor copied to the clipboard unless --sequential-paste is given."
+    )]
+    Identity {
+        /// Specify the domain of the email aliases
+        #[arg(long, default_value = "example.com", value_parser = validate_domain)]
+        domain: String,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    analysis: Option<SecurityAnalysis<'a>>,
-}
+        /// Plus-address the given mailbox of the domain, instead of generating catch-all aliases
+        #[arg(long, value_name = "MAILBOX", value_parser = validate_mailbox)]
+        plus: Option<String>,
 
-#[derive(Serialize)]
-#[serde(rename_all = "lowercase")]
-enum PasswordKind {
-    Memorable,
-    Random,
-    Pin,
-}
+        /// Case and join the words of the username following a naming convention
+        #[arg(long, default_value = "pascal", value_enum)]
+        style: JoinStyle,
 
-impl Display for PasswordKind {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            PasswordKind::Memorable => write!(f, "memorable"),
-            PasswordKind::Random => write!(f, "random"),
-            PasswordKind::Pin => write!(f, "pin"),
-        }
-    }
-}
+        /// Specify the number of recovery codes of each identity
+        #[arg(long, default_value = "10", value_parser = validate_recovery_codes)]
+        recovery_codes: u32,
 
-struct SecurityAnalysis<'a> {
-    password: &'a str,
-    entropy: zxcvbn::Entropy,
-}
+        /// Copy the username to the clipboard, then the password once the username is pasted, to fill a signup form
+        #[arg(long)]
+        sequential_paste: bool,
+    },
 
-impl Serialize for SecurityAnalysis<'_> {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        let mut crack_times = HashMap::new();
-        crack_times.insert(
-            "100/h",
-            self.entropy
-                .crack_times()
-                .online_throttling_100_per_hour()
-                .to_string(),
-        );
+    #[command(name = "answers")]
+    #[command(about = "Generate random answers to security questions")]
+    #[command(
+        long_about = "Generate random, unrelated answers to security questions, such as \"What was the name of your first pet?\". Truthful answers can often be looked up or guessed, and random ones make the questions as strong as passwords; store them in your password manager, next to the account's password. No two answers share a word. Answers are never copied to the clipboard nor recorded in the history."
+    )]
+    Answers {
+        /// Specify the number of answers to generate, when no question is given
+        #[arg(long, default_value = "3", value_parser = validate_answer_count)]
+        count: u32,
 
-        crack_times.insert(
-            "10/s",
-            self.entropy
-                .crack_times()
-                .online_no_throttling_10_per_second()
-                .to_string(),
-        );
+        /// Label an answer with the question it belongs to; repeat for each question, one answer being generated per question
+        #[arg(long = "question", value_name = "QUESTION", conflicts_with = "count")]
+        questions: Vec<String>,
 
-        crack_times.insert(
-            "10^4/s",
-            self.entropy
-                .crack_times()
-                .offline_slow_hashing_1e4_per_second()
-                .to_string(),
-        );
+        /// Specify the number of words of each answer
+        #[arg(short, long, default_value = "3", value_parser = validate_word_count)]
+        words: u32,
+    },
 
-        crack_times.insert(
-            "10^10/s",
-            self.entropy
-                .crack_times()
-                .offline_fast_hashing_1e10_per_second()
-                .to_string(),
-        );
+    #[command(name = "dice")]
+    #[command(about = "Generate a password from physical dice rolls")]
+    #[command(
+        long_about = "Generate a password from the rolls of physical dice, typed in once rolled, for offline generation that does not trust any random number generator. Rolls are combined into numbers mapped onto words, or characters with --characters, rejecting the numbers that would make some words likelier than others; --explain shows every step, for the math to be checked by hand. Passwords are never copied to the clipboard nor recorded in the history."
+    )]
+    Dice {
+        /// Specify the number of rolls to type in
+        #[arg(long, default_value = "25", value_parser = clap::value_parser!(u32).range(1..=1000))]
+        rolls: u32,
 
-        let mut struct_serializer = serializer.serialize_struct("SecurityAnalysis", 3)?;
-        struct_serializer.serialize_field(
-            "strength",
-            &PasswordStrength::from(self.entropy.score()).to_string(),
-        )?;
-        struct_serializer.serialize_field(
-            "guesses",
-            format!("10^{:.0}", &self.entropy.guesses_log10()).as_str(),
-        )?;
-        struct_serializer.serialize_field("crack_times", &crack_times)?;
-        struct_serializer.end()
-    }
-}
+        /// Specify the number of sides of the dice
+        #[arg(long, default_value = "6", value_parser = clap::value_parser!(u32).range(2..=i64::from(motus::manual::MAX_SIDES)))]
+        sides: u32,
 
-impl<'a> SecurityAnalysis<'a> {
-    fn new(password: &'a str) -> Self {
-        let entropy = zxcvbn(password, &[]).expect("unable to analyze password's safety");
-        Self { password, entropy }
-    }
+        /// Map the rolls onto letters instead of words
+        #[arg(long)]
+        characters: bool,
 
-    fn display_report(&self, table_style: TableStyle, max_width: usize) {
-        self.display_password_table(table_style, max_width);
-        self.display_analysis_table(table_style, max_width);
-        self.display_crack_times_table(table_style, max_width);
-    }
+        /// With --characters, also map the rolls onto numbers
+        #[arg(short, long, requires = "characters")]
+        numbers: bool,
 
-    fn display_password_table(&self, table_style: TableStyle, max_width: usize) {
-        let mut table = Table::new();
-        table.max_column_width = max_width;
-        table.style = table_style;
+        /// With --characters, also map the rolls onto symbols
+        #[arg(short, long, requires = "characters")]
+        symbols: bool,
 
-        table.add_row(Row::new(vec![TableCell::new_with_alignment(
-            "Generated Password".bold(),
-            1,
-            Alignment::Left,
-        )]));
+        /// Prompt for the rolls one at a time, asking again for mistyped ones
+        #[arg(short, long)]
+        interactive: bool,
+    },
 
-        table.add_row(Row::new(vec![TableCell::new(self.password)]));
+    #[command(name = "coins")]
+    #[command(about = "Generate a password from physical coin flips")]
+    #[command(
+        long_about = "Generate a password from the flips of a physical coin, typed in as H for heads and T for tails, such as HTTHT, the way the dice command does from dice rolls. Each flip holds one bit of entropy. Passwords are never copied to the clipboard nor recorded in the history."
+    )]
+    Coins {
+        /// Specify the number of flips to type in
+        #[arg(long, default_value = "65", value_parser = clap::value_parser!(u32).range(1..=1000))]
+        flips: u32,
 
-        println!("{}", table.render());
-    }
+        /// Map the flips onto letters instead of words
+        #[arg(long)]
+        characters: bool,
 
-    fn display_analysis_table(&self, table_style: TableStyle, max_width: usize) {
-        let mut table = Table::new();
-        table.max_column_width = max_width;
-        table.style = table_style;
+        /// With --characters, also map the flips onto numbers
+        #[arg(short, long, requires = "characters")]
+        numbers: bool,
 
-        table.add_row(Row::new(vec![TableCell::new_with_alignment(
-            "Security Analysis",
-            2,
-            Alignment::Left,
-        )]));
+        /// With --characters, also map the flips onto symbols
+        #[arg(short, long, requires = "characters")]
+        symbols: bool,
 
-        table.add_row(Row::new(vec![
-            TableCell::new("Strength".bold()),
-            TableCell::new_with_alignment(
-                PasswordStrength::from(self.entropy.score()).to_colored_string(),
-                1,
-                Alignment::Left,
-            ),
-        ]));
-
-        table.add_row(Row::new(vec![
-            TableCell::new("Guesses".bold()),
-            TableCell::new_with_alignment(
-                format!("10^{:.0}", self.entropy.guesses_log10()),
-                1,
-                Alignment::Left,
-            ),
-        ]));
+        /// Prompt for the flips one at a time, asking again for mistyped ones
+        #[arg(short, long)]
+        interactive: bool,
+    },
 
-        println!("{}", table.render());
-    }
+    #[command(name = "deck")]
+    #[command(about = "Generate a password from a shuffled deck of cards")]
+    #[command(
+        long_about = "Generate a password from the order of a shuffled deck of 52 cards, typed in as their rank and suit, such as 10H for the ten of hearts or QS for the queen of spades, the way the dice command does from dice rolls. The order of a whole deck holds about 225.6 bits of entropy, provided the deck was thoroughly shuffled, with seven riffle shuffles or more. Passwords are never copied to the clipboard nor recorded in the history."
+    )]
+    Deck {
+        /// Specify the number of cards to type in, from the top of the deck
+        #[arg(long, default_value = "52", value_parser = clap::value_parser!(u32).range(1..=i64::from(motus::manual::DECK_SIZE)))]
+        cards: u32,
 
-    fn display_crack_times_table(&self, table_style: TableStyle, max_width: usize) {
-        let mut table = Table::new();
-        table.max_column_width = max_width;
-        table.style = table_style;
+        /// Map the cards onto letters instead of words
+        #[arg(long)]
+        characters: bool,
 
-        table.add_row(Row::new(vec![TableCell::new_with_alignment(
-            "Crack time estimations",
-            2,
-            Alignment::Left,
-        )]));
+        /// With --characters, also map the cards onto numbers
+        #[arg(short, long, requires = "characters")]
+        numbers: bool,
 
-        table.add_row(Row::new(vec![
-            TableCell::new("100 attempts/hour".bold()),
-            TableCell::new_with_alignment(
-                format!(
-                    "{}",
-                    self.entropy.crack_times().online_throttling_100_per_hour()
-                ),
-                1,
-                Alignment::Left,
-            ),
-        ]));
+        /// With --characters, also map the cards onto symbols
+        #[arg(short, long, requires = "characters")]
+        symbols: bool,
 
-        table.add_row(Row::new(vec![
-            TableCell::new("10 attempts/second".bold()),
-            TableCell::new_with_alignment(
-                format!(
-                    "{}",
-                    self.entropy
-                        .crack_times()
-                        .online_no_throttling_10_per_second()
-                ),
-                1,
-                Alignment::Left,
-            ),
-        ]));
+        /// Prompt for the cards one at a time, asking again for mistyped ones
+        #[arg(short, long)]
+        interactive: bool,
+    },
 
-        table.add_row(Row::new(vec![
-            TableCell::new("10^4 attempts/second".bold()),
-            TableCell::new_with_alignment(
-                format!(
-                    "{}",
-                    self.entropy
-                        .crack_times()
-                        .offline_slow_hashing_1e4_per_second()
-                ),
-                1,
-                Alignment::Left,
-            ),
-        ]));
+    #[command(name = "native-host")]
+    #[command(about = "Serve passwords to a browser extension as a native messaging host")]
+    #[command(
+        long_about = "Serve passwords to a companion browser extension with the Chrome and Firefox native messaging protocol, generating them with the policy of the site they are for. Site policies are policy files named after the domain they apply to, such as example.com.toml, which also applies to its subdomains; sites without one follow their rules from the site database, if it knows them. Browsers start motus by themselves once its manifest, printed with --manifest, is installed; passwords are never copied to the clipboard nor recorded in the history."
+    )]
+    NativeHost {
+        /// Read site policies from this directory, instead of the sites directory next to the configuration file
+        #[arg(long, value_name = "DIR", env = "MOTUS_SITES")]
+        sites: Option<PathBuf>,
 
-        table.add_row(Row::new(vec![
-            TableCell::new("10^10 attempts/second".bold()),
-            TableCell::new_with_alignment(
-                format!(
-                    "{}",
-                    self.entropy
-                        .crack_times()
-                        .offline_fast_hashing_1e10_per_second()
-                ),
-                1,
-                Alignment::Left,
-            ),
-        ]));
+        /// Print the manifest to install for the browser to start motus, instead of serving passwords
+        #[arg(long, value_name = "BROWSER", value_enum, requires = "extension_id")]
+        manifest: Option<native::Browser>,
 
-        println!("{}", table.render());
-    }
-}
+        /// Specify the ID of the browser extension allowed to start motus, in the manifest
+        #[arg(long, value_name = "ID", requires = "manifest")]
+        extension_id: Option<String>,
+    },
 
-enum PasswordStrength {
-    VeryWeak,
-    Weak,
-    Reasonable,
-    Strong,
-    VeryStrong,
-}
+    #[command(name = "fake")]
+    #[command(about = "Generate test data in bulk (never copied to the clipboard)")]
+    #[command(
+        long_about = "Generate large amounts of passwords for seeding test databases or training strength meters. Generated passwords are never copied to the clipboard nor analyzed."
+    )]
+    Fake {
+        /// Specify the number of passwords to generate
+        #[arg(long, default_value = "100", value_parser = validate_fake_count)]
+        count: u32,
 
-impl From<u8> for PasswordStrength {
-    fn from(score: u8) -> Self {
-        match score {
-            0 => PasswordStrength::VeryWeak,
-            1 => PasswordStrength::Weak,
-            2 => PasswordStrength::Reasonable,
-            3 => PasswordStrength::Strong,
-            4 => PasswordStrength::VeryStrong,
-            _ => panic!("invalid score"),
-        }
-    }
-}
+        /// Choose the kind of passwords to generate
+        #[arg(short, long, default_value = "random", value_enum)]
+        kind: PasswordKind,
 
-impl PasswordStrength {
-    fn to_colored_string(&self) -> ColoredString {
-        match self {
-            PasswordStrength::VeryWeak => self.to_string().red(),
-            PasswordStrength::Weak => self.to_string().bright_red(),
-            PasswordStrength::Reasonable => self.to_string().yellow(),
-            PasswordStrength::Strong => self.to_string().bright_green(),
-            PasswordStrength::VeryStrong => self.to_string().green(),
-        }
-    }
-}
+        /// Generate weak, human-style passwords from common patterns (INSECURE, test data only)
+        #[arg(long)]
+        weak: bool,
+    },
 
-impl Display for PasswordStrength {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let strength = match self {
-            PasswordStrength::VeryWeak => "very weak",
-            PasswordStrength::Weak => "weak",
-            PasswordStrength::Reasonable => "reasonable",
-            PasswordStrength::Strong => "strong",
-            PasswordStrength::VeryStrong => "very strong",
-        };
+    #[command(name = "card")]
+    #[command(about = "Generate a printable password card (never copied to the clipboard)")]
+    #[command(
+        long_about = "Generate a printable password card: a grid of random characters with lettered columns and numbered rows. Instead of remembering passwords, remember a starting cell and a path through the grid, such as \"row 3 from column F, 8 characters to the right\", and read them off the card. Keep the card as safe as the passwords read off it."
+    )]
+    Card {
+        /// Specify the number of rows of the card
+        #[arg(long, default_value = "8", value_parser = validate_card_rows)]
+        rows: u32,
 
-        write!(f, "{}", strength)
-    }
-}
+        /// Specify the number of columns of the card
+        #[arg(long, default_value = "26", value_parser = validate_card_columns)]
+        columns: u32,
 
-/// validate_word_count parses the given string as a u32 and returns an error if it is not between
-/// 3 and 15.
-fn validate_word_count(s: &str) -> Result<u32, String> {
-    match s.parse::<u32>() {
-        Ok(n) if (3..16).contains(&n) => Ok(n),
-        Ok(_) => Err("The number of words must be between 4 and 15".to_string()),
-        Err(_) => Err("The number of words must be an integer".to_string()),
-    }
-}
+        /// Enable the inclusion of numbers in the card
+        #[arg(short, long)]
+        numbers: bool,
 
-/// validate_character_count parses the given string as a u32 and returns an error if it is not between
-/// 8 and 100.
-fn validate_character_count(s: &str) -> Result<u32, String> {
+        /// Enable the inclusion of symbols in the card
+        #[arg(short, long)]
+        symbols: bool,
+
+        /// Choose the format the card is rendered in
+        #[arg(long, default_value = "text", value_enum)]
+        format: card::CardFormat,
+
+        /// Write the card to an SVG or PNG image, depending on the file extension, instead of displaying it
+        #[arg(long, value_name = "PATH", conflicts_with = "format")]
+        out: Option<PathBuf>,
+    },
+
+    #[command(name = "analyze")]
+    #[command(about = "Analyze the safety of an existing password")]
+    #[command(
+        long_about = "Analyze the safety of an existing password, read from the standard input unless provided as an argument. Note that passing the password as an argument may leave it in your shell's history."
+    )]
+    Analyze {
+        /// The password to analyze, or '-' to read it from the standard input
+        #[arg(default_value = "-", conflicts_with = "file")]
+        password: String,
+
+        /// Analyze every password in the given file, one per line, and print a summary report
+        #[arg(long, value_name = "PATH")]
+        file: Option<PathBuf>,
+
+        /// Read passwords from the given CSV column, by name or 1-based index, instead of one per line
+        #[arg(long, value_name = "NAME|INDEX", requires = "file")]
+        column: Option<String>,
+
+        /// Also check the password against a TOML policy file, exiting with a non-zero status when it does not comply
+        #[arg(long, value_name = "PATH", conflicts_with = "file")]
+        policy: Option<PathBuf>,
+    },
+
+    #[command(name = "verify-phrase")]
+    #[command(about = "Check a passphrase against its verification word")]
+    #[command(
+        long_about = "Check that a passphrase generated with --checksum-word ends with the verification word of its other words, read from the standard input unless provided as an argument, exiting with a non-zero status when it does not: one of its words is then likely mistyped. Only the letters of the passphrase matter, so it can be typed with any separators and capitalization. Note that passing the passphrase as an argument may leave it in your shell's history."
+    )]
+    VerifyPhrase {
+        /// The passphrase to verify, or '-' to read it from the standard input
+        #[arg(default_value = "-")]
+        phrase: String,
+    },
+
+    #[command(name = "t9")]
+    #[command(about = "Convert a word or password to telephone keypad digits")]
+    #[command(
+        long_about = "Convert a word or password, read from the standard input unless provided as an argument, to the digits of the telephone keypad keys its letters are on, such as 86423624 for voicemail, to set it as the PIN of a voicemail or phone system only accepting digits. Digits, '*' and '#' are kept, and words are separated by a space. Several letters share a key, so that the digits hold less entropy than the letters. Note that passing the password as an argument may leave it in your shell's history."
+    )]
+    T9 {
+        /// The word or password to convert, or '-' to read it from the standard input
+        #[arg(default_value = "-")]
+        text: String,
+    },
+
+    #[command(name = "recombine")]
+    #[command(about = "Recombine a password split with --split")]
+    #[command(
+        long_about = "Recombine a password displayed in parts with --split: prompt for each part in order, join them, and print the password."
+    )]
+    Recombine {
+        /// Specify the number of parts the password was split into
+        #[arg(long, default_value = "2", value_parser = clap::value_parser!(u32).range(2..=4))]
+        parts: u32,
+
+        /// Separator the parts are joined with, as given in the recombination instructions
+        #[arg(long, value_name = "TEXT", default_value = "")]
+        separator: String,
+    },
+
+    #[command(name = "compare")]
+    #[command(about = "Compare the entropy of password formats for a given length")]
+    #[command(
+        long_about = "Compare the entropy achievable within a budget of typed characters by each kind of password motus generates: random characters, memorable words, PIN codes and Apple-style patterns. Each strategy comes with the command generating it, to help pick a format when a site or device limits the length of passwords."
+    )]
+    Compare {
+        /// Specify the largest number of characters passwords may have
+        #[arg(short, long, default_value = "20", value_parser = validate_length_budget)]
+        length_budget: u32,
+    },
+
+    #[command(name = "info")]
+    #[command(about = "Print build information, for bug reports")]
+    #[command(
+        long_about = "Print how motus was built: version, git commit, build profile and target, random number generator, and a fingerprint of the embedded wordlist. Use --output json for a machine-readable report."
+    )]
+    Info,
+
+    #[command(name = "policy")]
+    #[command(about = "Work with password policy files")]
+    Policy {
+        #[command(subcommand)]
+        command: PolicyCommands,
+    },
+
+    #[command(name = "rules")]
+    #[command(about = "Work with passwordrules attributes")]
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommands,
+    },
+
+    #[command(name = "schema")]
+    #[command(about = "Print the schemas of the JSON output")]
+    #[command(
+        long_about = "Print the schemas of the payloads printed with --output json: generated passwords, batches, security analyses and errors. Use them to validate motus output or to generate typed clients for it."
+    )]
+    Schema {
+        /// Choose the format the schemas are printed in
+        #[arg(long, default_value = "json-schema", value_enum)]
+        format: schema::SchemaFormat,
+    },
+
+    #[command(name = "selftest")]
+    #[command(about = "Check the random number generator with statistical tests")]
+    #[command(
+        long_about = "Run quick statistical tests of the random number generator on this platform: a chi-square test of the distribution of generated characters, their serial correlation, and the monobit and runs tests of NIST SP 800-22 over its raw output. Exits with a non-zero status if any test fails. Passing them does not prove the generator secure, but a broken one will fail them."
+    )]
+    Selftest {
+        /// Specify the number of characters, and of bits, to test
+        #[arg(long, default_value = "2000000", value_parser = clap::value_parser!(u32).range(1000..))]
+        samples: u32,
+    },
+
+    #[command(name = "share")]
+    #[command(about = "Share a secret with a teammate through a one-time link")]
+    #[command(
+        long_about = "Serve a secret, read from the standard input, at a link holding a random retrieval token, until the link expires or, with --once, until it is first retrieved. The secret is only served on this machine unless a TLS certificate is provided, to share it with teammates on the local network without going through chat apps."
+    )]
+    Share {
+        /// Stop serving the secret once it has been retrieved
+        #[arg(long)]
+        once: bool,
+
+        /// How long the link remains valid, in seconds unless suffixed with s, m or h
+        #[arg(long, value_name = "DURATION", default_value = "5m", value_parser = validate_ttl)]
+        ttl: Duration,
+
+        /// Address and port to serve the secret on; addresses other than loopback ones require --tls-cert and --tls-key
+        #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1:0")]
+        bind: SocketAddr,
+
+        /// Path to the PEM certificate chain to serve the secret over HTTPS with
+        #[arg(long, value_name = "PATH", requires = "tls_key")]
+        tls_cert: Option<PathBuf>,
+
+        /// Path to the PEM private key of the certificate
+        #[arg(long, value_name = "PATH", requires = "tls_cert")]
+        tls_key: Option<PathBuf>,
+    },
+
+    #[command(name = "wrap")]
+    #[command(about = "Encrypt a secret into an envelope, to send it through untrusted channels")]
+    #[command(
+        long_about = "Encrypt a secret, read from the standard input, into an envelope sealed with a passphrase, using XChaCha20-Poly1305 and a key derived with Argon2id. The envelope is printed as text, to paste in a chat or an email, unless written to a file with --out. Share the passphrase through another channel than the envelope."
+    )]
+    Wrap {
+        /// Write the envelope to this file, in binary form, instead of printing it as text
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+
+    #[command(name = "unwrap")]
+    #[command(about = "Decrypt a secret from an envelope created with motus wrap")]
+    #[command(
+        long_about = "Decrypt the secret sealed in an envelope created with motus wrap, read from a file, in binary or text form, or from the standard input. Fails if the passphrase is wrong or the envelope was tampered with."
+    )]
+    Unwrap {
+        /// The envelope file to open, or '-' to read it from the standard input
+        #[arg(default_value = "-")]
+        input: PathBuf,
+    },
+
+    #[command(name = "askpass")]
+    #[command(about = "Answer the prompts of ssh and git, as their askpass helper")]
+    #[command(
+        long_about = "Act as the helper SSH_ASKPASS and GIT_ASKPASS point to: answer the prompt given as argument on the standard output, by prompting on the terminal, or with the password stored in the encrypted history under --from-history. Confirmations requested by ssh-agent are answered through the exit status."
+    )]
+    Askpass {
+        /// The prompt to answer, as given by ssh or git
+        #[arg(default_value = "Password: ")]
+        prompt: String,
+
+        /// Answer password prompts with the latest password stored in the history under this label, instead of prompting
+        #[arg(long, value_name = "LABEL", env = "MOTUS_ASKPASS_LABEL")]
+        from_history: Option<String>,
+    },
+
+    #[command(name = "external")]
+    #[command(about = "Derive passwords for Terraform and OpenTofu, as an external data source")]
+    #[command(
+        long_about = "Implement the protocol of Terraform's and OpenTofu's external data source: read a query as a JSON object of strings on the standard input, and write the password it asks for as one on the standard output. Passwords are derived from the secret key in MOTUS_EXTERNAL_KEY and the query's label, so that every plan reads the same ones."
+    )]
+    External,
+
+    #[command(name = "stats")]
+    #[command(about = "Show how many passwords of each kind you generated")]
+    #[command(
+        long_about = "Show the local usage stats: how many passwords of each kind were generated since they were enabled. Recording them is opt-in, and they are never sent anywhere nor include the passwords themselves."
+    )]
+    Stats {
+        #[command(subcommand)]
+        command: Option<StatsCommands>,
+    },
+
+    #[command(name = "store")]
+    #[command(about = "Back up or migrate the encrypted history")]
+    #[command(
+        long_about = "Export the entries of the encrypted history, or of the store given with --store, sealed with a new export passphrase, and import such an export into another history, on this machine or another one, under that history's own passphrase."
+    )]
+    Store {
+        #[command(subcommand)]
+        command: StoreCommands,
+    },
+
+    #[command(name = "audit-log")]
+    #[command(about = "Verify the signatures of an audit log")]
+    #[command(
+        long_about = "Verify the signature of every record of an audit log written with --audit-log-sign, with gpg for OpenPGP signatures, and with openssl and the slot's public key for PIV ones. Exits with a non-zero status if any record is unsigned or its signature is invalid."
+    )]
+    AuditLog {
+        #[command(subcommand)]
+        command: AuditLogCommands,
+    },
+
+    #[command(name = "wordlist")]
+    #[command(about = "Build and check wordlists for memorable passwords")]
+    #[command(
+        long_about = "Build a custom wordlist out of a frequency list, with the same pipeline and guarantees as the embedded one: normalized and deduplicated words, free of offensive ones, none of which is the prefix of another. Or check that an existing wordlist has them."
+    )]
+    Wordlist {
+        #[command(subcommand)]
+        command: WordlistCommands,
+    },
+
+    #[command(name = "agent")]
+    #[command(about = "Keep unlocked history keys in memory, to stop prompting for passphrases")]
+    #[command(
+        long_about = "Start an agent holding the keys of the histories and stores unlocked in the session in memory, for a limited time, and handing them out on a Unix socket. Like ssh-agent, it prints the shell commands pointing MOTUS_AGENT_SOCK to its socket, to evaluate: eval \"$(motus agent)\". Only derived keys are kept, never passphrases."
+    )]
+    Agent {
+        /// How long each key is kept after being unlocked, as a number of seconds optionally followed by s, m or h
+        #[arg(long, default_value = "15m", value_parser = validate_ttl)]
+        timeout: Duration,
+
+        /// Path of the socket to listen on, instead of a new one in the runtime directory
+        #[arg(long, value_name = "PATH")]
+        socket: Option<PathBuf>,
+
+        /// Run in the foreground, instead of in the background
+        #[arg(long)]
+        foreground: bool,
+
+        /// Stop the agent MOTUS_AGENT_SOCK points to, forgetting every key it holds
+        #[arg(long, conflicts_with_all = ["timeout", "socket", "foreground"])]
+        kill: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum AuditLogCommands {
+    #[command(name = "verify")]
+    #[command(about = "Verify the signature of every record of an audit log")]
+    Verify {
+        /// Path to the audit log, instead of the one given with --audit-log
+        path: Option<PathBuf>,
+
+        /// Path to the PEM public key of the PIV slot the records were signed with
+        #[arg(long, value_name = "PATH")]
+        piv_public_key: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum WordlistCommands {
+    #[command(name = "build")]
+    #[command(about = "Build a wordlist out of a frequency list")]
+    #[command(
+        long_about = "Build a wordlist out of a frequency list, holding one word per line, from the most to the least frequent, or followed by its number of occurrences. The most frequent words made of ASCII letters only, of the requested length, and not on the blocklist are kept, skipping those that are, or start with, a word kept before them. The same frequency list and options always build the same wordlist."
+    )]
+    Build {
+        /// Path to the frequency list
+        #[arg(long, value_name = "PATH")]
+        from: PathBuf,
+
+        /// Number of words of the wordlist
+        #[arg(long, default_value_t = 7776)]
+        size: usize,
+
+        /// Minimum length of the words
+        #[arg(long, default_value_t = 4)]
+        min_len: usize,
+
+        /// Maximum length of the words
+        #[arg(long, default_value_t = 9)]
+        max_len: usize,
+
+        /// Write the wordlist to this file instead of the standard output
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+
+    #[command(name = "lint")]
+    #[command(about = "Check a wordlist for duplicates, non-ASCII words and prefixes")]
+    #[command(
+        long_about = "Check a wordlist, one word per line, or the embedded one when no path is given: report duplicates, words with non-ASCII characters, words that are a prefix of another, which make passwords joined without a separator ambiguous, and the distribution of word lengths. Exits with a non-zero status if any problem is found."
+    )]
+    Lint {
+        /// Path to the wordlist, instead of the embedded one
+        path: Option<PathBuf>,
+
+        /// Accept words that are a prefix of another, for lists never joined without a separator
+        #[arg(long)]
+        allow_prefixes: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum StoreCommands {
+    #[command(name = "export")]
+    #[command(about = "Export the entries of the history, sealed with an export passphrase")]
+    Export {
+        /// Format of the export
+        #[arg(long, value_enum, default_value_t = backup::ExportFormat::EncryptedJson)]
+        format: backup::ExportFormat,
+
+        /// Write the export to this file instead of the standard output
+        #[arg(long, value_name = "PATH")]
+        out: Option<PathBuf>,
+    },
+
+    #[command(name = "import")]
+    #[command(about = "Import the entries of an export into the history")]
+    Import {
+        /// Path to the export, or '-' to read it from the standard input
+        #[arg(default_value = "-")]
+        input: PathBuf,
+    },
+
+    #[command(name = "protect")]
+    #[command(about = "Require a FIDO2 security key, such as a YubiKey, to unlock the history")]
+    #[command(
+        long_about = "Create a credential with the hmac-secret extension on the FIDO2 security key plugged in, and re-encrypt the history with a key derived from both the passphrase and the secret the security key computes, so that unlocking it requires a tap. Requires motus to be built with the fido feature."
+    )]
+    Protect {
+        /// Stop requiring the security key, re-encrypting the history with the passphrase alone
+        #[arg(long)]
+        disable: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum StatsCommands {
+    #[command(name = "enable")]
+    #[command(about = "Start recording usage stats in a local file")]
+    Enable,
+
+    #[command(name = "disable")]
+    #[command(about = "Stop recording usage stats, deleting the ones recorded so far")]
+    Disable,
+}
+
+#[derive(Debug, Subcommand)]
+enum PolicyCommands {
+    #[command(name = "check")]
+    #[command(about = "Check an existing password against a policy file")]
+    #[command(
+        long_about = "Check an existing password against a policy file, reporting which rules pass and which fail. Exits with a non-zero status when the password does not comply."
+    )]
+    Check {
+        /// Path to the TOML policy file
+        #[arg(long, value_name = "PATH")]
+        policy: PathBuf,
+
+        /// The password to check, or '-' to read it from the standard input
+        #[arg(long, default_value = "-")]
+        password: String,
+    },
+
+    #[command(name = "schema")]
+    #[command(about = "Print the JSON Schema of policy files")]
+    #[command(
+        long_about = "Print the JSON Schema describing the policy file format, for editors and CI pipelines to validate policy files with."
+    )]
+    Schema,
+}
+
+#[derive(Debug, Subcommand)]
+enum RulesCommands {
+    #[command(name = "parse")]
+    #[command(about = "Show the policy a passwordrules attribute converts to")]
+    #[command(
+        long_about = "Parse password rules written in the syntax of the passwordrules HTML attribute, proposed by WHATWG and used by Apple's password rules, and show the policy and the characters random passwords following them are generated with, as random --password-rules does. Rules requiring one of several classes of characters are enforced by requiring each of them."
+    )]
+    Parse {
+        /// The rules, such as 'minlength: 8; required: upper; allowed: [-().&@?ate]'
+        #[arg(value_parser = parse_password_rules)]
+        rules: SiteRules,
+    },
+}
+
+fn main() {
+    // Enable human-readable panic messages
+    setup_panic!();
+
+    // Parse command line arguments, falling back to the environment and then to the
+    // configuration file for the options that are not given
+    let matches =
+        Cli::command().get_matches_from(native::launch_args(std::env::args_os().collect()));
+    let mut opts = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
+    match config::load(opts.config.as_deref(), opts.profile.as_deref()) {
+        Ok(defaults) => apply_config(&mut opts, &matches, defaults),
+        Err(err) => {
+            fail(&opts.output, err);
+        }
+    }
+    require_features(&opts);
+    if !opts.no_harden && handles_secrets(&opts.command) {
+        if let Err(err) = harden::harden() {
+            eprintln!(
+                "warning: unable to disable core dumps and debugging: {}",
+                err
+            );
+        }
+    }
+    if let Some(store) = &opts.store {
+        opts.history_file = Some(history::store_path(store));
+    }
+    if matches!(opts.output, OutputFormat::SystemdCred | OutputFormat::Sops) {
+        check_sealed_output(&opts);
+    }
+    if matches!(opts.output, OutputFormat::Html) || opts.report_file.is_some() {
+        check_report_output(&opts);
+    }
+    if opts.min_entropy_bits.is_some() {
+        check_entropy_floor(&opts);
+    }
+    if opts.idempotent && opts.count > 1 {
+        fail(
+            &opts.output,
+            "--idempotent can only be used when generating a single password",
+        );
+    }
+    if opts.clipboard_content == ClipboardContent::Json
+        && !matches!(opts.output, OutputFormat::Json)
+    {
+        fail(
+            &opts.output,
+            "--clipboard-content json can only be used with --output json",
+        );
+    }
+
+    if opts.self_check {
+        match selfcheck::run() {
+            Ok(count) if matches!(opts.output, OutputFormat::Text) => {
+                eprintln!("note: self-check passed ({} known-answer tests)", count)
+            }
+            Ok(_) => {}
+            Err(err) => fail(&opts.output, format!("self-check failed: {}", err)),
+        }
+    }
+
+    // Initialize the randomness source
+    // If a seed is provided, or input to hash into one, use it to seed the randomness source
+    // Otherwise, use a generator reseeded from the operating system as it is used, and after a
+    // fork, which long-running commands such as share may be
+    let mut rng: Box<dyn RngCore> = match (opts.seed, input_seed(&opts)) {
+        (Some(seed), _) => Box::new(StdRng::seed_from_u64(seed)),
+        (None, Some(seed)) => Box::new(StdRng::from_seed(seed)),
+        (None, None) => Box::new(motus::rng::SecureReseeding::new()),
+    };
+
+    #[cfg(feature = "analysis")]
+    let analyzer = analyzer(&opts);
+
+    let count = opts.count as usize;
+    let floor = EntropyFloor {
+        bits: opts.min_entropy_bits,
+        auto_strengthen: opts.auto_strengthen,
+        output: opts.output,
+    };
+    let mut hints = false;
+    let mut story = false;
+    let mut split_separator = None;
+    let (kind, mut passwords, mut entropy, parameters): (
+        PasswordKind,
+        Vec<String>,
+        Entropy,
+        serde_json::Value,
+    ) = match opts.command {
+        Commands::Memorable {
+            words,
+            separator,
+            separator_count,
+            capitalize,
+            style,
+            disambiguate,
+            no_full_words,
+            hints: show_hints,
+            story: show_story,
+            max_syllables_per_word,
+            family_friendly,
+            shuffle_order,
+            phonetically_distinct,
+            checksum_word,
+        } => {
+            hints = show_hints;
+            story = show_story;
+
+            let mut filter = WordFilter::new()
+                .with_family_friendly(family_friendly)
+                .with_shuffled_order(shuffle_order)
+                .with_phonetically_distinct(phonetically_distinct);
+            if let Some(max) = max_syllables_per_word {
+                filter = filter.with_max_syllables(max as usize);
+            }
+            let available = filter.words().len();
+            let mut style = style.unwrap_or(JoinStyle::Separated {
+                separator,
+                capitalize,
+            });
+            let words = floor.enforce("--words", words, 15, |words| {
+                filter.memorable_entropy(
+                    words as usize,
+                    style.separator(),
+                    separator_count as usize,
+                    no_full_words,
+                )
+            });
+            if available < words as usize {
+                fail(
+                    &opts.output,
+                    format!(
+                        "only {} words are left once filtered, fewer than the {} requested",
+                        available, words
+                    ),
+                );
+            }
+
+            // Words joined without a separator may be read back in several ways, which the
+            // capitalization of each word but the first rules out, unless --case undoes it
+            let ambiguous = filter.joins_ambiguously(
+                style.separator(),
+                style.capitalizes(1) && opts.case == Case::Mixed,
+                no_full_words,
+            );
+            if ambiguous && disambiguate && opts.case != Case::Mixed {
+                fail(
+                    &opts.output,
+                    "--disambiguate capitalizes words, which --case would undo; join the words with a separator instead",
+                );
+            } else if ambiguous && disambiguate {
+                eprintln!("note: capitalizing each word but the first, for the words joined without a separator to be read back one way only");
+                style = JoinStyle::Camel;
+            } else if ambiguous {
+                eprintln!("warning: the words joined without a separator may be read back in several ways, so the password holds less entropy than reported; use --style camel or --disambiguate");
+            }
+            split_separator = match style.separator() {
+                motus::Separator::Space => Some(" "),
+                motus::Separator::Comma => Some(","),
+                motus::Separator::Hyphen => Some("-"),
+                motus::Separator::Period => Some("."),
+                motus::Separator::Underscore => Some("_"),
+                _ => None,
+            };
+            let mut entropy = filter.memorable_entropy(
+                words as usize,
+                style.separator(),
+                separator_count as usize,
+                no_full_words,
+            );
+            if ambiguous && !disambiguate {
+                entropy = entropy.with_ambiguity();
+            }
+
+            (
+                PasswordKind::Memorable,
+                (0..count)
+                    .map(|_| {
+                        avoid_denied(&opts, || {
+                            let password = filter.memorable_password_with_style(
+                                &mut rng,
+                                words as usize,
+                                style,
+                                separator_count as usize,
+                                no_full_words,
+                            );
+                            if checksum_word {
+                                motus::checksum::with_checksum_word(
+                                    &mut rng,
+                                    &password,
+                                    style,
+                                    words as usize,
+                                    separator_count as usize,
+                                )
+                            } else {
+                                password
+                            }
+                        })
+                    })
+                    .collect(),
+                entropy,
+                json!({
+                    "words": words,
+                    "separator": value_name(style.separator()),
+                    "separator_count": separator_count,
+                    "capitalize": capitalize,
+                    "style": value_name(style),
+                    "no_full_words": no_full_words,
+                    "max_syllables_per_word": max_syllables_per_word,
+                    "family_friendly": family_friendly,
+                    "shuffle_order": shuffle_order,
+                    "phonetically_distinct": phonetically_distinct,
+                    "checksum_word": checksum_word,
+                    "case": value_name(opts.case),
+                }),
+            )
+        }
+        Commands::Random {
+            characters,
+            numbers,
+            symbols,
+            symbols_set,
+            ref exclude_chars,
+            start_with,
+            max_repeat,
+            policy_preset,
+            ref site,
+            ref password_rules,
+            watch,
+        } => {
+            let mut excluded: Vec<char> = exclude_chars.chars().collect();
+            if ('a'..='z')
+                .chain('A'..='Z')
+                .filter(|&c| opts.case.allows(c))
+                .all(|c| excluded.contains(&c))
+            {
+                fail(&opts.output, "--exclude-chars cannot exclude every letter");
+            }
+            // Numbers and symbols explicitly asked for must not silently go missing either
+            let excludes_all = |set: Charset| set.chars().iter().all(|c| excluded.contains(c));
+            if numbers && excludes_all(Charset::numbers()) {
+                fail(
+                    &opts.output,
+                    "--exclude-chars cannot exclude every digit when --numbers is given",
+                );
+            }
+            if symbols && excludes_all(symbols_set.charset()) {
+                fail(
+                    &opts.output,
+                    "--exclude-chars cannot exclude every symbol when --symbols is given",
+                );
+            }
+
+            let sites = opts.sites_db.as_ref().unwrap_or(Database::embedded());
+            let site_rules = site.as_deref().and_then(|domain| {
+                let found = sites.lookup(domain);
+                if found.is_none() {
+                    eprintln!("note: no password rules are known for {domain}; use --sites-db to provide them");
+                }
+                found
+            });
+            let rules = site_rules
+                .map(|(_, rules)| rules)
+                .or(password_rules.as_ref());
+            let (numbers, symbols) = match rules {
+                Some(rules) => {
+                    if !('a'..='z').chain('A'..='Z').any(|c| rules.allows(c)) {
+                        fail(&opts.output, "the password rules accept no letter");
+                    }
+                    excluded.extend(rules.excluded());
+                    if let Some(required) = rules.unsatisfiable_requirement(&excluded) {
+                        fail(
+                            &opts.output,
+                            format!(
+                                "the excluded characters leave none to comply with the password rule '{}'",
+                                required
+                            ),
+                        );
+                    }
+                    let (_, numbers, symbols) = rules.fit(characters, numbers, symbols);
+                    (numbers, symbols)
+                }
+                None => (numbers, symbols),
+            };
+
+            let mut policy = policy_preset
+                .map(Preset::policy)
+                .or_else(|| rules.map(SiteRules::policy))
+                .unwrap_or_default()
+                .with_symbol_set(symbols_set)
+                .with_rule(Rule::StartWith(start_with));
+            if let Some(max) = max_repeat {
+                policy = policy.with_rule(Rule::MaxRepeat(max as usize));
+            }
+            if opts.print_safe {
+                policy = policy.with_rule(Rule::PrintSafe);
+            }
+            if opts.case != Case::Mixed {
+                policy = policy.with_rule(Rule::Case(opts.case));
+                if policy.is_contradictory() {
+                    fail(
+                        &opts.output,
+                        "--case rules out the letters the password rules require",
+                    );
+                }
+            }
+            let (characters, numbers, symbols) = policy.fit(characters, numbers, symbols);
+            let (max_characters, _, _) = policy.fit(100, numbers, symbols);
+            let characters = floor.enforce("--characters", characters, max_characters, |n| {
+                policy.random_entropy(n, numbers, symbols, &excluded)
+            });
+            if let Some(rule) = policy.unsatisfiable_rule(characters, numbers, symbols, &excluded) {
+                fail(
+                    &opts.output,
+                    format!(
+                        "the excluded characters leave too few to comply with the rule: {}",
+                        rule
+                    ),
+                );
+            }
+
+            if let Some(interval) = watch {
+                if opts.count > 1 {
+                    fail(&opts.output, "--watch shows a single password at a time");
+                }
+                if !matches!(opts.output, OutputFormat::Text) {
+                    fail(&opts.output, "--watch only applies to the text output");
+                }
+                ephemeral::watch(interval, io::stdout().is_terminal(), || {
+                    Secret::new(avoid_denied(&opts, || {
+                        policy.random_password(&mut rng, characters, numbers, symbols, &excluded)
+                    }))
+                })
+                .expect("unable to write to the terminal");
+                return;
+            }
+
+            (
+                PasswordKind::Random,
+                (0..count)
+                    .map(|_| {
+                        avoid_denied(&opts, || {
+                            policy
+                                .random_password(&mut rng, characters, numbers, symbols, &excluded)
+                        })
+                    })
+                    .collect(),
+                policy.random_entropy(characters, numbers, symbols, &excluded),
+                json!({
+                    "characters": characters,
+                    "numbers": numbers,
+                    "symbols": symbols,
+                    "symbols_set": value_name(symbols_set),
+                    "exclude_chars": exclude_chars,
+                    "start_with": value_name(start_with),
+                    "max_repeat": max_repeat,
+                    "policy_preset": policy_preset.and_then(value_name),
+                    "site": site_rules.map(|(domain, _)| domain),
+                    "print_safe": opts.print_safe,
+                    "case": value_name(opts.case),
+                }),
+            )
+        }
+        Commands::Pin { numbers, radix } => {
+            let alphabet = radix.alphabet();
+            let numbers = floor.enforce("--numbers", numbers, 12, |numbers| {
+                motus::entropy::code(numbers, alphabet)
+            });
+
+            (
+                PasswordKind::Pin,
+                (0..count)
+                    .map(|_| {
+                        avoid_denied(&opts, || motus::code_password(&mut rng, numbers, alphabet))
+                    })
+                    .collect(),
+                motus::entropy::code(numbers, alphabet),
+                json!({"numbers": numbers, "radix": value_name(radix)}),
+            )
+        }
+        Commands::Apple => {
+            if opts.case != Case::Mixed {
+                fail(
+                    &opts.output,
+                    "Apple-style passwords hold an uppercase letter among lowercase ones, and cannot be converted with --case",
+                );
+            }
+
+            floor.check("Apple-style passwords", &motus::entropy::apple());
+
+            (
+                PasswordKind::Apple,
+                (0..count)
+                    .map(|_| avoid_denied(&opts, || motus::apple_password(&mut rng)))
+                    .collect(),
+                motus::entropy::apple(),
+                json!({}),
+            )
+        }
+        Commands::Username {
+            digits,
+            style,
+            max_length,
+        } => {
+            let generator = Usernames::new(style, digits, max_length)
+                .unwrap_or_else(|err| fail(&opts.output, err));
+            let usernames: Vec<String> = (0..count)
+                .map(|_| avoid_denied(&opts, || generator.generate(&mut rng)))
+                .collect();
+            username::display(&usernames, &generator.entropy(), &opts.output)
+                .expect("unable to write generated usernames");
+            return;
+        }
+        Commands::Alias {
+            ref domain,
+            ref plus,
+            with_password,
+        } => {
+            let tags = Usernames::new(
+                JoinStyle::Separated {
+                    separator: motus::Separator::Period,
+                    capitalize: false,
+                },
+                2,
+                None,
+            )
+            .expect("tags without a maximum length should fit");
+            let aliases: Vec<Alias> = (0..count)
+                .map(|_| {
+                    let tag = avoid_denied(&opts, || tags.generate(&mut rng));
+                    let alias = Alias::new(&tag, domain, plus.as_deref());
+                    if with_password {
+                        alias.with_password(avoid_denied(&opts, || {
+                            motus::random_password(&mut rng, 20, true, true, &[])
+                        }))
+                    } else {
+                        alias
+                    }
+                })
+                .collect();
+            alias::display(&aliases, &opts.output).expect("unable to write generated aliases");
+            return;
+        }
+        Commands::Identity {
+            ref domain,
+            ref plus,
+            style,
+            recovery_codes,
+            sequential_paste,
+        } => {
+            check_password_display(&opts, "identity");
+            if sequential_paste && (count > 1 || opts.no_clipboard) {
+                fail(
+                    &opts.output,
+                    "--sequential-paste can only be used when generating a single identity, with the clipboard enabled",
+                );
+            }
+
+            // Every secret of an identity has a fixed length: the weakest, the PIN, is checked
+            // first for the error to name it
+            floor.check("identity PINs", &motus::entropy::pin(identity::PIN_LENGTH));
+            floor.check(
+                "identity passwords",
+                &motus::entropy::random(20, true, true, &[]),
+            );
+            if recovery_codes > 0 {
+                floor.check(
+                    "recovery codes",
+                    &motus::entropy::code(
+                        identity::RECOVERY_CODE_LENGTH,
+                        motus::Radix::Base32.alphabet(),
+                    ),
+                );
+            }
+
+            let usernames = Usernames::new(style, 2, None)
+                .expect("usernames without a maximum length should fit");
+            let identities: Vec<Identity> = (0..count)
+                .map(|_| {
+                    let username = avoid_denied(&opts, || usernames.generate(&mut rng));
+                    let email =
+                        Alias::new(&username.to_lowercase(), domain, plus.as_deref()).address;
+                    let password = avoid_denied(&opts, || {
+                        motus::random_password(&mut rng, 20, true, true, &[])
+                    });
+                    let pin = avoid_denied(&opts, || {
+                        motus::pin_password(&mut rng, identity::PIN_LENGTH)
+                    });
+                    let recovery_codes = (0..recovery_codes)
+                        .map(|_| {
+                            let code = motus::code_password(
+                                &mut rng,
+                                identity::RECOVERY_CODE_LENGTH,
+                                motus::Radix::Base32.alphabet(),
+                            );
+                            Secret::new(group(&code, Some(5), "-"))
+                        })
+                        .collect();
+
+                    Identity {
+                        username,
+                        email,
+                        password: Secret::new(password),
+                        pin: Secret::new(pin),
+                        recovery_codes,
+                    }
+                })
+                .collect();
+            identity::display(&identities, &opts.output)
+                .expect("unable to write generated identities");
+            if let [identity] = identities.as_slice() {
+                if sequential_paste {
+                    let fields = [
+                        ("username", identity.username.as_str()),
+                        ("password", identity.password.expose()),
+                    ];
+                    if let Err(err) = clipboard::copy_in_turn(&fields, &copy_options(&opts)) {
+                        fail(
+                            &opts.output,
+                            format!("unable to copy to the clipboard: {err}"),
+                        );
+                    }
+                }
+            }
+            return;
+        }
+        Commands::Answers {
+            count,
+            ref questions,
+            words,
+        } => {
+            check_password_display(&opts, "answers");
+            let questions = if questions.is_empty() {
+                (1..=count)
+                    .map(|index| format!("question {index}"))
+                    .collect()
+            } else {
+                questions.clone()
+            };
+            let entropy = |words: u32| {
+                motus::entropy::memorable(words as usize, motus::Separator::Space, 1, false)
+            };
+            let words = floor.enforce("--words", words, 15, entropy);
+            let mut answers: Vec<Answer> = Vec::with_capacity(questions.len());
+            for question in questions {
+                let answer = avoid_denied(&opts, || {
+                    answers::answer(&mut rng, words as usize, &answers)
+                });
+                answers.push(Answer {
+                    question,
+                    answer: Secret::new(answer),
+                });
+            }
+            answers::display(&answers, entropy(words).bits(), &opts.output)
+                .expect("unable to write generated answers");
+            return;
+        }
+        Commands::Dice {
+            rolls,
+            sides,
+            characters,
+            numbers,
+            symbols,
+            interactive,
+        } => {
+            let source = motus::manual::Source::Dice { sides };
+            let alphabet = characters.then(|| manual_alphabet(numbers, symbols));
+            manual_password(source, rolls, alphabet, interactive, &opts);
+            return;
+        }
+        Commands::Coins {
+            flips,
+            characters,
+            numbers,
+            symbols,
+            interactive,
+        } => {
+            let source = motus::manual::Source::Coins;
+            let alphabet = characters.then(|| manual_alphabet(numbers, symbols));
+            manual_password(source, flips, alphabet, interactive, &opts);
+            return;
+        }
+        Commands::Deck {
+            cards,
+            characters,
+            numbers,
+            symbols,
+            interactive,
+        } => {
+            let source = motus::manual::Source::Deck;
+            let alphabet = characters.then(|| manual_alphabet(numbers, symbols));
+            manual_password(source, cards, alphabet, interactive, &opts);
+            return;
+        }
+        Commands::NativeHost {
+            ref sites,
+            manifest,
+            ref extension_id,
+        } => {
+            if let Some(browser) = manifest {
+                let path = std::env::current_exe().unwrap_or_else(|err| fail(&opts.output, err));
+                let extension_id = extension_id
+                    .as_deref()
+                    .expect("--manifest should require --extension-id");
+                let manifest = native::manifest(browser, extension_id, &path);
+                println!("{}", serde_json::to_string_pretty(&manifest).unwrap());
+                return;
+            }
+
+            let sites = sites.clone().unwrap_or_else(native::default_sites_dir);
+            let mut host = NativeHost::new(
+                sites,
+                opts.sites_db.as_ref().unwrap_or(Database::embedded()),
+                opts.deny_list.as_ref(),
+                &mut *rng,
+            );
+            if let Err(err) = host.serve(io::stdin().lock(), io::stdout().lock()) {
+                fail(&opts.output, err);
+            }
+            return;
+        }
+        Commands::Fake { count, kind, weak } => {
+            fake::run(&mut rng, count, kind, weak, &opts.output)
+                .expect("unable to write generated passwords");
+            return;
+        }
+        Commands::Card {
+            rows,
+            columns,
+            numbers,
+            symbols,
+            format,
+            out,
+        } => {
+            let policy = if opts.print_safe {
+                Policy::new().with_rule(Rule::PrintSafe)
+            } else {
+                Policy::new()
+            };
+            let card = Card::generate(
+                &mut rng,
+                rows as usize,
+                columns as usize,
+                numbers,
+                symbols,
+                &policy,
+            );
+            match out {
+                #[cfg(feature = "qr")]
+                Some(path) => {
+                    if let Err(err) = render_file(&path, |format| render::card(&card, format)) {
+                        fail(&opts.output, err);
+                    }
+                }
+                _ => print!("{}", card::render(&card, format)),
+            }
+            return;
+        }
+        #[cfg(feature = "analysis")]
+        Commands::Analyze {
+            file: Some(path),
+            column,
+            ..
+        } => {
+            audit(&path, column.as_deref(), &opts.output);
+            return;
+        }
+        #[cfg(feature = "analysis")]
+        Commands::Analyze { ref password, .. } => {
+            analyze(password.clone(), &analyzer, &opts);
+            return;
+        }
+        #[cfg(not(feature = "analysis"))]
+        Commands::Analyze { .. } => unreachable!("rejected by require_features"),
+        Commands::VerifyPhrase { phrase } => {
+            verify_phrase(phrase, &opts.output);
+            return;
+        }
+        Commands::T9 { text } => {
+            t9(text, &opts.output);
+            return;
+        }
+        Commands::Recombine {
+            parts,
+            ref separator,
+        } => {
+            recombine(parts, separator, &opts.output);
+            return;
+        }
+        Commands::Compare { length_budget } => {
+            compare::display(length_budget, &opts.output).expect("unable to write the comparison");
+            return;
+        }
+        Commands::Info => {
+            info::BuildInfo::current().display(&opts.output);
+            return;
+        }
+        Commands::Rules {
+            command: RulesCommands::Parse { ref rules },
+        } => {
+            rules::display(rules, &opts.output).expect("unable to write the password rules");
+            return;
+        }
+        Commands::Policy {
+            command:
+                PolicyCommands::Check {
+                    ref policy,
+                    ref password,
+                },
+        } => {
+            check_policy(policy, password.clone(), &opts);
+            return;
+        }
+        Commands::Policy {
+            command: PolicyCommands::Schema,
+        } => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&policy::schema()).unwrap()
+            );
+            return;
+        }
+        Commands::Schema { format } => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&schema::render(format, opts.output_schema)).unwrap()
+            );
+            return;
+        }
+        Commands::Selftest { samples } => {
+            let report = selftest::SelfTest::run(&mut rng, samples as usize);
+            report.display(&opts.output);
+            if !report.passed() {
+                std::process::exit(1);
+            }
+            return;
+        }
+        Commands::Share {
+            once,
+            ttl,
+            bind,
+            tls_cert,
+            tls_key,
+        } => {
+            let options = share::ShareOptions {
+                bind,
+                ttl,
+                once,
+                tls: tls_cert.zip(tls_key),
+            };
+            let token = motus::code_password(&mut rng, 26, motus::Radix::Base32.alphabet());
+            share(options, token, &opts.output);
+            return;
+        }
+        Commands::Wrap { out } => {
+            wrap(out.as_deref(), &opts.output);
+            return;
+        }
+        Commands::Unwrap { input } => {
+            unwrap(&input, &opts.output);
+            return;
+        }
+        Commands::Askpass {
+            prompt,
+            from_history,
+        } => {
+            let path = opts
+                .history_file
+                .take()
+                .unwrap_or_else(history::default_path);
+            askpass(&prompt, from_history.as_deref(), &path, &opts.output);
+            return;
+        }
+        Commands::External => {
+            let key = std::env::var(external::KEY_ENV).unwrap_or_default();
+            match external::read_query(io::stdin().lock())
+                .and_then(|query| external::generate(&query, &key))
+            {
+                Ok(result) => println!("{}", json!(result)),
+                Err(err) => fail(&opts.output, err),
+            }
+            return;
+        }
+        Commands::Stats { command } => {
+            let path = opts.stats_file.unwrap_or_else(stats::default_path);
+            manage_stats(&path, command, &opts.output);
+            return;
+        }
+        Commands::Store { command } => {
+            let path = opts.history_file.unwrap_or_else(history::default_path);
+            manage_store(&path, command, &opts.output);
+            return;
+        }
+        Commands::AuditLog {
+            command:
+                AuditLogCommands::Verify {
+                    path,
+                    piv_public_key,
+                },
+        } => {
+            let Some(path) = path.or(opts.audit_log) else {
+                fail(&opts.output, "give the path to the audit log to verify");
+            };
+            verify_audit_log(&path, piv_public_key.as_deref(), &opts.output);
+            return;
+        }
+        Commands::Wordlist {
+            command:
+                WordlistCommands::Build {
+                    from,
+                    size,
+                    min_len,
+                    max_len,
+                    out,
+                },
+        } => {
+            build_wordlist(&from, size, min_len..=max_len, out.as_deref(), &opts.output);
+            return;
+        }
+        Commands::Wordlist {
+            command:
+                WordlistCommands::Lint {
+                    path,
+                    allow_prefixes,
+                },
+        } => {
+            lint_wordlist(path.as_deref(), allow_prefixes, &opts.output);
+            return;
+        }
+        Commands::Agent {
+            timeout,
+            socket,
+            foreground,
+            kill,
+        } => {
+            #[cfg(unix)]
+            run_agent(timeout, socket, foreground, kill, &opts.output);
+            #[cfg(not(unix))]
+            {
+                let _ = (timeout, socket, foreground, kill);
+                fail(&opts.output, "the agent is only available on Unix systems");
+            }
+            return;
+        }
+    };
+
+    // Convert the case of the generated passwords, before the static prefix and suffix: random
+    // passwords were generated in the case, and the case of the words of memorable passwords and
+    // of the single-case alphabets of PIN codes carries no entropy
+    if opts.case != Case::Mixed {
+        for password in &mut passwords {
+            *password = opts.case.apply(password);
+        }
+    }
+
+    // Compose the final passwords with the static prefix and suffix, if any
+    if let Some(prefix) = &opts.prefix {
+        entropy = entropy.with_static(EntropySource::Prefix);
+        for password in &mut passwords {
+            password.insert_str(0, prefix);
+        }
+    }
+    if let Some(suffix) = &opts.suffix {
+        entropy = entropy.with_static(EntropySource::Suffix);
+        for password in &mut passwords {
+            password.push_str(suffix);
+        }
+    }
+
+    // Reuse the password previously stored in the history under the label, if requested and there
+    // is one, or store the new passwords in the history, if requested
+    let history_path = opts
+        .history_file
+        .take()
+        .unwrap_or_else(history::default_path);
+    let mut reused = false;
+    // The password stored for the label is only reused when asked for the same password again:
+    // the same kind, with the same parameters, prefix and suffix
+    let mut stored_parameters = parameters.clone();
+    stored_parameters["case"] = json!(value_name(opts.case));
+    stored_parameters["prefix"] = json!(opts.prefix);
+    stored_parameters["suffix"] = json!(opts.suffix);
+    if opts.idempotent {
+        let label = opts
+            .label
+            .as_deref()
+            .expect("--idempotent should require a label");
+        match history::recall(&history_path, label) {
+            Ok(Some(entry)) => {
+                if entry.kind != kind {
+                    fail(
+                        &opts.output,
+                        format!(
+                            "the password stored for '{}' was generated by the {} command, not the {} one; use another label",
+                            label, entry.kind, kind
+                        ),
+                    );
+                }
+                if entry
+                    .parameters
+                    .as_ref()
+                    .is_some_and(|stored| *stored != stored_parameters)
+                {
+                    fail(
+                        &opts.output,
+                        format!(
+                            "the password stored for '{}' was generated with other parameters; use the same ones, or another label",
+                            label
+                        ),
+                    );
+                }
+                if matches!(opts.output, OutputFormat::Text) {
+                    eprintln!(
+                        "note: reusing the password stored for '{}' in the history",
+                        label
+                    );
+                }
+                passwords = vec![entry.password];
+                reused = true;
+            }
+            Ok(None) => {}
+            Err(err) => fail(&opts.output, err),
+        }
+    }
+    if opts.history && !reused {
+        let label = opts
+            .label
+            .as_deref()
+            .expect("--history should require a label");
+
+        for password in &passwords {
+            if let Err(err) = history::record(
+                &history_path,
+                label,
+                kind,
+                password,
+                &tags(&opts),
+                &stored_parameters,
+            ) {
+                fail(&opts.output, err);
+            }
+        }
+    }
+
+    // Record the generation in the audit log, if requested, before any password is shown; reused
+    // passwords were recorded when they were generated
+    if let Some(path) = opts.audit_log.as_ref().filter(|_| !reused) {
+        let mut parameters = parameters;
+        parameters["prefix"] = json!(opts.prefix.is_some());
+        parameters["suffix"] = json!(opts.suffix.is_some());
+        let event = auditlog::Event::new(
+            kind,
+            passwords.len(),
+            parameters,
+            entropy.bits(),
+            opts.label.as_deref(),
+        );
+        if let Err(err) = auditlog::record(path, event, opts.audit_log_sign.as_ref()) {
+            fail(&opts.output, err);
+        }
+    }
+
+    // Count the generated passwords in the usage stats, if enabled; failing to do so is not
+    // worth failing the generation for
+    if !reused {
+        let stats_path = opts.stats_file.take().unwrap_or_else(stats::default_path);
+        if let Err(err) = stats::record(&stats_path, kind, passwords.len() as u64) {
+            eprintln!("warning: unable to record usage stats: {}", err);
+        }
+    }
+
+    let bits = entropy.bits();
+    let entropy = opts.explain.then(|| EntropyReport::from(&entropy));
+
+    if (hints || story) && passwords.len() > 1 {
+        fail(
+            &opts.output,
+            "--hints and --story can only be used when generating a single password",
+        );
+    }
+    if opts.qr_file.is_some() && passwords.len() > 1 {
+        fail(
+            &opts.output,
+            "--qr-file can only be used when generating a single password",
+        );
+    }
+
+    // Hold the passwords in memory wiped once they are displayed, and locked so that it is never
+    // swapped to disk with the memlock feature
+    let passwords: Vec<Secret> = passwords.into_iter().map(Secret::new).collect();
+    if cfg!(feature = "memlock") && !passwords.iter().all(Secret::is_locked) {
+        eprintln!("warning: unable to lock the password in memory; it may be swapped to disk");
+    }
+
+    // Grouping only affects how passwords are displayed: the analysis, and the clipboard if
+    // requested, get the original passwords
+    let displayed: Vec<Secret> = passwords
+        .iter()
+        .map(|password| Secret::new(group(password, opts.group, &opts.group_separator)))
+        .collect();
+
+    let ([password], [shown]) = (passwords.as_slice(), displayed.as_slice()) else {
+        display_batch(kind, &passwords, &displayed, entropy, &opts);
+        return;
+    };
+
+    // Split passwords are only displayed in parts, for no single custodian to know them whole:
+    // they are never copied to the clipboard
+    if let Some(parts) = opts.split {
+        if hints || story {
+            fail(
+                &opts.output,
+                "--hints and --story would display the whole password, and cannot be used with --split",
+            );
+        }
+        if !matches!(opts.output, OutputFormat::Text | OutputFormat::Json) {
+            fail(
+                &opts.output,
+                "--split only applies to the text and JSON outputs",
+            );
+        }
+        split::Split::new(password, parts as usize, split_separator, bits)
+            .unwrap_or_else(|err| fail(&opts.output, err))
+            .display(kind, &opts.output)
+            .expect("unable to write the split password");
+        return;
+    }
+
+    if opts.big && !matches!(opts.output, OutputFormat::Text) {
+        fail(&opts.output, "--big only applies to the text output");
+    }
+    if opts.accessible && !matches!(opts.output, OutputFormat::Text | OutputFormat::Json) {
+        fail(
+            &opts.output,
+            "--accessible only applies to the text and JSON outputs",
+        );
+    }
+
+    // Write the password as a QR code, for a phone to scan it, if requested
+    #[cfg(feature = "qr")]
+    if let Some(path) = &opts.qr_file {
+        if let Err(err) = render_file(path, |format| render::qr(password, format)) {
+            fail(&opts.output, err);
+        }
+    }
+
+    // Copy the password to the clipboard, unless there is plausibly none to copy it to, or it is
+    // only output encrypted as a systemd credential; the JSON output is copied once rendered
+    let copy = !opts.no_clipboard
+        && !matches!(opts.output, OutputFormat::SystemdCred | OutputFormat::Sops);
+    if copy && opts.clipboard_content == ClipboardContent::Password {
+        copy_to_clipboard(&opts, if opts.clipboard_raw { password } else { shown });
+    }
+
+    // The report written with --report-file replaces the output
+    let output = if opts.report_file.is_some() {
+        OutputFormat::Html
+    } else {
+        opts.output
+    };
+    match output {
+        OutputFormat::Text => {
+            if opts.analyze {
+                #[cfg(feature = "analysis")]
+                analyzer
+                    .analyze(password)
+                    .display_report(TableStyle::extended(), 80);
+            } else {
+                // Large letters and spellings are displayed in place of the password
+                let rendered = if opts.big {
+                    Some(Secret::new(banner::render(shown, 80)))
+                } else if opts.accessible {
+                    Some(Secret::new(motus::spelling::spell_out(password).join("\n")))
+                } else {
+                    None
+                };
+                let shown = rendered.as_ref().unwrap_or(shown);
+                if let Some(seconds) = opts.ephemeral.filter(|_| io::stdout().is_terminal()) {
+                    ephemeral::display(shown, Duration::from_secs(seconds))
+                        .expect("unable to write to the terminal");
+                } else {
+                    println!("{}", shown.expose());
+                }
+            }
+
+            if opts.t9 {
+                println!("T9: {}", motus::keypad::t9(password));
+            }
+
+            if story {
+                println!("{}", motus::story::story(&motus::glossary::words(password)));
+            }
+
+            if hints {
+                HintsReport::new(password).display(TableStyle::extended(), 80);
+            }
+
+            if let Some(entropy) = entropy {
+                entropy.display(TableStyle::extended(), 80);
+            }
+        }
+        OutputFormat::Json => {
+            let output = PasswordOutput {
+                kind,
+                password: shown,
+                #[cfg(feature = "analysis")]
+                analysis: opts.analyze.then(|| analyzer.analyze(password)),
+                entropy,
+                hints: hints.then(|| HintsReport::new(password)),
+                story: story.then(|| motus::story::story(&motus::glossary::words(password))),
+                t9: opts.t9.then(|| motus::keypad::t9(password)),
+                spelling: opts
+                    .accessible
+                    .then(|| motus::spelling::spell_out(password)),
+                label: opts.label.as_deref(),
+                tags: tags(&opts),
+            };
+            let json = opts.output_schema.json(Payload::Password, &output);
+            if copy && opts.clipboard_content == ClipboardContent::Json {
+                copy_to_clipboard(&opts, &json);
+            }
+            println!("{}", json);
+        }
+        OutputFormat::Csv => {
+            let (header, annotations) = csv_annotations(&opts);
+            println!("kind,password{}", header);
+            println!("{},{}{}", kind, csv_field(shown), annotations);
+        }
+        #[cfg(feature = "analysis")]
+        OutputFormat::Html => {
+            write_report(&analyzer.analyze(password), Some(shown.expose()), &opts)
+        }
+        #[cfg(not(feature = "analysis"))]
+        OutputFormat::Html => unreachable!("rejected by require_features"),
+        OutputFormat::SystemdCred => {
+            let name = opts.name.as_deref().expect("--name should be checked");
+            match systemd::encrypt(name, password) {
+                Ok(credential) => io::stdout()
+                    .write_all(&credential)
+                    .expect("unable to write the credential"),
+                Err(err) => fail(&opts.output, err),
+            }
+        }
+        OutputFormat::Sops => {
+            let recipient = match (&opts.age, &opts.kms) {
+                (Some(age), _) => sops::Recipient::Age(age),
+                (_, Some(kms)) => sops::Recipient::Kms(kms),
+                _ => unreachable!("checked by check_sealed_output"),
+            };
+            let name = opts.name.as_deref().unwrap_or("password");
+            match sops::encrypt(name, password, &recipient) {
+                Ok(document) => io::stdout()
+                    .write_all(&document)
+                    .expect("unable to write the document"),
+                Err(err) => fail(&opts.output, err),
+            }
+        }
+    }
+
+    if let Some(rounds) = opts.practice {
+        if let Err(err) = practice::run(password, rounds) {
+            fail(&opts.output, format!("unable to practice: {}", err));
+        }
+    }
+}
+
+/// display_batch prints a batch of generated passwords.
+///
+/// When `analyze` is set, aggregate statistics about the batch are displayed instead of one full
+/// report per password, which would quickly become unmanageable, unless `per_item` is set too.
+/// As every password of the batch is generated with the same settings, they share the same
+/// `entropy`. Passwords are printed as `displayed`, and analyzed as they are.
+#[cfg_attr(not(feature = "analysis"), allow(unused_variables))]
+fn display_batch(
+    kind: PasswordKind,
+    passwords: &[Secret],
+    displayed: &[Secret],
+    entropy: Option<EntropyReport>,
+    opts: &Cli,
+) {
+    let per_item = opts.analyze && opts.per_item;
+    let summary = opts.analyze && !opts.per_item;
+    #[cfg(feature = "analysis")]
+    let analyzer = analyzer(opts);
+
+    match opts.output {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            for (password, shown) in passwords.iter().zip(displayed) {
+                if per_item {
+                    #[cfg(feature = "analysis")]
+                    analyzer
+                        .analyze(password)
+                        .display_report(TableStyle::extended(), 80);
+                } else {
+                    println!("{}", shown.expose());
+                }
+            }
+
+            if summary {
+                #[cfg(feature = "analysis")]
+                BatchSummary::new(passwords).display(TableStyle::extended(), 80);
+            }
+
+            if let Some(entropy) = entropy {
+                entropy.display(TableStyle::extended(), 80);
+            }
+        }
+        OutputFormat::Json => {
+            let outputs: Vec<PasswordOutput> = passwords
+                .iter()
+                .zip(displayed)
+                .map(|(password, shown)| PasswordOutput {
+                    kind,
+                    password: shown,
+                    #[cfg(feature = "analysis")]
+                    analysis: per_item.then(|| analyzer.analyze(password)),
+                    entropy: entropy.clone(),
+                    hints: None,
+                    story: None,
+                    t9: None,
+                    spelling: None,
+                    label: opts.label.as_deref(),
+                    tags: tags(opts),
+                })
+                .collect();
+
+            let output = BatchOutput {
+                passwords: outputs,
+                #[cfg(feature = "analysis")]
+                summary: summary.then(|| BatchSummary::new(passwords)),
+            };
+            println!("{}", opts.output_schema.json(Payload::Batch, &output));
+        }
+        OutputFormat::Csv => {
+            let (header, annotations) = csv_annotations(opts);
+            println!("kind,password{}", header);
+            for shown in displayed {
+                println!("{},{}{}", kind, csv_field(shown), annotations);
+            }
+        }
+    }
+}
+
+/// analyzer returns the analyzer of passwords, estimating their crack times against the attacks
+/// given with --attack-rate and --attack-cost on top of the default ones.
+#[cfg(feature = "analysis")]
+fn analyzer(opts: &Cli) -> Analyzer {
+    let attacks: Vec<AttackModel> = opts
+        .attack_rate
+        .iter()
+        .map(|&rate| match opts.attack_cost {
+            Some(cost) => AttackModel::new(rate).with_cost_per_hour(cost),
+            None => AttackModel::new(rate),
+        })
+        .collect();
+    let policy = match &opts.command {
+        Commands::Analyze {
+            policy: Some(path), ..
+        } => Some(load_policy(path, opts.deny_list.as_ref(), &opts.output)),
+        _ => None,
+    };
+    Analyzer::new(attacks, opts.locale, opts.print_safe)
+        .with_deny_list(opts.deny_list.clone().unwrap_or_default())
+        .with_estimators(opts.estimator.clone())
+        .with_humanize(opts.humanize)
+        .with_policy(policy)
+}
+
+// avoid_denied generates passwords with `generate` until one contains none of the terms of the
+// deny list, if any, and fails if they cannot be avoided.
+fn avoid_denied(opts: &Cli, mut generate: impl FnMut() -> String) -> String {
+    match &opts.deny_list {
+        Some(deny_list) => deny_list.resample(generate).unwrap_or_else(|| {
+            fail(
+                &opts.output,
+                format!(
+                    "unable to generate a password containing none of the denied terms in {} attempts; remove the shortest ones from the deny list",
+                    motus::denylist::MAX_ATTEMPTS
+                ),
+            )
+        }),
+        None => generate(),
+    }
+}
+
+// copy_to_clipboard copies `text` to the clipboard, unless there is plausibly none to copy it to
+// and --clipboard force is not given, warning when copying fails.
+fn copy_to_clipboard(opts: &Cli, text: &str) {
+    let headless = match opts.clipboard {
+        ClipboardMode::Auto if opts.clipboard_file.is_none() => clipboard::headless(),
+        _ => None,
+    };
+    if let Some(reason) = headless {
+        eprintln!(
+            "note: not copying the password to the clipboard ({}); use --clipboard force to copy it anyway",
+            reason
+        );
+        return;
+    }
+
+    if let Err(err) = clipboard::copy(text, &copy_options(opts)) {
+        eprintln!(
+            "warning: unable to copy the password to the clipboard: {}",
+            err
+        );
+    }
+}
+
+// copy_options returns the settings of copies to the clipboard given on the command line.
+fn copy_options(opts: &Cli) -> clipboard::CopyOptions {
+    clipboard::CopyOptions {
+        hints: !opts.no_clipboard_hints,
+        local_only: opts.clipboard_local_only,
+        #[cfg(feature = "headless")]
+        file: opts.clipboard_file.clone(),
+    }
+}
+
+/// analyze prints the safety analysis of a user-provided password, reading it from the
+/// standard input if `password` is '-'.
+#[cfg(feature = "analysis")]
+fn analyze(password: String, analyzer: &Analyzer, opts: &Cli) {
+    let output = &opts.output;
+    let password = if password == "-" {
+        read_secret("Password to analyze: ").expect("unable to read password")
+    } else {
+        password
+    };
+
+    if password.is_empty() {
+        fail(output, "the password to analyze cannot be empty");
+    }
+
+    let analysis = analyzer.analyze(&password);
+    let output = if opts.report_file.is_some() {
+        &OutputFormat::Html
+    } else {
+        output
+    };
+    match output {
+        OutputFormat::Text | OutputFormat::SystemdCred | OutputFormat::Sops => {
+            analysis.display_analysis(TableStyle::extended(), 80);
+            if let Some(compliance) = analysis.compliance() {
+                compliance.display(output, opts.output_schema);
+            }
+        }
+        OutputFormat::Html => write_report(&analysis, None, opts),
+        OutputFormat::Json => println!("{}", opts.output_schema.json(Payload::Analysis, &analysis)),
+        OutputFormat::Csv => match analysis.compliance() {
+            Some(compliance) => {
+                println!("strength,guesses,warnings,compliant");
+                println!(
+                    "{},{},{},{}",
+                    analysis.strength(),
+                    analysis.guesses(),
+                    analysis.warnings().len(),
+                    compliance.is_compliant()
+                );
+            }
+            None => {
+                println!("strength,guesses,warnings");
+                println!(
+                    "{},{},{}",
+                    analysis.strength(),
+                    analysis.guesses(),
+                    analysis.warnings().len()
+                );
+            }
+        },
+    }
+
+    if analysis
+        .compliance()
+        .is_some_and(|compliance| !compliance.is_compliant())
+    {
+        std::process::exit(1);
+    }
+}
+
+/// write_report writes the analysis report, listing the password first if given, to the file
+/// given with --report-file in the format of its extension, or to stdout as HTML.
+#[cfg(feature = "analysis")]
+fn write_report(analysis: &Report, password: Option<&str>, opts: &Cli) {
+    let document = report::Document::new(analysis, password);
+    let Some(path) = &opts.report_file else {
+        print!("{}", document.html());
+        return;
+    };
+
+    let content = match ReportFormat::from_path(path) {
+        ReportFormat::Html => document.html().into_bytes(),
+        #[cfg(feature = "pdf")]
+        ReportFormat::Pdf => pdf::render(&document),
+        #[cfg(not(feature = "pdf"))]
+        ReportFormat::Pdf => unreachable!("rejected by require_features"),
+    };
+    if let Err(err) = write_private(path, &content) {
+        fail(
+            &opts.output,
+            format!("unable to write {}: {}", path.display(), err),
+        );
+    }
+}
+
+/// audit prints a summary report of the safety of every password found in the file at `path`.
+#[cfg(feature = "analysis")]
+fn audit(path: &Path, column: Option<&str>, output: &OutputFormat) {
+    let entries = match audit::read_entries(path, column) {
+        Ok(entries) => entries,
+        Err(err) => {
+            fail(output, err);
+        }
+    };
+
+    if entries.is_empty() {
+        fail(
+            output,
+            format!("no password to analyze found in {}", path.display()),
+        );
+    }
+
+    audit::AuditReport::new(&entries).display(output);
+}
+
+/// recombine prompts for the `parts` parts of a split password, joins them with `separator`, and
+/// prints the password.
+fn recombine(parts: u32, separator: &str, output: &OutputFormat) {
+    let password = (1..=parts)
+        .map(|part| {
+            read_secret(&format!("Part {} of {}: ", part, parts)).expect("unable to read the part")
+        })
+        .collect::<Vec<_>>()
+        .join(separator);
+    let password = Secret::new(password);
+
+    match output {
+        OutputFormat::Json => println!("{}", json!({ "password": password.expose() })),
+        OutputFormat::Csv => {
+            println!("password");
+            println!("{}", csv_field(password.expose()));
+        }
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => println!("{}", password.expose()),
+    }
+}
+
+/// t9 prints the telephone keypad digits a word or password is typed with, reading it from the
+/// standard input if it is '-'.
+fn t9(text: String, output: &OutputFormat) {
+    let text = if text == "-" {
+        read_secret("Text to convert: ").expect("unable to read text")
+    } else {
+        text
+    };
+
+    let digits = Secret::new(motus::keypad::t9(&text));
+    if digits.is_empty() {
+        fail(
+            output,
+            "the text holds no character found on a telephone keypad",
+        );
+    }
+
+    match output {
+        OutputFormat::Json => println!("{}", json!({ "t9": digits.expose() })),
+        OutputFormat::Csv => {
+            println!("t9");
+            println!("{}", csv_field(digits.expose()));
+        }
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => println!("{}", digits.expose()),
+    }
+}
+
+/// verify_phrase checks that a passphrase ends with the verification word of its other words,
+/// reading it from the standard input if it is '-', and exits with an error status if it does not.
+fn verify_phrase(phrase: String, output: &OutputFormat) {
+    let phrase = if phrase == "-" {
+        read_secret("Passphrase to verify: ").expect("unable to read passphrase")
+    } else {
+        phrase
+    };
+
+    let verification = motus::checksum::verify(&phrase);
+    match output {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => match verification {
+            Ok(_) => println!("The passphrase matches its verification word"),
+            Err(err) => fail(output, err),
+        },
+        OutputFormat::Json => println!(
+            "{}",
+            match verification {
+                Ok(_) => json!({"valid": true}),
+                Err(err) => json!({"valid": false, "error": err.to_string()}),
+            }
+        ),
+        OutputFormat::Csv => {
+            println!("valid");
+            println!("{}", verification.is_ok());
+        }
+    }
+
+    if verification.is_err() {
+        std::process::exit(1);
+    }
+}
+
+/// check_policy validates a user-provided password against the policy file at `path`, reading
+/// the password from the standard input if it is '-', and exits with an error status if the
+/// password does not comply.
+fn check_policy(path: &Path, password: String, opts: &Cli) {
+    let policy = load_policy(path, opts.deny_list.as_ref(), &opts.output);
+
+    let password = if password == "-" {
+        read_secret("Password to check: ").expect("unable to read password")
+    } else {
+        password
+    };
+
+    let report = policy::CheckReport::new(&policy, &password);
+    report.display(&opts.output, opts.output_schema);
+
+    if !report.is_compliant() {
+        std::process::exit(1);
+    }
+}
+
+/// load_policy loads the policy file at `path`, adding the terms of the deny list, if any, to
+/// the ones of the policy.
+fn load_policy(path: &Path, deny_list: Option<&DenyList>, output: &OutputFormat) -> Policy {
+    let policy = match policy::load(path) {
+        Ok(policy) => policy,
+        Err(err) => {
+            fail(output, err);
+        }
+    };
+
+    match deny_list {
+        Some(deny_list) => {
+            let terms = policy.deny_list().terms().iter().chain(deny_list.terms());
+            let merged = DenyList::new(terms);
+            policy.with_deny_list(merged)
+        }
+        None => policy,
+    }
+}
+
+/// apply_config sets the options that were neither given as flags nor as environment variables
+/// to their value in the configuration file, if any.
+fn apply_config(opts: &mut Cli, matches: &ArgMatches, defaults: config::Defaults) {
+    let unset = |matches: &ArgMatches, id: &str| {
+        matches.value_source(id) == Some(ValueSource::DefaultValue)
+    };
+
+    if let Some(no_clipboard) = defaults
+        .no_clipboard
+        .filter(|_| unset(matches, "no_clipboard"))
+    {
+        opts.no_clipboard = no_clipboard;
+    }
+    if let Some(output) = defaults.output.filter(|_| unset(matches, "output")) {
+        opts.output = output;
+    }
+    if opts.audit_log.is_none() {
+        opts.audit_log = defaults.audit_log;
+    }
+    if opts.audit_log_sign.is_none() {
+        if let Some(signer) = defaults.audit_log_sign {
+            match auditlog::Signer::parse(&signer) {
+                Ok(signer) => opts.audit_log_sign = Some(signer),
+                Err(err) => fail(
+                    &opts.output,
+                    format!("invalid audit_log_sign in configuration: {}", err),
+                ),
+            }
+        }
+    }
+    if opts.store.is_none() && opts.history_file.is_none() {
+        if let Some(store) = defaults.store {
+            match history::validate_store_name(&store) {
+                Ok(store) => opts.store = Some(store),
+                Err(err) => fail(
+                    &opts.output,
+                    format!("invalid store in configuration: {}", err),
+                ),
+            }
+        }
+    }
+    if let (Commands::Memorable { separator, .. }, Some(default), Some(matches)) = (
+        &mut opts.command,
+        defaults.separator,
+        matches.subcommand_matches("memorable"),
+    ) {
+        if unset(matches, "separator") {
+            *separator = default;
+        }
+    }
+}
+
+/// share serves a secret read from the standard input at a one-time link holding `token`, until
+/// it is retrieved or the link expires.
+fn share(options: share::ShareOptions, token: String, output: &OutputFormat) {
+    let secret = read_secret("Secret to share: ").expect("unable to read secret");
+    if secret.is_empty() {
+        fail(output, "the secret to share cannot be empty");
+    }
+
+    let (ttl, once) = (options.ttl, options.once);
+    let share = share::Share::bind(options, token).unwrap_or_else(|err| fail(output, err));
+    match output {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => println!(
+            "Retrieve the secret at {} within {}s{}",
+            share.url(),
+            ttl.as_secs(),
+            if once { ", once" } else { "" }
+        ),
+        OutputFormat::Json => println!(
+            "{}",
+            json!({ "url": share.url(), "ttl": ttl.as_secs(), "once": once })
+        ),
+        OutputFormat::Csv => {
+            println!("url,ttl,once");
+            println!("{},{},{}", share.url(), ttl.as_secs(), once);
+        }
+    }
+
+    match share.serve(&secret) {
+        Ok(share::Outcome::Retrieved(peers)) => {
+            for peer in peers {
+                eprintln!("note: the secret was retrieved from {}", peer.ip());
+            }
+        }
+        Ok(share::Outcome::Expired) => {
+            fail(output, "the link expired before the secret was retrieved")
+        }
+        Err(err) => fail(output, err),
+    }
+}
+
+/// wrap seals a secret read from the standard input into an envelope, printed as text unless
+/// written to `out`.
+fn wrap(out: Option<&Path>, output: &OutputFormat) {
+    let secret = read_secret("Secret to wrap: ").expect("unable to read secret");
+    if secret.is_empty() {
+        fail(output, "the secret to wrap cannot be empty");
+    }
+
+    let envelope = envelope::read_passphrase(true)
+        .and_then(|passphrase| envelope::seal(secret.as_bytes(), &passphrase))
+        .unwrap_or_else(|err| fail(output, err));
+
+    if let Some(path) = out {
+        if let Err(err) = write_private(path, &envelope) {
+            fail(
+                output,
+                format!(
+                    "unable to write the envelope to {}: {}",
+                    path.display(),
+                    err
+                ),
+            );
+        }
+        return;
+    }
+
+    let armored = envelope::armor(&envelope);
+    match output {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            println!("{}", armored)
+        }
+        OutputFormat::Json => println!("{}", json!({ "envelope": armored })),
+        OutputFormat::Csv => {
+            println!("envelope");
+            println!("{}", armored);
+        }
+    }
+}
+
+/// unwrap prints the secret sealed in the envelope at `input`, or read from the standard input
+/// if `input` is '-'.
+fn unwrap(input: &Path, output: &OutputFormat) {
+    let data = if input == Path::new("-") {
+        let mut data = Vec::new();
+        io::Read::read_to_end(&mut io::stdin(), &mut data).map(|_| data)
+    } else {
+        std::fs::read(input)
+    };
+
+    let secret = data
+        .map_err(envelope::EnvelopeError::from)
+        .and_then(|data| envelope::dearmor(&data))
+        .and_then(|sealed| {
+            envelope::read_passphrase(false)
+                .and_then(|passphrase| envelope::open(&sealed, &passphrase))
+        })
+        .unwrap_or_else(|err| fail(output, err));
+    let secret = String::from_utf8_lossy(&secret);
+
+    match output {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            println!("{}", secret)
+        }
+        OutputFormat::Json => println!("{}", json!({ "secret": secret })),
+        OutputFormat::Csv => {
+            println!("secret");
+            println!("{}", csv_field(&secret));
+        }
+    }
+}
+
+/// askpass answers the prompt of a program using motus as its askpass helper, on the standard
+/// output, or through the exit status for confirmations.
+fn askpass(prompt: &str, label: Option<&str>, history_path: &Path, output: &OutputFormat) {
+    let ssh_askpass_prompt = std::env::var("SSH_ASKPASS_PROMPT").ok();
+    let answer = match askpass::Request::new(prompt, ssh_askpass_prompt.as_deref()) {
+        askpass::Request::Notify => {
+            eprintln!("{}", prompt);
+            return;
+        }
+        askpass::Request::Confirm => {
+            let answer = askpass::read_tty_line(&format!("{} [y/N] ", prompt.trim_end()))
+                .unwrap_or_default();
+            if !matches!(answer.trim().to_lowercase().as_str(), "y" | "yes") {
+                std::process::exit(1);
+            }
+            return;
+        }
+        askpass::Request::Username => askpass::read_tty_line(prompt),
+        askpass::Request::Secret => {
+            if let Some(label) = label {
+                match askpass::lookup(history_path, label) {
+                    Ok(Some(password)) => {
+                        println!("{}", password);
+                        return;
+                    }
+                    Ok(None) => {
+                        eprintln!("note: no password stored for '{}' in the history", label)
+                    }
+                    Err(err) => fail(output, err),
+                }
+            }
+            rpassword::prompt_password(prompt)
+        }
+    };
+
+    match answer {
+        Ok(answer) => println!("{}", answer),
+        Err(err) => fail(output, format!("unable to read from the terminal: {}", err)),
+    }
+}
+
+/// manage_stats runs the `stats` subcommands against the stats file at `path`, displaying the
+/// stats when no subcommand is given.
+fn manage_stats(path: &Path, command: Option<StatsCommands>, output: &OutputFormat) {
+    let result = match command {
+        None => match stats::Stats::load(path) {
+            Ok(Some(stats)) => {
+                stats.display(output);
+                Ok(())
+            }
+            Ok(None) => {
+                fail(
+                    output,
+                    "usage stats are not enabled; enable them with 'motus stats enable'",
+                );
+            }
+            Err(err) => Err(err),
+        },
+        Some(StatsCommands::Enable) => match stats::Stats::load(path) {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => stats::Stats::new().save(path),
+            Err(err) => Err(err),
+        },
+        Some(StatsCommands::Disable) => match std::fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        },
+    };
+
+    if let Err(err) = result {
+        fail(output, err);
+    }
+}
+
+/// verify_audit_log verifies the signatures and the chain of the audit log at `path`, and exits
+/// with a non-zero status if any record is unsigned, its signature invalid, or out of the chain.
+fn verify_audit_log(path: &Path, piv_public_key: Option<&Path>, output: &OutputFormat) {
+    let verification =
+        auditlog::verify(path, piv_public_key).unwrap_or_else(|err| fail(output, err));
+
+    let lines = |numbers: &[usize]| {
+        numbers
+            .iter()
+            .map(usize::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    match output {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            println!("{} valid signatures", verification.valid);
+            if !verification.invalid.is_empty() {
+                println!(
+                    "Invalid signatures on lines: {}",
+                    lines(&verification.invalid)
+                );
+            }
+            if !verification.unsigned.is_empty() {
+                println!(
+                    "Unsigned records on lines: {}",
+                    lines(&verification.unsigned)
+                );
+            }
+            if !verification.broken.is_empty() {
+                println!(
+                    "Records out of the chain on lines: {}",
+                    lines(&verification.broken)
+                );
+            }
+        }
+        OutputFormat::Json => println!("{}", json!(verification)),
+        OutputFormat::Csv => {
+            println!("valid,invalid,unsigned,broken");
+            println!(
+                "{},{},{},{}",
+                verification.valid,
+                lines(&verification.invalid),
+                lines(&verification.unsigned),
+                lines(&verification.broken)
+            );
+        }
+    }
+
+    if !verification.invalid.is_empty()
+        || !verification.unsigned.is_empty()
+        || !verification.broken.is_empty()
+    {
+        std::process::exit(1);
+    }
+}
+
+/// build_wordlist builds a wordlist of `size` words out of the frequency list at `from`, and writes
+/// it to `out`, or to stdout.
+fn build_wordlist(
+    from: &Path,
+    size: usize,
+    lengths: RangeInclusive<usize>,
+    out: Option<&Path>,
+    output: &OutputFormat,
+) {
+    if size < 2 {
+        fail(output, "the wordlist must hold at least 2 words");
+    }
+    if lengths.is_empty() || *lengths.start() == 0 {
+        fail(
+            output,
+            "--min-len must be at least 1, and at most --max-len",
+        );
+    }
+
+    let frequency_list = std::fs::read_to_string(from).unwrap_or_else(|err| {
+        fail(
+            output,
+            format!(
+                "unable to read the frequency list {}: {}",
+                from.display(),
+                err
+            ),
+        )
+    });
+    let words = motus::wordlist::build(&frequency_list, size, lengths)
+        .unwrap_or_else(|err| fail(output, err));
+    let content = words.join("\n") + "\n";
+
+    let Some(out) = out else {
+        print!("{}", content);
+        return;
+    };
+    if let Err(err) = std::fs::write(out, content) {
+        fail(
+            output,
+            format!("unable to write the wordlist to {}: {}", out.display(), err),
+        );
+    }
+
+    let bits_per_word = (words.len() as f64).log2();
+    match output {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => println!(
+            "Wrote {} words to {}, {:.2} bits of entropy per word",
+            words.len(),
+            out.display(),
+            bits_per_word
+        ),
+        OutputFormat::Json => println!(
+            "{}",
+            json!({ "words": words.len(), "bits_per_word": bits_per_word, "out": out })
+        ),
+        OutputFormat::Csv => {
+            println!("words,bits_per_word");
+            println!("{},{}", words.len(), bits_per_word);
+        }
+    }
+}
+
+/// lint_wordlist checks the wordlist at `path`, or the embedded one, and exits with a non-zero
+/// status if it holds duplicates, non-ASCII words, or, unless `allow_prefixes`, words that are a
+/// prefix of another.
+fn lint_wordlist(path: Option<&Path>, allow_prefixes: bool, output: &OutputFormat) {
+    let lint = match path {
+        Some(path) => {
+            let wordlist = std::fs::read_to_string(path).unwrap_or_else(|err| {
+                fail(
+                    output,
+                    format!("unable to read the wordlist {}: {}", path.display(), err),
+                )
+            });
+            lint::lint(wordlist.lines())
+        }
+        None => lint::lint(WordFilter::new().words()),
+    };
+
+    match output {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            let lengths = lint
+                .lengths
+                .iter()
+                .map(|(length, count)| format!("{}: {}", length, count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!("{} words, by length: {}", lint.words, lengths);
+
+            for (line, word) in &lint.duplicates {
+                println!("Duplicate word on line {}: {}", line, word);
+            }
+            for (line, word) in &lint.non_ascii {
+                println!("Non-ASCII word on line {}: {}", line, word);
+            }
+            for (prefix, word) in &lint.prefixes {
+                println!("{} is a prefix of {}", prefix, word);
+            }
+            if lint.is_valid() && lint.is_prefix_free() {
+                println!("No duplicates, non-ASCII words nor prefixes");
+            }
+        }
+        OutputFormat::Json => println!(
+            "{}",
+            json!({
+                "words": lint.words,
+                "lengths": lint.lengths,
+                "duplicates": lint
+                    .duplicates
+                    .iter()
+                    .map(|(line, word)| json!({ "line": line, "word": word }))
+                    .collect::<Vec<_>>(),
+                "non_ascii": lint
+                    .non_ascii
+                    .iter()
+                    .map(|(line, word)| json!({ "line": line, "word": word }))
+                    .collect::<Vec<_>>(),
+                "prefixes": lint
+                    .prefixes
+                    .iter()
+                    .map(|(prefix, word)| json!({ "prefix": prefix, "word": word }))
+                    .collect::<Vec<_>>(),
+            })
+        ),
+        OutputFormat::Csv => {
+            println!("words,duplicates,non_ascii,prefixes");
+            println!(
+                "{},{},{},{}",
+                lint.words,
+                lint.duplicates.len(),
+                lint.non_ascii.len(),
+                lint.prefixes.len()
+            );
+        }
+    }
+
+    if !lint.is_valid() || (!lint.is_prefix_free() && !allow_prefixes) {
+        std::process::exit(1);
+    }
+}
+
+/// manage_store runs the `store` subcommands against the history at `path`.
+fn manage_store(path: &Path, command: StoreCommands, output: &OutputFormat) {
+    match command {
+        StoreCommands::Export { format, out } => {
+            let history = history::History::unlock(path).unwrap_or_else(|err| fail(output, err));
+            let document = match format {
+                backup::ExportFormat::EncryptedJson => backup::read_passphrase(true)
+                    .and_then(|passphrase| backup::export(history.entries(), &passphrase))
+                    .unwrap_or_else(|err| fail(output, err)),
+            };
+
+            match out {
+                Some(out) => {
+                    if let Err(err) = write_private(&out, (document + "\n").as_bytes()) {
+                        fail(
+                            output,
+                            format!("unable to write the export to {}: {}", out.display(), err),
+                        );
+                    }
+                }
+                None => println!("{}", document),
+            }
+        }
+        StoreCommands::Import { input } => {
+            let data = if input == Path::new("-") {
+                let mut data = Vec::new();
+                io::Read::read_to_end(&mut io::stdin(), &mut data).map(|_| data)
+            } else {
+                std::fs::read(&input)
+            };
+            let entries = data
+                .map_err(backup::BackupError::from)
+                .and_then(|data| {
+                    backup::read_passphrase(false)
+                        .and_then(|passphrase| backup::import(&data, &passphrase))
+                })
+                .unwrap_or_else(|err| fail(output, err));
+
+            let total = entries.len();
+            let imported = history::History::unlock(path)
+                .and_then(|mut history| {
+                    let imported = history.merge(entries);
+                    history.save().map(|_| imported)
+                })
+                .unwrap_or_else(|err| fail(output, err));
+
+            match output {
+                OutputFormat::Text
+                | OutputFormat::SystemdCred
+                | OutputFormat::Sops
+                | OutputFormat::Html => println!(
+                    "Imported {} entries, skipping {} already stored",
+                    imported,
+                    total - imported
+                ),
+                OutputFormat::Json => println!(
+                    "{}",
+                    json!({ "imported": imported, "skipped": total - imported })
+                ),
+                OutputFormat::Csv => {
+                    println!("imported,skipped");
+                    println!("{},{}", imported, total - imported);
+                }
+            }
+        }
+        StoreCommands::Protect { disable } => {
+            if let Err(err) = protect_store(path, disable) {
+                fail(output, err);
+            }
+        }
+    }
+}
+
+/// protect_store re-encrypts the history at `path` so that unlocking it requires the FIDO2
+/// security key plugged in, or no longer does if `disable` is set.
+///
+/// The token recording the credential is written before the history is re-encrypted, and only
+/// removed after, for a failure in between to never leave the history unopenable: deleting a
+/// token left behind by a failed `--disable` is enough to recover.
+fn protect_store(path: &Path, disable: bool) -> Result<(), history::HistoryError> {
+    let protected = fido::Token::load(path)?.is_some();
+    let passphrase = history::read_passphrase()?;
+    let mut history = history::History::open(path, &passphrase)?;
+
+    match (disable, protected) {
+        (false, true) | (true, false) => Ok(()),
+        (false, false) => {
+            let token = fido::Token::enroll()?;
+            let secret = token.secret()?;
+            history.rekey(&passphrase, Some(&secret))?;
+            token.save(path)?;
+            history.save().inspect_err(|_| {
+                let _ = std::fs::remove_file(fido::path(path));
+            })
+        }
+        (true, true) => {
+            history.rekey(&passphrase, None)?;
+            history.save()?;
+            std::fs::remove_file(fido::path(path))?;
+            Ok(())
+        }
+    }
+}
+
+/// run_agent starts the agent, in the background unless `foreground` is set, or stops the running
+/// one if `kill` is set.
+#[cfg(unix)]
+fn run_agent(
+    timeout: Duration,
+    socket: Option<PathBuf>,
+    foreground: bool,
+    kill: bool,
+    output: &OutputFormat,
+) {
+    use std::os::unix::process::CommandExt;
+
+    if kill {
+        if let Err(err) = agent::stop() {
+            fail(output, err);
+        }
+        return;
+    }
+
+    let socket = socket
+        .map(Ok)
+        .unwrap_or_else(agent::socket_path)
+        .unwrap_or_else(|err| {
+            fail(
+                output,
+                format!("unable to create the agent socket: {}", err),
+            )
+        });
+
+    if foreground {
+        if let Err(err) = agent::Agent::bind(&socket, timeout).and_then(|agent| agent.serve()) {
+            fail(output, err);
+        }
+        return;
+    }
+
+    // Run the agent in a child process of its own process group, so that interrupting the
+    // shell does not stop it, and report its socket once it listens on it; the child outlives
+    // this process, and is never waited for
+    let pid = std::env::current_exe()
+        .and_then(|exe| {
+            std::process::Command::new(exe)
+                .arg("agent")
+                .arg("--foreground")
+                .arg("--timeout")
+                .arg(format!("{}s", timeout.as_secs()))
+                .arg("--socket")
+                .arg(&socket)
+                .stdin(std::process::Stdio::null())
+                .stdout(std::process::Stdio::null())
+                .stderr(std::process::Stdio::null())
+                .process_group(0)
+                .spawn()
+                .map(|child| child.id())
+        })
+        .unwrap_or_else(|err| fail(output, format!("unable to start the agent: {}", err)));
+
+    let deadline = std::time::Instant::now() + Duration::from_secs(5);
+    while !socket.exists() {
+        if std::time::Instant::now() > deadline {
+            fail(output, "the agent did not start listening in time");
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+
+    let socket = socket.display();
+    match output {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            println!(
+                "{}={}; export {};",
+                agent::SOCKET_ENV,
+                socket,
+                agent::SOCKET_ENV
+            );
+            println!("echo Agent pid {};", pid);
+        }
+        OutputFormat::Json => println!("{}", json!({ "socket": socket.to_string(), "pid": pid })),
+        OutputFormat::Csv => {
+            println!("socket,pid");
+            println!("{},{}", csv_field(&socket.to_string()), pid);
+        }
+    }
+}
+
+/// EntropyFloor is the minimum theoretical entropy generated passwords must reach, if any.
+struct EntropyFloor {
+    bits: Option<u32>,
+    auto_strengthen: bool,
+    output: OutputFormat,
+}
+
+impl EntropyFloor {
+    /// enforce returns the length the password should be generated with for its entropy, as
+    /// computed by `entropy`, to reach the floor.
+    ///
+    /// If the requested `length` falls short, the process exits with an error, unless automatic
+    /// strengthening is enabled, in which case the shortest sufficient length up to `max` is
+    /// returned instead. `option` names the command line option controlling the length.
+    fn enforce(
+        &self,
+        option: &str,
+        length: u32,
+        max: u32,
+        entropy: impl Fn(u32) -> Entropy,
+    ) -> u32 {
+        let Some(floor) = self.bits.map(f64::from) else {
+            return length;
+        };
+
+        let bits = entropy(length).bits();
+        if bits >= floor {
+            return length;
+        }
+
+        if !self.auto_strengthen {
+            fail(&self.output, format!("the requested password only has {:.1} bits of entropy, below the {} bits floor; increase {} or use --auto-strengthen", bits, floor, option));
+        }
+
+        match (length..=max).find(|&n| entropy(n).bits() >= floor) {
+            Some(strengthened) => {
+                eprintln!(
+                    "note: raised {} from {} to {} to reach the {} bits entropy floor",
+                    option, length, strengthened, floor
+                );
+                strengthened
+            }
+            None => {
+                fail(
+                    &self.output,
+                    format!(
+                        "no password reaches the {} bits entropy floor with {} up to {}",
+                        floor, option, max
+                    ),
+                );
+            }
+        }
+    }
+
+    /// check exits with an error if `what`, a secret of fixed length whose entropy is `entropy`,
+    /// falls short of the floor, as no length can be raised to reach it.
+    fn check(&self, what: &str, entropy: &Entropy) {
+        let Some(floor) = self.bits.map(f64::from) else {
+            return;
+        };
+
+        let bits = entropy.bits();
+        if bits < floor {
+            fail(
+                &self.output,
+                format!(
+                    "{} only have {:.1} bits of entropy, below the {} bits floor, and cannot be lengthened",
+                    what, bits, floor
+                ),
+            );
+        }
+    }
+}
+
+/// read_secret reads a secret from the standard input, prompting for it without echoing it if
+/// the standard input is a terminal. The trailing line break is not included.
+fn read_secret(prompt: &str) -> io::Result<String> {
+    if io::stdin().is_terminal() {
+        return rpassword::prompt_password(prompt);
+    }
+
+    let mut secret = String::new();
+    io::stdin().lock().read_line(&mut secret)?;
+    Ok(secret.trim_end_matches(['\n', '\r']).to_string())
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum OutputFormat {
+    Text,
+    Json,
+    Csv,
+    /// A systemd credential named with --name, encrypted with systemd-creds
+    SystemdCred,
+    /// A YAML document encrypted with sops for --age or --kms, holding the password under --name
+    Sops,
+    /// A standalone HTML analysis report, written to --report-file or to stdout
+    Html,
+}
+
+/// fail reports the error and exits with an error status. With `--output json`, the error is
+/// printed on the standard error as a JSON object, as documented by `motus schema`.
+fn fail(output: &OutputFormat, message: impl Display) -> ! {
+    match output {
+        OutputFormat::Json => eprintln!("{}", serde_json::json!({ "error": message.to_string() })),
+        OutputFormat::Text
+        | OutputFormat::Csv
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            eprintln!("error: {}", message)
+        }
+    }
+    std::process::exit(1);
+}
+
+/// manual_password reads `expected` outcomes of the physical `source` from the standard input,
+/// and displays the password derived from them, made of characters of `alphabet`, or of words
+/// without one.
+fn manual_password(
+    source: motus::manual::Source,
+    expected: u32,
+    alphabet: Option<Charset>,
+    interactive: bool,
+    opts: &Cli,
+) {
+    let stdin = io::stdin();
+    let prompt = stdin.is_terminal();
+    let read = |outcomes: &mut Outcomes, count: usize| {
+        manual::read_outcomes(stdin.lock(), outcomes, count, prompt, interactive)
+            .unwrap_or_else(|err| fail(&opts.output, err));
+    };
+    let derive = |outcomes: &Outcomes| match &alphabet {
+        Some(alphabet) => motus::manual::random_password(outcomes, alphabet),
+        None => motus::manual::memorable_password(outcomes),
+    };
+    let (candidates, entropy_source) = match alphabet {
+        Some(_) => ("character", EntropySource::Characters),
+        None => ("word", EntropySource::Words),
+    };
+    let name = source.outcome_name();
+
+    // Physical outcomes are easily overestimated: with an entropy floor, the password is only
+    // generated once they cover it, asking for more of them when they are typed in
+    let mut outcomes = Outcomes::new(source);
+    read(&mut outcomes, expected as usize);
+    let (password, derivation) = loop {
+        let (password, derivation) = derive(&outcomes);
+        let Some(floor) = opts.min_entropy_bits.map(f64::from) else {
+            break (password, derivation);
+        };
+
+        let message = format!(
+            "the password holds {:.1} bits of the {:.1} collected from {} {}s, below the {} bits floor",
+            derivation.bits(),
+            outcomes.bits(),
+            outcomes.len(),
+            name,
+            floor
+        );
+        match outcomes.outcomes_needed(&derivation, floor) {
+            Some(0) => break (password, derivation),
+            Some(more) if prompt || interactive => {
+                eprintln!("{}; about {} more {}s are needed", message, more, name);
+                read(&mut outcomes, more);
+            }
+            Some(more) => fail(
+                &opts.output,
+                format!("{}; type in about {} more {}s", message, more, name),
+            ),
+            None => fail(
+                &opts.output,
+                format!("{}, and no more {}s can be drawn", message, name),
+            ),
+        }
+    };
+    if password.is_empty() {
+        fail(
+            &opts.output,
+            format!("the {}s are too few to draw a single {}", name, candidates),
+        );
+    }
+
+    let report = ManualReport {
+        password: Secret::new(password),
+        entropy: EntropyReport::from(&derivation.entropy(entropy_source)),
+        outcomes: &outcomes,
+        derivation,
+        candidates,
+    };
+    report
+        .display(opts.explain, &opts.output)
+        .expect("unable to write the generated password");
+}
+
+// manual_alphabet returns the characters passwords derived from physical outcomes are made of.
+fn manual_alphabet(numbers: bool, symbols: bool) -> Charset {
+    let mut alphabet = Charset::letters();
+    if numbers {
+        alphabet = alphabet | Charset::numbers();
+    }
+    if symbols {
+        alphabet = alphabet | Charset::symbols();
+    }
+    alphabet
+}
+
+/// input_seed hashes the content of --seed-file, or the standard input with --seed-stdin, into a
+/// seed for the randomness source, exiting with an error if it cannot be read or is empty.
+fn input_seed(opts: &Cli) -> Option<<StdRng as SeedableRng>::Seed> {
+    let input = match &opts.seed_file {
+        Some(path) => std::fs::read(path),
+        None if opts.seed_stdin => {
+            if io::stdin().is_terminal() {
+                eprintln!("note: type the seed, then press Ctrl-D on a new line");
+            }
+            let mut data = Vec::new();
+            io::Read::read_to_end(&mut io::stdin(), &mut data).map(|_| data)
+        }
+        None => return None,
+    };
+
+    let input =
+        input.unwrap_or_else(|err| fail(&opts.output, format!("unable to read the seed: {}", err)));
+    if input.is_empty() {
+        fail(&opts.output, "the seed input is empty");
+    }
+    Some(Blake2s256::digest(&input).into())
+}
+
+/// check_sealed_output exits with an error if the options cannot produce the single encrypted
+/// password that --output systemd-cred and --output sops require.
+fn check_sealed_output(opts: &Cli) {
+    let (format, systemd) = match opts.output {
+        OutputFormat::SystemdCred => ("systemd-cred", true),
+        _ => ("sops", false),
+    };
+    let generates = matches!(
+        opts.command,
+        Commands::Memorable { .. }
+            | Commands::Random { .. }
+            | Commands::Pin { .. }
+            | Commands::Apple
+    );
+
+    let error = if !generates {
+        format!("--output {} only applies to generated passwords", format)
+    } else if systemd && opts.name.is_none() {
+        "--output systemd-cred requires --name, naming the credential".to_string()
+    } else if !systemd && opts.age.is_none() && opts.kms.is_none() {
+        "--output sops requires --age or --kms, the key to encrypt the document to".to_string()
+    } else if opts.count > 1 {
+        format!(
+            "--output {} can only be used when generating a single password",
+            format
+        )
+    } else {
+        return;
+    };
+    fail(&opts.output, error);
+}
+
+/// check_report_output exits with an error if the options cannot produce the single analysis
+/// report that --output html and --report-file render.
+fn check_report_output(opts: &Cli) {
+    let generates = matches!(
+        opts.command,
+        Commands::Memorable { .. }
+            | Commands::Random { .. }
+            | Commands::Pin { .. }
+            | Commands::Apple
+    );
+    let analyzes = matches!(opts.command, Commands::Analyze { file: None, .. });
+
+    let error = if !analyzes && !generates {
+        "analysis reports only apply to the analyze command and to generated passwords"
+    } else if generates && opts.count > 1 {
+        "analysis reports can only be made when generating a single password"
+    } else {
+        return;
+    };
+    fail(&opts.output, error);
+}
+
+/// check_password_display exits with an error if options shaping how a single generated password
+/// is displayed are given to `command`, which displays its secrets its own way, rather than
+/// ignoring them silently.
+fn check_password_display(opts: &Cli, command: &str) {
+    let options = [
+        ("--analyze", opts.analyze),
+        ("--explain", opts.explain),
+        ("--case", opts.case != Case::Mixed),
+        ("--prefix", opts.prefix.is_some()),
+        ("--suffix", opts.suffix.is_some()),
+        ("--group", opts.group.is_some()),
+        ("--split", opts.split.is_some()),
+        ("--big", opts.big),
+        ("--accessible", opts.accessible),
+        ("--t9", opts.t9),
+        ("--qr-file", opts.qr_file.is_some()),
+        ("--ephemeral", opts.ephemeral.is_some()),
+        ("--practice", opts.practice.is_some()),
+    ];
+    if let Some((option, _)) = options.iter().find(|(_, given)| *given) {
+        fail(
+            &opts.output,
+            format!("{} does not apply to the {} command", option, command),
+        );
+    }
+}
+
+/// check_entropy_floor exits with an error if --min-entropy-bits is given to a command it cannot
+/// hold to the floor, rather than letting it generate weaker secrets silently.
+fn check_entropy_floor(opts: &Cli) {
+    let enforced = matches!(
+        opts.command,
+        Commands::Memorable { .. }
+            | Commands::Random { .. }
+            | Commands::Pin { .. }
+            | Commands::Apple
+            | Commands::Identity { .. }
+            | Commands::Answers { .. }
+            | Commands::Dice { .. }
+            | Commands::Coins { .. }
+            | Commands::Deck { .. }
+    );
+    if !enforced {
+        fail(
+            &opts.output,
+            "--min-entropy-bits only applies to the commands generating passwords of a known entropy: memorable, random, pin, apple, identity, answers, dice, coins and deck",
+        );
+    }
+}
+
+// handles_secrets returns whether `command` generates, reads or stores secrets, for the process to
+// be hardened before it does.
+fn handles_secrets(command: &Commands) -> bool {
+    !matches!(
+        command,
+        Commands::Compare { .. }
+            | Commands::Info
+            | Commands::Rules { .. }
+            | Commands::Schema { .. }
+            | Commands::Selftest { .. }
+            | Commands::Stats { .. }
+            | Commands::Username { .. }
+            | Commands::Wordlist { .. }
+    )
+}
+
+/// require_features exits with an error if the options ask for a capability motus was built
+/// without, naming the cargo feature providing it.
+fn require_features(opts: &Cli) {
+    let analysis = opts.analyze
+        || matches!(opts.command, Commands::Analyze { .. })
+        || matches!(opts.output, OutputFormat::Html)
+        || opts.report_file.is_some();
+    #[cfg(feature = "analysis")]
+    let pdf = opts
+        .report_file
+        .as_deref()
+        .is_some_and(|path| ReportFormat::from_path(path) == ReportFormat::Pdf);
+    // Reports are rejected along with the analysis feature already
+    #[cfg(not(feature = "analysis"))]
+    let pdf = false;
+    let rendering =
+        opts.qr_file.is_some() || matches!(opts.command, Commands::Card { out: Some(_), .. });
+
+    for (used, available, feature) in [
+        (analysis, cfg!(feature = "analysis"), "analysis"),
+        (rendering, cfg!(feature = "qr"), "qr"),
+        (pdf, cfg!(feature = "pdf"), "pdf"),
+        (
+            opts.clipboard_file.is_some(),
+            cfg!(feature = "headless"),
+            "headless",
+        ),
+        (
+            matches!(
+                opts.command,
+                Commands::Share {
+                    tls_cert: Some(_),
+                    ..
+                }
+            ),
+            cfg!(feature = "tls"),
+            "tls",
+        ),
+        (
+            matches!(
+                opts.command,
+                Commands::Store {
+                    command: StoreCommands::Protect { .. }
+                }
+            ),
+            cfg!(feature = "fido"),
+            "fido",
+        ),
+    ] {
+        if used && !available {
+            fail(
+                &opts.output,
+                format!(
+                    "motus was built without the {} feature; rebuild it with `--features {}`",
+                    feature, feature
+                ),
+            );
+        }
+    }
+}
+
+/// render_file renders an image in the format matching the extension of `path`, and writes it
+/// there.
+#[cfg(feature = "qr")]
+fn render_file(
+    path: &Path,
+    render: impl FnOnce(Format) -> Result<Vec<u8>, RenderError>,
+) -> Result<(), String> {
+    let format = Format::from_extension(&path.to_string_lossy()).ok_or_else(|| {
+        format!(
+            "unsupported image format for {}: use a .svg or .png file",
+            path.display()
+        )
+    })?;
+    let image = render(format).map_err(|err| err.to_string())?;
+
+    write_private(path, &image)
+        .map_err(|err| format!("unable to write {}: {}", path.display(), err))
+}
+
+/// write_private writes `contents` to the file at `path`, readable by the current user only, as
+/// the files motus writes secrets to must not be readable by other users.
+///
+/// Files that already exist are restricted too, before they are overwritten.
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let mut file = options.open(path)?;
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    file.write_all(contents)
+}
+
+/// group splits the password into chunks of `size` characters joined by `separator`, or returns
+/// it as is if no size is given.
+fn group(password: &str, size: Option<u32>, separator: &str) -> String {
+    let Some(size) = size else {
+        return password.to_string();
+    };
+
+    let chars: Vec<char> = password.chars().collect();
+    chars
+        .chunks(size as usize)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+/// csv_field formats the given value as a CSV field, quoting and escaping it when it contains
+/// characters that would otherwise break the CSV structure.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// tags returns the tags given with --tag, by key; when a key is given several times, the last
+/// value wins.
+fn tags(opts: &Cli) -> BTreeMap<String, String> {
+    opts.tags.iter().cloned().collect()
+}
+
+/// csv_annotations returns the label and tags columns appended to the CSV output of generated
+/// passwords, as a header and the fields of every row, or empty strings if neither --label nor
+/// --tag is used. Tags are written as key=value pairs separated by semicolons.
+fn csv_annotations(opts: &Cli) -> (String, String) {
+    if opts.label.is_none() && opts.tags.is_empty() {
+        return (String::new(), String::new());
+    }
+
+    let tags: Vec<String> = tags(opts)
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect();
+    (
+        ",label,tags".to_string(),
+        format!(
+            ",{},{}",
+            csv_field(opts.label.as_deref().unwrap_or_default()),
+            csv_field(&tags.join(";"))
+        ),
+    )
+}
+
+#[derive(Serialize)]
+struct PasswordOutput<'a> {
+    kind: PasswordKind,
+    password: &'a str,
+
+    #[cfg(feature = "analysis")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    analysis: Option<Report<'a>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entropy: Option<EntropyReport>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hints: Option<HintsReport>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    story: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    t9: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spelling: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<&'a str>,
+
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    tags: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct BatchOutput<'a> {
+    passwords: Vec<PasswordOutput<'a>>,
+
+    #[cfg(feature = "analysis")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    summary: Option<BatchSummary>,
+}
+
+#[derive(Serialize, Deserialize, ValueEnum, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+enum PasswordKind {
+    Memorable,
+    Random,
+    Pin,
+    Apple,
+}
+
+impl Display for PasswordKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PasswordKind::Memorable => write!(f, "memorable"),
+            PasswordKind::Random => write!(f, "random"),
+            PasswordKind::Pin => write!(f, "pin"),
+            PasswordKind::Apple => write!(f, "apple"),
+        }
+    }
+}
+
+// value_name returns the name the given option value is passed on the command line with.
+fn value_name(value: impl ValueEnum) -> Option<String> {
+    value
+        .to_possible_value()
+        .map(|value| value.get_name().to_string())
+}
+
+/// validate_word_count parses the given string as a u32 and returns an error if it is not between
+/// 3 and 15.
+fn validate_word_count(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (3..16).contains(&n) => Ok(n),
+        Ok(_) => Err("The number of words must be between 4 and 15".to_string()),
+        Err(_) => Err("The number of words must be an integer".to_string()),
+    }
+}
+
+/// parse_deny_list reads the deny list file at the given path.
+fn parse_deny_list(path: &str) -> Result<DenyList, String> {
+    std::fs::read_to_string(path)
+        .map(|content| DenyList::parse(&content))
+        .map_err(|err| format!("unable to read the deny list: {}", err))
+}
+
+/// parse_sites_db reads the site password rules file at the given path, and adds its sites to
+/// the embedded database.
+fn parse_sites_db(path: &str) -> Result<Database, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| format!("unable to read the site rules: {}", err))?;
+    let sites = Database::parse(&content).map_err(|err| format!("invalid site rules: {}", err))?;
+    Ok(Database::embedded().clone().merge(sites))
+}
+
+/// parse_password_rules parses password rules written in the syntax of the passwordrules HTML
+/// attribute.
+fn parse_password_rules(s: &str) -> Result<SiteRules, String> {
+    SiteRules::parse(s).map_err(|err| format!("invalid password rules: {}", err))
+}
+
+/// validate_separator_count parses the given string as a u32 and returns an error if it is not
+/// between 1 and 5.
+fn validate_separator_count(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (1..=5).contains(&n) => Ok(n),
+        Ok(_) => Err("The number of separator characters must be between 1 and 5".to_string()),
+        Err(_) => Err("The number of separator characters must be an integer".to_string()),
+    }
+}
+
+/// validate_max_repeat parses the given string as a u32 and returns an error if it is not
+/// between 1 and 100.
+fn validate_max_repeat(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (1..=100).contains(&n) => Ok(n),
+        Ok(_) => {
+            Err("The maximum number of repeated characters must be between 1 and 100".to_string())
+        }
+        Err(_) => Err("The maximum number of repeated characters must be an integer".to_string()),
+    }
+}
+
+/// validate_max_syllables parses the given string as a u32 and returns an error if it is not
+/// between 1 and 10.
+fn validate_max_syllables(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (1..=10).contains(&n) => Ok(n),
+        Ok(_) => Err("The maximum number of syllables must be between 1 and 10".to_string()),
+        Err(_) => Err("The maximum number of syllables must be an integer".to_string()),
+    }
+}
+
+/// validate_character_count parses the given string as a u32 and returns an error if it is not between
+/// 8 and 100.
+fn validate_character_count(s: &str) -> Result<u32, String> {
     match s.parse::<u32>() {
         Ok(n) if (8..101).contains(&n) => Ok(n),
         Ok(_) => Err("The number of words must be between 8 and 100".to_string()),
@@ -457,6 +3997,16 @@ fn validate_character_count(s: &str) -> Result<u32, String> {
     }
 }
 
+/// validate_length_budget parses the given string as a u32 and returns an error if it is not
+/// between 8 and 100, the lengths of the random passwords the comparison suggests commands for.
+fn validate_length_budget(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (8..101).contains(&n) => Ok(n),
+        Ok(_) => Err("The length budget must be between 8 and 100 characters".to_string()),
+        Err(_) => Err("The length budget must be an integer".to_string()),
+    }
+}
+
 /// validate_ping_length parses the given string as a u32 and returns an error if it is not between
 /// 3 and 12.
 fn validate_pin_length(s: &str) -> Result<u32, String> {
@@ -467,10 +4017,256 @@ fn validate_pin_length(s: &str) -> Result<u32, String> {
     }
 }
 
+/// validate_group_size parses the given string as a u32 and returns an error if it is not
+/// between 2 and 16.
+fn validate_group_size(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (2..=16).contains(&n) => Ok(n),
+        Ok(_) => Err("The group size must be between 2 and 16".to_string()),
+        Err(_) => Err("The group size must be an integer".to_string()),
+    }
+}
+
+/// validate_attack_rate parses the given string as a positive number of guesses, optionally
+/// followed by a time unit among /s, /min, /h and /d, and returns it as guesses per second.
+fn validate_attack_rate(s: &str) -> Result<f64, String> {
+    let (guesses, seconds) = match s.split_once('/') {
+        None | Some((_, "s")) => (s.trim_end_matches("/s"), 1.0),
+        Some((guesses, "min")) => (guesses, 60.0),
+        Some((guesses, "h")) => (guesses, 3600.0),
+        Some((guesses, "d")) => (guesses, 86400.0),
+        Some(_) => return Err("The attack rate unit must be one of /s, /min, /h or /d".to_string()),
+    };
+
+    match guesses.parse::<f64>() {
+        Ok(n) if n.is_finite() && n > 0.0 => Ok(n / seconds),
+        Ok(_) => Err("The attack rate must be a positive number".to_string()),
+        Err(_) => Err("The attack rate must be a number, such as 1e12/s".to_string()),
+    }
+}
+
+/// validate_ttl parses the given string as a positive number of seconds, optionally followed by
+/// a time unit among s, m and h, and returns it as a duration.
+fn validate_ttl(s: &str) -> Result<Duration, String> {
+    let (value, seconds) = match s.find(|c: char| !c.is_ascii_digit()) {
+        None => (s, 1),
+        Some(index) => match s.split_at(index) {
+            (value, "s") => (value, 1),
+            (value, "m") => (value, 60),
+            (value, "h") => (value, 3600),
+            _ => return Err("The duration unit must be one of s, m or h".to_string()),
+        },
+    };
+
+    match value.parse::<u64>() {
+        Ok(n) if n > 0 => Ok(Duration::from_secs(n.saturating_mul(seconds))),
+        Ok(_) => Err("The duration must be positive".to_string()),
+        Err(_) => Err("The duration must be a whole number, such as 5m".to_string()),
+    }
+}
+
+/// validate_tag parses the given string as a key=value tag. Keys must be non-empty and made of
+/// ASCII letters, digits, dashes, underscores and dots; values can hold anything but semicolons,
+/// which separate tags in CSV output.
+fn validate_tag(s: &str) -> Result<(String, String), String> {
+    let Some((key, value)) = s.split_once('=') else {
+        return Err("The tag must be a key=value pair, such as env=prod".to_string());
+    };
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(
+            "The tag key must be made of letters, digits, dashes, underscores and dots".to_string(),
+        );
+    }
+    if value.contains(';') {
+        return Err("The tag value cannot contain semicolons".to_string());
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// validate_count parses the given string as a u32 and returns an error if it is not between
+/// 1 and 10000.
+fn validate_count(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (1..=10_000).contains(&n) => Ok(n),
+        Ok(_) => Err("The number of passwords must be between 1 and 10000".to_string()),
+        Err(_) => Err("The number of passwords must be an integer".to_string()),
+    }
+}
+
+/// validate_username_digits parses the given string as a u32 and returns an error if it is
+/// above the number of digits usernames may end with.
+fn validate_username_digits(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if n <= Usernames::MAX_DIGITS => Ok(n),
+        Ok(_) => Err(format!(
+            "The number of digits must be between 0 and {}",
+            Usernames::MAX_DIGITS
+        )),
+        Err(_) => Err("The number of digits must be an integer".to_string()),
+    }
+}
+
+/// validate_domain returns the given string, lowercased, if it is a domain name made of at least
+/// two labels of letters, digits and inner hyphens.
+fn validate_domain(s: &str) -> Result<String, String> {
+    let domain = s.trim_end_matches('.').to_ascii_lowercase();
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid_label = |label: &&str| {
+        (1..=63).contains(&label.len())
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+    };
+    if labels.len() < 2 || !labels.iter().all(valid_label) {
+        return Err(format!("{s} is not a domain name, such as example.com"));
+    }
+    Ok(domain)
+}
+
+/// validate_mailbox returns the given string if it is the local part of an address aliases can be
+/// plus-addressed to: letters, digits, dots, hyphens and underscores, without a plus sign.
+fn validate_mailbox(s: &str) -> Result<String, String> {
+    if s.is_empty()
+        || s.starts_with('.')
+        || s.ends_with('.')
+        || !s
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+    {
+        return Err(
+            "The mailbox must be the part of an address before the @, made of letters, digits, dots, hyphens and underscores".to_string(),
+        );
+    }
+    Ok(s.to_string())
+}
+
+/// validate_recovery_codes parses the given string as a u32 and returns an error if it is above
+/// 20.
+fn validate_recovery_codes(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if n <= 20 => Ok(n),
+        Ok(_) => Err("The number of recovery codes must be between 0 and 20".to_string()),
+        Err(_) => Err("The number of recovery codes must be an integer".to_string()),
+    }
+}
+
+/// validate_answer_count parses the given string as a u32 and returns an error if it is not
+/// between 1 and 10.
+fn validate_answer_count(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (1..=10).contains(&n) => Ok(n),
+        Ok(_) => Err("The number of answers must be between 1 and 10".to_string()),
+        Err(_) => Err("The number of answers must be an integer".to_string()),
+    }
+}
+
+/// validate_card_rows parses the given string as a u32 and returns an error if it is not between
+/// 1 and 50.
+fn validate_card_rows(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (1..=50).contains(&n) => Ok(n),
+        Ok(_) => Err("The number of rows must be between 1 and 50".to_string()),
+        Err(_) => Err("The number of rows must be an integer".to_string()),
+    }
+}
+
+/// validate_card_columns parses the given string as a u32 and returns an error if it is not
+/// between 4 and 26, one column per letter of the alphabet.
+fn validate_card_columns(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (4..=26).contains(&n) => Ok(n),
+        Ok(_) => Err("The number of columns must be between 4 and 26".to_string()),
+        Err(_) => Err("The number of columns must be an integer".to_string()),
+    }
+}
+
+/// validate_fake_count parses the given string as a u32 and returns an error if it is not between
+/// 1 and 1000000.
+fn validate_fake_count(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if (1..=1_000_000).contains(&n) => Ok(n),
+        Ok(_) => Err("The number of passwords must be between 1 and 1000000".to_string()),
+        Err(_) => Err("The number of passwords must be an integer".to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_validate_attack_rate() {
+        assert_eq!(validate_attack_rate("1e12"), Ok(1e12));
+        assert_eq!(validate_attack_rate("1e12/s"), Ok(1e12));
+        assert_eq!(validate_attack_rate("120/min"), Ok(2.0));
+        assert_eq!(validate_attack_rate("7200/h"), Ok(2.0));
+        assert!(validate_attack_rate("0").is_err());
+        assert!(validate_attack_rate("10/week").is_err());
+        assert!(validate_attack_rate("fast").is_err());
+    }
+
+    #[test]
+    fn test_validate_ttl() {
+        assert_eq!(validate_ttl("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(validate_ttl("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(validate_ttl("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(validate_ttl("1h"), Ok(Duration::from_secs(3600)));
+        assert!(validate_ttl("0m").is_err());
+        assert!(validate_ttl("5d").is_err());
+        assert!(validate_ttl("m").is_err());
+    }
+
+    #[test]
+    fn test_validate_tag() {
+        assert_eq!(
+            validate_tag("env=prod"),
+            Ok(("env".to_string(), "prod".to_string()))
+        );
+        assert_eq!(
+            validate_tag("owner=ops=team"),
+            Ok(("owner".to_string(), "ops=team".to_string()))
+        );
+        assert_eq!(
+            validate_tag("note="),
+            Ok(("note".to_string(), String::new()))
+        );
+        assert!(validate_tag("env").is_err());
+        assert!(validate_tag("=prod").is_err());
+        assert!(validate_tag("my env=prod").is_err());
+        assert!(validate_tag("env=a;b").is_err());
+    }
+
+    #[test]
+    fn test_group() {
+        assert_eq!(group("ab3d9fghk", Some(4), "-"), "ab3d-9fgh-k");
+        assert_eq!(group("ab3d9fgh", Some(4), " "), "ab3d 9fgh");
+        assert_eq!(group("ab3d9fgh", None, "-"), "ab3d9fgh");
+    }
+
+    #[test]
+    fn test_validate_group_size() {
+        assert!(validate_group_size("1").is_err());
+        assert!(validate_group_size("4").is_ok());
+        assert!(validate_group_size("17").is_err());
+    }
+
+    #[test]
+    fn test_entropy_floor_auto_strengthen() {
+        let floor = EntropyFloor {
+            bits: Some(20),
+            auto_strengthen: true,
+            output: OutputFormat::Text,
+        };
+
+        // Each digit adds log2(10) ~= 3.3 bits
+        assert_eq!(floor.enforce("--numbers", 3, 12, motus::entropy::pin), 7);
+        assert_eq!(floor.enforce("--numbers", 9, 12, motus::entropy::pin), 9);
+    }
+
     #[test]
     fn test_validate_word_count() {
         assert!(validate_word_count("2").is_err());
@@ -479,6 +4275,39 @@ mod tests {
         assert!(validate_word_count("16").is_err());
     }
 
+    #[test]
+    fn test_validate_separator_count() {
+        assert!(validate_separator_count("0").is_err());
+        assert!(validate_separator_count("1").is_ok());
+        assert!(validate_separator_count("5").is_ok());
+        assert!(validate_separator_count("6").is_err());
+    }
+
+    #[test]
+    fn test_validate_max_repeat() {
+        assert!(validate_max_repeat("0").is_err());
+        assert!(validate_max_repeat("1").is_ok());
+        assert!(validate_max_repeat("100").is_ok());
+        assert!(validate_max_repeat("101").is_err());
+    }
+
+    #[test]
+    fn test_validate_card_size() {
+        assert!(validate_card_rows("0").is_err());
+        assert!(validate_card_rows("50").is_ok());
+        assert!(validate_card_columns("3").is_err());
+        assert!(validate_card_columns("26").is_ok());
+        assert!(validate_card_columns("27").is_err());
+    }
+
+    #[test]
+    fn test_validate_max_syllables() {
+        assert!(validate_max_syllables("0").is_err());
+        assert!(validate_max_syllables("1").is_ok());
+        assert!(validate_max_syllables("10").is_ok());
+        assert!(validate_max_syllables("11").is_err());
+    }
+
     #[test]
     fn test_validate_character_count() {
         assert!(validate_character_count("7").is_err());
@@ -487,6 +4316,17 @@ mod tests {
         assert!(validate_character_count("101").is_err());
     }
 
+    #[test]
+    fn test_validate_length_budget() {
+        assert_eq!(
+            validate_length_budget("0"),
+            Err("The length budget must be between 8 and 100 characters".to_string())
+        );
+        assert!(validate_length_budget("8").is_ok());
+        assert!(validate_length_budget("100").is_ok());
+        assert!(validate_length_budget("101").is_err());
+    }
+
     #[test]
     fn test_validate_pin_length() {
         assert!(validate_pin_length("2").is_err());
@@ -494,4 +4334,63 @@ mod tests {
         assert!(validate_pin_length("12").is_ok());
         assert!(validate_pin_length("13").is_err());
     }
+
+    #[test]
+    fn test_validate_fake_count() {
+        assert!(validate_fake_count("0").is_err());
+        assert!(validate_fake_count("1").is_ok());
+        assert!(validate_fake_count("1000000").is_ok());
+        assert!(validate_fake_count("1000001").is_err());
+    }
+
+    #[test]
+    fn test_validate_username_digits() {
+        assert!(validate_username_digits("0").is_ok());
+        assert!(validate_username_digits("9").is_ok());
+        assert!(validate_username_digits("10").is_err());
+        assert!(validate_username_digits("two").is_err());
+    }
+
+    #[test]
+    fn test_validate_domain() {
+        assert_eq!(
+            validate_domain("Example.COM."),
+            Ok("example.com".to_string())
+        );
+        assert!(validate_domain("mail.example-1.org").is_ok());
+        assert!(validate_domain("localhost").is_err());
+        assert!(validate_domain("-example.com").is_err());
+        assert!(validate_domain("exa mple.com").is_err());
+        assert!(validate_domain("example..com").is_err());
+    }
+
+    #[test]
+    fn test_validate_mailbox() {
+        assert!(validate_mailbox("jane.doe").is_ok());
+        assert!(validate_mailbox("jane+news").is_err());
+        assert!(validate_mailbox("jane@example.com").is_err());
+        assert!(validate_mailbox(".jane").is_err());
+        assert!(validate_mailbox("").is_err());
+    }
+
+    #[test]
+    fn test_validate_recovery_codes() {
+        assert!(validate_recovery_codes("0").is_ok());
+        assert!(validate_recovery_codes("20").is_ok());
+        assert!(validate_recovery_codes("21").is_err());
+    }
+
+    #[test]
+    fn test_validate_answer_count() {
+        assert!(validate_answer_count("0").is_err());
+        assert!(validate_answer_count("10").is_ok());
+        assert!(validate_answer_count("11").is_err());
+    }
+
+    #[test]
+    fn test_csv_field() {
+        assert_eq!(csv_field("abc"), "abc");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+    }
 }
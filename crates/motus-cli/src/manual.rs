@@ -0,0 +1,287 @@
+use std::io::{self, BufRead, BufWriter, Write};
+
+use colored::Colorize;
+use motus::manual::{Derivation, Outcomes, Source};
+use motus::secret::Secret;
+use serde::Serialize;
+
+use crate::explain::EntropyReport;
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+use crate::{csv_field, OutputFormat};
+
+/// read_outcomes reads `count` more outcomes from `input`, line by line, appending them to
+/// `outcomes`, and prompts for them on stderr when `prompt` is set.
+///
+/// When `interactive` is set, outcomes are prompted for one at a time, and a line that does not
+/// hold exactly one valid outcome is asked for again instead of failing, so that a typo does not
+/// waste the outcomes typed so far.
+pub fn read_outcomes(
+    mut input: impl BufRead,
+    outcomes: &mut Outcomes,
+    count: usize,
+    prompt: bool,
+    interactive: bool,
+) -> Result<(), String> {
+    let name = outcomes.source().outcome_name();
+    let expected = outcomes.len() + count;
+    while outcomes.len() < expected {
+        if interactive {
+            eprint!(
+                "{} {} of {}: ",
+                capitalize(name),
+                outcomes.len() + 1,
+                expected
+            );
+        } else if prompt {
+            eprint!(
+                "{}s ({} of {} typed): ",
+                capitalize(name),
+                outcomes.len(),
+                expected
+            );
+        }
+
+        let mut line = String::new();
+        let read = input
+            .read_line(&mut line)
+            .map_err(|err| format!("unable to read the {}s: {}", name, err))?;
+        if read == 0 {
+            return Err(format!(
+                "expected {} {}s, got {}",
+                expected,
+                name,
+                outcomes.len()
+            ));
+        }
+
+        if !interactive {
+            outcomes.push_str(&line).map_err(|err| err.to_string())?;
+            continue;
+        }
+
+        let mut next = outcomes.clone();
+        match next.push_str(&line) {
+            Ok(()) if next.len() == outcomes.len() + 1 => *outcomes = next,
+            Ok(()) if next.len() == outcomes.len() => {}
+            Ok(()) => eprintln!("type a single {}, then press Enter", name),
+            Err(err) => eprintln!("{}; try again", err),
+        }
+    }
+
+    if outcomes.len() > expected {
+        return Err(format!(
+            "expected {} {}s, got {}",
+            expected,
+            name,
+            outcomes.len()
+        ));
+    }
+    Ok(())
+}
+
+/// ManualReport is the password derived from physical outcomes, and how it was derived from
+/// them.
+pub struct ManualReport<'a> {
+    pub password: Secret,
+    pub outcomes: &'a Outcomes,
+    pub derivation: Derivation,
+    pub candidates: &'static str,
+    pub entropy: EntropyReport,
+}
+
+impl ManualReport<'_> {
+    /// display writes the password to stdout in the requested format, followed by its entropy and
+    /// the steps deriving it from the outcomes when `explain` is set.
+    pub fn display(&self, explain: bool, format: &OutputFormat) -> io::Result<()> {
+        match format {
+            OutputFormat::Text
+            | OutputFormat::SystemdCred
+            | OutputFormat::Sops
+            | OutputFormat::Html => {
+                println!("{}", self.password.expose());
+                eprintln!(
+                    "note: collected {:.1} bits from {} {}s, of which the password holds {:.1}",
+                    self.outcomes.bits(),
+                    self.outcomes.len(),
+                    self.outcomes.source().outcome_name(),
+                    self.derivation.bits()
+                );
+                if explain {
+                    self.entropy.display(TableStyle::extended(), 80);
+                    self.display_derivation(TableStyle::extended(), 80);
+                }
+                Ok(())
+            }
+            OutputFormat::Json => {
+                let mut out = BufWriter::new(io::stdout().lock());
+                serde_json::to_writer(&mut out, &self.output(explain))?;
+                writeln!(out)?;
+                out.flush()
+            }
+            OutputFormat::Csv => {
+                println!("password");
+                println!("{}", csv_field(self.password.expose()));
+                Ok(())
+            }
+        }
+    }
+
+    fn display_derivation(&self, table_style: TableStyle, max_width: usize) {
+        let source = self.outcomes.source();
+        let candidates = self.derivation.candidates();
+
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder("Derivation")
+            .col_span(3)
+            .alignment(Alignment::Left)
+            .build()]));
+        table.add_row(Row::new(vec![
+            TableCell::new(format!("{}s", capitalize(source.outcome_name())).bold()),
+            TableCell::new("Number".bold()),
+            TableCell::new("Result".bold()),
+        ]));
+
+        for draw in self.derivation.draws() {
+            let result = match draw.index {
+                Some(index) => format!("{} {}", self.candidates, index),
+                None => format!("rejected, not below {}", draw.limit),
+            };
+            table.add_row(Row::new(vec![
+                TableCell::new(self.labels(draw.outcomes.clone()).join(" ")),
+                TableCell::builder(format!("{} of {}", draw.value, draw.range))
+                    .alignment(Alignment::Right)
+                    .build(),
+                TableCell::new(result),
+            ]));
+        }
+
+        println!("{}", table.render());
+        let digits = match source {
+            Source::Dice { sides } => format!("Each roll is a digit in base {}, the roll minus 1", sides),
+            Source::Coins => "Each flip is a binary digit, 1 for heads and 0 for tails".to_string(),
+            Source::Deck => "Each card is a digit in base the number of cards left in the deck, its position among them from 0, in the order ace to king of spades, hearts, diamonds then clubs".to_string(),
+        };
+        println!(
+            "{}, and consecutive digits make a number, most significant first. Numbers at or above the largest multiple of {} the {}s can make are rejected, for every {} to be equally likely; the others, modulo {}, are the index of a {}, from 0.",
+            digits, candidates, source.outcome_name(), self.candidates, candidates, self.candidates
+        );
+        if self.derivation.unused() > 0 {
+            println!(
+                "The last {} {}s were too few to make a number, and were not used.",
+                self.derivation.unused(),
+                source.outcome_name()
+            );
+        }
+    }
+
+    fn labels(&self, range: std::ops::Range<usize>) -> Vec<&str> {
+        self.outcomes.outcomes()[range]
+            .iter()
+            .map(|outcome| outcome.label.as_str())
+            .collect()
+    }
+
+    fn output(&self, explain: bool) -> ManualOutput<'_> {
+        ManualOutput {
+            password: self.password.expose(),
+            collected_bits: (self.outcomes.bits() * 10.0).round() / 10.0,
+            entropy: explain.then_some(&self.entropy),
+            derivation: explain.then(|| {
+                self.derivation
+                    .draws()
+                    .iter()
+                    .map(|draw| DrawOutput {
+                        outcomes: self.labels(draw.outcomes.clone()),
+                        value: draw.value,
+                        range: draw.range,
+                        limit: draw.limit,
+                        index: draw.index,
+                    })
+                    .collect()
+            }),
+        }
+    }
+}
+
+// capitalize returns `word` with its first letter in uppercase.
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|first| first.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+#[derive(Serialize)]
+struct ManualOutput<'a> {
+    password: &'a str,
+    collected_bits: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entropy: Option<&'a EntropyReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    derivation: Option<Vec<DrawOutput<'a>>>,
+}
+
+#[derive(Serialize)]
+struct DrawOutput<'a> {
+    outcomes: Vec<&'a str>,
+    value: u64,
+    range: u64,
+    limit: u64,
+    index: Option<usize>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const D6: Source = Source::Dice { sides: 6 };
+
+    #[test]
+    fn test_read_outcomes() {
+        let read = |input: &str, count| {
+            let mut rolls = Outcomes::new(D6);
+            read_outcomes(input.as_bytes(), &mut rolls, count, false, false).map(|()| rolls)
+        };
+
+        assert_eq!(
+            read("3 5 1\n6 2\n", 5),
+            Ok(Outcomes::parse_dice("35162", 6).unwrap())
+        );
+        assert_eq!(
+            read("3 5 1\n", 5),
+            Err("expected 5 rolls, got 3".to_string())
+        );
+        assert_eq!(
+            read("3 5 1 6 2 4\n", 5),
+            Err("expected 5 rolls, got 6".to_string())
+        );
+        assert_eq!(
+            read("3 5 9\n", 5),
+            Err("a 6-sided die cannot roll 9".to_string())
+        );
+
+        // More outcomes are appended to the ones already read
+        let mut rolls = Outcomes::parse_dice("35", 6).unwrap();
+        read_outcomes("1 6 2\n".as_bytes(), &mut rolls, 3, false, false).unwrap();
+        assert_eq!(rolls, Outcomes::parse_dice("35162", 6).unwrap());
+    }
+
+    #[test]
+    fn test_read_outcomes_interactively() {
+        // Invalid, repeated and several outcomes at once are asked for again
+        let input = "QS\nQS\n\nKH 2C\n1S\n10h\n";
+        let mut cards = Outcomes::new(Source::Deck);
+        read_outcomes(input.as_bytes(), &mut cards, 2, false, true).unwrap();
+        assert_eq!(cards, Outcomes::parse(Source::Deck, "QS 10H").unwrap());
+
+        let mut flips = Outcomes::new(Source::Coins);
+        assert_eq!(
+            read_outcomes("H\nX\n".as_bytes(), &mut flips, 3, false, true),
+            Err("expected 3 flips, got 1".to_string())
+        );
+    }
+}
@@ -0,0 +1,440 @@
+use std::ffi::OsString;
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read, Write};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use motus::denylist::DenyList;
+use motus::policy::Policy;
+use motus::sites::Database;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::policy::{self, PolicyError};
+
+/// HOST_NAME is the name browser extensions connect to the native messaging host with.
+pub const HOST_NAME: &str = "io.github.oleiade.motus";
+
+// MAX_REQUEST_LEN is the largest request the host reads, in bytes. Requests are small JSON
+// objects, and the limit keeps a misbehaving extension from making the host allocate gigabytes.
+const MAX_REQUEST_LEN: u32 = 64 * 1024;
+
+// CHARACTERS is the range of lengths requests may ask for, the same as the random command's.
+const CHARACTERS: RangeInclusive<u32> = 8..=100;
+
+// DEFAULT_CHARACTERS is the length of the passwords generated when the request does not give
+// one, before fitting it to the site's policy.
+const DEFAULT_CHARACTERS: u32 = 20;
+
+/// Browser is a browser native messaging host manifests are written for.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Browser {
+    /// Chrome, Chromium, and the browsers based on them, such as Edge and Brave
+    Chrome,
+    /// Firefox
+    Firefox,
+}
+
+/// manifest returns the native messaging host manifest allowing the browser extension whose ID
+/// is given to start motus, at `path`, as its host.
+pub fn manifest(browser: Browser, extension_id: &str, path: &Path) -> serde_json::Value {
+    let mut manifest = json!({
+        "name": HOST_NAME,
+        "description": "motus password generator",
+        "path": path,
+        "type": "stdio",
+    });
+    match browser {
+        Browser::Chrome => {
+            manifest["allowed_origins"] = json!([format!("chrome-extension://{extension_id}/")])
+        }
+        Browser::Firefox => manifest["allowed_extensions"] = json!([extension_id]),
+    }
+    manifest
+}
+
+/// launch_args returns the command line arguments motus was started with, replaced by the
+/// `native-host` subcommand when a browser started it as a native messaging host.
+///
+/// Browsers start hosts with arguments of their own, and manifests cannot add any: Chrome passes
+/// the origin of the extension, `chrome-extension://ID/`, and Firefox the path of the manifest
+/// followed by the ID of the extension.
+pub fn launch_args(args: Vec<OsString>) -> Vec<OsString> {
+    let launched = match args.as_slice() {
+        [_, origin, ..] if origin.to_string_lossy().starts_with("chrome-extension://") => true,
+        [_, manifest, _] => Path::new(manifest)
+            .extension()
+            .is_some_and(|extension| extension == "json"),
+        _ => false,
+    };
+
+    if launched {
+        vec![args[0].clone(), OsString::from("native-host")]
+    } else {
+        args
+    }
+}
+
+/// default_sites_dir returns the default location of the per-domain policy files, in the user's
+/// configuration directory.
+pub fn default_sites_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("motus")
+        .join("sites")
+}
+
+/// Request is a message a browser extension sends to the host.
+#[derive(Deserialize, Debug, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Request {
+    /// Checks that the host is installed, answered with its version.
+    Ping,
+    /// Asks for a random password for the site at `domain`, complying with its policy.
+    Generate {
+        domain: Option<String>,
+        characters: Option<u32>,
+        #[serde(default = "enabled")]
+        numbers: bool,
+        #[serde(default = "enabled")]
+        symbols: bool,
+    },
+}
+
+// enabled is the default of the character sets requests may disable.
+const fn enabled() -> bool {
+    true
+}
+
+/// Response is the host's answer to a request.
+#[derive(Serialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Response {
+    Pong {
+        version: &'static str,
+    },
+    Password {
+        password: String,
+        entropy_bits: f64,
+        /// The domain whose policy the password complies with, if any
+        policy: Option<String>,
+    },
+    Error {
+        error: String,
+    },
+}
+
+/// NativeHost answers the requests of a browser extension with the native messaging protocol:
+/// each message is a JSON object, preceded by its length in bytes as a 32-bit integer in native
+/// byte order, on the standard streams.
+///
+/// Passwords are generated with the policy of the requested site, read from `<domain>.toml` in
+/// the sites directory, or from the file of its closest parent domain, such as `example.com.toml`
+/// for `login.example.com`. Sites without a policy file follow their rules from the site
+/// database, if it knows them. Passwords are never copied to the clipboard nor recorded in the
+/// history: the extension fills them in.
+pub struct NativeHost<'a> {
+    sites: PathBuf,
+    database: &'a Database,
+    deny_list: Option<&'a DenyList>,
+    rng: &'a mut dyn RngCore,
+}
+
+impl<'a> NativeHost<'a> {
+    /// Creates a host reading site policies from `sites`, falling back to the rules of
+    /// `database`, and keeping the terms of `deny_list` out of every password.
+    pub fn new(
+        sites: PathBuf,
+        database: &'a Database,
+        deny_list: Option<&'a DenyList>,
+        rng: &'a mut dyn RngCore,
+    ) -> Self {
+        Self {
+            sites,
+            database,
+            deny_list,
+            rng,
+        }
+    }
+
+    /// Answers the messages read from `input` on `output`, until `input` is closed, which
+    /// browsers do when the extension disconnects.
+    pub fn serve(
+        &mut self,
+        mut input: impl Read,
+        mut output: impl Write,
+    ) -> Result<(), NativeError> {
+        while let Some(message) = read_message(&mut input)? {
+            let response = match serde_json::from_slice::<Request>(&message) {
+                Ok(request) => self.answer(request),
+                Err(err) => Response::Error {
+                    error: format!("invalid request: {err}"),
+                },
+            };
+            write_message(&mut output, &response)?;
+        }
+
+        Ok(())
+    }
+
+    // answer returns the response to a single request.
+    fn answer(&mut self, request: Request) -> Response {
+        match request {
+            Request::Ping => Response::Pong {
+                version: env!("CARGO_PKG_VERSION"),
+            },
+            Request::Generate {
+                domain,
+                characters,
+                numbers,
+                symbols,
+            } => {
+                let characters = characters.unwrap_or(DEFAULT_CHARACTERS);
+                if !CHARACTERS.contains(&characters) {
+                    return Response::Error {
+                        error: format!(
+                            "the number of characters must be between {} and {}",
+                            CHARACTERS.start(),
+                            CHARACTERS.end()
+                        ),
+                    };
+                }
+
+                let (policy, excluded, source) =
+                    match domain.as_deref().map(|d| self.site_policy(d)) {
+                        Some(Ok(found)) => found,
+                        Some(Err(err)) => {
+                            return Response::Error {
+                                error: err.to_string(),
+                            }
+                        }
+                        None => (Policy::new(), Vec::new(), None),
+                    };
+                let policy = match self.deny_list {
+                    Some(deny_list) => {
+                        let terms = policy.deny_list().terms().iter().chain(deny_list.terms());
+                        let merged = DenyList::new(terms);
+                        policy.with_deny_list(merged)
+                    }
+                    None => policy,
+                };
+
+                let (characters, numbers, symbols) = policy.fit(characters, numbers, symbols);
+                let password =
+                    policy.random_password(&mut self.rng, characters, numbers, symbols, &excluded);
+
+                Response::Password {
+                    password,
+                    entropy_bits: policy
+                        .random_entropy(characters, numbers, symbols, &excluded)
+                        .bits(),
+                    policy: source,
+                }
+            }
+        }
+    }
+
+    // site_policy returns the policy of the site at `domain` and the characters it rejects, along
+    // with the domain whose policy file or database rules they come from, or an empty policy if
+    // there are none.
+    fn site_policy(
+        &self,
+        domain: &str,
+    ) -> Result<(Policy, Vec<char>, Option<String>), NativeError> {
+        let domain = crate::validate_domain(domain).map_err(NativeError::Domain)?;
+
+        let mut candidate = domain.as_str();
+        while candidate.contains('.') {
+            let path = self.sites.join(format!("{candidate}.toml"));
+            if path.is_file() {
+                let policy = policy::load(&path).map_err(|err| NativeError::Policy(path, err))?;
+                return Ok((policy, Vec::new(), Some(candidate.to_string())));
+            }
+            candidate = candidate.split_once('.').map_or("", |(_, parent)| parent);
+        }
+
+        Ok(match self.database.lookup(&domain) {
+            Some((found, rules)) => (rules.policy(), rules.excluded(), Some(found.to_string())),
+            None => (Policy::new(), Vec::new(), None),
+        })
+    }
+}
+
+// read_message reads a message from `input`, or returns None if it is closed.
+fn read_message(input: &mut impl Read) -> Result<Option<Vec<u8>>, NativeError> {
+    let mut len = [0; 4];
+    match input.read_exact(&mut len) {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+
+    let len = u32::from_ne_bytes(len);
+    if len > MAX_REQUEST_LEN {
+        return Err(NativeError::TooLong(len));
+    }
+    let mut message = vec![0; len as usize];
+    input.read_exact(&mut message)?;
+    Ok(Some(message))
+}
+
+// write_message writes `response` to `output` as a message.
+fn write_message(output: &mut impl Write, response: &Response) -> Result<(), NativeError> {
+    let message = serde_json::to_vec(response).map_err(io::Error::from)?;
+    let len = u32::try_from(message.len()).expect("responses should be way below 4 GB");
+    output.write_all(&len.to_ne_bytes())?;
+    output.write_all(&message)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// NativeError represents the errors that can occur while answering a browser extension.
+#[derive(Debug)]
+pub enum NativeError {
+    Io(io::Error),
+    TooLong(u32),
+    Domain(String),
+    Policy(PathBuf, PolicyError),
+}
+
+impl Display for NativeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NativeError::Io(err) => write!(f, "unable to talk to the browser: {}", err),
+            NativeError::TooLong(len) => write!(
+                f,
+                "the browser sent a message of {} bytes, more than the {} allowed",
+                len, MAX_REQUEST_LEN
+            ),
+            NativeError::Domain(err) => write!(f, "{}", err),
+            NativeError::Policy(path, err) => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+
+impl From<io::Error> for NativeError {
+    fn from(err: io::Error) -> Self {
+        NativeError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    // exchange sends the requests to a host reading site policies from `sites`, and returns its
+    // responses.
+    fn exchange(sites: &Path, requests: &[serde_json::Value]) -> Vec<serde_json::Value> {
+        let mut input = Vec::new();
+        for request in requests {
+            let message = serde_json::to_vec(request).unwrap();
+            input.extend((message.len() as u32).to_ne_bytes());
+            input.extend(message);
+        }
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut output = Vec::new();
+        NativeHost::new(sites.to_path_buf(), Database::embedded(), None, &mut rng)
+            .serve(input.as_slice(), &mut output)
+            .unwrap();
+
+        let mut responses = Vec::new();
+        let mut output = output.as_slice();
+        while let Some(message) = read_message(&mut output).unwrap() {
+            responses.push(serde_json::from_slice(&message).unwrap());
+        }
+        responses
+    }
+
+    #[test]
+    fn test_native_host_site_policy() {
+        let sites = tempfile::tempdir().unwrap();
+        std::fs::write(
+            sites.path().join("example.com.toml"),
+            "max_length = 12\nrequire = [\"digit\"]\n",
+        )
+        .unwrap();
+
+        let responses = exchange(
+            sites.path(),
+            &[
+                json!({"type": "ping"}),
+                json!({"type": "generate", "domain": "login.Example.com", "symbols": false}),
+                json!({"type": "generate", "domain": "example.org"}),
+                json!({"type": "generate", "domain": "../etc"}),
+                json!({"type": "generate", "characters": 1_000_000_000}),
+                json!({"type": "unknown"}),
+                json!({"type": "generate", "domain": "www.americanexpress.com", "characters": 30}),
+            ],
+        );
+
+        assert_eq!(responses[0]["type"], "pong");
+
+        let password = responses[1]["password"].as_str().unwrap();
+        assert_eq!(password.len(), 12);
+        assert!(password.chars().any(|c| c.is_ascii_digit()));
+        assert!(password.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert_eq!(responses[1]["policy"], "example.com");
+
+        assert_eq!(responses[2]["password"].as_str().unwrap().len(), 20);
+        assert_eq!(responses[2]["policy"], serde_json::Value::Null);
+
+        assert_eq!(responses[3]["type"], "error");
+        assert_eq!(responses[4]["type"], "error");
+        assert_eq!(responses[5]["type"], "error");
+
+        let password = responses[6]["password"].as_str().unwrap();
+        assert_eq!(password.len(), 20);
+        assert!(password
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "%&_?#=".contains(c)));
+        assert_eq!(responses[6]["policy"], "americanexpress.com");
+    }
+
+    #[test]
+    fn test_native_host_rejects_long_messages() {
+        let input = (MAX_REQUEST_LEN + 1).to_ne_bytes();
+        let mut rng = StdRng::seed_from_u64(42);
+        let result = NativeHost::new(PathBuf::new(), Database::embedded(), None, &mut rng)
+            .serve(&input[..], io::sink());
+        assert!(matches!(result, Err(NativeError::TooLong(_))));
+    }
+
+    #[test]
+    fn test_launch_args() {
+        let args = |args: &[&str]| args.iter().map(OsString::from).collect::<Vec<_>>();
+
+        assert_eq!(
+            launch_args(args(&["motus", "chrome-extension://abcdef/"])),
+            args(&["motus", "native-host"])
+        );
+        assert_eq!(
+            launch_args(args(&[
+                "motus",
+                "/usr/lib/mozilla/motus.json",
+                "motus@example.com"
+            ])),
+            args(&["motus", "native-host"])
+        );
+        assert_eq!(
+            launch_args(args(&["motus", "--seed", "42"])),
+            args(&["motus", "--seed", "42"])
+        );
+    }
+
+    #[test]
+    fn test_manifest() {
+        let manifest = manifest(
+            Browser::Firefox,
+            "motus@example.com",
+            Path::new("/bin/motus"),
+        );
+        assert_eq!(manifest["name"], HOST_NAME);
+        assert_eq!(manifest["allowed_extensions"][0], "motus@example.com");
+        assert_eq!(manifest["path"], "/bin/motus");
+    }
+}
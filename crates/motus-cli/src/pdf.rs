@@ -0,0 +1,401 @@
+//! Printable PDF export of analysis reports.
+//!
+//! Reports are short and made of text only, so the PDF is written by hand rather than with a
+//! layout engine: A4 pages, laid out line by line with the standard Helvetica and Courier fonts,
+//! which every PDF reader provides without embedding them.
+
+use std::fmt::Write;
+
+use motus::analysis::report::PasswordStrength;
+
+use crate::report::{Document, Style};
+
+// The A4 page size and the margins around the text, in points.
+const PAGE_WIDTH: f64 = 595.0;
+const PAGE_HEIGHT: f64 = 842.0;
+const MARGIN: f64 = 56.0;
+
+// LABEL_WIDTH is the width of the column of labels, values being laid out to its right.
+const LABEL_WIDTH: f64 = 190.0;
+
+const TITLE_SIZE: f64 = 18.0;
+const CAPTION_SIZE: f64 = 12.0;
+const TEXT_SIZE: f64 = 10.0;
+const LEADING: f64 = 14.0;
+
+type Color = (f64, f64, f64);
+
+const BLACK: Color = (0.12, 0.14, 0.16);
+const GRAY: Color = (0.4, 0.43, 0.46);
+const RED: Color = (0.81, 0.13, 0.18);
+const GREEN: Color = (0.18, 0.64, 0.31);
+
+/// Font is one of the standard fonts the report is written in.
+#[derive(Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+    Mono,
+}
+
+impl Font {
+    // resource returns the name the pages refer to the font with.
+    const fn resource(self) -> &'static str {
+        match self {
+            Font::Regular => "F1",
+            Font::Bold => "F2",
+            Font::Mono => "F3",
+        }
+    }
+
+    // width estimates the width of `text` at the given size. Courier's characters are all 0.6
+    // em wide, and Helvetica's average around 0.55 em, which errs on the side of wrapping early.
+    fn width(self, text: &str, size: f64) -> f64 {
+        let em = match self {
+            Font::Mono => 0.6,
+            Font::Regular | Font::Bold => 0.55,
+        };
+        text.chars().count() as f64 * em * size
+    }
+}
+
+/// render returns the document as a PDF file.
+pub fn render(document: &Document) -> Vec<u8> {
+    let mut pages = Pages::default();
+
+    pages.line(Font::Bold, TITLE_SIZE, MARGIN, document.title, BLACK);
+    pages.advance(LEADING);
+
+    for section in &document.sections {
+        pages.reserve(3.0 * LEADING);
+        pages.advance(LEADING);
+        pages.line(Font::Bold, CAPTION_SIZE, MARGIN, &section.caption, BLACK);
+        pages.rule();
+
+        for entry in &section.entries {
+            let (font, color) = match entry.style {
+                Style::Plain => (Font::Regular, BLACK),
+                Style::Code => (Font::Mono, BLACK),
+                Style::Strength(strength) => (Font::Bold, strength_color(strength)),
+                Style::Pass => (Font::Bold, GREEN),
+                Style::Fail => (Font::Bold, RED),
+            };
+
+            let (x, labels) = match &entry.label {
+                Some(label) => (
+                    MARGIN + LABEL_WIDTH,
+                    wrap(label, Font::Bold, TEXT_SIZE, LABEL_WIDTH - 10.0),
+                ),
+                None => (MARGIN, Vec::new()),
+            };
+            let values = wrap(&entry.value, font, TEXT_SIZE, PAGE_WIDTH - MARGIN - x);
+
+            let lines = labels.len().max(values.len());
+            pages.reserve(lines as f64 * LEADING);
+            for i in 0..lines {
+                if let Some(label) = labels.get(i) {
+                    pages.text(Font::Bold, TEXT_SIZE, MARGIN, label, BLACK);
+                }
+                if let Some(value) = values.get(i) {
+                    pages.text(font, TEXT_SIZE, x, value, color);
+                }
+                pages.advance(LEADING);
+            }
+        }
+    }
+
+    pages.advance(LEADING);
+    pages.reserve(LEADING);
+    pages.line(Font::Regular, 8.0, MARGIN, &document.footer, GRAY);
+
+    write(document, &pages.finish())
+}
+
+// strength_color returns the color of the strength, from red to green.
+const fn strength_color(strength: PasswordStrength) -> Color {
+    match strength {
+        PasswordStrength::VeryWeak => RED,
+        PasswordStrength::Weak => (0.98, 0.27, 0.29),
+        PasswordStrength::Reasonable => (0.6, 0.4, 0.0),
+        PasswordStrength::Strong => GREEN,
+        PasswordStrength::VeryStrong => (0.07, 0.39, 0.16),
+    }
+}
+
+// wrap splits `text` into lines fitting in `width` at the given size, breaking between words,
+// or within words too long for a line of their own, such as passwords.
+fn wrap(text: &str, font: Font, size: f64, width: f64) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split(' ') {
+        let candidate = if line.is_empty() {
+            word.to_string()
+        } else {
+            format!("{} {}", line, word)
+        };
+        if font.width(&candidate, size) <= width {
+            line = candidate;
+            continue;
+        }
+
+        if !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+        }
+        for c in word.chars() {
+            line.push(c);
+            if font.width(&line, size) > width {
+                line.pop();
+                lines.push(std::mem::replace(&mut line, c.to_string()));
+            }
+        }
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Pages lays text out from the top of each page to the bottom, starting new pages as needed.
+struct Pages {
+    done: Vec<String>,
+    content: String,
+    y: f64,
+}
+
+impl Default for Pages {
+    fn default() -> Self {
+        Pages {
+            done: Vec::new(),
+            content: String::new(),
+            y: PAGE_HEIGHT - MARGIN,
+        }
+    }
+}
+
+impl Pages {
+    // reserve starts a new page unless `height` fits above the bottom margin of the current one.
+    fn reserve(&mut self, height: f64) {
+        if self.y - height < MARGIN && !self.content.is_empty() {
+            self.done.push(std::mem::take(&mut self.content));
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+
+    // advance moves down to the next line.
+    fn advance(&mut self, height: f64) {
+        self.y -= height;
+    }
+
+    // line writes a line of text at `x`, and moves down past it.
+    fn line(&mut self, font: Font, size: f64, x: f64, text: &str, color: Color) {
+        self.text(font, size, x, text, color);
+        self.advance(size.max(LEADING));
+    }
+
+    // text writes text at `x` on the current line.
+    fn text(&mut self, font: Font, size: f64, x: f64, text: &str, color: Color) {
+        let (r, g, b) = color;
+        let _ = writeln!(
+            self.content,
+            "BT /{} {} Tf {} {} {} rg {:.2} {:.2} Td ({}) Tj ET",
+            font.resource(),
+            size,
+            r,
+            g,
+            b,
+            x,
+            self.y,
+            literal(text)
+        );
+    }
+
+    // rule draws a thin line across the page below the current line.
+    fn rule(&mut self) {
+        let y = self.y + LEADING - 4.0;
+        let _ = writeln!(
+            self.content,
+            "0.82 0.84 0.87 RG 0.5 w {:.2} {:.2} m {:.2} {:.2} l S",
+            MARGIN,
+            y,
+            PAGE_WIDTH - MARGIN,
+            y
+        );
+    }
+
+    // finish returns the content streams of every page.
+    fn finish(mut self) -> Vec<String> {
+        self.done.push(self.content);
+        self.done
+    }
+}
+
+// write assembles the PDF file: the catalog, the page tree, the fonts, the document
+// information, and a page object and a content stream for each page, followed by the cross
+// reference table locating each of them.
+fn write(document: &Document, pages: &[String]) -> Vec<u8> {
+    let fonts = ["Helvetica", "Helvetica-Bold", "Courier"];
+    let first_page = 3 + fonts.len() + 1;
+    let kids: Vec<String> = (0..pages.len())
+        .map(|i| format!("{} 0 R", first_page + 2 * i))
+        .collect();
+
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+    ];
+    for font in fonts {
+        objects.push(format!(
+            "<< /Type /Font /Subtype /Type1 /BaseFont /{} /Encoding /WinAnsiEncoding >>",
+            font
+        ));
+    }
+    objects.push(format!(
+        "<< /Title ({}) /Producer ({}) >>",
+        literal(document.title),
+        literal(&document.footer)
+    ));
+    for (i, content) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R /F2 4 0 R /F3 5 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            first_page + 2 * i + 1
+        ));
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{}endstream",
+            content.len(),
+            content
+        ));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        let _ = write!(pdf, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+    }
+
+    let xref = pdf.len();
+    let _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(pdf, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        first_page - 1,
+        xref
+    );
+
+    pdf.into_bytes()
+}
+
+// literal returns `text` as the content of a PDF string literal in the WinAnsi encoding of the
+// fonts, escaping the delimiters, and writing the characters beyond ASCII as octal escapes so
+// that the file remains plain ASCII. Characters the encoding lacks are replaced with '?'.
+fn literal(text: &str) -> String {
+    let mut literal = String::new();
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                literal.push('\\');
+                literal.push(c);
+            }
+            ' '..='~' => literal.push(c),
+            _ => {
+                let byte = match c {
+                    // Narrow no-break spaces group digits in French
+                    '\u{202f}' => 0xa0,
+                    '\u{a0}'..='\u{ff}' => c as u32,
+                    '€' => 0x80,
+                    '…' => 0x85,
+                    'Œ' => 0x8c,
+                    '’' => 0x92,
+                    '–' => 0x96,
+                    '—' => 0x97,
+                    'œ' => 0x9c,
+                    _ => '?' as u32,
+                };
+                let _ = write!(literal, "\\{:03o}", byte);
+            }
+        }
+    }
+    literal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{Entry, Section};
+
+    fn document(entries: usize) -> Document {
+        Document {
+            lang: "fr",
+            title: "Analyse de sécurité",
+            sections: vec![Section {
+                caption: "Temps (estimés)".to_string(),
+                entries: (0..entries)
+                    .map(|i| Entry {
+                        label: Some(format!("scénario {}", i)),
+                        value: "moins d'une seconde, moins qu'un clignement d'œil".to_string(),
+                        style: Style::Strength(PasswordStrength::Weak),
+                    })
+                    .collect(),
+            }],
+            footer: "Généré par motus".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_render_cross_references() {
+        let pdf = String::from_utf8(render(&document(3))).expect("the PDF should be ASCII");
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("(Temps \\(estim\\351s\\)) Tj"));
+        assert!(pdf.contains("clignement d'\\234il"));
+
+        let xref: usize = pdf
+            .lines()
+            .skip_while(|line| *line != "startxref")
+            .nth(1)
+            .and_then(|offset| offset.parse().ok())
+            .expect("the cross reference table should be located");
+        assert!(pdf[xref..].starts_with("xref\n"));
+        for (i, entry) in pdf[xref..].lines().skip(3).take(7).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", i + 1)));
+        }
+    }
+
+    #[test]
+    fn test_render_pages() {
+        let count = |pdf: Vec<u8>| {
+            String::from_utf8(pdf)
+                .unwrap()
+                .matches("/Type /Page ")
+                .count()
+        };
+        assert_eq!(count(render(&document(3))), 1);
+        assert!(count(render(&document(100))) > 1);
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("aaaa bbbb cccc", Font::Mono, 10.0, 60.0),
+            ["aaaa bbbb", "cccc"]
+        );
+        assert_eq!(
+            wrap("abcdefghijkl", Font::Mono, 10.0, 36.0),
+            ["abcdef", "ghijkl"]
+        );
+        assert_eq!(wrap("", Font::Mono, 10.0, 36.0), [""]);
+    }
+}
@@ -0,0 +1,513 @@
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use colored::Colorize;
+use motus::denylist::DenyList;
+use motus::policy::{Case, CharClass, Policy, Rule, StartWith};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use toml::Spanned;
+
+use crate::schema::{OutputSchema, Payload};
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+use crate::OutputFormat;
+
+/// PolicyFile is the format of policy files, as written by teams to describe the passwords
+/// their systems accept.
+///
+/// ```toml
+/// min_length = 12
+/// require = ["lowercase", "uppercase", "digit"]
+/// start_with = "letter"
+/// max_repeat = 2
+/// case = "mixed"
+/// deny_list = ["acme", "rocketship"]
+/// ```
+#[derive(Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PolicyFile {
+    min_length: Option<Spanned<usize>>,
+    max_length: Option<Spanned<usize>>,
+    #[serde(default)]
+    require: Vec<ClassName>,
+    min_classes: Option<Spanned<usize>>,
+    start_with: Option<StartWithName>,
+    max_repeat: Option<Spanned<usize>>,
+    case: Option<Spanned<CaseName>>,
+    #[serde(default)]
+    deny_list: Vec<String>,
+}
+
+impl PolicyFile {
+    // validate checks the values the file format cannot express constraints on, returning the
+    // location of the first offending value in `content` along with the error.
+    fn validate(&self, content: &str) -> Result<(), PolicyError> {
+        let invalid = |span: Range<usize>, message: &str| {
+            let (line, column) = location(content, span.start);
+            Err(PolicyError::Invalid {
+                line,
+                column,
+                message: message.to_string(),
+            })
+        };
+
+        for (value, name) in [
+            (&self.min_length, "min_length"),
+            (&self.max_length, "max_length"),
+            (&self.max_repeat, "max_repeat"),
+        ] {
+            if let Some(value) = value.as_ref().filter(|v| *v.get_ref() == 0) {
+                return invalid(value.span(), &format!("{name} must be at least 1"));
+            }
+        }
+
+        if let (Some(min), Some(max)) = (&self.min_length, &self.max_length) {
+            if max.get_ref() < min.get_ref() {
+                return invalid(max.span(), "max_length cannot be lower than min_length");
+            }
+        }
+
+        if let Some(min) = self
+            .min_classes
+            .as_ref()
+            .filter(|v| !(1..=4).contains(v.get_ref()))
+        {
+            return invalid(min.span(), "min_classes must be between 1 and 4");
+        }
+
+        if let Some(case) = &self.case {
+            let other_case = match case.get_ref() {
+                CaseName::Upper => Some(ClassName::Lowercase),
+                CaseName::Lower => Some(ClassName::Uppercase),
+                CaseName::Mixed => None,
+            };
+            let min_classes = self.min_classes.as_ref().map(|v| *v.get_ref());
+            if other_case.is_some_and(|other| self.require.contains(&other))
+                || (other_case.is_some() && min_classes == Some(4))
+            {
+                return invalid(
+                    case.span(),
+                    "case rules out letters that require or min_classes asks for",
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum ClassName {
+    Lowercase,
+    Uppercase,
+    Letter,
+    Digit,
+    Symbol,
+}
+
+impl From<ClassName> for CharClass {
+    fn from(class: ClassName) -> Self {
+        match class {
+            ClassName::Lowercase => CharClass::Lowercase,
+            ClassName::Uppercase => CharClass::Uppercase,
+            ClassName::Letter => CharClass::Letter,
+            ClassName::Digit => CharClass::Digit,
+            ClassName::Symbol => CharClass::Symbol,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum StartWithName {
+    Letter,
+    Any,
+}
+
+impl From<StartWithName> for StartWith {
+    fn from(start_with: StartWithName) -> Self {
+        match start_with {
+            StartWithName::Letter => StartWith::Letter,
+            StartWithName::Any => StartWith::Any,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+enum CaseName {
+    Upper,
+    Lower,
+    Mixed,
+}
+
+impl From<CaseName> for Case {
+    fn from(case: CaseName) -> Self {
+        match case {
+            CaseName::Upper => Case::Upper,
+            CaseName::Lower => Case::Lower,
+            CaseName::Mixed => Case::Mixed,
+        }
+    }
+}
+
+impl From<PolicyFile> for Policy {
+    fn from(file: PolicyFile) -> Self {
+        let mut policy = Policy::new();
+
+        if let Some(min) = file.min_length {
+            policy = policy.with_rule(Rule::MinLength(min.into_inner()));
+        }
+        if let Some(max) = file.max_length {
+            policy = policy.with_rule(Rule::MaxLength(max.into_inner()));
+        }
+        for class in file.require {
+            policy = policy.with_rule(Rule::Require(class.into()));
+        }
+        if let Some(min) = file.min_classes {
+            policy = policy.with_rule(Rule::MinClasses(min.into_inner()));
+        }
+        if let Some(start_with) = file.start_with {
+            policy = policy.with_rule(Rule::StartWith(start_with.into()));
+        }
+        if let Some(max) = file.max_repeat {
+            policy = policy.with_rule(Rule::MaxRepeat(max.into_inner()));
+        }
+        if let Some(case) = file.case {
+            policy = policy.with_rule(Rule::Case(case.into_inner().into()));
+        }
+        if !file.deny_list.is_empty() {
+            policy = policy.with_deny_list(DenyList::new(file.deny_list));
+        }
+
+        policy
+    }
+}
+
+/// load reads and parses the policy file at the given path.
+pub fn load(path: &Path) -> Result<Policy, PolicyError> {
+    parse(&fs::read_to_string(path)?)
+}
+
+/// parse parses the content of a policy file, rejecting unknown fields and out of range values.
+pub fn parse(content: &str) -> Result<Policy, PolicyError> {
+    let file: PolicyFile = toml::from_str(content)?;
+    file.validate(content)?;
+    Ok(file.into())
+}
+
+/// schema returns the JSON Schema describing the policy file format.
+pub fn schema() -> serde_json::Value {
+    let length = |description: &str| {
+        json!({
+            "description": description,
+            "type": "integer",
+            "minimum": 1,
+        })
+    };
+
+    json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "motus password policy",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "min_length": length("Minimum number of characters"),
+            "max_length": length("Maximum number of characters, no lower than min_length"),
+            "require": {
+                "description": "Classes of characters the password must contain at least one of",
+                "type": "array",
+                "items": {
+                    "enum": ["lowercase", "uppercase", "letter", "digit", "symbol"],
+                },
+            },
+            "min_classes": {
+                "description": "Minimum number of lowercase, uppercase, digit and symbol categories the password must use",
+                "type": "integer",
+                "minimum": 1,
+                "maximum": 4,
+            },
+            "start_with": {
+                "description": "Kind of character the password must start with",
+                "enum": ["letter", "any"],
+            },
+            "max_repeat": length("Maximum number of identical characters in a row"),
+            "case": {
+                "description": "Case every letter of the password must be of, for systems comparing passwords regardless of case",
+                "enum": ["upper", "lower", "mixed"],
+            },
+            "deny_list": {
+                "description": "Terms the password must not contain, regardless of case, such as company names, product names or usernames",
+                "type": "array",
+                "items": {"type": "string"},
+            },
+        },
+    })
+}
+
+// location returns the 1-based line and column of the given byte offset in `content`.
+fn location(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .map_or(0, |line| line.chars().count())
+        + 1;
+    (line, column)
+}
+
+/// CheckReport holds the outcome of validating a password against each rule of a policy.
+#[derive(Serialize)]
+pub struct CheckReport {
+    compliant: bool,
+    rules: Vec<RuleReport>,
+}
+
+#[derive(Serialize)]
+struct RuleReport {
+    rule: String,
+    passed: bool,
+}
+
+impl CheckReport {
+    /// Checks the password against every rule of the policy, and its deny list if any.
+    pub fn new(policy: &Policy, password: &str) -> Self {
+        let mut rules: Vec<RuleReport> = policy
+            .rules()
+            .iter()
+            .map(|rule| RuleReport {
+                rule: rule.to_string(),
+                passed: rule.is_satisfied_by(password),
+            })
+            .collect();
+        let deny_list = policy.deny_list();
+        if !deny_list.is_empty() {
+            rules.push(RuleReport {
+                rule: "contains no denied term".to_string(),
+                passed: deny_list.allows(password),
+            });
+        }
+
+        Self {
+            compliant: rules.iter().all(|rule| rule.passed),
+            rules,
+        }
+    }
+
+    /// Returns whether the password passed every rule.
+    pub fn is_compliant(&self) -> bool {
+        self.compliant
+    }
+
+    /// Returns the description of each rule, along with whether the password passed it.
+    #[cfg_attr(not(feature = "analysis"), allow(dead_code))]
+    pub fn rules(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.rules
+            .iter()
+            .map(|rule| (rule.rule.as_str(), rule.passed))
+    }
+
+    /// Writes the report to stdout in the requested format.
+    pub fn display(&self, output: &OutputFormat, schema: OutputSchema) {
+        match output {
+            OutputFormat::Text
+            | OutputFormat::SystemdCred
+            | OutputFormat::Sops
+            | OutputFormat::Html => self.display_table(TableStyle::extended(), 80),
+            OutputFormat::Json => println!("{}", schema.json(Payload::PolicyCheck, self)),
+            OutputFormat::Csv => {
+                println!("rule,passed");
+                for rule in &self.rules {
+                    println!("{},{}", crate::csv_field(&rule.rule), rule.passed);
+                }
+            }
+        }
+    }
+
+    fn display_table(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        let verdict = if self.compliant {
+            "compliant".green()
+        } else {
+            "not compliant".red()
+        };
+        table.add_row(Row::new(vec![TableCell::builder(format!(
+            "Policy check: {}",
+            verdict
+        ))
+        .col_span(2)
+        .alignment(Alignment::Left)
+        .build()]));
+
+        for rule in &self.rules {
+            let outcome = if rule.passed {
+                "pass".green()
+            } else {
+                "fail".red()
+            };
+            table.add_row(Row::new(vec![
+                TableCell::new(rule.rule.as_str().bold()),
+                TableCell::builder(outcome)
+                    .alignment(Alignment::Left)
+                    .build(),
+            ]));
+        }
+
+        println!("{}", table.render());
+    }
+}
+
+/// PolicyError represents the errors that can occur while loading a policy file.
+#[derive(Debug)]
+pub enum PolicyError {
+    Io(io::Error),
+    Parse(toml::de::Error),
+    Invalid {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+}
+
+impl Display for PolicyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PolicyError::Io(err) => write!(f, "unable to read the policy file: {}", err),
+            PolicyError::Parse(err) => write!(f, "invalid policy file: {}", err),
+            PolicyError::Invalid {
+                line,
+                column,
+                message,
+            } => write!(
+                f,
+                "invalid policy file: {} at line {}, column {}",
+                message, line, column
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for PolicyError {
+    fn from(err: io::Error) -> Self {
+        PolicyError::Io(err)
+    }
+}
+
+impl From<toml::de::Error> for PolicyError {
+    fn from(err: toml::de::Error) -> Self {
+        PolicyError::Parse(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_file_rules() {
+        let policy = parse(
+            r#"
+            min_length = 12
+            require = ["uppercase", "digit"]
+            start_with = "letter"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            policy.rules(),
+            &[
+                Rule::MinLength(12),
+                Rule::Require(CharClass::Uppercase),
+                Rule::Require(CharClass::Digit),
+                Rule::StartWith(StartWith::Letter),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_policy_file_rejects_unknown_fields() {
+        assert!(toml::from_str::<PolicyFile>("min_lenght = 12").is_err());
+    }
+
+    #[test]
+    fn test_policy_file_reports_invalid_value_location() {
+        let err = parse("min_length = 12\nmax_length = 8\n").unwrap_err();
+        assert!(matches!(
+            err,
+            PolicyError::Invalid {
+                line: 2,
+                column: 14,
+                ..
+            }
+        ));
+
+        let err = parse("min_classes = 5").unwrap_err();
+        assert!(matches!(
+            err,
+            PolicyError::Invalid {
+                line: 1,
+                column: 15,
+                ..
+            }
+        ));
+
+        assert!(parse("max_repeat = 0").is_err());
+
+        let err = parse("require = [\"uppercase\"]\ncase = \"lower\"\n").unwrap_err();
+        assert!(matches!(
+            err,
+            PolicyError::Invalid {
+                line: 2,
+                column: 8,
+                ..
+            }
+        ));
+        assert_eq!(
+            parse("case = \"lower\"").unwrap().rules(),
+            &[Rule::Case(Case::Lower)]
+        );
+    }
+
+    #[test]
+    fn test_check_report() {
+        let policy = Policy::new()
+            .with_rule(Rule::MinLength(12))
+            .with_rule(Rule::Require(CharClass::Digit));
+
+        let report = CheckReport::new(&policy, "correcthorse");
+        assert!(!report.is_compliant());
+        assert!(report.rules[0].passed);
+        assert!(!report.rules[1].passed);
+
+        assert!(CheckReport::new(&policy, "correcthorse7").is_compliant());
+    }
+
+    #[test]
+    fn test_policy_file_deny_list() {
+        let policy = parse(
+            r#"
+            min_length = 8
+            deny_list = ["Acme", "rocketship"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.deny_list().terms(), ["acme", "rocketship"]);
+
+        let report = CheckReport::new(&policy, "acmecorp2024");
+        assert!(!report.is_compliant());
+        assert!(report.rules[0].passed);
+        assert_eq!(report.rules[1].rule, "contains no denied term");
+        assert!(!report.rules[1].passed);
+
+        assert!(CheckReport::new(&policy, "correcthorse").is_compliant());
+    }
+}
@@ -0,0 +1,93 @@
+use std::io;
+
+use motus::ct;
+
+/// run has the user retype `expected` up to `rounds` times, without echoing it, giving feedback
+/// on their mistakes after each attempt, and returns how many attempts were correct.
+///
+/// Practicing stops early if the user submits an empty line. Feedback is written to stderr, so
+/// that practicing does not mix with the generated password on stdout.
+pub fn run(expected: &str, rounds: u32) -> io::Result<u32> {
+    let mut correct = 0;
+    for round in 1..=rounds {
+        let typed = rpassword::prompt_password(format!(
+            "Retype the password ({}/{}, empty to stop): ",
+            round, rounds
+        ))?;
+        if typed.is_empty() {
+            break;
+        }
+
+        match feedback(expected, &typed) {
+            None => {
+                correct += 1;
+                eprintln!("correct!");
+            }
+            Some(mistake) => eprintln!("not quite: {}", mistake),
+        }
+    }
+
+    eprintln!("{} of {} attempts correct", correct, rounds);
+    Ok(correct)
+}
+
+// feedback describes how `typed` differs from `expected`, or returns None if they are the same,
+// without revealing any of the expected characters. Whether they are the same is checked in
+// constant time; only the mistakes made are described.
+fn feedback(expected: &str, typed: &str) -> Option<String> {
+    if ct::str_eq(expected, typed) {
+        return None;
+    }
+
+    let expected: Vec<char> = expected.chars().collect();
+    let typed: Vec<char> = typed.chars().collect();
+    let first_mistake = expected
+        .iter()
+        .zip(&typed)
+        .position(|(expected, typed)| expected != typed);
+
+    Some(match first_mistake {
+        Some(index) => format!("the first mistake is at character {}", index + 1),
+        None if typed.len() < expected.len() => format!(
+            "{} missing at the end",
+            characters(expected.len() - typed.len())
+        ),
+        None => format!(
+            "{} too many at the end",
+            characters(typed.len() - expected.len())
+        ),
+    })
+}
+
+// characters formats a number of characters, such as "1 character" or "3 characters".
+fn characters(count: usize) -> String {
+    match count {
+        1 => "1 character".to_string(),
+        count => format!("{} characters", count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feedback() {
+        assert_eq!(
+            feedback("choking natural dolly", "choking natural dolly"),
+            None
+        );
+        assert_eq!(
+            feedback("choking natural dolly", "choking natsral dolly"),
+            Some("the first mistake is at character 12".to_string())
+        );
+        assert_eq!(
+            feedback("choking natural dolly", "choking natural"),
+            Some("6 characters missing at the end".to_string())
+        );
+        assert_eq!(
+            feedback("choking natural dolly", "choking natural dollyy"),
+            Some("1 character too many at the end".to_string())
+        );
+    }
+}
@@ -0,0 +1,308 @@
+use std::fmt::Write;
+use std::path::Path;
+
+use motus::analysis::report::PasswordStrength;
+
+use crate::analysis::Report;
+
+// TEMPLATE is the standalone HTML document reports are rendered in, styled inline so that it
+// displays the same once attached to a ticket or sent by email.
+const TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body { font-family: system-ui, sans-serif; max-width: 48rem; margin: 2rem auto; padding: 0 1rem; color: #1f2328; }
+h1 { font-size: 1.5rem; }
+table { border-collapse: collapse; width: 100%; margin-bottom: 1.5rem; }
+caption { text-align: left; font-weight: bold; padding: 0.5rem 0; }
+th, td { border: 1px solid #d0d7de; padding: 0.4rem 0.6rem; text-align: left; }
+th { background: #f6f8fa; width: 40%; }
+code { font-size: 1.1rem; word-break: break-all; }
+.very-weak, .fail { color: #cf222e; font-weight: bold; }
+.weak { color: #fa4549; font-weight: bold; }
+.reasonable { color: #9a6700; font-weight: bold; }
+.strong, .pass { color: #2da44e; font-weight: bold; }
+.very-strong { color: #116329; font-weight: bold; }
+footer { color: #656d76; font-size: 0.85rem; }
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{sections}<footer>{footer}</footer>
+</body>
+</html>
+"#;
+
+/// ReportFormat is a format analysis reports are exported in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    Html,
+    /// A printable document, with the `pdf` feature
+    Pdf,
+}
+
+impl ReportFormat {
+    /// from_path returns the format of the report file at `path`: PDF for a `.pdf` extension,
+    /// and HTML otherwise.
+    pub fn from_path(path: &Path) -> Self {
+        let pdf = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("pdf"));
+        if pdf {
+            ReportFormat::Pdf
+        } else {
+            ReportFormat::Html
+        }
+    }
+}
+
+/// Document is an analysis report laid out as titled sections of labeled values, in the
+/// report's locale, which every export format renders the same way.
+pub struct Document {
+    /// The ISO 639-1 code of the language of the document
+    pub lang: &'static str,
+    pub title: &'static str,
+    pub sections: Vec<Section>,
+    pub footer: String,
+}
+
+/// Section is a titled group of entries of a report.
+pub struct Section {
+    pub caption: String,
+    pub entries: Vec<Entry>,
+}
+
+/// Entry is a value of a report, labeled unless it stands for its whole section.
+pub struct Entry {
+    pub label: Option<String>,
+    pub value: String,
+    pub style: Style,
+}
+
+/// Style is how the value of an entry is highlighted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    Plain,
+    /// A password, in a monospace font
+    Code,
+    /// A strength, colored from red to green
+    Strength(PasswordStrength),
+    Pass,
+    Fail,
+}
+
+impl Document {
+    /// new lays out the report, listing the password it analyzes first if given.
+    pub fn new(report: &Report, password: Option<&str>) -> Self {
+        let locale = report.locale();
+        let mut sections = Vec::new();
+
+        if let Some(password) = password {
+            sections.push(Section {
+                caption: locale.text("Generated Password").to_string(),
+                entries: vec![Entry {
+                    label: None,
+                    value: password.to_string(),
+                    style: Style::Code,
+                }],
+            });
+        }
+
+        sections.push(Section {
+            caption: locale.text("Security Analysis").to_string(),
+            entries: vec![
+                Entry::new(
+                    locale.text("Strength"),
+                    locale.text(report.strength().label()),
+                    Style::Strength(report.strength()),
+                ),
+                Entry::new(locale.text("Guesses"), report.guesses(), Style::Plain),
+            ],
+        });
+
+        if !report.estimates().is_empty() {
+            let entries = report
+                .estimates()
+                .iter()
+                .map(|estimate| {
+                    let strength = PasswordStrength::from(estimate.score());
+                    Entry::new(
+                        estimate.estimator,
+                        format!(
+                            "{} (10^{:.0})",
+                            locale.text(strength.label()),
+                            estimate.guesses_log10
+                        ),
+                        Style::Strength(strength),
+                    )
+                })
+                .collect();
+            sections.push(Section {
+                caption: locale.text("Strength estimators").to_string(),
+                entries,
+            });
+        }
+
+        sections.push(Section::plain(
+            locale.text("Crack time estimations"),
+            report.crack_time_rows(),
+        ));
+        sections.push(Section::plain(
+            locale.text("Typo tolerance"),
+            report.typo_rows(),
+        ));
+
+        let warnings = report.warning_rows();
+        if !warnings.is_empty() {
+            sections.push(Section::plain(locale.text("Warnings"), warnings));
+        }
+
+        if let Some(compliance) = report.compliance() {
+            let (verdict, style) = if compliance.is_compliant() {
+                ("compliant", Style::Pass)
+            } else {
+                ("not compliant", Style::Fail)
+            };
+            let mut entries = vec![Entry {
+                label: None,
+                value: locale.text(verdict).to_string(),
+                style,
+            }];
+            entries.extend(compliance.rules().map(|(rule, passed)| {
+                if passed {
+                    Entry::new(rule, locale.text("pass"), Style::Pass)
+                } else {
+                    Entry::new(rule, locale.text("fail"), Style::Fail)
+                }
+            }));
+            sections.push(Section {
+                caption: locale.text("Policy compliance").to_string(),
+                entries,
+            });
+        }
+
+        Document {
+            lang: locale.code(),
+            title: locale.text("Security Analysis"),
+            sections,
+            footer: format!(
+                "{} {}",
+                locale.text("Generated by motus"),
+                env!("CARGO_PKG_VERSION")
+            ),
+        }
+    }
+
+    /// html renders the document as a standalone HTML page.
+    pub fn html(&self) -> String {
+        let mut sections = String::new();
+        for section in &self.sections {
+            let _ = writeln!(
+                sections,
+                "<table>\n<caption>{}</caption>",
+                escape(&section.caption)
+            );
+            for entry in &section.entries {
+                let value = match entry.style {
+                    Style::Plain => escape(&entry.value),
+                    Style::Code => format!("<code>{}</code>", escape(&entry.value)),
+                    Style::Strength(strength) => format!(
+                        "<span class=\"{}\">{}</span>",
+                        strength.label().replace(' ', "-"),
+                        escape(&entry.value)
+                    ),
+                    Style::Pass => format!("<span class=\"pass\">{}</span>", escape(&entry.value)),
+                    Style::Fail => format!("<span class=\"fail\">{}</span>", escape(&entry.value)),
+                };
+                let _ = match &entry.label {
+                    Some(label) => writeln!(
+                        sections,
+                        "<tr><th>{}</th><td>{}</td></tr>",
+                        escape(label),
+                        value
+                    ),
+                    None => writeln!(sections, "<tr><td colspan=\"2\">{}</td></tr>", value),
+                };
+            }
+            sections.push_str("</table>\n");
+        }
+
+        TEMPLATE
+            .replace("{lang}", self.lang)
+            .replace("{title}", &escape(self.title))
+            .replace("{footer}", &escape(&self.footer))
+            .replace("{sections}", &sections)
+    }
+}
+
+impl Section {
+    // plain returns a section of the given labeled values, without highlighting.
+    fn plain(caption: &str, rows: Vec<(String, String)>) -> Self {
+        Section {
+            caption: caption.to_string(),
+            entries: rows
+                .into_iter()
+                .map(|(label, value)| Entry::new(label, value, Style::Plain))
+                .collect(),
+        }
+    }
+}
+
+impl Entry {
+    // new returns a labeled entry.
+    fn new(label: impl ToString, value: impl ToString, style: Style) -> Self {
+        Entry {
+            label: Some(label.to_string()),
+            value: value.to_string(),
+            style,
+        }
+    }
+}
+
+// escape escapes the characters that are special in HTML text and attribute values.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Analyzer;
+    use crate::i18n::Locale;
+
+    #[test]
+    fn test_html_escapes_password() {
+        let analyzer = Analyzer::new(Vec::new(), Locale::Fr, false);
+        let html = Document::new(&analyzer.analyze("<b>&'\""), Some("<b>&'\"")).html();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<html lang=\"fr\">"));
+        assert!(html.contains("<code>&lt;b&gt;&amp;&#39;&quot;</code>"));
+        assert!(!html.contains("<b>"));
+        assert!(html.contains("Temps de cassage estimés"));
+        assert!(!html.contains("{sections}"));
+    }
+
+    #[test]
+    fn test_report_format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("audit/Report.PDF")),
+            ReportFormat::Pdf
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report.html")),
+            ReportFormat::Html
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("report")),
+            ReportFormat::Html
+        );
+    }
+}
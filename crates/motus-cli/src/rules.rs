@@ -0,0 +1,58 @@
+use std::io::{self, BufWriter, Write};
+
+use motus::charset::Charset;
+use motus::sites::SiteRules;
+use serde::Serialize;
+
+use crate::{csv_field, OutputFormat};
+
+/// display writes the policy the password rules convert to, and the characters random passwords
+/// following them are drawn from, to stdout in the requested format.
+pub fn display(rules: &SiteRules, format: &OutputFormat) -> io::Result<()> {
+    let policy: Vec<String> = rules
+        .policy()
+        .rules()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    let pool = &(&Charset::letters() | &Charset::numbers()) | &Charset::symbols();
+    let characters: String = pool.chars().iter().filter(|&&c| rules.allows(c)).collect();
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    match format {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            for rule in &policy {
+                writeln!(out, "{rule}")?;
+            }
+            writeln!(out, "characters: {characters}")?;
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer(
+                &mut out,
+                &RulesOutput {
+                    rules: &policy,
+                    characters: &characters,
+                },
+            )?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "kind,value")?;
+            for rule in &policy {
+                writeln!(out, "rule,{}", csv_field(rule))?;
+            }
+            writeln!(out, "characters,{}", csv_field(&characters))?;
+        }
+    }
+
+    out.flush()
+}
+
+#[derive(Serialize)]
+struct RulesOutput<'a> {
+    rules: &'a [String],
+    characters: &'a str,
+}
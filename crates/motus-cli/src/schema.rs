@@ -0,0 +1,574 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// SchemaFormat is the format the output schemas are documented in.
+#[derive(Copy, Clone, Debug, ValueEnum)]
+pub enum SchemaFormat {
+    /// A JSON Schema (draft 2020-12) document
+    JsonSchema,
+}
+
+/// OutputSchema is the version of the schema the `--output json` payloads follow.
+///
+/// Payloads are always built in the latest version, and converted down to older ones by shims,
+/// so that scripts written against earlier versions of motus keep working as fields are added.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputSchema {
+    /// The original payloads, without a schema_version field nor any field added since
+    V1,
+    /// Adds schema_version, the feedback, typo likelihood and warnings of analyses, and the fields
+    /// of newer options, and prints batches as objects
+    #[default]
+    V2,
+}
+
+impl OutputSchema {
+    /// version returns the number of the version, as found in the `schema_version` field.
+    pub fn version(self) -> u64 {
+        match self {
+            OutputSchema::V1 => 1,
+            OutputSchema::V2 => 2,
+        }
+    }
+
+    /// shim converts a payload, serialized in the latest version of the schema, to this version.
+    pub fn shim(self, payload: Payload, value: Value) -> Value {
+        match self {
+            OutputSchema::V1 => v2_to_v1(payload, value),
+            OutputSchema::V2 => match value {
+                Value::Object(fields) => {
+                    let mut versioned = Map::new();
+                    versioned.insert("schema_version".to_string(), json!(self.version()));
+                    versioned.extend(fields);
+                    Value::Object(versioned)
+                }
+                value => value,
+            },
+        }
+    }
+
+    /// json serializes a payload and converts it to this version of the schema.
+    pub fn json(self, payload: Payload, value: &impl Serialize) -> String {
+        let value = serde_json::to_value(value).expect("unable to serialize the output");
+        self.shim(payload, value).to_string()
+    }
+}
+
+/// Payload is a kind of versioned payload printed with `--output json`.
+///
+/// Errors are not versioned: they keep the same `{"error": "..."}` shape in every version.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Payload {
+    Password,
+    Batch,
+    #[cfg(feature = "analysis")]
+    Analysis,
+    PolicyCheck,
+}
+
+// V1_PASSWORD and V1_ANALYSIS are the fields of the original password and analysis payloads, the
+// only ones version 1 holds.
+const V1_PASSWORD: [&str; 3] = ["kind", "password", "analysis"];
+const V1_ANALYSIS: [&str; 3] = ["strength", "guesses", "crack_times"];
+
+// v2_to_v1 converts a version 2 payload to version 1: passwords and analyses lose every field added
+// since the original payloads, and batches without a summary are printed as a bare array of
+// passwords.
+fn v2_to_v1(payload: Payload, mut value: Value) -> Value {
+    let strip_password = |password: &mut Value| {
+        retain(password, &V1_PASSWORD);
+        if let Some(analysis) = password.get_mut("analysis") {
+            retain(analysis, &V1_ANALYSIS);
+        }
+    };
+
+    match payload {
+        Payload::Password => strip_password(&mut value),
+        #[cfg(feature = "analysis")]
+        Payload::Analysis => retain(&mut value, &V1_ANALYSIS),
+        Payload::Batch => {
+            if let Some(passwords) = value.get_mut("passwords").and_then(Value::as_array_mut) {
+                passwords.iter_mut().for_each(strip_password);
+            }
+            if value.get("summary").is_none() {
+                return value.get_mut("passwords").map(Value::take).unwrap_or(value);
+            }
+        }
+        Payload::PolicyCheck => {}
+    }
+
+    value
+}
+
+// retain removes the fields of the `value` object but the given ones.
+fn retain(value: &mut Value, fields: &[&str]) {
+    if let Some(object) = value.as_object_mut() {
+        object.retain(|field, _| fields.contains(&field.as_str()));
+    }
+}
+
+/// render returns the documentation of the `--output json` payloads, in the given version of the
+/// schema, in the given format.
+pub fn render(format: SchemaFormat, schema: OutputSchema) -> Value {
+    match format {
+        SchemaFormat::JsonSchema => json_schema(schema),
+    }
+}
+
+/// json_schema returns the JSON Schema of the payloads printed with `--output json`, in the given
+/// version of the schema.
+///
+/// Every payload is described under `$defs`, and the document as a whole validates any of them:
+/// a generated password, a batch of generated passwords, a security analysis, or an error.
+pub fn json_schema(schema: OutputSchema) -> Value {
+    let bits = |description: &str| {
+        json!({
+            "description": description,
+            "type": "number",
+            "minimum": 0,
+        })
+    };
+    let count = |description: &str| {
+        json!({
+            "description": description,
+            "type": "integer",
+            "minimum": 0,
+        })
+    };
+    let compliance = |description: &str| {
+        json!({
+            "description": description,
+            "type": "object",
+            "additionalProperties": false,
+            "required": ["compliant", "rules"],
+            "properties": {
+                "compliant": { "type": "boolean" },
+                "rules": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "additionalProperties": false,
+                        "required": ["rule", "passed"],
+                        "properties": {
+                            "rule": { "type": "string" },
+                            "passed": { "type": "boolean" },
+                        },
+                    },
+                },
+            },
+        })
+    };
+    let crack_time = |description: &str| {
+        json!({
+            "description": description,
+            "type": "string",
+        })
+    };
+    let passwords = json!({
+        "type": "array",
+        "items": { "$ref": "#/$defs/password" },
+    });
+    let batch = match schema {
+        OutputSchema::V1 => json!([
+            passwords,
+            {
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["passwords", "summary"],
+                "properties": {
+                    "passwords": passwords,
+                    "summary": { "$ref": "#/$defs/summary" },
+                },
+            },
+        ]),
+        OutputSchema::V2 => json!([{
+            "type": "object",
+            "additionalProperties": false,
+            "required": ["passwords"],
+            "properties": {
+                "passwords": passwords,
+                "summary": { "$ref": "#/$defs/summary" },
+            },
+        }]),
+    };
+
+    let mut document = json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "motus JSON output",
+        "oneOf": [
+            { "$ref": "#/$defs/password" },
+            { "$ref": "#/$defs/batch" },
+            { "$ref": "#/$defs/analysis" },
+            { "$ref": "#/$defs/policy_check" },
+            { "$ref": "#/$defs/error" },
+        ],
+        "$defs": {
+            "password": {
+                "description": "A generated password, printed on the standard output",
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["kind", "password"],
+                "properties": {
+                    "kind": {
+                        "description": "Kind of password generated",
+                        "enum": ["memorable", "random", "pin", "apple"],
+                    },
+                    "password": {
+                        "description": "The password, grouped if --group is used",
+                        "type": "string",
+                    },
+                    "analysis": {
+                        "description": "Security analysis of the password, with --analyze",
+                        "$ref": "#/$defs/analysis",
+                    },
+                    "entropy": {
+                        "description": "Theoretical entropy of the password, with --explain",
+                        "$ref": "#/$defs/entropy",
+                    },
+                    "hints": {
+                        "description": "Definitions of the words of a memorable password found in the embedded glossary, with --hints; words it does not define are left out",
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "additionalProperties": false,
+                            "required": ["word", "hint"],
+                            "properties": {
+                                "word": { "type": "string" },
+                                "hint": { "type": "string" },
+                            },
+                        },
+                    },
+                    "story": {
+                        "description": "A sentence weaving the words of a memorable password together, with --story",
+                        "type": "string",
+                    },
+                    "t9": {
+                        "description": "The telephone keypad digits the password is typed with, with --t9",
+                        "type": "string",
+                    },
+                    "spelling": {
+                        "description": "The password spelled out one character at a time, such as \"capital B\" or \"symbol dollar\", with --accessible",
+                        "type": "array",
+                        "items": { "type": "string" },
+                    },
+                    "label": {
+                        "description": "What the password is for, with --label",
+                        "type": "string",
+                    },
+                    "tags": {
+                        "description": "Annotations of the password, with --tag",
+                        "type": "object",
+                        "additionalProperties": { "type": "string" },
+                    },
+                },
+            },
+            "batch": {
+                "description": "Passwords generated with --count, along with a summary of their strength when --analyze is used without --per-item",
+                "oneOf": batch,
+            },
+            "summary": {
+                "description": "Aggregate statistics about a batch of passwords",
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["count", "min_entropy", "median_entropy", "max_entropy", "strengths"],
+                "properties": {
+                    "count": count("Number of passwords in the batch"),
+                    "min_entropy": bits("Entropy of the weakest password, in bits"),
+                    "median_entropy": bits("Median entropy of the passwords, in bits"),
+                    "max_entropy": bits("Entropy of the strongest password, in bits"),
+                    "strengths": {
+                        "description": "Number of passwords per strength score, from 0 (very weak) to 4 (very strong)",
+                        "type": "object",
+                        "propertyNames": { "pattern": "^[0-4]$" },
+                        "additionalProperties": { "type": "integer", "minimum": 0 },
+                    },
+                },
+            },
+            "analysis": {
+                "description": "Security analysis of a password, printed by motus analyze",
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["strength", "guesses", "crack_times", "typo", "warnings"],
+                "properties": {
+                    "strength": {
+                        "description": "Overall strength of the password",
+                        "enum": ["very weak", "weak", "reasonable", "strong", "very strong"],
+                    },
+                    "guesses": {
+                        "description": "Order of magnitude of the number of guesses needed to find the password",
+                        "type": "string",
+                        "pattern": "^10\\^[0-9]+$",
+                    },
+                    "crack_times": {
+                        "description": "Estimated time to find the password, per attack rate",
+                        "type": "object",
+                        "additionalProperties": false,
+                        "required": ["100/h", "10/s", "10^4/s", "10^10/s"],
+                        "properties": {
+                            "100/h": crack_time("Throttled online attack"),
+                            "10/s": crack_time("Unthrottled online attack"),
+                            "10^4/s": crack_time("Offline attack against a slow hash"),
+                            "10^10/s": crack_time("Offline attack against a fast hash"),
+                        },
+                    },
+                    "typo": {
+                        "description": "Likelihood of the password being mistyped",
+                        "type": "object",
+                        "additionalProperties": false,
+                        "required": ["neighbors", "adjacent_key", "case_slip", "transposition", "probability"],
+                        "properties": {
+                            "neighbors": count("Number of passwords one typo away"),
+                            "adjacent_key": count("Typos hitting a key next to the intended one"),
+                            "case_slip": count("Typos getting the case of a letter wrong"),
+                            "transposition": count("Typos swapping two consecutive characters"),
+                            "probability": {
+                                "description": "Probability of at least one typo when typing the password",
+                                "type": "number",
+                                "minimum": 0,
+                                "maximum": 1,
+                            },
+                        },
+                    },
+                    "warnings": {
+                        "description": "Specific weaknesses found in the password",
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "additionalProperties": false,
+                            "required": ["category", "token", "message"],
+                            "properties": {
+                                "category": { "enum": ["keyboard-walk", "homoglyph"] },
+                                "token": {
+                                    "description": "The characters of the password the warning is about",
+                                    "type": "string",
+                                },
+                                "message": { "type": "string" },
+                                "lookalikes": {
+                                    "description": "What a homoglyph could be mistaken for",
+                                    "type": "array",
+                                    "items": { "type": "string" },
+                                },
+                            },
+                        },
+                    },
+                    "attacks": {
+                        "description": "Crack time estimations for the attacker models given with --attack-rate",
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "additionalProperties": false,
+                            "required": ["guesses_per_second", "seconds", "time"],
+                            "properties": {
+                                "guesses_per_second": { "type": "number", "exclusiveMinimum": 0 },
+                                "seconds": { "type": "number", "minimum": 0 },
+                                "time": { "type": "string" },
+                                "cost": {
+                                    "description": "Cost of the attack, when the attacker model has one",
+                                    "type": "number",
+                                    "minimum": 0,
+                                },
+                            },
+                        },
+                    },
+                    "estimates": {
+                        "description": "Strength estimates of the estimators given with --estimator",
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "additionalProperties": false,
+                            "required": ["estimator", "strength", "guesses", "bits"],
+                            "properties": {
+                                "estimator": { "enum": ["zxcvbn", "markov", "entropy"] },
+                                "strength": {
+                                    "enum": ["very weak", "weak", "reasonable", "strong", "very strong"],
+                                },
+                                "guesses": {
+                                    "type": "string",
+                                    "pattern": "^10\\^[0-9]+$",
+                                },
+                                "bits": bits("Number of guesses needed to find the password, in bits"),
+                            },
+                        },
+                    },
+                    "policy": compliance("Compliance of the password with the policy given to analyze --policy"),
+                },
+            },
+            "entropy": {
+                "description": "Theoretical entropy of a password, broken down by source",
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["bits", "components"],
+                "properties": {
+                    "bits": bits("Total entropy, in bits"),
+                    "components": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "additionalProperties": false,
+                            "required": ["source", "bits"],
+                            "properties": {
+                                "source": { "type": "string" },
+                                "bits": bits("Entropy brought by the source, in bits"),
+                            },
+                        },
+                    },
+                    "ambiguous": {
+                        "description": "Whether the words, joined without a separator, may be read back in several ways, so that the password holds less entropy than reported; omitted when false",
+                        "type": "boolean",
+                    },
+                },
+            },
+            "policy_check": compliance("Compliance of a password with a policy, printed by policy check"),
+            "error": {
+                "description": "An error, printed on the standard error instead of the other payloads",
+                "type": "object",
+                "additionalProperties": false,
+                "required": ["error"],
+                "properties": {
+                    "error": {
+                        "description": "Description of what went wrong",
+                        "type": "string",
+                    },
+                },
+            },
+        },
+    });
+
+    match schema {
+        OutputSchema::V1 => {
+            for (payload, fields) in [("password", &V1_PASSWORD), ("analysis", &V1_ANALYSIS)] {
+                let definition = &mut document["$defs"][payload];
+                retain(&mut definition["properties"], fields);
+                if let Some(required) = definition["required"].as_array_mut() {
+                    required.retain(|field| field.as_str().is_some_and(|f| fields.contains(&f)));
+                }
+            }
+        }
+        OutputSchema::V2 => version(&mut document, schema),
+    }
+
+    document
+}
+
+// version adds the schema_version field, and the fields introduced by version 2, to the schema
+// of the payloads.
+fn version(document: &mut Value, schema: OutputSchema) {
+    let schema_version = json!({
+        "description": "Version of the schema the payload follows, as chosen with --output-schema",
+        "const": schema.version(),
+    });
+
+    for payload in ["password", "batch", "analysis", "policy_check"] {
+        let definition = &mut document["$defs"][payload];
+        let properties = if payload == "batch" {
+            &mut definition["oneOf"][0]["properties"]
+        } else {
+            &mut definition["properties"]
+        };
+        if let Some(properties) = properties.as_object_mut() {
+            properties.insert("schema_version".to_string(), schema_version.clone());
+        }
+    }
+
+    document["$defs"]["analysis"]["properties"]["feedback"] = json!({
+        "description": "Advice on making the password stronger",
+        "type": "object",
+        "additionalProperties": false,
+        "required": ["suggestions"],
+        "properties": {
+            "warning": {
+                "description": "What makes the password weak",
+                "type": "string",
+            },
+            "suggestions": {
+                "type": "array",
+                "items": { "type": "string" },
+            },
+        },
+    });
+
+    // Nested payloads, such as the passwords of a batch, are not versioned themselves
+    document["oneOf"] = json!([
+        { "$ref": "#/$defs/password", "required": ["schema_version"] },
+        { "$ref": "#/$defs/batch", "required": ["schema_version"] },
+        { "$ref": "#/$defs/analysis", "required": ["schema_version"] },
+        { "$ref": "#/$defs/policy_check", "required": ["schema_version"] },
+        { "$ref": "#/$defs/error" },
+    ]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_schema() {
+        let schema = render(SchemaFormat::JsonSchema, OutputSchema::V2);
+        let defs = schema["$defs"]
+            .as_object()
+            .expect("schema should have $defs");
+
+        for payload in ["password", "batch", "analysis", "policy_check", "error"] {
+            assert!(defs.contains_key(payload), "missing {} schema", payload);
+        }
+
+        // Every reference should point to a definition
+        let text = schema.to_string();
+        for reference in text.split("\"#/$defs/").skip(1) {
+            let name = reference.split('"').next().expect("reference should end");
+            assert!(defs.contains_key(name), "dangling reference to {}", name);
+        }
+
+        let v1 = render(SchemaFormat::JsonSchema, OutputSchema::V1);
+        assert!(v1["$defs"]["password"]["properties"]["schema_version"].is_null());
+        assert!(v1["$defs"]["password"]["properties"]["entropy"].is_null());
+        assert_eq!(
+            v1["$defs"]["analysis"]["required"],
+            json!(["strength", "guesses", "crack_times"])
+        );
+        assert_eq!(
+            schema["$defs"]["analysis"]["properties"]["schema_version"]["const"],
+            2
+        );
+    }
+
+    #[test]
+    fn test_shim() {
+        let password = json!({
+            "kind": "random",
+            "password": "abc",
+            "analysis": {
+                "strength": "very weak",
+                "feedback": { "suggestions": [] },
+                "typo": { "probability": 0.1 },
+                "warnings": [],
+            },
+            "label": "github",
+        });
+
+        let v2 = OutputSchema::V2.shim(Payload::Password, password.clone());
+        assert_eq!(v2["schema_version"], 2);
+        assert_eq!(
+            v2.as_object()
+                .and_then(|v2| v2.keys().next())
+                .map(String::as_str),
+            Some("schema_version")
+        );
+
+        let v1 = OutputSchema::V1.shim(Payload::Password, password.clone());
+        assert_eq!(
+            v1,
+            json!({
+                "kind": "random",
+                "password": "abc",
+                "analysis": { "strength": "very weak" },
+            })
+        );
+
+        let batch = json!({ "passwords": [password] });
+        let v1 = OutputSchema::V1.shim(Payload::Batch, batch.clone());
+        assert!(v1.is_array());
+        assert!(v1[0]["analysis"].get("feedback").is_none());
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::{Display, Formatter};
+
+use motus::{Radix, Separator};
+use rand::rngs::StdRng;
+use rand::{thread_rng, RngCore, SeedableRng};
+
+// SEED is the seed the generators are run with to compare their output to the known answers.
+const SEED: u64 = 42;
+
+/// SelfCheck is a known-answer test: the output of a deterministic computation, compared to the
+/// one recorded when motus was released.
+#[derive(Debug)]
+pub struct SelfCheck {
+    name: &'static str,
+    expected: &'static str,
+    actual: String,
+}
+
+/// run runs the known-answer tests, the way power-on self-tests do before a module produces any
+/// secret, and returns the number of tests that passed, or the first one that failed.
+///
+/// Every generator is run with a fixed seed and compared to golden vectors, which catches a
+/// miscompiled binary, a changed wordlist, or an upgraded random number generator drawing other
+/// values. The password strength estimator is checked against known weak and strong passwords,
+/// and the system random number generator against being stuck on a single value.
+pub fn run() -> Result<usize, SelfCheckError> {
+    let checks = known_answers();
+    let count = checks.len();
+    if let Some(check) = checks
+        .into_iter()
+        .find(|check| check.actual != check.expected)
+    {
+        return Err(SelfCheckError::Mismatch(check));
+    }
+
+    // Consecutive draws of a working generator are all but guaranteed to differ
+    let mut rng = thread_rng();
+    if rng.next_u64() == rng.next_u64() {
+        return Err(SelfCheckError::StuckGenerator);
+    }
+
+    Ok(count + 1)
+}
+
+// known_answers returns the known-answer tests, with their actual output.
+fn known_answers() -> Vec<SelfCheck> {
+    let rng = || StdRng::seed_from_u64(SEED);
+
+    #[cfg_attr(not(feature = "analysis"), allow(unused_mut))]
+    let mut checks = vec![
+        SelfCheck {
+            name: "memorable password",
+            expected: "choking natural dolly ominous",
+            actual: motus::memorable_password(&mut rng(), 4, Separator::Space, 1, false, false),
+        },
+        SelfCheck {
+            name: "random password",
+            expected: "mH)vj1Q^7B6BIRYdpPAI",
+            actual: motus::random_password(&mut rng(), 20, true, true, &[]),
+        },
+        SelfCheck {
+            name: "PIN",
+            expected: "55640478",
+            actual: motus::pin_password(&mut rng(), 8),
+        },
+        SelfCheck {
+            name: "Apple password",
+            expected: "oqKktw-day5kd-nfanbq",
+            actual: motus::apple_password(&mut rng()),
+        },
+        SelfCheck {
+            name: "base32 code",
+            expected: "RUBN3EAQA3QLOZ4H",
+            actual: motus::code_password(&mut rng(), 16, Radix::Base32.alphabet()),
+        },
+    ];
+
+    #[cfg(feature = "analysis")]
+    {
+        use motus::analysis::report::SecurityAnalysis;
+
+        checks.push(SelfCheck {
+            name: "weak password strength",
+            expected: "0",
+            actual: SecurityAnalysis::new("qwert").score().to_string(),
+        });
+        checks.push(SelfCheck {
+            name: "strong password strength",
+            expected: "4",
+            actual: SecurityAnalysis::new("vH3#kQ9!mX2@pL7$")
+                .score()
+                .to_string(),
+        });
+    }
+
+    checks
+}
+
+/// SelfCheckError represents a failed known-answer test.
+#[derive(Debug)]
+pub enum SelfCheckError {
+    Mismatch(SelfCheck),
+    StuckGenerator,
+}
+
+impl Display for SelfCheckError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SelfCheckError::Mismatch(check) => write!(
+                f,
+                "the {} known-answer test failed: expected {:?}, got {:?}",
+                check.name, check.expected, check.actual
+            ),
+            SelfCheckError::StuckGenerator => write!(
+                f,
+                "the system random number generator returned the same value twice in a row"
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_answers() {
+        for check in known_answers() {
+            assert_eq!(check.actual, check.expected, "{}", check.name);
+        }
+        assert!(run().is_ok());
+    }
+}
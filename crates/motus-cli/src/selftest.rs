@@ -0,0 +1,263 @@
+use colored::Colorize;
+use motus::{code_password, Radix};
+use rand::RngCore;
+use serde::Serialize;
+
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+use crate::OutputFormat;
+
+// SIGNIFICANCE is the p-value under which a check fails. It is small enough for a sound RNG to
+// fail a check about once in ten thousand runs, rather than once in a hundred.
+const SIGNIFICANCE: f64 = 1e-4;
+
+/// SelfTest is the outcome of quick statistical checks of the random number generator, over
+/// characters generated the way passwords are, and over its raw output.
+///
+/// These checks catch a broken or badly seeded generator, such as one stuck on a value or
+/// biased towards some characters. They cannot prove the generator cryptographically secure.
+#[derive(Serialize)]
+pub struct SelfTest {
+    samples: usize,
+    passed: bool,
+    checks: Vec<Check>,
+}
+
+#[derive(Serialize)]
+struct Check {
+    name: &'static str,
+    statistic: f64,
+    p_value: f64,
+    passed: bool,
+}
+
+impl SelfTest {
+    /// Runs the checks over `samples` characters, and as many bits of raw output.
+    pub fn run<R: RngCore>(rng: &mut R, samples: usize) -> Self {
+        let alphabet = Radix::Alnum.alphabet();
+        let indices: Vec<usize> = code_password(rng, samples as u32, alphabet)
+            .chars()
+            .filter_map(|c| alphabet.iter().position(|&a| a == c))
+            .collect();
+        let bits: Vec<bool> = (0..samples.div_ceil(32))
+            .map(|_| rng.next_u32())
+            .flat_map(|word| (0..32).map(move |i| word >> i & 1 == 1))
+            .take(samples)
+            .collect();
+
+        let checks = vec![
+            Check::new("chi-square", chi_square(&indices, alphabet.len())),
+            Check::new("serial correlation", serial_correlation(&indices)),
+            Check::new("monobit", monobit(&bits)),
+            Check::new("runs", runs(&bits)),
+        ];
+
+        SelfTest {
+            samples,
+            passed: checks.iter().all(|check| check.passed),
+            checks,
+        }
+    }
+
+    /// Returns whether every check passed.
+    pub fn passed(&self) -> bool {
+        self.passed
+    }
+
+    /// Writes the outcome of the checks to stdout in the requested format.
+    pub fn display(&self, output: &OutputFormat) {
+        match output {
+            OutputFormat::Text
+            | OutputFormat::SystemdCred
+            | OutputFormat::Sops
+            | OutputFormat::Html => self.display_table(TableStyle::extended(), 80),
+            OutputFormat::Json => println!("{}", serde_json::to_string(self).unwrap()),
+            OutputFormat::Csv => {
+                println!("check,statistic,p_value,passed");
+                for check in &self.checks {
+                    println!(
+                        "{},{},{},{}",
+                        check.name, check.statistic, check.p_value, check.passed
+                    );
+                }
+            }
+        }
+    }
+
+    fn display_table(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder(format!(
+            "Randomness self-test ({} samples)",
+            self.samples
+        ))
+        .col_span(4)
+        .alignment(Alignment::Left)
+        .build()]));
+        table.add_row(Row::new(vec![
+            TableCell::new("Check".bold()),
+            TableCell::builder("Statistic".bold())
+                .alignment(Alignment::Right)
+                .build(),
+            TableCell::builder("p-value".bold())
+                .alignment(Alignment::Right)
+                .build(),
+            TableCell::new("Result".bold()),
+        ]));
+
+        for check in &self.checks {
+            table.add_row(Row::new(vec![
+                TableCell::new(check.name),
+                TableCell::builder(format!("{:.4}", check.statistic))
+                    .alignment(Alignment::Right)
+                    .build(),
+                TableCell::builder(format!("{:.4}", check.p_value))
+                    .alignment(Alignment::Right)
+                    .build(),
+                TableCell::new(if check.passed {
+                    "pass".green()
+                } else {
+                    "FAIL".red().bold()
+                }),
+            ]));
+        }
+
+        println!("{}", table.render());
+    }
+}
+
+impl Check {
+    // new returns the outcome of a check given its statistic and p-value.
+    fn new(name: &'static str, (statistic, p_value): (f64, f64)) -> Self {
+        Check {
+            name,
+            statistic,
+            p_value,
+            passed: p_value >= SIGNIFICANCE,
+        }
+    }
+}
+
+// chi_square tests whether the values, in 0..categories, are uniformly distributed. Both tails
+// fail: a distribution too even to be random is as suspicious as a skewed one. The p-value of the
+// statistic is approximated with the Wilson-Hilferty transformation, accurate enough for the
+// dozens of degrees of freedom at hand.
+fn chi_square(values: &[usize], categories: usize) -> (f64, f64) {
+    let mut counts = vec![0u64; categories];
+    for &value in values {
+        counts[value] += 1;
+    }
+
+    let expected = values.len() as f64 / categories as f64;
+    let statistic: f64 = counts
+        .iter()
+        .map(|&count| (count as f64 - expected).powi(2) / expected)
+        .sum();
+
+    let k = (categories - 1) as f64;
+    let z = ((statistic / k).cbrt() - (1.0 - 2.0 / (9.0 * k))) / (2.0 / (9.0 * k)).sqrt();
+    (statistic, erfc(z.abs() / std::f64::consts::SQRT_2))
+}
+
+// serial_correlation tests whether each value is independent from the previous one, with the
+// correlation coefficient of consecutive values, wrapping around, as the ent tool computes it.
+fn serial_correlation(values: &[usize]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let (mut sum, mut squares, mut products) = (0.0, 0.0, 0.0);
+    for (i, &value) in values.iter().enumerate() {
+        let value = value as f64;
+        let next = values[(i + 1) % values.len()] as f64;
+        sum += value;
+        squares += value * value;
+        products += value * next;
+    }
+
+    let correlation = (n * products - sum * sum) / (n * squares - sum * sum);
+    (
+        correlation,
+        erfc((correlation * n.sqrt()).abs() / std::f64::consts::SQRT_2),
+    )
+}
+
+// monobit tests whether the bits are as often set as not, as the frequency test of NIST SP
+// 800-22 does.
+fn monobit(bits: &[bool]) -> (f64, f64) {
+    let sum: i64 = bits.iter().map(|&bit| if bit { 1 } else { -1 }).sum();
+    let statistic = sum.unsigned_abs() as f64 / (bits.len() as f64).sqrt();
+    (statistic, erfc(statistic / std::f64::consts::SQRT_2))
+}
+
+// runs tests whether the bits change value as often as random ones would, as the runs test of
+// NIST SP 800-22 does, catching generators oscillating too fast or too slowly.
+fn runs(bits: &[bool]) -> (f64, f64) {
+    let n = bits.len() as f64;
+    let ones = bits.iter().filter(|&&bit| bit).count() as f64 / n;
+
+    // The test is meaningless when the monobit one fails badly
+    if (ones - 0.5).abs() >= 2.0 / n.sqrt() {
+        return (0.0, 0.0);
+    }
+
+    let runs = 1 + bits.windows(2).filter(|pair| pair[0] != pair[1]).count();
+    let expected = 2.0 * n * ones * (1.0 - ones);
+    let statistic = (runs as f64 - expected).abs() / (2.0 * (2.0 * n).sqrt() * ones * (1.0 - ones));
+    (runs as f64, erfc(statistic))
+}
+
+// erfc returns the complementary error function of x, with a relative error below 1.2e-7, as
+// Numerical Recipes' erfcc computes it.
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t
+        * (-z * z - 1.265_512_23
+            + t * (1.000_023_68
+                + t * (0.374_091_96
+                    + t * (0.096_784_18
+                        + t * (-0.186_288_06
+                            + t * (0.278_868_07
+                                + t * (-1.135_203_98
+                                    + t * (1.488_515_87
+                                        + t * (-0.822_152_23 + t * 0.170_872_77)))))))))
+            .exp();
+    if x >= 0.0 {
+        r
+    } else {
+        2.0 - r
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_erfc() {
+        assert!((erfc(0.0) - 1.0).abs() < 1e-7);
+        assert!((erfc(1.0) - 0.157_299_207).abs() < 1e-7);
+        assert!((erfc(-1.0) - 1.842_700_793).abs() < 1e-7);
+    }
+
+    #[test]
+    fn test_sound_generator_passes() {
+        let report = SelfTest::run(&mut StdRng::seed_from_u64(42), 100_000);
+        assert!(report.passed());
+    }
+
+    #[test]
+    fn test_broken_generators_fail() {
+        // Stuck on a single character
+        assert!(chi_square(&[3; 10_000], 36).1 < SIGNIFICANCE);
+        // Cycling through the characters in order
+        let cycling: Vec<usize> = (0..10_000).map(|i| i % 36).collect();
+        assert!(chi_square(&cycling, 36).1 < SIGNIFICANCE);
+        assert!(serial_correlation(&cycling).1 < SIGNIFICANCE);
+        // Alternating bits
+        let alternating: Vec<bool> = (0..10_000).map(|i| i % 2 == 0).collect();
+        assert!(monobit(&alternating).1 >= SIGNIFICANCE);
+        assert!(runs(&alternating).1 < SIGNIFICANCE);
+    }
+}
@@ -0,0 +1,389 @@
+use std::fmt::{Display, Formatter};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream, UdpSocket};
+use std::path::PathBuf;
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use motus::ct;
+
+// POLL_INTERVAL is how often the server checks for new connections and for the link expiring.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+// CLIENT_TIMEOUT is how long a client has to send its request and read the response, in total,
+// for a stalled or trickling one not to hold the server up until the link expires.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+// MAX_REQUEST_SIZE is the size of the largest request read, headers included.
+const MAX_REQUEST_SIZE: usize = 8192;
+
+/// ShareOptions configures how a secret is shared.
+pub struct ShareOptions {
+    pub bind: SocketAddr,
+    pub ttl: Duration,
+    pub once: bool,
+    pub tls: Option<(PathBuf, PathBuf)>,
+}
+
+/// Share serves a secret over HTTP to whoever knows its retrieval token, until the link expires.
+///
+/// Plain HTTP is only served on loopback addresses: serving the secret to the local network
+/// requires a TLS certificate, for it not to cross the network in the clear.
+pub struct Share {
+    listener: TcpListener,
+    token: String,
+    url: String,
+    ttl: Duration,
+    once: bool,
+    #[cfg(feature = "tls")]
+    tls: Option<Arc<rustls::ServerConfig>>,
+}
+
+/// Outcome is how serving a secret ended.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The secret was retrieved, by the clients at these addresses.
+    Retrieved(Vec<SocketAddr>),
+    /// The link expired before anyone retrieved the secret.
+    Expired,
+}
+
+impl Share {
+    /// Binds the server, to serve a secret at a URL holding `token`.
+    pub fn bind(options: ShareOptions, token: String) -> Result<Self, ShareError> {
+        if options.tls.is_none() && !options.bind.ip().is_loopback() {
+            return Err(ShareError::Insecure(options.bind));
+        }
+        #[cfg(not(feature = "tls"))]
+        if options.tls.is_some() {
+            return Err(ShareError::Tls(
+                "motus was built without the tls feature".to_string(),
+            ));
+        }
+
+        let listener = TcpListener::bind(options.bind)?;
+        listener.set_nonblocking(true)?;
+
+        let address = listener.local_addr()?;
+        let host = match address.ip() {
+            ip if ip.is_unspecified() => local_ip().unwrap_or(ip),
+            ip => ip,
+        };
+        let scheme = if options.tls.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        let url = format!(
+            "{}://{}/{}",
+            scheme,
+            SocketAddr::new(host, address.port()),
+            token
+        );
+
+        Ok(Self {
+            listener,
+            token,
+            url,
+            ttl: options.ttl,
+            once: options.once,
+            #[cfg(feature = "tls")]
+            tls: options
+                .tls
+                .map(|(cert, key)| tls_config(&cert, &key))
+                .transpose()?,
+        })
+    }
+
+    /// Returns the URL the secret is retrieved at.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Serves the secret until the link expires or, with `once`, until it is first retrieved.
+    ///
+    /// Requests for any other path than the token's are answered with a 404, and failing
+    /// connections are ignored: only the expiry of the link ends the sharing early.
+    pub fn serve(self, secret: &str) -> Result<Outcome, ShareError> {
+        let deadline = Instant::now() + self.ttl;
+        let mut retrievals = Vec::new();
+
+        while Instant::now() < deadline {
+            let (stream, peer) = match self.listener.accept() {
+                Ok(connection) => connection,
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                    thread::sleep(POLL_INTERVAL);
+                    continue;
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            // Connections are bounded by the link's expiry too, for none to outlive it
+            let stream = Bounded {
+                stream,
+                deadline: deadline.min(Instant::now() + CLIENT_TIMEOUT),
+            };
+            if let Ok(true) = self.respond(stream, secret) {
+                retrievals.push(peer);
+                if self.once {
+                    break;
+                }
+            }
+        }
+
+        if retrievals.is_empty() {
+            Ok(Outcome::Expired)
+        } else {
+            Ok(Outcome::Retrieved(retrievals))
+        }
+    }
+
+    // respond answers the request on `stream`, over TLS if configured, and returns whether the
+    // secret was served.
+    fn respond(&self, stream: Bounded, secret: &str) -> io::Result<bool> {
+        stream.stream.set_nonblocking(false)?;
+
+        #[cfg(feature = "tls")]
+        if let Some(config) = &self.tls {
+            let connection = rustls::ServerConnection::new(Arc::clone(config))
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            let mut stream = rustls::StreamOwned::new(connection, stream);
+            let served = handle(&mut stream, &self.token, secret)?;
+            stream.conn.send_close_notify();
+            stream.flush()?;
+            return Ok(served);
+        }
+
+        handle(&mut { stream }, &self.token, secret)
+    }
+}
+
+// Bounded is a client connection dropped once its deadline passes, however slowly the client
+// sends its request or reads the response: every read and write times out at the deadline.
+struct Bounded {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Bounded {
+    // remaining returns the time left before the deadline, or an error once it passed.
+    fn remaining(&self) -> io::Result<Duration> {
+        let remaining = self.deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(io::Error::new(
+                io::ErrorKind::TimedOut,
+                "the connection outlived its deadline",
+            ));
+        }
+        Ok(remaining)
+    }
+}
+
+impl Read for Bounded {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.set_read_timeout(Some(self.remaining()?))?;
+        self.stream.read(buf)
+    }
+}
+
+impl Write for Bounded {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.stream.set_write_timeout(Some(self.remaining()?))?;
+        self.stream.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.stream.flush()
+    }
+}
+
+// handle reads an HTTP request from `stream` and answers it, with the secret if it is a GET
+// request for the token's path, and returns whether the secret was served.
+fn handle(stream: &mut (impl Read + Write), token: &str, secret: &str) -> io::Result<bool> {
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 || request.len() + read > MAX_REQUEST_SIZE {
+            break;
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let mut request_line = request.lines().next().unwrap_or_default().split(' ');
+    let (method, path) = (request_line.next(), request_line.next().unwrap_or_default());
+
+    let (status, body, served) = match method {
+        Some("GET") if path.strip_prefix('/').is_some_and(|t| ct::str_eq(t, token)) => {
+            ("200 OK", secret, true)
+        }
+        Some("GET") => ("404 Not Found", "not found\n", false),
+        _ => ("405 Method Not Allowed", "method not allowed\n", false),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nCache-Control: no-store\r\nReferrer-Policy: no-referrer\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+
+    Ok(served)
+}
+
+// local_ip returns the address of this machine on the network its default route goes through,
+// for the URL to be reachable by teammates when serving on every interface. Connecting a UDP
+// socket sends nothing: it only picks the route.
+fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    socket.local_addr().ok().map(|address| address.ip())
+}
+
+// tls_config loads the certificate chain and private key to serve the secret over TLS with.
+#[cfg(feature = "tls")]
+fn tls_config(
+    cert: &std::path::Path,
+    key: &std::path::Path,
+) -> Result<Arc<rustls::ServerConfig>, ShareError> {
+    use rustls::pki_types::pem::PemObject;
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|err| ShareError::Tls(format!("unable to read {}: {}", cert.display(), err)))?;
+    let key = PrivateKeyDer::from_pem_file(key)
+        .map_err(|err| ShareError::Tls(format!("unable to read {}: {}", key.display(), err)))?;
+
+    let config = rustls::ServerConfig::builder_with_provider(Arc::new(
+        rustls::crypto::ring::default_provider(),
+    ))
+    .with_safe_default_protocol_versions()
+    .and_then(|builder| builder.with_no_client_auth().with_single_cert(certs, key))
+    .map_err(|err| ShareError::Tls(err.to_string()))?;
+
+    Ok(Arc::new(config))
+}
+
+/// ShareError represents the errors that can occur while sharing a secret.
+#[derive(Debug)]
+pub enum ShareError {
+    Io(io::Error),
+    Insecure(SocketAddr),
+    Tls(String),
+}
+
+impl Display for ShareError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareError::Io(err) => write!(f, "unable to serve the secret: {}", err),
+            ShareError::Insecure(address) => write!(
+                f,
+                "refusing to serve the secret in the clear on {}: use a loopback address, or provide --tls-cert and --tls-key",
+                address
+            ),
+            ShareError::Tls(err) => write!(f, "unable to set up TLS: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for ShareError {
+    fn from(err: io::Error) -> Self {
+        ShareError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // get sends a GET request for `path` to the server at `address`, and returns the response.
+    fn get(address: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(stream, "GET {} HTTP/1.1\r\nHost: motus\r\n\r\n", path).unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    }
+
+    fn options(once: bool) -> ShareOptions {
+        ShareOptions {
+            bind: "127.0.0.1:0".parse().unwrap(),
+            ttl: Duration::from_secs(10),
+            once,
+            tls: None,
+        }
+    }
+
+    #[test]
+    fn test_serve_once() {
+        let share = Share::bind(options(true), "t0k3n".to_string()).unwrap();
+        let address = share.listener.local_addr().unwrap();
+        assert_eq!(share.url(), format!("http://{}/t0k3n", address));
+
+        let server = thread::spawn(move || share.serve("mHYvjgQAKBHBIRYdpPAI").unwrap());
+
+        let response = get(address, "/guess");
+        assert!(response.starts_with("HTTP/1.1 404"));
+        assert!(!response.contains("mHYvjgQAKBHBIRYdpPAI"));
+
+        let response = get(address, "/t0k3n");
+        assert!(response.starts_with("HTTP/1.1 200"));
+        assert!(response.contains("Cache-Control: no-store"));
+        assert!(response.ends_with("\r\n\r\nmHYvjgQAKBHBIRYdpPAI"));
+
+        assert!(matches!(server.join().unwrap(), Outcome::Retrieved(peers) if peers.len() == 1));
+        assert!(TcpStream::connect(address).is_err());
+    }
+
+    #[test]
+    fn test_serve_expires() {
+        let mut options = options(true);
+        options.ttl = Duration::from_millis(200);
+        let share = Share::bind(options, "t0k3n".to_string()).unwrap();
+
+        assert_eq!(share.serve("secret").unwrap(), Outcome::Expired);
+    }
+
+    #[test]
+    fn test_serve_drops_slow_clients_when_the_link_expires() {
+        let mut options = options(false);
+        options.ttl = Duration::from_millis(500);
+        let share = Share::bind(options, "t0k3n".to_string()).unwrap();
+        let address = share.listener.local_addr().unwrap();
+
+        let server = thread::spawn(move || {
+            let started = Instant::now();
+            (share.serve("secret").unwrap(), started.elapsed())
+        });
+
+        // A client trickling its request in, a byte at a time, does not keep the server alive
+        let mut stream = TcpStream::connect(address).unwrap();
+        for byte in b"GET /t0k3n HTTP/1.1\r\n".iter().cycle().take(40) {
+            if stream.write_all(&[*byte]).is_err() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(50));
+        }
+
+        let (outcome, elapsed) = server.join().unwrap();
+        assert_eq!(outcome, Outcome::Expired);
+        assert!(elapsed < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_refuse_plain_http_on_the_network() {
+        let mut options = options(true);
+        options.bind = "0.0.0.0:0".parse().unwrap();
+
+        assert!(matches!(
+            Share::bind(options, "t0k3n".to_string()),
+            Err(ShareError::Insecure(_))
+        ));
+    }
+}
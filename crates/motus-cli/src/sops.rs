@@ -0,0 +1,107 @@
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Recipient is the key the data key of a SOPS document is encrypted to.
+pub enum Recipient<'a> {
+    /// An age public key, such as `age1...`.
+    Age(&'a str),
+    /// An AWS KMS key ARN.
+    Kms(&'a str),
+}
+
+/// document returns the YAML document holding `secret` under the `name` key.
+///
+/// Both are written as JSON strings, which YAML parses as plain strings whatever characters they
+/// hold, so that no secret can be mistaken for a number, a boolean or another YAML construct.
+pub fn document(name: &str, secret: &str) -> String {
+    format!(
+        "{}: {}\n",
+        serde_json::to_string(name).expect("strings should be serializable"),
+        serde_json::to_string(secret).expect("strings should be serializable")
+    )
+}
+
+/// encrypt encrypts the YAML document holding `secret` under the `name` key with `sops`, for
+/// `recipient`, and returns the encrypted document, ready to be committed.
+///
+/// The document is handed over on sops' standard input, so that the secret never touches the
+/// disk unencrypted.
+pub fn encrypt(name: &str, secret: &str, recipient: &Recipient) -> Result<Vec<u8>, SopsError> {
+    let (flag, key) = match recipient {
+        Recipient::Age(key) => ("--age", key),
+        Recipient::Kms(key) => ("--kms", key),
+    };
+
+    let mut child = Command::new("sops")
+        .args(["--encrypt", "--input-type", "yaml", "--output-type", "yaml"])
+        .args([flag, key])
+        .arg("/dev/stdin")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => SopsError::NotFound,
+            _ => SopsError::Io(err),
+        })?;
+
+    child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(document(name, secret).as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(SopsError::Failed(stderr.trim().to_string()));
+    }
+
+    Ok(output.stdout)
+}
+
+/// SopsError represents the errors that can occur while encrypting a document with sops.
+#[derive(Debug)]
+pub enum SopsError {
+    Io(io::Error),
+    NotFound,
+    Failed(String),
+}
+
+impl Display for SopsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SopsError::Io(err) => write!(f, "unable to run sops: {}", err),
+            SopsError::NotFound => write!(
+                f,
+                "sops was not found; install it from https://github.com/getsops/sops"
+            ),
+            SopsError::Failed(err) => write!(f, "sops failed: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for SopsError {
+    fn from(err: io::Error) -> Self {
+        SopsError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document() {
+        assert_eq!(
+            document("db-password", "mHYvjgQAKBHBIRYdpPAI"),
+            "\"db-password\": \"mHYvjgQAKBHBIRYdpPAI\"\n"
+        );
+        assert_eq!(document("pin", "0042"), "\"pin\": \"0042\"\n");
+        assert_eq!(
+            document("password", "a\"b\\c #d"),
+            "\"password\": \"a\\\"b\\\\c #d\"\n"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use std::io;
+
+use motus::secret::Secret;
+use serde::Serialize;
+
+use crate::{OutputFormat, PasswordKind};
+
+/// Split is a secret cut into parts for as many custodians, for no single one of them to know it
+/// whole, as required by two-person control.
+///
+/// Unlike a secret sharing scheme, each part reveals its share of the secret: a custodian knowing
+/// one half of a passphrase only has the other half left to guess. Parts are told apart at the
+/// separator of memorable passwords, whose words are not cut, and split evenly by characters
+/// otherwise.
+///
+/// No digest of the secret is handed out with the parts: it would let a custodian check their
+/// guesses of the other parts offline.
+pub struct Split {
+    parts: Vec<Secret>,
+    separator: String,
+}
+
+/// MIN_PART_BITS is the entropy every part of a split secret should hold, for the other parts to
+/// still be worth guessing to a custodian knowing one of them.
+pub const MIN_PART_BITS: f64 = 32.0;
+
+impl Split {
+    /// Cuts `password`, holding `bits` of entropy, into `count` parts, at the occurrences of
+    /// `separator` if given and frequent enough, and evenly by characters otherwise.
+    ///
+    /// The entropy is assumed to be spread evenly across the words or characters of the password,
+    /// and the password is refused if a part would hold less than [`MIN_PART_BITS`].
+    pub fn new(
+        password: &str,
+        count: usize,
+        separator: Option<&str>,
+        bits: f64,
+    ) -> Result<Self, String> {
+        let (tokens, separator): (Vec<&str>, &str) =
+            match separator.filter(|separator| !separator.is_empty()) {
+                Some(separator) if password.split(separator).count() >= count => {
+                    (password.split(separator).collect(), separator)
+                }
+                _ => (
+                    password
+                        .char_indices()
+                        .map(|(index, c)| &password[index..index + c.len_utf8()])
+                        .collect(),
+                    "",
+                ),
+            };
+        if tokens.len() < count {
+            return Err(format!(
+                "the password is too short to be split into {} parts",
+                count
+            ));
+        }
+
+        // The last part holds the fewest tokens, and the least entropy
+        let smallest = bits * (tokens.len() / count) as f64 / tokens.len() as f64;
+        if smallest < MIN_PART_BITS {
+            return Err(format!(
+                "a part of the split password would only hold {:.1} bits of entropy, below the {} bits every part needs; generate a longer password or split it into fewer parts",
+                smallest, MIN_PART_BITS
+            ));
+        }
+
+        // The first parts get the remaining tokens when they cannot be shared evenly
+        let mut parts = Vec::with_capacity(count);
+        let mut rest = tokens.as_slice();
+        for index in 0..count {
+            let size = rest.len().div_ceil(count - index);
+            let (part, next) = rest.split_at(size);
+            parts.push(Secret::new(part.join(separator)));
+            rest = next;
+        }
+
+        Ok(Self {
+            parts,
+            separator: separator.to_string(),
+        })
+    }
+
+    /// display writes the parts of the secret and how to recombine them to stdout, in the requested
+    /// format.
+    pub fn display(&self, kind: PasswordKind, format: &OutputFormat) -> io::Result<()> {
+        match format {
+            OutputFormat::Json => {
+                let output = SplitOutput {
+                    kind,
+                    parts: self.parts.iter().map(Secret::expose).collect(),
+                    separator: &self.separator,
+                };
+                println!("{}", serde_json::to_string(&output)?);
+            }
+            _ => {
+                let count = self.parts.len();
+                for (index, part) in self.parts.iter().enumerate() {
+                    println!("Part {} of {}: {}", index + 1, count, part.expose());
+                }
+                println!();
+                println!(
+                    "Hand each part to a different custodian. To recombine the secret, type the parts in order, {}, or run: motus recombine --parts {}{}",
+                    match self.separator.as_str() {
+                        "" => "with nothing in between".to_string(),
+                        " " => "separated by a space".to_string(),
+                        separator => format!("separated by '{}'", separator),
+                    },
+                    count,
+                    match self.separator.as_str() {
+                        "" => String::new(),
+                        separator => format!(" --separator '{}'", separator),
+                    },
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct SplitOutput<'a> {
+    kind: PasswordKind,
+    parts: Vec<&'a str>,
+    separator: &'a str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parts(split: &Split) -> Vec<&str> {
+        split.parts.iter().map(Secret::expose).collect()
+    }
+
+    #[test]
+    fn test_split_words() {
+        let password = "chokehold nativity dolly ominous throat copper";
+        let split = Split::new(password, 2, Some(" "), 77.5).unwrap();
+
+        assert_eq!(
+            parts(&split),
+            ["chokehold nativity dolly", "ominous throat copper"]
+        );
+        assert_eq!(split.separator, " ");
+        assert_eq!(parts(&split).join(&split.separator), password);
+    }
+
+    #[test]
+    fn test_split_characters() {
+        let split = Split::new("mHYvjgQAKBHBIRYdpPAIx", 2, None, 125.0).unwrap();
+        assert_eq!(parts(&split), ["mHYvjgQAKBH", "BIRYdpPAIx"]);
+        assert_eq!(split.separator, "");
+
+        // Too few words fall back to characters
+        let split = Split::new("hyphenated-word", 3, Some("-"), 105.0).unwrap();
+        assert_eq!(parts(&split), ["hyphe", "nated", "-word"]);
+        assert_eq!(split.separator, "");
+
+        assert!(Split::new("a", 2, None, 64.0).is_err());
+    }
+
+    #[test]
+    fn test_split_entropy_floor() {
+        // Two words of a five-word passphrase hold about 25.9 bits
+        let password = "chokehold nativity dolly ominous throat";
+        assert!(Split::new(password, 2, Some(" "), 64.6).is_err());
+        assert!(Split::new(password, 3, Some(" "), 64.6).is_err());
+
+        // A 7-digit PIN split in two
+        assert!(Split::new("7513687", 2, None, 23.3).is_err());
+    }
+}
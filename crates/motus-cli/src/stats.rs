@@ -0,0 +1,194 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::table::{Alignment, Row, Table, TableCell, TableStyle};
+use crate::{OutputFormat, PasswordKind};
+
+/// Stats counts how many passwords of each kind were generated.
+///
+/// Recording them is opt-in: they are only updated if the stats file exists, which
+/// `motus stats enable` creates. The file is local and never sent anywhere, and it only holds
+/// counters: neither the passwords nor the options they were generated with are recorded.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    since: u64,
+    passwords: BTreeMap<PasswordKind, u64>,
+}
+
+impl Stats {
+    /// Returns empty stats, starting now.
+    pub fn new() -> Self {
+        let since = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        Self {
+            since,
+            passwords: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the stats stored at `path`, or returns None if recording them is not enabled.
+    pub fn load(path: &Path) -> Result<Option<Self>, StatsError> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|_| StatsError::Corrupted)
+    }
+
+    /// Counts `count` newly generated passwords of the given kind.
+    pub fn add(&mut self, kind: PasswordKind, count: u64) {
+        *self.passwords.entry(kind).or_default() += count;
+    }
+
+    /// Writes the stats to `path`, creating parent directories as needed.
+    pub fn save(&self, path: &Path) -> Result<(), StatsError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string(self).expect("stats should be serializable");
+        fs::write(path, content)?;
+
+        Ok(())
+    }
+
+    /// Writes the stats to stdout in the requested format.
+    pub fn display(&self, output: &OutputFormat) {
+        match output {
+            OutputFormat::Text
+            | OutputFormat::SystemdCred
+            | OutputFormat::Sops
+            | OutputFormat::Html => self.display_table(TableStyle::extended(), 80),
+            OutputFormat::Json => println!("{}", serde_json::to_string(self).unwrap()),
+            OutputFormat::Csv => {
+                println!("kind,passwords");
+                for (kind, count) in &self.passwords {
+                    println!("{},{}", kind, count);
+                }
+            }
+        }
+    }
+
+    fn display_table(&self, table_style: TableStyle, max_width: usize) {
+        let mut table = Table::new();
+        table.max_column_width = max_width;
+        table.style = table_style;
+
+        table.add_row(Row::new(vec![TableCell::builder("Usage stats")
+            .col_span(2)
+            .alignment(Alignment::Left)
+            .build()]));
+
+        for (kind, count) in &self.passwords {
+            table.add_row(Row::new(vec![
+                TableCell::new(kind.to_string().bold()),
+                TableCell::builder(count)
+                    .alignment(Alignment::Right)
+                    .build(),
+            ]));
+        }
+
+        table.add_row(Row::new(vec![
+            TableCell::new("total".bold()),
+            TableCell::builder(self.passwords.values().sum::<u64>())
+                .alignment(Alignment::Right)
+                .build(),
+        ]));
+
+        println!("{}", table.render());
+        println!("Recorded since {} (unix time)", self.since);
+    }
+}
+
+/// record counts `count` newly generated passwords of the given kind in the stats at `path`, if
+/// recording them is enabled.
+pub fn record(path: &Path, kind: PasswordKind, count: u64) -> Result<(), StatsError> {
+    let Some(mut stats) = Stats::load(path)? else {
+        return Ok(());
+    };
+
+    stats.add(kind, count);
+    stats.save(path)
+}
+
+/// default_path returns the default location of the stats file, in the user's data directory.
+pub fn default_path() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("motus")
+        .join("stats.json")
+}
+
+/// StatsError represents the errors that can occur while interacting with the stats file.
+#[derive(Debug)]
+pub enum StatsError {
+    Io(io::Error),
+    Corrupted,
+}
+
+impl Display for StatsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StatsError::Io(err) => write!(f, "unable to access the stats file: {}", err),
+            StatsError::Corrupted => write!(f, "the stats file is corrupted"),
+        }
+    }
+}
+
+impl From<io::Error> for StatsError {
+    fn from(err: io::Error) -> Self {
+        StatsError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("motus-stats-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_record_requires_opt_in() {
+        let path = temp_path("opt-in");
+
+        record(&path, PasswordKind::Random, 1).unwrap();
+        assert!(!path.exists());
+
+        Stats::new().save(&path).unwrap();
+        record(&path, PasswordKind::Random, 1).unwrap();
+        record(&path, PasswordKind::Pin, 3).unwrap();
+        record(&path, PasswordKind::Random, 2).unwrap();
+
+        let stats = Stats::load(&path).unwrap().unwrap();
+        assert_eq!(stats.passwords[&PasswordKind::Random], 3);
+        assert_eq!(stats.passwords[&PasswordKind::Pin], 3);
+        assert!(!stats.passwords.contains_key(&PasswordKind::Memorable));
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_load_corrupted() {
+        let path = temp_path("corrupted");
+        fs::write(&path, "mHYvjgQAKBHBIRYdpPAI").unwrap();
+
+        assert!(matches!(Stats::load(&path), Err(StatsError::Corrupted)));
+
+        fs::remove_file(path).unwrap();
+    }
+}
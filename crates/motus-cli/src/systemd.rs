@@ -0,0 +1,91 @@
+use std::fmt::{Display, Formatter};
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// encrypt encrypts `secret` into a credential named `name` with `systemd-creds encrypt`, and
+/// returns it, ready to be written where `LoadCredentialEncrypted=` reads it from.
+///
+/// The credential is bound to the host's credential key or TPM, as systemd-creds picks by
+/// default, so that it can only be decrypted on this machine. The secret is handed over on
+/// systemd-creds' standard input, never as an argument other processes could see.
+pub fn encrypt(name: &str, secret: &str) -> Result<Vec<u8>, SystemdCredError> {
+    let mut child = Command::new("systemd-creds")
+        .arg("encrypt")
+        .arg(format!("--name={}", name))
+        .args(["-", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => SystemdCredError::NotFound,
+            _ => SystemdCredError::Io(err),
+        })?;
+
+    child
+        .stdin
+        .take()
+        .expect("stdin should be piped")
+        .write_all(secret.as_bytes())?;
+
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(SystemdCredError::Failed(stderr.trim().to_string()));
+    }
+
+    Ok(output.stdout)
+}
+
+/// validate_name parses the given string as a credential name, which systemd requires to be a
+/// valid file name.
+pub fn validate_name(s: &str) -> Result<String, String> {
+    if s.is_empty() || s == "." || s == ".." || s.contains('/') || s.len() > 255 {
+        return Err(
+            "The credential name must be a valid file name, such as db-password".to_string(),
+        );
+    }
+    Ok(s.to_string())
+}
+
+/// SystemdCredError represents the errors that can occur while encrypting a credential.
+#[derive(Debug)]
+pub enum SystemdCredError {
+    Io(io::Error),
+    NotFound,
+    Failed(String),
+}
+
+impl Display for SystemdCredError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SystemdCredError::Io(err) => write!(f, "unable to run systemd-creds: {}", err),
+            SystemdCredError::NotFound => write!(
+                f,
+                "systemd-creds was not found; it ships with systemd 250 and later"
+            ),
+            SystemdCredError::Failed(err) => write!(f, "systemd-creds failed: {}", err),
+        }
+    }
+}
+
+impl From<io::Error> for SystemdCredError {
+    fn from(err: io::Error) -> Self {
+        SystemdCredError::Io(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate_name("db-password"), Ok("db-password".to_string()));
+        assert_eq!(validate_name("api.token"), Ok("api.token".to_string()));
+        assert!(validate_name("").is_err());
+        assert!(validate_name("..").is_err());
+        assert!(validate_name("../etc/passwd").is_err());
+        assert!(validate_name(&"a".repeat(256)).is_err());
+    }
+}
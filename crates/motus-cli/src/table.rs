@@ -0,0 +1,188 @@
+//! Tables the text reports are displayed in.
+//!
+//! With the `tables` feature, the tables are drawn by term_table. Without it, a plain renderer
+//! with the same interface lays the cells out on aligned lines instead, for minimal builds.
+
+#[cfg(feature = "tables")]
+pub use term_table::row::Row;
+#[cfg(feature = "tables")]
+pub use term_table::table_cell::{Alignment, TableCell};
+#[cfg(feature = "tables")]
+pub use term_table::{Table, TableStyle};
+
+#[cfg(not(feature = "tables"))]
+pub use plain::{Alignment, Row, Table, TableCell, TableStyle};
+
+#[cfg(not(feature = "tables"))]
+mod plain {
+    use std::fmt::Write;
+
+    /// TableStyle is accepted for compatibility with term_table, plain tables have no borders.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct TableStyle;
+
+    impl TableStyle {
+        pub fn extended() -> Self {
+            TableStyle
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub enum Alignment {
+        #[default]
+        Left,
+        Right,
+    }
+
+    /// TableCell is a cell of a plain table. Cells spanning several columns are displayed in a
+    /// single one.
+    #[derive(Clone, Debug)]
+    pub struct TableCell {
+        data: String,
+        alignment: Alignment,
+    }
+
+    impl TableCell {
+        pub fn new(data: impl ToString) -> Self {
+            Self::builder(data).build()
+        }
+
+        pub fn builder(data: impl ToString) -> TableCellBuilder {
+            TableCellBuilder {
+                data: data.to_string(),
+                alignment: Alignment::Left,
+            }
+        }
+    }
+
+    /// TableCellBuilder builds cells with the same interface as term_table's builder.
+    #[derive(Clone, Debug)]
+    pub struct TableCellBuilder {
+        data: String,
+        alignment: Alignment,
+    }
+
+    impl TableCellBuilder {
+        pub fn col_span(&mut self, _col_span: usize) -> &mut Self {
+            self
+        }
+
+        pub fn alignment(&mut self, alignment: Alignment) -> &mut Self {
+            self.alignment = alignment;
+            self
+        }
+
+        pub fn build(&self) -> TableCell {
+            TableCell {
+                data: self.data.clone(),
+                alignment: self.alignment,
+            }
+        }
+    }
+
+    #[derive(Clone, Debug)]
+    pub struct Row {
+        cells: Vec<TableCell>,
+    }
+
+    impl Row {
+        pub fn new(cells: impl IntoIterator<Item = TableCell>) -> Self {
+            Row {
+                cells: cells.into_iter().collect(),
+            }
+        }
+    }
+
+    /// Table lays its rows out on lines, with the cells of rows of several cells separated by
+    /// '|' and aligned in columns.
+    #[derive(Clone, Debug, Default)]
+    pub struct Table {
+        pub max_column_width: usize,
+        pub style: TableStyle,
+        rows: Vec<Row>,
+    }
+
+    impl Table {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn add_row(&mut self, row: Row) -> &mut Self {
+            self.rows.push(row);
+            self
+        }
+
+        pub fn render(&self) -> String {
+            let mut widths: Vec<usize> = Vec::new();
+            for row in self.rows.iter().filter(|row| row.cells.len() > 1) {
+                for (i, cell) in row.cells.iter().enumerate() {
+                    let width = console_width(&cell.data);
+                    match widths.get_mut(i) {
+                        Some(max) => *max = (*max).max(width),
+                        None => widths.push(width),
+                    }
+                }
+            }
+
+            let mut output = String::new();
+            for row in &self.rows {
+                let cells: Vec<String> = row
+                    .cells
+                    .iter()
+                    .enumerate()
+                    .map(|(i, cell)| {
+                        let padding = " ".repeat(
+                            widths
+                                .get(i)
+                                .filter(|_| row.cells.len() > 1 && i + 1 < row.cells.len())
+                                .map_or(0, |width| width.saturating_sub(console_width(&cell.data))),
+                        );
+                        match cell.alignment {
+                            Alignment::Left => format!("{}{}", cell.data, padding),
+                            Alignment::Right => format!("{}{}", padding, cell.data),
+                        }
+                    })
+                    .collect();
+                let _ = writeln!(output, "{}", cells.join(" | ").trim_end());
+            }
+
+            output
+        }
+    }
+
+    // console_width returns the number of characters of `text` displayed in the terminal,
+    // leaving out the escape sequences coloring it.
+    fn console_width(text: &str) -> usize {
+        let mut width = 0;
+        let mut escaped = false;
+        for c in text.chars() {
+            match c {
+                '\u{1b}' => escaped = true,
+                'm' if escaped => escaped = false,
+                _ if escaped => {}
+                _ => width += 1,
+            }
+        }
+        width
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_render() {
+            let mut table = Table::new();
+            table.add_row(Row::new(vec![TableCell::new("Title")]));
+            table.add_row(Row::new(vec![TableCell::new("a"), TableCell::new("1")]));
+            table.add_row(Row::new(vec![TableCell::new("bcd"), TableCell::new("2")]));
+
+            assert_eq!(table.render(), "Title\na   | 1\nbcd | 2\n");
+        }
+
+        #[test]
+        fn test_console_width() {
+            assert_eq!(console_width("\u{1b}[31mred\u{1b}[0m"), 3);
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::io::{self, BufWriter, Write};
+
+use motus::entropy::Entropy;
+use serde::Serialize;
+
+use crate::{csv_field, OutputFormat};
+
+/// display writes the generated usernames to stdout in the requested format.
+///
+/// Usernames are not secrets: they are never copied to the clipboard nor recorded in the history,
+/// and their entropy is only reported in JSON, for callers to tell how likely collisions are.
+pub fn display(usernames: &[String], entropy: &Entropy, format: &OutputFormat) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    match format {
+        OutputFormat::Text
+        | OutputFormat::SystemdCred
+        | OutputFormat::Sops
+        | OutputFormat::Html => {
+            for username in usernames {
+                writeln!(out, "{username}")?;
+            }
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer(
+                &mut out,
+                &UsernameOutput {
+                    usernames,
+                    entropy_bits: entropy.bits(),
+                },
+            )?;
+            writeln!(out)?;
+        }
+        OutputFormat::Csv => {
+            writeln!(out, "username")?;
+            for username in usernames {
+                writeln!(out, "{}", csv_field(username))?;
+            }
+        }
+    }
+
+    out.flush()
+}
+
+#[derive(Serialize)]
+struct UsernameOutput<'a> {
+    usernames: &'a [String],
+    entropy_bits: f64,
+}
@@ -200,10 +200,57 @@ fn test_memorable_command_analyze_json_output() {
                 "10^10/s": "57 years",
                 "10^4/s": "centuries"
             },
+            "typo": {
+                "neighbors": 268,
+                "adjacent_key": 196,
+                "case_slip": 35,
+                "transposition": 37,
+                "probability": 0.1776,
+            },
+            "warnings": [],
         },
     });
 }
 
+#[test]
+fn test_memorable_command_checksum_word() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // motus --seed 42 memorable --words 3 --style camel --checksum-word
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("memorable")
+        .arg("--words")
+        .arg("3")
+        .arg("--style")
+        .arg("camel")
+        .arg("--checksum-word")
+        .assert()
+        .success()
+        .stdout("chokingNaturalDollyChatty\n");
+
+    // The passphrase verifies whatever its separators and capitalization
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("verify-phrase")
+        .write_stdin("Choking-Natural-Dolly-Chatty\n")
+        .assert()
+        .success()
+        .stdout("The passphrase matches its verification word\n");
+
+    // A mistyped word is detected
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--output")
+        .arg("json")
+        .arg("verify-phrase")
+        .arg("choking neutral dolly chatty")
+        .assert()
+        .failure()
+        .stdout(
+            "{\"valid\":false,\"error\":\"the last word is not the verification word of the others: a word is likely mistyped\"}\n",
+        );
+}
+
 #[test]
 fn test_random_command_default_behavior() {
     let mut cmd = Command::cargo_bin("motus").unwrap();
@@ -282,6 +329,31 @@ fn test_random_command_all_options() {
         .stdout("mH)vj1Q^7B\n");
 }
 
+#[test]
+fn test_random_command_intl_safe_symbols() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // motus --seed 42 random --numbers --symbols --symbols-set intl-safe
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("random")
+        .arg("--numbers")
+        .arg("--symbols")
+        .arg("--symbols-set")
+        .arg("intl-safe")
+        .assert()
+        .success()
+        .stdout("mH_vj1Q-7B6BIRYdpPAI\n");
+
+    // `motus random --help` describes the sets in plain text
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd.args(["random", "--help"]).output().unwrap();
+    let help = String::from_utf8(output.stdout).unwrap();
+    assert!(help.contains("default:   The symbols !@#$%^&*()"));
+    assert!(!help.contains("[`"));
+}
+
 #[test]
 fn test_random_command_too_little_characters() {
     let mut cmd = Command::cargo_bin("motus").unwrap();
@@ -371,10 +443,68 @@ fn test_random_command_analyze_json_output() {
                 "10^10/s": "57 years",
                 "10^4/s": "centuries"
             },
+            "typo": {
+                "neighbors": 145,
+                "adjacent_key": 106,
+                "case_slip": 20,
+                "transposition": 19,
+                "probability": 0.1399,
+            },
+            "warnings": [],
         },
     });
 }
 
+#[test]
+fn test_case_option() {
+    // `motus --seed 7 --case lower random --numbers`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--seed", "7", "--case", "lower"])
+        .args(["random", "--numbers"])
+        .assert()
+        .success()
+        .stdout("ddqytdnaiqk50kauwx2y\n");
+
+    // Random passwords lose a bit of entropy per letter
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args([
+            "--no-clipboard",
+            "--seed",
+            "42",
+            "--case",
+            "upper",
+            "--explain",
+        ])
+        .args(["--output", "json", "random"])
+        .output()
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["password"], "GQZKEDVNSNQNRVZBHUNR");
+    assert_eq!(json["entropy"]["bits"], 94.0);
+
+    // `motus --seed 42 --case upper memorable`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args([
+        "--no-clipboard",
+        "--seed",
+        "42",
+        "--case",
+        "upper",
+        "memorable",
+    ])
+    .assert()
+    .success()
+    .stdout("CHOKEHOLD NATIVITY DOLLY OMINOUS THROAT\n");
+
+    // `motus --case lower random --password-rules 'required: upper'`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--case", "lower", "random"])
+        .args(["--password-rules", "required: upper"])
+        .assert()
+        .failure();
+}
+
 #[test]
 fn test_pin_command_default_behavior() {
     let mut cmd = Command::cargo_bin("motus").unwrap();
@@ -494,6 +624,3612 @@ fn test_pin_command_analyze_json_output() {
                 "10^10/s": "less than a second",
                 "10^4/s": "1 minute"
             },
+            "typo": {
+                "neighbors": 40,
+                "adjacent_key": 28,
+                "case_slip": 7,
+                "transposition": 5,
+                "probability": 0.0345,
+            },
+            "warnings": [],
         },
     });
 }
+
+#[test]
+fn test_fake_command_csv_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 fake --count 3 --output csv`
+    cmd.arg("--seed")
+        .arg("42")
+        .arg("fake")
+        .arg("--count")
+        .arg("3")
+        .arg("--output")
+        .arg("csv")
+        .assert()
+        .success()
+        .stdout(
+            "kind,password,insecure\n\
+             random,mHYvjgQAKBHBIRYdpPAI,false\n\
+             random,kVHltdbFmiaYXNZmZlTy,false\n\
+             random,XyGrpJLFnzfcRqoLteLW,false\n",
+        );
+}
+
+#[test]
+fn test_fake_command_weak_pin() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 fake --count 3 --kind pin --weak`
+    cmd.arg("--seed")
+        .arg("42")
+        .arg("fake")
+        .arg("--count")
+        .arg("3")
+        .arg("--kind")
+        .arg("pin")
+        .arg("--weak")
+        .assert()
+        .success()
+        .stdout("1964\n2002\n6666\n")
+        .stderr("warning: --weak passwords are INSECURE and must only be used as test data\n");
+}
+
+#[test]
+fn test_fake_command_weak_json_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --output json fake --count 2 --weak`
+    cmd.arg("--seed")
+        .arg("42")
+        .arg("--output")
+        .arg("json")
+        .arg("fake")
+        .arg("--count")
+        .arg("2")
+        .arg("--weak")
+        .assert()
+        .success()
+        .stdout(
+            r#"[{"kind":"random","password":"asdfgh1234","insecure":true},{"kind":"random","password":"@$hl3y","insecure":true}]
+"#,
+        );
+}
+
+#[test]
+fn test_fake_command_zero_count() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus fake --count 0`
+    cmd.arg("fake").arg("--count").arg("0").assert().failure();
+}
+
+#[test]
+fn test_history_warns_about_similar_password() {
+    let dir = tempfile::tempdir().unwrap();
+    let history_file = dir.path().join("history.enc");
+
+    // `motus --seed 42 --history --label db random`, twice in a row
+    for expected_stderr in ["", "warning: the generated password is 100% similar to one previously generated for 'db'; consider generating a new one\n"] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+            .arg("--no-clipboard")
+            .arg("--seed")
+            .arg("42")
+            .arg("--history")
+            .arg("--history-file")
+            .arg(&history_file)
+            .arg("--label")
+            .arg("db")
+            .arg("random")
+            .assert()
+            .success()
+            .stdout("mHYvjgQAKBHBIRYdpPAI\n")
+            .stderr(expected_stderr);
+    }
+}
+
+#[test]
+fn test_history_requires_label() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --history random`
+    cmd.arg("--no-clipboard")
+        .arg("--history")
+        .arg("random")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_no_clipboard_hints_flag() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --no-clipboard-hints --seed 42 pin`
+    cmd.arg("--no-clipboard")
+        .arg("--no-clipboard-hints")
+        .arg("--seed")
+        .arg("42")
+        .arg("pin")
+        .assert()
+        .success()
+        .stdout("5564047\n");
+}
+
+#[test]
+fn test_analyze_command_keyboard_walk_warnings() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `echo "Qwerty1qaz" | motus --output json analyze`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("analyze")
+        .write_stdin("Qwerty1qaz\n")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    assert_eq!(
+        json["warnings"],
+        serde_json::json!([
+            {
+                "category": "keyboard-walk",
+                "token": "Qwerty",
+                "message": "keys follow each other along a keyboard row",
+            },
+            {
+                "category": "keyboard-walk",
+                "token": "1qaz",
+                "message": "keys follow each other across keyboard rows",
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_analyze_command_empty_password() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `echo "" | motus analyze`
+    cmd.arg("analyze").write_stdin("\n").assert().failure();
+}
+
+#[test]
+fn test_analyze_command_file() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("passwords.txt");
+    std::fs::write(
+        &path,
+        "Summer2023!\nmHYvjgQAKBHBIRYdpPAI\n\nSummer2024!\nmHYvjgQAKBHBIRYdpPAI\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json analyze --file passwords.txt`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("analyze")
+        .arg("--file")
+        .arg(&path)
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    assert_eq!(json["total"], 4);
+    assert_eq!(json["duplicates"], serde_json::json!([[2, 5]]));
+    assert_eq!(json["clusters"], serde_json::json!([[1, 4]]));
+}
+
+#[test]
+fn test_analyze_command_file_csv_column() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("export.csv");
+    std::fs::write(
+        &path,
+        "name,password\ndb,hunter2\nmail,\"hunter2\"\nvpn,5564047\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json analyze --file export.csv --column password`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("analyze")
+        .arg("--file")
+        .arg(&path)
+        .arg("--column")
+        .arg("password")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    assert_eq!(json["total"], 3);
+    assert_eq!(json["duplicates"], serde_json::json!([[2, 3]]));
+
+    // Unknown columns are reported as errors
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("analyze")
+        .arg("--file")
+        .arg(&path)
+        .arg("--column")
+        .arg("secret")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_count_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --count 3 pin`
+    cmd.arg("--seed")
+        .arg("42")
+        .arg("--count")
+        .arg("3")
+        .arg("pin")
+        .assert()
+        .success()
+        .stdout("5564047\n8109515\n2050685\n");
+}
+
+#[test]
+fn test_count_option_aggregates_analysis() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --count 3 --analyze --output json pin`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--count")
+        .arg("3")
+        .arg("--analyze")
+        .arg("--output")
+        .arg("json")
+        .arg("pin")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    assert_eq!(json["passwords"].as_array().map(Vec::len), Some(3));
+    assert_eq!(json["summary"]["count"], 3);
+    assert!(json["passwords"][0].get("analysis").is_none());
+}
+
+#[test]
+fn test_count_option_per_item_analysis() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --count 2 --analyze --per-item --output json pin`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--count")
+        .arg("2")
+        .arg("--analyze")
+        .arg("--per-item")
+        .arg("--output")
+        .arg("json")
+        .arg("pin")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    let passwords = json["passwords"]
+        .as_array()
+        .expect("passwords should be listed");
+    assert_eq!(passwords.len(), 2);
+    assert!(passwords.iter().all(|p| p.get("analysis").is_some()));
+}
+
+#[test]
+fn test_explain_option_reports_separator_entropy() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --no-clipboard --explain --output json memorable --separator numbers`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--no-clipboard")
+        .arg("--explain")
+        .arg("--output")
+        .arg("json")
+        .arg("memorable")
+        .arg("--separator")
+        .arg("numbers")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    // Four random digits are inserted between the five words
+    assert_eq!(
+        json["entropy"]["components"][1],
+        serde_json::json!({"source": "separators", "bits": 13.3})
+    );
+}
+
+#[test]
+fn test_memorable_command_separator_count() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --no-clipboard memorable --separator numbers --separator-count 2`
+    cmd.arg("--seed")
+        .arg("42")
+        .arg("--no-clipboard")
+        .arg("memorable")
+        .arg("--separator")
+        .arg("numbers")
+        .arg("--separator-count")
+        .arg("2")
+        .assert()
+        .success()
+        .stdout("chokehold64nativity06dolly43ominous78throat\n");
+}
+
+#[test]
+fn test_prefix_and_suffix_options() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --no-clipboard --prefix ACME- --suffix ! --explain --output json pin`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--no-clipboard")
+        .arg("--prefix")
+        .arg("ACME-")
+        .arg("--suffix")
+        .arg("!")
+        .arg("--explain")
+        .arg("--output")
+        .arg("json")
+        .arg("pin")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    assert_eq!(json["password"], "ACME-5564047!");
+    assert_eq!(
+        json["entropy"],
+        serde_json::json!({
+            "bits": 23.3,
+            "components": [
+                {"source": "digits", "bits": 23.3},
+                {"source": "static prefix", "bits": 0.0},
+                {"source": "static suffix", "bits": 0.0},
+            ]
+        })
+    );
+}
+
+#[test]
+fn test_random_command_exclude_chars() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --no-clipboard random --numbers --symbols --exclude-chars "()0Oa"`
+    cmd.arg("--seed")
+        .arg("42")
+        .arg("--no-clipboard")
+        .arg("random")
+        .arg("--numbers")
+        .arg("--symbols")
+        .arg("--exclude-chars")
+        .arg("()0Oa")
+        .assert()
+        .success()
+        .stdout("nG*vk2R%7B6AISYdqPAH\n");
+}
+
+#[test]
+fn test_random_command_exclude_every_letter() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --no-clipboard random --exclude-chars "a..zA..Z"`
+    cmd.arg("--no-clipboard")
+        .arg("random")
+        .arg("--exclude-chars")
+        .arg("abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_random_command_exclude_every_digit() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --no-clipboard random --numbers --exclude-chars 0123456789`
+    cmd.arg("--no-clipboard")
+        .arg("random")
+        .arg("--numbers")
+        .arg("--exclude-chars")
+        .arg("0123456789")
+        .assert()
+        .failure()
+        .stderr("error: --exclude-chars cannot exclude every digit when --numbers is given\n");
+
+    // Without --numbers, digits are not expected in the first place
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("random")
+        .arg("--exclude-chars")
+        .arg("0123456789")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_random_command_start_with_letter() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --count 50 random --characters 8 --numbers --symbols --start-with letter`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--count")
+        .arg("50")
+        .arg("random")
+        .arg("--characters")
+        .arg("8")
+        .arg("--numbers")
+        .arg("--symbols")
+        .arg("--start-with")
+        .arg("letter")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    assert_eq!(stdout.lines().count(), 50);
+    assert!(stdout
+        .lines()
+        .all(|password| password.starts_with(|c: char| c.is_ascii_alphabetic())));
+}
+
+#[test]
+fn test_random_command_max_repeat() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --count 50 random --characters 100 --max-repeat 1`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--count")
+        .arg("50")
+        .arg("random")
+        .arg("--characters")
+        .arg("100")
+        .arg("--max-repeat")
+        .arg("1")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    for password in stdout.lines() {
+        let chars: Vec<char> = password.chars().collect();
+        assert!(chars.windows(2).all(|w| w[0] != w[1]));
+    }
+
+    // A single letter left can only be repeated
+    let excluded: String = ('b'..='z').chain('A'..='Z').collect();
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args([
+        "--no-clipboard",
+        "random",
+        "--max-repeat",
+        "1",
+        "--exclude-chars",
+        &excluded,
+        "-c",
+        "8",
+    ])
+    .assert()
+    .failure()
+    .stderr("error: the excluded characters leave too few to comply with the rule: at most 1 identical characters in a row\n");
+}
+
+#[test]
+fn test_random_command_policy_preset() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --count 50 random --characters 8 --policy-preset pci-dss`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--count")
+        .arg("50")
+        .arg("random")
+        .arg("--characters")
+        .arg("8")
+        .arg("--policy-preset")
+        .arg("pci-dss")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    // The preset raises the length to 12, and requires letters and digits
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    assert_eq!(stdout.lines().count(), 50);
+    for password in stdout.lines() {
+        assert_eq!(password.chars().count(), 12);
+        assert!(password.chars().any(|c| c.is_ascii_alphabetic()));
+        assert!(password.chars().any(|c| c.is_ascii_digit()));
+    }
+
+    // `motus random --policy-preset pci-dss --exclude-chars 0123456789` leaves no digit to require
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args([
+        "--no-clipboard",
+        "random",
+        "--policy-preset",
+        "pci-dss",
+        "--exclude-chars",
+        "0123456789",
+    ])
+    .assert()
+    .failure()
+    .stderr(
+        "error: the excluded characters leave too few to comply with the rule: contains a digit\n",
+    );
+}
+
+#[test]
+fn test_policy_check_command() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("corp.toml");
+    std::fs::write(
+        &path,
+        "min_length = 12\nrequire = [\"digit\"]\nstart_with = \"letter\"\n",
+    )
+    .unwrap();
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `echo correcthorse | motus --output json policy check --policy corp.toml`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("policy")
+        .arg("check")
+        .arg("--policy")
+        .arg(&path)
+        .write_stdin("correcthorse\n")
+        .output()
+        .expect("failed to execute process");
+    assert!(!output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    assert_eq!(json["schema_version"], 2);
+    assert_eq!(json["compliant"], false);
+    assert_eq!(json["rules"][0]["passed"], true);
+    assert_eq!(json["rules"][1]["rule"], "contains a digit");
+    assert_eq!(json["rules"][1]["passed"], false);
+
+    // `motus --output json --output-schema v1 policy check --policy corp.toml`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args([
+            "--output",
+            "json",
+            "--output-schema",
+            "v1",
+            "policy",
+            "check",
+            "--policy",
+        ])
+        .arg(&path)
+        .write_stdin("correcthorse7\n")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+    assert!(json.get("schema_version").is_none());
+    assert_eq!(json["compliant"], true);
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("policy")
+        .arg("check")
+        .arg("--policy")
+        .arg(&path)
+        .write_stdin("correcthorse7\n")
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_policy_check_command_invalid_file() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("corp.toml");
+    std::fs::write(&path, "min_lenght = 12\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .arg("policy")
+        .arg("check")
+        .arg("--policy")
+        .arg(&path)
+        .arg("--password")
+        .arg("correcthorse")
+        .output()
+        .expect("failed to execute process");
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).expect("output should be valid UTF-8");
+    assert!(stderr.contains("invalid policy file"));
+}
+
+#[test]
+fn test_policy_check_command_reports_invalid_value_location() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("corp.toml");
+    std::fs::write(&path, "min_length = 12\nmax_length = 8\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .arg("policy")
+        .arg("check")
+        .arg("--policy")
+        .arg(&path)
+        .arg("--password")
+        .arg("correcthorse")
+        .output()
+        .expect("failed to execute process");
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).expect("output should be valid UTF-8");
+    assert!(stderr.contains("at line 2, column 14"));
+}
+
+#[test]
+fn test_policy_schema_command() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus policy schema`
+    let output = cmd
+        .arg("policy")
+        .arg("schema")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    assert_eq!(json["type"], "object");
+    assert_eq!(json["additionalProperties"], false);
+    for field in [
+        "min_length",
+        "max_length",
+        "require",
+        "min_classes",
+        "start_with",
+        "max_repeat",
+    ] {
+        assert!(json["properties"][field].is_object(), "{field}");
+    }
+}
+
+#[test]
+fn test_min_entropy_bits_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --min-entropy-bits 20 pin --numbers 3`
+    let output = cmd
+        .arg("--no-clipboard")
+        .arg("--min-entropy-bits")
+        .arg("20")
+        .arg("pin")
+        .arg("--numbers")
+        .arg("3")
+        .output()
+        .expect("failed to execute process");
+    assert!(!output.status.success());
+
+    let stderr = String::from_utf8(output.stderr).expect("output should be valid UTF-8");
+    assert!(stderr.contains("below the 20 bits floor"));
+}
+
+#[test]
+fn test_min_entropy_bits_option_unenforced_commands() {
+    // Commands the floor cannot hold to it refuse it rather than generating weaker secrets
+    for command in [&["card"][..], &["fake", "--count", "1"], &["username"]] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        let output = cmd
+            .args(["--no-clipboard", "--min-entropy-bits", "200"])
+            .args(command)
+            .output()
+            .expect("failed to execute process");
+        assert!(!output.status.success(), "{command:?}");
+        assert!(output.stdout.is_empty(), "{command:?}");
+
+        let stderr = String::from_utf8(output.stderr).expect("output should be valid UTF-8");
+        assert!(stderr.contains("--min-entropy-bits only applies to"));
+    }
+}
+
+#[test]
+fn test_min_entropy_bits_option_apple() {
+    // Apple-style passwords hold 91.5 bits, whatever --auto-strengthen says
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--min-entropy-bits", "100", "--auto-strengthen", "apple"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr("error: Apple-style passwords only have 91.5 bits of entropy, below the 100 bits floor, and cannot be lengthened\n");
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--min-entropy-bits", "90", "apple"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_min_entropy_bits_option_identity() {
+    // The 6-digit PIN is the weakest secret of an identity
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--min-entropy-bits", "30", "identity"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr("error: identity PINs only have 19.9 bits of entropy, below the 30 bits floor, and cannot be lengthened\n");
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--min-entropy-bits", "19", "identity"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_min_entropy_bits_option_answers() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--min-entropy-bits", "50", "answers"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr("error: the requested password only has 38.7 bits of entropy, below the 50 bits floor; increase --words or use --auto-strengthen\n");
+
+    // `motus --seed 4 --min-entropy-bits 50 --auto-strengthen answers --count 1`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--seed", "4", "--min-entropy-bits", "50"])
+        .args(["--auto-strengthen", "answers", "--count", "1"])
+        .assert()
+        .success()
+        .stdout("question 1: retype nintendo bouncing rebate\n");
+}
+
+#[test]
+fn test_min_entropy_bits_option_auto_strengthen() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --min-entropy-bits 20 --auto-strengthen pin --numbers 3`
+    let output = cmd
+        .arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--min-entropy-bits")
+        .arg("20")
+        .arg("--auto-strengthen")
+        .arg("pin")
+        .arg("--numbers")
+        .arg("3")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    assert_eq!(stdout.trim(), "5564047");
+}
+
+#[test]
+fn test_pin_command_radix() {
+    for (radix, alphabet) in [
+        ("hex", "0123456789abcdef"),
+        ("base32", "ABCDEFGHIJKLMNOPQRSTUVWXYZ234567"),
+        ("alnum", "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ"),
+    ] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+
+        // `motus --seed 42 --count 20 pin --numbers 12 --radix <radix>`
+        let output = cmd
+            .arg("--seed")
+            .arg("42")
+            .arg("--count")
+            .arg("20")
+            .arg("pin")
+            .arg("--numbers")
+            .arg("12")
+            .arg("--radix")
+            .arg(radix)
+            .output()
+            .expect("failed to execute process");
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+        for code in stdout.lines() {
+            assert_eq!(code.len(), 12);
+            assert!(
+                code.chars().all(|c| alphabet.contains(c)),
+                "{radix}: {code}"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_group_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --no-clipboard --group 4 random`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--no-clipboard")
+        .arg("--group")
+        .arg("4")
+        .arg("random")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    assert_eq!(stdout.trim(), "mHYv-jgQA-KBHB-IRYd-pPAI");
+}
+
+#[test]
+fn test_group_separator_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --no-clipboard --group 3 --group-separator ' ' pin`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--no-clipboard")
+        .arg("--group")
+        .arg("3")
+        .arg("--group-separator")
+        .arg(" ")
+        .arg("pin")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    assert_eq!(stdout.trim(), "556 404 7");
+}
+
+#[test]
+fn test_apple_command() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --no-clipboard apple`
+    let output = cmd
+        .arg("--seed")
+        .arg("42")
+        .arg("--no-clipboard")
+        .arg("apple")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    let password = stdout.trim();
+    assert_eq!(password, "oqKktw-day5kd-nfanbq");
+    assert_eq!(password.chars().filter(char::is_ascii_uppercase).count(), 1);
+    assert_eq!(password.chars().filter(char::is_ascii_digit).count(), 1);
+}
+
+#[test]
+fn test_analyze_command_attack_models() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json analyze --attack-rate 1e12/s --attack-rate 3600/h --attack-cost 2 Summer2023!`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("analyze")
+        .arg("--attack-rate")
+        .arg("1e12/s")
+        .arg("--attack-rate")
+        .arg("3600/h")
+        .arg("--attack-cost")
+        .arg("2")
+        .arg("Summer2023!")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    let attacks = json["attacks"]
+        .as_array()
+        .expect("attacks should be listed");
+    assert_eq!(attacks.len(), 2);
+    assert_eq!(attacks[0]["guesses_per_second"], 1e12);
+    assert_eq!(attacks[1]["guesses_per_second"], 1.0);
+    assert!(attacks[0]["time"].is_string());
+
+    // The slower attacker takes 10^12 times longer, and costs as much more
+    let seconds = |attack: &serde_json::Value| attack["seconds"].as_f64().unwrap();
+    assert!((seconds(&attacks[1]) / seconds(&attacks[0]) / 1e12 - 1.0).abs() < 1e-9);
+    assert!(attacks[1]["cost"].as_f64().unwrap() > attacks[0]["cost"].as_f64().unwrap());
+}
+
+#[test]
+fn test_analyze_command_estimators() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json analyze --estimator zxcvbn,markov --estimator entropy lantern`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("analyze")
+        .arg("--estimator")
+        .arg("zxcvbn,markov")
+        .arg("--estimator")
+        .arg("entropy")
+        .arg("lantern")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    let estimates = json["estimates"]
+        .as_array()
+        .expect("estimates should be listed");
+    let estimators: Vec<&str> = estimates
+        .iter()
+        .map(|estimate| estimate["estimator"].as_str().unwrap())
+        .collect();
+    assert_eq!(estimators, ["zxcvbn", "markov", "entropy"]);
+    assert_eq!(estimates[0]["guesses"], json["guesses"]);
+
+    // The Markov model knows the word, where the pool of characters only sees lowercase letters
+    let bits = |estimate: &serde_json::Value| estimate["bits"].as_f64().unwrap();
+    assert!(bits(&estimates[1]) < bits(&estimates[2]));
+    assert!((bits(&estimates[2]) - 7.0 * 26f64.log2()).abs() < 0.01);
+}
+
+#[test]
+fn test_analyze_command_locale() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --locale fr analyze Qwerty1qaz`
+    let output = cmd
+        .arg("--locale")
+        .arg("fr")
+        .arg("analyze")
+        .arg("Qwerty1qaz")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    assert!(stdout.contains("Analyse de sécurité"));
+    assert!(stdout.contains("Robustesse"));
+    assert!(stdout.contains("suite de touches"));
+    assert!(!stdout.contains("Typo likelihood"));
+}
+
+#[test]
+fn test_analyze_command_humanize() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --humanize --locale fr analyze lantern`
+    let output = cmd
+        .arg("--humanize")
+        .arg("--locale")
+        .arg("fr")
+        .arg("analyze")
+        .arg("lantern")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    // Fast offline attacks find a single word instantly
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    assert!(stdout.contains("moins qu'un clignement d'œil"));
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .arg("analyze")
+        .arg("lantern")
+        .output()
+        .expect("failed to execute process");
+    let stdout = String::from_utf8(output.stdout).expect("output should be valid UTF-8");
+    assert!(!stdout.contains("blink of an eye"));
+}
+
+#[test]
+fn test_analyze_command_html_report() {
+    let dir = tempfile::tempdir().expect("unable to create a temporary directory");
+    let path = dir.path().join("report.html");
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--output")
+        .arg("html")
+        .arg("--report-file")
+        .arg(&path)
+        .arg("analyze")
+        .arg("Qwerty1qaz<")
+        .assert()
+        .success();
+
+    let html = std::fs::read_to_string(&path).expect("the report should be written");
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(
+            mode & 0o777,
+            0o600,
+            "the report should only be readable by its owner"
+        );
+    }
+    assert!(html.contains("Crack time estimations"));
+    assert!(html.contains("&#39;Qwerty&#39;: keys follow each other"));
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .arg("--output")
+        .arg("html")
+        .arg("info")
+        .output()
+        .expect("failed to execute process");
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("analysis reports only apply"));
+}
+
+#[cfg(feature = "pdf")]
+#[test]
+fn test_analyze_command_pdf_report() {
+    let dir = tempfile::tempdir().expect("unable to create a temporary directory");
+    let path = dir.path().join("report.pdf");
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--output")
+        .arg("html")
+        .arg("--report-file")
+        .arg(&path)
+        .arg("analyze")
+        .arg("Qwerty1qaz<")
+        .assert()
+        .success();
+
+    let pdf = std::fs::read(&path).expect("the report should be written");
+    assert!(pdf.starts_with(b"%PDF"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(
+            mode & 0o777,
+            0o600,
+            "the report should only be readable by its owner"
+        );
+    }
+}
+
+#[test]
+fn test_analyze_command_policy() {
+    let dir = tempfile::tempdir().expect("unable to create a temporary directory");
+    let policy = dir.path().join("policy.toml");
+    std::fs::write(&policy, "min_length = 30\nrequire = [\"digit\"]\n")
+        .expect("unable to write the policy");
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("analyze")
+        .arg("--policy")
+        .arg(&policy)
+        .arg("Qwerty1qaz")
+        .output()
+        .expect("failed to execute process");
+    assert!(!output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+    assert_eq!(json["policy"]["compliant"], false);
+    assert_eq!(json["policy"]["rules"][1]["passed"], true);
+    assert!(json["strength"].is_string());
+}
+
+#[test]
+fn test_info_command() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json info`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("info")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    assert!(json["git_commit"].is_string());
+    assert!(json["features"].is_array());
+    assert!(json["wordlist"]["words"].as_u64().unwrap() > 7000);
+    assert!(json["wordlist"]["fingerprint"]
+        .as_str()
+        .unwrap()
+        .starts_with("fnv1a64:"));
+}
+
+#[test]
+fn test_stats_command() {
+    let dir = tempfile::tempdir().unwrap();
+    let stats_file = dir.path().join("stats.json");
+    let motus = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.arg("--no-clipboard")
+            .arg("--stats-file")
+            .arg(&stats_file)
+            .args(args)
+            .assert()
+    };
+
+    // Nothing is recorded until stats are enabled
+    motus(&["random"]).success();
+    assert!(!stats_file.exists());
+    motus(&["stats"]).failure();
+
+    // `motus stats enable`, then generate a few passwords
+    motus(&["stats", "enable"]).success();
+    motus(&["--seed", "42", "--count", "3", "random"]).success();
+    motus(&["--seed", "42", "pin"]).success();
+
+    let output = motus(&["--output", "json", "stats"]).success();
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.get_output().stdout).expect("unable to parse json output");
+    assert_eq!(json["passwords"]["random"], 3);
+    assert_eq!(json["passwords"]["pin"], 1);
+    assert!(json["passwords"]["memorable"].is_null());
+
+    // The stats file never contains the generated passwords
+    let content = std::fs::read_to_string(&stats_file).unwrap();
+    assert!(!content.contains("mHYvjgQAKBHBIRYdpPAI"));
+    assert!(!content.contains("5564047"));
+
+    // `motus stats disable` deletes the recorded stats
+    motus(&["stats", "disable"]).success();
+    assert!(!stats_file.exists());
+}
+
+#[test]
+fn test_environment_variables() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `MOTUS_NO_CLIPBOARD=1 MOTUS_OUTPUT=csv MOTUS_SEPARATOR=hyphen motus --seed 42 memorable --words 3`
+    cmd.env("MOTUS_NO_CLIPBOARD", "1")
+        .env("MOTUS_OUTPUT", "csv")
+        .env("MOTUS_SEPARATOR", "hyphen")
+        .arg("--seed")
+        .arg("42")
+        .arg("memorable")
+        .arg("--words")
+        .arg("3")
+        .assert()
+        .success()
+        .stdout("kind,password\nmemorable,choking-natural-dolly\n");
+}
+
+#[test]
+fn test_configuration_precedence() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = dir.path().join("config.toml");
+    std::fs::write(
+        &config,
+        "[default]\nno_clipboard = true\noutput = \"json\"\n\n[profiles.ci]\noutput = \"csv\"\nseparator = \"comma\"\n",
+    )
+    .unwrap();
+    let motus = |env: &[(&str, &str)], args: &[&str]| {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.envs(env.iter().copied())
+            .arg("--config")
+            .arg(&config)
+            .arg("--seed")
+            .arg("42")
+            .args(args)
+            .assert()
+    };
+
+    // The [default] table applies when no profile is selected
+    motus(&[], &["pin"])
+        .success()
+        .stdout("{\"schema_version\":2,\"kind\":\"pin\",\"password\":\"5564047\"}\n");
+
+    // Profiles override it, whether selected by flag or environment variable
+    motus(&[], &["--profile", "ci", "pin"])
+        .success()
+        .stdout("kind,password\npin,5564047\n");
+    motus(&[("MOTUS_PROFILE", "ci")], &["memorable", "--words", "3"])
+        .success()
+        .stdout("kind,password\nmemorable,\"choking,natural,dolly\"\n");
+
+    // Environment variables override the configuration file, and flags override both
+    motus(
+        &[("MOTUS_PROFILE", "ci"), ("MOTUS_OUTPUT", "text")],
+        &["pin"],
+    )
+    .success()
+    .stdout("5564047\n");
+    motus(&[("MOTUS_OUTPUT", "text")], &["--output", "csv", "pin"])
+        .success()
+        .stdout("kind,password\npin,5564047\n");
+
+    // Selecting a profile the file lacks is an error
+    motus(&[], &["--profile", "staging", "pin"])
+        .failure()
+        .stderr("error: no profile named 'staging' in the configuration file\n");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_clipboard_skipped_when_headless() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `SSH_TTY=/dev/pts/0 motus --seed 42 random`, without any display
+    cmd.env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .env_remove("MOTUS_NO_CLIPBOARD")
+        .env("SSH_TTY", "/dev/pts/0")
+        .arg("--seed")
+        .arg("42")
+        .arg("random")
+        .assert()
+        .success()
+        .stdout("mHYvjgQAKBHBIRYdpPAI\n")
+        .stderr("note: not copying the password to the clipboard (running over SSH without display forwarding); use --clipboard force to copy it anyway\n");
+}
+
+#[test]
+#[cfg(not(target_os = "macos"))]
+fn test_clipboard_local_only_unsupported() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --clipboard force --clipboard-local-only --seed 42 random`
+    cmd.env_remove("MOTUS_NO_CLIPBOARD")
+        .arg("--clipboard")
+        .arg("force")
+        .arg("--clipboard-local-only")
+        .arg("--seed")
+        .arg("42")
+        .arg("random")
+        .assert()
+        .success()
+        .stdout("mHYvjgQAKBHBIRYdpPAI\n")
+        .stderr("warning: unable to copy the password to the clipboard: keeping the clipboard local is only supported on macOS\n");
+}
+
+#[test]
+fn test_ephemeral_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --ephemeral --seed 42 random | cat` prints the password as is, without waiting
+    cmd.arg("--no-clipboard")
+        .arg("--ephemeral")
+        .arg("--seed")
+        .arg("42")
+        .arg("random")
+        .assert()
+        .success()
+        .stdout("mHYvjgQAKBHBIRYdpPAI\n");
+
+    // `motus --ephemeral --analyze random`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--ephemeral")
+        .arg("--analyze")
+        .arg("random")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_seed_from_input() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let rolls = dir.path().join("rolls.txt");
+    std::fs::write(&rolls, "3 5 1 6 2 4 6 6 1 2\n").unwrap();
+
+    // `motus --seed-file rolls.txt random`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--seed-file")
+        .arg(&rolls)
+        .arg("random")
+        .assert()
+        .success()
+        .stdout("JEGZZvoyaNEtxrQjPTzV\n");
+
+    // `motus --seed-stdin random < rolls.txt` generates the same password
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--seed-stdin")
+        .arg("random")
+        .write_stdin("3 5 1 6 2 4 6 6 1 2\n")
+        .assert()
+        .success()
+        .stdout("JEGZZvoyaNEtxrQjPTzV\n");
+
+    // `motus --seed-stdin random < /dev/null`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--seed-stdin")
+        .arg("random")
+        .write_stdin("")
+        .assert()
+        .failure()
+        .stderr("error: the seed input is empty\n");
+
+    // `motus --seed 42 --seed-file rolls.txt random`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--seed-file")
+        .arg(&rolls)
+        .arg("random")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_dice_command() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json --explain dice --rolls 6 --characters`, with a rejected number
+    cmd.args(["--output", "json", "--explain", "dice", "--rolls", "6", "--characters"])
+        .write_stdin("1 1 2\n6 6 6\n")
+        .assert()
+        .success()
+        .stdout(
+            r#"{"password":"b","collected_bits":15.5,"entropy":{"bits":5.7,"components":[{"source":"characters","bits":5.7}]},"derivation":[{"outcomes":["1","1","2"],"value":1,"range":216,"limit":208,"index":1},{"outcomes":["6","6","6"],"value":215,"range":216,"limit":208,"index":null}]}
+"#,
+        );
+
+    // `motus dice --rolls 5`, with too few rolls typed in
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["dice", "--rolls", "5"])
+        .write_stdin("1 2 3\n")
+        .assert()
+        .failure()
+        .stderr("error: expected 5 rolls, got 3\n");
+
+    // `motus dice --rolls 3`, too few to draw a word
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["dice", "--rolls", "3"])
+        .write_stdin("1 2 3\n")
+        .assert()
+        .failure()
+        .stderr("error: the rolls are too few to draw a single word\n");
+
+    // `motus --min-entropy-bits 80 dice`, with piped rolls falling short of the floor
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--min-entropy-bits", "80", "dice"])
+        .write_stdin(format!("{}\n", ["1"; 25].join(" ")))
+        .assert()
+        .failure()
+        .stderr("error: the password holds 64.5 bits of the 64.6 collected from 25 rolls, below the 80 bits floor; type in about 10 more rolls\n");
+}
+
+#[test]
+fn test_coins_and_deck_commands() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus coins --flips 6 --characters`: 000001 is the letter b
+    cmd.args(["coins", "--flips", "6", "--characters"])
+        .write_stdin("TTTTTH\n")
+        .assert()
+        .success()
+        .stdout("b\n");
+
+    // `motus deck --cards 2 --characters --interactive`, with mistyped cards asked for again:
+    // 2S is the digit 1 in base 52, the letter b, and AS alone is too few for another letter
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["deck", "--cards", "2", "--characters", "--interactive"])
+        .write_stdin("2S\n2S\nZZ\nas\n")
+        .assert()
+        .success()
+        .stdout("b\n")
+        .stderr(
+            "Card 1 of 2: Card 2 of 2: 2S was already drawn from the deck; try again\nCard 2 of 2: invalid outcome \"ZZ\"; try again\nCard 2 of 2: note: collected 11.4 bits from 2 cards, of which the password holds 5.7\n",
+        );
+}
+
+#[test]
+fn test_split_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --split 2 memorable -w 6` cuts the passphrase between words
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--split")
+        .arg("2")
+        .arg("memorable")
+        .arg("-w")
+        .arg("6")
+        .assert()
+        .success()
+        .stdout(
+            "Part 1 of 2: chokehold nativity dolly\nPart 2 of 2: omen thriving woozy\n\nHand each part to a different custodian. To recombine the secret, type the parts in order, separated by a space, or run: motus recombine --parts 2 --separator ' '\n",
+        );
+
+    // `motus recombine --separator ' '`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["recombine", "--separator", " "])
+        .write_stdin("chokehold nativity dolly\nomen thriving woozy\n")
+        .assert()
+        .success()
+        .stdout("chokehold nativity dolly omen thriving woozy\n");
+
+    // Parts too weak to stand on their own are refused: a custodian would easily guess the others
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--split", "2", "pin"])
+        .assert()
+        .failure()
+        .stderr("error: a part of the split password would only hold 10.0 bits of entropy, below the 32 bits every part needs; generate a longer password or split it into fewer parts\n");
+
+    // `motus --seed 42 --split 3 --output json random`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args([
+        "--no-clipboard",
+        "--seed",
+        "42",
+        "--split",
+        "3",
+        "--output",
+        "json",
+        "random",
+    ])
+    .assert()
+    .success()
+    .stdout(
+        r#"{"kind":"random","parts":["mHYvjgQ","AKBHBIR","YdpPAI"],"separator":""}
+"#,
+    );
+}
+
+#[test]
+fn test_random_watch_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `echo | motus --seed 42 random --watch 1s | cat` prints a single password, and stops on Enter
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("random")
+        .arg("--watch")
+        .arg("1s")
+        .write_stdin("\n")
+        .assert()
+        .success()
+        .stdout("mHYvjgQAKBHBIRYdpPAI\n");
+
+    // `motus --count 2 random --watch 1s`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--count")
+        .arg("2")
+        .arg("random")
+        .arg("--watch")
+        .arg("1s")
+        .assert()
+        .failure();
+
+    // `motus --output json random --watch 1s`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--output")
+        .arg("json")
+        .arg("random")
+        .arg("--watch")
+        .arg("1s")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_practice_option_requires_single_password() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --practice --count 2 memorable`
+    cmd.arg("--no-clipboard")
+        .arg("--practice")
+        .arg("--count")
+        .arg("2")
+        .arg("memorable")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_memorable_command_hints() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json --seed 42 memorable --hints`
+    let output = cmd
+        .arg("--no-clipboard")
+        .arg("--output")
+        .arg("json")
+        .arg("--seed")
+        .arg("42")
+        .arg("memorable")
+        .arg("--hints")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+    let hints = json["hints"].as_array().unwrap();
+
+    // "ominous" is not in the glossary, and gets no hint
+    assert_eq!(hints.len(), 4);
+    assert_eq!(hints[0]["word"], "chokehold");
+    assert_eq!(
+        hints[0]["hint"],
+        "a grip around the neck that cuts off breath"
+    );
+    assert_eq!(hints[3]["word"], "throat");
+    assert!(hints.iter().all(|hint| hint["word"] != "ominous"));
+}
+
+#[test]
+fn test_memorable_command_story() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 memorable --words 3 --story`
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("memorable")
+        .arg("--words")
+        .arg("3")
+        .arg("--story")
+        .assert()
+        .success()
+        .stdout("choking natural dolly\nThe CHOKING traded its NATURAL for a nap. Then the DOLLY showed up.\n");
+}
+
+#[test]
+fn test_memorable_command_max_syllables_per_word() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 memorable --max-syllables-per-word 1`
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("memorable")
+        .arg("--max-syllables-per-word")
+        .arg("1")
+        .assert()
+        .success()
+        .stdout("drum pound swipe hertz wilt\n");
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("memorable")
+        .arg("--max-syllables-per-word")
+        .arg("0")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_memorable_command_family_friendly() {
+    // With seed 18, the unfiltered password holds a blocked word
+    let unfiltered = "evaporate washhouse backed granular delegator grope resume demeanor vineyard graveness dagger qualify dinginess ranger hacked\n";
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("18")
+        .arg("memorable")
+        .arg("--words")
+        .arg("15")
+        .arg("--family-friendly")
+        .arg("false")
+        .assert()
+        .success()
+        .stdout(unfiltered);
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .arg("--no-clipboard")
+        .arg("--seed")
+        .arg("18")
+        .arg("memorable")
+        .arg("--words")
+        .arg("15")
+        .output()
+        .unwrap();
+    let password = String::from_utf8(output.stdout).unwrap();
+    assert_ne!(password, unfiltered);
+    assert!(!password.split_whitespace().any(|word| word == "grope"));
+}
+
+#[test]
+fn test_analyze_command_print_safe_warnings() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `echo "c0rner" | motus --output json analyze --print-safe`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("analyze")
+        .arg("--print-safe")
+        .write_stdin("c0rner\n")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    assert_eq!(
+        json["warnings"],
+        serde_json::json!([
+            {
+                "category": "homoglyph",
+                "token": "0",
+                "message": "prints ambiguously in common fonts",
+                "lookalikes": ["O", "o", "D"],
+            },
+            {
+                "category": "homoglyph",
+                "token": "rn",
+                "message": "prints ambiguously in common fonts",
+                "lookalikes": ["m"],
+            },
+        ])
+    );
+}
+
+#[test]
+fn test_random_command_print_safe() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 random --numbers --symbols --print-safe`
+    let output = cmd
+        .arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("random")
+        .arg("--numbers")
+        .arg("--symbols")
+        .arg("--print-safe")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let password = String::from_utf8(output.stdout).unwrap();
+    let password = password.trim_end();
+    assert_eq!(password.chars().count(), 20);
+    assert!(!password.contains(['0', 'O', 'o', '1', 'l', 'I', '5', 'S']));
+    assert!(!password.contains("rn"));
+}
+
+#[test]
+fn test_card_command() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 card --rows 3 --columns 10`
+    cmd.arg("--seed")
+        .arg("42")
+        .arg("card")
+        .arg("--rows")
+        .arg("3")
+        .arg("--columns")
+        .arg("10")
+        .assert()
+        .success()
+        .stdout(
+            "  | A B C D E F G H I J\n\
+             --+--------------------\n\
+             1 | m H Y v j g Q A K B\n\
+             2 | H B I R Y d p P A I\n\
+             3 | k V H l t d b F m i\n",
+        );
+}
+
+#[test]
+fn test_card_command_svg() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus card --format svg`
+    let output = cmd
+        .arg("card")
+        .arg("--format")
+        .arg("svg")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let svg = String::from_utf8(output.stdout).unwrap();
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    // 8 rows of 26 characters, along with their labels
+    assert_eq!(svg.matches("<text").count(), 8 * 26 + 8 + 26);
+}
+
+#[test]
+fn test_card_command_out_png() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("card.png");
+
+    // `motus card --out card.png`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("card")
+        .arg("--out")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("");
+
+    let image = std::fs::read(&path).expect("unable to read the card");
+    assert!(image.starts_with(b"\x89PNG\r\n\x1a\n"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_card_command_out_restricts_existing_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("card.svg");
+    std::fs::write(&path, "").unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+    // `motus card --out card.svg`, overwriting a file readable by other users
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("card").arg("--out").arg(&path).assert().success();
+
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+}
+
+#[test]
+fn test_qr_file() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("password.svg");
+
+    // `motus --seed 42 --qr-file password.svg random`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--qr-file")
+        .arg(&path)
+        .arg("random")
+        .assert()
+        .success()
+        .stdout("mHYvjgQAKBHBIRYdpPAI\n");
+
+    let image = std::fs::read_to_string(&path).expect("unable to read the QR code");
+    assert!(image.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(
+            mode & 0o777,
+            0o600,
+            "the QR code should only be readable by its owner"
+        );
+    }
+
+    // The image format is guessed from the file extension
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--qr-file")
+        .arg(dir.path().join("password.pdf"))
+        .arg("random")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_schema_command() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus schema --format json-schema`
+    let output = cmd
+        .arg("schema")
+        .arg("--format")
+        .arg("json-schema")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    for payload in ["password", "batch", "analysis", "error"] {
+        assert!(json["$defs"][payload].is_object(), "missing {}", payload);
+    }
+    assert_eq!(
+        json["$defs"]["password"]["required"],
+        serde_json::json!(["kind", "password"])
+    );
+}
+
+#[test]
+fn test_json_error_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json analyze ''`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("analyze")
+        .arg("")
+        .output()
+        .expect("failed to execute process");
+    assert!(!output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stderr).expect("unable to parse json error");
+    assert_eq!(json["error"], "the password to analyze cannot be empty");
+}
+
+#[test]
+fn test_output_schema_option() {
+    let motus = |args: &[&str]| {
+        let output = Command::cargo_bin("motus")
+            .unwrap()
+            .arg("--no-clipboard")
+            .arg("--seed")
+            .arg("42")
+            .arg("--output")
+            .arg("json")
+            .args(args)
+            .output()
+            .expect("failed to execute process");
+        assert!(output.status.success());
+        serde_json::from_slice::<serde_json::Value>(&output.stdout)
+            .expect("unable to parse json output")
+    };
+
+    // The latest version of the schema is used by default
+    let json = motus(&["--analyze", "random"]);
+    assert_eq!(json["schema_version"], 2);
+    assert!(json["analysis"]["feedback"]["suggestions"].is_array());
+
+    let json = motus(&["--count", "2", "pin"]);
+    assert_eq!(json["schema_version"], 2);
+    assert_eq!(json["passwords"].as_array().map(Vec::len), Some(2));
+
+    // Version 1 keeps the payloads as they were before versioning
+    let json = motus(&["--output-schema", "v1", "--analyze", "random"]);
+    assert!(json.get("schema_version").is_none());
+    assert!(json["analysis"].get("feedback").is_none());
+    assert_eq!(json["password"], "mHYvjgQAKBHBIRYdpPAI");
+
+    let json = motus(&["--output-schema", "v1", "--count", "2", "pin"]);
+    assert_eq!(json.as_array().map(Vec::len), Some(2));
+
+    // `motus --seed 42 --output json --output-schema v1 --analyze memorable` prints the payload
+    // of the first versions of motus, field for field
+    let json = motus(&["--output-schema", "v1", "--analyze", "memorable"]);
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "kind": "memorable",
+            "password": "chokehold nativity dolly ominous throat",
+            "analysis": {
+                "strength": "very strong",
+                "guesses": "10^19",
+                "crack_times": {
+                    "10/s": "centuries",
+                    "100/h": "centuries",
+                    "10^10/s": "57 years",
+                    "10^4/s": "centuries"
+                },
+            },
+        })
+    );
+
+    // Fields of newer options are left out too
+    let json = motus(&["--output-schema", "v1", "--label", "github", "random"]);
+    assert_eq!(
+        json,
+        serde_json::json!({"kind": "random", "password": "mHYvjgQAKBHBIRYdpPAI"})
+    );
+}
+
+#[test]
+fn test_default_features() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json info`
+    let output = cmd
+        .arg("--output")
+        .arg("json")
+        .arg("info")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+
+    let features = json["features"].as_array().unwrap();
+    for feature in ["analysis", "clipboard", "qr", "tables", "tls"] {
+        assert!(features.contains(&serde_json::json!(feature)));
+    }
+}
+
+#[test]
+fn test_selftest_command() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --output json selftest --samples 100000`
+    let output = cmd
+        .arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--output")
+        .arg("json")
+        .arg("selftest")
+        .arg("--samples")
+        .arg("100000")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let json: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+    assert_eq!(json["passed"], true);
+    assert_eq!(json["checks"].as_array().unwrap().len(), 4);
+}
+
+#[test]
+fn test_self_check_flag() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --self-check --seed 42 random`
+    let output = cmd
+        .arg("--no-clipboard")
+        .arg("--self-check")
+        .arg("--seed")
+        .arg("42")
+        .arg("random")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.trim(), "mHYvjgQAKBHBIRYdpPAI");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("self-check passed"));
+}
+
+#[test]
+fn test_audit_log() {
+    let path = std::env::temp_dir().join(format!("motus-audit-{}.jsonl", std::process::id()));
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --audit-log <path> --label github random --characters 24 --numbers`
+    let output = cmd
+        .arg("--no-clipboard")
+        .arg("--audit-log")
+        .arg(&path)
+        .arg("--label")
+        .arg("github")
+        .arg("random")
+        .arg("--characters")
+        .arg("24")
+        .arg("--numbers")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+    let password = String::from_utf8(output.stdout).unwrap();
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(!log.contains(password.trim()));
+
+    let event: serde_json::Value = serde_json::from_str(log.trim()).unwrap();
+    assert_eq!(event["kind"], "random");
+    assert_eq!(event["label"], "github");
+    assert_eq!(event["parameters"]["characters"], 24);
+    assert_eq!(event["parameters"]["numbers"], true);
+    assert!(event["entropy_bits"].as_f64().unwrap() > 100.0);
+}
+
+#[test]
+fn test_share_once() {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::process::Stdio;
+
+    // `echo mHYvjgQAKBHBIRYdpPAI | motus --output json share --once --ttl 30`
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("motus"))
+        .args(["--output", "json", "share", "--once", "--ttl", "30"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to execute process");
+    writeln!(child.stdin.take().unwrap(), "mHYvjgQAKBHBIRYdpPAI").unwrap();
+
+    let mut line = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_str(&line).unwrap();
+    let url = json["url"].as_str().unwrap();
+    let (address, path) = url
+        .strip_prefix("http://")
+        .and_then(|rest| rest.split_once('/'))
+        .unwrap();
+
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "GET /{} HTTP/1.1\r\nHost: {}\r\n\r\n",
+        path, address
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200"));
+    assert!(response.ends_with("mHYvjgQAKBHBIRYdpPAI"));
+
+    assert!(child.wait().unwrap().success());
+}
+
+#[test]
+fn test_wrap_unwrap_commands() {
+    // `echo mHYvjgQAKBHBIRYdpPAI | motus wrap`
+    let output = Command::cargo_bin("motus")
+        .unwrap()
+        .env("MOTUS_WRAP_PASSPHRASE", "correct horse battery staple")
+        .arg("wrap")
+        .write_stdin("mHYvjgQAKBHBIRYdpPAI\n")
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+    let envelope = String::from_utf8(output.stdout).unwrap();
+    assert!(!envelope.contains("mHYvjgQAKBHBIRYdpPAI"));
+
+    // `motus unwrap`, with the envelope on the standard input
+    let output = Command::cargo_bin("motus")
+        .unwrap()
+        .env("MOTUS_WRAP_PASSPHRASE", "correct horse battery staple")
+        .arg("unwrap")
+        .write_stdin(envelope.clone())
+        .output()
+        .expect("failed to execute process");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "mHYvjgQAKBHBIRYdpPAI\n"
+    );
+
+    // A wrong passphrase fails
+    let output = Command::cargo_bin("motus")
+        .unwrap()
+        .env("MOTUS_WRAP_PASSPHRASE", "tr0ub4dor&3")
+        .arg("unwrap")
+        .write_stdin(envelope)
+        .output()
+        .expect("failed to execute process");
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("wrong passphrase"));
+}
+
+#[test]
+fn test_askpass_from_history() {
+    let dir = tempfile::tempdir().unwrap();
+    let history_file = dir.path().join("history.enc");
+
+    // `motus --seed 42 --history --label db random`
+    Command::cargo_bin("motus")
+        .unwrap()
+        .env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+        .arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--history")
+        .arg("--history-file")
+        .arg(&history_file)
+        .arg("--label")
+        .arg("db")
+        .arg("random")
+        .assert()
+        .success();
+
+    // `MOTUS_ASKPASS_LABEL=db motus askpass "Password for 'https://db.example.com': "`
+    Command::cargo_bin("motus")
+        .unwrap()
+        .env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+        .env("MOTUS_ASKPASS_LABEL", "db")
+        .arg("--history-file")
+        .arg(&history_file)
+        .arg("askpass")
+        .arg("Password for 'https://db.example.com': ")
+        .assert()
+        .success()
+        .stdout("mHYvjgQAKBHBIRYdpPAI\n");
+}
+
+#[test]
+fn test_systemd_cred_output_checks() {
+    // `motus --output systemd-cred random`, without --name
+    Command::cargo_bin("motus")
+        .unwrap()
+        .args(["--no-clipboard", "--output", "systemd-cred", "random"])
+        .assert()
+        .failure()
+        .stderr("error: --output systemd-cred requires --name, naming the credential\n");
+
+    // `motus --output systemd-cred --name db-password --count 2 random`
+    Command::cargo_bin("motus")
+        .unwrap()
+        .args([
+            "--no-clipboard",
+            "--output",
+            "systemd-cred",
+            "--name",
+            "db-password",
+        ])
+        .args(["--count", "2", "random"])
+        .assert()
+        .failure();
+
+    // `motus --output systemd-cred --name ../db-password random`
+    Command::cargo_bin("motus")
+        .unwrap()
+        .args([
+            "--no-clipboard",
+            "--output",
+            "systemd-cred",
+            "--name",
+            "../db-password",
+        ])
+        .arg("random")
+        .assert()
+        .failure();
+
+    // `motus --output systemd-cred --name db-password info`
+    Command::cargo_bin("motus")
+        .unwrap()
+        .args(["--output", "systemd-cred", "--name", "db-password", "info"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_external_command() {
+    // `echo '{"label": "db", "characters": "24"}' | motus external`, twice
+    let mut passwords = Vec::new();
+    for _ in 0..2 {
+        let output = Command::cargo_bin("motus")
+            .unwrap()
+            .env("MOTUS_EXTERNAL_KEY", "correct horse battery staple")
+            .arg("external")
+            .write_stdin(r#"{"label": "db", "characters": "24"}"#)
+            .output()
+            .expect("failed to execute process");
+        assert!(output.status.success());
+
+        let json: serde_json::Value =
+            serde_json::from_slice(&output.stdout).expect("unable to parse json output");
+        assert_eq!(json["kind"], "random");
+        assert!(json["entropy_bits"].is_string());
+        passwords.push(json["password"].as_str().unwrap().to_string());
+    }
+    assert_eq!(passwords[0].len(), 24);
+    assert_eq!(passwords[0], passwords[1]);
+
+    // Without the key
+    Command::cargo_bin("motus")
+        .unwrap()
+        .env_remove("MOTUS_EXTERNAL_KEY")
+        .arg("external")
+        .write_stdin(r#"{"label": "db"}"#)
+        .assert()
+        .failure();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_sops_output() {
+    use std::os::unix::fs::PermissionsExt;
+
+    // A stand-in for sops, echoing its arguments and the document it is given
+    let dir = tempfile::tempdir().unwrap();
+    let sops = dir.path().join("sops");
+    std::fs::write(&sops, "#!/bin/sh\necho \"$@\"\ncat\n").unwrap();
+    std::fs::set_permissions(&sops, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let path = format!(
+        "{}:{}",
+        dir.path().display(),
+        std::env::var("PATH").unwrap_or_default()
+    );
+
+    // `motus --output sops --age age1... --name db-password --seed 42 random`
+    Command::cargo_bin("motus")
+        .unwrap()
+        .env("PATH", path)
+        .args([
+            "--no-clipboard",
+            "--output",
+            "sops",
+            "--age",
+            "age1recipient",
+        ])
+        .args(["--name", "db-password", "--seed", "42", "random"])
+        .assert()
+        .success()
+        .stdout(
+            "--encrypt --input-type yaml --output-type yaml --age age1recipient /dev/stdin\n\
+             \"db-password\": \"mHYvjgQAKBHBIRYdpPAI\"\n",
+        );
+
+    // `motus --output sops random`, without a recipient
+    Command::cargo_bin("motus")
+        .unwrap()
+        .args(["--no-clipboard", "--output", "sops", "random"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_label_and_tags_json_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --output json --label db --tag env=prod --tag team=ops random`
+    let output = cmd
+        .arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--output")
+        .arg("json")
+        .arg("--label")
+        .arg("db")
+        .arg("--tag")
+        .arg("team=ops")
+        .arg("--tag")
+        .arg("env=prod")
+        .arg("random")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let output: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(output["password"], "mHYvjgQAKBHBIRYdpPAI");
+    assert_eq!(output["label"], "db");
+    assert_eq!(output["tags"]["env"], "prod");
+    assert_eq!(output["tags"]["team"], "ops");
+}
+
+#[test]
+fn test_label_and_tags_csv_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --output csv --count 2 --label db --tag env=prod --tag team=ops random`
+    cmd.arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--output")
+        .arg("csv")
+        .arg("--count")
+        .arg("2")
+        .arg("--label")
+        .arg("db")
+        .arg("--tag")
+        .arg("env=prod")
+        .arg("--tag")
+        .arg("team=ops")
+        .arg("random")
+        .assert()
+        .success()
+        .stdout(
+            "kind,password,label,tags\n\
+             random,mHYvjgQAKBHBIRYdpPAI,db,env=prod;team=ops\n\
+             random,kVHltdbFmiaYXNZmZlTy,db,env=prod;team=ops\n",
+        );
+
+    // `motus --tag env random`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.arg("--no-clipboard")
+        .arg("--tag")
+        .arg("env")
+        .arg("random")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_idempotent_reuses_stored_password() {
+    let dir = tempfile::tempdir().unwrap();
+    let history_file = dir.path().join("history.enc");
+
+    // `motus --seed <seed> --idempotent --label db random`, with a different seed each time
+    for seed in ["42", "43"] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+            .arg("--no-clipboard")
+            .arg("--seed")
+            .arg(seed)
+            .arg("--history")
+            .arg("--idempotent")
+            .arg("--history-file")
+            .arg(&history_file)
+            .arg("--label")
+            .arg("db")
+            .arg("random")
+            .assert()
+            .success()
+            .stdout("mHYvjgQAKBHBIRYdpPAI\n");
+    }
+
+    // `motus --idempotent --label db --count 2 random`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+        .arg("--no-clipboard")
+        .arg("--history")
+        .arg("--idempotent")
+        .arg("--history-file")
+        .arg(&history_file)
+        .arg("--label")
+        .arg("db")
+        .arg("--count")
+        .arg("2")
+        .arg("random")
+        .assert()
+        .failure();
+
+    // `motus --idempotent --label db pin`, asking for another kind of password
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+        .arg("--no-clipboard")
+        .arg("--history")
+        .arg("--idempotent")
+        .arg("--history-file")
+        .arg(&history_file)
+        .args(["--label", "db", "pin"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr("error: the password stored for 'db' was generated by the random command, not the pin one; use another label\n");
+
+    // `motus --idempotent --label db random --characters 32`, asking for a longer password
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+        .arg("--no-clipboard")
+        .arg("--history")
+        .arg("--idempotent")
+        .arg("--history-file")
+        .arg(&history_file)
+        .args(["--label", "db", "random", "--characters", "32"])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr("error: the password stored for 'db' was generated with other parameters; use the same ones, or another label\n");
+
+    // `motus --idempotent --label db random`, without --history
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+        .arg("--no-clipboard")
+        .arg("--idempotent")
+        .arg("--history-file")
+        .arg(&history_file)
+        .args(["--label", "db", "random"])
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn test_stores_are_kept_apart() {
+    let dir = tempfile::tempdir().unwrap();
+
+    // `motus --seed 42 --store work --idempotent --label db random`, then the same in the
+    // personal store with the same seed but another passphrase
+    for (store, passphrase) in [("work", "correct horse"), ("personal", "battery staple")] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.env("XDG_DATA_HOME", dir.path())
+            .env("MOTUS_HISTORY_PASSPHRASE", passphrase)
+            .arg("--no-clipboard")
+            .arg("--seed")
+            .arg("42")
+            .arg("--store")
+            .arg(store)
+            .arg("--history")
+            .arg("--idempotent")
+            .arg("--label")
+            .arg("db")
+            .arg("random")
+            .assert()
+            .success()
+            .stdout("mHYvjgQAKBHBIRYdpPAI\n")
+            .stderr("");
+    }
+    assert!(dir.path().join("motus/stores/work.enc").exists());
+    assert!(dir.path().join("motus/stores/personal.enc").exists());
+
+    // `motus --store work --idempotent --label db random`, with the personal store's passphrase
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("XDG_DATA_HOME", dir.path())
+        .env("MOTUS_HISTORY_PASSPHRASE", "battery staple")
+        .arg("--no-clipboard")
+        .arg("--store")
+        .arg("work")
+        .arg("--history")
+        .arg("--idempotent")
+        .arg("--label")
+        .arg("db")
+        .arg("random")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_store_export_and_import() {
+    let dir = tempfile::tempdir().unwrap();
+    let source = dir.path().join("source.enc");
+    let destination = dir.path().join("destination.enc");
+    let export = dir.path().join("export.json");
+
+    // `motus --seed 42 --history --label db random`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_HISTORY_PASSPHRASE", "old passphrase")
+        .arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--history")
+        .arg("--history-file")
+        .arg(&source)
+        .arg("--label")
+        .arg("db")
+        .arg("random")
+        .assert()
+        .success();
+
+    // `motus --history-file source.enc store export --out export.json`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_HISTORY_PASSPHRASE", "old passphrase")
+        .env("MOTUS_EXPORT_PASSPHRASE", "export passphrase")
+        .arg("--history-file")
+        .arg(&source)
+        .arg("store")
+        .arg("export")
+        .arg("--out")
+        .arg(&export)
+        .assert()
+        .success();
+    let document = std::fs::read_to_string(&export).unwrap();
+    assert!(document.contains("motus-store"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&export).unwrap().permissions().mode();
+        assert_eq!(
+            mode & 0o777,
+            0o600,
+            "the export should only be readable by its owner"
+        );
+    }
+    assert!(!document.contains("mHYvjgQAKBHBIRYdpPAI"));
+
+    // `motus --history-file destination.enc store import export.json`, twice
+    for expected in [
+        "Imported 1 entries, skipping 0 already stored\n",
+        "Imported 0 entries, skipping 1 already stored\n",
+    ] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.env("MOTUS_HISTORY_PASSPHRASE", "new passphrase")
+            .env("MOTUS_EXPORT_PASSPHRASE", "export passphrase")
+            .arg("--history-file")
+            .arg(&destination)
+            .arg("store")
+            .arg("import")
+            .arg(&export)
+            .assert()
+            .success()
+            .stdout(expected);
+    }
+
+    // `motus --idempotent --label db random`, against the destination under its new passphrase
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_HISTORY_PASSPHRASE", "new passphrase")
+        .arg("--no-clipboard")
+        .arg("--history")
+        .arg("--idempotent")
+        .arg("--history-file")
+        .arg(&destination)
+        .arg("--label")
+        .arg("db")
+        .arg("random")
+        .assert()
+        .success()
+        .stdout("mHYvjgQAKBHBIRYdpPAI\n");
+
+    // `motus --history-file destination.enc store import export.json`, with a wrong passphrase
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_HISTORY_PASSPHRASE", "new passphrase")
+        .env("MOTUS_EXPORT_PASSPHRASE", "wrong passphrase")
+        .arg("--history-file")
+        .arg(&destination)
+        .arg("store")
+        .arg("import")
+        .arg(&export)
+        .assert()
+        .failure();
+}
+
+#[test]
+#[cfg(unix)]
+fn test_agent_caches_history_key() {
+    let dir = tempfile::tempdir().unwrap();
+    let history_file = dir.path().join("history.enc");
+    let socket = dir.path().join("agent.sock");
+
+    // `motus agent --foreground --socket agent.sock`
+    let mut agent = std::process::Command::new(assert_cmd::cargo::cargo_bin("motus"))
+        .arg("agent")
+        .arg("--foreground")
+        .arg("--socket")
+        .arg(&socket)
+        .spawn()
+        .unwrap();
+    while !socket.exists() {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+    }
+
+    // `motus --seed 42 --history --label db random`, unlocking the history with the passphrase
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_AGENT_SOCK", &socket)
+        .env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+        .arg("--no-clipboard")
+        .arg("--seed")
+        .arg("42")
+        .arg("--history")
+        .arg("--history-file")
+        .arg(&history_file)
+        .arg("--label")
+        .arg("db")
+        .arg("random")
+        .assert()
+        .success();
+
+    // `motus --idempotent --label db random`, unlocking the history through the agent alone
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_AGENT_SOCK", &socket)
+        .env_remove("MOTUS_HISTORY_PASSPHRASE")
+        .arg("--no-clipboard")
+        .arg("--history")
+        .arg("--idempotent")
+        .arg("--history-file")
+        .arg(&history_file)
+        .arg("--label")
+        .arg("db")
+        .arg("random")
+        .assert()
+        .success()
+        .stdout("mHYvjgQAKBHBIRYdpPAI\n");
+
+    // `motus agent --kill`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.env("MOTUS_AGENT_SOCK", &socket)
+        .arg("agent")
+        .arg("--kill")
+        .assert()
+        .success();
+    assert!(agent.wait().unwrap().success());
+    assert!(!socket.exists());
+}
+
+#[test]
+fn test_protected_history_requires_fido_feature() {
+    if cfg!(feature = "fido") {
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let history_file = dir.path().join("history.enc");
+    std::fs::write(
+        dir.path().join("history.fido"),
+        r#"{"credential_id": "AQID", "salt": "AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI="}"#,
+    )
+    .unwrap();
+
+    // `motus --history --label db random`, with the history protected by a security key
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .env("MOTUS_HISTORY_PASSPHRASE", "correct horse battery staple")
+        .arg("--no-clipboard")
+        .arg("--history")
+        .arg("--history-file")
+        .arg(&history_file)
+        .arg("--label")
+        .arg("db")
+        .arg("random")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--features fido"));
+}
+
+#[test]
+#[cfg(unix)]
+fn test_audit_log_signatures() {
+    let dir = tempfile::tempdir().unwrap();
+    let gnupg = dir.path().join("gnupg");
+    let log = dir.path().join("audit.jsonl");
+    std::fs::create_dir(&gnupg).unwrap();
+    std::fs::set_permissions(&gnupg, std::os::unix::fs::PermissionsExt::from_mode(0o700)).unwrap();
+
+    // Skip when gpg is not installed
+    let Ok(status) = std::process::Command::new("gpg")
+        .env("GNUPGHOME", &gnupg)
+        .args(["--batch", "--passphrase", "", "--quick-gen-key"])
+        .args(["audit@example.com", "ed25519", "sign", "never"])
+        .stderr(std::process::Stdio::null())
+        .status()
+    else {
+        return;
+    };
+    assert!(status.success());
+
+    // `motus --audit-log <path> --audit-log-sign gpg:audit@example.com random`, twice
+    for _ in 0..2 {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.env("GNUPGHOME", &gnupg)
+            .arg("--no-clipboard")
+            .arg("--audit-log")
+            .arg(&log)
+            .arg("--audit-log-sign")
+            .arg("gpg:audit@example.com")
+            .arg("random")
+            .assert()
+            .success();
+    }
+
+    // `motus --output json audit-log verify <path>`
+    let verify = || {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        let output = cmd
+            .env("GNUPGHOME", &gnupg)
+            .args(["--output", "json", "audit-log", "verify"])
+            .arg(&log)
+            .output()
+            .unwrap();
+        let verification: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.success(), verification)
+    };
+    let (success, verification) = verify();
+    assert!(success);
+    assert_eq!(verification["valid"], 2);
+
+    // Lines record the fingerprint of the key, rather than the name it was given by
+    let content = std::fs::read_to_string(&log).unwrap();
+    let first: serde_json::Value = serde_json::from_str(content.lines().next().unwrap()).unwrap();
+    let signer = first["signature"]["signer"].as_str().unwrap();
+    assert!(signer
+        .strip_prefix("gpg:")
+        .is_some_and(|fingerprint| fingerprint.len() == 40));
+
+    // A good signature does not pass for one by another key of the keyring
+    let status = std::process::Command::new("gpg")
+        .env("GNUPGHOME", &gnupg)
+        .args(["--batch", "--passphrase", "", "--quick-gen-key"])
+        .args(["mallory@example.com", "ed25519", "sign", "never"])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let keys = std::process::Command::new("gpg")
+        .env("GNUPGHOME", &gnupg)
+        .args([
+            "--batch",
+            "--with-colons",
+            "--list-keys",
+            "mallory@example.com",
+        ])
+        .output()
+        .unwrap();
+    let keys = String::from_utf8(keys.stdout).unwrap();
+    let mallory = keys
+        .lines()
+        .find_map(|line| line.strip_prefix("fpr:")?.split(':').nth(8))
+        .unwrap();
+    std::fs::write(
+        &log,
+        content.replacen(signer, &format!("gpg:{}", mallory), 1),
+    )
+    .unwrap();
+    let (success, verification) = verify();
+    assert!(!success);
+    assert_eq!(verification["invalid"], serde_json::json!([1]));
+
+    // Removing a line breaks the chain, though every signature is valid
+    std::fs::write(&log, format!("{}\n", content.lines().nth(1).unwrap())).unwrap();
+    let (success, verification) = verify();
+    assert!(!success);
+    assert_eq!(verification["valid"], 1);
+    assert_eq!(verification["broken"], serde_json::json!([1]));
+
+    // Tampering with the first event, and appending an unsigned one, is detected
+    let tampered = content.replacen("\"count\":1", "\"count\":2", 1);
+    std::fs::write(&log, tampered + "{\"kind\":\"pin\",\"count\":1}\n").unwrap();
+    let (success, verification) = verify();
+    assert!(!success);
+    assert_eq!(verification["valid"], 1);
+    assert_eq!(verification["invalid"], serde_json::json!([1]));
+    assert_eq!(verification["unsigned"], serde_json::json!([3]));
+    assert_eq!(verification["broken"], serde_json::json!([2, 3]));
+}
+
+#[test]
+fn test_wordlist_build() {
+    let dir = tempfile::tempdir().unwrap();
+    let frequencies = dir.path().join("frequencies.txt");
+    let list = dir.path().join("list.txt");
+    std::fs::write(
+        &frequencies,
+        "stone 90\ntree 80\nstones 70\nTable 60\ncafé 50\ntreetop 40\nbird 30\n",
+    )
+    .unwrap();
+
+    // `motus --output json wordlist build --from frequencies.txt --size 4 --out list.txt`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["--output", "json", "wordlist", "build", "--size", "4"])
+        .arg("--from")
+        .arg(&frequencies)
+        .arg("--out")
+        .arg(&list)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["words"], 4);
+    assert_eq!(summary["bits_per_word"], 2.0);
+    assert_eq!(
+        std::fs::read_to_string(&list).unwrap(),
+        "bird\nstone\ntable\ntree\n"
+    );
+
+    // `motus wordlist build --from frequencies.txt --size 5`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["wordlist", "build", "--size", "5"])
+        .arg("--from")
+        .arg(&frequencies)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("only 4 words"));
+}
+
+#[test]
+fn test_wordlist_lint() {
+    // `motus wordlist lint`, on the embedded wordlist
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["wordlist", "lint"]).assert().success();
+
+    let dir = tempfile::tempdir().unwrap();
+    let list = dir.path().join("list.txt");
+    std::fs::write(&list, "tree\ntreetop\nstone\n").unwrap();
+
+    // `motus --output json wordlist lint list.txt`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["--output", "json", "wordlist", "lint"])
+        .arg(&list)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let lint: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(lint["words"], 3);
+    assert_eq!(lint["prefixes"][0]["prefix"], "tree");
+    assert_eq!(lint["prefixes"][0]["word"], "treetop");
+    assert_eq!(lint["duplicates"], serde_json::json!([]));
+
+    // `motus wordlist lint --allow-prefixes list.txt`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["wordlist", "lint", "--allow-prefixes"])
+        .arg(&list)
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_memorable_without_separator() {
+    // `motus --seed 42 memorable --separator none`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args([
+            "--no-clipboard",
+            "--seed",
+            "42",
+            "memorable",
+            "--separator",
+            "none",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap().trim(),
+        "chokeholdnativitydollyominousthroat"
+    );
+    assert!(output.stderr.is_empty());
+
+    // `motus --output json --explain memorable --separator none --no-full-words`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["--no-clipboard", "--output", "json", "--explain"])
+        .args(["memorable", "--separator", "none", "--no-full-words"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(report["entropy"]["ambiguous"], true);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--disambiguate"));
+
+    // `motus --seed 42 --output json --explain memorable --separator none --no-full-words --disambiguate`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args([
+            "--no-clipboard",
+            "--seed",
+            "42",
+            "--output",
+            "json",
+            "--explain",
+        ])
+        .args(["memorable", "--separator", "none", "--no-full-words"])
+        .arg("--disambiguate")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report["entropy"].get("ambiguous").is_none());
+    let password = report["password"].as_str().unwrap();
+    assert_eq!(password.chars().filter(char::is_ascii_uppercase).count(), 4);
+}
+
+#[test]
+fn test_memorable_join_styles() {
+    for (style, expected) in [
+        ("camel", "chokingNaturalDolly"),
+        ("pascal", "ChokingNaturalDolly"),
+        ("snake", "choking_natural_dolly"),
+        ("kebab", "choking-natural-dolly"),
+    ] {
+        // `motus --seed 42 memorable --words 3 --style <style>`
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        let output = cmd
+            .args([
+                "--no-clipboard",
+                "--seed",
+                "42",
+                "memorable",
+                "--words",
+                "3",
+            ])
+            .args(["--style", style])
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), expected);
+    }
+
+    // `motus memorable --style camel --capitalize`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args([
+        "--no-clipboard",
+        "memorable",
+        "--style",
+        "camel",
+        "--capitalize",
+    ])
+    .assert()
+    .failure();
+}
+
+#[test]
+fn test_memorable_shuffle_order() {
+    // `motus --seed 42 memorable --shuffle-order`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args([
+            "--no-clipboard",
+            "--seed",
+            "42",
+            "memorable",
+            "--shuffle-order",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    // The same words as without the option, in another order
+    let password = String::from_utf8(output.stdout).unwrap();
+    let mut words: Vec<&str> = password.split_whitespace().collect();
+    assert_eq!(
+        words,
+        ["throat", "dolly", "nativity", "chokehold", "ominous"]
+    );
+    words.sort_unstable();
+    assert_eq!(
+        words,
+        ["chokehold", "dolly", "nativity", "ominous", "throat"]
+    );
+}
+
+#[test]
+fn test_memorable_phonetically_distinct() {
+    // `motus --seed 9 memorable`, whose words "stainable" and "quotable" rhyme
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--seed", "9", "memorable"])
+        .assert()
+        .success()
+        .stdout("quizzical stainable skewed shifty quotable\n");
+
+    // `motus --seed 9 memorable --phonetically-distinct`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args([
+        "--no-clipboard",
+        "--seed",
+        "9",
+        "memorable",
+        "--phonetically-distinct",
+    ])
+    .assert()
+    .success()
+    .stdout("protract coming quarry vastly preshow\n");
+}
+
+#[test]
+fn test_deny_list() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("deny.txt");
+    std::fs::write(&path, "# Products\nDolly\n").unwrap();
+
+    // `motus --seed 42 --deny-list deny.txt memorable --words 3`, which would otherwise generate
+    // "choking natural dolly"
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--seed", "42", "--deny-list"])
+        .arg(&path)
+        .args(["memorable", "--words", "3"])
+        .assert()
+        .success()
+        .stdout("omen thriving woozy\n");
+
+    // Terms that cannot be avoided
+    std::fs::write(&path, "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n").unwrap();
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["--no-clipboard", "--deny-list"])
+        .arg(&path)
+        .arg("pin")
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("none of the denied terms"));
+}
+
+#[test]
+fn test_policy_check_deny_list() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let policy = dir.path().join("corp.toml");
+    std::fs::write(&policy, "min_length = 8\ndeny_list = [\"acme\"]\n").unwrap();
+    let deny_list = dir.path().join("deny.txt");
+    std::fs::write(&deny_list, "rocketship\n").unwrap();
+
+    // `motus --deny-list deny.txt --output json policy check --policy corp.toml --password ...`
+    for (password, compliant) in [
+        ("AcmeCorp2024", false),
+        ("rocketship42", false),
+        ("correcthorse", true),
+    ] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        let output = cmd
+            .arg("--deny-list")
+            .arg(&deny_list)
+            .args(["--output", "json", "policy", "check", "--policy"])
+            .arg(&policy)
+            .args(["--password", password])
+            .output()
+            .unwrap();
+        assert_eq!(output.status.success(), compliant, "{password}");
+
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        assert_eq!(json["rules"][1]["rule"], "contains no denied term");
+        assert_eq!(json["rules"][1]["passed"], compliant, "{password}");
+    }
+}
+
+#[test]
+fn test_username() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --count 3 username --style snake --max-length 14`
+    cmd.args(["--seed", "42", "--count", "3", "username"])
+        .args(["--style", "snake", "--max-length", "14"])
+        .assert()
+        .success()
+        .stdout("mosaic_malt_86\nfolic_boots_37\nrelive_claw_40\n");
+}
+
+#[test]
+fn test_username_json_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json --count 5 username --digits 3`
+    let output = cmd
+        .args([
+            "--output", "json", "--count", "5", "username", "--digits", "3",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let usernames = json["usernames"].as_array().unwrap();
+    assert_eq!(usernames.len(), 5);
+    for username in usernames {
+        let username = username.as_str().unwrap();
+        assert!(username.starts_with(|c: char| c.is_ascii_uppercase()));
+        assert!(username[username.len() - 3..]
+            .chars()
+            .all(|c| c.is_ascii_digit()));
+    }
+    assert!(json["entropy_bits"].as_f64().unwrap() > 20.0);
+}
+
+#[test]
+fn test_username_max_length_too_short() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus username --max-length 8`
+    let output = cmd
+        .args(["username", "--max-length", "8"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("the shortest username is 11 characters long"));
+}
+
+#[test]
+fn test_alias() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --count 2 alias --domain Example.com --plus jane`
+    cmd.args(["--seed", "42", "--count", "2", "alias"])
+        .args(["--domain", "Example.com", "--plus", "jane"])
+        .assert()
+        .success()
+        .stdout("jane+laxative.undergo.94@example.com\njane+angelic.huntsman.18@example.com\n");
+}
+
+#[test]
+fn test_alias_with_password_json_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json --count 3 alias --domain example.com --with-password`
+    let output = cmd
+        .args(["--output", "json", "--count", "3", "alias"])
+        .args(["--domain", "example.com", "--with-password"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let aliases = json["aliases"].as_array().unwrap();
+    assert_eq!(aliases.len(), 3);
+    for alias in aliases {
+        let address = alias["address"].as_str().unwrap();
+        assert!(address.ends_with("@example.com") && !address.contains('+'));
+        assert_eq!(alias["password"].as_str().unwrap().chars().count(), 20);
+    }
+}
+
+#[test]
+fn test_identity_json_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --output json --count 2 identity --plus jane --recovery-codes 4`
+    let output = cmd
+        .args([
+            "--seed", "42", "--output", "json", "--count", "2", "identity",
+        ])
+        .args(["--plus", "jane", "--recovery-codes", "4"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let identities = json["identities"].as_array().unwrap();
+    assert_eq!(identities.len(), 2);
+
+    let identity = &identities[0];
+    assert_eq!(identity["username"], "LaxativeUndergo94");
+    assert_eq!(identity["email"], "jane+laxativeundergo94@example.com");
+    assert_eq!(identity["password"].as_str().unwrap().len(), 20);
+    assert_eq!(identity["pin"].as_str().unwrap().len(), 6);
+    let codes = identity["recovery_codes"].as_array().unwrap();
+    assert_eq!(codes.len(), 4);
+    assert!(codes
+        .iter()
+        .all(|code| code.as_str().unwrap().len() == 11 && code.as_str().unwrap().contains('-')));
+    assert_ne!(identities[1]["username"], identity["username"]);
+}
+
+#[test]
+fn test_identity_rejects_password_display_options() {
+    for (option, args) in [
+        ("--prefix", &["--prefix", "acme-"][..]),
+        ("--case", &["--case", "upper"]),
+        ("--big", &["--big"]),
+    ] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.arg("--no-clipboard")
+            .args(args)
+            .arg("identity")
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(format!(
+                "error: {option} does not apply to the identity command\n"
+            ));
+    }
+}
+
+#[test]
+fn test_answers() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 answers --count 2`
+    cmd.args(["--seed", "42", "answers", "--count", "2"])
+        .assert()
+        .success()
+        .stdout("question 1: choking natural dolly\nquestion 2: omen thriving woozy\n");
+}
+
+#[test]
+fn test_answers_rejects_password_display_options() {
+    for (option, args) in [
+        ("--prefix", &["--prefix", "acme-"][..]),
+        ("--case", &["--case", "upper"]),
+        ("--big", &["--big"]),
+    ] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.arg("--no-clipboard")
+            .args(args)
+            .arg("answers")
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(format!(
+                "error: {option} does not apply to the answers command\n"
+            ));
+    }
+}
+
+#[test]
+fn test_answers_json_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --output json answers --question "First pet?" --question "Birth city?" --words 4`
+    let output = cmd
+        .args(["--output", "json", "answers"])
+        .args([
+            "--question",
+            "First pet?",
+            "--question",
+            "Birth city?",
+            "--words",
+            "4",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let answers = json["answers"].as_array().unwrap();
+    assert_eq!(answers.len(), 2);
+    assert_eq!(answers[0]["question"], "First pet?");
+    assert_eq!(answers[1]["question"], "Birth city?");
+    for answer in answers {
+        assert_eq!(answer["answer"].as_str().unwrap().split(' ').count(), 4);
+        assert!(answer["entropy_bits"].as_f64().unwrap() > 50.0);
+    }
+}
+
+#[test]
+fn test_clipboard_content_json_requires_json_output() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --clipboard-content json random`
+    let output = cmd
+        .args(["--clipboard-content", "json", "random"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("--clipboard-content json can only be used with --output json"));
+}
+
+#[cfg(feature = "headless")]
+#[test]
+fn test_clipboard_content_json() {
+    let dir = tempfile::tempdir().expect("unable to create temporary directory");
+    let path = dir.path().join("clipboard");
+
+    // `motus --seed 42 --output json --clipboard-content json --clipboard-file clipboard pin`
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["--seed", "42", "--output", "json"])
+        .args(["--clipboard-content", "json", "--clipboard-file"])
+        .arg(&path)
+        .arg("pin")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let copied = std::fs::read_to_string(&path).expect("the JSON output should be copied");
+    assert_eq!(
+        copied.trim(),
+        String::from_utf8(output.stdout).unwrap().trim()
+    );
+    let json: serde_json::Value = serde_json::from_str(&copied).unwrap();
+    assert_eq!(json["kind"], "pin");
+}
+
+#[test]
+fn test_identity_sequential_paste_single_identity() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --count 2 identity --sequential-paste`
+    let output = cmd
+        .args(["--count", "2", "identity", "--sequential-paste"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(
+        stderr.contains("--sequential-paste can only be used when generating a single identity")
+    );
+}
+
+#[test]
+fn test_native_host() {
+    let sites = tempfile::tempdir().expect("unable to create temporary directory");
+    std::fs::write(sites.path().join("example.com.toml"), "max_length = 12\n").unwrap();
+
+    let mut input = Vec::new();
+    for request in [
+        serde_json::json!({"type": "ping"}),
+        serde_json::json!({"type": "generate", "domain": "www.example.com"}),
+    ] {
+        let message = serde_json::to_vec(&request).unwrap();
+        input.extend((message.len() as u32).to_ne_bytes());
+        input.extend(message);
+    }
+
+    // Chrome starts native messaging hosts with the origin of the extension as only argument
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .arg("chrome-extension://abcdef/")
+        .env("MOTUS_SITES", sites.path())
+        .write_stdin(input)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    let mut responses = Vec::new();
+    let mut stdout = output.stdout.as_slice();
+    while let [a, b, c, d, rest @ ..] = stdout {
+        let len = u32::from_ne_bytes([*a, *b, *c, *d]) as usize;
+        responses.push(serde_json::from_slice::<serde_json::Value>(&rest[..len]).unwrap());
+        stdout = &rest[len..];
+    }
+
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0]["type"], "pong");
+    assert_eq!(responses[1]["password"].as_str().unwrap().len(), 12);
+    assert_eq!(responses[1]["policy"], "example.com");
+}
+
+#[test]
+fn test_random_site_rules() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args([
+            "--no-clipboard",
+            "--seed",
+            "42",
+            "--count",
+            "5",
+            "random",
+            "--characters",
+            "40",
+            "--symbols",
+            "--site",
+            "www.americanexpress.com",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    for password in String::from_utf8(output.stdout).unwrap().lines() {
+        assert_eq!(password.len(), 20, "{password}");
+        assert!(password.chars().any(|c| c.is_ascii_digit()), "{password}");
+        assert!(
+            password
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "%&_?#=".contains(c)),
+            "{password}"
+        );
+    }
+
+    let sites_db = tempfile::NamedTempFile::new().expect("unable to create temporary file");
+    std::fs::write(
+        sites_db.path(),
+        "example.com minlength: 10; maxlength: 10; allowed: lower;\n",
+    )
+    .unwrap();
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["--no-clipboard", "random", "--site", "example.com"])
+        .env("MOTUS_SITES_DB", sites_db.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let password = String::from_utf8(output.stdout).unwrap();
+    let password = password.trim_end();
+    assert_eq!(password.len(), 10);
+    assert!(password.chars().all(|c| c.is_ascii_lowercase()));
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["--no-clipboard", "random", "--site", "example.org"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("no password rules are known"));
+}
+
+#[test]
+fn test_password_rules() {
+    let rules = "minlength: 8; required: upper; allowed: [-().&@?ate]";
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["--output", "json", "rules", "parse", rules])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let parsed: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(parsed["rules"][0], "at least 8 characters long");
+    assert_eq!(parsed["characters"], "aetABCDEFGHIJKLMNOPQRSTUVWXYZ@&()");
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args([
+            "--no-clipboard",
+            "--count",
+            "5",
+            "random",
+            "--symbols",
+            "--password-rules",
+            rules,
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    for password in String::from_utf8(output.stdout).unwrap().lines() {
+        assert_eq!(password.len(), 20);
+        assert!(
+            password
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || "aet@&()".contains(c)),
+            "{password}"
+        );
+    }
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["rules", "parse", "required: emoji"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown character class"));
+
+    // A required class the excluded characters leave nothing of fails rather than panicking
+    for (rules, excluded, required) in [
+        (
+            "minlength: 10; required: digit; allowed: lower",
+            "0123456789",
+            "required: digit",
+        ),
+        (
+            "minlength: 10; required: [!]; allowed: lower",
+            "!",
+            "required: [!]",
+        ),
+    ] {
+        let mut cmd = Command::cargo_bin("motus").unwrap();
+        cmd.args([
+            "--no-clipboard",
+            "random",
+            "--password-rules",
+            rules,
+            "--exclude-chars",
+            excluded,
+        ])
+        .assert()
+        .failure()
+        .stderr(format!(
+            "error: the excluded characters leave none to comply with the password rule '{}'\n",
+            required
+        ));
+    }
+}
+
+#[test]
+fn test_compare_command() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["--output", "json", "compare", "--length-budget", "12"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let compared: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(compared["length_budget"], 12);
+
+    let strategies = compared["strategies"].as_array().unwrap();
+    assert_eq!(
+        strategies[0]["command"],
+        "motus random --characters 12 --numbers --symbols"
+    );
+    let apple = strategies
+        .iter()
+        .find(|s| s["command"] == "motus apple")
+        .unwrap();
+    assert_eq!(apple["fits"], false);
+    for strategy in strategies.iter().filter(|s| s["fits"] == true) {
+        assert!(strategy["length"].as_f64().unwrap() <= 12.0);
+    }
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    let output = cmd
+        .args(["compare", "--length-budget", "4"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("The length budget must be between 8 and 100 characters"));
+    assert!(!stderr.contains("words"));
+}
+
+#[test]
+fn test_t9_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --t9 memorable --words 3` maps each word onto the keypad
+    cmd.args(["--no-clipboard", "--seed", "42", "--t9"])
+        .args(["memorable", "--words", "3"])
+        .assert()
+        .success()
+        .stdout("choking natural dolly\nT9: 2465464 6288725 36559\n");
+
+    // `motus t9` reads the text from the standard input, keeping digits, '*' and '#'
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--output", "json", "t9"])
+        .write_stdin("*Voicemail-42#\n")
+        .assert()
+        .success()
+        .stdout("{\"t9\":\"*864236245 42#\"}\n");
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["t9", "--", "-_-"])
+        .assert()
+        .failure()
+        .stderr("error: the text holds no character found on a telephone keypad\n");
+}
+
+#[test]
+fn test_big_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --big pin --numbers 4` draws 5564 in large digits
+    cmd.args(["--no-clipboard", "--seed", "42", "--big"])
+        .args(["pin", "--numbers", "4"])
+        .assert()
+        .success()
+        .stdout(
+            [
+                "##### #####   ##     #",
+                "#     #      #      ##",
+                "####  ####  #      # #",
+                "    #     # ####  #  #",
+                "    #     # #   # #####",
+                "#   # #   # #   #    #",
+                " ###   ###   ###     #\n",
+            ]
+            .join("\n"),
+        );
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--big", "--output", "json", "pin"])
+        .assert()
+        .failure()
+        .stderr("{\"error\":\"--big only applies to the text output\"}\n");
+}
+
+#[test]
+fn test_accessible_option() {
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+
+    // `motus --seed 42 --accessible random -c 8 -ns` spells mH)vj1Q^ out
+    cmd.args(["--no-clipboard", "--seed", "42", "--accessible"])
+        .args(["random", "-c", "8", "-n", "-s"])
+        .assert()
+        .success()
+        .stdout(
+            "small m\ncapital H\nsymbol right parenthesis\nsmall v\nsmall j\ndigit 1\ncapital Q\nsymbol caret\n",
+        );
+
+    let mut cmd = Command::cargo_bin("motus").unwrap();
+    cmd.args(["--no-clipboard", "--seed", "42", "--accessible"])
+        .args(["--output", "json", "pin", "--numbers", "4"])
+        .assert()
+        .success()
+        .stdout(
+            "{\"schema_version\":2,\"kind\":\"pin\",\"password\":\"5564\",\"spelling\":[\"digit 5\",\"digit 5\",\"digit 6\",\"digit 4\"]}\n",
+        );
+}
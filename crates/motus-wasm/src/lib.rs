@@ -1,20 +1,57 @@
+use motus::analysis::report::{PasswordStrength, SecurityAnalysis};
+use motus::charset::Charset;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
 pub fn memorable_password(
     word_count: usize,
     separator: Separator,
+    separator_count: usize,
     capitalize: bool,
     scramble: bool,
 ) -> String {
     let mut rng = rand::thread_rng();
-    motus::memorable_password(&mut rng, word_count, separator.into(), capitalize, scramble)
+    motus::memorable_password(
+        &mut rng,
+        word_count,
+        separator.into(),
+        separator_count,
+        capitalize,
+        scramble,
+    )
 }
 
 #[wasm_bindgen]
-pub fn random_password(characters: u32, numbers: bool, symbols: bool) -> String {
+pub fn random_password(
+    characters: u32,
+    numbers: bool,
+    symbols: bool,
+    excluded: &str,
+) -> Result<String, JsError> {
+    let excluded: Vec<char> = excluded.chars().collect();
+    // Letters are drawn whatever the options, and none of them can be excluded altogether
+    if ('a'..='z').chain('A'..='Z').all(|c| excluded.contains(&c)) {
+        return Err(JsError::new(
+            "the excluded characters cannot hold every letter",
+        ));
+    }
+    // Numbers and symbols asked for must not silently go missing either
+    let excludes_all = |set: Charset| set.chars().iter().all(|c| excluded.contains(c));
+    if numbers && excludes_all(Charset::numbers()) {
+        return Err(JsError::new(
+            "the excluded characters cannot hold every digit when numbers are enabled",
+        ));
+    }
+    if symbols && excludes_all(Charset::symbols()) {
+        return Err(JsError::new(
+            "the excluded characters cannot hold every symbol when symbols are enabled",
+        ));
+    }
+
     let mut rng = rand::thread_rng();
-    motus::random_password(&mut rng, characters, numbers, symbols)
+    Ok(motus::random_password(
+        &mut rng, characters, numbers, symbols, &excluded,
+    ))
 }
 
 #[wasm_bindgen]
@@ -23,6 +60,43 @@ pub fn pin_password(numbers: u32) -> String {
     motus::pin_password(&mut rng, numbers)
 }
 
+#[wasm_bindgen]
+pub fn apple_password() -> String {
+    let mut rng = rand::thread_rng();
+    motus::apple_password(&mut rng)
+}
+
+#[wasm_bindgen]
+pub fn code_password(length: u32, alphabet: &str) -> Result<String, JsError> {
+    let alphabet: Vec<char> = alphabet.chars().collect();
+    if alphabet.is_empty() {
+        return Err(JsError::new(
+            "the alphabet should hold at least one character",
+        ));
+    }
+    if let Some((index, c)) = alphabet
+        .iter()
+        .enumerate()
+        .find(|(index, c)| alphabet[..*index].contains(c))
+    {
+        return Err(JsError::new(&format!(
+            "the alphabet holds '{c}' more than once, at position {}",
+            index + 1
+        )));
+    }
+
+    let mut rng = rand::thread_rng();
+    Ok(motus::code_password(&mut rng, length, &alphabet))
+}
+
+#[wasm_bindgen]
+pub fn password_strength(password: &str) -> String {
+    if password.is_empty() {
+        return PasswordStrength::VeryWeak.to_string();
+    }
+    SecurityAnalysis::new(password).strength().to_string()
+}
+
 #[wasm_bindgen]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Separator {
@@ -33,6 +107,7 @@ pub enum Separator {
     Underscore,
     Numbers,
     NumbersAndSymbols,
+    None,
 }
 
 #[allow(clippy::from_over_into)]
@@ -46,6 +121,7 @@ impl Into<motus::Separator> for Separator {
             Separator::Underscore => motus::Separator::Underscore,
             Separator::Numbers => motus::Separator::Numbers,
             Separator::NumbersAndSymbols => motus::Separator::NumbersAndSymbols,
+            Separator::None => motus::Separator::None,
         }
     }
 }
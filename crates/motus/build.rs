@@ -0,0 +1,30 @@
+// The build script checks the embedded wordlist, so that a list holding duplicates, non-ASCII
+// words, or words that are a prefix of another, which would credit memorable passwords with more
+// entropy than they have, never ships.
+#[path = "src/wordlist/lint.rs"]
+#[allow(dead_code)]
+mod lint;
+
+fn main() {
+    println!("cargo:rerun-if-changed=wordlist.txt");
+    println!("cargo:rerun-if-changed=src/wordlist/lint.rs");
+
+    let wordlist =
+        std::fs::read_to_string("wordlist.txt").expect("the wordlist should be readable");
+    let lint = lint::lint(wordlist.lines());
+    assert!(
+        lint.duplicates.is_empty(),
+        "the embedded wordlist holds duplicates: {:?}",
+        lint.duplicates
+    );
+    assert!(
+        lint.non_ascii.is_empty(),
+        "the embedded wordlist holds non-ASCII words: {:?}",
+        lint.non_ascii
+    );
+    assert!(
+        lint.is_prefix_free(),
+        "the embedded wordlist holds words that are a prefix of another: {:?}",
+        lint.prefixes
+    );
+}
@@ -0,0 +1,98 @@
+//! Fuzzes every password generator with arbitrary options, asserting the same invariants as the
+//! property tests: passwords have the requested length and only hold characters from the sets
+//! they are drawn from.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use clap::ValueEnum;
+use libfuzzer_sys::fuzz_target;
+use motus::card::{Card, MAX_COLUMNS};
+use motus::charset::Charset;
+use motus::policy::Policy;
+use motus::{
+    charset_password, code_password, memorable_password, random_password, Radix, Separator,
+};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    seed: u64,
+    // Lengths are capped to keep each run fast, generation being linear in them
+    length: u16,
+    numbers: bool,
+    symbols: bool,
+    excluded: Vec<char>,
+    sets: Vec<(String, u8)>,
+    radix: u8,
+    separator: u8,
+    separator_count: u8,
+    capitalize: bool,
+    scramble: bool,
+}
+
+fuzz_target!(|input: Input| {
+    let mut rng = StdRng::seed_from_u64(input.seed);
+    let length = u32::from(input.length % 1024);
+
+    // Excluding every letter is the only way for random passwords to have no character left
+    if !('a'..='z')
+        .chain('A'..='Z')
+        .all(|c| input.excluded.contains(&c))
+    {
+        let password = random_password(
+            &mut rng,
+            length,
+            input.numbers,
+            input.symbols,
+            &input.excluded,
+        );
+        assert_eq!(password.chars().count(), length as usize);
+        assert!(!password.contains(input.excluded.as_slice()));
+    }
+
+    let sets: Vec<(Charset, u32)> = input
+        .sets
+        .iter()
+        .map(|(chars, weight)| (Charset::from(chars.as_str()), u32::from(*weight)))
+        .collect();
+    if sets.iter().any(|(set, weight)| !set.is_empty() && *weight > 0) {
+        let sets: Vec<(Charset, u32)> = sets.into_iter().filter(|(_, w)| *w > 0).collect();
+        let password = charset_password(&mut rng, length, &sets);
+        assert_eq!(password.chars().count(), length as usize);
+        assert!(password
+            .chars()
+            .all(|c| sets.iter().any(|(set, _)| set.contains(c))));
+    }
+
+    let radixes = Radix::value_variants();
+    let alphabet = radixes[usize::from(input.radix) % radixes.len()].alphabet();
+    let code = code_password(&mut rng, length, alphabet);
+    assert_eq!(code.chars().count(), length as usize);
+    assert!(code.chars().all(|c| alphabet.contains(&c)));
+
+    let separators = Separator::value_variants();
+    let password = memorable_password(
+        &mut rng,
+        usize::from(input.length % 64),
+        separators[usize::from(input.separator) % separators.len()],
+        usize::from(input.separator_count % 8),
+        input.capitalize,
+        input.scramble,
+    );
+    assert_eq!(password.is_empty(), input.length % 64 == 0);
+
+    let card = Card::generate(
+        &mut rng,
+        usize::from(input.length % 32),
+        usize::from(input.separator_count),
+        input.numbers,
+        input.symbols,
+        &Policy::new(),
+    );
+    assert!(card
+        .rows()
+        .iter()
+        .all(|row| row.chars().count() == usize::from(input.separator_count).min(MAX_COLUMNS)));
+});
@@ -0,0 +1,203 @@
+//! Crack time estimations for custom attacker models.
+//!
+//! zxcvbn estimates how long cracking a password takes for four fixed scenarios, from throttled
+//! online attacks to offline attacks on fast hashes. An [`AttackModel`] describes any other
+//! attacker, by the number of guesses it makes per second and optionally by what running its
+//! hardware costs, and estimates are computed from the raw number of guesses needed to find the
+//! password.
+
+/// An attacker trying to guess passwords.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AttackModel {
+    guesses_per_second: f64,
+    cost_per_hour: Option<f64>,
+}
+
+impl AttackModel {
+    /// Creates an attacker making the given number of guesses per second.
+    #[must_use]
+    pub const fn new(guesses_per_second: f64) -> Self {
+        Self {
+            guesses_per_second,
+            cost_per_hour: None,
+        }
+    }
+
+    /// Sets what running the attacker's hardware costs per hour, in any currency.
+    #[must_use]
+    pub const fn with_cost_per_hour(mut self, cost: f64) -> Self {
+        self.cost_per_hour = Some(cost);
+        self
+    }
+
+    /// Returns the number of guesses the attacker makes per second.
+    #[must_use]
+    pub const fn guesses_per_second(&self) -> f64 {
+        self.guesses_per_second
+    }
+
+    /// Returns what running the attacker's hardware costs per hour, if known.
+    #[must_use]
+    pub const fn cost_per_hour(&self) -> Option<f64> {
+        self.cost_per_hour
+    }
+
+    /// Estimates the effort needed for the attacker to find a password requiring
+    /// `10^guesses_log10` guesses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use motus::analysis::crack::AttackModel;
+    ///
+    /// let attacker = AttackModel::new(1e10).with_cost_per_hour(2.0);
+    /// let estimate = attacker.estimate(14.0);
+    ///
+    /// assert!((estimate.seconds - 1e4).abs() < 1e-6);
+    /// assert_eq!(estimate.duration(), "3 hours");
+    /// ```
+    #[must_use]
+    pub fn estimate(&self, guesses_log10: f64) -> CrackEstimate {
+        let seconds = 10f64.powf(guesses_log10) / self.guesses_per_second;
+
+        CrackEstimate {
+            seconds,
+            cost: self.cost_per_hour.map(|cost| cost * seconds / HOUR),
+        }
+    }
+}
+
+/// The effort needed for an attacker to find a password.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CrackEstimate {
+    /// The time needed to find the password, in seconds.
+    pub seconds: f64,
+    /// What running the attack costs, if the attacker's hourly cost is known.
+    pub cost: Option<f64>,
+}
+
+impl CrackEstimate {
+    /// Returns the time needed to find the password in human terms, such as "3 hours" or
+    /// "centuries", as zxcvbn displays its own estimations.
+    #[must_use]
+    pub fn duration(&self) -> String {
+        humanize(self.seconds)
+    }
+
+    /// Returns a relatable comparison for the time needed to find the password, such as "about
+    /// one World Cup cycle", as [`comparison`] does.
+    #[must_use]
+    pub fn comparison(&self) -> &'static str {
+        comparison(self.seconds)
+    }
+}
+
+/// Returns a relatable comparison for a duration in seconds.
+///
+/// Comparisons such as "less than watching a movie" or "longer than the age of the universe"
+/// speak to readers to whom durations of millions of years mean little.
+///
+/// # Examples
+///
+/// ```
+/// use motus::analysis::crack::comparison;
+///
+/// assert_eq!(comparison(3600.0), "less than watching a movie");
+/// assert_eq!(comparison(1e30), "longer than the age of the universe");
+/// ```
+#[must_use]
+pub fn comparison(seconds: f64) -> &'static str {
+    [
+        (1.0, "less than the blink of an eye"),
+        (10.0 * MINUTE, "less than brewing a pot of coffee"),
+        (3.0 * HOUR, "less than watching a movie"),
+        (DAY, "less than a day"),
+        (2.0 * MONTH, "less than a summer vacation"),
+        (YEAR, "less than a trip around the sun"),
+        (6.0 * YEAR, "about one World Cup cycle"),
+        (CENTURY, "within a human lifetime"),
+        (5e3 * YEAR, "longer than a human lifetime"),
+        (3e5 * YEAR, "longer than recorded history"),
+        (6.6e7 * YEAR, "longer than humanity has existed"),
+        (1.38e10 * YEAR, "longer than since the dinosaurs died out"),
+    ]
+    .into_iter()
+    .find(|(bound, _)| seconds < *bound)
+    .map_or("longer than the age of the universe", |(_, comparison)| {
+        comparison
+    })
+}
+
+const MINUTE: f64 = 60.0;
+const HOUR: f64 = MINUTE * 60.0;
+const DAY: f64 = HOUR * 24.0;
+const MONTH: f64 = DAY * 31.0;
+const YEAR: f64 = MONTH * 12.0;
+const CENTURY: f64 = YEAR * 100.0;
+
+// humanize formats a duration in seconds in its largest relevant unit.
+fn humanize(seconds: f64) -> String {
+    if seconds < 1.0 {
+        return "less than a second".to_string();
+    }
+    if seconds >= CENTURY {
+        return "centuries".to_string();
+    }
+
+    let (value, unit) = [
+        (YEAR, "year"),
+        (MONTH, "month"),
+        (DAY, "day"),
+        (HOUR, "hour"),
+        (MINUTE, "minute"),
+    ]
+    .into_iter()
+    .find(|(unit, _)| seconds >= *unit)
+    .map_or((seconds, "second"), |(unit, name)| (seconds / unit, name));
+
+    let value = value.round();
+    if value <= 1.0 {
+        format!("1 {unit}")
+    } else {
+        format!("{value} {unit}s")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_humanize() {
+        assert_eq!(humanize(0.5), "less than a second");
+        assert_eq!(humanize(1.0), "1 second");
+        assert_eq!(humanize(90.0), "2 minutes");
+        assert_eq!(humanize(DAY * 3.0), "3 days");
+        assert_eq!(humanize(YEAR * 42.0), "42 years");
+        assert_eq!(humanize(CENTURY * 2.0), "centuries");
+    }
+
+    #[test]
+    fn test_comparison() {
+        assert_eq!(comparison(0.2), "less than the blink of an eye");
+        assert_eq!(comparison(DAY * 3.0), "less than a summer vacation");
+        assert_eq!(comparison(YEAR * 4.0), "about one World Cup cycle");
+        assert_eq!(comparison(YEAR * 1e6), "longer than humanity has existed");
+        assert_eq!(
+            comparison(f64::INFINITY),
+            "longer than the age of the universe"
+        );
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        // 10^12 guesses at 10^9 guesses per second take 1000 seconds
+        let estimate = AttackModel::new(1e9)
+            .with_cost_per_hour(36.0)
+            .estimate(12.0);
+
+        assert!((estimate.seconds - 1000.0).abs() < 1e-6);
+        assert!((estimate.cost.expect("cost should be known") - 10.0).abs() < 1e-6);
+        assert_eq!(AttackModel::new(1e9).estimate(12.0).cost, None);
+    }
+}
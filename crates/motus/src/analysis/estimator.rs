@@ -0,0 +1,312 @@
+//! Alternative password strength estimators.
+//!
+//! zxcvbn estimates the number of guesses needed to find a password by matching it against
+//! dictionaries and common patterns, which can over- or underestimate the strength of some
+//! generated formats, such as passphrases of rare words or long random strings. An [`Estimator`]
+//! is any model estimating that number, and several can be compared side by side:
+//!
+//! ```
+//! use motus::analysis::estimator::{CharsetEntropy, Estimator, Markov};
+//!
+//! let estimators: [&dyn Estimator; 2] = [&CharsetEntropy, Markov::embedded()];
+//! for estimator in estimators {
+//!     let estimate = estimator.estimate("correct horse battery staple");
+//!     assert!(estimate.guesses_log10 > 10.0, "{}", estimate.estimator);
+//! }
+//! ```
+
+use std::sync::LazyLock;
+
+use clap::ValueEnum;
+
+// ALPHABET is the number of characters the Markov model knows: the printable ASCII characters,
+// letters regardless of their case, and a last one standing for every other character.
+const ALPHABET: usize = 70;
+
+// SMOOTHING is the share of the probability of each transition of the Markov model spread
+// uniformly over the alphabet, for the transitions missing from its training words.
+const SMOOTHING: f64 = 0.05;
+
+static MARKOV: LazyLock<Markov> = LazyLock::new(|| Markov::train(crate::WORDS_LIST.iter()));
+
+/// A model estimating the number of guesses an attacker needs to find a password.
+pub trait Estimator {
+    /// Returns the name of the estimator, as it is selected on the command line.
+    fn name(&self) -> &'static str;
+
+    /// Returns the base 10 logarithm of the number of guesses needed to find the password.
+    fn guesses_log10(&self, password: &str) -> f64;
+
+    /// Estimates the strength of the password.
+    fn estimate(&self, password: &str) -> Estimate {
+        Estimate {
+            estimator: self.name(),
+            guesses_log10: self.guesses_log10(password),
+        }
+    }
+}
+
+impl<E: Estimator + ?Sized> Estimator for &E {
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+
+    fn guesses_log10(&self, password: &str) -> f64 {
+        (**self).guesses_log10(password)
+    }
+}
+
+impl<E: Estimator + ?Sized> Estimator for Box<E> {
+    fn name(&self) -> &'static str {
+        (**self).name()
+    }
+
+    fn guesses_log10(&self, password: &str) -> f64 {
+        (**self).guesses_log10(password)
+    }
+}
+
+/// The strength of a password, as estimated by an [`Estimator`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Estimate {
+    /// The name of the estimator.
+    pub estimator: &'static str,
+
+    /// The base 10 logarithm of the number of guesses needed to find the password.
+    pub guesses_log10: f64,
+}
+
+impl Estimate {
+    /// Returns the number of guesses needed to find the password, in bits.
+    #[must_use]
+    pub fn bits(&self) -> f64 {
+        self.guesses_log10 * std::f64::consts::LOG2_10
+    }
+
+    /// Returns the score of the estimate, from 0 (very weak) to 4 (very strong), with the same
+    /// thresholds on the number of guesses as zxcvbn's, so that estimators compare.
+    #[must_use]
+    pub fn score(&self) -> u8 {
+        match self.guesses_log10 {
+            g if g < 3.0 => 0,
+            g if g < 6.0 => 1,
+            g if g < 8.0 => 2,
+            g if g < 10.0 => 3,
+            _ => 4,
+        }
+    }
+}
+
+/// The estimators motus provides.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EstimatorKind {
+    /// zxcvbn's pattern matching, the estimator of the security analysis.
+    #[cfg(feature = "analysis")]
+    Zxcvbn,
+    /// A character-level Markov model trained on the embedded wordlist.
+    Markov,
+    /// The size of the pool of characters the password draws from, raised to its length.
+    Entropy,
+}
+
+impl EstimatorKind {
+    /// Returns the estimator, treating the words of `dictionary` as dictionary words when it
+    /// supports them.
+    #[must_use]
+    #[cfg_attr(not(feature = "analysis"), allow(unused_variables))]
+    pub fn estimator(self, dictionary: &[String]) -> Box<dyn Estimator> {
+        match self {
+            #[cfg(feature = "analysis")]
+            Self::Zxcvbn => Box::new(Zxcvbn::with_dictionary(dictionary.to_vec())),
+            Self::Markov => Box::new(Markov::embedded()),
+            Self::Entropy => Box::new(CharsetEntropy),
+        }
+    }
+}
+
+/// Estimates strength by the size of the pool of the classes of characters a password holds,
+/// raised to its length: the entropy of the password if its characters were drawn uniformly
+/// from these classes.
+///
+/// The model knows nothing of words or patterns, and overestimates every password that is not
+/// random.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct CharsetEntropy;
+
+impl Estimator for CharsetEntropy {
+    fn name(&self) -> &'static str {
+        "entropy"
+    }
+
+    #[allow(clippy::cast_precision_loss)] // lengths are way below f64's precision limits
+    fn guesses_log10(&self, password: &str) -> f64 {
+        let chars: Vec<char> = password.chars().collect();
+        let has = |class: fn(&char) -> bool| chars.iter().any(class);
+        let pool: usize = [
+            (has(char::is_ascii_lowercase), 26),
+            (has(char::is_ascii_uppercase), 26),
+            (has(char::is_ascii_digit), 10),
+            (has(|c| c.is_ascii_punctuation() || *c == ' '), 33),
+            // Attackers rarely try non-ASCII characters, which are counted as a hundred more
+            (has(|c| !c.is_ascii()), 100),
+        ]
+        .iter()
+        .filter(|(present, _)| *present)
+        .map(|(_, size)| size)
+        .sum();
+
+        chars.len() as f64 * (pool.max(1) as f64).log10()
+    }
+}
+
+/// Estimates strength by the probability of a password under a character-level Markov model,
+/// as attackers enumerating the likeliest strings first would.
+///
+/// Each character is predicted from the one before it, with transition probabilities learned
+/// from training words. Letters are predicted regardless of their case, each uppercase one
+/// doubling the number of guesses, and characters missing from the training words are
+/// predicted as if drawn uniformly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Markov {
+    // log2 holds the base 2 logarithm of the probability of each character following each
+    // other, the last row being for the first character
+    log2: Vec<f64>,
+}
+
+impl Markov {
+    /// Returns the model trained on the embedded wordlist.
+    #[must_use]
+    pub fn embedded() -> &'static Self {
+        &MARKOV
+    }
+
+    /// Trains a model on the given words.
+    #[allow(clippy::cast_precision_loss)] // counts are way below f64's precision limits
+    pub fn train<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        let mut counts = vec![0usize; (ALPHABET + 1) * ALPHABET];
+        for word in words {
+            let mut previous = ALPHABET;
+            for c in word.as_ref().chars() {
+                let current = index(c);
+                counts[previous * ALPHABET + current] += 1;
+                previous = current;
+            }
+        }
+
+        let log2 = counts
+            .chunks(ALPHABET)
+            .flat_map(|row| {
+                let total: usize = row.iter().sum();
+                row.iter().map(move |&count| {
+                    let learned = if total == 0 {
+                        0.0
+                    } else {
+                        count as f64 / total as f64
+                    };
+                    (1.0 - SMOOTHING)
+                        .mul_add(learned, SMOOTHING / ALPHABET as f64)
+                        .log2()
+                })
+            })
+            .collect();
+
+        Self { log2 }
+    }
+}
+
+impl Estimator for Markov {
+    fn name(&self) -> &'static str {
+        "markov"
+    }
+
+    fn guesses_log10(&self, password: &str) -> f64 {
+        let mut previous = ALPHABET;
+        let mut bits = 0.0;
+        for c in password.chars() {
+            let current = index(c);
+            bits -= self.log2[previous * ALPHABET + current];
+            if c.is_uppercase() {
+                bits += 1.0;
+            }
+            previous = current;
+        }
+
+        bits / std::f64::consts::LOG2_10
+    }
+}
+
+/// Estimates strength with zxcvbn, matching the password against dictionaries and common
+/// patterns, and against the words of an optional user dictionary.
+#[cfg(feature = "analysis")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Zxcvbn {
+    dictionary: Vec<String>,
+}
+
+#[cfg(feature = "analysis")]
+impl Zxcvbn {
+    /// Creates an estimator treating the given words as dictionary words, such as the terms of
+    /// a [`crate::denylist::DenyList`].
+    #[must_use]
+    pub const fn with_dictionary(dictionary: Vec<String>) -> Self {
+        Self { dictionary }
+    }
+}
+
+#[cfg(feature = "analysis")]
+impl Estimator for Zxcvbn {
+    fn name(&self) -> &'static str {
+        "zxcvbn"
+    }
+
+    fn guesses_log10(&self, password: &str) -> f64 {
+        let dictionary: Vec<&str> = self.dictionary.iter().map(String::as_str).collect();
+        zxcvbn::zxcvbn(password, &dictionary).map_or(0.0, |entropy| entropy.guesses_log10())
+    }
+}
+
+// index returns the position of `c` in the alphabet of the Markov model.
+const fn index(c: char) -> usize {
+    let c = c.to_ascii_uppercase();
+    match c {
+        ' '..='`' => c as usize - ' ' as usize,
+        // Lowercase letters were folded onto uppercase ones, which come before them
+        '{'..='~' => c as usize - '{' as usize + 65,
+        _ => ALPHABET - 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markov_alphabet() {
+        let mut indices: Vec<usize> = (' '..='~').map(index).collect();
+        indices.sort_unstable();
+        indices.dedup();
+        assert_eq!(indices.len(), ALPHABET - 1);
+        assert_eq!(index('a'), index('A'));
+        assert_eq!(index('é'), ALPHABET - 1);
+    }
+
+    #[test]
+    fn test_markov_ranks_words_first() {
+        let markov = Markov::embedded();
+        let word = markov.guesses_log10("lantern");
+        let random = markov.guesses_log10("xqzvkjw");
+        assert!(word < random, "{word} < {random}");
+        assert!(markov.guesses_log10("Lantern") > word);
+        assert!(markov.guesses_log10("") == 0.0);
+    }
+
+    #[test]
+    fn test_charset_entropy() {
+        let estimate = CharsetEntropy.estimate("abc1");
+        assert!(4.0f64.mul_add(-36f64.log10(), estimate.guesses_log10).abs() < 1e-9);
+        assert!(4.0f64.mul_add(-36f64.log2(), estimate.bits()).abs() < 1e-9);
+        assert_eq!(estimate.score(), 2);
+        assert_eq!(CharsetEntropy.estimate("abc").score(), 1);
+        assert_eq!(CharsetEntropy.estimate("x7#Kq9!mZ2").score(), 4);
+    }
+}
@@ -0,0 +1,151 @@
+//! Homoglyph detection.
+//!
+//! Some characters look alike in common fonts, such as `0` and `O`, or `1`, `l` and `I`, and so
+//! do some sequences of characters, such as `rn` and `m`. They are harmless in passwords that
+//! are copied and pasted, but make the ones written on paper or printed on labels easy to
+//! misread.
+
+/// Groups of characters, or sequences of characters, that print alike in common fonts.
+pub const CONFUSABLES: &[&[&str]] = &[
+    &["0", "O", "o", "D"],
+    &["1", "l", "I", "|", "!"],
+    &["2", "Z"],
+    &["5", "S"],
+    &["8", "B"],
+    &["'", "`"],
+    &["rn", "m"],
+    &["vv", "w"],
+    &["cl", "d"],
+];
+
+/// A character, or sequence of characters, found in a password that prints ambiguously.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Homoglyph {
+    /// The ambiguous characters.
+    pub token: String,
+
+    /// The index, in characters, of the token's first character in the password.
+    pub start: usize,
+
+    /// What the token could be mistaken for.
+    pub lookalikes: Vec<&'static str>,
+}
+
+/// Detects the characters of `password` that print ambiguously.
+///
+/// Single characters are flagged when they have a lookalike, and sequences such as `rn` when
+/// they could be read as a single character. The reverse, reading `m` as `rn`, is unlikely
+/// enough not to be flagged.
+///
+/// # Examples
+///
+/// ```
+/// use motus::analysis::homoglyph::detect;
+///
+/// let homoglyphs = detect("c0rner");
+/// assert_eq!(homoglyphs.len(), 2);
+/// assert_eq!(homoglyphs[0].token, "0");
+/// assert_eq!(homoglyphs[0].lookalikes, vec!["O", "o", "D"]);
+/// assert_eq!(homoglyphs[1].token, "rn");
+/// assert_eq!(homoglyphs[1].lookalikes, vec!["m"]);
+///
+/// assert!(detect("xkcd").is_empty());
+/// ```
+#[must_use]
+pub fn detect(password: &str) -> Vec<Homoglyph> {
+    let chars: Vec<char> = password.chars().collect();
+    let mut homoglyphs = Vec::new();
+
+    let mut i = 0;
+    while i < chars.len() {
+        let pair: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        let single = chars[i].to_string();
+
+        let found = [pair, single]
+            .into_iter()
+            .filter(|token| token.chars().count() > 1 || is_ambiguous(chars[i]))
+            .find_map(|token| lookalikes(&token).map(|lookalikes| (token, lookalikes)));
+
+        if let Some((token, lookalikes)) = found {
+            let length = token.chars().count();
+            homoglyphs.push(Homoglyph {
+                token,
+                start: i,
+                lookalikes,
+            });
+            i += length;
+        } else {
+            i += 1;
+        }
+    }
+
+    homoglyphs
+}
+
+/// Returns whether `c` has a lookalike character in common fonts.
+#[must_use]
+pub fn is_ambiguous(c: char) -> bool {
+    let mut buffer = [0; 4];
+    let c = &*c.encode_utf8(&mut buffer);
+
+    CONFUSABLES
+        .iter()
+        .any(|group| group.iter().all(|glyph| glyph.chars().count() == 1) && group.contains(&c))
+}
+
+/// Returns whether `c` following `previous` forms a sequence that could be read as another
+/// character, as `r` followed by `n` reads as `m`.
+#[must_use]
+pub fn forms_lookalike(previous: char, c: char) -> bool {
+    let pair: String = [previous, c].iter().collect();
+    lookalikes(&pair).is_some()
+}
+
+// lookalikes returns what the given token could be mistaken for, or None if it prints
+// unambiguously. Multi-character tokens only match the sequences of the confusable groups.
+fn lookalikes(token: &str) -> Option<Vec<&'static str>> {
+    CONFUSABLES
+        .iter()
+        .find(|group| group.contains(&token))
+        .map(|group| {
+            group
+                .iter()
+                .copied()
+                .filter(|glyph| *glyph != token)
+                .collect()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        let homoglyphs = detect("Il1Oc");
+        let tokens: Vec<&str> = homoglyphs.iter().map(|h| h.token.as_str()).collect();
+        assert_eq!(tokens, vec!["I", "l", "1", "O"]);
+        assert_eq!(homoglyphs[3].start, 3);
+    }
+
+    #[test]
+    fn test_detect_sequences() {
+        let homoglyphs = detect("burnvvclm");
+        let tokens: Vec<&str> = homoglyphs.iter().map(|h| h.token.as_str()).collect();
+        assert_eq!(tokens, vec!["rn", "vv", "cl"]);
+    }
+
+    #[test]
+    fn test_is_ambiguous() {
+        assert!(is_ambiguous('0'));
+        assert!(is_ambiguous('l'));
+        assert!(!is_ambiguous('m'));
+        assert!(!is_ambiguous('x'));
+    }
+
+    #[test]
+    fn test_forms_lookalike() {
+        assert!(forms_lookalike('r', 'n'));
+        assert!(!forms_lookalike('n', 'r'));
+    }
+}
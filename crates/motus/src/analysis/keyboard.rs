@@ -0,0 +1,123 @@
+// Model of the US QWERTY keyboard layout, used to reason about which keys are physically close
+// to each other.
+
+// ROWS holds the unshifted and shifted characters of each row of the keyboard, from top to
+// bottom.
+const ROWS: [(&str, &str); 4] = [
+    ("`1234567890-=", "~!@#$%^&*()_+"),
+    ("qwertyuiop[]\\", "QWERTYUIOP{}|"),
+    ("asdfghjkl;'", "ASDFGHJKL:\""),
+    ("zxcvbnm,./", "ZXCVBNM<>?"),
+];
+
+// ROW_OFFSETS holds the horizontal offset of each row's first key, in key widths, accounting for
+// the staggering of the rows.
+const ROW_OFFSETS: [f64; 4] = [0.0, 1.5, 1.75, 2.25];
+
+/// Key is the physical location of a character on the keyboard.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Key {
+    pub row: usize,
+    pub column: usize,
+    pub shifted: bool,
+}
+
+impl Key {
+    // x returns the horizontal position of the key's center, in key widths.
+    #[allow(clippy::cast_precision_loss)] // columns are way below f64's precision limits
+    fn x(&self) -> f64 {
+        ROW_OFFSETS[self.row] + self.column as f64
+    }
+
+    /// Returns whether the two keys are physically next to each other, regardless of shift.
+    pub fn is_adjacent(&self, other: &Self) -> bool {
+        match self.row.abs_diff(other.row) {
+            0 => self.column.abs_diff(other.column) == 1,
+            1 => (self.x() - other.x()).abs() < 1.0,
+            _ => false,
+        }
+    }
+}
+
+/// Returns the location of `c` on the keyboard, if it can be typed on it.
+pub fn locate(c: char) -> Option<Key> {
+    ROWS.iter()
+        .enumerate()
+        .find_map(|(row, (unshifted, shifted))| {
+            unshifted
+                .chars()
+                .position(|k| k == c)
+                .map(|column| Key {
+                    row,
+                    column,
+                    shifted: false,
+                })
+                .or_else(|| {
+                    shifted.chars().position(|k| k == c).map(|column| Key {
+                        row,
+                        column,
+                        shifted: true,
+                    })
+                })
+        })
+}
+
+/// Returns the character typed by pressing `key`.
+pub fn character(key: Key) -> Option<char> {
+    let (unshifted, shifted) = ROWS.get(key.row)?;
+    let row = if key.shifted { shifted } else { unshifted };
+    row.chars().nth(key.column)
+}
+
+/// Returns the characters whose keys are adjacent to `c`'s, typed with the same shift state.
+pub fn adjacent_characters(c: char) -> Vec<char> {
+    let Some(key) = locate(c) else {
+        return Vec::new();
+    };
+
+    ROWS.iter()
+        .enumerate()
+        .flat_map(|(row, (unshifted, _))| {
+            (0..unshifted.chars().count()).map(move |column| Key {
+                row,
+                column,
+                shifted: key.shifted,
+            })
+        })
+        .filter(|other| key.is_adjacent(other))
+        .filter_map(character)
+        .collect()
+}
+
+/// Returns the character typed by the same key as `c` with the opposite shift state.
+pub fn shift_counterpart(c: char) -> Option<char> {
+    let key = locate(c)?;
+    character(Key {
+        shifted: !key.shifted,
+        ..key
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjacent_characters() {
+        let mut adjacent = adjacent_characters('g');
+        adjacent.sort_unstable();
+        assert_eq!(adjacent, vec!['b', 'f', 'h', 't', 'v', 'y']);
+
+        let mut adjacent = adjacent_characters('Q');
+        adjacent.sort_unstable();
+        assert_eq!(adjacent, vec!['!', '@', 'A', 'W']);
+    }
+
+    #[test]
+    fn test_shift_counterpart() {
+        assert_eq!(shift_counterpart('a'), Some('A'));
+        assert_eq!(shift_counterpart('1'), Some('!'));
+        assert_eq!(shift_counterpart('?'), Some('/'));
+        assert_eq!(shift_counterpart('é'), None);
+    }
+}
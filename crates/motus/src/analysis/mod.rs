@@ -0,0 +1,14 @@
+//! Password analysis helpers.
+//!
+//! This module hosts the analyses motus performs on passwords. With the `analysis` feature, the
+//! [`report`] module combines them with the strength estimation provided by zxcvbn, which the
+//! [`estimator`] module complements with alternative models.
+
+pub mod crack;
+pub mod estimator;
+pub mod homoglyph;
+mod keyboard;
+#[cfg(feature = "analysis")]
+pub mod report;
+pub mod typo;
+pub mod walk;
@@ -0,0 +1,547 @@
+//! Security analysis reports.
+//!
+//! A [`SecurityAnalysis`] gathers everything motus knows about the safety of a password: its
+//! overall strength as estimated by zxcvbn, how long cracking it takes, how easily it is
+//! mistyped, and the specific weaknesses found in it. It serializes to the same JSON as the
+//! `--output json` reports of the command-line application.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use serde::ser::{SerializeMap, SerializeStruct, Serializer};
+use serde::Serialize;
+use zxcvbn::zxcvbn;
+
+use crate::analysis::crack::AttackModel;
+use crate::analysis::estimator::{Estimate, Estimator};
+use crate::analysis::homoglyph;
+use crate::analysis::typo::{self, TypoAnalysis};
+use crate::analysis::walk::{self, WalkPattern};
+
+/// The overall strength of a password, from its zxcvbn score.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PasswordStrength {
+    VeryWeak,
+    Weak,
+    Reasonable,
+    Strong,
+    VeryStrong,
+}
+
+impl From<u8> for PasswordStrength {
+    /// Converts a zxcvbn score, from 0 to 4, to a strength. Scores above 4 are very strong.
+    fn from(score: u8) -> Self {
+        match score {
+            0 => Self::VeryWeak,
+            1 => Self::Weak,
+            2 => Self::Reasonable,
+            3 => Self::Strong,
+            _ => Self::VeryStrong,
+        }
+    }
+}
+
+impl PasswordStrength {
+    /// Returns the English name of the strength.
+    #[must_use]
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::VeryWeak => "very weak",
+            Self::Weak => "weak",
+            Self::Reasonable => "reasonable",
+            Self::Strong => "strong",
+            Self::VeryStrong => "very strong",
+        }
+    }
+}
+
+impl Display for PasswordStrength {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+impl Serialize for PasswordStrength {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.label())
+    }
+}
+
+/// Counts how many of the given scores fall into each strength level, from 0 (very weak) to 4
+/// (very strong). Every level is present in the result, even when empty.
+pub fn strength_distribution(scores: impl Iterator<Item = u8>) -> BTreeMap<u8, usize> {
+    let mut distribution: BTreeMap<u8, usize> = (0..=4).map(|score| (score, 0)).collect();
+    for score in scores {
+        *distribution.entry(score).or_default() += 1;
+    }
+    distribution
+}
+
+/// A specific weakness found in a password, on top of its overall strength.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Warning {
+    /// The kind of weakness.
+    pub category: WarningCategory,
+
+    /// The characters of the password the warning is about.
+    pub token: String,
+
+    /// What is wrong with the token, in English.
+    pub message: &'static str,
+
+    /// What the token could be mistaken for, for homoglyphs.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lookalikes: Vec<&'static str>,
+}
+
+/// The kind of a [`Warning`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WarningCategory {
+    KeyboardWalk,
+    Homoglyph,
+}
+
+impl WarningCategory {
+    /// Returns the English name of the category.
+    #[must_use]
+    pub const fn label(&self) -> &'static str {
+        match self {
+            Self::KeyboardWalk => "keyboard walk",
+            Self::Homoglyph => "homoglyph",
+        }
+    }
+}
+
+impl Display for WarningCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.label())
+    }
+}
+
+/// The time needed to crack a password in one of the reference scenarios of zxcvbn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CrackTime {
+    /// The rate of guesses of the scenario, as it is keyed in JSON reports, such as `10/s`.
+    pub rate: &'static str,
+
+    /// The rate of guesses of the scenario, in English, such as `10 attempts/second`.
+    pub label: &'static str,
+
+    /// The time needed, in English, such as `3 hours`.
+    pub time: String,
+
+    /// The time needed, in seconds, computed from the number of guesses rather than rounded as
+    /// `time` is.
+    pub seconds: f64,
+}
+
+/// The effort needed by a custom attacker to crack a password.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct AttackReport {
+    /// The number of guesses the attacker makes per second.
+    pub guesses_per_second: f64,
+
+    /// The time needed to find the password, in seconds.
+    pub seconds: f64,
+
+    /// The time needed to find the password, in English, such as `3 hours`.
+    pub time: String,
+
+    /// What running the attacker's hardware for that long costs, if known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+/// The security analysis of a password.
+///
+/// # Examples
+///
+/// ```
+/// use motus::analysis::report::{PasswordStrength, SecurityAnalysis};
+///
+/// let analysis = SecurityAnalysis::new("qwert");
+///
+/// assert_eq!(analysis.strength(), PasswordStrength::VeryWeak);
+/// assert_eq!(analysis.warnings()[0].token, "qwert");
+/// ```
+#[derive(Clone, Debug)]
+pub struct SecurityAnalysis<'a> {
+    password: &'a str,
+    entropy: zxcvbn::Entropy,
+    typo: TypoAnalysis,
+    warnings: Vec<Warning>,
+    attacks: Vec<AttackModel>,
+    estimates: Vec<Estimate>,
+}
+
+impl<'a> SecurityAnalysis<'a> {
+    /// Analyzes the given password.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the password is empty, as there is nothing to analyze.
+    #[must_use]
+    pub fn new(password: &'a str) -> Self {
+        Self::new_with_dictionary(password, &[])
+    }
+
+    /// Analyzes the given password, as [`SecurityAnalysis::new`] does, with a dictionary of words
+    /// an attacker would try first, such as the terms of a [`crate::denylist::DenyList`], the
+    /// name of the organization or the usernames of its users.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the password is empty, as there is nothing to analyze.
+    #[must_use]
+    pub fn new_with_dictionary(password: &'a str, dictionary: &[&str]) -> Self {
+        let entropy = zxcvbn(password, dictionary).expect("unable to analyze password's safety");
+        let typo = typo::analyze(password);
+
+        let warnings = walk::detect(password)
+            .into_iter()
+            .map(|walk| Warning {
+                category: WarningCategory::KeyboardWalk,
+                message: match walk.pattern {
+                    WalkPattern::Row => "keys follow each other along a keyboard row",
+                    WalkPattern::Zigzag => "keys follow each other across keyboard rows",
+                },
+                token: walk.token,
+                lookalikes: Vec::new(),
+            })
+            .collect();
+
+        Self {
+            password,
+            entropy,
+            typo,
+            warnings,
+            attacks: Vec::new(),
+            estimates: Vec::new(),
+        }
+    }
+
+    /// Adds crack time estimations for the given custom attackers to the analysis.
+    #[must_use]
+    pub fn with_attacks(mut self, attacks: Vec<AttackModel>) -> Self {
+        self.attacks = attacks;
+        self
+    }
+
+    /// Adds the estimates of the given estimators to the analysis, to compare them with zxcvbn's.
+    #[must_use]
+    pub fn with_estimators<E: Estimator>(mut self, estimators: &[E]) -> Self {
+        self.estimates = estimators
+            .iter()
+            .map(|estimator| estimator.estimate(self.password))
+            .collect();
+        self
+    }
+
+    /// Flags the characters of the password that print ambiguously, for passwords meant to be
+    /// written down or printed.
+    #[must_use]
+    pub fn with_homoglyphs(mut self) -> Self {
+        self.warnings.extend(
+            homoglyph::detect(self.password)
+                .into_iter()
+                .map(|homoglyph| Warning {
+                    category: WarningCategory::Homoglyph,
+                    token: homoglyph.token,
+                    message: "prints ambiguously in common fonts",
+                    lookalikes: homoglyph.lookalikes,
+                }),
+        );
+        self
+    }
+
+    /// Returns the analyzed password.
+    #[must_use]
+    pub const fn password(&self) -> &'a str {
+        self.password
+    }
+
+    /// Returns the overall strength of the password.
+    #[must_use]
+    pub fn strength(&self) -> PasswordStrength {
+        PasswordStrength::from(self.entropy.score())
+    }
+
+    /// Returns the zxcvbn score of the password, from 0 (very weak) to 4 (very strong).
+    #[must_use]
+    pub fn score(&self) -> u8 {
+        self.entropy.score()
+    }
+
+    /// Returns the base 10 logarithm of the number of guesses needed to find the password.
+    #[must_use]
+    pub fn guesses_log10(&self) -> f64 {
+        self.entropy.guesses_log10()
+    }
+
+    /// Returns the order of magnitude of the number of guesses needed to find the password,
+    /// formatted as a power of ten.
+    #[must_use]
+    pub fn guesses(&self) -> String {
+        format!("10^{:.0}", self.entropy.guesses_log10())
+    }
+
+    /// Returns how likely the password is to be mistyped.
+    #[must_use]
+    pub const fn typo(&self) -> &TypoAnalysis {
+        &self.typo
+    }
+
+    /// Returns the specific weaknesses found in the password.
+    #[must_use]
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Returns the estimates of the estimators added to the analysis.
+    #[must_use]
+    pub fn estimates(&self) -> &[Estimate] {
+        &self.estimates
+    }
+
+    /// Returns the time needed to crack the password in each reference scenario, from the
+    /// slowest attack to the fastest.
+    #[must_use]
+    pub fn crack_times(&self) -> Vec<CrackTime> {
+        let crack_times = self.entropy.crack_times();
+        [
+            (
+                "100/h",
+                "100 attempts/hour",
+                crack_times.online_throttling_100_per_hour(),
+                100.0 / 3600.0,
+            ),
+            (
+                "10/s",
+                "10 attempts/second",
+                crack_times.online_no_throttling_10_per_second(),
+                10.0,
+            ),
+            (
+                "10^4/s",
+                "10^4 attempts/second",
+                crack_times.offline_slow_hashing_1e4_per_second(),
+                1e4,
+            ),
+            (
+                "10^10/s",
+                "10^10 attempts/second",
+                crack_times.offline_fast_hashing_1e10_per_second(),
+                1e10,
+            ),
+        ]
+        .into_iter()
+        .map(|(rate, label, time, guesses_per_second)| CrackTime {
+            rate,
+            label,
+            time: time.to_string(),
+            seconds: AttackModel::new(guesses_per_second)
+                .estimate(self.entropy.guesses_log10())
+                .seconds,
+        })
+        .collect()
+    }
+
+    /// Returns the effort needed by each custom attacker to find the password.
+    #[must_use]
+    pub fn attack_reports(&self) -> Vec<AttackReport> {
+        self.attacks
+            .iter()
+            .map(|attack| {
+                let estimate = attack.estimate(self.entropy.guesses_log10());
+                AttackReport {
+                    guesses_per_second: attack.guesses_per_second(),
+                    seconds: estimate.seconds,
+                    time: estimate.duration(),
+                    cost: estimate.cost.map(|cost| (cost * 100.0).round() / 100.0),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns what makes the password weak, according to zxcvbn, if anything.
+    #[must_use]
+    pub fn feedback_warning(&self) -> Option<String> {
+        self.entropy
+            .feedback()
+            .as_ref()
+            .and_then(zxcvbn::feedback::Feedback::warning)
+            .map(|warning| warning.to_string())
+    }
+
+    /// Returns the advice of zxcvbn on making the password stronger.
+    #[must_use]
+    pub fn suggestions(&self) -> Vec<String> {
+        self.entropy
+            .feedback()
+            .as_ref()
+            .map(|feedback| {
+                feedback
+                    .suggestions()
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+// CrackTimes serializes the crack times of an analysis as a map keyed by rate.
+struct CrackTimes(Vec<CrackTime>);
+
+impl Serialize for CrackTimes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for crack_time in &self.0 {
+            map.serialize_entry(crack_time.rate, &crack_time.time)?;
+        }
+        map.end()
+    }
+}
+
+#[derive(Serialize)]
+struct TypoReport {
+    neighbors: usize,
+    adjacent_key: usize,
+    case_slip: usize,
+    transposition: usize,
+    probability: f64,
+}
+
+impl From<&TypoAnalysis> for TypoReport {
+    fn from(analysis: &TypoAnalysis) -> Self {
+        Self {
+            neighbors: analysis.neighbors(),
+            adjacent_key: analysis.adjacent_key_neighbors,
+            case_slip: analysis.case_slip_neighbors,
+            transposition: analysis.transposition_neighbors,
+            probability: (analysis.typo_probability * 10_000.0).round() / 10_000.0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct EstimateReport {
+    estimator: &'static str,
+    strength: PasswordStrength,
+    guesses: String,
+    bits: f64,
+}
+
+impl From<&Estimate> for EstimateReport {
+    fn from(estimate: &Estimate) -> Self {
+        Self {
+            estimator: estimate.estimator,
+            strength: PasswordStrength::from(estimate.score()),
+            guesses: format!("10^{:.0}", estimate.guesses_log10),
+            bits: (estimate.bits() * 100.0).round() / 100.0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct FeedbackReport {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<String>,
+    suggestions: Vec<String>,
+}
+
+impl Serialize for SecurityAnalysis<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut report = serializer.serialize_struct("SecurityAnalysis", 8)?;
+        report.serialize_field("strength", &self.strength())?;
+        report.serialize_field("guesses", &self.guesses())?;
+        report.serialize_field("crack_times", &CrackTimes(self.crack_times()))?;
+        report.serialize_field("typo", &TypoReport::from(&self.typo))?;
+        report.serialize_field("warnings", &self.warnings)?;
+        report.serialize_field(
+            "feedback",
+            &FeedbackReport {
+                warning: self.feedback_warning(),
+                suggestions: self.suggestions(),
+            },
+        )?;
+        if self.attacks.is_empty() {
+            report.skip_field("attacks")?;
+        } else {
+            report.serialize_field("attacks", &self.attack_reports())?;
+        }
+        if self.estimates.is_empty() {
+            report.skip_field("estimates")?;
+        } else {
+            let estimates: Vec<EstimateReport> =
+                self.estimates.iter().map(EstimateReport::from).collect();
+            report.serialize_field("estimates", &estimates)?;
+        }
+        report.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strength() {
+        assert_eq!(PasswordStrength::from(0), PasswordStrength::VeryWeak);
+        assert_eq!(PasswordStrength::from(4), PasswordStrength::VeryStrong);
+        assert_eq!(PasswordStrength::from(7), PasswordStrength::VeryStrong);
+        assert_eq!(PasswordStrength::Reasonable.to_string(), "reasonable");
+    }
+
+    #[test]
+    fn test_strength_distribution() {
+        let distribution = strength_distribution([0, 4, 4].into_iter());
+        assert_eq!(distribution.len(), 5);
+        assert_eq!(distribution[&0], 1);
+        assert_eq!(distribution[&2], 0);
+        assert_eq!(distribution[&4], 2);
+    }
+
+    #[test]
+    fn test_with_homoglyphs() {
+        let analysis = SecurityAnalysis::new("c0rnfake").with_homoglyphs();
+        let categories: Vec<WarningCategory> = analysis
+            .warnings()
+            .iter()
+            .map(|warning| warning.category)
+            .collect();
+        assert_eq!(
+            categories,
+            vec![WarningCategory::Homoglyph, WarningCategory::Homoglyph]
+        );
+        assert!(SecurityAnalysis::new("c0rnfake").warnings().is_empty());
+    }
+
+    #[test]
+    fn test_with_estimators() {
+        use crate::analysis::estimator::{CharsetEntropy, Markov};
+
+        let estimators: [&dyn Estimator; 2] = [Markov::embedded(), &CharsetEntropy];
+        let analysis = SecurityAnalysis::new("correct horse").with_estimators(&estimators);
+        let names: Vec<&str> = analysis
+            .estimates()
+            .iter()
+            .map(|estimate| estimate.estimator)
+            .collect();
+        assert_eq!(names, ["markov", "entropy"]);
+        assert!(SecurityAnalysis::new("correct horse")
+            .estimates()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_crack_times() {
+        let rates: Vec<&str> = SecurityAnalysis::new("correct horse")
+            .crack_times()
+            .iter()
+            .map(|crack_time| crack_time.rate)
+            .collect();
+        assert_eq!(rates, vec!["100/h", "10/s", "10^4/s", "10^10/s"]);
+    }
+}
@@ -0,0 +1,165 @@
+//! Typo-tolerance estimation.
+//!
+//! Passwords of the same length and strength are not equally easy to type. This module estimates
+//! how likely a password is to be mistyped, and how many "neighbor" passwords are one common typo
+//! away from it: pressing an adjacent key, slipping on the shift key, or swapping two consecutive
+//! characters.
+
+use std::collections::HashSet;
+
+use super::keyboard;
+
+// KEYSTROKE_ERROR_RATE is the estimated probability of mistyping any given character.
+const KEYSTROKE_ERROR_RATE: f64 = 0.005;
+
+// SHIFT_ERROR_RATE is the estimated additional probability of mistyping a character whose
+// shift state differs from the previous character's.
+const SHIFT_ERROR_RATE: f64 = 0.01;
+
+// UNTYPABLE_ERROR_RATE is the estimated probability of mistyping a character that is not found
+// on a US QWERTY keyboard, and thus requires a layout switch or a compose sequence.
+const UNTYPABLE_ERROR_RATE: f64 = 0.05;
+
+/// The result of a typo-tolerance analysis.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypoAnalysis {
+    /// Number of distinct passwords obtained by pressing a key adjacent to the intended one.
+    pub adjacent_key_neighbors: usize,
+
+    /// Number of distinct passwords obtained by slipping on the shift key for one character.
+    pub case_slip_neighbors: usize,
+
+    /// Number of distinct passwords obtained by swapping two consecutive characters.
+    pub transposition_neighbors: usize,
+
+    /// Estimated probability that typing the password results in at least one typo.
+    pub typo_probability: f64,
+}
+
+impl TypoAnalysis {
+    /// Returns the total number of passwords one common typo away from the analyzed one.
+    #[must_use]
+    pub const fn neighbors(&self) -> usize {
+        self.adjacent_key_neighbors + self.case_slip_neighbors + self.transposition_neighbors
+    }
+}
+
+/// Estimates how resistant `password` is to common typos.
+///
+/// # Examples
+///
+/// ```
+/// use motus::analysis::typo;
+///
+/// let lowercase = typo::analyze("chokeholdnativity");
+/// let mixed = typo::analyze("cHoKeHoLdNaTiViTy");
+///
+/// // Alternating shift states makes a password more likely to be mistyped
+/// assert!(lowercase.typo_probability < mixed.typo_probability);
+/// ```
+#[must_use]
+pub fn analyze(password: &str) -> TypoAnalysis {
+    let chars: Vec<char> = password.chars().collect();
+
+    let mut adjacent_key = HashSet::new();
+    let mut case_slip = HashSet::new();
+    let mut transposition = HashSet::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        for replacement in keyboard::adjacent_characters(c) {
+            adjacent_key.insert(replace(&chars, i, replacement));
+        }
+
+        if let Some(replacement) = keyboard::shift_counterpart(c) {
+            case_slip.insert(replace(&chars, i, replacement));
+        }
+
+        if i + 1 < chars.len() && c != chars[i + 1] {
+            let mut swapped = chars.clone();
+            swapped.swap(i, i + 1);
+            transposition.insert(swapped.into_iter().collect::<String>());
+        }
+    }
+
+    TypoAnalysis {
+        adjacent_key_neighbors: adjacent_key.len(),
+        case_slip_neighbors: case_slip.len(),
+        transposition_neighbors: transposition.len(),
+        typo_probability: typo_probability(&chars),
+    }
+}
+
+// typo_probability estimates the probability of making at least one typo while typing the given
+// characters, assuming keystroke errors are independent from each other.
+fn typo_probability(chars: &[char]) -> f64 {
+    let mut previous_shifted = false;
+    let mut success_probability = 1.0;
+
+    for &c in chars {
+        // The space bar is typed with the thumbs, independently of the shift state
+        if c == ' ' {
+            success_probability *= 1.0 - KEYSTROKE_ERROR_RATE;
+            continue;
+        }
+
+        let error_rate = keyboard::locate(c).map_or(UNTYPABLE_ERROR_RATE, |key| {
+            let shift_toggle = key.shifted != previous_shifted;
+            previous_shifted = key.shifted;
+            if shift_toggle {
+                KEYSTROKE_ERROR_RATE + SHIFT_ERROR_RATE
+            } else {
+                KEYSTROKE_ERROR_RATE
+            }
+        });
+
+        success_probability *= 1.0 - error_rate;
+    }
+
+    1.0 - success_probability
+}
+
+// replace returns the string made of `chars` where the character at `index` is replaced by
+// `replacement`.
+fn replace(chars: &[char], index: usize, replacement: char) -> String {
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| if i == index { replacement } else { c })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_neighbors() {
+        let analysis = analyze("ab");
+
+        // a: q, w, s, z; b: v, g, h, n
+        assert_eq!(analysis.adjacent_key_neighbors, 8);
+        // Ab, aB
+        assert_eq!(analysis.case_slip_neighbors, 2);
+        // ba
+        assert_eq!(analysis.transposition_neighbors, 1);
+        assert_eq!(analysis.neighbors(), 11);
+    }
+
+    #[test]
+    fn test_analyze_repeated_characters() {
+        let analysis = analyze("aa");
+        assert_eq!(analysis.transposition_neighbors, 0);
+    }
+
+    #[test]
+    fn test_analyze_empty() {
+        let analysis = analyze("");
+        assert_eq!(analysis.neighbors(), 0);
+        assert!(analysis.typo_probability.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_typo_probability_grows_with_length() {
+        assert!(analyze("abcd").typo_probability < analyze("abcdefgh").typo_probability);
+    }
+}
@@ -0,0 +1,125 @@
+//! Keyboard walk detection.
+//!
+//! Keyboard walks are sequences of physically adjacent keys, such as `qwerty`, `asdf` or
+//! `1qaz`. They look random but are among the first patterns attackers try, and are worth
+//! flagging explicitly when auditing existing credentials.
+
+use super::keyboard::{self, Key};
+
+/// The minimum number of consecutive adjacent keys for a sequence to be considered a walk.
+pub const MIN_WALK_LENGTH: usize = 4;
+
+/// The shape of a keyboard walk.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WalkPattern {
+    /// All the keys of the walk are on the same row, as in `qwerty`.
+    Row,
+
+    /// The walk moves across rows, as in `1qaz` or `zaqwsx`.
+    Zigzag,
+}
+
+/// A keyboard walk found in a password.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyboardWalk {
+    /// The characters forming the walk.
+    pub token: String,
+
+    /// The index, in characters, of the walk's first character in the password.
+    pub start: usize,
+
+    /// The shape of the walk.
+    pub pattern: WalkPattern,
+}
+
+/// Detects the keyboard walks of at least `MIN_WALK_LENGTH` keys found in `password`.
+///
+/// Walks are detected on a US QWERTY layout, regardless of the shift state of each key.
+///
+/// # Examples
+///
+/// ```
+/// use motus::analysis::walk::{detect, WalkPattern};
+///
+/// let walks = detect("Qwerty1qaz!");
+/// assert_eq!(walks.len(), 2);
+/// assert_eq!(walks[0].token, "Qwerty");
+/// assert_eq!(walks[0].pattern, WalkPattern::Row);
+/// assert_eq!(walks[1].token, "1qaz");
+/// assert_eq!(walks[1].pattern, WalkPattern::Zigzag);
+/// ```
+#[must_use]
+pub fn detect(password: &str) -> Vec<KeyboardWalk> {
+    let chars: Vec<char> = password.chars().collect();
+    let keys: Vec<Option<Key>> = chars.iter().map(|&c| keyboard::locate(c)).collect();
+
+    let mut walks = Vec::new();
+    let mut start = 0;
+
+    for end in 1..=chars.len() {
+        let continues = end < chars.len()
+            && matches!(
+                (keys[end - 1], keys[end]),
+                (Some(previous), Some(current)) if previous.is_adjacent(&current)
+            );
+
+        if !continues {
+            if end - start >= MIN_WALK_LENGTH {
+                let row = keys[start].map(|key| key.row);
+                let pattern = if keys[start..end].iter().all(|key| key.map(|k| k.row) == row) {
+                    WalkPattern::Row
+                } else {
+                    WalkPattern::Zigzag
+                };
+
+                walks.push(KeyboardWalk {
+                    token: chars[start..end].iter().collect(),
+                    start,
+                    pattern,
+                });
+            }
+            start = end;
+        }
+    }
+
+    walks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_row_walks() {
+        let walks = detect("xxasdfghxx");
+        assert_eq!(
+            walks,
+            vec![KeyboardWalk {
+                token: "asdfgh".to_string(),
+                start: 2,
+                pattern: WalkPattern::Row,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_detect_reversed_walks() {
+        let walks = detect("ytrewq");
+        assert_eq!(walks.len(), 1);
+        assert_eq!(walks[0].pattern, WalkPattern::Row);
+    }
+
+    #[test]
+    fn test_detect_zigzag_walks() {
+        let walks = detect("zaqwsx");
+        assert_eq!(walks.len(), 1);
+        assert_eq!(walks[0].token, "zaqwsx");
+        assert_eq!(walks[0].pattern, WalkPattern::Zigzag);
+    }
+
+    #[test]
+    fn test_detect_ignores_short_sequences() {
+        assert!(detect("qwe").is_empty());
+        assert!(detect("chokehold nativity").is_empty());
+    }
+}
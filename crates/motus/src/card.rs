@@ -0,0 +1,119 @@
+//! Password cards.
+//!
+//! A password card is a printable grid of random characters, with lettered columns and numbered
+//! rows. Rather than remembering a password, its owner remembers a starting cell and a path
+//! through the grid, such as "row 3 from column F, eight characters to the right", and reads the
+//! password off the card. The card alone does not tell which characters form the password, and
+//! the path alone is useless without the card.
+
+use rand::Rng;
+
+use crate::policy::Policy;
+
+/// The maximum number of columns of a card, one per letter of the alphabet.
+pub const MAX_COLUMNS: usize = 26;
+
+/// A grid of random characters.
+///
+/// # Examples
+///
+/// ```
+/// use motus::card::Card;
+/// use motus::policy::Policy;
+/// use rand::thread_rng;
+///
+/// let card = Card::generate(&mut thread_rng(), 8, 26, true, false, &Policy::new());
+///
+/// assert_eq!(card.rows().len(), 8);
+/// assert_eq!(card.column_labels().len(), 26);
+/// assert_eq!(card.read(0, 'A', 4).map(|password| password.len()), Some(4));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Card {
+    rows: Vec<String>,
+}
+
+impl Card {
+    /// Generates a card of `rows` rows of `columns` random characters each.
+    ///
+    /// Each row is generated as a random password complying with `policy`, with the same
+    /// `numbers` and `symbols` options as [`crate::random_password`]. The number of columns is
+    /// capped to [`MAX_COLUMNS`].
+    #[must_use]
+    pub fn generate<R: Rng>(
+        rng: &mut R,
+        rows: usize,
+        columns: usize,
+        numbers: bool,
+        symbols: bool,
+        policy: &Policy,
+    ) -> Self {
+        let columns = u32::try_from(columns.min(MAX_COLUMNS)).unwrap_or_default();
+        let rows = (0..rows)
+            .map(|_| policy.random_password(rng, columns, numbers, symbols, &[]))
+            .collect();
+
+        Self { rows }
+    }
+
+    /// Returns the rows of the card, from top to bottom.
+    #[must_use]
+    pub fn rows(&self) -> &[String] {
+        &self.rows
+    }
+
+    /// Returns the labels of the columns of the card, from left to right.
+    #[must_use]
+    pub fn column_labels(&self) -> Vec<char> {
+        let columns = self.rows.first().map_or(0, |row| row.chars().count());
+        ('A'..='Z').take(columns).collect()
+    }
+
+    /// Reads `length` characters off the card, from left to right, starting at the given
+    /// 0-based row and labelled column, and wrapping around to the start of the row.
+    ///
+    /// Returns None if the row or the column is not on the card.
+    #[must_use]
+    pub fn read(&self, row: usize, column: char, length: usize) -> Option<String> {
+        let chars: Vec<char> = self.rows.get(row)?.chars().collect();
+        let start = self
+            .column_labels()
+            .iter()
+            .position(|&label| label == column.to_ascii_uppercase())?;
+
+        Some(chars.iter().cycle().skip(start).take(length).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let card = Card::generate(&mut rng, 4, 40, true, true, &Policy::new());
+
+        assert_eq!(card.rows().len(), 4);
+        assert!(card
+            .rows()
+            .iter()
+            .all(|row| row.chars().count() == MAX_COLUMNS));
+        assert_eq!(card.column_labels().first(), Some(&'A'));
+        assert_eq!(card.column_labels().last(), Some(&'Z'));
+    }
+
+    #[test]
+    fn test_read() {
+        let card = Card {
+            rows: vec!["abcdef".to_string(), "ghijkl".to_string()],
+        };
+
+        assert_eq!(card.read(1, 'B', 3), Some("hij".to_string()));
+        assert_eq!(card.read(0, 'e', 4), Some("efab".to_string()));
+        assert_eq!(card.read(2, 'A', 3), None);
+        assert_eq!(card.read(0, 'G', 3), None);
+    }
+}
@@ -0,0 +1,232 @@
+//! Character sets.
+//!
+//! A [`Charset`] is a set of characters passwords are drawn from. The sets motus uses for its own
+//! random passwords are available as [`Charset::letters`], [`Charset::numbers`] and
+//! [`Charset::symbols`], and sets compose with `|` for their union and `-` for their difference,
+//! so that precise pools can be built from them and from custom sets:
+//!
+//! ```
+//! use motus::charset::Charset;
+//!
+//! let pool = &(&Charset::letters() | &Charset::numbers()) - &Charset::custom("O0Il1".chars());
+//! assert_eq!(pool.len(), 62 - 5);
+//! assert!(!pool.contains('0'));
+//! ```
+
+use std::ops::{BitOr, Sub};
+
+use clap::ValueEnum;
+
+use crate::{INTL_SAFE_SYMBOL_CHARS, LETTER_CHARS, NUMBER_CHARS, SYMBOL_CHARS};
+
+/// An ordered set of characters, without duplicates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Charset {
+    chars: Vec<char>,
+}
+
+impl Charset {
+    /// Returns the lowercase and uppercase ASCII letters.
+    #[must_use]
+    pub fn letters() -> Self {
+        Self::custom(LETTER_CHARS.iter().copied())
+    }
+
+    /// Returns the lowercase ASCII letters.
+    #[must_use]
+    pub fn lowercase() -> Self {
+        Self::custom(LETTER_CHARS[..26].iter().copied())
+    }
+
+    /// Returns the uppercase ASCII letters.
+    #[must_use]
+    pub fn uppercase() -> Self {
+        Self::custom(LETTER_CHARS[26..].iter().copied())
+    }
+
+    /// Returns the decimal digits.
+    #[must_use]
+    pub fn numbers() -> Self {
+        Self::custom(NUMBER_CHARS.iter().copied())
+    }
+
+    /// Returns the symbols random passwords include, `!@#$%^&*()`.
+    #[must_use]
+    pub fn symbols() -> Self {
+        Self::custom(SYMBOL_CHARS.iter().copied())
+    }
+
+    /// Returns the symbols found on every major Latin keyboard layout, `!%()*+,-./:;=?_`.
+    ///
+    /// Each of them is typed with at most Shift, without the Alt Gr key nor dead keys, on the US, UK,
+    /// French, German, Spanish, Italian, Portuguese and Nordic layouts, and none of them looks
+    /// like another, for passwords typed on unfamiliar keyboards such as hotel or kiosk ones.
+    #[must_use]
+    pub fn intl_safe_symbols() -> Self {
+        Self::custom(INTL_SAFE_SYMBOL_CHARS.iter().copied())
+    }
+
+    /// Creates a set of the given characters. Duplicates are only kept once, in the order they
+    /// first appear.
+    pub fn custom(chars: impl IntoIterator<Item = char>) -> Self {
+        let mut set = Self::default();
+        for c in chars {
+            if !set.contains(c) {
+                set.chars.push(c);
+            }
+        }
+        set
+    }
+
+    /// Returns the characters of both sets: the ones of this set, followed by the ones of `other`
+    /// it lacks.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        Self::custom(self.chars.iter().chain(&other.chars).copied())
+    }
+
+    /// Returns the characters of this set that are not in `other`.
+    #[must_use]
+    pub fn minus(&self, other: &Self) -> Self {
+        Self {
+            chars: self
+                .chars
+                .iter()
+                .filter(|&&c| !other.contains(c))
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// Returns the characters of the set, in order.
+    #[must_use]
+    pub fn chars(&self) -> &[char] {
+        &self.chars
+    }
+
+    /// Returns the number of characters of the set.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    /// Returns whether the set has no characters.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Returns whether `c` belongs to the set.
+    #[must_use]
+    pub fn contains(&self, c: char) -> bool {
+        self.chars.contains(&c)
+    }
+}
+
+/// The symbols random passwords draw from.
+///
+/// The documentation of the variants is the help of the `--symbols-set` command-line option, and
+/// is kept plain text.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum SymbolSet {
+    /// The symbols !@#$%^&*().
+    #[default]
+    Default,
+    /// The symbols !%()*+,-./:;=?_, typed alike on international keyboards.
+    IntlSafe,
+}
+
+impl SymbolSet {
+    /// Returns the symbols of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use motus::charset::SymbolSet;
+    ///
+    /// assert!(SymbolSet::Default.charset().contains('^'));
+    /// assert!(!SymbolSet::IntlSafe.charset().contains('^'));
+    /// ```
+    #[must_use]
+    pub fn charset(self) -> Charset {
+        match self {
+            Self::Default => Charset::symbols(),
+            Self::IntlSafe => Charset::intl_safe_symbols(),
+        }
+    }
+}
+
+impl From<&str> for Charset {
+    fn from(chars: &str) -> Self {
+        Self::custom(chars.chars())
+    }
+}
+
+impl FromIterator<char> for Charset {
+    fn from_iter<I: IntoIterator<Item = char>>(chars: I) -> Self {
+        Self::custom(chars)
+    }
+}
+
+impl BitOr for &Charset {
+    type Output = Charset;
+
+    fn bitor(self, other: Self) -> Charset {
+        self.union(other)
+    }
+}
+
+impl BitOr for Charset {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(&other)
+    }
+}
+
+impl Sub for &Charset {
+    type Output = Charset;
+
+    fn sub(self, other: Self) -> Charset {
+        self.minus(other)
+    }
+}
+
+impl Sub for Charset {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self.minus(&other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_sets() {
+        assert_eq!(Charset::letters().len(), 52);
+        assert_eq!(
+            Charset::lowercase() | Charset::uppercase(),
+            Charset::letters()
+        );
+        assert_eq!(Charset::numbers().chars(), NUMBER_CHARS);
+        assert_eq!(Charset::symbols().chars(), SYMBOL_CHARS);
+    }
+
+    #[test]
+    fn test_custom_deduplicates() {
+        assert_eq!(Charset::from("abca").chars(), &['a', 'b', 'c']);
+    }
+
+    #[test]
+    fn test_union_and_minus() {
+        let set = Charset::from("abc") | Charset::from("cd");
+        assert_eq!(set.chars(), &['a', 'b', 'c', 'd']);
+
+        let set = set - Charset::from("bx");
+        assert_eq!(set.chars(), &['a', 'c', 'd']);
+        assert!((Charset::numbers() - Charset::numbers()).is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+//! Verification words, detecting mistyped memorable passwords.
+//!
+//! Like the checksum of BIP-39 mnemonics, a verification word is derived from the other words of
+//! a passphrase and appended to it. It adds no entropy, as anyone knowing the other words knows
+//! it too, but a passphrase retyped with a wrong word is told apart from the right one with a
+//! probability of about 1 - 1/n, n being the number of words verification words are drawn from.
+//!
+//! Verification words are derived from the letters of the passphrase alone, regardless of their
+//! case, so that they do not depend on the separators or the capitalization of the words, nor on
+//! the random numbers and symbols separating them.
+
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+
+use rand::Rng;
+
+use crate::wordlist::is_family_friendly;
+use crate::{separator_string, JoinStyle, WORDS_LIST};
+
+// CANDIDATES are the words verification words are drawn from: the family-friendly words of the
+// wordlist made of letters only, so that verification words can be told apart from the
+// passphrase by their letters.
+static CANDIDATES: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
+    WORDS_LIST
+        .iter()
+        .copied()
+        .filter(|word| word.chars().all(|c| c.is_ascii_alphabetic()))
+        .filter(|word| is_family_friendly(&[word]))
+        .collect()
+});
+
+/// Returns the verification word of a passphrase.
+///
+/// The word is drawn from the 64-bit FNV-1a hash of the lowercase letters of `phrase`, every
+/// other character being ignored.
+///
+/// # Example
+///
+/// ```
+/// use motus::checksum::checksum_word;
+///
+/// let word = checksum_word("choking natural dolly");
+/// assert_eq!(checksum_word("Choking-Natural-Dolly"), word);
+/// assert_eq!(checksum_word("choking4natural!dolly"), word);
+/// ```
+#[must_use]
+pub fn checksum_word(phrase: &str) -> &'static str {
+    checksum_of(letters(phrase))
+}
+
+/// Appends the verification word of `password`, a memorable password of `words` words, to it.
+///
+/// The verification word is cased and joined to the password in the style of its other words,
+/// random separators drawing `separator_count` characters from `rng`.
+///
+/// # Example
+///
+/// ```
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+/// use motus::checksum::{verify, with_checksum_word};
+/// use motus::JoinStyle;
+///
+/// let mut rng = StdRng::seed_from_u64(42);
+/// let password = with_checksum_word(&mut rng, "chokingNaturalDolly", JoinStyle::Camel, 3, 1);
+/// assert!(password.starts_with("chokingNaturalDolly"));
+/// assert!(verify(&password).is_ok());
+/// ```
+pub fn with_checksum_word<R: Rng>(
+    rng: &mut R,
+    password: &str,
+    style: JoinStyle,
+    words: usize,
+    separator_count: usize,
+) -> String {
+    let mut word = checksum_word(password).to_string();
+    if style.capitalizes(words) {
+        word[0..1].make_ascii_uppercase();
+    }
+
+    let separator = separator_string(rng, style.separator(), separator_count);
+    format!("{password}{separator}{word}")
+}
+
+/// Verifies that a passphrase ends with the verification word of its other words.
+///
+/// As verification words only depend on letters, the passphrase can be typed with any
+/// separators and capitalization, even joined without separators. On success, returns the
+/// verification word.
+///
+/// # Errors
+///
+/// Returns [`ChecksumError::Empty`] if `phrase` holds no letter, and [`ChecksumError::Mismatch`]
+/// if it does not end with the verification word of its other words.
+///
+/// # Example
+///
+/// ```
+/// use motus::checksum::{checksum_word, verify, ChecksumError};
+///
+/// let phrase = format!("choking natural dolly {}", checksum_word("choking natural dolly"));
+/// assert!(verify(&phrase).is_ok());
+/// assert_eq!(verify("choking natural dolly"), Err(ChecksumError::Mismatch));
+/// ```
+pub fn verify(phrase: &str) -> Result<&'static str, ChecksumError> {
+    let letters: Vec<u8> = letters(phrase).collect();
+    if letters.is_empty() {
+        return Err(ChecksumError::Empty);
+    }
+
+    // The verification word cannot be told apart from the previous word by its letters alone,
+    // so every candidate the passphrase ends with is tried
+    CANDIDATES
+        .iter()
+        .copied()
+        .filter(|word| word.len() < letters.len() && letters.ends_with(word.as_bytes()))
+        .find(|word| checksum_of(letters[..letters.len() - word.len()].iter().copied()) == *word)
+        .ok_or(ChecksumError::Mismatch)
+}
+
+// checksum_of returns the verification word of the given lowercase letters.
+#[allow(clippy::cast_possible_truncation)] // the remainder is below the number of candidates
+fn checksum_of(letters: impl Iterator<Item = u8>) -> &'static str {
+    let hash = letters.fold(0xcbf2_9ce4_8422_2325, |hash: u64, letter| {
+        (hash ^ u64::from(letter)).wrapping_mul(0x0100_0000_01b3)
+    });
+    CANDIDATES[(hash % CANDIDATES.len() as u64) as usize]
+}
+
+// letters returns the lowercase ASCII letters of `phrase`, in order.
+fn letters(phrase: &str) -> impl Iterator<Item = u8> + '_ {
+    phrase
+        .bytes()
+        .filter(u8::is_ascii_alphabetic)
+        .map(|letter| letter.to_ascii_lowercase())
+}
+
+/// The reasons a passphrase fails verification.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChecksumError {
+    /// The passphrase holds no letter.
+    Empty,
+    /// The passphrase does not end with the verification word of its other words.
+    Mismatch,
+}
+
+impl Display for ChecksumError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Empty => write!(f, "the passphrase holds no word"),
+            Self::Mismatch => write!(
+                f,
+                "the last word is not the verification word of the others: a word is likely mistyped"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ChecksumError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{memorable_password_with_style, Separator};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_checksum_word_ignores_separators_and_case() {
+        let word = checksum_word("choking natural dolly");
+
+        assert!(CANDIDATES.contains(&word));
+        assert_eq!(checksum_word("CHOKING_NATURAL_DOLLY"), word);
+        assert_eq!(checksum_word("choking12natural&7dolly"), word);
+        assert_ne!(checksum_word("choking natural dollies"), word);
+    }
+
+    #[test]
+    fn test_verify_generated_passwords() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let styles = [
+            JoinStyle::Separated {
+                separator: Separator::NumbersAndSymbols,
+                capitalize: true,
+            },
+            JoinStyle::Separated {
+                separator: Separator::None,
+                capitalize: false,
+            },
+            JoinStyle::Camel,
+            JoinStyle::Kebab,
+        ];
+
+        for style in styles {
+            for words in 1..=6 {
+                let password = memorable_password_with_style(&mut rng, words, style, 2, false);
+                let password = with_checksum_word(&mut rng, &password, style, words, 2);
+                assert!(verify(&password).is_ok(), "{password} should verify");
+            }
+        }
+    }
+
+    #[test]
+    fn test_verify_detects_mistyped_words() {
+        let phrase = format!(
+            "choking natural dolly {}",
+            checksum_word("choking natural dolly")
+        );
+
+        assert_eq!(verify(&phrase), Ok(checksum_word("choking natural dolly")));
+        assert_eq!(
+            verify(&phrase.replace("natural", "neutral")),
+            Err(ChecksumError::Mismatch)
+        );
+        assert_eq!(verify(" - 42 "), Err(ChecksumError::Empty));
+    }
+}
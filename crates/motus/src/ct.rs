@@ -0,0 +1,73 @@
+//! Constant-time comparison of secrets.
+//!
+//! Comparing a secret with `==` returns as soon as the first differing byte is found, so the time
+//! it takes tells how much of a guess was right. The functions of this module look at every byte
+//! of their inputs, whatever their contents, and only their lengths may leak through timing.
+//!
+//! Every comparison involving a password or a token should go through them: checking a retyped
+//! password, a share token, or whether a password is already stored.
+
+use std::hint::black_box;
+
+/// Returns whether `a` and `b` hold the same bytes, in a time that only depends on their lengths.
+///
+/// # Examples
+///
+/// ```
+/// use motus::ct;
+///
+/// assert!(ct::eq(b"t0k3n", b"t0k3n"));
+/// assert!(!ct::eq(b"t0k3n", b"t0k3m"));
+/// assert!(!ct::eq(b"t0k3n", b"t0k3"));
+/// ```
+#[must_use]
+pub fn eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    // black_box keeps the compiler from turning the fold into an early-returning loop
+    let diff = a
+        .iter()
+        .zip(b)
+        .fold(0u8, |diff, (x, y)| black_box(diff | (x ^ y)));
+    black_box(diff) == 0
+}
+
+/// Returns whether the strings `a` and `b` are the same, in a time that only depends on their
+/// lengths.
+///
+/// # Examples
+///
+/// ```
+/// use motus::ct;
+///
+/// assert!(ct::str_eq("choking natural dolly", "choking natural dolly"));
+/// assert!(!ct::str_eq("choking natural dolly", "choking natsral dolly"));
+/// ```
+#[must_use]
+pub fn str_eq(a: &str, b: &str) -> bool {
+    eq(a.as_bytes(), b.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eq() {
+        assert!(eq(b"", b""));
+        assert!(eq(b"t0k3n", b"t0k3n"));
+        assert!(!eq(b"t0k3n", b"t0k3m"));
+        assert!(!eq(b"t0k3n", b"u0k3n"));
+        assert!(!eq(b"t0k3n", b"t0k3"));
+        assert!(!eq(b"", b"t0k3n"));
+    }
+
+    #[test]
+    fn test_str_eq_compares_bytes() {
+        assert!(str_eq("mot de passe é", "mot de passe é"));
+        // Same characters, differently normalized
+        assert!(!str_eq("é", "e\u{301}"));
+    }
+}
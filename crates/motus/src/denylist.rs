@@ -0,0 +1,147 @@
+//! Terms generated passwords must not contain, such as company-specific words, product names or
+//! usernames.
+//!
+//! A [`DenyList`] is a hard constraint on generation: passwords are drawn again until they hold
+//! none of its terms, which [`DenyList::resample`] takes care of. The terms are also the words an
+//! attacker targeting an organization would try first, and make up the user dictionary of the
+//! security analysis.
+
+use std::collections::BTreeSet;
+
+/// How many passwords [`DenyList::resample`] draws at most before giving up.
+pub const MAX_ATTEMPTS: usize = 1000;
+
+/// Terms passwords must not contain, compared regardless of case.
+///
+/// # Examples
+///
+/// ```
+/// use motus::denylist::DenyList;
+///
+/// let deny_list = DenyList::parse("# Products\nRocketship\nacme\n");
+///
+/// assert_eq!(deny_list.terms(), ["acme", "rocketship"]);
+/// assert_eq!(deny_list.find("ACME-r0cket"), Some("acme"));
+/// assert!(deny_list.allows("choking natural dolly"));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DenyList {
+    terms: Vec<String>,
+}
+
+impl DenyList {
+    /// Creates a deny list of the given terms, lowercased and trimmed. Blank terms are ignored.
+    #[must_use]
+    pub fn new<I, S>(terms: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let terms: BTreeSet<String> = terms
+            .into_iter()
+            .map(|term| term.as_ref().trim().to_lowercase())
+            .filter(|term| !term.is_empty())
+            .collect();
+
+        Self {
+            terms: terms.into_iter().collect(),
+        }
+    }
+
+    /// Parses a deny list file, holding one term per line. Lines starting with `#` are comments.
+    #[must_use]
+    pub fn parse(content: &str) -> Self {
+        Self::new(
+            content
+                .lines()
+                .filter(|line| !line.trim_start().starts_with('#')),
+        )
+    }
+
+    /// Returns the terms of the deny list, lowercased and sorted.
+    #[must_use]
+    pub fn terms(&self) -> &[String] {
+        &self.terms
+    }
+
+    /// Returns whether the deny list holds no term, and allows every password.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// Returns the first term, in alphabetical order, the password contains, if any.
+    #[must_use]
+    pub fn find(&self, password: &str) -> Option<&str> {
+        if self.terms.is_empty() {
+            return None;
+        }
+
+        let password = password.to_lowercase();
+        self.terms
+            .iter()
+            .find(|term| password.contains(term.as_str()))
+            .map(String::as_str)
+    }
+
+    /// Returns whether the password contains none of the terms.
+    #[must_use]
+    pub fn allows(&self, password: &str) -> bool {
+        self.find(password).is_none()
+    }
+
+    /// Calls `generate` until it returns a password containing none of the terms, and returns
+    /// it, or None after [`MAX_ATTEMPTS`] attempts, when the terms can hardly be avoided.
+    ///
+    /// Without any term, `generate` is called once, so that seeded generators keep generating the
+    /// same passwords. Passwords holding a term only make up a small share of the possible ones,
+    /// and their absence is not accounted for in the entropy.
+    pub fn resample(&self, mut generate: impl FnMut() -> String) -> Option<String> {
+        (0..MAX_ATTEMPTS)
+            .map(|_| generate())
+            .find(|password| self.allows(password))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_deny_list_terms() {
+        let deny_list = DenyList::new(["  Acme ", "", "acme", "Zorglub", "bob"]);
+        assert_eq!(deny_list.terms(), ["acme", "bob", "zorglub"]);
+        assert!(!deny_list.is_empty());
+        assert!(DenyList::parse("# nothing\n\n").is_empty());
+    }
+
+    #[test]
+    fn test_deny_list_find() {
+        let deny_list = DenyList::new(["acme", "bob"]);
+        assert_eq!(deny_list.find("Bobby-ACME"), Some("acme"));
+        assert_eq!(deny_list.find("xBOBx"), Some("bob"));
+        assert_eq!(deny_list.find("a-c-m-e"), None);
+        assert!(DenyList::default().allows("acme"));
+    }
+
+    #[test]
+    fn test_deny_list_resample() {
+        let deny_list = DenyList::new(["a"]);
+        let mut rng = StdRng::seed_from_u64(42);
+        let password = deny_list
+            .resample(|| crate::code_password(&mut rng, 4, crate::Radix::Hex.alphabet()))
+            .expect("4 hexadecimal digits should avoid 'a' often enough");
+        assert!(!password.contains('a'));
+
+        let mut calls = 0;
+        let password = DenyList::default().resample(|| {
+            calls += 1;
+            "acme".to_string()
+        });
+        assert_eq!((password.as_deref(), calls), (Some("acme"), 1));
+
+        assert_eq!(DenyList::new(["e"]).resample(|| "e".to_string()), None);
+    }
+}
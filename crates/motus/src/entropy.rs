@@ -0,0 +1,428 @@
+//! Theoretical entropy of generated passwords.
+//!
+//! Unlike strength estimators such as zxcvbn, which guess how an attacker would crack a given
+//! password, the entropy computed here derives from the generator's settings: it measures how
+//! many passwords the generator could have produced, assuming the attacker knows exactly how it
+//! was configured. It is broken down by source, so that one can tell how much each setting
+//! contributes to the overall strength.
+
+use std::fmt::{Display, Formatter};
+
+use crate::charset::{Charset, SymbolSet};
+use crate::pattern::{APPLE_CHUNKS, APPLE_CHUNK_LEN};
+use crate::wordlist::lint::lint;
+use crate::{random_charsets, Separator, NUMBER_CHARS, SYMBOL_CHARS, WORDS_LIST};
+
+/// The part of a generated password some entropy comes from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EntropySource {
+    /// The random choice of words in a memorable password.
+    Words,
+    /// The random order of the letters in scrambled words.
+    Scrambling,
+    /// The random characters inserted between words in a memorable password.
+    Separators,
+    /// The random characters of a random password.
+    Characters,
+    /// The random digits of a PIN or code.
+    Digits,
+    /// The random placement of the required characters in a pattern, such as the uppercase letter
+    /// and digit of Apple-style passwords.
+    Layout,
+    /// Static text prepended to the password.
+    Prefix,
+    /// Static text appended to the password.
+    Suffix,
+}
+
+impl Display for EntropySource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let source = match self {
+            Self::Words => "words",
+            Self::Scrambling => "scrambling",
+            Self::Separators => "separators",
+            Self::Characters => "characters",
+            Self::Digits => "digits",
+            Self::Layout => "layout",
+            Self::Prefix => "static prefix",
+            Self::Suffix => "static suffix",
+        };
+
+        write!(f, "{source}")
+    }
+}
+
+/// The entropy, in bits, contributed by a single source.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EntropyComponent {
+    pub source: EntropySource,
+    pub bits: f64,
+}
+
+/// The theoretical entropy of a generated password, broken down by source.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Entropy {
+    components: Vec<EntropyComponent>,
+    ambiguous: bool,
+}
+
+impl Entropy {
+    /// Returns the total entropy, in bits.
+    #[must_use]
+    pub fn bits(&self) -> f64 {
+        self.components.iter().map(|c| c.bits).sum()
+    }
+
+    /// Returns the entropy contributed by each source.
+    #[must_use]
+    pub fn components(&self) -> &[EntropyComponent] {
+        &self.components
+    }
+
+    /// Records a static part of the password, such as a prefix, in the breakdown.
+    ///
+    /// Static text is assumed to be known to an attacker, and thus contributes no entropy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use motus::entropy::{pin, EntropySource};
+    ///
+    /// let entropy = pin(4).with_static(EntropySource::Prefix);
+    /// assert_eq!(entropy.components().len(), 2);
+    /// assert!((entropy.bits() - pin(4).bits()).abs() < f64::EPSILON);
+    /// ```
+    #[must_use]
+    pub fn with_static(mut self, source: EntropySource) -> Self {
+        self.push(source, 0.0);
+        self
+    }
+
+    /// Marks the password as ambiguous: several choices of words may have produced it, so that
+    /// it holds less entropy than [`Entropy::bits`] reports, see [`joins_ambiguously`].
+    #[must_use]
+    pub const fn with_ambiguity(mut self) -> Self {
+        self.ambiguous = true;
+        self
+    }
+
+    /// Returns whether the password is ambiguous, and holds less entropy than reported.
+    #[must_use]
+    pub const fn is_ambiguous(&self) -> bool {
+        self.ambiguous
+    }
+
+    pub(crate) fn push(&mut self, source: EntropySource, bits: f64) {
+        self.components.push(EntropyComponent { source, bits });
+    }
+}
+
+/// Computes the entropy of a password generated by [`crate::memorable_password`] with the same
+/// options.
+///
+/// Words are drawn without replacement. Capitalization is applied to every word, thus adds no
+/// entropy, and is not an input. Fixed separators such as spaces add no entropy either, whereas
+/// the `separator_count` random characters inserted between words by `Separator::Numbers` and
+/// `Separator::NumbersAndSymbols` do, and are reported as a separate component.
+///
+/// # Examples
+///
+/// ```
+/// use motus::entropy::{memorable, EntropySource};
+/// use motus::Separator;
+///
+/// let spaced = memorable(5, Separator::Space, 1, false);
+/// let numbered = memorable(5, Separator::Numbers, 1, false);
+///
+/// // Four random digits are inserted between the five words
+/// let separators = numbered.components()[1];
+/// assert_eq!(separators.source, EntropySource::Separators);
+/// assert!((numbered.bits() - spaced.bits() - 4.0 * 10f64.log2()).abs() < 1e-9);
+/// ```
+#[must_use]
+pub fn memorable(
+    word_count: usize,
+    separator: Separator,
+    separator_count: usize,
+    scramble: bool,
+) -> Entropy {
+    words_memorable(
+        WORDS_LIST.len(),
+        word_count,
+        separator,
+        separator_count,
+        scramble,
+    )
+}
+
+// words_memorable computes the entropy of a memorable password whose words are drawn from a
+// list of `words` words rather than from the whole wordlist.
+#[allow(clippy::cast_precision_loss)] // word counts are way below f64's precision limits
+pub(crate) fn words_memorable(
+    words: usize,
+    word_count: usize,
+    separator: Separator,
+    separator_count: usize,
+    scramble: bool,
+) -> Entropy {
+    let mut entropy = Entropy::default();
+
+    let words_bits = (0..word_count.min(words))
+        .map(|i| ((words - i) as f64).log2())
+        .sum();
+    entropy.push(EntropySource::Words, words_bits);
+
+    if scramble {
+        entropy.push(
+            EntropySource::Scrambling,
+            word_count as f64 * mean_scrambling_bits(),
+        );
+    }
+
+    let separator_choices = match separator {
+        Separator::Numbers => NUMBER_CHARS.len(),
+        Separator::NumbersAndSymbols => NUMBER_CHARS.len() + SYMBOL_CHARS.len(),
+        _ => 1,
+    };
+    let separator_count = word_count.saturating_sub(1) * separator_count;
+    entropy.push(
+        EntropySource::Separators,
+        separator_count as f64 * (separator_choices as f64).log2(),
+    );
+
+    entropy
+}
+
+/// Returns whether memorable passwords made of the given words can be read back in several ways.
+///
+/// Several choices of words then produce the same password, which holds less entropy than
+/// [`memorable`] credits it with.
+///
+/// Only words joined with `Separator::None` can be ambiguous: when a word is a prefix of another,
+/// such as "tree" and "treetop", "treetopaz" may be read as "tree topaz" or "treetop az". A
+/// prefix-free wordlist, such as the embedded one, rules it out, unless the words are scrambled.
+/// Capitalizing the words marks where each one starts, which rules it out in any case.
+///
+/// # Examples
+///
+/// ```
+/// use motus::entropy::joins_ambiguously;
+/// use motus::Separator;
+///
+/// let words = ["tree", "treetop", "topaz"];
+///
+/// assert!(joins_ambiguously(&words, Separator::None, false, false));
+/// assert!(!joins_ambiguously(&words, Separator::None, true, false));
+/// assert!(!joins_ambiguously(&words, Separator::Hyphen, false, false));
+/// assert!(!joins_ambiguously(&["tree", "topaz"], Separator::None, false, false));
+/// ```
+#[must_use]
+pub fn joins_ambiguously(
+    words: &[&str],
+    separator: Separator,
+    capitalize: bool,
+    scramble: bool,
+) -> bool {
+    separator == Separator::None
+        && !capitalize
+        && (scramble || !lint(words.iter().copied()).is_prefix_free())
+}
+
+/// Computes the entropy of a password generated by [`crate::random_password`] with the same
+/// options.
+///
+/// Each character is drawn by first picking a character set according to its weight, then a
+/// character from that set, so the entropy per character is the entropy of the set choice plus
+/// the expected entropy of the character choice. Excluded characters shrink the sets accordingly.
+///
+/// # Examples
+///
+/// ```
+/// use motus::entropy::random;
+///
+/// let entropy = random(20, false, false, &[]);
+/// assert!((entropy.bits() - 20.0 * 52f64.log2()).abs() < 1e-9);
+/// ```
+#[must_use]
+pub fn random(characters: u32, numbers: bool, symbols: bool, excluded: &[char]) -> Entropy {
+    charsets(
+        characters,
+        &random_charsets(numbers, symbols, SymbolSet::Default, excluded),
+    )
+}
+
+/// Computes the entropy of a password generated by [`crate::charset_password`] with the same
+/// options.
+///
+/// # Examples
+///
+/// ```
+/// use motus::charset::Charset;
+/// use motus::entropy::charsets;
+///
+/// let entropy = charsets(10, &[(Charset::numbers(), 1), (Charset::symbols(), 1)]);
+/// assert!((entropy.bits() - 10.0 * (1.0 + 10f64.log2())).abs() < 1e-9);
+/// ```
+#[must_use]
+#[allow(clippy::cast_precision_loss)] // set sizes are way below f64's precision limits
+pub fn charsets(characters: u32, sets: &[(Charset, u32)]) -> Entropy {
+    let sets: Vec<&(Charset, u32)> = sets
+        .iter()
+        .filter(|(set, weight)| !set.is_empty() && *weight > 0)
+        .collect();
+    let total_weight: u32 = sets.iter().map(|(_, weight)| weight).sum();
+
+    let per_character: f64 = sets
+        .iter()
+        .map(|(set, weight)| {
+            let p = f64::from(*weight) / f64::from(total_weight);
+            p.mul_add((set.len() as f64).log2(), -p * p.log2())
+        })
+        .sum();
+
+    let mut entropy = Entropy::default();
+    entropy.push(
+        EntropySource::Characters,
+        f64::from(characters) * per_character,
+    );
+    entropy
+}
+
+/// Computes the entropy of a PIN generated by [`crate::pin_password`] with the same options.
+///
+/// # Examples
+///
+/// ```
+/// use motus::entropy::pin;
+///
+/// assert!((pin(4).bits() - 4.0 * 10f64.log2()).abs() < 1e-9);
+/// ```
+#[must_use]
+pub fn pin(numbers: u32) -> Entropy {
+    code(numbers, NUMBER_CHARS)
+}
+
+/// Computes the entropy of a password generated by [`crate::apple_password`].
+///
+/// On top of the random characters, the positions of the uppercase letter and of the digit among
+/// the letters are random too, and are reported as a separate layout component.
+///
+/// # Examples
+///
+/// ```
+/// use motus::entropy::apple;
+///
+/// assert!(apple().bits() > 70.0);
+/// ```
+#[must_use]
+#[allow(clippy::cast_precision_loss)] // password lengths are way below f64's precision limits
+pub fn apple() -> Entropy {
+    let letters = APPLE_CHUNKS * APPLE_CHUNK_LEN;
+    let lowercase_bits = 26f64.log2();
+
+    let mut entropy = Entropy::default();
+    entropy.push(
+        EntropySource::Characters,
+        ((letters - 1) as f64).mul_add(lowercase_bits, (NUMBER_CHARS.len() as f64).log2()),
+    );
+    entropy.push(
+        EntropySource::Layout,
+        (letters as f64 * (letters - 1) as f64).log2(),
+    );
+    entropy
+}
+
+/// Computes the entropy of a code generated by [`crate::code_password`] with the same options.
+///
+/// # Examples
+///
+/// ```
+/// use motus::entropy::code;
+/// use motus::Radix;
+///
+/// // Each hexadecimal digit carries 4 bits
+/// assert!((code(6, Radix::Hex.alphabet()).bits() - 24.0).abs() < 1e-9);
+/// ```
+#[must_use]
+#[allow(clippy::cast_precision_loss)] // alphabet sizes are way below f64's precision limits
+pub fn code(length: u32, alphabet: &[char]) -> Entropy {
+    let mut entropy = Entropy::default();
+    entropy.push(
+        EntropySource::Digits,
+        f64::from(length) * (alphabet.len() as f64).log2(),
+    );
+    entropy
+}
+
+// mean_scrambling_bits returns the average entropy, in bits, added by shuffling the letters of a
+// word from the list, accounting for repeated letters which yield identical arrangements.
+#[allow(clippy::cast_precision_loss)] // word lengths are way below f64's precision limits
+fn mean_scrambling_bits() -> f64 {
+    let log2_factorial = |n: usize| (2..=n).map(|i| (i as f64).log2()).sum::<f64>();
+
+    let total: f64 = WORDS_LIST
+        .iter()
+        .map(|word| {
+            let mut counts = [0usize; 256];
+            for byte in word.bytes() {
+                counts[usize::from(byte)] += 1;
+            }
+            log2_factorial(word.len()) - counts.iter().map(|&c| log2_factorial(c)).sum::<f64>()
+        })
+        .sum();
+
+    total / WORDS_LIST.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_memorable_fixed_separator_adds_nothing() {
+        let entropy = memorable(5, Separator::Hyphen, 1, false);
+        let separators = entropy
+            .components()
+            .iter()
+            .find(|c| c.source == EntropySource::Separators)
+            .expect("separators should be reported");
+
+        assert!(separators.bits.abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_memorable_numbers_and_symbols_separators() {
+        let entropy = memorable(3, Separator::NumbersAndSymbols, 1, false);
+        let separators = entropy
+            .components()
+            .iter()
+            .find(|c| c.source == EntropySource::Separators)
+            .expect("separators should be reported");
+
+        // Two random characters out of 20 are inserted between the three words
+        assert!((separators.bits / 2.0 - 20f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_memorable_scrambling_adds_entropy() {
+        let plain = memorable(4, Separator::Space, 1, false);
+        let scrambled = memorable(4, Separator::Space, 1, true);
+        assert!(scrambled.bits() > plain.bits());
+    }
+
+    #[test]
+    fn test_random_with_numbers_and_symbols() {
+        // 70% letters, 20% numbers, 10% symbols
+        let sets = [(0.7f64, 52f64), (0.2, 10.0), (0.1, 10.0)];
+        let expected: f64 = sets.iter().map(|(p, size)| p * (size / p).log2()).sum();
+
+        let entropy = random(1, true, true, &[]);
+        assert!((entropy.bits() - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_random_with_excluded_characters() {
+        let entropy = random(1, false, false, &['a', 'b']);
+        assert!((entropy.bits() - 50f64.log2()).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,185 @@
+//! Hints helping to remember memorable passwords.
+//!
+//! Each word of a memorable password defined in the compact glossary embedded along the wordlist
+//! can be given its definition as a one-line hint, for users to weave the words into a story
+//! they remember. Words the glossary does not define get no hint, rather than a guess.
+
+use crate::WORDS_LIST;
+
+// GLOSSARY is the raw content of the embedded glossary file, holding one tab-separated word and
+// definition per line.
+const GLOSSARY: &str = include_str!("../glossary.txt");
+
+/// A rough grammatical category of words, guessed from their ending.
+///
+/// The guess is often wrong, for "copper" is no doer, and "steed" no event: it is only good enough
+/// to sort words in bulk, such as the adjectives and nouns usernames are made of.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Category {
+    /// Something being done, such as "boasting".
+    Action,
+    /// Something that happened, such as "flanked".
+    Event,
+    /// A way of doing something, such as "drearily".
+    Manner,
+    /// A quality or a state, such as "briskness".
+    Quality,
+    /// A description, such as "bountiful".
+    Description,
+    /// Someone or something that does something, such as "punisher".
+    Doer,
+    /// Anything else, most likely a thing or a being.
+    Thing,
+}
+
+impl Category {
+    // SUFFIXES maps word endings to the category of the words having them, checked in order.
+    const SUFFIXES: &'static [(&'static str, Self)] = &[
+        ("ing", Self::Action),
+        ("ed", Self::Event),
+        ("ly", Self::Manner),
+        ("ness", Self::Quality),
+        ("ity", Self::Quality),
+        ("ment", Self::Quality),
+        ("tion", Self::Quality),
+        ("sion", Self::Quality),
+        ("ism", Self::Quality),
+        ("hood", Self::Quality),
+        ("ship", Self::Quality),
+        ("able", Self::Description),
+        ("ible", Self::Description),
+        ("ful", Self::Description),
+        ("less", Self::Description),
+        ("ous", Self::Description),
+        ("ive", Self::Description),
+        ("ish", Self::Description),
+        ("ic", Self::Description),
+        ("er", Self::Doer),
+        ("or", Self::Doer),
+        ("ist", Self::Doer),
+    ];
+
+    /// Guesses the category of the given word from its ending.
+    #[must_use]
+    pub fn of(word: &str) -> Self {
+        Self::SUFFIXES
+            .iter()
+            .find(|(suffix, _)| word.len() > suffix.len() + 2 && word.ends_with(suffix))
+            .map_or(Self::Thing, |(_, category)| *category)
+    }
+}
+
+/// Returns the definition of the given word, or None if the glossary does not define it.
+///
+/// # Examples
+///
+/// ```
+/// use motus::glossary::hint;
+///
+/// assert_eq!(hint("abacus"), Some("a frame of beads slid along rods to count"));
+/// assert_eq!(hint("Abacus"), Some("a frame of beads slid along rods to count"));
+/// assert_eq!(hint("boasting"), None);
+/// ```
+#[must_use]
+pub fn hint(word: &str) -> Option<&'static str> {
+    let word = find_word(word)?;
+    GLOSSARY
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .find_map(|(defined, definition)| (defined == word).then_some(definition))
+}
+
+/// Returns the wordlist words found in a memorable password, in order.
+///
+/// Words are told apart by any separator, and compared regardless of their case. Parts of the
+/// password that are not words of the wordlist, such as scrambled words, are skipped.
+#[must_use]
+pub fn words(password: &str) -> Vec<&'static str> {
+    password
+        .split(|c: char| !c.is_ascii_alphabetic() && c != '-')
+        .flat_map(|token| {
+            find_word(token).map_or_else(|| split_hyphenated(token), |word| vec![word])
+        })
+        .collect()
+}
+
+// split_hyphenated returns the words found in a token of words joined by hyphens, keeping the
+// hyphenated words of the wordlist, such as "t-shirt", whole.
+fn split_hyphenated(token: &str) -> Vec<&'static str> {
+    let parts: Vec<&str> = token.split('-').collect();
+    let mut words = Vec::new();
+
+    let mut i = 0;
+    while i < parts.len() {
+        let hyphenated = parts
+            .get(i + 1)
+            .and_then(|next| find_word(&format!("{}-{}", parts[i], next)));
+        if let Some(word) = hyphenated {
+            words.push(word);
+            i += 2;
+        } else {
+            words.extend(find_word(parts[i]));
+            i += 1;
+        }
+    }
+
+    words
+}
+
+// find_word returns the wordlist entry matching the given word, regardless of its case.
+fn find_word(word: &str) -> Option<&'static str> {
+    let word = word.to_ascii_lowercase();
+    WORDS_LIST
+        .binary_search(&word.as_str())
+        .ok()
+        .map(|index| WORDS_LIST[index])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glossary_words_are_in_wordlist() {
+        for line in GLOSSARY.lines() {
+            let (word, definition) = line.split_once('\t').expect("line should have a tab");
+            assert!(find_word(word).is_some(), "{word} is not in the wordlist");
+            assert!(!definition.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_category_of() {
+        assert_eq!(Category::of("boasting"), Category::Action);
+        assert_eq!(Category::of("flanked"), Category::Event);
+        assert_eq!(Category::of("drearily"), Category::Manner);
+        assert_eq!(Category::of("briskness"), Category::Quality);
+        assert_eq!(Category::of("bountiful"), Category::Description);
+        assert_eq!(Category::of("punisher"), Category::Doer);
+        assert_eq!(Category::of("sip"), Category::Thing);
+        // Short words are not mistaken for having a suffix
+        assert_eq!(Category::of("king"), Category::Thing);
+    }
+
+    #[test]
+    fn test_hint() {
+        assert_eq!(hint("anchovy"), Some("a small salty fish, often on pizza"));
+        // Words the glossary does not define get no hint, even when their ending is telling
+        assert_eq!(hint("copper"), None);
+        assert_eq!(hint("boasting"), None);
+        assert_eq!(hint("xylophonist"), None);
+    }
+
+    #[test]
+    fn test_words() {
+        assert_eq!(
+            words("Chokehold4nativity#dolly"),
+            vec!["chokehold", "nativity", "dolly"]
+        );
+        assert_eq!(
+            words("abacus-t-shirt-yo-yo-dolly"),
+            vec!["abacus", "t-shirt", "yo-yo", "dolly"]
+        );
+        assert!(words("kohhcoled tytivian").is_empty());
+    }
+}
@@ -0,0 +1,85 @@
+//! Telephone keypad digits of passwords.
+//!
+//! Phone systems, such as voicemail, only accept PINs typed on a telephone keypad, whose keys
+//! carry letters as well as digits: 2 for ABC, 3 for DEF, and so on up to 9 for WXYZ, as laid out
+//! by ITU-T E.161. A word-based password can then be typed as the digits of the keys its letters
+//! are on, the way words were typed with T9 on older phones.
+//!
+//! Several letters share a key, so that the digits of a password hold less entropy than the
+//! password itself: words typed alike, such as "good" and "home", cannot be told apart anymore.
+
+/// Returns the keypad key `c` is typed with, if any.
+///
+/// Letters are typed with the digit of their key, regardless of their case, while digits, `*` and
+/// `#` have keys of their own.
+///
+/// # Examples
+///
+/// ```
+/// use motus::keypad::key;
+///
+/// assert_eq!(key('c'), Some('2'));
+/// assert_eq!(key('S'), Some('7'));
+/// assert_eq!(key('#'), Some('#'));
+/// assert_eq!(key('-'), None);
+/// ```
+#[must_use]
+pub const fn key(c: char) -> Option<char> {
+    match c.to_ascii_lowercase() {
+        'a'..='c' => Some('2'),
+        'd'..='f' => Some('3'),
+        'g'..='i' => Some('4'),
+        'j'..='l' => Some('5'),
+        'm'..='o' => Some('6'),
+        'p'..='s' => Some('7'),
+        't'..='v' => Some('8'),
+        'w'..='z' => Some('9'),
+        '0'..='9' | '*' | '#' => Some(c),
+        _ => None,
+    }
+}
+
+/// Returns the keypad digits `text` is typed with.
+///
+/// Characters without a key, such as the separators of memorable passwords, end the group of
+/// digits typed so far: groups are separated by a space, for each word to be read on its own.
+///
+/// # Examples
+///
+/// ```
+/// use motus::keypad::t9;
+///
+/// assert_eq!(t9("choking-natural-dolly"), "2465464 6288725 36559");
+/// assert_eq!(t9("Voicemail42"), "86423624542");
+/// ```
+#[must_use]
+pub fn t9(text: &str) -> String {
+    text.split(|c| key(c).is_none())
+        .filter(|group| !group.is_empty())
+        .map(|group| group.chars().filter_map(key).collect::<String>())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key() {
+        let keys: String = ('a'..='z').filter_map(key).collect();
+        assert_eq!(keys, "22233344455566677778889999");
+        assert_eq!(key('Z'), Some('9'));
+        assert_eq!(key('0'), Some('0'));
+        assert_eq!(key(' '), None);
+        assert_eq!(key('é'), None);
+    }
+
+    #[test]
+    fn test_t9() {
+        assert_eq!(t9("good home"), "4663 4663");
+        assert_eq!(t9("  hello,  world! "), "43556 96753");
+        assert_eq!(t9("*67#"), "*67#");
+        assert_eq!(t9("--"), "");
+    }
+}
@@ -1,24 +1,70 @@
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, LazyLock};
 
+use clap::builder::PossibleValue;
 use clap::ValueEnum;
 use itertools::Itertools;
-use lazy_static::lazy_static;
-use rand::distributions::{Uniform, WeightedIndex};
+use rand::distributions::WeightedIndex;
 use rand::prelude::*;
 
+use crate::charset::{Charset, SymbolSet};
+use crate::policy::Policy;
+
+pub mod analysis;
+pub mod card;
+pub mod charset;
+pub mod checksum;
+pub mod ct;
+pub mod denylist;
+pub mod entropy;
+pub mod glossary;
+pub mod keypad;
+pub mod manual;
+pub mod pattern;
+pub mod policy;
+#[cfg(feature = "render")]
+pub mod render;
+pub mod rng;
+pub mod secret;
+pub mod similarity;
+pub mod sites;
+pub mod spelling;
+pub mod story;
+pub mod username;
+pub mod wordlist;
+
+// WORDLIST is the raw content of the embedded wordlist file.
+const WORDLIST: &str = include_str!("../wordlist.txt");
+
 // WORDS_LIST is a list of words to use for generating memorable passwords, which
 // we directly embed in the executable.
 //
 // It is lazily initialized to avoid the cost of reading the wordlist from disk if it is not used
 // in a given run of the program.
-lazy_static! {
-    static ref WORDS_LIST: Arc<Vec<&'static str>> = {
-        let words = include_str!("../wordlist.txt")
-            .lines()
-            .filter(|l| l.len() >= 4)
-            .collect::<Vec<&str>>();
-        Arc::new(words)
-    };
+static WORDS_LIST: LazyLock<Arc<Vec<&'static str>>> = LazyLock::new(|| {
+    let words = WORDLIST
+        .lines()
+        .filter(|l| l.len() >= 4)
+        .collect::<Vec<&str>>();
+    Arc::new(words)
+});
+
+/// Returns the number of words memorable passwords are drawn from.
+#[must_use]
+pub fn wordlist_len() -> usize {
+    WORDS_LIST.len()
+}
+
+/// Returns a fingerprint of the embedded wordlist.
+///
+/// The fingerprint is the 64-bit FNV-1a hash of the wordlist file. It identifies the exact
+/// wordlist a build embeds, which matters when reproducing passwords from a seed, as any change
+/// to the list changes the generated passwords.
+#[must_use]
+pub fn wordlist_fingerprint() -> u64 {
+    WORDLIST.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
 }
 
 /// Generates a memorable password with the given options.
@@ -27,14 +73,22 @@ lazy_static! {
 /// optionally scrambling them and/or capitalizing them, and then joining them
 /// with the specified separator.
 ///
+/// When using the `Numbers` or `NumbersAndSymbols` separators, `separator_count` random
+/// characters are inserted between each pair of words, which increases the password's entropy
+/// without adding more words. It is ignored for the other, fixed, separators.
+///
 /// # Arguments
 ///
 /// * `rng` - A mutable reference to a random number generator that implements the `Rng` trait
 /// * `word_count` - The number of words to include in the password
 /// * `separator` - The type of separator to use between words (see `Separator` enum)
+/// * `separator_count` - The number of random characters inserted between words by random separators
 /// * `capitalize` - Whether to capitalize the first letter of each word
 /// * `scramble` - Whether to scramble the characters of each word
 ///
+/// Words are drawn without replacement, so a `word_count` larger than the embedded list yields
+/// every word of the list, in random order.
+///
 /// # Example
 ///
 /// ```
@@ -44,44 +98,92 @@ lazy_static! {
 /// let rng = &mut thread_rng();
 /// let word_count = 3;
 /// let separator = Separator::Hyphen;
+/// let separator_count = 1;
 /// let capitalize = true;
 /// let scramble = false;
 ///
-/// let password = memorable_password(rng, word_count, separator, capitalize, scramble);
+/// let password = memorable_password(rng, word_count, separator, separator_count, capitalize, scramble);
 /// println!("Generated password: {}", password);
 /// ```
 ///
-/// # Panics
-///
-/// The function may panic in the event a word from the list the crate embeds were to contain
-/// non-UTF-8 characters.
-///
 /// # Returns
 ///
 /// A `String` containing the generated memorable password
-#[allow(unstable_name_collisions)] // using itertools::intersperse_with until it is stabilized
 pub fn memorable_password<R: Rng>(
     rng: &mut R,
     word_count: usize,
     separator: Separator,
+    separator_count: usize,
     capitalize: bool,
     scramble: bool,
 ) -> String {
-    // Get the random words and format them
-    let formatted_words: Vec<String> = get_random_words(rng, word_count)
-        .into_iter()
-        .map(|word| {
+    memorable_password_with_style(
+        rng,
+        word_count,
+        JoinStyle::Separated {
+            separator,
+            capitalize,
+        },
+        separator_count,
+        scramble,
+    )
+}
+
+/// Generates a memorable password whose words are cased and joined in the given style.
+///
+/// This function behaves like [`memorable_password`], with the style determining both the
+/// separator and which words are capitalized. `separator_count` only matters to the random
+/// separators of [`JoinStyle::Separated`].
+///
+/// # Example
+///
+/// ```
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+/// use motus::{memorable_password_with_style, JoinStyle};
+///
+/// let mut rng = StdRng::seed_from_u64(42);
+/// let password = memorable_password_with_style(&mut rng, 3, JoinStyle::Camel, 1, false);
+/// assert_eq!(password, "chokingNaturalDolly");
+/// ```
+pub fn memorable_password_with_style<R: Rng>(
+    rng: &mut R,
+    word_count: usize,
+    style: JoinStyle,
+    separator_count: usize,
+    scramble: bool,
+) -> String {
+    let words = get_random_words(rng, &WORDS_LIST, word_count);
+    format_memorable_password(rng, &words, style, separator_count, scramble)
+}
+
+// format_memorable_password formats the given words into a memorable password, casing and
+// joining them in the given style.
+#[allow(unstable_name_collisions)] // using itertools::intersperse_with until it is stabilized
+pub(crate) fn format_memorable_password<R: Rng>(
+    rng: &mut R,
+    words: &[&str],
+    style: JoinStyle,
+    separator_count: usize,
+    scramble: bool,
+) -> String {
+    // Format the random words
+    let formatted_words: Vec<String> = words
+        .iter()
+        .enumerate()
+        .map(|(index, word)| {
             let mut word = word.to_string();
 
-            // Scramble the word if requested
+            // Scramble the word if requested, shuffling characters rather than bytes so that
+            // words beyond ASCII stay valid UTF-8
             if scramble {
-                let mut bytes = word.to_string().into_bytes();
-                bytes.shuffle(rng);
-                word = String::from_utf8(bytes).expect("random words should be valid UTF-8");
+                let mut chars: Vec<char> = word.chars().collect();
+                chars.shuffle(rng);
+                word = chars.into_iter().collect();
             }
 
-            // Capitalize the word if requested
-            if capitalize {
+            // Capitalize the word if the style requires it
+            if style.capitalizes(index) {
                 if let Some(first_letter) = word.get_mut(0..1) {
                     first_letter.make_ascii_uppercase();
                 }
@@ -91,16 +193,29 @@ pub fn memorable_password<R: Rng>(
         .collect();
 
     // Join the formatted words with the separator
+    let separator = style.separator();
+    formatted_words
+        .into_iter()
+        .intersperse_with(|| separator_string(rng, separator, separator_count))
+        .collect()
+}
+
+// separator_string returns the characters joining two words of a memorable password, drawing
+// `separator_count` of them from `rng` for the random separators.
+pub(crate) fn separator_string<R: Rng>(
+    rng: &mut R,
+    separator: Separator,
+    separator_count: usize,
+) -> String {
     match separator {
-        Separator::Space => formatted_words.join(" "),
-        Separator::Comma => formatted_words.join(","),
-        Separator::Hyphen => formatted_words.join("-"),
-        Separator::Period => formatted_words.join("."),
-        Separator::Underscore => formatted_words.join("_"),
-        Separator::Numbers => formatted_words
-            .iter()
-            .map(String::to_string)
-            .intersperse_with(|| rng.gen_range(0..10).to_string())
+        Separator::Space => " ".to_string(),
+        Separator::Comma => ",".to_string(),
+        Separator::Hyphen => "-".to_string(),
+        Separator::Period => ".".to_string(),
+        Separator::Underscore => "_".to_string(),
+        Separator::None => String::new(),
+        Separator::Numbers => (0..separator_count)
+            .map(|_| rng.gen_range(0..10).to_string())
             .collect(),
         Separator::NumbersAndSymbols => {
             let numbers_and_symbols: Vec<char> = SYMBOL_CHARS
@@ -108,14 +223,11 @@ pub fn memorable_password<R: Rng>(
                 .chain(NUMBER_CHARS.iter())
                 .copied()
                 .collect();
-            formatted_words
-                .iter()
-                .map(String::to_string)
-                .intersperse_with(|| {
+            (0..separator_count)
+                .map(|_| {
                     numbers_and_symbols
                         .choose(rng)
                         .expect("numbers and symbols should have a length >= 1")
-                        .to_string()
                 })
                 .collect()
         }
@@ -135,6 +247,8 @@ pub fn memorable_password<R: Rng>(
 /// * `Hyphen` - Use a hyphen character ('-') as the separator
 /// * `Period` - Use a period character ('.') as the separator
 /// * `Underscore` - Use an underscore character ('_') as the separator
+/// * `None` - Join the words without any separator, which is only unambiguous with a prefix-free
+///   wordlist, see [`entropy::joins_ambiguously`]
 /// * `Numbers` - Use random numbers (0-9) as separators between words
 /// * `NumbersAndSymbols` - Use a mix of random numbers (0-9) and symbols from the `SYMBOL_CHARS` const as separators between words
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
@@ -144,14 +258,91 @@ pub enum Separator {
     Hyphen,
     Period,
     Underscore,
+    None,
     Numbers,
     NumbersAndSymbols,
 }
 
+/// The style the words of a memorable password are cased and joined in.
+///
+/// Besides words joined with any [`Separator`], and either all capitalized or none, a style can
+/// follow one of the naming conventions of programming languages, which determines both at once.
+/// The conventions are the values of the `--style` command-line option.
+///
+/// # Examples
+///
+/// ```
+/// use motus::{JoinStyle, Separator};
+///
+/// assert_eq!(JoinStyle::Snake.separator(), Separator::Underscore);
+/// assert!(!JoinStyle::Camel.capitalizes(0));
+/// assert!(JoinStyle::Camel.capitalizes(1));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum JoinStyle {
+    /// Words joined with the separator, and all capitalized or none: `chokehold nativity dolly`.
+    Separated {
+        separator: Separator,
+        capitalize: bool,
+    },
+    /// Words joined without a separator, each capitalized but the first: `chokeholdNativityDolly`.
+    Camel,
+    /// Words joined without a separator, each capitalized: `ChokeholdNativityDolly`.
+    Pascal,
+    /// Lowercase words joined with underscores: `chokehold_nativity_dolly`.
+    Snake,
+    /// Lowercase words joined with hyphens: `chokehold-nativity-dolly`.
+    Kebab,
+}
+
+impl JoinStyle {
+    /// Returns the separator words are joined with.
+    #[must_use]
+    pub const fn separator(self) -> Separator {
+        match self {
+            Self::Separated { separator, .. } => separator,
+            Self::Camel | Self::Pascal => Separator::None,
+            Self::Snake => Separator::Underscore,
+            Self::Kebab => Separator::Hyphen,
+        }
+    }
+
+    /// Returns whether the word at `index`, from 0, is capitalized.
+    #[must_use]
+    pub const fn capitalizes(self, index: usize) -> bool {
+        match self {
+            Self::Separated { capitalize, .. } => capitalize,
+            Self::Camel => index > 0,
+            Self::Pascal => true,
+            Self::Snake | Self::Kebab => false,
+        }
+    }
+}
+
+// JoinStyle only lists the naming conventions as values, as the separated style is configured
+// with separate options.
+impl ValueEnum for JoinStyle {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Self::Camel, Self::Pascal, Self::Snake, Self::Kebab]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        match self {
+            Self::Separated { .. } => None,
+            Self::Camel => Some(PossibleValue::new("camel")),
+            Self::Pascal => Some(PossibleValue::new("pascal")),
+            Self::Snake => Some(PossibleValue::new("snake")),
+            Self::Kebab => Some(PossibleValue::new("kebab")),
+        }
+    }
+}
+
 /// Generates a random password with a specified length and optional inclusion of numbers and symbols.
 ///
 /// This function creates a random password with the desired number of characters.
 /// The generated password can include letters, numbers, and symbols based on the provided boolean flags.
+/// Characters listed in `excluded` are removed from every character set, which is useful for
+/// systems that choke on quotes, backslashes, or shell metacharacters.
 ///
 /// # Arguments
 ///
@@ -159,10 +350,12 @@ pub enum Separator {
 /// * `characters: u32` - The number of characters desired for the password
 /// * `numbers: bool` - A flag indicating whether numbers should be included in the password
 /// * `symbols: bool` - A flag indicating whether symbols should be included in the password
+/// * `excluded: &[char]` - Characters that must never appear in the password
 ///
 /// # Panics
 ///
-/// The function may panic in the event that the provided `characters` argument is 0.
+/// The function will panic if `excluded` contains every available character. A `characters`
+/// argument of 0 yields an empty password.
 ///
 /// # Returns
 ///
@@ -175,51 +368,141 @@ pub enum Separator {
 /// use motus::random_password;
 ///
 /// let mut rng = thread_rng();
-/// let password = random_password(&mut rng, 12, true, true);
+/// let password = random_password(&mut rng, 12, true, true, &['(', ')']);
 /// assert_eq!(password.len(), 12);
+/// assert!(!password.contains(['(', ')']));
 /// ```
 pub fn random_password<R: Rng>(
     rng: &mut R,
     characters: u32,
     numbers: bool,
     symbols: bool,
+    excluded: &[char],
 ) -> String {
-    let mut available_sets = vec![LETTER_CHARS];
+    constrained_random_password(
+        rng,
+        characters,
+        &random_charsets(numbers, symbols, SymbolSet::Default, excluded),
+        &Policy::default(),
+    )
+}
 
-    if numbers {
-        available_sets.push(NUMBER_CHARS);
-    }
+/// Generates a random password drawing each character from the given weighted character sets.
+///
+/// Each character is drawn by first picking a set according to its weight, then a character from
+/// that set, the way [`random_password`] draws from letters, numbers and symbols. Empty sets are
+/// left out.
+///
+/// # Panics
+///
+/// The function will panic if every set is empty, or if every weight is zero.
+///
+/// # Examples
+///
+/// ```
+/// use rand::thread_rng;
+/// use motus::charset::Charset;
+/// use motus::charset_password;
+///
+/// // Mostly lowercase letters, with a few digits other than 0 and 1
+/// let digits = Charset::numbers() - Charset::from("01");
+/// let password = charset_password(&mut thread_rng(), 16, &[(Charset::lowercase(), 4), (digits, 1)]);
+/// assert_eq!(password.len(), 16);
+/// assert!(password.chars().all(|c| c.is_ascii_lowercase() || ('2'..='9').contains(&c)));
+/// ```
+#[must_use]
+pub fn charset_password<R: Rng>(rng: &mut R, characters: u32, sets: &[(Charset, u32)]) -> String {
+    let sets: Vec<(Charset, u32)> = sets
+        .iter()
+        .filter(|(set, _)| !set.is_empty())
+        .cloned()
+        .collect();
+    constrained_random_password(rng, characters, &sets, &Policy::default())
+}
+
+// constrained_random_password generates a random password drawing each character from the given
+// weighted sets, while honoring the policy's rules.
+//
+// Rules are enforced as constraints on each position rather than by resampling whole passwords:
+// characters the policy does not allow at a given position are removed from the candidates. Should
+// the selected set end up empty, the character is drawn from the allowed characters of all sets.
+fn constrained_random_password<R: Rng>(
+    rng: &mut R,
+    characters: u32,
+    sets: &[(Charset, u32)],
+    policy: &Policy,
+) -> String {
+    let weights: Vec<u32> = sets.iter().map(|(_, weight)| *weight).collect();
+    let dist_set = WeightedIndex::new(weights).expect("weights should be valid");
+    let mut password: Vec<char> = Vec::with_capacity(characters as usize);
+
+    for i in 0..characters {
+        let remaining = (characters - i - 1) as usize;
+        let (selected_set, _) = sets
+            .get(dist_set.sample(rng))
+            .expect("index should be valid");
+
+        let mut candidates: Vec<char> = selected_set
+            .chars()
+            .iter()
+            .filter(|&&c| policy.allows(&password, c, remaining))
+            .copied()
+            .collect();
+        if candidates.is_empty() {
+            // Sets may overlap, and a character found in several must not be likelier
+            candidates = sets
+                .iter()
+                .flat_map(|(set, _)| set.chars())
+                .filter(|&&c| policy.allows(&password, c, remaining))
+                .copied()
+                .collect();
+            let mut seen = HashSet::new();
+            candidates.retain(|c| seen.insert(*c));
+        }
+
+        assert!(
+            !candidates.is_empty(),
+            "the policy should allow at least one character at every position"
+        );
 
-    if symbols {
-        available_sets.push(SYMBOL_CHARS);
+        password.push(rng::uniform_candidate(rng, &candidates));
     }
 
-    let weights: Vec<u32> = match (numbers, symbols) {
+    password.into_iter().collect()
+}
+
+// random_charsets returns the character sets random passwords are drawn from, along with their
+// weights, symbols being drawn from `symbol_set`, leaving out the excluded characters, and the
+// sets that end up empty.
+fn random_charsets(
+    numbers: bool,
+    symbols: bool,
+    symbol_set: SymbolSet,
+    excluded: &[char],
+) -> Vec<(Charset, u32)> {
+    let sets: Vec<(Charset, u32)> = match (numbers, symbols) {
         // If numbers and symbols are both true, we want to make sure that
         // we apply the following distribution: 70% letters, 20% numbers, 10% symbols.
-        (true, true) => vec![7, 2, 1],
+        (true, true) => vec![
+            (Charset::letters(), 7),
+            (Charset::numbers(), 2),
+            (symbol_set.charset(), 1),
+        ],
 
         // If either numbers or symbols is true, but not the other, we want
         // to make sure that we apply the following distribution: 80% letters, 20% numbers.
-        (true, false) | (false, true) => vec![8, 2],
+        (true, false) => vec![(Charset::letters(), 8), (Charset::numbers(), 2)],
+        (false, true) => vec![(Charset::letters(), 8), (symbol_set.charset(), 2)],
 
         // Otherwise we want to make sure that we apply the following distribution: 100% letters.
-        (false, false) => vec![10],
+        (false, false) => vec![(Charset::letters(), 10)],
     };
 
-    let dist_set = WeightedIndex::new(weights).expect("weights should be valid");
-    let mut password = String::with_capacity(characters as usize);
-
-    for _ in 0..characters {
-        let selected_set = available_sets
-            .get(dist_set.sample(rng))
-            .expect("index should be valid");
-        let dist_char = Uniform::from(0..selected_set.len());
-        let index = dist_char.sample(rng);
-        password.push(selected_set[index]);
-    }
-
-    password
+    let excluded = Charset::custom(excluded.iter().copied());
+    sets.into_iter()
+        .map(|(set, weight)| (set.minus(&excluded), weight))
+        .filter(|(set, _)| !set.is_empty())
+        .collect()
 }
 
 /// Generates a random numeric PIN with a specified length.
@@ -247,11 +530,83 @@ pub fn random_password<R: Rng>(
 /// assert!(pin.chars().all(|c| c.is_digit(10)));
 /// ```
 pub fn pin_password<R: Rng>(rng: &mut R, numbers: u32) -> String {
-    (0..numbers)
-        .map(|_| NUMBER_CHARS[rng.gen_range(0..NUMBER_CHARS.len())])
+    code_password(rng, numbers, NUMBER_CHARS)
+}
+
+/// Generates a random password shaped like the ones Apple's iCloud Keychain suggests.
+///
+/// The password is made of three chunks of six lowercase letters separated by hyphens, with one
+/// uppercase letter and one digit at random positions, e.g. `fexnux-7ipvoq-reXdyk`. This shape is
+/// familiar to users and accepted by most sites' password rules.
+///
+/// # Examples
+///
+/// ```
+/// use rand::thread_rng;
+/// use motus::apple_password;
+///
+/// let mut rng = thread_rng();
+/// let password = apple_password(&mut rng);
+/// assert_eq!(password.len(), 20);
+/// assert_eq!(password.split('-').count(), 3);
+/// ```
+pub fn apple_password<R: Rng>(rng: &mut R) -> String {
+    pattern::apple(rng).generate(rng)
+}
+
+/// Generates a random code of a specified length, with characters drawn uniformly from `alphabet`.
+///
+/// Codes are short secrets meant to be typed by hand, such as device pairing codes. See [`Radix`]
+/// for the alphabets available out of the box.
+///
+/// # Panics
+///
+/// The function will panic if `alphabet` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use rand::thread_rng;
+/// use motus::{code_password, Radix};
+///
+/// let mut rng = thread_rng();
+/// let code = code_password(&mut rng, 8, Radix::Hex.alphabet());
+/// assert_eq!(code.len(), 8);
+/// assert!(code.chars().all(|c| c.is_ascii_hexdigit()));
+/// ```
+pub fn code_password<R: Rng>(rng: &mut R, length: u32, alphabet: &[char]) -> String {
+    (0..length)
+        .map(|_| rng::uniform_char(rng, alphabet))
         .collect()
 }
 
+/// The alphabet of a code generated with [`code_password`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Radix {
+    /// Decimal digits, as in regular PINs.
+    #[default]
+    Decimal,
+    /// Lowercase hexadecimal digits.
+    Hex,
+    /// The RFC 4648 base32 alphabet: uppercase letters and the digits 2 to 7.
+    Base32,
+    /// Uppercase letters and decimal digits.
+    Alnum,
+}
+
+impl Radix {
+    /// Returns the characters codes in this radix are made of.
+    #[must_use]
+    pub const fn alphabet(self) -> &'static [char] {
+        match self {
+            Self::Decimal => NUMBER_CHARS,
+            Self::Hex => HEX_CHARS,
+            Self::Base32 => BASE32_CHARS,
+            Self::Alnum => ALNUM_CHARS,
+        }
+    }
+}
+
 // LETTER_CHARS is a list of letters that can be used in passwords
 const LETTER_CHARS: &[char] = &[
     'a', 'b', 'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's',
@@ -265,38 +620,135 @@ const NUMBER_CHARS: &[char] = &['0', '1', '2', '3', '4', '5', '6', '7', '8', '9'
 // SYMBOL_CHARS is a list of symbols that can be used in passwords
 const SYMBOL_CHARS: &[char] = &['!', '@', '#', '$', '%', '^', '&', '*', '(', ')'];
 
-// get_random_words returns a vector of n random words from the word list
-fn get_random_words<R: Rng>(rng: &mut R, n: usize) -> Vec<&'static str> {
-    WORDS_LIST.choose_multiple(rng, n).copied().collect()
+// INTL_SAFE_SYMBOL_CHARS is a list of symbols typed alike on major international keyboard layouts
+const INTL_SAFE_SYMBOL_CHARS: &[char] = &[
+    '!', '%', '(', ')', '*', '+', ',', '-', '.', '/', ':', ';', '=', '?', '_',
+];
+
+// HEX_CHARS is the alphabet of hexadecimal codes
+const HEX_CHARS: &[char] = &[
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'a', 'b', 'c', 'd', 'e', 'f',
+];
+
+// BASE32_CHARS is the RFC 4648 base32 alphabet, which leaves out digits looking like letters
+const BASE32_CHARS: &[char] = &[
+    'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S',
+    'T', 'U', 'V', 'W', 'X', 'Y', 'Z', '2', '3', '4', '5', '6', '7',
+];
+
+// ALNUM_CHARS is the alphabet of alphanumeric codes
+const ALNUM_CHARS: &[char] = &[
+    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I',
+    'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+];
+
+// get_random_words returns a vector of n random words from the given word list
+pub(crate) fn get_random_words<R: Rng>(
+    rng: &mut R,
+    words: &[&'static str],
+    n: usize,
+) -> Vec<&'static str> {
+    words.choose_multiple(rng, n).copied().collect()
+}
+
+// get_shuffled_random_words returns a vector of n random words from the given word list, like
+// get_random_words, shuffled again so that their order is uniformly random whatever the order
+// choose_multiple's sampling algorithm yields them in.
+pub(crate) fn get_shuffled_random_words<R: Rng>(
+    rng: &mut R,
+    words: &[&'static str],
+    n: usize,
+) -> Vec<&'static str> {
+    let mut chosen = get_random_words(rng, words, n);
+    chosen.shuffle(rng);
+    chosen
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_shuffled_random_words_order_is_uniform() {
+        const WORDS: [&str; 4] = ["abacus", "dolly", "throat", "stone"];
+        const DRAWS: usize = 24_000;
+
+        // Count how often each word lands in each position, over many draws of every word
+        let mut counts = [[0usize; 4]; 4];
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..DRAWS {
+            for (position, word) in get_shuffled_random_words(&mut rng, &WORDS, 4)
+                .iter()
+                .enumerate()
+            {
+                let index = WORDS
+                    .iter()
+                    .position(|w| w == word)
+                    .expect("word should be listed");
+                counts[index][position] += 1;
+            }
+        }
+
+        // Chi-square test of uniformity over the positions, for each word: with 3 degrees of
+        // freedom, 16.27 is only exceeded with a 0.1% probability
+        #[allow(clippy::cast_precision_loss)]
+        let expected = DRAWS as f64 / 4.0;
+        for positions in counts {
+            let chi_square: f64 = positions
+                .iter()
+                .map(|&count| {
+                    #[allow(clippy::cast_precision_loss)]
+                    let deviation = count as f64 - expected;
+                    deviation * deviation / expected
+                })
+                .sum();
+            assert!(chi_square < 16.27, "{positions:?}");
+        }
+    }
+
     #[test]
     fn test_memorable_password() {
         let seed = 42; // Fixed seed for predictable randomness
         let mut rng = StdRng::seed_from_u64(seed);
 
-        let password = memorable_password(&mut rng, 4, Separator::Space, false, false);
+        let password = memorable_password(&mut rng, 4, Separator::Space, 1, false, false);
         assert_eq!(password, "choking natural dolly ominous");
 
-        let password = memorable_password(&mut rng, 4, Separator::Comma, false, false);
+        let password = memorable_password(&mut rng, 4, Separator::Comma, 1, false, false);
         assert_eq!(password, "thrive,punctured,wool,hardcover");
 
-        let password = memorable_password(&mut rng, 4, Separator::Hyphen, true, false);
+        let password = memorable_password(&mut rng, 4, Separator::Hyphen, 1, true, false);
         assert_eq!(password, "Violate-Applause-Preorder-Headstone");
 
-        let password = memorable_password(&mut rng, 4, Separator::Numbers, true, true);
+        let password = memorable_password(&mut rng, 4, Separator::Numbers, 1, true, true);
         assert_eq!(password, "Nioutfna2Cerslua5Aborrcw4Wtpse");
     }
 
+    #[test]
+    fn test_memorable_password_separator_count() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        let password = memorable_password(&mut rng, 3, Separator::Numbers, 2, false, false);
+        let separators: Vec<String> = password
+            .split(char::is_alphabetic)
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect();
+
+        assert_eq!(separators.len(), 2);
+        assert!(separators
+            .iter()
+            .all(|s| s.len() == 2 && s.chars().all(|c| c.is_ascii_digit())));
+
+        let password = memorable_password(&mut rng, 3, Separator::Hyphen, 3, false, false);
+        assert_eq!(password.matches('-').count(), 2);
+    }
+
     #[test]
     fn test_random_password_length() {
         let mut rng = StdRng::seed_from_u64(0);
         let length = 12;
-        let password = random_password(&mut rng, length, true, true);
+        let password = random_password(&mut rng, length, true, true, &[]);
         assert_eq!(password.len(), length as usize);
     }
 
@@ -305,28 +757,46 @@ mod tests {
         let mut rng = StdRng::seed_from_u64(0);
         let length = 12;
 
-        let password_letters = random_password(&mut rng, length, false, false);
+        let password_letters = random_password(&mut rng, length, false, false, &[]);
         assert!(password_letters.chars().all(|c| LETTER_CHARS.contains(&c)));
 
-        let password_numbers = random_password(&mut rng, length, true, false);
+        let password_numbers = random_password(&mut rng, length, true, false, &[]);
         assert!(password_numbers.chars().any(|c| NUMBER_CHARS.contains(&c)));
 
-        let password_symbols = random_password(&mut rng, length, false, true);
+        let password_symbols = random_password(&mut rng, length, false, true, &[]);
         assert!(password_symbols.chars().any(|c| SYMBOL_CHARS.contains(&c)));
 
-        let password_numbers_symbols = random_password(&mut rng, length, true, true);
+        let password_numbers_symbols = random_password(&mut rng, length, true, true, &[]);
         assert!(password_numbers_symbols
             .chars()
             .any(|c| NUMBER_CHARS.contains(&c) || SYMBOL_CHARS.contains(&c)));
     }
 
+    #[test]
+    fn test_random_password_excluded_characters() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let excluded = ['a', 'B', '0', '!', '(', ')'];
+
+        for _ in 0..20 {
+            let password = random_password(&mut rng, 50, true, true, &excluded);
+            assert!(!password.contains(excluded));
+        }
+    }
+
+    #[test]
+    fn test_random_charsets_drops_empty_sets() {
+        let sets = random_charsets(true, false, SymbolSet::Default, NUMBER_CHARS);
+        assert_eq!(sets.len(), 1);
+        assert_eq!(sets[0].0.chars(), LETTER_CHARS);
+    }
+
     #[test]
     fn test_random_password_different_seeds() {
         let mut rng1 = StdRng::seed_from_u64(0);
         let mut rng2 = StdRng::seed_from_u64(1);
         let length = 12;
-        let password1 = random_password(&mut rng1, length, true, true);
-        let password2 = random_password(&mut rng2, length, true, true);
+        let password1 = random_password(&mut rng1, length, true, true, &[]);
+        let password2 = random_password(&mut rng2, length, true, true, &[]);
         assert_ne!(password1, password2);
     }
 
@@ -361,7 +831,7 @@ mod tests {
         let seed = 42; // Fixed seed for predictable randomness
         let mut rng = StdRng::seed_from_u64(seed);
 
-        let words = get_random_words(&mut rng, 5);
+        let words = get_random_words(&mut rng, &WORDS_LIST, 5);
 
         // Note that the expected word list is fixed as we provide a fixed
         // random seed. If you change the seed, you should change the expected
@@ -371,4 +841,22 @@ mod tests {
             vec!["chokehold", "nativity", "dolly", "ominous", "throat"]
         );
     }
+
+    #[test]
+    fn test_code_password_alphabets() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for radix in [Radix::Decimal, Radix::Hex, Radix::Base32, Radix::Alnum] {
+            let code = code_password(&mut rng, 100, radix.alphabet());
+            assert_eq!(code.len(), 100);
+            assert!(code.chars().all(|c| radix.alphabet().contains(&c)));
+        }
+    }
+
+    #[test]
+    fn test_pin_password_is_a_decimal_code() {
+        let pin = pin_password(&mut StdRng::seed_from_u64(42), 7);
+        let code = code_password(&mut StdRng::seed_from_u64(42), 7, Radix::Decimal.alphabet());
+        assert_eq!(pin, code);
+    }
 }
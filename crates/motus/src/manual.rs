@@ -0,0 +1,704 @@
+//! Passwords generated from physical randomness, such as dice rolls, coin flips or a shuffled
+//! deck of cards.
+//!
+//! Users generating passwords on an offline machine may trust dice they can see more than any
+//! random number generator. The outcomes they report are turned into digits:
+//!
+//! * a roll of a die with `sides` sides is a digit in base `sides`, its value minus one;
+//! * a coin flip is a binary digit, 1 for heads and 0 for tails;
+//! * a card drawn from a shuffled deck is a digit in base the number of cards left in the deck,
+//!   its position among them, in the order ace to king of spades, hearts, diamonds then clubs.
+//!   A whole deck holds log2(52!), about 225.6 bits, the entropy of its order.
+//!
+//! Digits are combined into a number, most significant first, until the number of values they
+//! can take reaches the number of candidates, words or characters, the password is drawn from.
+//!
+//! That number of values is rarely a multiple of the number of candidates, and taking the number
+//! modulo the candidates would then favor the first ones. Numbers falling in the incomplete last
+//! band of candidates are rejected instead, and the next digits are used, so that every candidate
+//! is exactly as likely as the others. With six-sided dice and the embedded wordlist, five rolls
+//! make a number below 7776, kept when below 7694, the number of words: about one word in a
+//! hundred is rejected.
+//!
+//! Every step is recorded in a [`Derivation`], for users to check the math by hand.
+
+use std::fmt::{Display, Formatter};
+
+use crate::charset::Charset;
+use crate::entropy::{Entropy, EntropySource};
+use crate::WORDS_LIST;
+
+/// The most sides a die may have.
+pub const MAX_SIDES: u32 = 100;
+
+/// The number of cards of a deck.
+pub const DECK_SIZE: u32 = 52;
+
+// RANKS and SUITS are the ranks and suits of cards, in the order cards are numbered in.
+const RANKS: [&str; 13] = [
+    "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+];
+const SUITS: [char; 4] = ['S', 'H', 'D', 'C'];
+
+/// A physical source of randomness.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Source {
+    /// Rolls of dice with the given number of sides, typed as numbers from 1.
+    Dice { sides: u32 },
+    /// Coin flips, typed as `H` for heads and `T` for tails.
+    Coins,
+    /// Cards drawn from a shuffled deck, typed as their rank and suit, such as `10H` or `QS`.
+    Deck,
+}
+
+impl Source {
+    /// Returns the name of one outcome of the source.
+    #[must_use]
+    pub const fn outcome_name(self) -> &'static str {
+        match self {
+            Self::Dice { .. } => "roll",
+            Self::Coins => "flip",
+            Self::Deck => "card",
+        }
+    }
+}
+
+/// A digit drawn uniformly below its radix by a physical random event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Digit {
+    /// The value of the digit, below `radix`.
+    pub value: u32,
+    /// The number of values the digit could have taken.
+    pub radix: u32,
+}
+
+/// A physical random event, as it was reported and as a digit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome {
+    /// The outcome, normalized, such as `3`, `H` or `10S`.
+    pub label: String,
+    /// The digit the outcome stands for.
+    pub digit: Digit,
+}
+
+/// The outcomes of physical random events of a single source.
+///
+/// # Examples
+///
+/// ```
+/// use motus::manual::{Outcomes, Source};
+///
+/// let rolls = Outcomes::parse_dice("3 5 1, 6 2", 6).expect("rolls should parse");
+/// assert_eq!(rolls.len(), 5);
+/// assert!((rolls.bits() - 5.0 * 6f64.log2()).abs() < 1e-9);
+///
+/// // Rolls of dice with fewer than ten sides may also be typed without separators
+/// assert_eq!(Outcomes::parse_dice("35162", 6), Ok(rolls));
+/// assert!(Outcomes::parse_dice("7", 6).is_err());
+///
+/// // Flips are typed as heads and tails, and cards as their rank and suit
+/// let flips = Outcomes::parse(Source::Coins, "HTTH ht").expect("flips should parse");
+/// assert_eq!(flips.len(), 6);
+/// let cards = Outcomes::parse(Source::Deck, "QS 10h 2D").expect("cards should parse");
+/// assert_eq!(cards.outcomes()[1].label, "10H");
+/// assert!(Outcomes::parse(Source::Deck, "QS 2D QS").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcomes {
+    source: Source,
+    outcomes: Vec<Outcome>,
+}
+
+impl Outcomes {
+    /// Returns an empty list of outcomes of `source`.
+    #[must_use]
+    pub const fn new(source: Source) -> Self {
+        Self {
+            source,
+            outcomes: Vec::new(),
+        }
+    }
+
+    /// Parses outcomes of `source`, separated by whitespace or commas.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an outcome is not one `source` can produce, see [`Outcomes::push_str`].
+    pub fn parse(source: Source, input: &str) -> Result<Self, ManualError> {
+        let mut outcomes = Self::new(source);
+        outcomes.push_str(input)?;
+        Ok(outcomes)
+    }
+
+    /// Parses rolls of dice with `sides` sides, see [`Outcomes::push_str`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `sides` is not between 2 and [`MAX_SIDES`], or if a roll is not a
+    /// number between 1 and `sides`.
+    pub fn parse_dice(input: &str, sides: u32) -> Result<Self, ManualError> {
+        Self::parse(Source::Dice { sides }, input)
+    }
+
+    /// Parses outcomes separated by whitespace or commas, and appends them. Nothing is appended
+    /// when an outcome is invalid.
+    ///
+    /// Rolls are numbers from 1 to the number of sides; when dice have fewer than ten sides,
+    /// consecutive digits are separate rolls. Flips are `H` or `heads` and `T` or `tails`, and
+    /// consecutive letters are separate flips. Cards are a rank, `A`, `2` to `10`, `J`, `Q` or
+    /// `K`, followed by a suit, `S`, `H`, `D` or `C`, and each card may only be drawn once.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an outcome cannot be read, if a roll is above the number of sides of
+    /// the die, if dice do not have between 2 and [`MAX_SIDES`] sides, or if a card was already
+    /// drawn.
+    pub fn push_str(&mut self, input: &str) -> Result<(), ManualError> {
+        if let Source::Dice { sides } = self.source {
+            if !(2..=MAX_SIDES).contains(&sides) {
+                return Err(ManualError::InvalidSides(sides));
+            }
+        }
+
+        let mut parsed = self.clone();
+        let tokens = input
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|token| !token.is_empty());
+        for token in tokens {
+            match self.source {
+                Source::Dice { sides } => parsed.push_rolls(token, sides)?,
+                Source::Coins => parsed.push_flips(token)?,
+                Source::Deck => parsed.push_card(token)?,
+            }
+        }
+
+        *self = parsed;
+        Ok(())
+    }
+
+    // push_rolls appends the rolls of a die with `sides` sides `token` holds.
+    fn push_rolls(&mut self, token: &str, sides: u32) -> Result<(), ManualError> {
+        let rolls = if sides < 10 && token.chars().all(|c| c.is_ascii_digit()) {
+            token.chars().filter_map(|c| c.to_digit(10)).collect()
+        } else {
+            let roll = token
+                .parse::<u32>()
+                .map_err(|_| ManualError::InvalidOutcome(token.to_string()))?;
+            vec![roll]
+        };
+        for roll in rolls {
+            if !(1..=sides).contains(&roll) {
+                return Err(ManualError::OutOfRange { roll, sides });
+            }
+            self.push(roll.to_string(), roll - 1, sides);
+        }
+        Ok(())
+    }
+
+    // push_flips appends the coin flips `token` holds.
+    fn push_flips(&mut self, token: &str) -> Result<(), ManualError> {
+        let token = token.to_ascii_uppercase();
+        let flips = match token.as_str() {
+            "HEADS" => "H",
+            "TAILS" => "T",
+            flips if flips.chars().all(|c| c == 'H' || c == 'T') => flips,
+            _ => return Err(ManualError::InvalidOutcome(token)),
+        };
+        for flip in flips.chars() {
+            self.push(flip.to_string(), u32::from(flip == 'H'), 2);
+        }
+        Ok(())
+    }
+
+    // push_card appends the card `token` names, numbered by its position among the cards left.
+    fn push_card(&mut self, token: &str) -> Result<(), ManualError> {
+        let label = token.to_ascii_uppercase();
+        let invalid = || ManualError::InvalidOutcome(token.to_string());
+        let suit = label.chars().last().ok_or_else(invalid)?;
+        let rank = match &label[..label.len() - suit.len_utf8()] {
+            "T" => "10",
+            rank => rank,
+        };
+        let rank = RANKS.iter().position(|&r| r == rank).ok_or_else(invalid)?;
+        let suit = SUITS.iter().position(|&s| s == suit).ok_or_else(invalid)?;
+        let label = format!("{}{}", RANKS[rank], SUITS[suit]);
+
+        if self.outcomes.iter().any(|outcome| outcome.label == label) {
+            return Err(ManualError::DuplicateCard(label));
+        }
+
+        // The cards left before this one are the deck minus the drawn ones; the digit is the
+        // number of them numbered before this card
+        let number = suit * RANKS.len() + rank;
+        let drawn_before = self
+            .outcomes
+            .iter()
+            .filter(|outcome| card_number(&outcome.label) < number)
+            .count();
+        let value = u32::try_from(number - drawn_before).expect("cards should number below 52");
+        let left = DECK_SIZE
+            - u32::try_from(self.outcomes.len()).expect("decks should hold at most 52 cards");
+        self.push(label, value, left);
+        Ok(())
+    }
+
+    fn push(&mut self, label: String, value: u32, radix: u32) {
+        self.outcomes.push(Outcome {
+            label,
+            digit: Digit { value, radix },
+        });
+    }
+
+    /// Returns the source of the outcomes.
+    #[must_use]
+    pub const fn source(&self) -> Source {
+        self.source
+    }
+
+    /// Returns the outcomes, in the order the events occurred.
+    #[must_use]
+    pub fn outcomes(&self) -> &[Outcome] {
+        &self.outcomes
+    }
+
+    /// Returns the number of events.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.outcomes.len()
+    }
+
+    /// Returns whether no event occurred.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.outcomes.is_empty()
+    }
+
+    /// Returns the entropy of the events, in bits, the most a password derived from them holds.
+    #[must_use]
+    pub fn bits(&self) -> f64 {
+        self.outcomes
+            .iter()
+            .map(|outcome| f64::from(outcome.digit.radix).log2())
+            .sum()
+    }
+
+    /// Estimates how many more outcomes a password drawn as `derivation` from these outcomes
+    /// needs to hold `bits` of entropy, accounting for the numbers expected to be rejected.
+    ///
+    /// Physical outcomes hold less entropy than one may think once mapped onto words: numbers are
+    /// rejected, and trailing outcomes too few to make a number are left unused. Returns `None`
+    /// when the source cannot produce enough outcomes, such as a deck running out of cards.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use motus::manual::{self, Outcomes};
+    ///
+    /// // 25 rolls of a six-sided die hold 64.6 bits, but five words only 64.3 of them
+    /// let rolls = Outcomes::parse_dice(&"1".repeat(25), 6).expect("rolls should parse");
+    /// let (_, derivation) = manual::memorable_password(&rolls);
+    /// assert_eq!(rolls.outcomes_needed(&derivation, 64.0), Some(0));
+    /// assert_eq!(rolls.outcomes_needed(&derivation, 65.0), Some(5));
+    /// ```
+    #[allow(clippy::cast_precision_loss)] // candidate counts are way below f64's precision limits
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)] // the count is positive
+    #[must_use]
+    pub fn outcomes_needed(&self, derivation: &Derivation, bits: f64) -> Option<usize> {
+        let candidates = derivation.candidates() as u64;
+        let bits_per_draw = (candidates as f64).log2();
+        let missing = bits - derivation.bits();
+        if missing <= 0.0 {
+            return Some(0);
+        }
+
+        let cards = u32::try_from(self.len()).unwrap_or(u32::MAX);
+        let radix = match self.source {
+            Source::Dice { sides } => sides,
+            Source::Coins => 2,
+            Source::Deck => DECK_SIZE.saturating_sub(cards),
+        };
+        if radix < 2 {
+            return None;
+        }
+
+        // The digits of the next outcomes make numbers of the same range, and a share of them are
+        // rejected; decks are estimated with the radix of their next card
+        let (mut range, mut digits) = (1u64, 0usize);
+        while range < candidates {
+            range *= u64::from(radix);
+            digits += 1;
+        }
+        let accepted = (range - range % candidates) as f64 / range as f64;
+        let draws = (missing / bits_per_draw / accepted).ceil() as usize;
+        let needed = (draws * digits).saturating_sub(derivation.unused());
+
+        match self.source {
+            Source::Deck if self.len() + needed > DECK_SIZE as usize => None,
+            _ => Some(needed),
+        }
+    }
+
+    /// Draws as many indices below `candidates` as the digits allow, with rejection sampling.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `candidates` is 0.
+    #[must_use]
+    pub fn draw(&self, candidates: usize) -> Derivation {
+        assert!(candidates > 0, "there should be candidates to draw from");
+        let candidates_count = candidates as u64;
+
+        let mut draws = Vec::new();
+        let mut start = 0;
+        while start < self.outcomes.len() {
+            let mut value = 0u64;
+            let mut range = 1u64;
+            let mut end = start;
+            while range < candidates_count {
+                let Some(Outcome { digit, .. }) = self.outcomes.get(end) else {
+                    break;
+                };
+                value = value * u64::from(digit.radix) + u64::from(digit.value);
+                range *= u64::from(digit.radix);
+                end += 1;
+            }
+            if range < candidates_count {
+                break;
+            }
+
+            let limit = range - range % candidates_count;
+            let index = (value < limit).then(|| {
+                usize::try_from(value % candidates_count)
+                    .expect("indices should be below the number of candidates")
+            });
+            draws.push(Draw {
+                outcomes: start..end,
+                value,
+                range,
+                limit,
+                index,
+            });
+            start = end;
+        }
+
+        Derivation {
+            candidates,
+            draws,
+            unused: self.outcomes.len() - start,
+        }
+    }
+}
+
+/// A number made of consecutive digits, and the index it was mapped to, if it was not rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Draw {
+    /// The positions of the outcomes the number is made of.
+    pub outcomes: std::ops::Range<usize>,
+    /// The number, below `range`.
+    pub value: u64,
+    /// The number of values the digits could make.
+    pub range: u64,
+    /// The largest multiple of the number of candidates not above `range`: numbers from it are
+    /// rejected.
+    pub limit: u64,
+    /// The index of the candidate drawn, the number modulo the number of candidates, unless the
+    /// number was rejected.
+    pub index: Option<usize>,
+}
+
+/// How physical outcomes were mapped onto candidates.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Derivation {
+    candidates: usize,
+    draws: Vec<Draw>,
+    unused: usize,
+}
+
+impl Derivation {
+    /// Returns the number of candidates indices were drawn below.
+    #[must_use]
+    pub const fn candidates(&self) -> usize {
+        self.candidates
+    }
+
+    /// Returns every draw, rejected ones included.
+    #[must_use]
+    pub fn draws(&self) -> &[Draw] {
+        &self.draws
+    }
+
+    /// Returns the indices drawn, leaving out rejected numbers.
+    #[must_use]
+    pub fn indices(&self) -> Vec<usize> {
+        self.draws.iter().filter_map(|draw| draw.index).collect()
+    }
+
+    /// Returns the number of trailing outcomes too few to make a number, which were not used.
+    #[must_use]
+    pub const fn unused(&self) -> usize {
+        self.unused
+    }
+
+    /// Returns the entropy of a password made of the drawn candidates, in bits.
+    #[allow(clippy::cast_precision_loss)] // candidate counts are way below f64's precision limits
+    #[must_use]
+    pub fn bits(&self) -> f64 {
+        self.indices().len() as f64 * (self.candidates as f64).log2()
+    }
+
+    /// Returns the entropy of a password made of the drawn candidates, as `source`.
+    #[must_use]
+    pub fn entropy(&self, source: EntropySource) -> Entropy {
+        let mut entropy = Entropy::default();
+        entropy.push(source, self.bits());
+        entropy
+    }
+}
+
+/// Draws words of the embedded wordlist from the outcomes, and joins them with spaces.
+///
+/// Words are drawn with replacement, the way diceware does, so that each word holds the same
+/// entropy whatever the others are.
+///
+/// # Examples
+///
+/// ```
+/// use motus::manual::{self, Outcomes};
+///
+/// let rolls = Outcomes::parse_dice("35162 44444", 6).expect("rolls should parse");
+/// let (password, derivation) = manual::memorable_password(&rolls);
+/// assert_eq!(password.split(' ').count(), 2);
+/// // 35162 are the digits 24051 in base 6
+/// assert_eq!(derivation.draws()[0].value, 3487);
+/// ```
+#[must_use]
+pub fn memorable_password(outcomes: &Outcomes) -> (String, Derivation) {
+    let derivation = outcomes.draw(WORDS_LIST.len());
+    let words: Vec<&str> = derivation
+        .indices()
+        .into_iter()
+        .map(|index| WORDS_LIST[index])
+        .collect();
+    (words.join(" "), derivation)
+}
+
+/// Draws characters of `alphabet` from the outcomes.
+///
+/// # Panics
+///
+/// Panics if `alphabet` is empty.
+///
+/// # Examples
+///
+/// ```
+/// use motus::charset::Charset;
+/// use motus::manual::{self, Outcomes};
+///
+/// let rolls = Outcomes::parse_dice("111 112 666", 6).expect("rolls should parse");
+/// let (password, derivation) = manual::random_password(&rolls, &Charset::letters());
+///
+/// // 216 values make four bands of 52 letters; 215, from 666, falls out of them
+/// assert_eq!(password, "ab");
+/// assert_eq!(derivation.draws()[2].index, None);
+/// ```
+#[must_use]
+pub fn random_password(outcomes: &Outcomes, alphabet: &Charset) -> (String, Derivation) {
+    let derivation = outcomes.draw(alphabet.len());
+    let password = derivation
+        .indices()
+        .into_iter()
+        .map(|index| alphabet.chars()[index])
+        .collect();
+    (password, derivation)
+}
+
+/// The errors that can occur while reading physical outcomes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ManualError {
+    /// Dice must have between 2 and [`MAX_SIDES`] sides.
+    InvalidSides(u32),
+    /// The outcome could not be read.
+    InvalidOutcome(String),
+    /// The roll is above the number of sides of the die, or 0.
+    OutOfRange { roll: u32, sides: u32 },
+    /// The card was already drawn from the deck.
+    DuplicateCard(String),
+}
+
+impl Display for ManualError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidSides(sides) => {
+                write!(
+                    f,
+                    "dice must have between 2 and {MAX_SIDES} sides, not {sides}"
+                )
+            }
+            Self::InvalidOutcome(outcome) => write!(f, "invalid outcome {outcome:?}"),
+            Self::OutOfRange { roll, sides } => {
+                write!(f, "a {sides}-sided die cannot roll {roll}")
+            }
+            Self::DuplicateCard(card) => write!(f, "{card} was already drawn from the deck"),
+        }
+    }
+}
+
+impl std::error::Error for ManualError {}
+
+// card_number returns the number of the card with the given normalized label, from 0 for the ace
+// of spades to 51 for the king of clubs.
+fn card_number(label: &str) -> usize {
+    let (rank, suit) = label.split_at(label.len() - 1);
+    let rank = RANKS.iter().position(|&r| r == rank);
+    let suit = SUITS.iter().position(|s| suit.starts_with(*s));
+    match (rank, suit) {
+        (Some(rank), Some(suit)) => suit * RANKS.len() + rank,
+        _ => unreachable!("labels of cards should be normalized"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draw_rejects_incomplete_band() {
+        // Two rolls of a d6 make 36 values: three bands of 10, and 30 to 35 rejected
+        let rolls = Outcomes::parse_dice("11 16 61 66 6", 6).expect("rolls should parse");
+        let derivation = rolls.draw(10);
+
+        let indices: Vec<Option<usize>> = derivation.draws().iter().map(|d| d.index).collect();
+        assert_eq!(indices, [Some(0), Some(5), None, None]);
+        assert!(derivation
+            .draws()
+            .iter()
+            .all(|d| d.range == 36 && d.limit == 30));
+        assert_eq!(derivation.unused(), 1);
+        assert_eq!(derivation.indices(), [0, 5]);
+    }
+
+    #[test]
+    fn test_draw_is_uniform() {
+        // Every combination of three rolls of a d6 maps onto each of the 52 letters 4 times
+        let mut counts = [0; 52];
+        for value in 0..216 {
+            let input = format!("{}{}{}", value / 36 + 1, value / 6 % 6 + 1, value % 6 + 1);
+            let rolls = Outcomes::parse_dice(&input, 6).expect("rolls should parse");
+            for index in rolls.draw(52).indices() {
+                counts[index] += 1;
+            }
+        }
+        assert!(counts.iter().all(|&count| count == 4));
+    }
+
+    #[test]
+    fn test_memorable_password_with_diceware_rolls() {
+        let rolls = Outcomes::parse_dice("11111 66666 11112", 6).expect("rolls should parse");
+        let (password, derivation) = memorable_password(&rolls);
+
+        // 66666 is 7775, above the 7694 words
+        assert_eq!(password, format!("{} {}", WORDS_LIST[0], WORDS_LIST[1]));
+        assert_eq!(derivation.draws().len(), 3);
+        let bits = derivation.entropy(EntropySource::Words).bits();
+        assert!((bits - (7694f64 * 7694f64).log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_parse_dice() {
+        let rolls = Outcomes::parse_dice("12, 20\n7", 20).expect("rolls should parse");
+        let values: Vec<u32> = rolls.outcomes().iter().map(|o| o.digit.value).collect();
+        assert_eq!(values, [11, 19, 6]);
+
+        assert_eq!(
+            Outcomes::parse_dice("1 2 0", 6),
+            Err(ManualError::OutOfRange { roll: 0, sides: 6 })
+        );
+        assert_eq!(
+            Outcomes::parse_dice("1 two", 6),
+            Err(ManualError::InvalidOutcome("two".to_string()))
+        );
+        assert_eq!(
+            Outcomes::parse_dice("1", 1),
+            Err(ManualError::InvalidSides(1))
+        );
+        assert!(Outcomes::parse_dice("", 6)
+            .expect("rolls should parse")
+            .is_empty());
+    }
+
+    #[test]
+    fn test_parse_coins() {
+        let flips = Outcomes::parse(Source::Coins, "HT, tails heads\nhh").expect("should parse");
+        let values: Vec<u32> = flips.outcomes().iter().map(|o| o.digit.value).collect();
+        assert_eq!(values, [1, 0, 0, 1, 1, 1]);
+        assert!((flips.bits() - 6.0).abs() < 1e-9);
+
+        assert_eq!(
+            Outcomes::parse(Source::Coins, "HTX"),
+            Err(ManualError::InvalidOutcome("HTX".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_parse_deck() {
+        // A deck in order is all zeros, the first card of each remaining deck
+        let ordered: Vec<String> = SUITS
+            .iter()
+            .flat_map(|suit| RANKS.iter().map(move |rank| format!("{rank}{suit}")))
+            .collect();
+        let deck = Outcomes::parse(Source::Deck, &ordered.join(" ")).expect("should parse");
+        assert!(deck.outcomes().iter().all(|o| o.digit.value == 0));
+        let factorial: f64 = (1..=52).map(f64::from).map(f64::log2).sum();
+        assert!((deck.bits() - factorial).abs() < 1e-9);
+
+        // Reversed, each card is the last of the remaining deck
+        let reversed: Vec<&str> = ordered.iter().rev().map(String::as_str).collect();
+        let deck = Outcomes::parse(Source::Deck, &reversed.join(" ")).expect("should parse");
+        let values: Vec<u32> = deck.outcomes().iter().map(|o| o.digit.value).collect();
+        assert_eq!(values, (0..52).rev().collect::<Vec<u32>>());
+
+        // Cards already drawn are skipped when numbering the next ones
+        let cards = Outcomes::parse(Source::Deck, "2S as 3s tc").expect("should parse");
+        let digits: Vec<Digit> = cards.outcomes().iter().map(|o| o.digit).collect();
+        assert_eq!(
+            digits,
+            [
+                Digit {
+                    value: 1,
+                    radix: 52
+                },
+                Digit {
+                    value: 0,
+                    radix: 51
+                },
+                Digit {
+                    value: 0,
+                    radix: 50
+                },
+                Digit {
+                    value: 45,
+                    radix: 49
+                },
+            ]
+        );
+
+        let mut deck = Outcomes::new(Source::Deck);
+        deck.push_str("QS").expect("should parse");
+        assert_eq!(
+            deck.push_str("KH qs"),
+            Err(ManualError::DuplicateCard("QS".to_string()))
+        );
+        assert_eq!(deck.len(), 1);
+
+        // Estimated with the 51 cards left, three cards make a word, and five words make 64.5 bits,
+        // but an extra one is asked for to make up for the rejected numbers
+        let (_, derivation) = memorable_password(&deck);
+        assert_eq!(deck.outcomes_needed(&derivation, 64.0), Some(6 * 3 - 1));
+        let whole: Vec<&str> = ordered.iter().map(String::as_str).collect();
+        let whole = Outcomes::parse(Source::Deck, &whole.join(" ")).expect("should parse");
+        let (_, derivation) = memorable_password(&whole);
+        assert_eq!(whole.outcomes_needed(&derivation, 300.0), None);
+        assert!(Outcomes::parse(Source::Deck, "1S").is_err());
+        assert!(Outcomes::parse(Source::Deck, "QX").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! Pattern-based password generation.
+//!
+//! A pattern describes the shape of a password character by character, such as the
+//! `xxxxxx-xxxxxx-xxxxxx` layout of the passwords generated by Apple's iCloud Keychain. Each
+//! placeholder is replaced by a random character of its class, whereas any other character is
+//! kept verbatim:
+//!
+//! * `l` - a lowercase letter
+//! * `u` - an uppercase letter
+//! * `d` - a digit
+//! * `s` - a symbol
+//! * `\` - escapes the next character, for placeholders to be kept verbatim
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::entropy::{Entropy, EntropySource};
+use crate::rng::uniform_char;
+use crate::{LETTER_CHARS, NUMBER_CHARS, SYMBOL_CHARS};
+
+/// A single element of a [`Pattern`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Token {
+    /// A random lowercase letter.
+    Lowercase,
+    /// A random uppercase letter.
+    Uppercase,
+    /// A random digit.
+    Digit,
+    /// A random symbol.
+    Symbol,
+    /// A character kept verbatim.
+    Literal(char),
+}
+
+impl Token {
+    // alphabet returns the characters the token is drawn from, or None for literals.
+    fn alphabet(self) -> Option<&'static [char]> {
+        match self {
+            Self::Lowercase => Some(&LETTER_CHARS[..26]),
+            Self::Uppercase => Some(&LETTER_CHARS[26..]),
+            Self::Digit => Some(NUMBER_CHARS),
+            Self::Symbol => Some(SYMBOL_CHARS),
+            Self::Literal(_) => None,
+        }
+    }
+}
+
+/// The shape of a password, as a sequence of tokens.
+///
+/// # Examples
+///
+/// ```
+/// use motus::pattern::Pattern;
+/// use rand::thread_rng;
+///
+/// let pattern: Pattern = "ulll-dddd".parse().unwrap();
+/// let password = pattern.generate(&mut thread_rng());
+///
+/// assert_eq!(password.len(), 9);
+/// assert_eq!(password.chars().nth(4), Some('-'));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+}
+
+impl Pattern {
+    /// Creates a pattern from the given tokens.
+    #[must_use]
+    pub const fn new(tokens: Vec<Token>) -> Self {
+        Self { tokens }
+    }
+
+    /// Returns the tokens of the pattern.
+    #[must_use]
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Generates a password following the pattern.
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        self.tokens
+            .iter()
+            .map(|token| match (token, token.alphabet()) {
+                (_, Some(alphabet)) => uniform_char(rng, alphabet),
+                (Token::Literal(c), None) => *c,
+                _ => unreachable!("only literals have no alphabet"),
+            })
+            .collect()
+    }
+
+    /// Computes the entropy of a password generated by [`Pattern::generate`].
+    ///
+    /// Literals are assumed to be known to an attacker, and contribute no entropy.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // alphabet sizes are way below f64's precision limits
+    pub fn entropy(&self) -> Entropy {
+        let bits = self
+            .tokens
+            .iter()
+            .filter_map(|token| token.alphabet())
+            .map(|alphabet| (alphabet.len() as f64).log2())
+            .sum();
+
+        let mut entropy = Entropy::default();
+        entropy.push(EntropySource::Characters, bits);
+        entropy
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = PatternError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens = Vec::new();
+        let mut chars = s.chars();
+
+        while let Some(c) = chars.next() {
+            tokens.push(match c {
+                'l' => Token::Lowercase,
+                'u' => Token::Uppercase,
+                'd' => Token::Digit,
+                's' => Token::Symbol,
+                '\\' => Token::Literal(chars.next().ok_or(PatternError::TrailingEscape)?),
+                c => Token::Literal(c),
+            });
+        }
+
+        Ok(Self { tokens })
+    }
+}
+
+// APPLE_CHUNKS and APPLE_CHUNK_LEN describe the layout of Apple-style passwords.
+pub(crate) const APPLE_CHUNKS: usize = 3;
+pub(crate) const APPLE_CHUNK_LEN: usize = 6;
+
+/// Returns a random pattern of the Apple iCloud Keychain style.
+///
+/// The pattern is made of three chunks of six lowercase letters separated by hyphens, where one
+/// of the letters is replaced by an uppercase letter, and another by a digit.
+///
+/// # Examples
+///
+/// ```
+/// use motus::pattern::{apple, Token};
+/// use rand::thread_rng;
+///
+/// let pattern = apple(&mut thread_rng());
+/// assert_eq!(pattern.tokens().len(), 20);
+/// assert_eq!(pattern.tokens().iter().filter(|&&t| t == Token::Digit).count(), 1);
+/// ```
+pub fn apple<R: Rng>(rng: &mut R) -> Pattern {
+    let mut tokens = Vec::new();
+    for chunk in 0..APPLE_CHUNKS {
+        if chunk > 0 {
+            tokens.push(Token::Literal('-'));
+        }
+        tokens.extend([Token::Lowercase; APPLE_CHUNK_LEN]);
+    }
+
+    let letters: Vec<usize> = (0..tokens.len())
+        .filter(|&i| tokens[i] == Token::Lowercase)
+        .collect();
+    let picked: Vec<usize> = letters.choose_multiple(rng, 2).copied().collect();
+    tokens[picked[0]] = Token::Uppercase;
+    tokens[picked[1]] = Token::Digit;
+
+    Pattern::new(tokens)
+}
+
+/// The error returned when parsing an invalid pattern.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PatternError {
+    /// The pattern ends with an escape character, with nothing to escape.
+    TrailingEscape,
+}
+
+impl Display for PatternError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TrailingEscape => write!(f, "the pattern ends with an unfinished escape"),
+        }
+    }
+}
+
+impl std::error::Error for PatternError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn test_parse() {
+        let pattern: Pattern = "lu-d\\ds".parse().expect("pattern should be valid");
+        assert_eq!(
+            pattern.tokens(),
+            &[
+                Token::Lowercase,
+                Token::Uppercase,
+                Token::Literal('-'),
+                Token::Digit,
+                Token::Literal('d'),
+                Token::Symbol,
+            ]
+        );
+
+        assert_eq!("ll\\".parse::<Pattern>(), Err(PatternError::TrailingEscape));
+    }
+
+    #[test]
+    fn test_generate() {
+        let pattern: Pattern = "lu-d\\ds".parse().expect("pattern should be valid");
+        let password: Vec<char> = pattern
+            .generate(&mut StdRng::seed_from_u64(42))
+            .chars()
+            .collect();
+
+        assert!(password[0].is_ascii_lowercase());
+        assert!(password[1].is_ascii_uppercase());
+        assert_eq!(password[2], '-');
+        assert!(password[3].is_ascii_digit());
+        assert_eq!(password[4], 'd');
+        assert!(SYMBOL_CHARS.contains(&password[5]));
+    }
+
+    #[test]
+    fn test_entropy() {
+        let pattern: Pattern = "dd-dd".parse().expect("pattern should be valid");
+        assert!((pattern.entropy().bits() / 4.0 - 10f64.log2()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_apple() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..50 {
+            let password = apple(&mut rng).generate(&mut rng);
+            let chunks: Vec<&str> = password.split('-').collect();
+
+            assert_eq!(chunks.len(), 3);
+            assert!(chunks.iter().all(|chunk| chunk.len() == 6));
+            assert_eq!(password.chars().filter(char::is_ascii_uppercase).count(), 1);
+            assert_eq!(password.chars().filter(char::is_ascii_digit).count(), 1);
+        }
+    }
+}
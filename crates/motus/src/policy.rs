@@ -0,0 +1,902 @@
+//! Password policies.
+//!
+//! A policy is a set of rules generated passwords must follow, such as the constraints legacy
+//! systems put on the passwords they accept. Rules are enforced during generation, character by
+//! character, rather than by generating passwords until one happens to comply: this keeps
+//! generation fast and its entropy predictable, whatever the rules. Deny lists are the exception,
+//! as terms are not known to be denied until they are whole: passwords containing one are drawn
+//! again.
+
+use std::fmt::{Display, Formatter};
+
+use clap::ValueEnum;
+use rand::Rng;
+
+use crate::analysis::homoglyph;
+use crate::charset::SymbolSet;
+use crate::denylist::DenyList;
+use crate::entropy::{self, Entropy, EntropySource};
+use crate::{constrained_random_password, random_charsets};
+
+/// What kind of character a password is allowed to start with.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum StartWith {
+    /// The password must start with a letter, as many legacy systems reject passwords starting
+    /// with a digit or a symbol.
+    Letter,
+    /// The password may start with any character.
+    #[default]
+    Any,
+}
+
+/// The case of the letters of a password.
+///
+/// Some legacy systems compare passwords regardless of case: mixed-case passwords then only make
+/// them look more complex than they are, and normalizing their case keeps their entropy honest.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum Case {
+    /// Every letter is uppercase.
+    Upper,
+    /// Every letter is lowercase.
+    Lower,
+    /// Letters keep the case they were generated with.
+    #[default]
+    Mixed,
+}
+
+impl Case {
+    /// Returns `password` with its letters converted to the case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use motus::policy::Case;
+    ///
+    /// assert_eq!(Case::Lower.apply("Choking-Natural-Dolly"), "choking-natural-dolly");
+    /// assert_eq!(Case::Upper.apply("x7fQ!"), "X7FQ!");
+    /// assert_eq!(Case::Mixed.apply("x7fQ!"), "x7fQ!");
+    /// ```
+    #[must_use]
+    pub fn apply(self, password: &str) -> String {
+        match self {
+            Self::Upper => password.to_uppercase(),
+            Self::Lower => password.to_lowercase(),
+            Self::Mixed => password.to_string(),
+        }
+    }
+
+    /// Returns whether a letter of the case allows `c`.
+    #[must_use]
+    pub const fn allows(self, c: char) -> bool {
+        match self {
+            Self::Upper => !c.is_lowercase(),
+            Self::Lower => !c.is_uppercase(),
+            Self::Mixed => true,
+        }
+    }
+}
+
+/// A class of characters passwords can be required to contain.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CharClass {
+    Lowercase,
+    Uppercase,
+    /// Any letter, whatever its case.
+    Letter,
+    Digit,
+    /// Any character that is neither a letter nor a digit.
+    Symbol,
+}
+
+// CATEGORIES are the character classes complexity rules such as "3 out of 4 categories" count.
+const CATEGORIES: [CharClass; 4] = [
+    CharClass::Lowercase,
+    CharClass::Uppercase,
+    CharClass::Digit,
+    CharClass::Symbol,
+];
+
+impl CharClass {
+    /// Returns whether `c` belongs to the class.
+    #[must_use]
+    pub fn contains(self, c: char) -> bool {
+        match self {
+            Self::Lowercase => c.is_lowercase(),
+            Self::Uppercase => c.is_uppercase(),
+            Self::Letter => c.is_alphabetic(),
+            Self::Digit => c.is_ascii_digit(),
+            Self::Symbol => !c.is_alphanumeric(),
+        }
+    }
+}
+
+impl Display for CharClass {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let class = match self {
+            Self::Lowercase => "lowercase letter",
+            Self::Uppercase => "uppercase letter",
+            Self::Letter => "letter",
+            Self::Digit => "digit",
+            Self::Symbol => "symbol",
+        };
+
+        write!(f, "{class}")
+    }
+}
+
+/// A single rule of a password policy.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// Restricts the first character of the password.
+    StartWith(StartWith),
+    /// Limits how many times a character may appear consecutively, as Active Directory style
+    /// complexity rules do.
+    MaxRepeat(usize),
+    /// Requires the password to be at least this many characters long.
+    MinLength(usize),
+    /// Requires the password to be at most this many characters long.
+    MaxLength(usize),
+    /// Requires the password to contain at least one character of the class.
+    Require(CharClass),
+    /// Requires the password to contain characters from at least this many of the lowercase,
+    /// uppercase, digit, and symbol categories.
+    MinClasses(usize),
+    /// Forbids the characters and sequences of characters that print ambiguously in common
+    /// fonts, such as `0` and `O`, or `rn` and `m`, for passwords written on paper.
+    PrintSafe,
+    /// Requires every letter of the password to be of the case, for systems comparing passwords
+    /// regardless of case.
+    Case(Case),
+}
+
+impl Rule {
+    /// Returns whether the rule allows `candidate` to follow the `preceding` characters of a
+    /// password being generated.
+    ///
+    /// Rules on the password as a whole, such as length or required classes, allow any
+    /// character: they are enforced by [`Policy::allows`], which knows how many characters are
+    /// left to generate.
+    #[must_use]
+    pub fn allows(&self, preceding: &[char], candidate: char) -> bool {
+        match self {
+            Self::StartWith(StartWith::Letter) => {
+                !preceding.is_empty() || candidate.is_alphabetic()
+            }
+            Self::MaxRepeat(max) => {
+                let run = preceding
+                    .iter()
+                    .rev()
+                    .take_while(|&&c| c == candidate)
+                    .count();
+                run < *max
+            }
+            Self::PrintSafe => {
+                !homoglyph::is_ambiguous(candidate)
+                    && !preceding
+                        .last()
+                        .is_some_and(|&previous| homoglyph::forms_lookalike(previous, candidate))
+            }
+            Self::Case(case) => case.allows(candidate),
+            Self::StartWith(StartWith::Any)
+            | Self::MinLength(_)
+            | Self::MaxLength(_)
+            | Self::Require(_)
+            | Self::MinClasses(_) => true,
+        }
+    }
+
+    /// Returns whether the given password complies with the rule.
+    #[must_use]
+    pub fn is_satisfied_by(&self, password: &str) -> bool {
+        let chars: Vec<char> = password.chars().collect();
+        match self {
+            Self::MinLength(min) => chars.len() >= *min,
+            Self::MaxLength(max) => chars.len() <= *max,
+            Self::Require(class) => chars.iter().any(|&c| class.contains(c)),
+            Self::MinClasses(min) => {
+                CATEGORIES
+                    .iter()
+                    .filter(|class| chars.iter().any(|&c| class.contains(c)))
+                    .count()
+                    >= *min
+            }
+            Self::StartWith(_) | Self::MaxRepeat(_) | Self::PrintSafe | Self::Case(_) => {
+                (0..chars.len()).all(|i| self.allows(&chars[..i], chars[i]))
+            }
+        }
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StartWith(StartWith::Letter) => write!(f, "starts with a letter"),
+            Self::StartWith(StartWith::Any) => write!(f, "starts with any character"),
+            Self::MaxRepeat(max) => write!(f, "at most {max} identical characters in a row"),
+            Self::MinLength(min) => write!(f, "at least {min} characters long"),
+            Self::MaxLength(max) => write!(f, "at most {max} characters long"),
+            Self::Require(class @ CharClass::Uppercase) => write!(f, "contains an {class}"),
+            Self::Require(class) => write!(f, "contains a {class}"),
+            Self::MinClasses(min) => write!(
+                f,
+                "contains at least {min} of lowercase letters, uppercase letters, digits and symbols"
+            ),
+            Self::PrintSafe => write!(f, "no characters that print ambiguously"),
+            Self::Case(Case::Upper) => write!(f, "only uppercase letters"),
+            Self::Case(Case::Lower) => write!(f, "only lowercase letters"),
+            Self::Case(Case::Mixed) => write!(f, "letters of any case"),
+        }
+    }
+}
+
+/// A set of rules generated passwords must follow.
+///
+/// # Examples
+///
+/// ```
+/// use motus::policy::{CharClass, Policy, Rule, StartWith};
+/// use rand::thread_rng;
+///
+/// let policy = Policy::new()
+///     .with_rule(Rule::StartWith(StartWith::Letter))
+///     .with_rule(Rule::Require(CharClass::Digit));
+/// let password = policy.random_password(&mut thread_rng(), 12, true, true, &[]);
+///
+/// assert!(password.starts_with(char::is_alphabetic));
+/// assert!(policy.is_satisfied_by(&password));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Policy {
+    rules: Vec<Rule>,
+    deny_list: DenyList,
+    symbol_set: SymbolSet,
+}
+
+impl Policy {
+    /// Creates an empty policy, which allows any password.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a rule to the policy.
+    #[must_use]
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    /// Returns the rules of the policy.
+    #[must_use]
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Forbids passwords containing any of the terms of the deny list.
+    #[must_use]
+    pub fn with_deny_list(mut self, deny_list: DenyList) -> Self {
+        self.deny_list = deny_list;
+        self
+    }
+
+    /// Returns the terms passwords must not contain.
+    #[must_use]
+    pub const fn deny_list(&self) -> &DenyList {
+        &self.deny_list
+    }
+
+    /// Draws the symbols of random passwords from the set, such as the symbols typed alike on
+    /// international keyboards.
+    #[must_use]
+    pub const fn with_symbol_set(mut self, symbol_set: SymbolSet) -> Self {
+        self.symbol_set = symbol_set;
+        self
+    }
+
+    /// Returns the set the symbols of random passwords are drawn from.
+    #[must_use]
+    pub const fn symbol_set(&self) -> SymbolSet {
+        self.symbol_set
+    }
+
+    /// Returns whether `candidate` may follow the `preceding` characters of a password being
+    /// generated, with `remaining` characters left to generate after it.
+    ///
+    /// On top of every rule allowing the candidate, enough characters must be left to satisfy
+    /// the required character classes the password would still be missing.
+    #[must_use]
+    pub fn allows(&self, preceding: &[char], candidate: char, remaining: usize) -> bool {
+        if !self
+            .rules
+            .iter()
+            .all(|rule| rule.allows(preceding, candidate))
+        {
+            return false;
+        }
+
+        let mut chars = preceding.to_vec();
+        chars.push(candidate);
+        self.missing_characters(&chars) <= remaining
+    }
+
+    /// Returns whether the given password complies with every rule of the policy, and contains
+    /// none of the terms of its deny list.
+    #[must_use]
+    pub fn is_satisfied_by(&self, password: &str) -> bool {
+        self.rules.iter().all(|rule| rule.is_satisfied_by(password))
+            && self.deny_list.allows(password)
+    }
+
+    /// Returns the length and character sets options a random password should be generated with
+    /// for it to be able to comply with the policy.
+    ///
+    /// The length is clamped to the policy's bounds, and digits or symbols are enabled when the
+    /// policy requires them.
+    #[must_use]
+    pub fn fit(&self, characters: u32, numbers: bool, symbols: bool) -> (u32, bool, bool) {
+        let mut characters = characters as usize;
+        let (mut numbers, mut symbols) = (numbers, symbols);
+
+        // Letters provide both lowercase and uppercase characters, unless their case is fixed
+        let letter_classes = match self.case() {
+            Case::Mixed => 2,
+            Case::Upper | Case::Lower => 1,
+        };
+        for rule in &self.rules {
+            match rule {
+                Rule::MinLength(min) => characters = characters.max(*min),
+                Rule::MaxLength(max) => characters = characters.min(*max),
+                Rule::Require(CharClass::Digit) => numbers = true,
+                Rule::Require(CharClass::Symbol) => symbols = true,
+                Rule::MinClasses(min) => {
+                    let missing = min.saturating_sub(letter_classes);
+                    if missing >= 1 && !numbers && !symbols {
+                        numbers = true;
+                    }
+                    if missing >= 2 {
+                        (numbers, symbols) = (true, true);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        (
+            u32::try_from(characters).unwrap_or(u32::MAX),
+            numbers,
+            symbols,
+        )
+    }
+
+    /// Returns the case the letters of passwords must be of, [`Case::Mixed`] when the policy
+    /// does not fix it.
+    #[must_use]
+    pub fn case(&self) -> Case {
+        self.rules
+            .iter()
+            .find_map(|rule| match rule {
+                Rule::Case(case) => Some(*case),
+                _ => None,
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns whether some of the policy's rules contradict each other, so that no password can
+    /// comply with it: fixing the case of letters rules out requiring letters of the other case,
+    /// and every character category.
+    #[must_use]
+    pub fn is_contradictory(&self) -> bool {
+        let other_case = match self.case() {
+            Case::Upper => CharClass::Lowercase,
+            Case::Lower => CharClass::Uppercase,
+            Case::Mixed => return false,
+        };
+        self.rules.iter().any(|rule| match rule {
+            Rule::Require(class) => *class == other_case,
+            Rule::MinClasses(min) => *min >= CATEGORIES.len(),
+            _ => false,
+        })
+    }
+
+    /// Returns a rule of the policy no password generated by [`Policy::random_password`] with the
+    /// same options can comply with, as the characters left once `excluded` ones and the ones the
+    /// policy rules out are removed hold none of the class it requires, or too few of them to
+    /// avoid repeating one more times in a row than it allows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use motus::policy::{CharClass, Preset, Rule};
+    ///
+    /// let policy = Preset::PciDss.policy();
+    /// let digits: Vec<char> = ('0'..='9').collect();
+    /// assert_eq!(policy.unsatisfiable_rule(12, true, false, &[]), None);
+    /// assert_eq!(
+    ///     policy.unsatisfiable_rule(12, true, false, &digits),
+    ///     Some(Rule::Require(CharClass::Digit))
+    /// );
+    /// ```
+    #[must_use]
+    pub fn unsatisfiable_rule(
+        &self,
+        characters: u32,
+        numbers: bool,
+        symbols: bool,
+        excluded: &[char],
+    ) -> Option<Rule> {
+        let mut pool: Vec<char> = random_charsets(numbers, symbols, self.symbol_set, excluded)
+            .iter()
+            .flat_map(|(set, _)| set.chars().to_vec())
+            .filter(|&c| {
+                self.rules.iter().all(|rule| {
+                    !matches!(rule, Rule::Case(_) | Rule::PrintSafe) || rule.allows(&[], c)
+                })
+            })
+            .collect();
+        pool.sort_unstable();
+        pool.dedup();
+        let available = |class: CharClass| pool.iter().any(|&c| class.contains(c));
+
+        self.rules.iter().copied().find(|rule| match rule {
+            Rule::Require(class) => !available(*class),
+            Rule::MinClasses(min) => {
+                CATEGORIES.iter().filter(|&&class| available(class)).count() < *min
+            }
+            // A single character can only be repeated, and no character can follow itself
+            Rule::MaxRepeat(max) => characters as usize > *max && (*max == 0 || pool.len() < 2),
+            _ => false,
+        })
+    }
+
+    /// Generates a random password complying with the policy.
+    ///
+    /// The arguments are the same as [`crate::random_password`]'s, which this function behaves
+    /// like when the policy is empty. See [`Policy::fit`] to adjust them to the policy first.
+    /// Passwords containing a term of the deny list are drawn again, as [`DenyList::resample`]
+    /// does.
+    ///
+    /// # Panics
+    ///
+    /// The function may panic in the event that `excluded` contains every available character,
+    /// that the policy rejects every remaining character at some position, which
+    /// [`Policy::unsatisfiable_rule`] tells ahead, or that the terms of the deny list cannot be
+    /// avoided.
+    pub fn random_password<R: Rng>(
+        &self,
+        rng: &mut R,
+        characters: u32,
+        numbers: bool,
+        symbols: bool,
+        excluded: &[char],
+    ) -> String {
+        let charsets = random_charsets(numbers, symbols, self.symbol_set, excluded);
+        self.deny_list
+            .resample(|| constrained_random_password(rng, characters, &charsets, self))
+            .expect("unable to generate a password containing none of the denied terms")
+    }
+
+    /// Computes the entropy of a password generated by [`Policy::random_password`] with the same
+    /// options.
+    ///
+    /// When the password must start with a letter, its first character is drawn uniformly from
+    /// the available letters, rather than from the weighted character sets. Limits on repeated
+    /// characters and required classes only forbid a small share of the possible passwords, and
+    /// are not accounted for. Neither are the sequences of characters print-safe passwords
+    /// forbid, while the ambiguous characters they leave out are, as are the letters of the case
+    /// the policy rules out.
+    #[must_use]
+    pub fn random_entropy(
+        &self,
+        characters: u32,
+        numbers: bool,
+        symbols: bool,
+        excluded: &[char],
+    ) -> Entropy {
+        let mut excluded = excluded.to_vec();
+        if self.rules.contains(&Rule::PrintSafe) {
+            excluded.extend(
+                random_charsets(numbers, symbols, self.symbol_set, &[])
+                    .iter()
+                    .flat_map(|(set, _)| set.chars().to_vec())
+                    .filter(|&c| homoglyph::is_ambiguous(c)),
+            );
+        }
+        let case = self.case();
+        excluded.extend(crate::LETTER_CHARS.iter().filter(|&&c| !case.allows(c)));
+        let excluded = excluded.as_slice();
+
+        let random = |characters, numbers, symbols| {
+            entropy::charsets(
+                characters,
+                &random_charsets(numbers, symbols, self.symbol_set, excluded),
+            )
+        };
+        if characters == 0 || !self.rules.contains(&Rule::StartWith(StartWith::Letter)) {
+            return random(characters, numbers, symbols);
+        }
+
+        let first = random(1, false, false);
+        let rest = random(characters - 1, numbers, symbols);
+        let mut entropy = Entropy::default();
+        entropy.push(EntropySource::Characters, first.bits() + rest.bits());
+        entropy
+    }
+
+    // missing_characters returns the minimum number of characters that must be added to `chars`
+    // for it to satisfy the policy's required classes.
+    fn missing_characters(&self, chars: &[char]) -> usize {
+        let present = |class: CharClass| chars.iter().any(|&c| class.contains(c));
+
+        let mut required: Vec<CharClass> = self
+            .rules
+            .iter()
+            .filter_map(|rule| match rule {
+                Rule::Require(class) if !present(*class) => Some(*class),
+                _ => None,
+            })
+            .collect();
+
+        // A missing lowercase or uppercase character will provide the missing letter too
+        if required.contains(&CharClass::Lowercase) || required.contains(&CharClass::Uppercase) {
+            required.retain(|&class| class != CharClass::Letter);
+        }
+
+        let min_classes = self
+            .rules
+            .iter()
+            .filter_map(|rule| match rule {
+                Rule::MinClasses(min) => Some(*min),
+                _ => None,
+            })
+            .max()
+            .unwrap_or_default();
+
+        // Each missing required class brings a new category along, except for letters when
+        // a letter is already present
+        let present_categories = CATEGORIES.iter().filter(|&&class| present(class)).count();
+        let required_categories = required
+            .iter()
+            .filter(|&&class| class != CharClass::Letter || !present(CharClass::Letter))
+            .count();
+
+        required.len() + min_classes.saturating_sub(present_categories + required_categories)
+    }
+}
+
+/// Well-known password policies.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Preset {
+    /// The default Active Directory domain policy: at least 7 characters, from at least three of
+    /// the lowercase, uppercase, digit and symbol categories.
+    AdDefault,
+    /// NIST SP 800-63B: at least 15 characters for passwords used as a single factor, no
+    /// composition rules, and at most 64 characters, the minimum verifiers must accept.
+    #[value(name = "nist-800-63b")]
+    Nist80063b,
+    /// PCI DSS v4.0 requirement 8.3.6: at least 12 characters, with both letters and digits.
+    PciDss,
+}
+
+impl Preset {
+    /// Returns the policy matching the preset.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use motus::policy::Preset;
+    ///
+    /// let policy = Preset::PciDss.policy();
+    /// assert!(policy.is_satisfied_by("correcthorse7"));
+    /// assert!(!policy.is_satisfied_by("correcthorse"));
+    /// ```
+    #[must_use]
+    pub fn policy(self) -> Policy {
+        let rules: &[Rule] = match self {
+            Self::AdDefault => &[Rule::MinLength(7), Rule::MinClasses(3)],
+            Self::Nist80063b => &[Rule::MinLength(15), Rule::MaxLength(64)],
+            Self::PciDss => &[
+                Rule::MinLength(12),
+                Rule::Require(CharClass::Letter),
+                Rule::Require(CharClass::Digit),
+            ],
+        };
+
+        Policy {
+            rules: rules.to_vec(),
+            ..Policy::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+
+    #[test]
+    fn test_start_with_letter() {
+        let policy = Policy::new().with_rule(Rule::StartWith(StartWith::Letter));
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            let password = policy.random_password(&mut rng, 8, true, true, &[]);
+            assert!(password.starts_with(char::is_alphabetic));
+        }
+    }
+
+    #[test]
+    fn test_start_with_letter_is_satisfied_by() {
+        let rule = Rule::StartWith(StartWith::Letter);
+        assert!(rule.is_satisfied_by("a1!"));
+        assert!(!rule.is_satisfied_by("1a!"));
+        assert!(!rule.is_satisfied_by("!a1"));
+        assert!(Rule::StartWith(StartWith::Any).is_satisfied_by("1a!"));
+    }
+
+    #[test]
+    fn test_max_repeat() {
+        let policy = Policy::new().with_rule(Rule::MaxRepeat(1));
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            // With only 10 digits to pick from, repeats would be frequent without the rule
+            let password = policy.random_password(&mut rng, 30, true, false, crate::LETTER_CHARS);
+            let chars: Vec<char> = password.chars().collect();
+            assert!(chars.windows(2).all(|w| w[0] != w[1]));
+        }
+    }
+
+    #[test]
+    fn test_max_repeat_is_satisfied_by() {
+        let rule = Rule::MaxRepeat(2);
+        assert!(rule.is_satisfied_by("aabbaa"));
+        assert!(!rule.is_satisfied_by("abbb"));
+        assert!(Rule::MaxRepeat(3).is_satisfied_by("abbb"));
+    }
+
+    #[test]
+    fn test_empty_policy_matches_random_password() {
+        let mut rng1 = StdRng::seed_from_u64(42);
+        let mut rng2 = StdRng::seed_from_u64(42);
+
+        assert_eq!(
+            Policy::new().random_password(&mut rng1, 20, true, true, &[]),
+            crate::random_password(&mut rng2, 20, true, true, &[])
+        );
+    }
+
+    #[test]
+    fn test_start_with_letter_entropy() {
+        let policy = Policy::new().with_rule(Rule::StartWith(StartWith::Letter));
+        let entropy = policy.random_entropy(10, true, false, &[]);
+        let unconstrained = entropy::random(10, true, false, &[]);
+
+        assert!(entropy.bits() < unconstrained.bits());
+    }
+
+    #[test]
+    fn test_required_classes() {
+        let policy = Policy::new()
+            .with_rule(Rule::Require(CharClass::Digit))
+            .with_rule(Rule::Require(CharClass::Symbol));
+        let mut rng = StdRng::seed_from_u64(42);
+
+        // Short passwords leave little room, and force the required classes in
+        for _ in 0..100 {
+            let password = policy.random_password(&mut rng, 3, true, true, &[]);
+            assert!(policy.is_satisfied_by(&password), "{password}");
+        }
+    }
+
+    #[test]
+    fn test_min_classes_is_satisfied_by() {
+        let rule = Rule::MinClasses(3);
+        assert!(rule.is_satisfied_by("abC1"));
+        assert!(rule.is_satisfied_by("ab1!"));
+        assert!(!rule.is_satisfied_by("abCD"));
+    }
+
+    #[test]
+    fn test_fit() {
+        let policy = Preset::AdDefault.policy();
+        assert_eq!(policy.fit(4, false, false), (7, true, false));
+
+        let policy = Preset::Nist80063b.policy();
+        assert_eq!(policy.fit(100, false, false), (64, false, false));
+    }
+
+    #[test]
+    fn test_preset_ad_default() {
+        let policy = Preset::AdDefault.policy();
+
+        assert!(policy.is_satisfied_by("Passw0rd"));
+        assert!(policy.is_satisfied_by("pass!w0rd"));
+        // Too short
+        assert!(!policy.is_satisfied_by("Pa$s1"));
+        // Only two categories
+        assert!(!policy.is_satisfied_by("password1"));
+        assert!(!policy.is_satisfied_by("PassWord"));
+    }
+
+    #[test]
+    fn test_preset_nist_800_63b() {
+        let policy = Preset::Nist80063b.policy();
+
+        // No composition rules
+        assert!(policy.is_satisfied_by("correcthorsebatterystaple"));
+        // Too short
+        assert!(!policy.is_satisfied_by("C0rrect!Horse"));
+        // Longer than verifiers are required to accept
+        assert!(!policy.is_satisfied_by(&"a".repeat(65)));
+    }
+
+    #[test]
+    fn test_preset_pci_dss() {
+        let policy = Preset::PciDss.policy();
+
+        assert!(policy.is_satisfied_by("correcthorse7"));
+        // Too short
+        assert!(!policy.is_satisfied_by("horse7batte"));
+        // Missing digits
+        assert!(!policy.is_satisfied_by("correcthorsebattery"));
+        // Missing letters
+        assert!(!policy.is_satisfied_by("123456789012"));
+    }
+
+    #[test]
+    fn test_presets_generate_compliant_passwords() {
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for preset in [Preset::AdDefault, Preset::Nist80063b, Preset::PciDss] {
+            let policy = preset.policy();
+            let (characters, numbers, symbols) = policy.fit(8, false, false);
+
+            for _ in 0..50 {
+                let password = policy.random_password(&mut rng, characters, numbers, symbols, &[]);
+                assert!(policy.is_satisfied_by(&password), "{preset:?}: {password}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_print_safe() {
+        let policy = Policy::new().with_rule(Rule::PrintSafe);
+        assert!(!policy.is_satisfied_by("c0rner"));
+        assert!(!policy.is_satisfied_by("corner"));
+        assert!(policy.is_satisfied_by("canary"));
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let password = policy.random_password(&mut rng, 20, true, true, &[]);
+            assert!(homoglyph::detect(&password).is_empty(), "{password}");
+        }
+
+        assert!(
+            policy.random_entropy(20, true, true, &[]).bits()
+                < Policy::new().random_entropy(20, true, true, &[]).bits()
+        );
+    }
+
+    #[test]
+    fn test_case() {
+        let policy = Policy::new().with_rule(Rule::Case(Case::Lower));
+        assert!(policy.is_satisfied_by("abc1!"));
+        assert!(!policy.is_satisfied_by("aBc1!"));
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let password = policy.random_password(&mut rng, 20, true, true, &[]);
+            assert_eq!(password, Case::Lower.apply(&password));
+        }
+
+        // Lowercase passwords are drawn from 26 letters rather than 52, losing a bit per letter
+        let letters = entropy::random(20, false, false, &[]).bits();
+        assert!(
+            (policy.random_entropy(20, false, false, &[]).bits() - (letters - 20.0)).abs() < 1e-9
+        );
+
+        // Letters only provide one category left
+        assert_eq!(
+            Preset::AdDefault
+                .policy()
+                .with_rule(Rule::Case(Case::Upper))
+                .fit(8, false, false),
+            (8, true, true)
+        );
+        assert!(
+            Preset::AdDefault
+                .policy()
+                .with_rule(Rule::Case(Case::Upper))
+                .fit(8, true, false)
+                .2
+        );
+    }
+
+    #[test]
+    fn test_contradictory_case() {
+        let policy = Policy::new().with_rule(Rule::Require(CharClass::Uppercase));
+        assert!(!policy.is_contradictory());
+        assert!(policy.with_rule(Rule::Case(Case::Lower)).is_contradictory());
+
+        let policy = Policy::new().with_rule(Rule::MinClasses(4));
+        assert!(!policy
+            .clone()
+            .with_rule(Rule::Case(Case::Mixed))
+            .is_contradictory());
+        assert!(policy.with_rule(Rule::Case(Case::Upper)).is_contradictory());
+    }
+
+    #[test]
+    fn test_unsatisfiable_rule() {
+        let digits: Vec<char> = ('0'..='9').collect();
+
+        let policy = Preset::PciDss.policy();
+        assert_eq!(policy.unsatisfiable_rule(12, true, false, &[]), None);
+        assert_eq!(
+            policy.unsatisfiable_rule(12, true, false, &digits),
+            Some(Rule::Require(CharClass::Digit))
+        );
+
+        // Letters and symbols still provide three categories
+        let policy = Preset::AdDefault.policy();
+        assert_eq!(policy.unsatisfiable_rule(12, true, true, &digits), None);
+        assert_eq!(
+            policy
+                .with_rule(Rule::Case(Case::Lower))
+                .unsatisfiable_rule(12, true, true, &digits),
+            Some(Rule::MinClasses(3))
+        );
+
+        let policy = Policy::new().with_rule(Rule::Require(CharClass::Symbol));
+        assert_eq!(
+            policy.unsatisfiable_rule(12, false, false, &[]),
+            Some(Rule::Require(CharClass::Symbol))
+        );
+
+        // A single letter left can only be repeated
+        let excluded: Vec<char> = ('b'..='z').chain('A'..='Z').collect();
+        let policy = Policy::new().with_rule(Rule::MaxRepeat(1));
+        assert_eq!(
+            policy.unsatisfiable_rule(8, false, false, &excluded),
+            Some(Rule::MaxRepeat(1))
+        );
+        assert_eq!(policy.unsatisfiable_rule(1, false, false, &excluded), None);
+        assert_eq!(policy.unsatisfiable_rule(8, true, false, &excluded), None);
+    }
+
+    #[test]
+    fn test_symbol_set() {
+        let policy = Policy::new().with_symbol_set(SymbolSet::IntlSafe);
+        let symbols = SymbolSet::IntlSafe.charset();
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let password = policy.random_password(&mut rng, 20, false, true, &[]);
+            assert!(
+                password
+                    .chars()
+                    .all(|c| c.is_ascii_alphabetic() || symbols.contains(c)),
+                "{password}"
+            );
+        }
+
+        // Intl-safe symbols outnumber the default ones
+        assert!(
+            policy.random_entropy(20, true, true, &[]).bits()
+                > Policy::new().random_entropy(20, true, true, &[]).bits()
+        );
+    }
+
+    #[test]
+    fn test_deny_list() {
+        let policy = Policy::new().with_deny_list(DenyList::new(["a", "b"]));
+        assert!(!policy.is_satisfied_by("cAfe"));
+        assert!(policy.is_satisfied_by("cheese"));
+
+        let mut rng = StdRng::seed_from_u64(42);
+        for _ in 0..100 {
+            let password = policy.random_password(&mut rng, 6, true, false, &[]);
+            assert!(policy.deny_list().allows(&password), "{password}");
+        }
+    }
+}
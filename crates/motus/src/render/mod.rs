@@ -0,0 +1,378 @@
+//! SVG and PNG rendering of QR codes and password cards.
+//!
+//! This module is available with the `render` feature. It produces standalone files, ready to be
+//! printed, without depending on external tools: SVG images are written by hand, and PNG images
+//! are drawn pixel by pixel, using an embedded bitmap font for the characters of password cards.
+
+use std::fmt::{Display, Formatter, Write};
+
+use qrcode::{Color, QrCode};
+
+use crate::card::Card;
+
+mod font;
+
+// QR_QUIET_ZONE is the width, in modules, of the blank margin around QR codes, which scanners
+// need to find them.
+const QR_QUIET_ZONE: usize = 4;
+
+// QR_MODULE_SIZE is the width and height, in pixels, of a module of a QR code.
+const QR_MODULE_SIZE: usize = 8;
+
+// CARD_CELL_SIZE is the width and height, in pixels, of a cell of a password card.
+const CARD_CELL_SIZE: usize = 24;
+
+// CARD_GLYPH_SCALE is how many pixels wide and tall each pixel of the font is drawn on PNG cards.
+const CARD_GLYPH_SCALE: usize = 2;
+
+// SHADE is the gray level of the shaded rows of password cards.
+const SHADE: u8 = 0xE8;
+
+/// The image formats artifacts can be rendered in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Format {
+    Svg,
+    Png,
+}
+
+impl Format {
+    /// Guesses the format of a file from its extension, regardless of its case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use motus::render::Format;
+    ///
+    /// assert_eq!(Format::from_extension("card.SVG"), Some(Format::Svg));
+    /// assert_eq!(Format::from_extension("qr.png"), Some(Format::Png));
+    /// assert_eq!(Format::from_extension("card.pdf"), None);
+    /// ```
+    #[must_use]
+    pub fn from_extension(path: &str) -> Option<Self> {
+        let (_, extension) = path.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "svg" => Some(Self::Svg),
+            "png" => Some(Self::Png),
+            _ => None,
+        }
+    }
+}
+
+/// Renders a QR code encoding `payload` in the given format.
+///
+/// # Errors
+///
+/// Returns an error if the payload is too long to fit in a QR code, or if the PNG image cannot
+/// be encoded.
+///
+/// # Examples
+///
+/// ```
+/// use motus::render::{qr, Format};
+///
+/// let svg = qr("mHYvjgQAKBHBIRYdpPAI", Format::Svg).unwrap();
+/// assert!(svg.starts_with(b"<svg"));
+///
+/// let png = qr("mHYvjgQAKBHBIRYdpPAI", Format::Png).unwrap();
+/// assert!(png.starts_with(b"\x89PNG"));
+/// ```
+pub fn qr(payload: &str, format: Format) -> Result<Vec<u8>, RenderError> {
+    let code = QrCode::new(payload).map_err(|_| RenderError::PayloadTooLong)?;
+    let modules = code.width();
+    let colors = code.to_colors();
+    let is_dark = |x: usize, y: usize| colors[y * modules + x] == Color::Dark;
+    let size = modules + 2 * QR_QUIET_ZONE;
+
+    match format {
+        Format::Svg => {
+            let mut path = String::new();
+            for y in 0..modules {
+                for x in (0..modules).filter(|&x| is_dark(x, y)) {
+                    let _ = write!(path, "M{},{}h1v1h-1z", x + QR_QUIET_ZONE, y + QR_QUIET_ZONE);
+                }
+            }
+
+            let pixels = size * QR_MODULE_SIZE;
+            Ok(format!(
+                concat!(
+                    r#"<svg xmlns="http://www.w3.org/2000/svg" width="{pixels}" height="{pixels}" viewBox="0 0 {size} {size}" shape-rendering="crispEdges">"#,
+                    "\n",
+                    r#"<rect width="{size}" height="{size}" fill="white"/>"#,
+                    "\n",
+                    r#"<path d="{path}" fill="black"/>"#,
+                    "\n</svg>\n"
+                ),
+                pixels = pixels,
+                size = size,
+                path = path
+            )
+            .into_bytes())
+        }
+        Format::Png => {
+            let mut canvas = Canvas::new(size * QR_MODULE_SIZE, size * QR_MODULE_SIZE);
+            for y in 0..modules {
+                for x in (0..modules).filter(|&x| is_dark(x, y)) {
+                    canvas.fill(
+                        (x + QR_QUIET_ZONE) * QR_MODULE_SIZE,
+                        (y + QR_QUIET_ZONE) * QR_MODULE_SIZE,
+                        QR_MODULE_SIZE,
+                        QR_MODULE_SIZE,
+                        0,
+                    );
+                }
+            }
+            canvas.png()
+        }
+    }
+}
+
+/// Renders a password card in the given format, with alternately shaded rows to help following
+/// them.
+///
+/// # Errors
+///
+/// Returns an error if the PNG image cannot be encoded.
+///
+/// # Examples
+///
+/// ```
+/// use motus::card::Card;
+/// use motus::policy::Policy;
+/// use motus::render::{card, Format};
+/// use rand::thread_rng;
+///
+/// let grid = Card::generate(&mut thread_rng(), 8, 26, true, false, &Policy::new());
+///
+/// let png = card(&grid, Format::Png).unwrap();
+/// assert!(png.starts_with(b"\x89PNG"));
+/// ```
+pub fn card(card: &Card, format: Format) -> Result<Vec<u8>, RenderError> {
+    match format {
+        Format::Svg => Ok(card_svg(card).into_bytes()),
+        Format::Png => card_png(card),
+    }
+}
+
+/// Renders a password card as an SVG image.
+#[must_use]
+pub fn card_svg(card: &Card) -> String {
+    let labels = card.column_labels();
+    let width = (labels.len() + 1) * CARD_CELL_SIZE;
+    let height = (card.rows().len() + 1) * CARD_CELL_SIZE;
+    let mut output = String::new();
+
+    let _ = writeln!(
+        output,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="monospace" font-size="16" text-anchor="middle" dominant-baseline="central">"#
+    );
+    let _ = writeln!(
+        output,
+        r#"<rect width="{width}" height="{height}" fill="white"/>"#
+    );
+
+    for (i, row) in card.rows().iter().enumerate() {
+        let y = (i + 1) * CARD_CELL_SIZE;
+        if i % 2 == 0 {
+            let _ = writeln!(
+                output,
+                r##"<rect y="{y}" width="{width}" height="{CARD_CELL_SIZE}" fill="#{SHADE:02x}{SHADE:02x}{SHADE:02x}"/>"##
+            );
+        }
+        svg_cell(&mut output, 0, i + 1, &(i + 1).to_string(), true);
+        for (j, c) in row.chars().enumerate() {
+            svg_cell(&mut output, j + 1, i + 1, &c.to_string(), false);
+        }
+    }
+    for (j, label) in labels.iter().enumerate() {
+        svg_cell(&mut output, j + 1, 0, &label.to_string(), true);
+    }
+
+    output.push_str("</svg>\n");
+    output
+}
+
+// svg_cell writes the text of the SVG card cell at the given column and row, in bold for labels.
+fn svg_cell(output: &mut String, column: usize, row: usize, text: &str, label: bool) {
+    let x = column * CARD_CELL_SIZE + CARD_CELL_SIZE / 2;
+    let y = row * CARD_CELL_SIZE + CARD_CELL_SIZE / 2;
+    let weight = if label { r#" font-weight="bold""# } else { "" };
+
+    let _ = writeln!(
+        output,
+        r#"<text x="{x}" y="{y}"{weight}>{}</text>"#,
+        xml_escape(text)
+    );
+}
+
+// card_png renders a password card as a PNG image.
+fn card_png(card: &Card) -> Result<Vec<u8>, RenderError> {
+    let labels = card.column_labels();
+    let mut canvas = Canvas::new(
+        (labels.len() + 1) * CARD_CELL_SIZE,
+        (card.rows().len() + 1) * CARD_CELL_SIZE,
+    );
+
+    for (i, row) in card.rows().iter().enumerate() {
+        let y = (i + 1) * CARD_CELL_SIZE;
+        if i % 2 == 0 {
+            canvas.fill(0, y, canvas.width, CARD_CELL_SIZE, SHADE);
+        }
+        canvas.text(0, y, &(i + 1).to_string(), true);
+        for (j, c) in row.chars().enumerate() {
+            canvas.text((j + 1) * CARD_CELL_SIZE, y, &c.to_string(), false);
+        }
+    }
+    for (j, label) in labels.iter().enumerate() {
+        canvas.text((j + 1) * CARD_CELL_SIZE, 0, &label.to_string(), true);
+    }
+
+    canvas.png()
+}
+
+// xml_escape escapes the characters that are special in XML text.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// Canvas is a grayscale image, white unless drawn on.
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![0xFF; width * height],
+        }
+    }
+
+    // fill paints the given rectangle with the given gray level, clipped to the canvas.
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, level: u8) {
+        for row in y..(y + height).min(self.height) {
+            for column in x..(x + width).min(self.width) {
+                self.pixels[row * self.width + column] = level;
+            }
+        }
+    }
+
+    // text draws the text centered in the card cell whose top left corner is at the given
+    // position, emboldened by drawing it twice, one pixel apart, for labels.
+    fn text(&mut self, x: usize, y: usize, text: &str, bold: bool) {
+        let glyph_width = font::GLYPH_WIDTH * CARD_GLYPH_SCALE;
+        let glyph_height = font::GLYPH_HEIGHT * CARD_GLYPH_SCALE;
+        let text_width = text.chars().count() * (glyph_width + CARD_GLYPH_SCALE);
+        let left = x + CARD_CELL_SIZE.saturating_sub(text_width) / 2;
+        let top = y + (CARD_CELL_SIZE - glyph_height) / 2;
+
+        for (i, c) in text.chars().enumerate() {
+            let origin = left + i * (glyph_width + CARD_GLYPH_SCALE);
+            for row in 0..font::GLYPH_HEIGHT {
+                for column in (0..font::GLYPH_WIDTH).filter(|&column| font::pixel(c, column, row)) {
+                    let px = origin + column * CARD_GLYPH_SCALE;
+                    let py = top + row * CARD_GLYPH_SCALE;
+                    let width = CARD_GLYPH_SCALE + usize::from(bold);
+                    self.fill(px, py, width, CARD_GLYPH_SCALE, 0);
+                }
+            }
+        }
+    }
+
+    // png encodes the canvas as a grayscale PNG image.
+    fn png(&self) -> Result<Vec<u8>, RenderError> {
+        let mut output = Vec::new();
+        let width = u32::try_from(self.width).map_err(|_| RenderError::TooLarge)?;
+        let height = u32::try_from(self.height).map_err(|_| RenderError::TooLarge)?;
+
+        let mut encoder = png::Encoder::new(&mut output, width, height);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+
+        Ok(output)
+    }
+}
+
+/// The errors that can occur while rendering an artifact.
+#[derive(Debug)]
+pub enum RenderError {
+    /// The payload is too long to fit in a QR code.
+    PayloadTooLong,
+    /// The image is too large to be encoded.
+    TooLarge,
+    /// The PNG image could not be encoded.
+    Png(png::EncodingError),
+}
+
+impl Display for RenderError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::PayloadTooLong => write!(f, "the payload is too long to fit in a QR code"),
+            Self::TooLarge => write!(f, "the image is too large"),
+            Self::Png(err) => write!(f, "unable to encode the PNG image: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for RenderError {}
+
+impl From<png::EncodingError> for RenderError {
+    fn from(err: png::EncodingError) -> Self {
+        Self::Png(err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+    use crate::policy::Policy;
+
+    #[test]
+    fn test_qr() {
+        let svg = qr("mHYvjgQAKBHBIRYdpPAI", Format::Svg).expect("payload should fit");
+        let svg = String::from_utf8(svg).expect("svg should be valid UTF-8");
+        // A version 2 QR code is 25 modules wide, plus the quiet zone
+        assert!(svg.contains(r#"viewBox="0 0 33 33""#));
+
+        assert!(matches!(
+            qr(&"a".repeat(8000), Format::Png),
+            Err(RenderError::PayloadTooLong)
+        ));
+    }
+
+    #[test]
+    fn test_card_png() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let grid = Card::generate(&mut rng, 8, 26, true, true, &Policy::new());
+
+        let png = card(&grid, Format::Png).expect("card should be encoded");
+        let decoder = png::Decoder::new(std::io::Cursor::new(png));
+        let info = decoder
+            .read_info()
+            .expect("png should be valid")
+            .info()
+            .clone();
+        assert_eq!(info.width, 27 * 24);
+        assert_eq!(info.height, 9 * 24);
+    }
+
+    #[test]
+    fn test_card_svg() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let grid = Card::generate(&mut rng, 2, 4, false, false, &Policy::new());
+
+        let svg = card_svg(&grid);
+        assert!(svg.starts_with("<svg "));
+        assert_eq!(svg.matches("<text").count(), 2 * 4 + 2 + 4);
+        assert_eq!(xml_escape("<&>"), "&lt;&amp;&gt;");
+    }
+}
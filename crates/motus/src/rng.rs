@@ -0,0 +1,287 @@
+//! Random number generation: uniform sampling of the characters passwords are made of, and a
+//! generator fit for long-running processes.
+//!
+//! # Uniform sampling
+//!
+//! Every character of a generated password is drawn so that each candidate is exactly as likely
+//! as the others, which the entropy computed by [`crate::entropy`] assumes:
+//!
+//! * indices are never derived with `next_u32() % len`, which favors the lowest indices whenever
+//!   `len` does not divide 2^32. `rand` maps random words to a range with a widening
+//!   multiplication, and rejects the words falling in the incomplete last band, both when
+//!   sampling a single value with `gen_range` and with a [`Uniform`] distribution;
+//! * character sets are weighted with integers, and [`rand::distributions::WeightedIndex`] draws
+//!   a uniform integer below their sum, so that no rounding skews the weights;
+//! * alphabets hold no duplicates, which would make some characters likelier than others.
+//!
+//! The two ways of sampling a range consume random numbers differently: each generator sticks to
+//! the one it always used, for seeded generators to keep generating the same passwords.
+//!
+//! # Long-running processes
+//!
+//! [`SecureReseeding`] is a CSPRNG reseeded from the operating system as it is used and as time
+//! goes by, and as soon as it notices the process forked, so that processes generating passwords
+//! for a long time, or forked from one another, never share a stream of random numbers.
+
+use std::time::{Duration, Instant};
+
+use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::{CryptoRng, Rng, RngCore, SeedableRng};
+
+/// A CSPRNG reseeded from the operating system periodically, and whenever the process forks.
+///
+/// It is reseeded once it generated `threshold` bytes, or `interval` after it was last seeded,
+/// whichever comes first. A forked process inherits the generator's state along with the rest of
+/// its parent's memory: the generator compares the process id it was seeded in to the current
+/// one before generating anything, and reseeds itself when they differ, so that parent and
+/// child never generate the same numbers.
+///
+/// # Examples
+///
+/// ```
+/// use rand::Rng;
+/// use motus::rng::SecureReseeding;
+///
+/// let mut rng = SecureReseeding::new();
+/// let password = motus::random_password(&mut rng, 20, true, true, &[]);
+/// assert_eq!(password.len(), 20);
+/// ```
+#[derive(Debug)]
+pub struct SecureReseeding {
+    inner: StdRng,
+    threshold: u64,
+    interval: Duration,
+    generated: u64,
+    seeded_at: Instant,
+    pid: u32,
+    reseeds: u64,
+}
+
+impl SecureReseeding {
+    /// The number of bytes generated before reseeding, by default.
+    pub const DEFAULT_THRESHOLD: u64 = 64 * 1024;
+
+    /// The time after which the generator is reseeded, by default.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_mins(1);
+
+    /// Creates a generator seeded from the operating system, reseeded with the default
+    /// threshold and interval.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::with_limits(Self::DEFAULT_THRESHOLD, Self::DEFAULT_INTERVAL)
+    }
+
+    /// Creates a generator seeded from the operating system, reseeded once it generated
+    /// `threshold` bytes, or `interval` after it was last seeded.
+    #[must_use]
+    pub fn with_limits(threshold: u64, interval: Duration) -> Self {
+        Self {
+            inner: StdRng::from_entropy(),
+            threshold,
+            interval,
+            generated: 0,
+            seeded_at: Instant::now(),
+            pid: std::process::id(),
+            reseeds: 0,
+        }
+    }
+
+    /// Returns how many times the generator was reseeded since it was created.
+    #[must_use]
+    pub const fn reseeds(&self) -> u64 {
+        self.reseeds
+    }
+
+    // prepare reseeds the generator if the process forked, or if generating `bytes` more bytes
+    // would exceed its limits, and counts them.
+    fn prepare(&mut self, bytes: usize) {
+        let bytes = u64::try_from(bytes).unwrap_or(u64::MAX);
+        if self.pid != std::process::id()
+            || self.generated.saturating_add(bytes) > self.threshold
+            || self.seeded_at.elapsed() >= self.interval
+        {
+            self.inner = StdRng::from_entropy();
+            self.generated = 0;
+            self.seeded_at = Instant::now();
+            self.pid = std::process::id();
+            self.reseeds += 1;
+        }
+        self.generated = self.generated.saturating_add(bytes);
+    }
+}
+
+impl Default for SecureReseeding {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RngCore for SecureReseeding {
+    fn next_u32(&mut self) -> u32 {
+        self.prepare(4);
+        self.inner.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.prepare(8);
+        self.inner.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.prepare(dest.len());
+        self.inner.fill_bytes(dest);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.prepare(dest.len());
+        self.inner.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for SecureReseeding {}
+
+/// Returns a character drawn uniformly from `alphabet`, with `gen_range`.
+///
+/// # Panics
+///
+/// The function panics if `alphabet` is empty. In debug builds, it also panics if `alphabet`
+/// holds duplicates.
+///
+/// # Examples
+///
+/// ```
+/// use rand::thread_rng;
+/// use motus::rng::uniform_char;
+///
+/// let c = uniform_char(&mut thread_rng(), &['a', 'b', 'c']);
+/// assert!(['a', 'b', 'c'].contains(&c));
+/// ```
+pub fn uniform_char<R: Rng + ?Sized>(rng: &mut R, alphabet: &[char]) -> char {
+    debug_assert!(is_distinct(alphabet), "alphabets should hold no duplicates");
+    alphabet[rng.gen_range(0..alphabet.len())]
+}
+
+/// Returns a character drawn uniformly from `candidates`, with a [`Uniform`] distribution.
+///
+/// # Panics
+///
+/// The function panics if `candidates` is empty. In debug builds, it also panics if
+/// `candidates` holds duplicates.
+pub fn uniform_candidate<R: Rng + ?Sized>(rng: &mut R, candidates: &[char]) -> char {
+    debug_assert!(
+        is_distinct(candidates),
+        "candidates should hold no duplicates"
+    );
+    candidates[Uniform::from(0..candidates.len()).sample(rng)]
+}
+
+// is_distinct returns whether the given characters hold no duplicates.
+fn is_distinct(chars: &[char]) -> bool {
+    chars
+        .iter()
+        .enumerate()
+        .all(|(index, c)| !chars[index + 1..].contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    // CHI_SQUARE_9 is the value the chi-square statistic of 10 categories, with 9 degrees of
+    // freedom, only exceeds with a 0.1% probability.
+    const CHI_SQUARE_9: f64 = 27.88;
+
+    // chi_square returns the chi-square statistic of the given counts, expected to be equal.
+    #[allow(clippy::cast_precision_loss)]
+    fn chi_square(counts: &[usize]) -> f64 {
+        let expected = counts.iter().sum::<usize>() as f64 / counts.len() as f64;
+        counts
+            .iter()
+            .map(|&count| (count as f64 - expected).powi(2) / expected)
+            .sum()
+    }
+
+    // counts draws 100000 characters from the 10 digits with `draw`, and counts each of them.
+    fn counts(draw: fn(&mut StdRng, &[char]) -> char) -> Vec<usize> {
+        let digits: Vec<char> = ('0'..='9').collect();
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut counts = vec![0; digits.len()];
+        for _ in 0..100_000 {
+            let c = draw(&mut rng, &digits);
+            counts[c.to_digit(10).expect("c should be a digit") as usize] += 1;
+        }
+        counts
+    }
+
+    #[test]
+    fn test_uniform_char_is_uniform() {
+        let counts = counts(uniform_char);
+        assert!(chi_square(&counts) < CHI_SQUARE_9, "{counts:?}");
+    }
+
+    #[test]
+    fn test_uniform_candidate_is_uniform() {
+        let counts = counts(uniform_candidate);
+        assert!(chi_square(&counts) < CHI_SQUARE_9, "{counts:?}");
+    }
+
+    #[test]
+    fn test_modulo_reduction_is_biased() {
+        // Reducing 8-bit words modulo 10 favors the 6 digits below 256 % 10, which the test
+        // statistic catches, as a sanity check of the tests above
+        let counts = counts(|rng, digits| digits[usize::from(rng.gen::<u8>()) % digits.len()]);
+        assert!(chi_square(&counts) > CHI_SQUARE_9, "{counts:?}");
+    }
+
+    #[test]
+    fn test_secure_reseeding_reseeds_after_threshold() {
+        let mut rng = SecureReseeding::with_limits(16, Duration::from_hours(1));
+        rng.next_u64();
+        rng.next_u64();
+        assert_eq!(rng.reseeds(), 0);
+
+        rng.next_u32();
+        assert_eq!(rng.reseeds(), 1);
+
+        let mut buffer = [0u8; 40];
+        rng.fill_bytes(&mut buffer);
+        assert_eq!(rng.reseeds(), 2);
+    }
+
+    #[test]
+    fn test_secure_reseeding_reseeds_after_interval() {
+        let mut rng = SecureReseeding::with_limits(u64::MAX, Duration::from_millis(20));
+        rng.next_u64();
+        assert_eq!(rng.reseeds(), 0);
+
+        std::thread::sleep(Duration::from_millis(30));
+        rng.next_u64();
+        assert_eq!(rng.reseeds(), 1);
+    }
+
+    #[test]
+    fn test_secure_reseeding_reseeds_after_fork() {
+        let mut rng = SecureReseeding::new();
+        let mut forked = SecureReseeding {
+            inner: rng.inner.clone(),
+            ..SecureReseeding::new()
+        };
+
+        // A forked child holds the same state as its parent, under another process id
+        forked.pid = std::process::id().wrapping_add(1);
+        assert_ne!(rng.next_u64(), forked.next_u64());
+        assert_eq!(rng.reseeds(), 0);
+        assert_eq!(forked.reseeds(), 1);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "duplicates")]
+    fn test_uniform_char_rejects_duplicates() {
+        uniform_char(&mut StdRng::seed_from_u64(42), &['a', 'b', 'a']);
+    }
+}
@@ -0,0 +1,281 @@
+//! A container for secrets, wiped from memory once dropped.
+//!
+//! With the `memlock` feature, the memory holding a [`Secret`] is also locked, with `mlock` on
+//! Unix and `VirtualLock` on Windows, so that it is never swapped to disk, where it would outlive
+//! the process. Locking is best effort: the operating system caps how much memory a process may
+//! lock, and [`Secret::is_locked`] tells whether it succeeded.
+
+use std::fmt::{Debug, Formatter};
+use std::ops::Deref;
+
+use zeroize::Zeroize;
+
+/// A secret string, such as a generated password, wiped from memory once dropped.
+///
+/// The secret is not copied when it is created: the buffer of the given string is the one wiped,
+/// and locked with the `memlock` feature. Its `Debug` implementation does not reveal it.
+///
+/// # Examples
+///
+/// ```
+/// use motus::secret::Secret;
+///
+/// let secret = Secret::new("choking natural dolly".to_string());
+/// assert_eq!(secret.expose(), "choking natural dolly");
+/// assert_eq!(format!("{secret:?}"), "Secret([REDACTED])");
+/// ```
+pub struct Secret {
+    value: String,
+    locked: bool,
+}
+
+impl Secret {
+    /// Takes ownership of `value`, locking the memory holding it with the `memlock` feature.
+    #[must_use]
+    pub fn new(value: String) -> Self {
+        let locked = value.capacity() == 0 || memlock::lock(value.as_ptr(), value.capacity());
+        Self { value, locked }
+    }
+
+    /// Returns the secret itself.
+    #[must_use]
+    pub fn expose(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns whether the memory holding the secret is locked, and cannot be swapped to disk.
+    ///
+    /// It is false without the `memlock` feature, and when the operating system refused to lock
+    /// more memory. An empty secret holds no memory, which cannot be swapped either: it is always
+    /// reported locked, with or without the feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use motus::secret::Secret;
+    ///
+    /// assert!(Secret::new(String::new()).is_locked());
+    /// ```
+    #[must_use]
+    pub const fn is_locked(&self) -> bool {
+        self.locked
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self::new(value)
+    }
+}
+
+impl Deref for Secret {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.expose()
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        let (ptr, capacity) = (self.value.as_ptr(), self.value.capacity());
+        self.value.zeroize();
+        if self.locked && capacity > 0 {
+            memlock::unlock(ptr, capacity);
+        }
+    }
+}
+
+#[cfg(feature = "memlock")]
+mod memlock {
+    use std::collections::BTreeMap;
+    use std::ops::RangeInclusive;
+    use std::sync::{Mutex, PoisonError};
+
+    // PAGES counts the secrets on each locked page, by page number. Unlocking a page unlocks it
+    // entirely, whatever the number of times it was locked, so it is only unlocked once the last
+    // secret on it is dropped.
+    pub(super) static PAGES: Mutex<BTreeMap<usize, usize>> = Mutex::new(BTreeMap::new());
+
+    /// Locks the pages holding the `len` bytes at `ptr`, and returns whether all of them are.
+    pub fn lock(ptr: *const u8, len: usize) -> bool {
+        let mut pages = PAGES.lock().unwrap_or_else(PoisonError::into_inner);
+        let range = page_range(ptr, len);
+
+        for page in range.clone() {
+            let count = pages.entry(page).or_insert(0);
+            if *count == 0 && !sys::lock(page * sys::page_size()) {
+                pages.remove(&page);
+                release(&mut pages, *range.start()..page);
+                return false;
+            }
+            *count += 1;
+        }
+        drop(pages);
+
+        true
+    }
+
+    /// Unlocks the pages holding the `len` bytes at `ptr`, unless other secrets are on them.
+    pub fn unlock(ptr: *const u8, len: usize) {
+        let mut pages = PAGES.lock().unwrap_or_else(PoisonError::into_inner);
+        release(&mut pages, page_range(ptr, len));
+    }
+
+    // release decrements the count of the given pages, and unlocks the ones no secret is on
+    // anymore.
+    fn release(pages: &mut BTreeMap<usize, usize>, range: impl IntoIterator<Item = usize>) {
+        for page in range {
+            let Some(count) = pages.get_mut(&page) else {
+                continue;
+            };
+            *count -= 1;
+            if *count == 0 {
+                pages.remove(&page);
+                sys::unlock(page * sys::page_size());
+            }
+        }
+    }
+
+    // page_range returns the numbers of the pages holding the `len` bytes at `ptr`.
+    fn page_range(ptr: *const u8, len: usize) -> RangeInclusive<usize> {
+        let size = sys::page_size();
+        let start = ptr.addr();
+        start / size..=(start + len - 1) / size
+    }
+
+    #[cfg(unix)]
+    #[allow(unsafe_code)]
+    pub(super) mod sys {
+        use std::sync::OnceLock;
+
+        pub fn page_size() -> usize {
+            static PAGE_SIZE: OnceLock<usize> = OnceLock::new();
+            *PAGE_SIZE.get_or_init(|| {
+                // SAFETY: sysconf has no preconditions
+                usize::try_from(unsafe { libc::sysconf(libc::_SC_PAGESIZE) }).unwrap_or(4096)
+            })
+        }
+
+        pub fn lock(address: usize) -> bool {
+            // SAFETY: mlock only changes how the page is paged, and fails on unmapped ones
+            unsafe { libc::mlock(address as *const libc::c_void, page_size()) == 0 }
+        }
+
+        pub fn unlock(address: usize) {
+            // SAFETY: munlock only changes how the page is paged, and fails on unmapped ones
+            unsafe {
+                libc::munlock(address as *const libc::c_void, page_size());
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    #[allow(unsafe_code)]
+    pub(super) mod sys {
+        use std::ffi::c_void;
+        use std::sync::OnceLock;
+
+        use windows_sys::Win32::System::Memory::{VirtualLock, VirtualUnlock};
+        use windows_sys::Win32::System::SystemInformation::{GetSystemInfo, SYSTEM_INFO};
+
+        pub fn page_size() -> usize {
+            static PAGE_SIZE: OnceLock<usize> = OnceLock::new();
+            *PAGE_SIZE.get_or_init(|| {
+                // SAFETY: GetSystemInfo fills the structure it is given, which outlives the call
+                let info = unsafe {
+                    let mut info: SYSTEM_INFO = std::mem::zeroed();
+                    GetSystemInfo(&mut info);
+                    info
+                };
+                info.dwPageSize as usize
+            })
+        }
+
+        pub fn lock(address: usize) -> bool {
+            // SAFETY: VirtualLock only changes how the page is paged, and fails on unmapped ones
+            unsafe { VirtualLock(address as *const c_void, page_size()) != 0 }
+        }
+
+        pub fn unlock(address: usize) {
+            // SAFETY: VirtualUnlock only changes how the page is paged, and fails on unmapped ones
+            unsafe {
+                VirtualUnlock(address as *const c_void, page_size());
+            }
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub(super) mod sys {
+        pub const fn page_size() -> usize {
+            4096
+        }
+
+        pub const fn lock(_address: usize) -> bool {
+            false
+        }
+
+        pub const fn unlock(_address: usize) {}
+    }
+}
+
+#[cfg(not(feature = "memlock"))]
+mod memlock {
+    pub const fn lock(_ptr: *const u8, _len: usize) -> bool {
+        false
+    }
+
+    pub const fn unlock(_ptr: *const u8, _len: usize) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_secret_exposes_its_value() {
+        let secret = Secret::from("mHYvjgQAKBHBIRYdpPAI".to_string());
+        assert_eq!(secret.expose(), "mHYvjgQAKBHBIRYdpPAI");
+        assert_eq!(secret.len(), 20);
+        assert_eq!(format!("{secret:?}"), "Secret([REDACTED])");
+    }
+
+    #[test]
+    fn test_empty_secret_is_locked() {
+        assert!(Secret::new(String::new()).is_locked());
+        assert!(Secret::new(String::with_capacity(0)).is_locked());
+        assert!(Secret::from(String::new()).expose().is_empty());
+    }
+
+    #[cfg(not(feature = "memlock"))]
+    #[test]
+    fn test_secret_is_not_locked_without_memlock() {
+        assert!(!Secret::new("t0k3n".to_string()).is_locked());
+    }
+
+    #[cfg(all(feature = "memlock", unix))]
+    #[test]
+    fn test_secrets_sharing_a_page_stay_locked() {
+        let first = Secret::new("choking".to_string());
+        let second = Secret::new("natural".to_string());
+        if !first.is_locked() || !second.is_locked() {
+            // The limit on locked memory is too low to lock anything
+            return;
+        }
+
+        let page = second.as_ptr().addr() / memlock::sys::page_size();
+        drop(first);
+
+        let locked = memlock::PAGES
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .contains_key(&page);
+        assert!(locked);
+    }
+}
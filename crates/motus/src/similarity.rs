@@ -0,0 +1,146 @@
+//! Similarity measures between passwords.
+//!
+//! These helpers are used to detect when a newly generated password is too close to one that was
+//! generated before, for instance when rotating a credential: `Summer2023!` and `Summer2024!` are
+//! technically different passwords, but an attacker knowing the former will quickly guess the
+//! latter.
+
+use std::collections::HashSet;
+
+/// The similarity score above which two passwords are considered too similar.
+pub const DEFAULT_THRESHOLD: f64 = 0.5;
+
+/// Computes the Levenshtein edit distance between two strings.
+///
+/// The distance is the minimum number of single character insertions, deletions or substitutions
+/// required to turn `a` into `b`.
+///
+/// # Examples
+///
+/// ```
+/// use motus::similarity::levenshtein;
+///
+/// assert_eq!(levenshtein("kitten", "sitting"), 3);
+/// assert_eq!(levenshtein("motus", "motus"), 0);
+/// ```
+#[must_use]
+pub fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+/// Computes the Jaccard index of the sets of words found in two passwords.
+///
+/// Words are the case-insensitive runs of alphabetic characters, which makes the measure
+/// independent of the separators used in memorable passwords. Passwords holding less than two
+/// words each are not considered passphrases, and yield a score of 0.
+///
+/// # Examples
+///
+/// ```
+/// use motus::similarity::jaccard_words;
+///
+/// let score = jaccard_words("chokehold-nativity-dolly", "Chokehold7Nativity!Throat");
+/// assert_eq!(score, 0.5);
+/// ```
+#[must_use]
+#[allow(clippy::cast_precision_loss)] // word counts are way below f64's precision limits
+pub fn jaccard_words(a: &str, b: &str) -> f64 {
+    let words_a = words(a);
+    let words_b = words(b);
+
+    if words_a.len() < 2 && words_b.len() < 2 {
+        return 0.0;
+    }
+
+    let intersection = words_a.intersection(&words_b).count();
+    let union = words_a.union(&words_b).count();
+
+    intersection as f64 / union as f64
+}
+
+/// Computes a similarity score between 0 (unrelated) and 1 (identical) for two passwords.
+///
+/// The score is the highest of the normalized Levenshtein similarity, which catches small edits
+/// such as incremented digits, and of the Jaccard index over words, which catches passphrases
+/// reusing words in a different order or with different separators.
+///
+/// # Examples
+///
+/// ```
+/// use motus::similarity::{similarity, DEFAULT_THRESHOLD};
+///
+/// assert!(similarity("Summer2023!", "Summer2024!") > DEFAULT_THRESHOLD);
+/// assert!(similarity("mHYvjgQAKBHBIRYdpPAI", "kVHltdbFmiaYXNZmZlTy") < DEFAULT_THRESHOLD);
+/// ```
+#[must_use]
+#[allow(clippy::cast_precision_loss)] // password lengths are way below f64's precision limits
+pub fn similarity(a: &str, b: &str) -> f64 {
+    let max_len = a.chars().count().max(b.chars().count());
+    if max_len == 0 {
+        return 1.0;
+    }
+
+    let edit_similarity = 1.0 - levenshtein(a, b) as f64 / max_len as f64;
+    edit_similarity.max(jaccard_words(a, b))
+}
+
+// words returns the set of lowercase alphabetic words contained in the given password.
+fn words(password: &str) -> HashSet<String> {
+    password
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("abc", ""), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("flaw", "lawn"), 2);
+    }
+
+    #[test]
+    fn test_jaccard_words_ignores_separators_and_case() {
+        let score = jaccard_words("Chokehold Nativity Dolly", "dolly_chokehold_nativity");
+        assert!((score - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_jaccard_words_single_words() {
+        assert!(jaccard_words("password", "password").abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_similarity_identical() {
+        assert!((similarity("abc", "abc") - 1.0).abs() < f64::EPSILON);
+        assert!((similarity("", "") - 1.0).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_similarity_shuffled_passphrase() {
+        let score = similarity(
+            "chokehold nativity dolly ominous throat",
+            "throat ominous dolly nativity chokehold",
+        );
+        assert!(score > DEFAULT_THRESHOLD);
+    }
+}
@@ -0,0 +1,582 @@
+//! Password rules of known sites.
+//!
+//! Many sites constrain the passwords they accept, with a maximum length or a short list of
+//! allowed symbols, and reject random passwords breaking them. motus embeds a database of the
+//! rules of well-known sites, written in the syntax of
+//! [Apple's password rules](https://github.com/apple/password-manager-resources), so that
+//! passwords for them are generated within their constraints:
+//!
+//! ```
+//! use motus::sites::Database;
+//! use rand::thread_rng;
+//!
+//! let (domain, rules) = Database::embedded().lookup("secure.chase.com").unwrap();
+//! assert_eq!(domain, "chase.com");
+//!
+//! let (characters, numbers, symbols) = rules.fit(40, false, false);
+//! let password = rules
+//!     .policy()
+//!     .random_password(&mut thread_rng(), characters, numbers, symbols, &rules.excluded());
+//! assert!(rules.is_satisfied_by(&password));
+//! ```
+//!
+//! Rules requiring one of several classes of characters, such as `required: digit, special`,
+//! are enforced by requiring each of them, which the site accepts just as well.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::LazyLock;
+
+use crate::charset::Charset;
+use crate::policy::{CharClass, Policy, Rule};
+
+// SITES is the embedded database, one site per line.
+const SITES: &str = include_str!("../sites.txt");
+
+static EMBEDDED: LazyLock<Database> =
+    LazyLock::new(|| Database::parse(SITES).expect("the embedded site database should be valid"));
+
+// Class is a class of characters of a password rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Class {
+    Lower,
+    Upper,
+    Digit,
+    Special,
+    AsciiPrintable,
+    Unicode,
+    Custom(Vec<char>),
+}
+
+impl Class {
+    // parse parses a class name, or a set of characters between brackets.
+    fn parse(class: &str) -> Result<Self, SiteError> {
+        match class {
+            "lower" => Ok(Self::Lower),
+            "upper" => Ok(Self::Upper),
+            "digit" => Ok(Self::Digit),
+            "special" => Ok(Self::Special),
+            "ascii-printable" => Ok(Self::AsciiPrintable),
+            "unicode" => Ok(Self::Unicode),
+            _ => class
+                .strip_prefix('[')
+                .and_then(|set| set.strip_suffix(']'))
+                .filter(|set| !set.is_empty())
+                .map(|set| Self::Custom(set.chars().collect()))
+                .ok_or_else(|| SiteError::UnknownClass(class.to_string())),
+        }
+    }
+
+    fn contains(&self, c: char) -> bool {
+        match self {
+            Self::Lower => c.is_ascii_lowercase(),
+            Self::Upper => c.is_ascii_uppercase(),
+            Self::Digit => c.is_ascii_digit(),
+            Self::Special => c.is_ascii_punctuation() || c == ' ',
+            Self::AsciiPrintable => c.is_ascii_graphic() || c == ' ',
+            Self::Unicode => true,
+            Self::Custom(chars) => chars.contains(&c),
+        }
+    }
+
+    // char_class returns the policy class a password must contain a character of to hold one of
+    // this class, or None if any character will do.
+    fn char_class(&self) -> Option<CharClass> {
+        match self {
+            Self::Lower => Some(CharClass::Lowercase),
+            Self::Upper => Some(CharClass::Uppercase),
+            Self::Digit => Some(CharClass::Digit),
+            Self::Special => Some(CharClass::Symbol),
+            Self::AsciiPrintable | Self::Unicode => None,
+            Self::Custom(chars) => {
+                if chars.iter().all(char::is_ascii_digit) {
+                    Some(CharClass::Digit)
+                } else if chars.iter().all(char::is_ascii_alphabetic) {
+                    Some(CharClass::Letter)
+                } else {
+                    Some(CharClass::Symbol)
+                }
+            }
+        }
+    }
+}
+
+impl Display for Class {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lower => write!(f, "lower"),
+            Self::Upper => write!(f, "upper"),
+            Self::Digit => write!(f, "digit"),
+            Self::Special => write!(f, "special"),
+            Self::AsciiPrintable => write!(f, "ascii-printable"),
+            Self::Unicode => write!(f, "unicode"),
+            Self::Custom(chars) => write!(f, "[{}]", chars.iter().collect::<String>()),
+        }
+    }
+}
+
+/// The password rules of a site.
+///
+/// Passwords may only hold the characters of the classes the rules allow or require, or any
+/// printable ASCII character when they mention none.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SiteRules {
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+    max_consecutive: Option<usize>,
+    required: Vec<Vec<Class>>,
+    allowed: Vec<Class>,
+}
+
+impl SiteRules {
+    /// Parses rules in the syntax of Apple's password rules and of the `passwordrules` HTML
+    /// attribute, such as
+    /// `minlength: 8; maxlength: 16; required: lower, upper; required: digit; allowed: [-_!];`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property or a class of characters is unknown, if a length is not an
+    /// integer, or if a set of characters is left open.
+    pub fn parse(rules: &str) -> Result<Self, SiteError> {
+        let mut parsed = Self::default();
+
+        for property in split_outside_sets(rules, ';')? {
+            let property = property.trim();
+            if property.is_empty() {
+                continue;
+            }
+
+            let (name, value) = property
+                .split_once(':')
+                .ok_or_else(|| SiteError::UnknownProperty(property.to_string()))?;
+            let (name, value) = (name.trim(), value.trim());
+            let length = || {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| SiteError::InvalidLength(value.to_string()))
+            };
+            match name {
+                "minlength" => parsed.min_length = Some(length()?),
+                "maxlength" => parsed.max_length = Some(length()?),
+                "max-consecutive" => parsed.max_consecutive = Some(length()?),
+                "required" => parsed.required.push(parse_classes(value)?),
+                "allowed" => parsed.allowed.extend(parse_classes(value)?),
+                _ => return Err(SiteError::UnknownProperty(name.to_string())),
+            }
+        }
+
+        Ok(parsed)
+    }
+
+    /// Returns whether the site accepts `c` in its passwords.
+    #[must_use]
+    pub fn allows(&self, c: char) -> bool {
+        if self.required.is_empty() && self.allowed.is_empty() {
+            return Class::AsciiPrintable.contains(c);
+        }
+
+        self.required
+            .iter()
+            .flatten()
+            .chain(&self.allowed)
+            .any(|class| class.contains(c))
+    }
+
+    /// Returns the policy random passwords for the site must follow.
+    ///
+    /// Required symbols are left out of the policy when the site accepts none of the symbols
+    /// random passwords are drawn from, as no generated password could hold one.
+    #[must_use]
+    pub fn policy(&self) -> Policy {
+        let mut policy = Policy::new();
+        if let Some(min) = self.min_length {
+            policy = policy.with_rule(Rule::MinLength(min));
+        }
+        if let Some(max) = self.max_length {
+            policy = policy.with_rule(Rule::MaxLength(max));
+        }
+        if let Some(max) = self.max_consecutive {
+            policy = policy.with_rule(Rule::MaxRepeat(max));
+        }
+
+        let symbols = Charset::symbols().chars().iter().any(|&c| self.allows(c));
+        let mut classes: Vec<CharClass> = Vec::new();
+        for required in &self.required {
+            let Some(required): Option<Vec<CharClass>> =
+                required.iter().map(Class::char_class).collect()
+            else {
+                continue;
+            };
+            let required = if required.contains(&CharClass::Lowercase)
+                && required.contains(&CharClass::Uppercase)
+                && required.len() == 2
+            {
+                vec![CharClass::Letter]
+            } else {
+                required
+            };
+            for class in required {
+                if !classes.contains(&class) && (class != CharClass::Symbol || symbols) {
+                    classes.push(class);
+                }
+            }
+        }
+
+        classes.into_iter().fold(policy, |policy, class| {
+            policy.with_rule(Rule::Require(class))
+        })
+    }
+
+    /// Returns the characters of random passwords the site rejects, to exclude from them.
+    #[must_use]
+    pub fn excluded(&self) -> Vec<char> {
+        let pool = &(&Charset::letters() | &Charset::numbers()) | &Charset::symbols();
+        pool.chars()
+            .iter()
+            .copied()
+            .filter(|&c| !self.allows(c))
+            .collect()
+    }
+
+    /// Returns the first `required` property of the rules that none of the characters of random
+    /// passwords satisfies once the `excluded` ones are removed, in the syntax of the rules, such
+    /// as `required: [!]`.
+    #[must_use]
+    pub fn unsatisfiable_requirement(&self, excluded: &[char]) -> Option<String> {
+        let pool = (&(&Charset::letters() | &Charset::numbers()) | &Charset::symbols())
+            .minus(&Charset::custom(excluded.iter().copied()));
+        self.required
+            .iter()
+            .find(|classes| {
+                !pool
+                    .chars()
+                    .iter()
+                    .any(|&c| classes.iter().any(|class| class.contains(c)))
+            })
+            .map(|classes| {
+                let classes: Vec<String> = classes.iter().map(ToString::to_string).collect();
+                format!("required: {}", classes.join(", "))
+            })
+    }
+
+    /// Returns the length and character sets options a random password should be generated with
+    /// for the site to accept it, as [`Policy::fit`] does, disabling digits or symbols when the
+    /// site accepts none of them.
+    #[must_use]
+    pub fn fit(&self, characters: u32, numbers: bool, symbols: bool) -> (u32, bool, bool) {
+        let (characters, numbers, symbols) = self.policy().fit(characters, numbers, symbols);
+        let accepts = |set: Charset| set.chars().iter().any(|&c| self.allows(c));
+
+        (
+            characters,
+            numbers && accepts(Charset::numbers()),
+            symbols && accepts(Charset::symbols()),
+        )
+    }
+
+    /// Returns whether the site accepts the given password.
+    #[must_use]
+    pub fn is_satisfied_by(&self, password: &str) -> bool {
+        password.chars().all(|c| self.allows(c))
+            && self.required.iter().all(|classes| {
+                password
+                    .chars()
+                    .any(|c| classes.iter().any(|class| class.contains(c)))
+            })
+            && Policy::new()
+                .with_rule(Rule::MinLength(self.min_length.unwrap_or_default()))
+                .with_rule(Rule::MaxLength(self.max_length.unwrap_or(usize::MAX)))
+                .with_rule(Rule::MaxRepeat(self.max_consecutive.unwrap_or(usize::MAX)))
+                .is_satisfied_by(password)
+    }
+}
+
+/// A database of the password rules of known sites.
+///
+/// The database file holds one site per line: its domain, followed by its rules, as
+/// [`SiteRules::parse`] reads them. Lines starting with `#` are comments.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Database {
+    sites: BTreeMap<String, SiteRules>,
+}
+
+impl Database {
+    /// Returns the database embedded in motus.
+    #[must_use]
+    pub fn embedded() -> &'static Self {
+        &EMBEDDED
+    }
+
+    /// Parses a database file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line holds a domain without rules, or rules [`SiteRules::parse`]
+    /// rejects, along with the number of the line.
+    pub fn parse(content: &str) -> Result<Self, SiteError> {
+        let mut sites = BTreeMap::new();
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let at_line = |error| SiteError::Line {
+                line: index + 1,
+                error: Box::new(error),
+            };
+            let (domain, rules) = line
+                .split_once(char::is_whitespace)
+                .ok_or_else(|| at_line(SiteError::MissingRules(line.to_string())))?;
+            let rules = SiteRules::parse(rules).map_err(at_line)?;
+            sites.insert(domain.to_lowercase(), rules);
+        }
+
+        Ok(Self { sites })
+    }
+
+    /// Returns the database with the sites of `other` added, replacing the rules of the sites
+    /// both know, such as to update the embedded database with a newer file.
+    #[must_use]
+    pub fn merge(mut self, other: Self) -> Self {
+        self.sites.extend(other.sites);
+        self
+    }
+
+    /// Returns the rules of the site at `domain`, or of its closest parent domain, along with the
+    /// domain they were found for.
+    #[must_use]
+    pub fn lookup(&self, domain: &str) -> Option<(&str, &SiteRules)> {
+        let domain = domain.trim_end_matches('.').to_lowercase();
+        let mut candidate = domain.as_str();
+
+        while !candidate.is_empty() {
+            if let Some((domain, rules)) = self.sites.get_key_value(candidate) {
+                return Some((domain, rules));
+            }
+            candidate = candidate.split_once('.').map_or("", |(_, parent)| parent);
+        }
+
+        None
+    }
+
+    /// Returns the number of sites in the database.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.sites.len()
+    }
+
+    /// Returns whether the database holds no site.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.sites.is_empty()
+    }
+}
+
+/// The errors that can occur while parsing site rules.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SiteError {
+    /// The property is not one of the rules' properties.
+    UnknownProperty(String),
+    /// The class is neither a known class name nor a set of characters between brackets.
+    UnknownClass(String),
+    /// The length is not an integer.
+    InvalidLength(String),
+    /// A set of characters is not closed.
+    UnterminatedSet,
+    /// A line of a database file holds a domain without rules.
+    MissingRules(String),
+    /// The error occurred on the given line of a database file.
+    Line { line: usize, error: Box<Self> },
+}
+
+impl Display for SiteError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownProperty(property) => write!(f, "unknown property {property:?}"),
+            Self::UnknownClass(class) => write!(f, "unknown character class {class:?}"),
+            Self::InvalidLength(length) => write!(f, "invalid length {length:?}"),
+            Self::UnterminatedSet => write!(f, "unterminated set of characters"),
+            Self::MissingRules(domain) => write!(f, "no rules given for {domain}"),
+            Self::Line { line, error } => write!(f, "line {line}: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for SiteError {}
+
+// parse_classes parses a comma-separated list of classes of characters.
+fn parse_classes(value: &str) -> Result<Vec<Class>, SiteError> {
+    split_outside_sets(value, ',')?
+        .into_iter()
+        .map(|class| Class::parse(class.trim()))
+        .collect()
+}
+
+// split_outside_sets splits `s` at every `separator` found outside of a set of characters
+// between brackets. A closing bracket only ends a set when it is followed by a separator,
+// whitespace or the end of `s`, so that sets may hold brackets and separators.
+fn split_outside_sets(s: &str, separator: char) -> Result<Vec<&str>, SiteError> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut in_set = false;
+    let mut chars = s.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        if in_set {
+            let closes = chars
+                .peek()
+                .is_none_or(|&(_, next)| next == ',' || next == ';' || next.is_whitespace());
+            if c == ']' && closes {
+                in_set = false;
+            }
+        } else if c == '[' {
+            in_set = true;
+        } else if c == separator {
+            parts.push(&s[start..index]);
+            start = index + c.len_utf8();
+        }
+    }
+
+    if in_set {
+        return Err(SiteError::UnterminatedSet);
+    }
+    parts.push(&s[start..]);
+    Ok(parts)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_rules() {
+        let rules = SiteRules::parse(
+            "minlength: 8; maxlength: 20; max-consecutive: 3; required: lower, upper; required: digit; allowed: [-@#*()+={}/?~;,._];",
+        )
+        .expect("rules should parse");
+
+        assert_eq!(
+            rules.policy().rules(),
+            [
+                Rule::MinLength(8),
+                Rule::MaxLength(20),
+                Rule::MaxRepeat(3),
+                Rule::Require(CharClass::Letter),
+                Rule::Require(CharClass::Digit),
+            ]
+        );
+        assert!(rules.allows(';') && rules.allows(',') && rules.allows('a'));
+        assert!(!rules.allows('!') && !rules.allows('$'));
+        assert_eq!(rules.excluded(), ['!', '$', '%', '^', '&']);
+        assert!(rules.is_satisfied_by("abc;DEF,12"));
+        assert!(!rules.is_satisfied_by("abcDEF!12"));
+        assert!(!rules.is_satisfied_by("abcdefgh"));
+
+        assert_eq!(
+            SiteRules::parse("minlength: eight"),
+            Err(SiteError::InvalidLength("eight".to_string()))
+        );
+        assert_eq!(
+            SiteRules::parse("required: emoji"),
+            Err(SiteError::UnknownClass("emoji".to_string()))
+        );
+        assert_eq!(
+            SiteRules::parse("allowed: [-_"),
+            Err(SiteError::UnterminatedSet)
+        );
+        assert!(SiteRules::parse("forbidden: upper").is_err());
+    }
+
+    #[test]
+    fn test_parse_attribute() {
+        let rules = SiteRules::parse("minlength: 8; required: upper; allowed: [-().&@?ate]")
+            .expect("rules should parse");
+
+        assert_eq!(
+            rules.policy().rules(),
+            [Rule::MinLength(8), Rule::Require(CharClass::Uppercase)]
+        );
+        assert!(rules.allows('Q') && rules.allows('t') && rules.allows('-'));
+        assert!(!rules.allows('b') && !rules.allows('7') && !rules.allows('!'));
+    }
+
+    #[test]
+    fn test_unsatisfiable_requirement() {
+        let rules = SiteRules::parse("minlength: 10; required: [!]; allowed: lower, [#]")
+            .expect("rules should parse");
+        assert_eq!(rules.unsatisfiable_requirement(&rules.excluded()), None);
+
+        // Other symbols do not make up for the one required
+        let mut excluded = rules.excluded();
+        excluded.push('!');
+        assert_eq!(
+            rules.unsatisfiable_requirement(&excluded),
+            Some("required: [!]".to_string())
+        );
+
+        let rules = SiteRules::parse("required: digit, special").expect("rules should parse");
+        let digits: Vec<char> = ('0'..='9').collect();
+        assert_eq!(rules.unsatisfiable_requirement(&digits), None);
+    }
+
+    #[test]
+    fn test_unconstrained_rules_allow_printable_characters() {
+        let rules = SiteRules::parse("minlength: 6; maxlength: 16;").expect("rules should parse");
+        assert!(rules.excluded().is_empty());
+        assert!(rules.allows(' '));
+        assert!(!rules.allows('é'));
+    }
+
+    #[test]
+    fn test_lookup_parent_domains() {
+        let database = Database::parse("# Banks\nexample.com minlength: 12;\n")
+            .expect("database should parse")
+            .merge(Database::parse("login.example.com maxlength: 16;").expect("should parse"));
+
+        assert_eq!(
+            database
+                .lookup("Accounts.Example.COM.")
+                .map(|(domain, _)| domain),
+            Some("example.com")
+        );
+        assert_eq!(
+            database
+                .lookup("sso.login.example.com")
+                .map(|(domain, _)| domain),
+            Some("login.example.com")
+        );
+        assert_eq!(database.lookup("example.org"), None);
+        assert_eq!(
+            Database::parse("\nexample.com\n"),
+            Err(SiteError::Line {
+                line: 2,
+                error: Box::new(SiteError::MissingRules("example.com".to_string()))
+            })
+        );
+    }
+
+    #[test]
+    fn test_embedded_sites_accept_generated_passwords() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let database = Database::embedded();
+        assert!(!database.is_empty());
+
+        for (domain, rules) in &database.sites {
+            for (numbers, symbols) in [(false, false), (true, true)] {
+                let (characters, numbers, symbols) = rules.fit(20, numbers, symbols);
+                let password = rules.policy().random_password(
+                    &mut rng,
+                    characters,
+                    numbers,
+                    symbols,
+                    &rules.excluded(),
+                );
+                assert!(rules.is_satisfied_by(&password), "{domain}: {password}");
+            }
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Spelling of passwords, one character at a time.
+//!
+//! Screen readers read passwords as words, when they can, and may skip over the symbols or the
+//! case of their letters. Spelled out, each character of a password is named along with its
+//! class, such as "capital B", "small c", "digit 4" or "symbol dollar", for the password to be
+//! typed in right when it cannot be seen.
+
+/// Returns the spelling of `c`: its name along with its case or class.
+///
+/// # Examples
+///
+/// ```
+/// use motus::spelling::spell;
+///
+/// assert_eq!(spell('B'), "capital B");
+/// assert_eq!(spell('c'), "small c");
+/// assert_eq!(spell('4'), "digit 4");
+/// assert_eq!(spell('$'), "symbol dollar");
+/// ```
+#[must_use]
+pub fn spell(c: char) -> String {
+    if c.is_uppercase() {
+        format!("capital {c}")
+    } else if c.is_lowercase() {
+        format!("small {c}")
+    } else if c.is_ascii_digit() {
+        format!("digit {c}")
+    } else if c == ' ' {
+        "space".to_string()
+    } else if let Some(name) = symbol_name(c) {
+        format!("symbol {name}")
+    } else {
+        format!("character {c}, code point U+{:04X}", u32::from(c))
+    }
+}
+
+/// Returns the spelling of each character of `text`, in order.
+///
+/// # Examples
+///
+/// ```
+/// use motus::spelling::spell_out;
+///
+/// assert_eq!(
+///     spell_out("Bc4$"),
+///     ["capital B", "small c", "digit 4", "symbol dollar"]
+/// );
+/// ```
+#[must_use]
+pub fn spell_out(text: &str) -> Vec<String> {
+    text.chars().map(spell).collect()
+}
+
+// symbol_name returns the name of an ASCII symbol, as screen readers commonly announce it.
+const fn symbol_name(c: char) -> Option<&'static str> {
+    Some(match c {
+        '!' => "exclamation mark",
+        '"' => "double quote",
+        '#' => "hash",
+        '$' => "dollar",
+        '%' => "percent",
+        '&' => "ampersand",
+        '\'' => "apostrophe",
+        '(' => "left parenthesis",
+        ')' => "right parenthesis",
+        '*' => "asterisk",
+        '+' => "plus",
+        ',' => "comma",
+        '-' => "dash",
+        '.' => "period",
+        '/' => "slash",
+        ':' => "colon",
+        ';' => "semicolon",
+        '<' => "less than",
+        '=' => "equals",
+        '>' => "greater than",
+        '?' => "question mark",
+        '@' => "at",
+        '[' => "left bracket",
+        '\\' => "backslash",
+        ']' => "right bracket",
+        '^' => "caret",
+        '_' => "underscore",
+        '`' => "backtick",
+        '{' => "left brace",
+        '|' => "vertical bar",
+        '}' => "right brace",
+        '~' => "tilde",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spell() {
+        assert_eq!(spell('Z'), "capital Z");
+        assert_eq!(spell('é'), "small é");
+        assert_eq!(spell('0'), "digit 0");
+        assert_eq!(spell(' '), "space");
+        assert_eq!(spell('\\'), "symbol backslash");
+        assert_eq!(spell('€'), "character €, code point U+20AC");
+    }
+
+    #[test]
+    fn test_symbols_are_named() {
+        for c in ('!'..='~').filter(char::is_ascii_punctuation) {
+            assert!(spell(c).starts_with("symbol "), "'{c}' has no name");
+        }
+    }
+}
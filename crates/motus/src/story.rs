@@ -0,0 +1,124 @@
+//! Silly stories helping to remember memorable passwords.
+//!
+//! The words of a memorable password are arranged, in order, into sentences built from a small
+//! set of templates, such as "The CHOKEHOLD at the NATIVITY scared the DOLLY." The more absurd
+//! the story, the easier it is to remember, and the words in it.
+//!
+//! Templates hold `{}` slots, each filled with the next word of the password, in uppercase so
+//! that the words stand out from the rest of the story. Which template tells each part of the
+//! story is derived from the words themselves, so the same password always tells the same story.
+
+use std::fmt::Write;
+
+// TEMPLATES are the sentences stories are made of.
+const TEMPLATES: &[&str] = &[
+    "The {} at the {} scared the {}.",
+    "The {} and the {} argued over the {}.",
+    "Nobody expected the {} to swallow the {}.",
+    "The {} fell in love with the {}.",
+    "The {} traded its {} for a nap.",
+    "Then the {} showed up.",
+    "Everyone blamed the {}.",
+];
+
+/// Tells a story made of the given words, in order.
+///
+/// # Examples
+///
+/// ```
+/// use motus::story::story;
+///
+/// let story = story(&["chokehold", "nativity", "dolly"]);
+/// assert!(story.contains("CHOKEHOLD"));
+/// assert!(story.find("NATIVITY") < story.find("DOLLY"));
+/// ```
+#[must_use]
+pub fn story(words: &[&str]) -> String {
+    let mut story = String::new();
+    let mut remaining = words;
+
+    while let Some(first) = remaining.first() {
+        let candidates: Vec<&str> = TEMPLATES
+            .iter()
+            .copied()
+            .filter(|template| slots(template) <= remaining.len())
+            .collect();
+        let template = candidates[first.bytes().map(usize::from).sum::<usize>() % candidates.len()];
+
+        let (told, rest) = remaining.split_at(slots(template));
+        if !story.is_empty() {
+            story.push(' ');
+        }
+        story.push_str(&fill(template, told));
+        remaining = rest;
+    }
+
+    story
+}
+
+// slots returns the number of words the template takes.
+fn slots(template: &str) -> usize {
+    template.matches("{}").count()
+}
+
+// fill replaces the slots of the template with the given words, in uppercase.
+fn fill(template: &str, words: &[&str]) -> String {
+    let mut sentence = String::new();
+    let mut words = words.iter();
+
+    for (i, part) in template.split("{}").enumerate() {
+        if i > 0 {
+            if let Some(word) = words.next() {
+                let _ = write!(sentence, "{}", word.to_uppercase());
+            }
+        }
+        sentence.push_str(part);
+    }
+
+    sentence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill() {
+        assert_eq!(
+            fill(TEMPLATES[0], &["chokehold", "nativity", "dolly"]),
+            "The CHOKEHOLD at the NATIVITY scared the DOLLY."
+        );
+    }
+
+    #[test]
+    fn test_story_tells_every_word_in_order() {
+        let words = [
+            "chokehold",
+            "nativity",
+            "dolly",
+            "ominous",
+            "throat",
+            "t-shirt",
+            "abacus",
+        ];
+        let story = story(&words);
+
+        let positions: Vec<usize> = words
+            .iter()
+            .map(|word| {
+                story
+                    .find(&word.to_uppercase())
+                    .expect("every word should be in the story")
+            })
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(story, super::story(&words));
+    }
+
+    #[test]
+    fn test_story_single_word() {
+        assert_eq!(slots(TEMPLATES[TEMPLATES.len() - 1]), 1);
+        assert!(story(&["dolly"]).contains("DOLLY"));
+        assert_eq!(story(&[]), "");
+    }
+}
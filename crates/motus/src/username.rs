@@ -0,0 +1,259 @@
+//! Handle-style usernames, for pseudonymous accounts created alongside their passwords.
+//!
+//! A username joins an adjective, a noun and a random number, such as `BrazenWalrus42`. The
+//! adjectives and nouns are the words of the embedded wordlist the glossary tags as descriptions,
+//! and as things or doers, respectively, leaving out the blocklisted ones: usernames are public,
+//! and meant to be shown to anyone.
+//!
+//! Usernames are not secrets, and should not be relied on as such: their entropy is only reported
+//! to tell how likely two generated usernames are to collide.
+
+use std::fmt::{Display, Formatter};
+
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+
+use crate::entropy::{Entropy, EntropySource};
+use crate::glossary::Category;
+use crate::wordlist::{is_family_friendly, WordFilter};
+use crate::{format_memorable_password, JoinStyle, Separator};
+
+/// A generator of handle-style usernames with the given casing, number of digits and maximum
+/// length.
+///
+/// When usernames are capped in length, every adjective and noun pair that fits is equally
+/// likely, so that the entropy reports exactly how many usernames can be generated.
+///
+/// # Examples
+///
+/// ```
+/// use motus::username::Usernames;
+/// use motus::JoinStyle;
+/// use rand::prelude::*;
+///
+/// let usernames = Usernames::new(JoinStyle::Snake, 2, Some(16)).unwrap();
+/// let username = usernames.generate(&mut StdRng::seed_from_u64(42));
+///
+/// assert!(username.len() <= 16);
+/// assert_eq!(username.split('_').count(), 3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Usernames {
+    style: JoinStyle,
+    digits: u32,
+    adjectives: Vec<&'static str>,
+    // nouns are sorted by length, so that the ones fitting after an adjective come first
+    nouns: Vec<&'static str>,
+    // fitting holds, for each adjective, the number of nouns that fit after it
+    fitting: Vec<usize>,
+    distribution: WeightedIndex<usize>,
+}
+
+impl Usernames {
+    /// The largest number of digits a username may end with.
+    pub const MAX_DIGITS: u32 = 9;
+
+    /// Creates a generator of usernames in the given `style`, ending with `digits` random digits,
+    /// and at most `max_length` characters long.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`UsernameError::TooManyDigits`] if `digits` is above [`Self::MAX_DIGITS`], and
+    /// [`UsernameError::TooShort`] if no username fits in `max_length` characters.
+    pub fn new(
+        style: JoinStyle,
+        digits: u32,
+        max_length: Option<usize>,
+    ) -> Result<Self, UsernameError> {
+        if digits > Self::MAX_DIGITS {
+            return Err(UsernameError::TooManyDigits(digits));
+        }
+
+        let filter = WordFilter::new().with_family_friendly(true);
+        let words = filter
+            .words()
+            .into_iter()
+            .filter(|word| word.chars().all(|c| c.is_ascii_lowercase()));
+        let (adjectives, mut nouns): (Vec<_>, Vec<_>) =
+            words.partition(|word| Category::of(word) == Category::Description);
+        nouns.retain(|word| matches!(Category::of(word), Category::Thing | Category::Doer));
+        nouns.sort_by_key(|word| word.len());
+
+        // The budget is the number of characters left for the words themselves
+        let parts = if digits == 0 { 2 } else { 3 };
+        let separator_len = match style.separator() {
+            Separator::None | Separator::Numbers | Separator::NumbersAndSymbols => 0,
+            _ => 1,
+        };
+        let budget =
+            max_length.map(|max| max.saturating_sub(digits as usize + separator_len * (parts - 1)));
+
+        let fitting: Vec<usize> = adjectives
+            .iter()
+            .map(|adjective| {
+                budget.map_or(nouns.len(), |budget| {
+                    let left = budget.saturating_sub(adjective.len());
+                    nouns.partition_point(|noun| noun.len() <= left)
+                })
+            })
+            .collect();
+
+        let shortest = adjectives.iter().map(|word| word.len()).min().unwrap_or(0)
+            + nouns.first().map_or(0, |word| word.len())
+            + digits as usize
+            + separator_len * (parts - 1);
+        let distribution =
+            WeightedIndex::new(&fitting).map_err(|_| UsernameError::TooShort { shortest })?;
+
+        Ok(Self {
+            style,
+            digits,
+            adjectives,
+            nouns,
+            fitting,
+            distribution,
+        })
+    }
+
+    /// Generates a username.
+    ///
+    /// Adjective and noun pairs on the blocklist are drawn again, which rules out so few of them
+    /// that it is not accounted for in the entropy.
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> String {
+        let (adjective, noun) = loop {
+            let index = self.distribution.sample(rng);
+            let adjective = self.adjectives[index];
+            let noun = self.nouns[rng.gen_range(0..self.fitting[index])];
+            if is_family_friendly(&[adjective, noun]) {
+                break (adjective, noun);
+            }
+        };
+
+        let number = (0..self.digits)
+            .map(|_| char::from(b'0' + rng.gen_range(0..10)))
+            .collect::<String>();
+        let mut parts = vec![adjective, noun];
+        if !number.is_empty() {
+            parts.push(&number);
+        }
+
+        format_memorable_password(rng, &parts, self.style, 0, false)
+    }
+
+    /// Returns the number of adjective and noun pairs usernames are drawn from.
+    #[must_use]
+    pub fn pairs(&self) -> usize {
+        self.fitting.iter().sum()
+    }
+
+    /// Returns the entropy of the generated usernames, broken down into the words and digits.
+    #[must_use]
+    #[allow(clippy::cast_precision_loss)] // the number of pairs is way below f64's precision limits
+    pub fn entropy(&self) -> Entropy {
+        let mut entropy = Entropy::default();
+        entropy.push(EntropySource::Words, (self.pairs() as f64).log2());
+        if self.digits > 0 {
+            entropy.push(EntropySource::Digits, f64::from(self.digits) * 10f64.log2());
+        }
+        entropy
+    }
+}
+
+/// The error returned when usernames cannot be generated with the given options.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UsernameError {
+    /// Usernames cannot end with that many digits.
+    TooManyDigits(u32),
+    /// The maximum length is below the length of the shortest username.
+    TooShort { shortest: usize },
+}
+
+impl Display for UsernameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TooManyDigits(digits) => write!(
+                f,
+                "usernames cannot end with {digits} digits, {} at most",
+                Usernames::MAX_DIGITS
+            ),
+            Self::TooShort { shortest } => write!(
+                f,
+                "the shortest username is {shortest} characters long; raise the maximum length or lower the digits"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for UsernameError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_username_parts() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let usernames = Usernames::new(JoinStyle::Kebab, 3, None).expect("defaults should fit");
+
+        for _ in 0..100 {
+            let username = usernames.generate(&mut rng);
+            let parts: Vec<&str> = username.split('-').collect();
+            assert_eq!(parts.len(), 3, "{username}");
+            assert_eq!(Category::of(parts[0]), Category::Description);
+            assert_ne!(Category::of(parts[1]), Category::Description);
+            assert!(parts[2].len() == 3 && parts[2].chars().all(|c| c.is_ascii_digit()));
+        }
+    }
+
+    #[test]
+    fn test_username_casing() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let pascal = Usernames::new(JoinStyle::Pascal, 0, None)
+            .expect("defaults should fit")
+            .generate(&mut rng);
+        assert_eq!(pascal.chars().filter(char::is_ascii_uppercase).count(), 2);
+        assert!(pascal.chars().all(|c| c.is_ascii_alphabetic()));
+
+        let camel = Usernames::new(JoinStyle::Camel, 2, None)
+            .expect("defaults should fit")
+            .generate(&mut rng);
+        assert!(camel.starts_with(|c: char| c.is_ascii_lowercase()));
+        assert!(camel.ends_with(|c: char| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn test_username_max_length() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let unbounded = Usernames::new(JoinStyle::Snake, 2, None).expect("defaults should fit");
+        let bounded = Usernames::new(JoinStyle::Snake, 2, Some(14)).expect("14 should fit");
+
+        assert!(bounded.pairs() < unbounded.pairs());
+        assert!(bounded.entropy().bits() < unbounded.entropy().bits());
+        for _ in 0..100 {
+            assert!(bounded.generate(&mut rng).len() <= 14);
+        }
+    }
+
+    #[test]
+    fn test_username_errors() {
+        assert_eq!(
+            Usernames::new(JoinStyle::Pascal, 10, None).map(|_| ()),
+            Err(UsernameError::TooManyDigits(10))
+        );
+        assert!(matches!(
+            Usernames::new(JoinStyle::Snake, 2, Some(8)),
+            Err(UsernameError::TooShort { shortest: 13 })
+        ));
+    }
+
+    #[test]
+    fn test_username_entropy() {
+        let usernames = Usernames::new(JoinStyle::Pascal, 2, None).expect("defaults should fit");
+        let entropy = usernames.entropy();
+
+        assert_eq!(entropy.components().len(), 2);
+        #[allow(clippy::cast_precision_loss)]
+        let expected = 2.0f64.mul_add(10f64.log2(), (usernames.pairs() as f64).log2());
+        assert!((entropy.bits() - expected).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,675 @@
+//! Restrictions on the words memorable passwords are drawn from.
+//!
+//! By default, memorable passwords are drawn from the whole embedded wordlist. A [`WordFilter`]
+//! narrows it down for specific scenarios, such as passphrases read out loud, at the cost of
+//! some entropy as fewer words remain to choose from.
+//!
+//! A filter can also keep memorable passwords family-friendly, for those shared with customers
+//! or read over the phone: words and combinations of words listed in the embedded blocklist,
+//! offensive or awkward ones, are then left out of them. It can also keep rhyming words and words
+//! that sound alike out of the same password, as [`is_phonetically_distinct`] tells, for them not
+//! to be mixed up when recalling it.
+//!
+//! [`build`] makes custom wordlists out of frequency lists, with the same guarantees as the
+//! embedded one, and [`lint`] checks any wordlist for them.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::{Bound, RangeInclusive};
+
+use itertools::Itertools;
+use rand::Rng;
+
+use crate::entropy::{self, Entropy};
+use crate::{
+    format_memorable_password, get_random_words, get_shuffled_random_words, JoinStyle, Separator,
+    WORDS_LIST,
+};
+
+pub mod lint;
+
+// BLOCKLIST is the raw content of the embedded blocklist file, holding one word, or one pair of
+// space-separated words that must not follow each other, per line.
+const BLOCKLIST: &str = include_str!("../blocklist.txt");
+
+/// Restrictions on the words memorable passwords are drawn from.
+///
+/// # Examples
+///
+/// ```
+/// use motus::wordlist::WordFilter;
+///
+/// let filter = WordFilter::new().with_max_syllables(2);
+///
+/// assert!(filter.allows("dolly"));
+/// assert!(!filter.allows("nativity"));
+/// assert!(filter.words().len() < motus::wordlist_len());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WordFilter {
+    max_syllables: Option<usize>,
+    family_friendly: bool,
+    shuffled_order: bool,
+    phonetically_distinct: bool,
+}
+
+impl WordFilter {
+    /// Creates a filter allowing every word.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_syllables: None,
+            family_friendly: false,
+            shuffled_order: false,
+            phonetically_distinct: false,
+        }
+    }
+
+    /// Only allows words of at most the given number of syllables, as estimated by [`syllables`].
+    #[must_use]
+    pub const fn with_max_syllables(mut self, max: usize) -> Self {
+        self.max_syllables = Some(max);
+        self
+    }
+
+    /// Leaves the words and combinations of words of the blocklist out of passwords, when
+    /// `enabled`.
+    #[must_use]
+    pub const fn with_family_friendly(mut self, enabled: bool) -> Self {
+        self.family_friendly = enabled;
+        self
+    }
+
+    /// Shuffles the chosen words once more before joining them, when `enabled`, so that their
+    /// order is uniformly random and tells nothing about their positions in the wordlist,
+    /// whatever order the sampling algorithm yields them in.
+    ///
+    /// The entropy already accounts for the order of the words, which shuffling leaves unchanged,
+    /// but it draws more random numbers, so that a seed generates other passwords.
+    #[must_use]
+    pub const fn with_shuffled_order(mut self, enabled: bool) -> Self {
+        self.shuffled_order = enabled;
+        self
+    }
+
+    /// Draws the words of passwords again until no two of them rhyme or sound alike, as
+    /// [`is_phonetically_distinct`] tells, when `enabled`.
+    ///
+    /// This rules out about 2% of five-word passwords, which lowers their entropy by a few
+    /// hundredths of a bit, and is not accounted for.
+    #[must_use]
+    pub const fn with_phonetically_distinct(mut self, enabled: bool) -> Self {
+        self.phonetically_distinct = enabled;
+        self
+    }
+
+    /// Returns whether the filter restricts the wordlist at all.
+    #[must_use]
+    pub const fn is_unrestricted(&self) -> bool {
+        self.max_syllables.is_none() && !self.family_friendly
+    }
+
+    /// Returns whether the given word is allowed.
+    #[must_use]
+    pub fn allows(&self, word: &str) -> bool {
+        self.max_syllables.is_none_or(|max| syllables(word) <= max)
+            && !(self.family_friendly && is_blocked(word))
+    }
+
+    /// Returns the words of the wordlist the filter allows.
+    #[must_use]
+    pub fn words(&self) -> Vec<&'static str> {
+        WORDS_LIST
+            .iter()
+            .copied()
+            .filter(|word| self.allows(word))
+            .collect()
+    }
+
+    /// Generates a memorable password whose words are all allowed by the filter.
+    ///
+    /// The arguments are the same as [`crate::memorable_password`]'s, which this function
+    /// behaves like when the filter is unrestricted. When fewer words than `word_count` are
+    /// allowed, the password holds every allowed word.
+    ///
+    /// Family-friendly passwords are drawn again until they hold no blocked word nor blocked
+    /// combination of words, so that blocked words do not shift the odds of the others, and so are
+    /// phonetically distinct ones until their words are.
+    pub fn memorable_password<R: Rng>(
+        &self,
+        rng: &mut R,
+        word_count: usize,
+        separator: Separator,
+        separator_count: usize,
+        capitalize: bool,
+        scramble: bool,
+    ) -> String {
+        self.memorable_password_with_style(
+            rng,
+            word_count,
+            JoinStyle::Separated {
+                separator,
+                capitalize,
+            },
+            separator_count,
+            scramble,
+        )
+    }
+
+    /// Generates a memorable password whose words are all allowed by the filter, cased and joined
+    /// in the given style, as [`crate::memorable_password_with_style`] does.
+    pub fn memorable_password_with_style<R: Rng>(
+        &self,
+        rng: &mut R,
+        word_count: usize,
+        style: JoinStyle,
+        separator_count: usize,
+        scramble: bool,
+    ) -> String {
+        let pool = self.with_family_friendly(false).words();
+        let enough = self.words().len() >= word_count;
+
+        loop {
+            let words = if self.shuffled_order {
+                get_shuffled_random_words(rng, &pool, word_count)
+            } else {
+                get_random_words(rng, &pool, word_count)
+            };
+            let accepted = (!self.family_friendly || is_family_friendly(&words))
+                && (!self.phonetically_distinct || is_phonetically_distinct(&words));
+            if !enough || accepted {
+                return format_memorable_password(rng, &words, style, separator_count, scramble);
+            }
+        }
+    }
+
+    /// Computes the entropy of a password generated by [`WordFilter::memorable_password`] with
+    /// the same options.
+    ///
+    /// Blocked combinations of words only forbid a tiny share of the possible passwords, and
+    /// are not accounted for.
+    #[must_use]
+    pub fn memorable_entropy(
+        &self,
+        word_count: usize,
+        separator: Separator,
+        separator_count: usize,
+        scramble: bool,
+    ) -> Entropy {
+        entropy::words_memorable(
+            self.words().len(),
+            word_count,
+            separator,
+            separator_count,
+            scramble,
+        )
+    }
+
+    /// Returns whether passwords generated by [`WordFilter::memorable_password`] with the same
+    /// options can be split back into words in more than one way, as
+    /// [`entropy::joins_ambiguously`] tells.
+    #[must_use]
+    pub fn joins_ambiguously(
+        &self,
+        separator: Separator,
+        capitalize: bool,
+        scramble: bool,
+    ) -> bool {
+        entropy::joins_ambiguously(&self.words(), separator, capitalize, scramble)
+    }
+}
+
+/// Returns whether the given words, in order, hold no word nor combination of words of the
+/// blocklist.
+///
+/// # Examples
+///
+/// ```
+/// use motus::wordlist::is_family_friendly;
+///
+/// assert!(is_family_friendly(&["chokehold", "nativity", "dolly"]));
+/// assert!(!is_family_friendly(&["dolly", "racism"]));
+/// assert!(!is_family_friendly(&["unzip", "pants"]));
+/// assert!(is_family_friendly(&["pants", "unzip"]));
+/// ```
+#[must_use]
+pub fn is_family_friendly(words: &[&str]) -> bool {
+    !words.iter().any(|word| is_blocked(word))
+        && !words.windows(2).any(|pair| {
+            BLOCKLIST
+                .lines()
+                .filter_map(|line| line.split_once(' '))
+                .any(|(first, second)| {
+                    pair[0].eq_ignore_ascii_case(first) && pair[1].eq_ignore_ascii_case(second)
+                })
+        })
+}
+
+/// Returns whether no two of the given words rhyme or sound alike.
+///
+/// Words sound alike when they have the same [`soundex`] code, and rhyme when they end the same
+/// from their second-to-last group of vowels, or their only one, such as "dolly" and "holly", or
+/// "nation" and "station".
+///
+/// # Examples
+///
+/// ```
+/// use motus::wordlist::is_phonetically_distinct;
+///
+/// assert!(is_phonetically_distinct(&["chokehold", "nativity", "dolly"]));
+/// assert!(!is_phonetically_distinct(&["dolly", "throat", "holly"]));
+/// assert!(!is_phonetically_distinct(&["robert", "rubbered"]));
+/// ```
+#[must_use]
+pub fn is_phonetically_distinct(words: &[&str]) -> bool {
+    words
+        .iter()
+        .tuple_combinations()
+        .all(|(a, b)| soundex(a) != soundex(b) && !rhyme(a).eq_ignore_ascii_case(rhyme(b)))
+}
+
+/// Encodes a word with Soundex, which gives the same code to English words that sound alike.
+///
+/// The code is the first letter of the word, followed by three digits standing for the groups of
+/// consonants it goes on with, padded with zeros: letters standing for similar sounds share a
+/// digit, and vowels are left out. Characters other than ASCII letters are ignored, and a word
+/// without any is encoded as an empty string.
+///
+/// # Examples
+///
+/// ```
+/// use motus::wordlist::soundex;
+///
+/// assert_eq!(soundex("robert"), "R163");
+/// assert_eq!(soundex("rupert"), "R163");
+/// assert_eq!(soundex("dolly"), "D400");
+/// ```
+#[must_use]
+pub fn soundex(word: &str) -> String {
+    let digit = |c: char| match c {
+        'b' | 'f' | 'p' | 'v' => Some('1'),
+        'c' | 'g' | 'j' | 'k' | 'q' | 's' | 'x' | 'z' => Some('2'),
+        'd' | 't' => Some('3'),
+        'l' => Some('4'),
+        'm' | 'n' => Some('5'),
+        'r' => Some('6'),
+        _ => None,
+    };
+
+    let mut letters = word
+        .chars()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_lowercase());
+    let Some(first) = letters.next() else {
+        return String::new();
+    };
+
+    let mut code = String::from(first.to_ascii_uppercase());
+    let mut previous = digit(first);
+    for c in letters {
+        if code.len() == 4 {
+            break;
+        }
+        // Consonants separated by h or w count once, unlike those separated by a vowel
+        if matches!(c, 'h' | 'w') {
+            continue;
+        }
+
+        let current = digit(c);
+        if let Some(d) = current.filter(|_| current != previous) {
+            code.push(d);
+        }
+        previous = current;
+    }
+
+    format!("{code:0<4}")
+}
+
+// rhyme returns the ending of a word from its second-to-last group of vowels, or its only one,
+// which the words rhyming with it end with too.
+fn rhyme(word: &str) -> &str {
+    let is_vowel = |c: char| matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let groups: Vec<usize> = word
+        .char_indices()
+        .zip(std::iter::once(None).chain(word.chars().map(Some)))
+        .filter(|&((_, c), previous)| is_vowel(c) && !previous.is_some_and(is_vowel))
+        .map(|((index, _), _)| index)
+        .collect();
+
+    match groups.as_slice() {
+        [.., start, _] | [start] => &word[*start..],
+        [] => word,
+    }
+}
+
+// is_blocked returns whether the given word is on its own in the blocklist.
+fn is_blocked(word: &str) -> bool {
+    BLOCKLIST
+        .lines()
+        .filter(|line| !line.contains(' '))
+        .any(|blocked| blocked.eq_ignore_ascii_case(word))
+}
+
+/// Builds a wordlist of `size` words out of a frequency list.
+///
+/// The frequency list holds one word per line, optionally followed by whitespace and its number
+/// of occurrences. Lines are otherwise expected from the most to the least frequent word. Words
+/// are lowercased, and the most frequent ones are kept as long as they:
+///
+/// * are made of ASCII letters only, and their length is within `lengths`,
+/// * are not on the embedded blocklist,
+/// * neither are, nor start with, a word kept before them, which drops duplicates and makes the
+///   list a prefix code: passwords whose words are joined without a separator can only be split
+///   back one way.
+///
+/// The same frequency list and options always build the same wordlist, sorted alphabetically.
+///
+/// # Errors
+///
+/// Returns [`BuildError::InvalidCount`] if a number of occurrences is not a number, and
+/// [`BuildError::NotEnoughWords`] if fewer than `size` words are kept.
+///
+/// # Examples
+///
+/// ```
+/// use motus::wordlist::build;
+///
+/// let words = build("tree 30\nstone 20\ntreetop 10\nTable 5\n", 3, 4..=9).unwrap();
+///
+/// assert_eq!(words, ["stone", "table", "tree"]);
+/// ```
+pub fn build(
+    frequency_list: &str,
+    size: usize,
+    lengths: RangeInclusive<usize>,
+) -> Result<Vec<String>, BuildError> {
+    let mut entries = Vec::new();
+    for (index, line) in frequency_list.lines().enumerate() {
+        let mut fields = line.split_whitespace();
+        let Some(word) = fields.next() else {
+            continue;
+        };
+        let count = fields
+            .next()
+            .map(str::parse::<u64>)
+            .transpose()
+            .map_err(|_| BuildError::InvalidCount(index + 1))?;
+        entries.push((word.to_lowercase(), count));
+    }
+
+    // Counts, when given, take precedence over the order of the lines
+    entries.sort_by_key(|(_, count)| std::cmp::Reverse(count.unwrap_or_default()));
+
+    let mut kept = BTreeSet::new();
+    for (word, _) in entries {
+        if kept.len() == size {
+            break;
+        }
+        if !word.bytes().all(|b| b.is_ascii_lowercase())
+            || !lengths.contains(&word.len())
+            || is_blocked(&word)
+        {
+            continue;
+        }
+
+        let has_prefix = (1..=word.len()).any(|len| kept.contains(&word[..len]));
+        let is_prefix = kept
+            .range::<str, _>((Bound::Included(word.as_str()), Bound::Unbounded))
+            .next()
+            .is_some_and(|next: &String| next.starts_with(&word));
+        if !has_prefix && !is_prefix {
+            kept.insert(word);
+        }
+    }
+
+    if kept.len() < size {
+        return Err(BuildError::NotEnoughWords {
+            found: kept.len(),
+            wanted: size,
+        });
+    }
+    Ok(kept.into_iter().collect())
+}
+
+/// The error returned when building a wordlist fails.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    /// The number of occurrences on the given line of the frequency list is not a number.
+    InvalidCount(usize),
+    /// Fewer words than wanted passed the pipeline.
+    NotEnoughWords { found: usize, wanted: usize },
+}
+
+impl Display for BuildError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidCount(line) => write!(
+                f,
+                "the number of occurrences on line {line} of the frequency list is not a number"
+            ),
+            Self::NotEnoughWords { found, wanted } => write!(
+                f,
+                "only {found} words of the frequency list qualify, {wanted} are needed; lower the size or widen the lengths"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+/// Estimates the number of syllables of an English word.
+///
+/// The estimation counts groups of consecutive vowels, discounting the usual silent endings,
+/// such as the final "e" of "stone" or the "ed" of "jumped". It is right for most words, and
+/// off by one for some others.
+///
+/// # Examples
+///
+/// ```
+/// use motus::wordlist::syllables;
+///
+/// assert_eq!(syllables("throat"), 1);
+/// assert_eq!(syllables("ominous"), 3);
+/// ```
+#[must_use]
+pub fn syllables(word: &str) -> usize {
+    let word = word.to_ascii_lowercase();
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+
+    let mut count = 0;
+    let mut previous_is_vowel = false;
+    for c in word.chars() {
+        let vowel = is_vowel(c);
+        if vowel && !previous_is_vowel {
+            count += 1;
+        }
+        previous_is_vowel = vowel;
+    }
+
+    // The letter before a silent ending, which must not be a vowel
+    let before = |suffix: &str| {
+        word.strip_suffix(suffix)
+            .and_then(|stem| stem.chars().last())
+            .filter(|c| !is_vowel(*c))
+    };
+    let silent = before("e").is_some_and(|c| c != 'l')
+        || before("ed").is_some_and(|c| c != 't' && c != 'd')
+        || before("es").is_some_and(|c| !matches!(c, 's' | 'x' | 'z' | 'c' | 'g' | 'h'));
+    if silent && count > 1 {
+        count -= 1;
+    }
+
+    count.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::prelude::*;
+
+    use super::*;
+
+    #[test]
+    fn test_syllables() {
+        for (word, expected) in [
+            ("dolly", 2),
+            ("throat", 1),
+            ("stone", 1),
+            ("table", 2),
+            ("flanked", 1),
+            ("delighted", 3),
+            ("volumes", 2),
+            ("boxes", 2),
+            ("nativity", 4),
+            ("abacus", 3),
+        ] {
+            assert_eq!(syllables(word), expected, "{word}");
+        }
+    }
+
+    #[test]
+    fn test_word_filter() {
+        assert!(WordFilter::new().is_unrestricted());
+        assert_eq!(WordFilter::new().words().len(), WORDS_LIST.len());
+
+        let filter = WordFilter::new().with_max_syllables(1);
+        assert!(!filter.is_unrestricted());
+        assert!(filter.words().iter().all(|word| syllables(word) == 1));
+    }
+
+    #[test]
+    fn test_blocklist_words_are_in_wordlist() {
+        for word in BLOCKLIST.lines().flat_map(|line| line.split(' ')) {
+            assert!(WORDS_LIST.contains(&word), "{word} is not in the wordlist");
+        }
+    }
+
+    #[test]
+    fn test_build() {
+        let frequency_list = "the\nstone 3\nstones 9\nracism 8\n\nstonework 7\nTREE 5\n\
+                              caf\u{e9} 4\ntree 2\ntreetop 1\ntable 0\n";
+
+        // "stone" is dropped for following "stones", despite being on an earlier line
+        let words = build(frequency_list, 3, 4..=9).expect("the wordlist should build");
+        assert_eq!(words, ["stones", "stonework", "tree"]);
+        assert!(lint::lint(words.iter().map(String::as_str)).is_prefix_free());
+        assert_eq!(
+            build(frequency_list, 4, 4..=9).expect("the wordlist should build"),
+            ["stones", "stonework", "table", "tree"]
+        );
+        assert_eq!(
+            build(frequency_list, 5, 4..=9),
+            Err(BuildError::NotEnoughWords {
+                found: 4,
+                wanted: 5
+            })
+        );
+        assert_eq!(
+            build("stone 3\ntree many\n", 1, 4..=9),
+            Err(BuildError::InvalidCount(2))
+        );
+    }
+
+    #[test]
+    fn test_family_friendly_filter() {
+        let filter = WordFilter::new().with_family_friendly(true);
+        assert!(!filter.is_unrestricted());
+        assert!(!filter.allows("racism"));
+        assert!(filter.allows("dolly"));
+        assert!(WordFilter::new().allows("racism"));
+
+        // Blocked words never show up, whatever the seed
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let password =
+                filter.memorable_password(&mut rng, 15, Separator::Space, 1, false, false);
+            let words: Vec<&str> = password.split(' ').collect();
+            assert!(is_family_friendly(&words), "{password}");
+        }
+    }
+
+    #[test]
+    fn test_soundex() {
+        for (word, expected) in [
+            ("robert", "R163"),
+            ("rupert", "R163"),
+            ("rubin", "R150"),
+            ("ashcraft", "A261"),
+            ("tymczak", "T522"),
+            ("pfister", "P236"),
+            ("honeyman", "H555"),
+            ("Lee", "L000"),
+            ("", ""),
+        ] {
+            assert_eq!(soundex(word), expected, "{word}");
+        }
+    }
+
+    #[test]
+    fn test_rhyme() {
+        for (word, expected) in [
+            ("dolly", "olly"),
+            ("throat", "oat"),
+            ("stone", "one"),
+            ("nation", "ation"),
+            ("chokehold", "ehold"),
+            ("rhythm", "ythm"),
+            ("nth", "nth"),
+        ] {
+            assert_eq!(rhyme(word), expected, "{word}");
+        }
+    }
+
+    #[test]
+    fn test_phonetically_distinct_filter() {
+        let filter = WordFilter::new().with_phonetically_distinct(true);
+        assert!(filter.is_unrestricted());
+
+        for seed in 0..200 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let password =
+                filter.memorable_password(&mut rng, 15, Separator::Space, 1, false, false);
+            let words: Vec<&str> = password.split(' ').collect();
+            assert!(is_phonetically_distinct(&words), "{password}");
+        }
+    }
+
+    #[test]
+    fn test_memorable_password() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let password = WordFilter::new().with_max_syllables(2).memorable_password(
+            &mut rng,
+            6,
+            Separator::Space,
+            1,
+            false,
+            false,
+        );
+
+        assert_eq!(password.split(' ').count(), 6);
+        assert!(password.split(' ').all(|word| syllables(word) <= 2));
+
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut same_rng = StdRng::seed_from_u64(42);
+        assert_eq!(
+            WordFilter::new().memorable_password(&mut rng, 5, Separator::Hyphen, 1, true, false),
+            crate::memorable_password(&mut same_rng, 5, Separator::Hyphen, 1, true, false)
+        );
+    }
+
+    #[test]
+    fn test_memorable_entropy() {
+        let filtered = WordFilter::new().with_max_syllables(2).memorable_entropy(
+            5,
+            Separator::Space,
+            1,
+            false,
+        );
+        let unrestricted = WordFilter::new().memorable_entropy(5, Separator::Space, 1, false);
+
+        assert!(filtered.bits() < unrestricted.bits());
+        assert!(
+            (unrestricted.bits() - entropy::memorable(5, Separator::Space, 1, false).bits()).abs()
+                < 1e-9
+        );
+    }
+}
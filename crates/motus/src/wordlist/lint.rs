@@ -0,0 +1,131 @@
+//! Checks of the properties wordlists must have for memorable passwords to hold the entropy they
+//! are credited with.
+//!
+//! This module only depends on the standard library, for the build script to check the embedded
+//! wordlist with it too.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The outcome of checking a wordlist.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Lint {
+    /// The number of distinct words of the list.
+    pub words: usize,
+    /// The words found again further down the list, along with the line they are repeated on.
+    pub duplicates: Vec<(usize, String)>,
+    /// The words holding characters other than ASCII ones, along with their line.
+    pub non_ascii: Vec<(usize, String)>,
+    /// The pairs of words the first of which is a prefix of the second, such as "tree" and
+    /// "treetop".
+    pub prefixes: Vec<(String, String)>,
+    /// The number of distinct words of each length, in characters.
+    pub lengths: BTreeMap<usize, usize>,
+}
+
+impl Lint {
+    /// Returns whether the list holds neither duplicates nor words with non-ASCII characters,
+    /// which would credit passwords with more entropy than they have, or be typed differently
+    /// across keyboards.
+    #[must_use]
+    pub const fn is_valid(&self) -> bool {
+        self.duplicates.is_empty() && self.non_ascii.is_empty()
+    }
+
+    /// Returns whether no word of the list is a prefix of another, so that passwords whose words
+    /// are joined without a separator can only be split back one way.
+    #[must_use]
+    pub const fn is_prefix_free(&self) -> bool {
+        self.prefixes.is_empty()
+    }
+}
+
+/// Checks the given words, one per line of a wordlist, skipping blank lines.
+///
+/// # Examples
+///
+/// ```
+/// use motus::wordlist::lint::lint;
+///
+/// let lint = lint("tree\ntreetop\nstone\nstone\n".lines());
+///
+/// assert!(!lint.is_valid());
+/// assert!(!lint.is_prefix_free());
+/// assert_eq!(lint.words, 3);
+/// ```
+pub fn lint<'a>(words: impl IntoIterator<Item = &'a str>) -> Lint {
+    let mut lint = Lint::default();
+    let mut distinct = BTreeSet::new();
+
+    for (index, word) in words.into_iter().enumerate() {
+        let word = word.trim();
+        if word.is_empty() {
+            continue;
+        }
+        if !word.is_ascii() {
+            lint.non_ascii.push((index + 1, word.to_string()));
+        }
+        if !distinct.insert(word) {
+            lint.duplicates.push((index + 1, word.to_string()));
+        }
+    }
+
+    // Words sharing a prefix are contiguous once sorted, right after the prefix itself
+    let sorted: Vec<&str> = distinct.into_iter().collect();
+    for (index, word) in sorted.iter().enumerate() {
+        *lint.lengths.entry(word.chars().count()).or_default() += 1;
+        lint.prefixes.extend(
+            sorted[index + 1..]
+                .iter()
+                .take_while(|longer| longer.starts_with(word))
+                .map(|longer| ((*word).to_string(), (*longer).to_string())),
+        );
+    }
+    lint.words = sorted.len();
+
+    lint
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint() {
+        let lint = lint([
+            "tree",
+            "stone",
+            "",
+            "treetop",
+            "caf\u{e9}",
+            "tree",
+            "trees",
+            "st",
+        ]);
+
+        assert_eq!(lint.words, 6);
+        assert_eq!(lint.duplicates, [(6, "tree".to_string())]);
+        assert_eq!(lint.non_ascii, [(5, "caf\u{e9}".to_string())]);
+        assert_eq!(
+            lint.prefixes,
+            [
+                ("st".to_string(), "stone".to_string()),
+                ("tree".to_string(), "trees".to_string()),
+                ("tree".to_string(), "treetop".to_string()),
+            ]
+        );
+        assert_eq!(
+            lint.lengths,
+            BTreeMap::from([(2, 1), (4, 2), (5, 2), (7, 1)])
+        );
+        assert!(!lint.is_valid());
+        assert!(!lint.is_prefix_free());
+    }
+
+    #[test]
+    fn test_lint_clean_list() {
+        let lint = lint("stone\ntable\ntree\n".lines());
+
+        assert!(lint.is_valid());
+        assert!(lint.is_prefix_free());
+    }
+}
@@ -0,0 +1,223 @@
+//! Property tests asserting the invariants of every password generator over arbitrary inputs.
+//!
+//! Lengths are kept below a few hundred characters: generation is linear in the length, and
+//! larger ones would only slow the suite down without exercising anything new.
+
+use std::collections::HashSet;
+
+use clap::ValueEnum;
+use motus::card::{Card, MAX_COLUMNS};
+use motus::charset::Charset;
+use motus::policy::{Policy, Preset};
+use motus::wordlist::WordFilter;
+use motus::{
+    apple_password, charset_password, code_password, memorable_password, pin_password,
+    random_password, Radix, Separator,
+};
+use proptest::prelude::*;
+use proptest::sample::{select, subsequence};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+// POOL is every character random passwords may contain.
+const POOL: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789!@#$%^&*()";
+
+// sorted returns the characters of the word in order, to compare words regardless of scrambling.
+fn sorted(word: &str) -> Vec<char> {
+    let mut chars: Vec<char> = word.chars().collect();
+    chars.sort_unstable();
+    chars
+}
+
+// word_separator returns the separators words can be told apart with, as some words of the list
+// hold hyphens themselves, and words joined with no separator cannot be split on one.
+fn word_separator() -> impl Strategy<Value = Separator> {
+    select(
+        Separator::value_variants()
+            .iter()
+            .copied()
+            .filter(|&separator| !matches!(separator, Separator::Hyphen | Separator::None))
+            .collect::<Vec<_>>(),
+    )
+}
+
+proptest! {
+    #[test]
+    fn random_password_honors_length_and_charsets(
+        seed: u64,
+        characters in 0..256u32,
+        numbers: bool,
+        symbols: bool,
+        excluded in subsequence(POOL.chars().collect::<Vec<_>>(), 0..32),
+    ) {
+        let password = random_password(
+            &mut StdRng::seed_from_u64(seed),
+            characters,
+            numbers,
+            symbols,
+            &excluded,
+        );
+
+        prop_assert_eq!(password.chars().count(), characters as usize);
+        for c in password.chars() {
+            prop_assert!(!excluded.contains(&c));
+            prop_assert!(
+                c.is_ascii_alphabetic()
+                    || (numbers && c.is_ascii_digit())
+                    || (symbols && Charset::symbols().contains(c))
+            );
+        }
+    }
+
+    #[test]
+    fn charset_password_draws_from_the_sets(
+        seed: u64,
+        characters in 0..256u32,
+        sets in prop::collection::vec(("\\PC{0,16}", 1..10u32), 1..4),
+    ) {
+        let sets: Vec<(Charset, u32)> = sets
+            .iter()
+            .map(|(chars, weight)| (Charset::from(chars.as_str()), *weight))
+            .collect();
+        prop_assume!(sets.iter().any(|(set, _)| !set.is_empty()));
+
+        let password = charset_password(&mut StdRng::seed_from_u64(seed), characters, &sets);
+
+        prop_assert_eq!(password.chars().count(), characters as usize);
+        prop_assert!(password
+            .chars()
+            .all(|c| sets.iter().any(|(set, _)| set.contains(c))));
+    }
+
+    #[test]
+    fn code_password_draws_from_the_alphabet(
+        seed: u64,
+        length in 0..256u32,
+        radix in select(Radix::value_variants().to_vec()),
+    ) {
+        let code = code_password(&mut StdRng::seed_from_u64(seed), length, radix.alphabet());
+
+        prop_assert_eq!(code.chars().count(), length as usize);
+        prop_assert!(code.chars().all(|c| radix.alphabet().contains(&c)));
+    }
+
+    #[test]
+    fn pin_password_is_made_of_digits(seed: u64, numbers in 0..256u32) {
+        let pin = pin_password(&mut StdRng::seed_from_u64(seed), numbers);
+
+        prop_assert_eq!(pin.len(), numbers as usize);
+        prop_assert!(pin.chars().all(|c| c.is_ascii_digit()));
+    }
+
+    #[test]
+    fn apple_password_has_the_apple_shape(seed: u64) {
+        let password = apple_password(&mut StdRng::seed_from_u64(seed));
+        let chunks: Vec<&str> = password.split('-').collect();
+
+        prop_assert_eq!(chunks.len(), 3);
+        prop_assert!(chunks.iter().all(|chunk| chunk.len() == 6));
+        prop_assert_eq!(password.chars().filter(char::is_ascii_uppercase).count(), 1);
+        prop_assert_eq!(password.chars().filter(char::is_ascii_digit).count(), 1);
+    }
+
+    #[test]
+    fn memorable_password_is_made_of_words(
+        seed: u64,
+        word_count in 0..16usize,
+        separator in word_separator(),
+        separator_count in 1..4usize,
+        capitalize: bool,
+        scramble: bool,
+    ) {
+        let password = memorable_password(
+            &mut StdRng::seed_from_u64(seed),
+            word_count,
+            separator,
+            separator_count,
+            capitalize,
+            scramble,
+        );
+
+        let words: Vec<String> = password
+            .split(|c: char| !c.is_alphabetic() && c != '-')
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        prop_assert_eq!(words.len(), word_count);
+
+        let wordlist: HashSet<Vec<char>> = WordFilter::new()
+            .words()
+            .into_iter()
+            .map(sorted)
+            .collect();
+        for word in &words {
+            prop_assert!(wordlist.contains(&sorted(word)), "{} is not from the wordlist", word);
+        }
+    }
+
+    #[test]
+    fn filtered_memorable_password_never_exceeds_the_allowed_words(
+        seed: u64,
+        word_count in 0..16usize,
+        max_syllables in 0..3usize,
+    ) {
+        let filter = WordFilter::new().with_max_syllables(max_syllables);
+        let password = filter.memorable_password(
+            &mut StdRng::seed_from_u64(seed),
+            word_count,
+            Separator::Space,
+            0,
+            false,
+            false,
+        );
+
+        let words: Vec<&str> = password.split_whitespace().collect();
+        prop_assert_eq!(words.len(), word_count.min(filter.words().len()));
+        prop_assert!(words.iter().all(|word| filter.allows(word)));
+    }
+
+    #[test]
+    fn card_has_the_requested_dimensions(
+        seed: u64,
+        rows in 0..32usize,
+        columns in 0..64usize,
+        numbers: bool,
+        symbols: bool,
+    ) {
+        let card = Card::generate(
+            &mut StdRng::seed_from_u64(seed),
+            rows,
+            columns,
+            numbers,
+            symbols,
+            &Policy::new(),
+        );
+
+        prop_assert_eq!(card.rows().len(), rows);
+        prop_assert!(card
+            .rows()
+            .iter()
+            .all(|row| row.chars().count() == columns.min(MAX_COLUMNS)));
+    }
+
+    #[test]
+    fn preset_passwords_comply_with_their_policy(
+        seed: u64,
+        characters in 0..128u32,
+        numbers: bool,
+        symbols: bool,
+        preset in select(Preset::value_variants().to_vec()),
+    ) {
+        let policy = preset.policy();
+        let (characters, numbers, symbols) = policy.fit(characters, numbers, symbols);
+        let password = policy.random_password(
+            &mut StdRng::seed_from_u64(seed),
+            characters,
+            numbers,
+            symbols,
+            &[],
+        );
+
+        prop_assert!(policy.is_satisfied_by(&password), "{} breaks {:?}", password, preset);
+    }
+}